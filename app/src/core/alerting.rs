@@ -0,0 +1,283 @@
+//! 错误突增告警
+//!
+//! [`error_metrics_middleware`] 把每个 4xx/5xx 响应的状态码投递给后台任务，
+//! 后台任务按 `alerting.rules` 统计滑动窗口内的错误数，超过阈值时通过共享 HTTP 客户端
+//! 向 webhook 发送告警。同一规则在冷却期内只告警一次。
+//!
+//! 投递使用有界通道的 `try_send`，队列满时直接丢弃；webhook 发送失败只记录日志。
+//! 两者都不会影响请求处理。
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use super::config::{AlertRule, AlertingConfig};
+
+/// 待处理状态码队列容量
+const QUEUE_CAPACITY: usize = 1024;
+
+/// 错误指标采集句柄
+///
+/// 未启用告警时为空操作。
+#[derive(Debug, Clone)]
+pub struct ErrorMetrics {
+    tx: Option<mpsc::Sender<u16>>,
+}
+
+impl ErrorMetrics {
+    /// 记录一个响应状态码，只有 4xx/5xx 会被投递
+    pub fn record(&self, status: u16) {
+        if let Some(tx) = &self.tx
+            && status >= 400
+        {
+            // 队列满说明告警任务跟不上，丢弃即可，不能阻塞请求
+            let _ = tx.try_send(status);
+        }
+    }
+}
+
+/// 错误指标中间件，记录每个响应的状态码
+pub async fn error_metrics_middleware(
+    State(metrics): State<ErrorMetrics>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    metrics.record(response.status().as_u16());
+    response
+}
+
+/// 触发的告警
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BurstAlert {
+    /// 规则名称
+    pub rule: String,
+
+    /// 状态码类别：4 或 5
+    pub status_class: u16,
+
+    /// 窗口内的错误数
+    pub count: usize,
+
+    /// 触发阈值
+    pub threshold: u32,
+
+    /// 统计窗口，单位秒
+    pub window_secs: u64,
+}
+
+struct RuleState {
+    rule: AlertRule,
+    hits: VecDeque<Instant>,
+    last_fired: Option<Instant>,
+}
+
+/// 错误突增检测器，按规则维护滑动窗口和冷却时间
+pub struct BurstDetector {
+    rules: Vec<RuleState>,
+    cooldown: Duration,
+}
+
+impl BurstDetector {
+    /// 使用告警规则和冷却时间创建检测器
+    pub fn new(rules: &[AlertRule], cooldown: Duration) -> Self {
+        Self {
+            rules: rules
+                .iter()
+                .map(|rule| RuleState {
+                    rule: rule.clone(),
+                    hits: VecDeque::new(),
+                    last_fired: None,
+                })
+                .collect(),
+            cooldown,
+        }
+    }
+
+    /// 记录一次 `now` 时刻的错误响应，返回因此触发的告警
+    pub fn observe(&mut self, status: u16, now: Instant) -> Vec<BurstAlert> {
+        let mut alerts = Vec::new();
+        for state in &mut self.rules {
+            if status / 100 != state.rule.status_class {
+                continue;
+            }
+
+            let window = Duration::from_secs(state.rule.window_secs);
+            state.hits.push_back(now);
+            while state
+                .hits
+                .front()
+                .is_some_and(|hit| now.duration_since(*hit) >= window)
+            {
+                state.hits.pop_front();
+            }
+
+            let over_threshold = state.hits.len() > state.rule.threshold as usize;
+            let cooled_down = state
+                .last_fired
+                .is_none_or(|fired| now.duration_since(fired) >= self.cooldown);
+            if over_threshold && cooled_down {
+                state.last_fired = Some(now);
+                alerts.push(BurstAlert {
+                    rule: state.rule.name.clone(),
+                    status_class: state.rule.status_class,
+                    count: state.hits.len(),
+                    threshold: state.rule.threshold,
+                    window_secs: state.rule.window_secs,
+                });
+            }
+        }
+        alerts
+    }
+}
+
+/// 启动告警后台任务，返回供中间件使用的采集句柄
+///
+/// 未配置 webhook 或规则时不启动任务，返回的句柄为空操作。
+pub fn spawn_watcher(config: &AlertingConfig, client: reqwest::Client) -> ErrorMetrics {
+    let Some(webhook_url) = config.webhook_url.clone().filter(|_| config.is_enabled()) else {
+        return ErrorMetrics { tx: None };
+    };
+
+    let (tx, mut rx) = mpsc::channel(QUEUE_CAPACITY);
+    let mut detector = BurstDetector::new(&config.rules, Duration::from_secs(config.cooldown_secs));
+    info!(rules = config.rules.len(), "错误突增告警已启用");
+
+    tokio::spawn(async move {
+        while let Some(status) = rx.recv().await {
+            for alert in detector.observe(status, Instant::now()) {
+                // 单独发送，慢速 webhook 不会拖住统计
+                tokio::spawn(send_alert(client.clone(), webhook_url.clone(), alert));
+            }
+        }
+    });
+
+    ErrorMetrics { tx: Some(tx) }
+}
+
+/// 向 webhook 发送告警，失败只记录日志
+async fn send_alert(client: reqwest::Client, webhook_url: String, alert: BurstAlert) {
+    warn!(
+        rule = %alert.rule,
+        count = alert.count,
+        window_secs = alert.window_secs,
+        "错误突增，发送告警"
+    );
+
+    let payload = json!({
+        "text": format!(
+            "[{}] {} 秒内 {}xx 响应 {} 个，超过阈值 {}",
+            alert.rule, alert.window_secs, alert.status_class, alert.count, alert.threshold
+        ),
+        "triggered_at": Utc::now(),
+        "alert": alert,
+    });
+
+    let result = client
+        .post(&webhook_url)
+        .json(&payload)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    if let Err(e) = result {
+        warn!(error = %e, "告警 webhook 发送失败");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(threshold: u32, window_secs: u64) -> AlertRule {
+        AlertRule {
+            name: "5xx-burst".to_string(),
+            status_class: 5,
+            threshold,
+            window_secs,
+        }
+    }
+
+    #[test]
+    fn test_fires_when_threshold_exceeded_in_window() {
+        let mut detector = BurstDetector::new(&[rule(2, 10)], Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(detector.observe(500, start).is_empty());
+        assert!(detector.observe(404, start).is_empty());
+        assert!(
+            detector
+                .observe(502, start + Duration::from_secs(1))
+                .is_empty()
+        );
+        // 第一个错误已滑出窗口
+        assert!(
+            detector
+                .observe(503, start + Duration::from_secs(10))
+                .is_empty()
+        );
+
+        let alerts = detector.observe(500, start + Duration::from_secs(10));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].count, 3);
+    }
+
+    #[test]
+    fn test_cooldown_suppresses_repeated_alerts() {
+        let mut detector = BurstDetector::new(&[rule(0, 10)], Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert_eq!(detector.observe(500, start).len(), 1);
+        assert!(
+            detector
+                .observe(500, start + Duration::from_secs(30))
+                .is_empty()
+        );
+        assert_eq!(
+            detector.observe(500, start + Duration::from_secs(60)).len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watcher_posts_alert_to_webhook() {
+        let (received_tx, mut received_rx) = mpsc::channel::<serde_json::Value>(1);
+        let webhook = axum::Router::new().route(
+            "/hook",
+            axum::routing::post(move |axum::Json(body): axum::Json<serde_json::Value>| {
+                let received_tx = received_tx.clone();
+                async move {
+                    received_tx.send(body).await.unwrap();
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, webhook).await.unwrap() });
+
+        let config = AlertingConfig {
+            webhook_url: Some(format!("http://{addr}/hook")),
+            cooldown_secs: 60,
+            rules: vec![rule(1, 60)],
+        };
+        let metrics = spawn_watcher(&config, reqwest::Client::new());
+        metrics.record(200);
+        metrics.record(500);
+        metrics.record(503);
+
+        let body = tokio::time::timeout(Duration::from_secs(5), received_rx.recv())
+            .await
+            .expect("webhook 未收到告警")
+            .unwrap();
+        assert_eq!(body["alert"]["rule"], "5xx-burst");
+        assert_eq!(body["alert"]["count"], 2);
+        assert!(body["text"].as_str().unwrap().contains("5xx"));
+    }
+}
@@ -0,0 +1,112 @@
+//! 路由级请求体大小限制
+//!
+//! ## 优先级
+//!
+//! - 全局上限 `server.body_limit_bytes` 通过 [`DefaultBodyLimit`] 挂在整个应用上，
+//!   只约束提取器（`Json`、`Bytes`、`Multipart` 等）读取的请求体大小。
+//! - `server.route_body_limits` 中的覆盖值挂在对应前缀的子路由上，位于全局层内侧，
+//!   因此该前缀下的路由以覆盖值为准，既可以放宽（如上传接口）也可以收紧。
+//! - 覆盖值同时添加 [`RequestBodyLimitLayer`]，在读取前按 `Content-Length` 直接拒绝，
+//!   流式读取请求体的处理器也会受到约束。
+//!
+//! 覆盖按子路由的完整挂载路径精确匹配（如 `/v1/users`），不做最长前缀匹配。
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use aide::axum::ApiRouter;
+use axum::extract::DefaultBodyLimit;
+use tower_http::limit::RequestBodyLimitLayer;
+use tracing::info;
+
+/// 按挂载路径覆盖的请求体大小上限
+#[derive(Debug, Clone, Default)]
+pub struct RouteBodyLimits {
+    limits: Arc<BTreeMap<String, usize>>,
+}
+
+impl RouteBodyLimits {
+    /// 由 `server.route_body_limits` 创建
+    pub fn new(limits: BTreeMap<String, usize>) -> Self {
+        Self {
+            limits: Arc::new(limits),
+        }
+    }
+
+    /// 若 `prefix` 配置了覆盖值，为挂载在该路径的子路由添加对应的请求体限制
+    pub fn apply<S>(&self, prefix: &str, router: ApiRouter<S>) -> ApiRouter<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        match self.limits.get(prefix) {
+            Some(&limit) => {
+                info!(prefix, limit, "路由请求体大小上限已覆盖");
+                router
+                    .layer(DefaultBodyLimit::max(limit))
+                    .layer(RequestBodyLimitLayer::new(limit))
+            }
+            None => router,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aide::axum::routing::post;
+    use axum::body::{Body, Bytes};
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    const GLOBAL_LIMIT: usize = 16;
+
+    async fn echo_len(body: Bytes) -> String {
+        body.len().to_string()
+    }
+
+    fn app() -> axum::Router {
+        let limits = RouteBodyLimits::new(BTreeMap::from([("/v1/upload".to_string(), 1024)]));
+        let upload = limits.apply(
+            "/v1/upload",
+            ApiRouter::new().api_route("/files", post(echo_len)),
+        );
+        let other = limits.apply(
+            "/v1/other",
+            ApiRouter::new().api_route("/files", post(echo_len)),
+        );
+
+        ApiRouter::new()
+            .nest("/v1/upload", upload)
+            .nest("/v1/other", other)
+            .layer(DefaultBodyLimit::max(GLOBAL_LIMIT))
+            .into()
+    }
+
+    async fn post_status(path: &str, len: usize) -> StatusCode {
+        let request = Request::post(path)
+            .body(Body::from(vec![b'x'; len]))
+            .unwrap();
+        app().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_route_override_accepts_body_over_global_limit() {
+        assert_eq!(post_status("/v1/upload/files", 100).await, StatusCode::OK);
+        assert_eq!(
+            post_status("/v1/other/files", 100).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        assert_eq!(
+            post_status("/v1/other/files", GLOBAL_LIMIT).await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_route_override_still_caps_upload() {
+        assert_eq!(
+            post_status("/v1/upload/files", 2048).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+}
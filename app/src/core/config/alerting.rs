@@ -0,0 +1,109 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+
+/// 错误突增告警规则
+///
+/// `window_secs` 秒内状态码类别为 `status_class` 的响应超过 `threshold` 个时触发告警。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertRule {
+    /// 规则名称，出现在告警消息中
+    pub name: String,
+
+    /// 状态码类别：4 表示 4xx，5 表示 5xx
+    pub status_class: u16,
+
+    /// 触发阈值，窗口内错误数超过该值时告警
+    pub threshold: u32,
+
+    /// 统计窗口，单位秒
+    pub window_secs: u64,
+}
+
+/// 错误告警配置
+///
+/// 配置 `webhook_url` 和至少一条规则后，后台任务按规则统计错误响应，
+/// 触发时向 webhook 发送 JSON（含 Slack 兼容的 `text` 字段）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertingConfig {
+    /// 告警 webhook 地址（可选，未配置时不启用告警）
+    pub webhook_url: Option<String>,
+
+    /// 同一规则两次告警的最小间隔，单位秒（默认：300）
+    pub cooldown_secs: u64,
+
+    /// 告警规则列表（默认：为空）
+    pub rules: Vec<AlertRule>,
+}
+
+impl Default for AlertingConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            cooldown_secs: 300,
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl AlertingConfig {
+    /// 是否启用告警（配置了 webhook 且至少有一条规则）
+    pub fn is_enabled(&self) -> bool {
+        self.webhook_url.is_some() && !self.rules.is_empty()
+    }
+}
+
+impl ConfigSection for AlertingConfig {
+    fn section_name(&self) -> &str {
+        "alerting"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(url) = obj.get("webhook_url").and_then(|v| v.as_str()) {
+                self.webhook_url = Some(url.to_string());
+            }
+            if let Some(cooldown) = obj.get("cooldown_secs").and_then(|v| v.as_u64()) {
+                self.cooldown_secs = cooldown;
+            }
+            if let Some(rules) = obj.get("rules") {
+                self.rules = serde_json::from_value(rules.clone())
+                    .map_err(|e| format!("无效的告警规则：{e}"))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(url) = &self.webhook_url
+            && !(url.starts_with("http://") || url.starts_with("https://"))
+        {
+            return Err(format!(
+                "webhook_url 必须以 http:// 或 https:// 开头：{url}"
+            ));
+        }
+        for rule in &self.rules {
+            if !matches!(rule.status_class, 4 | 5) {
+                return Err(format!(
+                    "告警规则 {} 的 status_class 必须为 4 或 5：{}",
+                    rule.name, rule.status_class
+                ));
+            }
+            if rule.window_secs == 0 {
+                return Err(format!("告警规则 {} 的 window_secs 必须大于 0", rule.name));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(url) = env::var("ALERT_WEBHOOK_URL") {
+            self.webhook_url = Some(url);
+        }
+        Ok(())
+    }
+}
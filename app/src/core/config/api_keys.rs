@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+use crate::shared::SharedSecret;
+
+/// API 密钥最短长度
+const MIN_KEY_LEN: usize = 32;
+
+/// 单个 API 密钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyEntry {
+    /// 密钥标识，出现在日志中，用于区分调用方（如 `ci`、`billing-sync`）
+    pub label: String,
+
+    /// 密钥值（至少 32 字符）
+    pub key: SharedSecret,
+
+    /// 授权范围（如 `stats:read`），由路由的访问级别或处理器检查（默认：空）
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// API 密钥认证配置
+///
+/// 机器客户端通过 `X-Auth-Key` 请求头携带密钥，作为 JWT 之外的认证方式。
+/// 密钥不要写进配置文件，通过环境变量 `API_KEYS` 设置：
+/// `label:key[:scope1+scope2],...`，设置后整体替换配置文件中的密钥。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiKeysConfig {
+    /// 有效的 API 密钥列表（默认：空，即不接受 API 密钥）
+    pub keys: Vec<ApiKeyEntry>,
+}
+
+impl ApiKeysConfig {
+    /// 解析 `API_KEYS` 环境变量的值
+    fn parse_env(value: &str) -> Result<Vec<ApiKeyEntry>, String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                let mut parts = item.splitn(3, ':');
+                let (Some(label), Some(key)) = (parts.next(), parts.next()) else {
+                    return Err("API_KEYS 条目格式应为 label:key[:scope1+scope2]".to_string());
+                };
+                let scopes = parts
+                    .next()
+                    .map(|s| s.split('+').map(String::from).collect())
+                    .unwrap_or_default();
+                Ok(ApiKeyEntry {
+                    label: label.to_string(),
+                    key: SharedSecret::new(key),
+                    scopes,
+                })
+            })
+            .collect()
+    }
+}
+
+impl ConfigSection for ApiKeysConfig {
+    fn section_name(&self) -> &str {
+        "api_keys"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(keys) = value.get("keys") {
+            self.keys = serde_json::from_value(keys.clone())
+                .map_err(|e| format!("API 密钥配置格式无效：{e}"))?;
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut labels = HashSet::new();
+        for entry in &self.keys {
+            if entry.label.trim().is_empty() {
+                return Err("API 密钥的 label 不能为空".to_string());
+            }
+            if !labels.insert(entry.label.as_str()) {
+                return Err(format!("API 密钥 label 重复：{}", entry.label));
+            }
+            if entry.key.len() < MIN_KEY_LEN {
+                return Err(format!(
+                    "API 密钥长度必须至少 {MIN_KEY_LEN} 个字符：{}",
+                    entry.label
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(keys) = env::var("API_KEYS") {
+            self.keys = Self::parse_env(&keys)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_keys_with_scopes() {
+        let keys = ApiKeysConfig::parse_env(
+            "ci:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa:users:export+audit:read, sync:bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+        )
+        .unwrap();
+
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].label, "ci");
+        assert_eq!(keys[0].scopes, ["users:export", "audit:read"]);
+        assert!(keys[1].scopes.is_empty());
+        assert!(ApiKeysConfig { keys }.validate().is_ok());
+
+        assert!(ApiKeysConfig::parse_env("missing-key").is_err());
+    }
+}
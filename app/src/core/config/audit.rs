@@ -0,0 +1,75 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+
+/// 审计配置
+///
+/// 管理接口的每次写操作都会连同请求体写入 `admin_audit` 表，
+/// 写入前按字段名（不区分大小写）脱敏。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    /// 需要脱敏的请求体字段名，任意层级匹配到的值都会被替换为 `[REDACTED]`
+    pub redact_fields: Vec<String>,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            redact_fields: [
+                "password",
+                "old_password",
+                "new_password",
+                "token",
+                "refresh_token",
+                "secret",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+impl ConfigSection for AuditConfig {
+    fn section_name(&self) -> &str {
+        "audit"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object()
+            && let Some(fields) = obj.get("redact_fields").and_then(|v| v.as_array())
+        {
+            self.redact_fields = fields
+                .iter()
+                .map(|v| {
+                    v.as_str()
+                        .map(String::from)
+                        .ok_or_else(|| format!("redact_fields 只能包含字符串：{v}"))
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.redact_fields.iter().any(|f| f.trim().is_empty()) {
+            return Err("redact_fields 不能包含空字段名".to_string());
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(fields) = env::var("AUDIT_REDACT_FIELDS") {
+            self.redact_fields = fields
+                .split(',')
+                .map(str::trim)
+                .filter(|f| !f.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        Ok(())
+    }
+}
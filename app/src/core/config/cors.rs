@@ -1,8 +1,31 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
 use super::section::ConfigSection;
 
+/// 解析预检缓存时间：非负整数为秒数，`false` 或空值表示不发送该响应头
+fn parse_max_age(value: &Value) -> Result<Option<u64>, String> {
+    match value {
+        Value::Null | Value::Bool(false) => Ok(None),
+        Value::String(s) => s
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("max_age 必须是非负整数，或 false 表示不发送该响应头：{s}")),
+        v => v
+            .as_u64()
+            .map(Some)
+            .ok_or_else(|| format!("max_age 必须是非负整数，或 false 表示不发送该响应头：{v}")),
+    }
+}
+
+fn deserialize_max_age<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    parse_max_age(&Value::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
 /// CORS 跨域资源共享配置
 ///
 /// 用于控制浏览器跨域请求的安全政策。包括允许的源、请求头、
@@ -19,7 +42,7 @@ pub struct CorsConfig {
     pub allow_methods: Vec<String>,
 
     /// 允许的请求头列表（如：["Authorization", "Content-Type"]）
-    /// （默认：["Authorization", "Content-Type", "Accept", "X-Request-ID"]）
+    /// （默认：["Authorization", "Content-Type", "Accept", "X-Request-ID", "Idempotency-Key"]）
     pub allow_headers: Vec<String>,
 
     /// 是否允许凭证（Cookie、Authorization）跨域传送
@@ -27,12 +50,20 @@ pub struct CorsConfig {
     pub allow_credentials: bool,
 
     /// 暴露给客户端的响应头列表
-    /// （默认：["Content-Type", "X-Total-Count"]）
+    /// （默认：["Content-Type", "X-Total-Count", "X-Total-Records", "X-Content-Length", "Idempotent-Replayed"]）
     pub expose_headers: Vec<String>,
 
     /// 预检请求（OPTIONS）的缓存时间，单位秒
+    ///
+    /// `None`（配置为 `false`）时不发送 `Access-Control-Max-Age`，由浏览器使用自身默认值；
+    /// `Some(0)` 显式发送 `0`，要求浏览器不缓存预检结果
     /// （默认：3600）
-    pub max_age: u64,
+    #[serde(deserialize_with = "deserialize_max_age")]
+    pub max_age: Option<u64>,
+
+    /// 额外追加到 `Vary` 响应头的请求头列表
+    /// 指定具体源时会自动包含 `Origin`（默认：[]）
+    pub vary_headers: Vec<String>,
 }
 
 impl Default for CorsConfig {
@@ -52,10 +83,20 @@ impl Default for CorsConfig {
                 "Content-Type".to_string(),
                 "Accept".to_string(),
                 "X-Request-ID".to_string(),
+                "Idempotency-Key".to_string(),
             ],
             allow_credentials: false,
-            expose_headers: vec!["Content-Type".to_string(), "X-Total-Count".to_string()],
-            max_age: 3600,
+            expose_headers: [
+                "Content-Type",
+                "X-Total-Count",
+                "X-Total-Records",
+                "X-Content-Length",
+                "Idempotent-Replayed",
+            ]
+            .map(String::from)
+            .to_vec(),
+            max_age: Some(3600),
+            vary_headers: vec![],
         }
     }
 }
@@ -94,8 +135,14 @@ impl ConfigSection for CorsConfig {
                     .filter_map(|v| v.as_str().map(|s| s.to_string()))
                     .collect();
             }
-            if let Some(age) = obj.get("max_age").and_then(|v| v.as_u64()) {
-                self.max_age = age;
+            if let Some(age) = obj.get("max_age") {
+                self.max_age = parse_max_age(age)?;
+            }
+            if let Some(vary) = obj.get("vary_headers").and_then(|v| v.as_array()) {
+                self.vary_headers = vary
+                    .iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect();
             }
         }
         Ok(())
@@ -121,3 +168,40 @@ impl ConfigSection for CorsConfig {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn load(value: Value) -> Result<CorsConfig, String> {
+        let mut config = CorsConfig::default();
+        config.load_from_value(&value)?;
+        Ok(config)
+    }
+
+    #[test]
+    fn test_max_age_unset_vs_explicit_zero() {
+        assert_eq!(load(json!({})).unwrap().max_age, Some(3600));
+        assert_eq!(load(json!({ "max_age": 0 })).unwrap().max_age, Some(0));
+        assert_eq!(load(json!({ "max_age": false })).unwrap().max_age, None);
+        assert_eq!(load(json!({ "max_age": null })).unwrap().max_age, None);
+        assert!(load(json!({ "max_age": -1 })).is_err());
+    }
+
+    #[test]
+    fn test_max_age_deserializes_from_toml() {
+        let parse = |toml: &str| {
+            config::Config::builder()
+                .add_source(config::File::from_str(toml, config::FileFormat::Toml))
+                .build()
+                .unwrap()
+                .try_deserialize::<CorsConfig>()
+                .map(|c| c.max_age)
+        };
+        assert_eq!(parse("").unwrap(), Some(3600));
+        assert_eq!(parse("max_age = 0").unwrap(), Some(0));
+        assert_eq!(parse("max_age = false").unwrap(), None);
+        assert!(parse("max_age = true").is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+use crate::shared::SharedSecret;
+
+/// API 文档配置
+///
+/// 文档页面（Scalar）和 OpenAPI JSON 挂载在 `path` 下，页面脚本在编译时嵌入，不依赖外部 CDN。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocsConfig {
+    /// 是否挂载文档路由；未配置时跟随日志级别，`logging.level = "debug"` 时启用
+    pub enabled: Option<bool>,
+
+    /// 文档挂载路径，页面为 `{path}`，OpenAPI JSON 为 `{path}/openapi.json`（默认：/docs）
+    pub path: String,
+
+    /// 是否在启动时预先序列化 OpenAPI 文档，页面首次加载无需等待生成（默认：true）
+    ///
+    /// 关闭时在第一次请求文档时生成，之后同样直接返回缓存
+    pub preload: bool,
+
+    /// HTTP Basic 认证用户名，与 `password` 同时设置时访问文档需要认证（可用 DOCS_USERNAME 覆盖）
+    pub username: Option<String>,
+
+    /// HTTP Basic 认证密码（可用 DOCS_PASSWORD 覆盖）
+    pub password: Option<SharedSecret>,
+
+    /// 是否允许管理员携带 JWT（`Authorization: Bearer`）访问文档（默认：false）
+    ///
+    /// 开启后即使未设置 Basic 认证，访问文档也需要管理员身份
+    pub allow_admin_token: bool,
+}
+
+impl Default for DocsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: None,
+            path: "/docs".to_string(),
+            preload: true,
+            username: None,
+            password: None,
+            allow_admin_token: false,
+        }
+    }
+}
+
+impl DocsConfig {
+    /// OpenAPI JSON 的访问路径
+    pub fn spec_path(&self) -> String {
+        format!("{}/openapi.json", self.path)
+    }
+
+    /// 访问文档是否需要认证
+    pub fn is_protected(&self) -> bool {
+        self.password.is_some() || self.allow_admin_token
+    }
+}
+
+impl ConfigSection for DocsConfig {
+    fn section_name(&self) -> &str {
+        "docs"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(enabled) = obj.get("enabled").and_then(|v| v.as_bool()) {
+                self.enabled = Some(enabled);
+            }
+            if let Some(path) = obj.get("path").and_then(|v| v.as_str()) {
+                self.path = path.to_string();
+            }
+            if let Some(preload) = obj.get("preload").and_then(|v| v.as_bool()) {
+                self.preload = preload;
+            }
+            if let Some(username) = obj.get("username").and_then(|v| v.as_str()) {
+                self.username = Some(username.to_string());
+            }
+            if let Some(password) = obj.get("password").and_then(|v| v.as_str()) {
+                self.password = Some(SharedSecret::new(password));
+            }
+            if let Some(allow) = obj.get("allow_admin_token").and_then(|v| v.as_bool()) {
+                self.allow_admin_token = allow;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !self.path.starts_with('/') || self.path.ends_with('/') {
+            return Err(format!(
+                "docs.path 必须以 / 开头且不以 / 结尾：{:?}",
+                self.path
+            ));
+        }
+        match (&self.username, &self.password) {
+            (Some(username), Some(password)) => {
+                if username.is_empty() || username.contains(':') {
+                    return Err("docs.username 不能为空，也不能包含冒号".to_string());
+                }
+                if password.is_empty() {
+                    return Err("docs.password 不能为空".to_string());
+                }
+            }
+            (None, None) => {}
+            _ => return Err("docs.username 和 docs.password 必须同时设置".to_string()),
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(enabled) = env::var("DOCS_ENABLED") {
+            self.enabled = Some(
+                enabled
+                    .parse()
+                    .map_err(|_| format!("DOCS_ENABLED 必须是 true 或 false：{enabled}"))?,
+            );
+        }
+        if let Ok(username) = env::var("DOCS_USERNAME") {
+            self.username = Some(username);
+        }
+        if let Ok(password) = env::var("DOCS_PASSWORD") {
+            self.password = Some(SharedSecret::new(password));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_and_validate() {
+        let mut config = DocsConfig::default();
+        assert!(config.enabled.is_none());
+        assert_eq!(config.spec_path(), "/docs/openapi.json");
+
+        config
+            .load_from_value(&json!({ "enabled": true, "path": "/api-docs", "preload": false }))
+            .unwrap();
+        assert_eq!(config.enabled, Some(true));
+        assert!(!config.preload);
+        assert_eq!(config.spec_path(), "/api-docs/openapi.json");
+        assert!(config.validate().is_ok());
+
+        for path in ["docs", "/docs/", "/", ""] {
+            config.path = path.to_string();
+            assert!(config.validate().is_err(), "{path:?}");
+        }
+    }
+
+    #[test]
+    fn test_basic_credentials_are_validated_and_redacted() {
+        let mut config = DocsConfig::default();
+        assert!(!config.is_protected());
+
+        config
+            .load_from_value(&json!({ "username": "docs", "password": "hunter2" }))
+            .unwrap();
+        assert!(config.is_protected());
+        assert!(config.validate().is_ok());
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(!serialized.contains("hunter2"), "{serialized}");
+        assert!(!format!("{config:?}").contains("hunter2"));
+
+        config.username = None;
+        assert!(config.validate().is_err());
+        config.username = Some("a:b".to_string());
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use strum::{Display, EnumString};
+
+use super::section::ConfigSection;
+
+/// 恶意软件扫描器
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ScannerBackend {
+    /// 不扫描，所有文件直接标记为 clean
+    #[default]
+    None,
+
+    /// ClamAV 守护进程（clamd），通过 TCP 的 INSTREAM 命令扫描
+    Clamav,
+}
+
+/// 发现恶意内容后对文件内容的处理方式
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum QuarantineAction {
+    /// 移动到同一存储后端的 `quarantine/` 前缀下，保留内容供人工排查
+    #[default]
+    Move,
+
+    /// 直接删除内容
+    Delete,
+}
+
+/// 上传文件的恶意软件扫描配置
+///
+/// 文件上传完成后状态为 `pending_scan`，由后台任务逐个扫描并标记为 `clean` 或 `infected`，
+/// 只有 `clean` 的文件可以下载。扫描器不可用时文件保持 `pending_scan`，上传不受影响，
+/// 就绪检查报告为降级状态，待扫描的文件每隔 `rescan_interval_secs` 秒重新投递。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileScanConfig {
+    /// 扫描器：none / clamav（默认：none）
+    pub backend: ScannerBackend,
+
+    /// clamd 地址（默认：127.0.0.1，可通过 `CLAMD_HOST` 环境变量覆盖）
+    pub clamd_host: String,
+
+    /// clamd 端口（默认：3310，可通过 `CLAMD_PORT` 环境变量覆盖）
+    pub clamd_port: u16,
+
+    /// 单个文件的扫描超时时间，单位秒（默认：60）
+    pub timeout_secs: u64,
+
+    /// 发现恶意内容后的处理方式：move / delete（默认：move）
+    pub quarantine: QuarantineAction,
+
+    /// 重新投递待扫描文件的间隔，单位秒，0 表示不重新投递（默认：300）
+    pub rescan_interval_secs: u64,
+}
+
+impl Default for FileScanConfig {
+    fn default() -> Self {
+        Self {
+            backend: ScannerBackend::None,
+            clamd_host: "127.0.0.1".to_string(),
+            clamd_port: 3310,
+            timeout_secs: 60,
+            quarantine: QuarantineAction::Move,
+            rescan_interval_secs: 300,
+        }
+    }
+}
+
+impl ConfigSection for FileScanConfig {
+    fn section_name(&self) -> &str {
+        "file_scan"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(backend) = obj.get("backend").and_then(|v| v.as_str()) {
+                self.backend = backend
+                    .parse()
+                    .map_err(|_| format!("backend 只能是 none 或 clamav：{backend}"))?;
+            }
+            if let Some(host) = obj.get("clamd_host").and_then(|v| v.as_str()) {
+                self.clamd_host = host.to_string();
+            }
+            if let Some(port) = obj.get("clamd_port").and_then(|v| v.as_u64()) {
+                self.clamd_port =
+                    u16::try_from(port).map_err(|_| format!("clamd_port 超出范围：{port}"))?;
+            }
+            if let Some(timeout) = obj.get("timeout_secs").and_then(|v| v.as_u64()) {
+                self.timeout_secs = timeout;
+            }
+            if let Some(action) = obj.get("quarantine").and_then(|v| v.as_str()) {
+                self.quarantine = action
+                    .parse()
+                    .map_err(|_| format!("quarantine 只能是 move 或 delete：{action}"))?;
+            }
+            if let Some(interval) = obj.get("rescan_interval_secs").and_then(|v| v.as_u64()) {
+                self.rescan_interval_secs = interval;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.backend == ScannerBackend::Clamav {
+            if self.clamd_host.is_empty() {
+                return Err("backend = \"clamav\" 时 clamd_host 不能为空".to_string());
+            }
+            if self.clamd_port == 0 {
+                return Err("clamd_port 不能为 0".to_string());
+            }
+        }
+        if self.timeout_secs == 0 {
+            return Err("timeout_secs 必须大于 0".to_string());
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(host) = env::var("CLAMD_HOST") {
+            self.clamd_host = host;
+        }
+        if let Ok(port) = env::var("CLAMD_PORT") {
+            self.clamd_port = port
+                .parse()
+                .map_err(|_| format!("CLAMD_PORT 不是有效的端口：{port}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_and_validate() {
+        let mut config = FileScanConfig::default();
+        assert_eq!(config.backend, ScannerBackend::None);
+        config
+            .load_from_value(&json!({
+                "backend": "clamav",
+                "clamd_host": "clamav",
+                "clamd_port": 3311,
+                "quarantine": "delete"
+            }))
+            .unwrap();
+        assert_eq!(config.backend, ScannerBackend::Clamav);
+        assert_eq!(
+            (config.clamd_host.as_str(), config.clamd_port),
+            ("clamav", 3311)
+        );
+        assert_eq!(config.quarantine, QuarantineAction::Delete);
+        assert!(config.validate().is_ok());
+
+        assert!(
+            config
+                .load_from_value(&json!({"backend": "virustotal"}))
+                .is_err()
+        );
+        assert!(
+            config
+                .load_from_value(&json!({"clamd_port": 70000}))
+                .is_err()
+        );
+        config.clamd_host.clear();
+        assert!(config.validate().is_err());
+    }
+}
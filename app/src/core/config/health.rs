@@ -0,0 +1,105 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+use crate::core::IpNetwork;
+
+/// 健康检查配置
+///
+/// `/health`（存活检查）始终公开。启用 `require_api_key` 或配置 `allowed_ips` 后，
+/// `/health/ready` 只对携带有效 `X-Auth-Key` 或来源 IP 在白名单内的调用方返回各依赖的详细状态，
+/// 其余调用方只得到不带响应体的 200/503。两者同时配置时满足任一即可。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// 是否允许凭 `api_keys` 中的密钥查看就绪检查详情（默认：false）
+    pub require_api_key: bool,
+
+    /// 可以查看就绪检查详情的来源 IP 或 CIDR 网段，如 `10.0.0.0/8`（默认：为空）
+    ///
+    /// 按 TCP 连接的对端地址匹配，不读取 `X-Forwarded-For`；部署在反向代理之后时应填写代理的地址
+    pub allowed_ips: Vec<String>,
+}
+
+impl HealthConfig {
+    /// 是否限制了就绪检查详情的访问
+    pub fn is_guarded(&self) -> bool {
+        self.require_api_key || !self.allowed_ips.is_empty()
+    }
+}
+
+impl ConfigSection for HealthConfig {
+    fn section_name(&self) -> &str {
+        "health"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(require) = obj.get("require_api_key").and_then(|v| v.as_bool()) {
+                self.require_api_key = require;
+            }
+            if let Some(ips) = obj.get("allowed_ips").and_then(|v| v.as_array()) {
+                self.allowed_ips = ips
+                    .iter()
+                    .map(|v| {
+                        v.as_str()
+                            .map(String::from)
+                            .ok_or_else(|| format!("allowed_ips 只能包含字符串：{v}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for ip in &self.allowed_ips {
+            ip.parse::<IpNetwork>()
+                .map_err(|e| format!("allowed_ips 包含无效的地址 {ip:?}：{e}"))?;
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(ips) = env::var("HEALTH_ALLOWED_IPS") {
+            self.allowed_ips = ips
+                .split(',')
+                .map(str::trim)
+                .filter(|ip| !ip.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_and_validate() {
+        let mut config = HealthConfig::default();
+        assert!(!config.is_guarded());
+        config
+            .load_from_value(&json!({
+                "require_api_key": true,
+                "allowed_ips": ["10.0.0.0/8", "::1"]
+            }))
+            .unwrap();
+        assert!(config.is_guarded());
+        assert_eq!(config.allowed_ips, ["10.0.0.0/8", "::1"]);
+        assert!(config.validate().is_ok());
+
+        for ip in ["10.0.0.0/33", "localhost", "10.0.0.1/"] {
+            let config = HealthConfig {
+                allowed_ips: vec![ip.to_string()],
+                ..Default::default()
+            };
+            assert!(config.validate().is_err(), "{ip}");
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+
+/// 批量导入配置
+///
+/// 约束 `POST /v1/admin/users/import` 接收的 CSV：单次导入的行数上限，
+/// 以及失败行占比超过多少时放弃整个导入。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportConfig {
+    /// 失败行占全部数据行的百分比上限，超过时整个导入失败、不写入任何数据（默认：10）
+    pub max_error_rate_percent: u8,
+
+    /// 单次导入的数据行上限，不含表头（默认：1000）
+    pub max_rows: usize,
+}
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        Self {
+            max_error_rate_percent: 10,
+            max_rows: 1000,
+        }
+    }
+}
+
+impl ImportConfig {
+    /// `failed` 行失败时是否超过了允许的失败比例
+    pub fn exceeds_error_rate(&self, failed: usize, total: usize) -> bool {
+        failed * 100 > total * usize::from(self.max_error_rate_percent)
+    }
+}
+
+impl ConfigSection for ImportConfig {
+    fn section_name(&self) -> &str {
+        "import"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(rate) = obj.get("max_error_rate_percent").and_then(|v| v.as_u64()) {
+                self.max_error_rate_percent = u8::try_from(rate)
+                    .map_err(|_| format!("max_error_rate_percent 不能超过 100：{rate}"))?;
+            }
+            if let Some(rows) = obj.get("max_rows").and_then(|v| v.as_u64()) {
+                self.max_rows = rows as usize;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.max_error_rate_percent > 100 {
+            return Err(format!(
+                "max_error_rate_percent 不能超过 100：{}",
+                self.max_error_rate_percent
+            ));
+        }
+        if self.max_rows == 0 {
+            return Err("max_rows 必须大于 0".to_string());
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(rate) = env::var("IMPORT_MAX_ERROR_RATE_PERCENT") {
+            self.max_error_rate_percent = rate.parse().map_err(|_| {
+                format!("IMPORT_MAX_ERROR_RATE_PERCENT 必须是 0-100 的整数：{rate}")
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_and_validate() {
+        let mut config = ImportConfig::default();
+        config
+            .load_from_value(&json!({ "max_error_rate_percent": 25, "max_rows": 500 }))
+            .unwrap();
+        assert_eq!(config.max_error_rate_percent, 25);
+        assert_eq!(config.max_rows, 500);
+        assert!(config.validate().is_ok());
+
+        config.max_error_rate_percent = 101;
+        assert!(config.validate().is_err());
+
+        let empty = ImportConfig {
+            max_rows: 0,
+            ..Default::default()
+        };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_exceeds_error_rate() {
+        let config = ImportConfig::default();
+        assert!(!config.exceeds_error_rate(0, 10));
+        assert!(!config.exceeds_error_rate(1, 10));
+        assert!(config.exceeds_error_rate(2, 10));
+
+        let strict = ImportConfig {
+            max_error_rate_percent: 0,
+            ..Default::default()
+        };
+        assert!(!strict.exceeds_error_rate(0, 10));
+        assert!(strict.exceeds_error_rate(1, 10));
+    }
+}
@@ -1,7 +1,11 @@
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal};
+
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
 use super::section::ConfigSection;
+use crate::core::LogTimezone;
 
 /// 自定义反序列化函数，支持多种格式的清理间隔
 fn deserialize_cleanup_interval<'de, D>(deserializer: D) -> Result<u64, D::Error>
@@ -71,36 +75,84 @@ pub struct LoggingConfig {
     /// 日志级别（trace、debug、info、warn、error）（默认：info）
     pub level: String,
 
-    /// 控制台日志格式（pretty、compact），文件日志始终使用 JSON（默认：pretty）
+    /// 按模块覆盖日志级别：target → 级别，合并到 `level` 之后（设置 `RUST_LOG` 时整体以其为准）
+    ///
+    /// 默认压低 `sqlx::query` 和 `hyper` 的日志，例如 `directives = { "app" = "debug", "tower_http" = "info" }`
+    pub directives: BTreeMap<String, String>,
+
+    /// 控制台日志格式（pretty、compact、json）（默认：pretty）
     pub console_format: String,
 
+    /// 文件日志格式（json、compact、pretty），与控制台格式相互独立（默认：json）
+    pub file_format: String,
+
     /// 是否输出日志到控制台（默认：true）
     pub console: bool,
 
-    /// 是否输出日志到文件（默认：false）
+    /// 控制台日志输出目标（stdout、stderr），不影响文件日志（默认：stdout）
+    pub target: String,
+
+    /// 是否输出日志到文件（默认：false，也可写作 `file_enabled`）
+    #[serde(alias = "file_enabled")]
     pub file: bool,
 
-    /// 日志文件存储目录（默认：./logs）
+    /// 日志文件存储目录（默认：./logs，也可写作 `directory`）
+    #[serde(alias = "directory")]
     pub file_dir: String,
 
     /// 日志文件名前缀（默认：app）
     pub file_prefix: String,
 
-    /// 日志文件轮转方式（daily、hourly、never）（默认：daily）
+    /// 日志文件轮转方式（daily、hourly、size、never）（默认：daily）
     pub rotation: String,
 
+    /// 按大小轮转时单个日志文件的上限，单位 MB（仅 `rotation = "size"` 时生效）（默认：100）
+    pub max_file_size_mb: u64,
+
     /// 保留的日志文件数量（0 表示不限制）（默认：30）
     pub max_files: usize,
 
+    /// 日志文件保留天数，超过的文件会被清理（0 表示不限制）（默认：30）
+    pub retention_days: u64,
+
+    /// 日志目录总大小上限，单位 MB，超出时从最旧的文件开始删除（0 表示不限制）（默认：1024）
+    pub max_total_size_mb: u64,
+
     /// 是否启用旧日志文件自动清理（默认：true）
     pub cleanup_enabled: bool,
 
     /// 日志清理间隔，单位小时（0 表示应用启动时立即清理，仅清理一次）（默认：168 即 7x24 小时）
     #[serde(deserialize_with = "deserialize_cleanup_interval")]
     pub cleanup_interval: u64,
+
+    /// 按路由采样请求日志：路径 → 保留比例（0.0 ~ 1.0），WARN 及以上级别始终保留（默认：空）
+    ///
+    /// 例如 `sampling = { "/health" = 0.01, "/metrics" = 0.0 }`
+    pub sampling: BTreeMap<String, f64>,
+
+    /// 是否在写入控制台和文件前脱敏敏感值（默认：false，生产配置中启用）
+    pub scrub: bool,
+
+    /// 敏感键关键字，键名包含任一关键字（不区分大小写）的值会被替换为 `[REDACTED]`
+    ///
+    /// `Bearer` 令牌和 JWT 形式的字符串无论键名都会被脱敏（默认：password、passwd、secret、token、api_key）
+    pub scrub_keys: Vec<String>,
+
+    /// 不做脱敏的键名（精确匹配，不区分大小写），如 `token_type`（默认：空）
+    pub scrub_allowlist: Vec<String>,
+
+    /// 日志时间戳的时区：`utc`、`local`（服务器本地时区）或 `+08:00` 形式的固定偏移（默认：utc）
+    ///
+    /// 只影响日志输出，API 响应中的时间仍为 UTC
+    pub timezone: String,
 }
 
 impl LoggingConfig {
+    /// 解析 `timezone` 配置
+    pub fn log_timezone(&self) -> Result<LogTimezone, String> {
+        LogTimezone::parse(&self.timezone)
+    }
+
     /// 获取带环境标签的文件前缀
     ///
     /// 在 debug/trace 级别下添加 "-dev" 后缀，其他级别添加 "-prod" 后缀。
@@ -116,6 +168,33 @@ impl LoggingConfig {
         format!("{}{}", self.file_prefix, env_suffix)
     }
 
+    /// 控制台日志是否输出到交互式终端
+    ///
+    /// 未启用控制台日志、或 `target` 指向的流被重定向到文件、管道（容器、systemd 等）时返回 false，
+    /// 此时日志会被机器收集，应输出结构化字段而不是为人阅读排版的文本。
+    pub fn console_is_terminal(&self) -> bool {
+        self.console
+            && match self.target.as_str() {
+                "stderr" => io::stderr().is_terminal(),
+                _ => io::stdout().is_terminal(),
+            }
+    }
+
+    /// 构建 `EnvFilter` 使用的过滤指令
+    ///
+    /// 以 `level` 作为默认级别，依次追加 `directives` 中的模块级别，
+    /// 如 `info,hyper=warn,sqlx::query=warn`。
+    pub fn filter_directives(&self) -> String {
+        std::iter::once(self.level.clone())
+            .chain(
+                self.directives
+                    .iter()
+                    .map(|(target, level)| format!("{target}={level}")),
+            )
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
     /// 解析清理间隔配置，支持多种格式
     ///
     /// 支持的格式：
@@ -181,19 +260,49 @@ impl LoggingConfig {
     }
 }
 
+/// 解析字符串数组配置项
+fn string_list(value: &Value, field: &str) -> Result<Vec<String>, String> {
+    value
+        .as_array()
+        .ok_or_else(|| format!("{field} 必须是字符串数组"))?
+        .iter()
+        .map(|v| {
+            v.as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("{field} 必须是字符串数组"))
+        })
+        .collect()
+}
+
 impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
             level: "info".to_string(),
+            directives: BTreeMap::from([
+                ("hyper".to_string(), "warn".to_string()),
+                ("sqlx::query".to_string(), "warn".to_string()),
+            ]),
             console_format: "pretty".to_string(),
+            file_format: "json".to_string(),
             console: true,
+            target: "stdout".to_string(),
             file: false,
             file_dir: "./logs".to_string(),
             file_prefix: "app".to_string(),
             rotation: "daily".to_string(),
+            max_file_size_mb: 100,
             max_files: 30,
+            retention_days: 30,
+            max_total_size_mb: 1024,
             cleanup_enabled: true,
             cleanup_interval: 168,
+            sampling: BTreeMap::new(),
+            scrub: false,
+            scrub_keys: ["password", "passwd", "secret", "token", "api_key"]
+                .map(String::from)
+                .to_vec(),
+            scrub_allowlist: Vec::new(),
+            timezone: "utc".to_string(),
         }
     }
 }
@@ -208,16 +317,41 @@ impl ConfigSection for LoggingConfig {
             if let Some(level) = obj.get("level").and_then(|v| v.as_str()) {
                 self.level = level.to_string();
             }
+            if let Some(directives) = obj.get("directives").and_then(|v| v.as_object()) {
+                self.directives = directives
+                    .iter()
+                    .map(|(target, level)| {
+                        level
+                            .as_str()
+                            .map(|level| (target.clone(), level.to_string()))
+                            .ok_or_else(|| format!("日志级别指令必须是字符串：{target}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
             if let Some(format) = obj.get("console_format").and_then(|v| v.as_str()) {
                 self.console_format = format.to_string();
             }
+            if let Some(format) = obj.get("file_format").and_then(|v| v.as_str()) {
+                self.file_format = format.to_string();
+            }
             if let Some(console) = obj.get("console").and_then(|v| v.as_bool()) {
                 self.console = console;
             }
-            if let Some(file) = obj.get("file").and_then(|v| v.as_bool()) {
+            if let Some(target) = obj.get("target").and_then(|v| v.as_str()) {
+                self.target = target.to_string();
+            }
+            if let Some(file) = obj
+                .get("file")
+                .or_else(|| obj.get("file_enabled"))
+                .and_then(|v| v.as_bool())
+            {
                 self.file = file;
             }
-            if let Some(dir) = obj.get("file_dir").and_then(|v| v.as_str()) {
+            if let Some(dir) = obj
+                .get("file_dir")
+                .or_else(|| obj.get("directory"))
+                .and_then(|v| v.as_str())
+            {
                 self.file_dir = dir.to_string();
             }
             if let Some(prefix) = obj.get("file_prefix").and_then(|v| v.as_str()) {
@@ -226,9 +360,18 @@ impl ConfigSection for LoggingConfig {
             if let Some(rotation) = obj.get("rotation").and_then(|v| v.as_str()) {
                 self.rotation = rotation.to_string();
             }
+            if let Some(size) = obj.get("max_file_size_mb").and_then(|v| v.as_u64()) {
+                self.max_file_size_mb = size;
+            }
             if let Some(max_files) = obj.get("max_files").and_then(|v| v.as_u64()) {
                 self.max_files = max_files as usize;
             }
+            if let Some(days) = obj.get("retention_days").and_then(|v| v.as_u64()) {
+                self.retention_days = days;
+            }
+            if let Some(size) = obj.get("max_total_size_mb").and_then(|v| v.as_u64()) {
+                self.max_total_size_mb = size;
+            }
             if let Some(cleanup_enabled) = obj.get("cleanup_enabled").and_then(|v| v.as_bool()) {
                 self.cleanup_enabled = cleanup_enabled;
             }
@@ -236,6 +379,28 @@ impl ConfigSection for LoggingConfig {
                 // 支持多种格式：数字、"7x24"、"7d"、"168h" 等
                 self.cleanup_interval = self.parse_interval(interval_value)?;
             }
+            if let Some(sampling) = obj.get("sampling").and_then(|v| v.as_object()) {
+                self.sampling = sampling
+                    .iter()
+                    .map(|(path, rate)| {
+                        rate.as_f64()
+                            .map(|rate| (path.clone(), rate))
+                            .ok_or_else(|| format!("日志采样率必须是数字：{path}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            if let Some(scrub) = obj.get("scrub").and_then(|v| v.as_bool()) {
+                self.scrub = scrub;
+            }
+            if let Some(keys) = obj.get("scrub_keys") {
+                self.scrub_keys = string_list(keys, "scrub_keys")?;
+            }
+            if let Some(allowlist) = obj.get("scrub_allowlist") {
+                self.scrub_allowlist = string_list(allowlist, "scrub_allowlist")?;
+            }
+            if let Some(timezone) = obj.get("timezone").and_then(|v| v.as_str()) {
+                self.timezone = timezone.to_string();
+            }
         }
         Ok(())
     }
@@ -245,14 +410,49 @@ impl ConfigSection for LoggingConfig {
             "trace" | "debug" | "info" | "warn" | "error" => {}
             _ => return Err(format!("无效的日志级别：{}", self.level)),
         }
+        for (target, level) in &self.directives {
+            if target.is_empty() || target.contains([',', '=', ' ']) {
+                return Err(format!("无效的日志级别指令目标：\"{target}\""));
+            }
+            match level.as_str() {
+                "trace" | "debug" | "info" | "warn" | "error" | "off" => {}
+                _ => return Err(format!("无效的日志级别指令：{target} = {level}")),
+            }
+            format!("{target}={level}")
+                .parse::<tracing_subscriber::filter::Directive>()
+                .map_err(|e| format!("无效的日志级别指令：{target} = {level}（{e}）"))?;
+        }
         match self.console_format.as_str() {
-            "pretty" | "compact" => {}
+            "pretty" | "compact" | "json" => {}
             _ => return Err(format!("无效的控制台日志格式：{}", self.console_format)),
         }
+        match self.target.as_str() {
+            "stdout" | "stderr" => {}
+            _ => return Err(format!("无效的控制台日志输出目标：{}", self.target)),
+        }
+        match self.file_format.as_str() {
+            "pretty" | "compact" | "json" => {}
+            _ => return Err(format!("无效的文件日志格式：{}", self.file_format)),
+        }
         match self.rotation.as_str() {
-            "daily" | "hourly" | "never" => {}
+            "daily" | "hourly" | "size" | "never" => {}
             _ => return Err(format!("无效的日志轮转方式：{}", self.rotation)),
         }
+        if self.rotation == "size" && self.max_file_size_mb == 0 {
+            return Err("按大小轮转时 max_file_size_mb 必须大于 0".to_string());
+        }
+        for (path, rate) in &self.sampling {
+            if !path.starts_with('/') {
+                return Err(format!("日志采样路由必须以 / 开头：{path}"));
+            }
+            if !(0.0..=1.0).contains(rate) {
+                return Err(format!("日志采样率必须在 0.0 到 1.0 之间：{path} = {rate}"));
+            }
+        }
+        if self.scrub && self.scrub_keys.iter().all(|k| k.trim().is_empty()) {
+            return Err("启用日志脱敏时 scrub_keys 不能为空".to_string());
+        }
+        self.log_timezone()?;
         Ok(())
     }
 
@@ -267,6 +467,65 @@ impl ConfigSection for LoggingConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_filter_directives_merge_module_levels() {
+        let mut config = LoggingConfig::default();
+        assert_eq!(
+            config.filter_directives(),
+            "info,hyper=warn,sqlx::query=warn"
+        );
+
+        config
+            .load_from_value(&serde_json::json!({
+                "level": "warn",
+                "directives": { "app": "debug", "tower_http": "info" },
+            }))
+            .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.filter_directives(), "warn,app=debug,tower_http=info");
+    }
+
+    #[test]
+    fn test_invalid_directive_reports_key() {
+        let mut config = LoggingConfig::default();
+        config
+            .directives
+            .insert("tower_http".to_string(), "loud".to_string());
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("tower_http"), "{err}");
+
+        let mut config = LoggingConfig::default();
+        config
+            .directives
+            .insert("app,sqlx".to_string(), "debug".to_string());
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("app,sqlx"), "{err}");
+    }
+
+    #[test]
+    fn test_scrub_settings_load_and_validate() {
+        let mut config = LoggingConfig::default();
+        config
+            .load_from_value(&serde_json::json!({
+                "scrub": true,
+                "scrub_keys": ["password", "cookie"],
+                "scrub_allowlist": ["token_type"],
+            }))
+            .unwrap();
+        assert!(config.scrub);
+        assert_eq!(config.scrub_keys, ["password", "cookie"]);
+        assert_eq!(config.scrub_allowlist, ["token_type"]);
+        assert!(config.validate().is_ok());
+
+        config.scrub_keys.clear();
+        assert!(config.validate().is_err());
+        assert!(
+            config
+                .load_from_value(&serde_json::json!({ "scrub_keys": "password" }))
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_parse_interval_formats() {
         let config = LoggingConfig::default();
@@ -1,26 +1,82 @@
+mod alerting;
+mod api_keys;
+mod audit;
 mod cors;
 mod database;
+mod docs;
+mod file_scan;
+mod health;
+mod import;
 mod logging;
+mod object_storage;
+mod performance;
+mod profiling;
 mod redis;
+mod request_id;
 mod secrets;
 mod section;
+mod sentry;
 mod server;
+mod summary;
+mod tls;
+mod upload;
+mod welcome;
 
+pub use alerting::{AlertRule, AlertingConfig};
+pub use api_keys::{ApiKeyEntry, ApiKeysConfig};
+pub use audit::AuditConfig;
 pub use cors::CorsConfig;
-pub use database::DatabaseConfig;
+pub use database::{DatabaseConfig, TransientDbError};
+pub use docs::DocsConfig;
+pub use file_scan::{FileScanConfig, QuarantineAction, ScannerBackend};
+pub use health::HealthConfig;
+pub use import::ImportConfig;
 pub use logging::LoggingConfig;
+pub use object_storage::{MIN_S3_PART_SIZE_BYTES, ObjectStorageConfig, S3Config};
+pub use performance::PerformanceConfig;
+pub use profiling::ProfilingConfig;
 pub use redis::RedisConfig;
+pub use request_id::RequestIdConfig;
 pub use secrets::SecretsConfig;
 pub use section::ConfigSection;
+pub use sentry::SentryConfig;
 pub use server::ServerConfig;
+pub use summary::ConfigSummary;
+pub use tls::{TlsConfig, TlsVersion};
+pub use upload::{MIN_CHUNK_SIZE_BYTES, UploadConfig};
+pub use welcome::WelcomeConfig;
 
+use crate::core::feature_flags::FeatureFlags;
 use crate::error::ConfigError;
 use config::{Config, Environment, File};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 命令行覆盖项
+///
+/// 优先级高于配置文件和 `APP_*` 环境变量，便于容器部署时直接通过启动参数调整（如 `--port 8080`）。
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// 指定配置文件，替代 `config/` 目录下的分层配置文件
+    pub config_file: Option<PathBuf>,
+
+    /// 覆盖 `server.host`
+    pub host: Option<String>,
+
+    /// 覆盖 `server.port`
+    pub port: Option<u16>,
+
+    /// 覆盖 `logging.level`
+    pub log_level: Option<String>,
+
+    /// 不启动服务、不连接数据库（如导出 OpenAPI 文档），跳过只在运行时需要的
+    /// `database` 和 `secrets` 配置段的校验
+    pub offline: bool,
+}
 
 /// 应用程序配置入口
 ///
-/// 聚合所有配置段（服务器、数据库、日志、敏感信息、跨域、Redis）。
+/// 聚合所有配置段（服务器、HTTPS、数据库、日志、敏感信息、跨域、Redis、Sentry、功能开关）。
 /// 通过 `load()` 方法从配置文件和环境变量加载配置，支持多层次优先级管理。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -29,6 +85,9 @@ pub struct AppConfig {
     /// 服务器配置
     pub server: ServerConfig,
 
+    /// HTTPS 配置
+    pub tls: TlsConfig,
+
     /// 数据库配置
     pub database: DatabaseConfig,
 
@@ -43,6 +102,51 @@ pub struct AppConfig {
 
     /// Redis 连接池配置
     pub redis: RedisConfig,
+
+    /// Sentry 错误上报配置
+    pub sentry: SentryConfig,
+
+    /// 功能开关
+    pub features: FeatureFlags,
+
+    /// 审计配置
+    pub audit: AuditConfig,
+
+    /// 错误告警配置
+    pub alerting: AlertingConfig,
+
+    /// API 密钥认证配置
+    pub api_keys: ApiKeysConfig,
+
+    /// 文件上传配置
+    pub upload: UploadConfig,
+
+    /// 对象存储配置（文件内容写入的后端）
+    pub object_storage: ObjectStorageConfig,
+
+    /// 上传文件的恶意软件扫描配置
+    pub file_scan: FileScanConfig,
+
+    /// 请求 ID 配置（内部路由的传递与严格模式）
+    pub request_id: RequestIdConfig,
+
+    /// 健康检查配置（就绪检查详情的访问限制）
+    pub health: HealthConfig,
+
+    /// 批量导入配置（CSV 导入用户的行数和失败比例上限）
+    pub import: ImportConfig,
+
+    /// 性能调优配置（运行时线程数、TCP 连接选项）
+    pub performance: PerformanceConfig,
+
+    /// CPU 性能剖析配置（需要启用 `profiling` feature 的 debug 构建）
+    pub profiling: ProfilingConfig,
+
+    /// API 文档配置（是否启用、挂载路径）
+    pub docs: DocsConfig,
+
+    /// 根路径欢迎响应配置
+    pub welcome: WelcomeConfig,
 }
 
 impl AppConfig {
@@ -55,28 +159,56 @@ impl AppConfig {
     /// 3. `config/{APP_ENV}.toml` — 当前环境专属配置（默认 `development`）
     /// 4. `config/local.toml` — 个人本地覆盖（gitignored，不进版本库）
     /// 5. `APP_*` 环境变量
-    /// 6. 敏感信息环境变量（`DATABASE_URL`、`JWT_SECRET` 等，最高优先级）
+    /// 6. 命令行参数（`--host`、`--port`、`--log-level`）
+    /// 7. 敏感信息环境变量（`DATABASE_URL`、`JWT_SECRET` 等，最高优先级）
+    ///
+    /// 指定 `--config <path>` 时只加载该文件，替代第 2 ~ 4 层。
     ///
     /// # 环境选择
     ///
     /// 通过 `APP_ENV` 环境变量指定，默认为 `development`。
     /// 例如：`APP_ENV=production cargo run`
-    pub fn load() -> Result<Self, ConfigError> {
+    pub fn load(overrides: &ConfigOverrides) -> Result<Self, ConfigError> {
         dotenvy::dotenv().ok();
 
-        // 读取当前环境（默认 development）
-        let env = std::env::var("APP_ENV").unwrap_or_else(|_| "development".to_string());
-
         // 构建配置源（优先级从低到高）
-        let builder = Config::builder()
-            .add_source(File::with_name("config/default").required(false))
-            .add_source(File::with_name(&format!("config/{env}")).required(false))
-            .add_source(File::with_name("config/local").required(false))
-            .add_source(
-                Environment::with_prefix("APP_")
-                    .try_parsing(true)
-                    .separator("_"),
-            );
+        let mut builder = Config::builder();
+        match &overrides.config_file {
+            Some(path) => {
+                builder = builder.add_source(File::from(path.as_path()).required(true));
+            }
+            None => {
+                // 读取当前环境（默认 development）
+                let env = std::env::var("APP_ENV").unwrap_or_else(|_| "development".to_string());
+                builder = builder
+                    .add_source(File::with_name("config/default").required(false))
+                    .add_source(File::with_name(&format!("config/{env}")).required(false))
+                    .add_source(File::with_name("config/local").required(false));
+            }
+        }
+        builder = builder.add_source(
+            Environment::with_prefix("APP_")
+                .try_parsing(true)
+                .separator("_"),
+        );
+
+        // 命令行参数
+        let invalid = |e: config::ConfigError| ConfigError::Invalid(format!("命令行参数无效：{e}"));
+        if let Some(host) = &overrides.host {
+            builder = builder
+                .set_override("server.host", host.as_str())
+                .map_err(invalid)?;
+        }
+        if let Some(port) = overrides.port {
+            builder = builder
+                .set_override("server.port", i64::from(port))
+                .map_err(invalid)?;
+        }
+        if let Some(level) = &overrides.log_level {
+            builder = builder
+                .set_override("logging.level", level.as_str())
+                .map_err(invalid)?;
+        }
 
         let config = builder
             .build()
@@ -90,7 +222,7 @@ impl AppConfig {
         app_config.apply_env_overrides()?;
 
         // 验证配置
-        app_config.validate()?;
+        app_config.validate(overrides.offline)?;
 
         Ok(app_config)
     }
@@ -106,11 +238,27 @@ impl AppConfig {
             .map_err(|e| ConfigError::Invalid(format!("配置反序列化失败：{}", e)))?;
 
         self.server = app_config.server;
+        self.tls = app_config.tls;
         self.database = app_config.database;
         self.logging = app_config.logging;
         self.secrets = app_config.secrets;
         self.cors = app_config.cors;
         self.redis = app_config.redis;
+        self.sentry = app_config.sentry;
+        self.features = app_config.features;
+        self.audit = app_config.audit;
+        self.alerting = app_config.alerting;
+        self.api_keys = app_config.api_keys;
+        self.upload = app_config.upload;
+        self.object_storage = app_config.object_storage;
+        self.file_scan = app_config.file_scan;
+        self.request_id = app_config.request_id;
+        self.health = app_config.health;
+        self.import = app_config.import;
+        self.performance = app_config.performance;
+        self.profiling = app_config.profiling;
+        self.docs = app_config.docs;
+        self.welcome = app_config.welcome;
 
         Ok(())
     }
@@ -121,11 +269,27 @@ impl AppConfig {
     fn apply_env_overrides(&mut self) -> Result<(), ConfigError> {
         let sections: Vec<&mut dyn ConfigSection> = vec![
             &mut self.server,
+            &mut self.tls,
             &mut self.database,
             &mut self.logging,
             &mut self.secrets,
             &mut self.cors,
             &mut self.redis,
+            &mut self.sentry,
+            &mut self.features,
+            &mut self.audit,
+            &mut self.alerting,
+            &mut self.api_keys,
+            &mut self.upload,
+            &mut self.object_storage,
+            &mut self.file_scan,
+            &mut self.request_id,
+            &mut self.health,
+            &mut self.import,
+            &mut self.performance,
+            &mut self.profiling,
+            &mut self.docs,
+            &mut self.welcome,
         ];
 
         for section in sections {
@@ -143,16 +307,35 @@ impl AppConfig {
 
     /// 验证所有配置段
     ///
-    /// 确保所有配置值都符合规范和约束条件。
-    fn validate(&self) -> Result<(), ConfigError> {
-        let sections: Vec<&dyn ConfigSection> = vec![
+    /// 确保所有配置值都符合规范和约束条件。`offline` 为 true 时不校验 `database` 和 `secrets` 配置段。
+    fn validate(&self, offline: bool) -> Result<(), ConfigError> {
+        let mut sections: Vec<&dyn ConfigSection> = vec![
             &self.server,
+            &self.tls,
             &self.database,
             &self.logging,
             &self.secrets,
             &self.cors,
             &self.redis,
+            &self.sentry,
+            &self.features,
+            &self.audit,
+            &self.alerting,
+            &self.api_keys,
+            &self.upload,
+            &self.object_storage,
+            &self.file_scan,
+            &self.request_id,
+            &self.health,
+            &self.import,
+            &self.performance,
+            &self.profiling,
+            &self.docs,
+            &self.welcome,
         ];
+        if offline {
+            sections.retain(|section| !matches!(section.section_name(), "database" | "secrets"));
+        }
 
         for section in sections {
             section.validate().map_err(|e| {
@@ -160,6 +343,18 @@ impl AppConfig {
             })?;
         }
 
+        // 跨配置段的约束
+        if self.server.redirect_http && !self.tls.enabled {
+            return Err(ConfigError::Invalid(
+                "启用 server.redirect_http 时必须同时启用 tls".to_string(),
+            ));
+        }
+        if self.server.csrf_enabled && self.secrets.session_secret.is_none() {
+            return Err(ConfigError::Invalid(
+                "启用 server.csrf_enabled 时必须设置 SESSION_SECRET".to_string(),
+            ));
+        }
+
         Ok(())
     }
 
@@ -176,8 +371,21 @@ impl AppConfig {
     ///
     /// # 返回值
     ///
-    /// 成功返回 `Ok(())`，失败返回应用错误
-    pub fn init_tracing(&self) -> Result<(), crate::AppError> {
-        crate::core::logging::init_tracing(&self.logging)
+    /// 成功返回日志守卫（需在进程生命周期内保持存活，并携带运行时日志级别控制句柄），失败返回应用错误
+    pub fn init_tracing(&self) -> Result<crate::core::logging::LogGuard, crate::AppError> {
+        crate::core::logging::init_tracing(&self.logging, &self.sentry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_skips_runtime_sections() {
+        let config = AppConfig::default();
+        assert!(config.database.url.is_empty());
+        assert!(config.validate(false).is_err());
+        assert!(config.validate(true).is_ok());
     }
 }
@@ -0,0 +1,233 @@
+use std::env;
+
+use entity::enums::StorageBackend;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+use crate::shared::SharedSecret;
+
+/// S3 单个分片的最小大小（字节），S3 要求除最后一个分片外不小于 5 MiB
+pub const MIN_S3_PART_SIZE_BYTES: usize = 5 * 1024 * 1024;
+
+/// 对象存储配置
+///
+/// 决定新上传的文件写入哪个存储后端。本地磁盘始终可用（根目录为 `upload.storage_dir`），
+/// 配置了 `s3.bucket` 后 S3 也可用，即使 `backend = "local"`，便于在两个后端之间迁移文件。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObjectStorageConfig {
+    /// 新文件写入的存储后端：local / s3（默认：local）
+    pub backend: StorageBackend,
+
+    /// S3 兼容对象存储配置
+    pub s3: S3Config,
+}
+
+/// S3 兼容对象存储（AWS S3、MinIO 等）配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct S3Config {
+    /// 服务地址，如 `http://localhost:9000`（默认：为空，使用 `https://s3.{region}.amazonaws.com`）
+    pub endpoint: String,
+
+    /// 区域（默认：us-east-1）
+    pub region: String,
+
+    /// 存储桶名称，为空表示未配置 S3（默认：为空）
+    pub bucket: String,
+
+    /// 访问密钥 ID（可通过 `S3_ACCESS_KEY_ID` 环境变量覆盖）
+    pub access_key_id: String,
+
+    /// 访问密钥（建议通过 `S3_SECRET_ACCESS_KEY` 环境变量设置）
+    pub secret_access_key: SharedSecret,
+
+    /// 分片上传时单个分片的大小，单位字节，不小于 5 MiB（默认：8 MiB）
+    ///
+    /// 写入时最多缓冲一个分片，决定了单个上传占用的内存
+    pub part_size_bytes: usize,
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            region: "us-east-1".to_string(),
+            bucket: String::new(),
+            access_key_id: String::new(),
+            secret_access_key: SharedSecret::default(),
+            part_size_bytes: 8 * 1024 * 1024,
+        }
+    }
+}
+
+impl S3Config {
+    /// 是否配置了 S3
+    pub fn is_configured(&self) -> bool {
+        !self.bucket.is_empty()
+    }
+
+    /// 服务地址（不带末尾 `/`），未配置时使用 AWS 区域地址
+    pub fn endpoint_url(&self) -> String {
+        if self.endpoint.is_empty() {
+            format!("https://s3.{}.amazonaws.com", self.region)
+        } else {
+            self.endpoint.trim_end_matches('/').to_string()
+        }
+    }
+}
+
+impl ConfigSection for ObjectStorageConfig {
+    fn section_name(&self) -> &str {
+        "object_storage"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(backend) = obj.get("backend").and_then(|v| v.as_str()) {
+                self.backend = backend
+                    .parse()
+                    .map_err(|_| format!("backend 只能是 local 或 s3：{backend}"))?;
+            }
+            if let Some(s3) = obj.get("s3").and_then(|v| v.as_object()) {
+                let text = |key: &str| s3.get(key).and_then(|v| v.as_str()).map(String::from);
+                if let Some(endpoint) = text("endpoint") {
+                    self.s3.endpoint = endpoint;
+                }
+                if let Some(region) = text("region") {
+                    self.s3.region = region;
+                }
+                if let Some(bucket) = text("bucket") {
+                    self.s3.bucket = bucket;
+                }
+                if let Some(id) = text("access_key_id") {
+                    self.s3.access_key_id = id;
+                }
+                if let Some(secret) = text("secret_access_key") {
+                    self.s3.secret_access_key = SharedSecret::new(secret);
+                }
+                if let Some(size) = s3.get("part_size_bytes").and_then(|v| v.as_u64()) {
+                    self.s3.part_size_bytes = size as usize;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let s3 = &self.s3;
+        if self.backend == StorageBackend::S3 && !s3.is_configured() {
+            return Err("backend = \"s3\" 时必须配置 s3.bucket".to_string());
+        }
+        if !s3.is_configured() {
+            return Ok(());
+        }
+        if s3.access_key_id.is_empty() || s3.secret_access_key.is_empty() {
+            return Err(
+                "配置 s3.bucket 时必须设置 S3_ACCESS_KEY_ID 和 S3_SECRET_ACCESS_KEY".to_string(),
+            );
+        }
+        if s3.region.is_empty() {
+            return Err("s3.region 不能为空".to_string());
+        }
+        if !(s3.endpoint.is_empty()
+            || s3.endpoint.starts_with("http://")
+            || s3.endpoint.starts_with("https://"))
+        {
+            return Err(format!(
+                "s3.endpoint 必须以 http:// 或 https:// 开头：{}",
+                s3.endpoint
+            ));
+        }
+        if s3.part_size_bytes < MIN_S3_PART_SIZE_BYTES {
+            return Err(format!(
+                "s3.part_size_bytes 不能小于 {MIN_S3_PART_SIZE_BYTES}：{}",
+                s3.part_size_bytes
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(id) = env::var("S3_ACCESS_KEY_ID") {
+            self.s3.access_key_id = id;
+        }
+        if let Ok(secret) = env::var("S3_SECRET_ACCESS_KEY") {
+            self.s3.secret_access_key = SharedSecret::new(secret);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn configured() -> ObjectStorageConfig {
+        let mut config = ObjectStorageConfig::default();
+        config
+            .load_from_value(&json!({
+                "backend": "s3",
+                "s3": {
+                    "endpoint": "http://localhost:9000/",
+                    "bucket": "uploads",
+                    "access_key_id": "minio",
+                    "secret_access_key": "minio-secret",
+                },
+            }))
+            .unwrap();
+        config
+    }
+
+    #[test]
+    fn test_load_s3_config() {
+        let config = configured();
+        assert_eq!(config.backend, StorageBackend::S3);
+        assert_eq!(config.s3.endpoint_url(), "http://localhost:9000");
+        assert_eq!(&*config.s3.secret_access_key, "minio-secret");
+        assert!(config.validate().is_ok());
+
+        let mut config = ObjectStorageConfig::default();
+        assert!(
+            config
+                .load_from_value(&json!({ "backend": "gcs" }))
+                .is_err()
+        );
+        assert_eq!(
+            config.s3.endpoint_url(),
+            "https://s3.us-east-1.amazonaws.com"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(ObjectStorageConfig::default().validate().is_ok());
+
+        let config = ObjectStorageConfig {
+            backend: StorageBackend::S3,
+            ..Default::default()
+        };
+        assert!(config.validate().unwrap_err().contains("bucket"));
+
+        let mut config = configured();
+        config.s3.secret_access_key = SharedSecret::default();
+        assert!(
+            config
+                .validate()
+                .unwrap_err()
+                .contains("S3_SECRET_ACCESS_KEY")
+        );
+
+        let mut config = configured();
+        config.s3.part_size_bytes = 1024;
+        assert!(config.validate().unwrap_err().contains("part_size_bytes"));
+
+        // S3 只用于读取和迁移时同样需要完整配置
+        let mut config = configured();
+        config.backend = StorageBackend::Local;
+        config.s3.access_key_id.clear();
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use std::io;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use socket2::{SockRef, TcpKeepalive};
+use tokio::net::TcpStream;
+
+use super::section::ConfigSection;
+
+/// 性能调优配置
+///
+/// 控制 Tokio 运行时的线程数，以及接入的每个 TCP 连接的 `TCP_NODELAY` 和 keepalive 设置。
+/// 运行时在加载配置后、启动服务前构建，修改后需要重启进程才能生效。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerformanceConfig {
+    /// 异步工作线程数，不设置时使用 CPU 核数（或 `TOKIO_WORKER_THREADS` 环境变量）
+    pub tokio_worker_threads: Option<usize>,
+
+    /// 阻塞任务（如 `spawn_blocking`、文件 IO）线程池的上限（默认：512）
+    pub blocking_threads: usize,
+
+    /// 是否为每个连接启用 `TCP_NODELAY`，关闭 Nagle 算法以降低小响应的延迟（默认：true）
+    pub tcp_nodelay: bool,
+
+    /// 连接空闲多少秒后开始发送 TCP keepalive 探测，不设置时不启用（默认：不启用）
+    pub tcp_keepalive_secs: Option<u64>,
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self {
+            tokio_worker_threads: None,
+            blocking_threads: 512,
+            tcp_nodelay: true,
+            tcp_keepalive_secs: None,
+        }
+    }
+}
+
+impl PerformanceConfig {
+    /// 按配置创建多线程运行时的构建器（已启用全部驱动）
+    pub fn runtime_builder(&self) -> tokio::runtime::Builder {
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        builder
+            .enable_all()
+            .max_blocking_threads(self.blocking_threads);
+        if let Some(threads) = self.tokio_worker_threads {
+            builder.worker_threads(threads);
+        }
+        builder
+    }
+
+    /// 对接入的 TCP 连接应用 `TCP_NODELAY` 和 keepalive 设置
+    pub fn tune_stream(&self, stream: &TcpStream) -> io::Result<()> {
+        if self.tcp_nodelay {
+            stream.set_nodelay(true)?;
+        }
+        if let Some(secs) = self.tcp_keepalive_secs {
+            let keepalive = TcpKeepalive::new().with_time(Duration::from_secs(secs));
+            SockRef::from(stream).set_tcp_keepalive(&keepalive)?;
+        }
+        Ok(())
+    }
+}
+
+impl ConfigSection for PerformanceConfig {
+    fn section_name(&self) -> &str {
+        "performance"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(threads) = obj.get("tokio_worker_threads").and_then(|v| v.as_u64()) {
+                self.tokio_worker_threads = Some(threads as usize);
+            }
+            if let Some(threads) = obj.get("blocking_threads").and_then(|v| v.as_u64()) {
+                self.blocking_threads = threads as usize;
+            }
+            if let Some(nodelay) = obj.get("tcp_nodelay").and_then(|v| v.as_bool()) {
+                self.tcp_nodelay = nodelay;
+            }
+            if let Some(secs) = obj.get("tcp_keepalive_secs").and_then(|v| v.as_u64()) {
+                self.tcp_keepalive_secs = Some(secs);
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.tokio_worker_threads == Some(0) {
+            return Err("tokio_worker_threads 必须大于 0".to_string());
+        }
+        if self.blocking_threads == 0 {
+            return Err("blocking_threads 必须大于 0".to_string());
+        }
+        if self.tcp_keepalive_secs == Some(0) {
+            return Err("tcp_keepalive_secs 必须大于 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_load_and_validate() {
+        let mut config = PerformanceConfig::default();
+        config
+            .load_from_value(&json!({
+                "tokio_worker_threads": 2,
+                "blocking_threads": 64,
+                "tcp_nodelay": false,
+                "tcp_keepalive_secs": 60
+            }))
+            .unwrap();
+        assert_eq!(config.tokio_worker_threads, Some(2));
+        assert_eq!(config.blocking_threads, 64);
+        assert!(!config.tcp_nodelay);
+        assert_eq!(config.tcp_keepalive_secs, Some(60));
+        assert!(config.validate().is_ok());
+
+        for invalid in [
+            PerformanceConfig {
+                tokio_worker_threads: Some(0),
+                ..Default::default()
+            },
+            PerformanceConfig {
+                blocking_threads: 0,
+                ..Default::default()
+            },
+            PerformanceConfig {
+                tcp_keepalive_secs: Some(0),
+                ..Default::default()
+            },
+        ] {
+            assert!(invalid.validate().is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_runtime_builder_respects_worker_threads() {
+        let config = PerformanceConfig {
+            tokio_worker_threads: Some(3),
+            ..Default::default()
+        };
+        let runtime = config.runtime_builder().build().unwrap();
+        assert_eq!(runtime.metrics().num_workers(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_tune_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let config = PerformanceConfig {
+            tcp_keepalive_secs: Some(30),
+            ..Default::default()
+        };
+        config.tune_stream(&stream).unwrap();
+        assert!(stream.nodelay().unwrap());
+        assert!(SockRef::from(&stream).keepalive().unwrap());
+    }
+}
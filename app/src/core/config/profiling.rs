@@ -0,0 +1,143 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+use crate::core::IpNetwork;
+
+/// 单次剖析时长上限的最大可配置值（秒）
+const MAX_PROFILE_SECONDS: u64 = 300;
+
+/// CPU 性能剖析配置
+///
+/// 只在启用 `profiling` feature 的 debug 构建中生效：`GET /v1/admin/profile/cpu` 要求管理员身份，
+/// 且来源 IP 在 `allowed_ips` 内。其他构建不注册该接口，此配置段被忽略。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfilingConfig {
+    /// 可以发起剖析的来源 IP 或 CIDR 网段（默认：仅本机 `127.0.0.1`、`::1`），为空时拒绝所有请求
+    ///
+    /// 按 TCP 连接的对端地址匹配，不读取 `X-Forwarded-For`；部署在反向代理之后时应填写代理的地址
+    pub allowed_ips: Vec<String>,
+
+    /// 单次剖析的最长时间（秒），请求的时长超过时按此截断（默认：60，最大 300）
+    pub max_seconds: u64,
+
+    /// 采样频率（Hz）（默认：99）
+    pub frequency_hz: i32,
+}
+
+impl Default for ProfilingConfig {
+    fn default() -> Self {
+        Self {
+            allowed_ips: vec!["127.0.0.1".to_string(), "::1".to_string()],
+            max_seconds: 60,
+            frequency_hz: 99,
+        }
+    }
+}
+
+impl ConfigSection for ProfilingConfig {
+    fn section_name(&self) -> &str {
+        "profiling"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(ips) = obj.get("allowed_ips").and_then(|v| v.as_array()) {
+                self.allowed_ips = ips
+                    .iter()
+                    .map(|v| {
+                        v.as_str()
+                            .map(String::from)
+                            .ok_or_else(|| format!("allowed_ips 只能包含字符串：{v}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            if let Some(seconds) = obj.get("max_seconds").and_then(|v| v.as_u64()) {
+                self.max_seconds = seconds;
+            }
+            if let Some(hz) = obj.get("frequency_hz").and_then(|v| v.as_i64()) {
+                self.frequency_hz =
+                    i32::try_from(hz).map_err(|_| format!("frequency_hz 超出范围：{hz}"))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for ip in &self.allowed_ips {
+            ip.parse::<IpNetwork>()
+                .map_err(|e| format!("allowed_ips 包含无效的地址 {ip:?}：{e}"))?;
+        }
+        if !(1..=MAX_PROFILE_SECONDS).contains(&self.max_seconds) {
+            return Err(format!(
+                "max_seconds 必须在 1-{MAX_PROFILE_SECONDS} 之间：{}",
+                self.max_seconds
+            ));
+        }
+        if !(1..=1000).contains(&self.frequency_hz) {
+            return Err(format!(
+                "frequency_hz 必须在 1-1000 之间：{}",
+                self.frequency_hz
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(ips) = env::var("PROFILING_ALLOWED_IPS") {
+            self.allowed_ips = ips
+                .split(',')
+                .map(str::trim)
+                .filter(|ip| !ip.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_and_validate() {
+        let mut config = ProfilingConfig::default();
+        assert!(config.validate().is_ok());
+        config
+            .load_from_value(&json!({
+                "allowed_ips": ["10.0.0.0/8"],
+                "max_seconds": 30,
+                "frequency_hz": 199
+            }))
+            .unwrap();
+        assert_eq!(config.allowed_ips, ["10.0.0.0/8"]);
+        assert_eq!((config.max_seconds, config.frequency_hz), (30, 199));
+        assert!(config.validate().is_ok());
+
+        for invalid in [
+            ProfilingConfig {
+                allowed_ips: vec!["localhost".to_string()],
+                ..Default::default()
+            },
+            ProfilingConfig {
+                max_seconds: 0,
+                ..Default::default()
+            },
+            ProfilingConfig {
+                max_seconds: MAX_PROFILE_SECONDS + 1,
+                ..Default::default()
+            },
+            ProfilingConfig {
+                frequency_hz: 0,
+                ..Default::default()
+            },
+        ] {
+            assert!(invalid.validate().is_err(), "{invalid:?}");
+        }
+    }
+}
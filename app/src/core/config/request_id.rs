@@ -0,0 +1,122 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+
+/// 请求 ID 配置
+///
+/// 公开路由始终由本服务生成 `x-request-id`；`internal_route_prefixes` 下的内部路由沿用上游传入的
+/// 请求 ID，便于在服务网格中串联调用链。严格模式下内部路由缺少有效的 `x-request-id` 时返回 400。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestIdConfig {
+    /// 是否拒绝缺少 `x-request-id` 的内部路由请求（默认：false，缺少时生成）
+    pub strict: bool,
+
+    /// 内部路由的路径前缀，按路径段匹配（`/v1/internal` 匹配 `/v1/internal/jobs`，不匹配 `/v1/internals`）
+    pub internal_route_prefixes: Vec<String>,
+}
+
+impl ConfigSection for RequestIdConfig {
+    fn section_name(&self) -> &str {
+        "request_id"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(strict) = obj.get("strict").and_then(|v| v.as_bool()) {
+                self.strict = strict;
+            }
+            if let Some(prefixes) = obj
+                .get("internal_route_prefixes")
+                .and_then(|v| v.as_array())
+            {
+                self.internal_route_prefixes = prefixes
+                    .iter()
+                    .map(|v| {
+                        v.as_str()
+                            .map(String::from)
+                            .ok_or_else(|| format!("internal_route_prefixes 只能包含字符串：{v}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(prefix) = self
+            .internal_route_prefixes
+            .iter()
+            .find(|p| !p.starts_with('/') || p.trim_end_matches('/').is_empty())
+        {
+            return Err(format!(
+                "internal_route_prefixes 必须以 / 开头且不能为根路径：{prefix:?}"
+            ));
+        }
+        if self.strict && self.internal_route_prefixes.is_empty() {
+            return Err("启用 strict 时必须配置 internal_route_prefixes".to_string());
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(strict) = env::var("REQUEST_ID_STRICT") {
+            self.strict = strict
+                .parse()
+                .map_err(|_| format!("REQUEST_ID_STRICT 必须是 true 或 false：{strict}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_from_value() {
+        let mut config = RequestIdConfig::default();
+        config
+            .load_from_value(&json!({
+                "strict": true,
+                "internal_route_prefixes": ["/v1/internal"]
+            }))
+            .unwrap();
+        assert!(config.strict);
+        assert_eq!(config.internal_route_prefixes, ["/v1/internal"]);
+
+        let err = config
+            .load_from_value(&json!({ "internal_route_prefixes": [1] }))
+            .unwrap_err();
+        assert!(err.contains("只能包含字符串"));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(RequestIdConfig::default().validate().is_ok());
+
+        let strict_without_routes = RequestIdConfig {
+            strict: true,
+            ..Default::default()
+        };
+        assert!(strict_without_routes.validate().is_err());
+
+        for prefix in ["v1/internal", "/", ""] {
+            let config = RequestIdConfig {
+                internal_route_prefixes: vec![prefix.to_string()],
+                ..Default::default()
+            };
+            assert!(config.validate().is_err(), "{prefix:?}");
+        }
+
+        let config = RequestIdConfig {
+            strict: true,
+            internal_route_prefixes: vec!["/v1/internal/".to_string()],
+        };
+        assert!(config.validate().is_ok());
+    }
+}
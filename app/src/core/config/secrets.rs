@@ -3,6 +3,7 @@ use serde_json::Value;
 use std::env;
 
 use super::section::ConfigSection;
+use crate::shared::{SharedSecret, encryption};
 
 /// 敏感信息配置
 ///
@@ -12,10 +13,20 @@ use super::section::ConfigSection;
 #[derive(Default)]
 pub struct SecretsConfig {
     /// JWT 签名密钥（必需，至少 32 字符）
-    pub jwt_secret: String,
+    pub jwt_secret: SharedSecret,
 
     /// Redis 连接 URL（可选）
     pub redis_url: Option<String>,
+
+    /// 数据库敏感列加密密钥（可选，Base64 编码的 32 字节 AES-256 密钥）
+    ///
+    /// 使用 `EncryptedString` 列的实体必须配置此项
+    pub encryption_key: Option<SharedSecret>,
+
+    /// 会话密钥（可选，至少 32 字符），用于签名 CSRF 令牌
+    ///
+    /// 启用 `server.csrf_enabled` 时必须配置此项
+    pub session_secret: Option<SharedSecret>,
 }
 
 impl ConfigSection for SecretsConfig {
@@ -26,11 +37,17 @@ impl ConfigSection for SecretsConfig {
     fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
         if let Some(obj) = value.as_object() {
             if let Some(secret) = obj.get("jwt_secret").and_then(|v| v.as_str()) {
-                self.jwt_secret = secret.to_string();
+                self.jwt_secret = SharedSecret::new(secret);
             }
             if let Some(redis) = obj.get("redis_url").and_then(|v| v.as_str()) {
                 self.redis_url = Some(redis.to_string());
             }
+            if let Some(key) = obj.get("encryption_key").and_then(|v| v.as_str()) {
+                self.encryption_key = Some(SharedSecret::new(key));
+            }
+            if let Some(secret) = obj.get("session_secret").and_then(|v| v.as_str()) {
+                self.session_secret = Some(SharedSecret::new(secret));
+            }
         }
         Ok(())
     }
@@ -42,6 +59,14 @@ impl ConfigSection for SecretsConfig {
         if self.jwt_secret.len() < 32 {
             return Err("JWT 密钥长度必须至少 32 个字符".to_string());
         }
+        if let Some(key) = &self.encryption_key {
+            encryption::parse_key(key)?;
+        }
+        if let Some(secret) = &self.session_secret
+            && secret.len() < 32
+        {
+            return Err("会话密钥长度必须至少 32 个字符".to_string());
+        }
         Ok(())
     }
 
@@ -51,11 +76,17 @@ impl ConfigSection for SecretsConfig {
 
     fn apply_env_overrides(&mut self) -> Result<(), String> {
         if let Ok(secret) = env::var("JWT_SECRET") {
-            self.jwt_secret = secret;
+            self.jwt_secret = SharedSecret::new(secret);
         }
         if let Ok(redis) = env::var("REDIS_URL") {
             self.redis_url = Some(redis);
         }
+        if let Ok(key) = env::var("ENCRYPTION_KEY") {
+            self.encryption_key = Some(SharedSecret::new(key));
+        }
+        if let Ok(secret) = env::var("SESSION_SECRET") {
+            self.session_secret = Some(SharedSecret::new(secret));
+        }
         Ok(())
     }
 }
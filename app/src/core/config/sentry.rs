@@ -0,0 +1,86 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+
+/// Sentry 错误上报配置
+///
+/// 配置 DSN 后，应用在启用 `sentry` cargo feature 构建时会上报 panic 和 5xx 错误。
+/// 未启用该 feature 时此配置仅被解析，不产生任何效果。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SentryConfig {
+    /// Sentry DSN（可选，未配置时不启用上报）
+    pub dsn: Option<String>,
+
+    /// 环境标识（如 production、staging）（默认：不设置）
+    pub environment: Option<String>,
+
+    /// 错误事件采样率，0.0-1.0（默认：1.0）
+    pub sample_rate: f32,
+
+    /// 性能追踪采样率，0.0-1.0（默认：0.0，即不采集）
+    pub traces_sample_rate: f32,
+}
+
+impl Default for SentryConfig {
+    fn default() -> Self {
+        Self {
+            dsn: None,
+            environment: None,
+            sample_rate: 1.0,
+            traces_sample_rate: 0.0,
+        }
+    }
+}
+
+impl ConfigSection for SentryConfig {
+    fn section_name(&self) -> &str {
+        "sentry"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(dsn) = obj.get("dsn").and_then(|v| v.as_str()) {
+                self.dsn = Some(dsn.to_string());
+            }
+            if let Some(environment) = obj.get("environment").and_then(|v| v.as_str()) {
+                self.environment = Some(environment.to_string());
+            }
+            if let Some(rate) = obj.get("sample_rate").and_then(|v| v.as_f64()) {
+                self.sample_rate = rate as f32;
+            }
+            if let Some(rate) = obj.get("traces_sample_rate").and_then(|v| v.as_f64()) {
+                self.traces_sample_rate = rate as f32;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (name, rate) in [
+            ("sample_rate", self.sample_rate),
+            ("traces_sample_rate", self.traces_sample_rate),
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(format!("{name} 必须在 0.0-1.0 之间：{rate}"));
+            }
+        }
+        if self.dsn.as_ref().is_some_and(|dsn| dsn.is_empty()) {
+            return Err("Sentry DSN 不能为空字符串".to_string());
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(dsn) = env::var("SENTRY_DSN") {
+            self.dsn = Some(dsn);
+        }
+        if let Ok(environment) = env::var("SENTRY_ENVIRONMENT") {
+            self.environment = Some(environment);
+        }
+        Ok(())
+    }
+}
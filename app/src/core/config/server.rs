@@ -1,3 +1,7 @@
+use std::collections::BTreeMap;
+use std::env;
+
+use axum::http::Method;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
@@ -17,14 +21,108 @@ pub struct ServerConfig {
 
     /// 请求超时时间，单位秒（默认：30）
     pub timeout: u64,
+
+    /// 优雅关闭时等待在途请求完成的最长时间，单位秒，超时后强制退出（默认：30）
+    pub graceful_shutdown_timeout_secs: u64,
+
+    /// `/health/ready` 检查结果的缓存时间，单位毫秒，0 表示不缓存（默认：1000）
+    pub readiness_cache_ttl_ms: u64,
+
+    /// 全局请求体大小上限，单位字节，作用于 `Json`、`Bytes`、`Multipart` 等提取器（默认：2 MiB）
+    pub body_limit_bytes: usize,
+
+    /// 按路由前缀覆盖请求体大小上限：挂载路径 → 字节数，如 `{ "/v1/upload" = 52428800 }`
+    ///
+    /// 覆盖值对该前缀下的路由优先于 `body_limit_bytes`，可以更大也可以更小（默认：为空）
+    pub route_body_limits: BTreeMap<String, usize>,
+
+    /// 静态文件目录，挂载在 `/static` 下（默认：app/assets）
+    pub static_dir: String,
+
+    /// 是否启用单页应用回退：未匹配的非 API 路径返回静态目录下的 `index.html`（默认：false）
+    pub spa_fallback: bool,
+
+    /// 前端单页应用的挂载路径，如 `/app`；设置且 `spa_dir` 存在时，该路径下的请求由 `spa_dir` 中的文件响应，
+    /// 文件不存在时返回 `index.html`（深层链接由前端路由接管）（默认：不挂载）
+    pub spa_path: Option<String>,
+
+    /// 前端单页应用的构建产物目录（默认：app/dist）
+    pub spa_dir: String,
+
+    /// 对外访问的基础 URL，用于拼接下载链接等绝对地址（如 `https://api.example.com`）
+    ///
+    /// 未配置时回退为 `http://host:port`
+    pub public_url: Option<String>,
+
+    /// 当前实例的机器 ID，用于 snowflake ID 生成，多实例部署时必须互不相同（默认：0）
+    pub machine_id: u16,
+
+    /// 是否格式化（缩进）输出 JSON 响应，便于开发时手动调试（默认：false）
+    ///
+    /// `logging.level = "debug"` 时自动启用；生产环境保持紧凑输出以节省带宽
+    pub pretty_json: bool,
+
+    /// 是否启用表单请求的 CSRF 防护，需同时设置 `SESSION_SECRET`（默认：false）
+    pub csrf_enabled: bool,
+
+    /// 是否额外监听 HTTP 端口并将请求重定向到 HTTPS，需启用 `tls`（默认：false）
+    pub redirect_http: bool,
+
+    /// HTTP 重定向监听端口（默认：80）
+    pub redirect_http_port: u16,
+
+    /// 全局允许的 HTTP 方法，其他方法（如 `TRACE`、`CONNECT`）在路由之前直接返回 405
+    ///
+    /// 默认：GET、HEAD、POST、PUT、PATCH、DELETE、OPTIONS
+    pub allowed_methods: Vec<String>,
 }
 
+/// 默认允许的 HTTP 方法
+const DEFAULT_ALLOWED_METHODS: [&str; 7] =
+    ["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"];
+
 impl Default for ServerConfig {
     fn default() -> Self {
         Self {
             host: "127.0.0.1".to_string(),
             port: 3001,
             timeout: 30,
+            graceful_shutdown_timeout_secs: 30,
+            readiness_cache_ttl_ms: 1000,
+            body_limit_bytes: 2 * 1024 * 1024,
+            route_body_limits: BTreeMap::new(),
+            static_dir: "app/assets".to_string(),
+            spa_fallback: false,
+            spa_path: None,
+            spa_dir: "app/dist".to_string(),
+            public_url: None,
+            machine_id: 0,
+            pretty_json: false,
+            csrf_enabled: false,
+            redirect_http: false,
+            redirect_http_port: 80,
+            allowed_methods: DEFAULT_ALLOWED_METHODS.map(String::from).to_vec(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// 解析 `allowed_methods`，方法名统一为大写
+    pub fn allowed_methods(&self) -> Result<Vec<Method>, String> {
+        self.allowed_methods
+            .iter()
+            .map(|name| {
+                Method::from_bytes(name.to_ascii_uppercase().as_bytes())
+                    .map_err(|_| format!("allowed_methods 包含无效的 HTTP 方法：{name}"))
+            })
+            .collect()
+    }
+
+    /// 解析对外访问的基础 URL（不带末尾 `/`）
+    pub fn resolved_public_url(&self) -> String {
+        match &self.public_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("http://{}:{}", self.host, self.port),
         }
     }
 }
@@ -45,6 +143,72 @@ impl ConfigSection for ServerConfig {
             if let Some(timeout) = obj.get("timeout").and_then(|v| v.as_u64()) {
                 self.timeout = timeout;
             }
+            if let Some(secs) = obj
+                .get("graceful_shutdown_timeout_secs")
+                .and_then(|v| v.as_u64())
+            {
+                self.graceful_shutdown_timeout_secs = secs;
+            }
+            if let Some(ttl) = obj.get("readiness_cache_ttl_ms").and_then(|v| v.as_u64()) {
+                self.readiness_cache_ttl_ms = ttl;
+            }
+            if let Some(limit) = obj.get("body_limit_bytes").and_then(|v| v.as_u64()) {
+                self.body_limit_bytes = limit as usize;
+            }
+            if let Some(limits) = obj.get("route_body_limits").and_then(|v| v.as_object()) {
+                self.route_body_limits = limits
+                    .iter()
+                    .map(|(prefix, limit)| {
+                        limit
+                            .as_u64()
+                            .map(|limit| (prefix.clone(), limit as usize))
+                            .ok_or_else(|| format!("请求体大小上限必须是非负整数：{prefix}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            if let Some(dir) = obj.get("static_dir").and_then(|v| v.as_str()) {
+                self.static_dir = dir.to_string();
+            }
+            if let Some(spa) = obj.get("spa_fallback").and_then(|v| v.as_bool()) {
+                self.spa_fallback = spa;
+            }
+            if let Some(path) = obj.get("spa_path").and_then(|v| v.as_str()) {
+                self.spa_path = Some(path.trim_end_matches('/').to_string());
+            }
+            if let Some(dir) = obj.get("spa_dir").and_then(|v| v.as_str()) {
+                self.spa_dir = dir.to_string();
+            }
+            if let Some(url) = obj.get("public_url").and_then(|v| v.as_str()) {
+                self.public_url = Some(url.to_string());
+            }
+            if let Some(pretty) = obj.get("pretty_json").and_then(|v| v.as_bool()) {
+                self.pretty_json = pretty;
+            }
+            if let Some(csrf) = obj.get("csrf_enabled").and_then(|v| v.as_bool()) {
+                self.csrf_enabled = csrf;
+            }
+            if let Some(redirect) = obj.get("redirect_http").and_then(|v| v.as_bool()) {
+                self.redirect_http = redirect;
+            }
+            if let Some(port) = obj.get("redirect_http_port").and_then(|v| v.as_u64()) {
+                self.redirect_http_port = u16::try_from(port)
+                    .map_err(|_| format!("redirect_http_port 必须在 1-65535 之间：{port}"))?;
+            }
+            if let Some(methods) = obj.get("allowed_methods").and_then(|v| v.as_array()) {
+                self.allowed_methods = methods
+                    .iter()
+                    .map(|method| {
+                        method
+                            .as_str()
+                            .map(str::to_string)
+                            .ok_or_else(|| format!("allowed_methods 必须是字符串数组：{method}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            if let Some(machine_id) = obj.get("machine_id").and_then(|v| v.as_u64()) {
+                self.machine_id = u16::try_from(machine_id)
+                    .map_err(|_| format!("machine_id 必须在 0-65535 之间：{machine_id}"))?;
+            }
         }
         Ok(())
     }
@@ -56,6 +220,58 @@ impl ConfigSection for ServerConfig {
         if self.timeout == 0 {
             return Err("服务器超时时间必须大于 0".to_string());
         }
+        if self.body_limit_bytes == 0 {
+            return Err("body_limit_bytes 必须大于 0".to_string());
+        }
+        for (prefix, limit) in &self.route_body_limits {
+            if !prefix.starts_with('/') || prefix.ends_with('/') {
+                return Err(format!(
+                    "请求体大小覆盖的路由前缀必须以 / 开头且不以 / 结尾：{prefix}"
+                ));
+            }
+            if *limit == 0 {
+                return Err(format!("请求体大小上限必须大于 0：{prefix}"));
+            }
+        }
+        if self.redirect_http
+            && (self.redirect_http_port == 0 || self.redirect_http_port == self.port)
+        {
+            return Err(format!(
+                "redirect_http_port 不能为 0 或与 port 相同：{}",
+                self.redirect_http_port
+            ));
+        }
+        if self.graceful_shutdown_timeout_secs == 0 {
+            return Err("graceful_shutdown_timeout_secs 必须大于 0".to_string());
+        }
+        if self.static_dir.is_empty() {
+            return Err("静态文件目录不能为空".to_string());
+        }
+        if let Some(path) = &self.spa_path
+            && (!path.starts_with('/') || path.len() < 2)
+        {
+            return Err(format!("spa_path 必须以 / 开头且不能是根路径：{path}"));
+        }
+        if self.allowed_methods()?.is_empty() {
+            return Err("allowed_methods 不能为空".to_string());
+        }
+        if let Some(url) = &self.public_url
+            && !(url.starts_with("http://") || url.starts_with("https://"))
+        {
+            return Err(format!("public_url 必须以 http:// 或 https:// 开头：{url}"));
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(url) = env::var("PUBLIC_URL") {
+            self.public_url = Some(url);
+        }
+        if let Ok(machine_id) = env::var("MACHINE_ID") {
+            self.machine_id = machine_id
+                .parse()
+                .map_err(|_| format!("MACHINE_ID 必须在 0-65535 之间：{machine_id}"))?;
+        }
         Ok(())
     }
 }
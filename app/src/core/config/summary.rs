@@ -0,0 +1,584 @@
+//! 启动配置摘要
+//!
+//! 启动时把各配置段的生效值汇总为一条日志，排查问题时可直接贴进 issue。
+//! 摘要中的值均已脱敏：密钥只显示是否配置，连接 URL 隐去账号密码，webhook 只保留 scheme 和 host。
+//!
+//! 输出到终端时使用 [`AppConfig::to_display_table`] 的对齐表格，输出到文件或管道时使用
+//! [`ConfigSummary::to_json`] 的结构化字段，便于日志系统解析。
+
+use std::fmt;
+
+use reqwest::Url;
+
+use super::{AppConfig, ScannerBackend};
+use crate::core::rate_limit::{GLOBAL_RATE_LIMIT_BURST, GLOBAL_RATE_LIMIT_PERIOD_SECS};
+
+/// 脱敏占位文本
+const REDACTED: &str = "[REDACTED]";
+
+/// 配置摘要，按 `段.字段 → 值` 顺序排列
+#[derive(Debug, Clone)]
+pub struct ConfigSummary {
+    entries: Vec<(&'static str, String)>,
+}
+
+impl ConfigSummary {
+    /// 摘要条目
+    pub fn entries(&self) -> &[(&'static str, String)] {
+        &self.entries
+    }
+
+    /// 序列化为单行 JSON 对象，作为结构化日志字段输出
+    pub fn to_json(&self) -> String {
+        let map: serde_json::Map<_, _> = self
+            .entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), serde_json::Value::from(value.as_str())))
+            .collect();
+        serde_json::Value::Object(map).to_string()
+    }
+}
+
+/// 以对齐的 `段 | 字段 | 值` 三列表格输出，供终端阅读
+///
+/// 同一段的条目相邻排列，段名只在该段的第一行显示；没有字段名的条目（如 `tls`）字段列留空。
+impl fmt::Display for ConfigSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const HEADER: [&str; 3] = ["SECTION", "FIELD", "VALUE"];
+        let rows: Vec<[&str; 3]> = self
+            .entries
+            .iter()
+            .map(|(key, value)| {
+                let (section, field) = key.split_once('.').unwrap_or((key, ""));
+                [section, field, value.as_str()]
+            })
+            .collect();
+        let widths: [usize; 3] = std::array::from_fn(|column| {
+            rows.iter()
+                .chain([&HEADER])
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        });
+        let rule = widths
+            .iter()
+            .map(|width| "-".repeat(width + 2))
+            .fold(String::from("+"), |rule, dashes| rule + &dashes + "+");
+        let line = |f: &mut fmt::Formatter<'_>, row: [&str; 3]| {
+            let cells: Vec<_> = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| pad(cell, width))
+                .collect();
+            writeln!(f, "| {} |", cells.join(" | "))
+        };
+
+        writeln!(f, "{rule}")?;
+        line(f, HEADER)?;
+        writeln!(f, "{rule}")?;
+        let mut previous = None;
+        for &[section, field, value] in &rows {
+            let first = previous != Some(section);
+            if first && previous.is_some() {
+                writeln!(f, "{rule}")?;
+            }
+            line(f, [if first { section } else { "" }, field, value])?;
+            previous = Some(section);
+        }
+        writeln!(f, "{rule}")
+    }
+}
+
+/// 按字符数右侧补空格到 `width`（`format!` 的宽度按字符计，这里显式处理以免误用字节长度）
+fn pad(value: &str, width: usize) -> String {
+    let len = value.chars().count();
+    format!("{value}{}", " ".repeat(width.saturating_sub(len)))
+}
+
+impl AppConfig {
+    /// 生成脱敏后的启动配置摘要
+    pub fn summary(&self) -> ConfigSummary {
+        let env = std::env::var("APP_ENV").unwrap_or_else(|_| "development".to_string());
+        let server = &self.server;
+        let logging = &self.logging;
+        let sentry = &self.sentry;
+
+        let entries = vec![
+            ("env", env),
+            ("server.addr", self.server_addr()),
+            ("server.public_url", server.resolved_public_url()),
+            ("server.timeout_secs", server.timeout.to_string()),
+            (
+                "server.graceful_shutdown_timeout_secs",
+                server.graceful_shutdown_timeout_secs.to_string(),
+            ),
+            (
+                "server.body_limit_bytes",
+                server.body_limit_bytes.to_string(),
+            ),
+            (
+                "server.route_body_limits",
+                server.route_body_limits.len().to_string(),
+            ),
+            ("server.static_dir", server.static_dir.clone()),
+            ("server.spa_fallback", server.spa_fallback.to_string()),
+            (
+                "server.spa_path",
+                match &server.spa_path {
+                    Some(path) => format!("{path} -> {}", server.spa_dir),
+                    None => "off".to_string(),
+                },
+            ),
+            ("server.machine_id", server.machine_id.to_string()),
+            ("server.pretty_json", server.pretty_json.to_string()),
+            ("server.csrf_enabled", server.csrf_enabled.to_string()),
+            ("server.allowed_methods", server.allowed_methods.join(",")),
+            (
+                "tls",
+                on_off(self.tls.enabled, || {
+                    format!(
+                        "{} (min TLS {}, redirect {})",
+                        self.tls.cert_path,
+                        self.tls.min_tls_version.as_str(),
+                        on_off(server.redirect_http, || {
+                            server.redirect_http_port.to_string()
+                        })
+                    )
+                }),
+            ),
+            ("database.url", mask_credentials(&self.database.url)),
+            (
+                "database.max_connections",
+                self.database.max_connections.to_string(),
+            ),
+            (
+                "database.pool_timeout_secs",
+                self.database.pool_timeout.to_string(),
+            ),
+            (
+                "database.ssl_mode",
+                match &self.database.ssl_mode {
+                    Some(mode) => match &self.database.ssl_root_cert {
+                        Some(cert) => format!("{mode} (ca: {cert})"),
+                        None => mode.to_string(),
+                    },
+                    None => "default".to_string(),
+                },
+            ),
+            (
+                "database.statement_timeout_ms",
+                self.database
+                    .statement_timeout_ms
+                    .map_or_else(|| "default".to_string(), |ms| ms.to_string()),
+            ),
+            (
+                "database.idle_transaction_timeout_ms",
+                self.database
+                    .idle_transaction_timeout_ms
+                    .map_or_else(|| "default".to_string(), |ms| ms.to_string()),
+            ),
+            (
+                "database.read_retry_on",
+                if self.database.read_retry_on.is_empty() {
+                    "off".to_string()
+                } else {
+                    format!(
+                        "{} (delay: {}ms)",
+                        self.database
+                            .read_retry_on
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        self.database.read_retry_delay_ms
+                    )
+                },
+            ),
+            ("logging.filter", logging.filter_directives()),
+            (
+                "logging.console",
+                on_off(logging.console, || {
+                    format!("{} -> {}", logging.console_format, logging.target)
+                }),
+            ),
+            (
+                "logging.file",
+                on_off(logging.file, || {
+                    format!(
+                        "{} -> {} ({})",
+                        logging.file_format, logging.file_dir, logging.rotation
+                    )
+                }),
+            ),
+            ("logging.scrub", logging.scrub.to_string()),
+            ("logging.timezone", logging.timezone.clone()),
+            (
+                "secrets.jwt_secret",
+                configured(!self.secrets.jwt_secret.is_empty()),
+            ),
+            (
+                "secrets.encryption_key",
+                configured(self.secrets.encryption_key.is_some()),
+            ),
+            (
+                "secrets.session_secret",
+                configured(self.secrets.session_secret.is_some()),
+            ),
+            (
+                "cors.allow_origins",
+                self.cors.allow_origins.len().to_string(),
+            ),
+            (
+                "cors.allow_credentials",
+                self.cors.allow_credentials.to_string(),
+            ),
+            (
+                "rate_limit.global",
+                format!(
+                    "burst {GLOBAL_RATE_LIMIT_BURST}, +1 / {GLOBAL_RATE_LIMIT_PERIOD_SECS}s per IP"
+                ),
+            ),
+            (
+                "docs",
+                on_off(self.docs_enabled(), || {
+                    let mode = if self.docs.preload { "preload" } else { "lazy" };
+                    let auth = match (&self.docs.username, self.docs.allow_admin_token) {
+                        (Some(username), true) => format!(", basic {username} / admin token"),
+                        (Some(username), false) => format!(", basic {username}"),
+                        (None, true) => ", admin token".to_string(),
+                        (None, false) => String::new(),
+                    };
+                    format!("{} ({mode}{auth})", self.docs.path)
+                }),
+            ),
+            (
+                "welcome",
+                if self.welcome.html {
+                    "json / html".to_string()
+                } else {
+                    "json".to_string()
+                },
+            ),
+            (
+                "redis",
+                self.redis
+                    .url
+                    .as_deref()
+                    .map_or_else(|| "off".to_string(), mask_credentials),
+            ),
+            (
+                "telemetry.sentry",
+                on_off(sentry_active(sentry.dsn.is_some()), || {
+                    format!(
+                        "{} (env {}, sample {}, traces {})",
+                        sentry
+                            .dsn
+                            .as_deref()
+                            .map_or_else(String::new, mask_credentials),
+                        sentry.environment.as_deref().unwrap_or("-"),
+                        sentry.sample_rate,
+                        sentry.traces_sample_rate
+                    )
+                }),
+            ),
+            (
+                "api_keys",
+                self.api_keys
+                    .keys
+                    .iter()
+                    .map(|entry| entry.label.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            ("features", self.features.enabled().join(", ")),
+            (
+                "upload",
+                format!(
+                    "max {} bytes, {} allowed / {} denied / {} inline mime types -> {}",
+                    self.upload.max_file_size_bytes,
+                    self.upload.allowed_mime_types.len(),
+                    self.upload.denied_mime_types.len(),
+                    self.upload.inline_mime_types.len(),
+                    self.upload.storage_dir
+                ),
+            ),
+            (
+                "object_storage",
+                format!(
+                    "{}{}",
+                    self.object_storage.backend,
+                    if self.object_storage.s3.is_configured() {
+                        format!(
+                            " (s3: {}/{})",
+                            self.object_storage.s3.endpoint_url(),
+                            self.object_storage.s3.bucket
+                        )
+                    } else {
+                        String::new()
+                    }
+                ),
+            ),
+            (
+                "file_scan",
+                match self.file_scan.backend {
+                    ScannerBackend::None => "none".to_string(),
+                    backend => format!(
+                        "{backend} ({}:{}), quarantine: {}",
+                        self.file_scan.clamd_host,
+                        self.file_scan.clamd_port,
+                        self.file_scan.quarantine
+                    ),
+                },
+            ),
+            (
+                "request_id",
+                format!(
+                    "{}, internal routes: [{}]",
+                    if self.request_id.strict {
+                        "strict"
+                    } else {
+                        "lenient"
+                    },
+                    self.request_id.internal_route_prefixes.join(", ")
+                ),
+            ),
+            (
+                "health",
+                if self.health.is_guarded() {
+                    format!(
+                        "guarded (api key: {}, allowed ips: [{}])",
+                        if self.health.require_api_key {
+                            "on"
+                        } else {
+                            "off"
+                        },
+                        self.health.allowed_ips.join(", ")
+                    )
+                } else {
+                    "public".to_string()
+                },
+            ),
+            (
+                "import",
+                format!(
+                    "max {} rows, max error rate {}%",
+                    self.import.max_rows, self.import.max_error_rate_percent
+                ),
+            ),
+            (
+                "performance",
+                format!(
+                    "workers: {}, blocking: {}, nodelay: {}, keepalive: {}",
+                    self.performance
+                        .tokio_worker_threads
+                        .map_or_else(|| "auto".to_string(), |n| n.to_string()),
+                    self.performance.blocking_threads,
+                    if self.performance.tcp_nodelay {
+                        "on"
+                    } else {
+                        "off"
+                    },
+                    self.performance
+                        .tcp_keepalive_secs
+                        .map_or_else(|| "off".to_string(), |s| format!("{s}s")),
+                ),
+            ),
+            (
+                "profiling",
+                on_off(profiling_active(), || {
+                    format!(
+                        "max {}s @ {}Hz, allowed ips: [{}]",
+                        self.profiling.max_seconds,
+                        self.profiling.frequency_hz,
+                        self.profiling.allowed_ips.join(", ")
+                    )
+                }),
+            ),
+            (
+                "audit.redact_fields",
+                self.audit.redact_fields.len().to_string(),
+            ),
+            (
+                "alerting",
+                on_off(self.alerting.is_enabled(), || {
+                    format!(
+                        "{} rules -> {}",
+                        self.alerting.rules.len(),
+                        self.alerting
+                            .webhook_url
+                            .as_deref()
+                            .map_or_else(String::new, origin_only)
+                    )
+                }),
+            ),
+        ];
+
+        ConfigSummary { entries }
+    }
+
+    /// 以对齐的表格展示脱敏后的配置，按配置段分组，供启动时输出到终端
+    pub fn to_display_table(&self) -> String {
+        self.summary().to_string()
+    }
+
+    /// 是否挂载 API 文档路由：以 `docs.enabled` 为准，未配置时仅在 debug 日志级别启用
+    pub fn docs_enabled(&self) -> bool {
+        self.docs
+            .enabled
+            .unwrap_or_else(|| self.logging.level == "debug")
+    }
+}
+
+/// Sentry 是否实际生效：需要配置 DSN 且构建时启用了 `sentry` feature
+fn sentry_active(dsn_configured: bool) -> bool {
+    dsn_configured && cfg!(feature = "sentry")
+}
+
+/// CPU 性能剖析接口是否注册：只在启用 `profiling` feature 的 debug 构建中存在
+fn profiling_active() -> bool {
+    cfg!(all(feature = "profiling", debug_assertions))
+}
+
+fn on_off(enabled: bool, detail: impl FnOnce() -> String) -> String {
+    if enabled { detail() } else { "off".to_string() }
+}
+
+fn configured(present: bool) -> String {
+    if present { REDACTED } else { "missing" }.to_string()
+}
+
+/// 隐去 URL 中的账号和密码，无法解析时整体脱敏
+fn mask_credentials(raw: &str) -> String {
+    let Ok(mut url) = Url::parse(raw) else {
+        return REDACTED.to_string();
+    };
+    if url.password().is_some() {
+        let _ = url.set_password(Some(REDACTED));
+    } else if !url.username().is_empty() {
+        // 无密码时用户名本身就是凭据，如 Sentry DSN 的公钥
+        let _ = url.set_username(REDACTED);
+    }
+    url.to_string()
+}
+
+/// 只保留 scheme 和 host，webhook 的路径和查询参数中常带有令牌
+fn origin_only(raw: &str) -> String {
+    match Url::parse(raw) {
+        Ok(url) => url.origin().ascii_serialization(),
+        Err(_) => REDACTED.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::AlertRule;
+    use crate::shared::SharedSecret;
+
+    #[test]
+    fn test_masks_urls() {
+        assert_eq!(
+            mask_credentials("postgres://app:hunter2@db:5432/app"),
+            "postgres://app:%5BREDACTED%5D@db:5432/app"
+        );
+        assert_eq!(
+            mask_credentials("https://abc123@o1.ingest.sentry.io/42"),
+            "https://%5BREDACTED%5D@o1.ingest.sentry.io/42"
+        );
+        assert_eq!(mask_credentials("redis://cache:6379"), "redis://cache:6379");
+        assert_eq!(mask_credentials("not a url"), REDACTED);
+        assert_eq!(
+            origin_only("https://hooks.slack.com/services/T000/B000/XXXX?token=y"),
+            "https://hooks.slack.com"
+        );
+    }
+
+    #[test]
+    fn test_summary_covers_sections_without_secrets() {
+        let mut config = AppConfig::default();
+        config.database.url = "postgres://app:db-password@db/app".to_string();
+        config.secrets.jwt_secret = SharedSecret::new("jwt-secret-value-at-least-32-chars");
+        config.redis.url = Some("redis://:redis-password@cache:6379/0".to_string());
+        config.sentry.dsn = Some("https://dsn-key@o1.ingest.sentry.io/42".to_string());
+        config.alerting.webhook_url = Some("https://hooks.example.com/webhook-token".to_string());
+        config.docs.enabled = Some(true);
+        config.docs.username = Some("docs".to_string());
+        config.docs.password = Some(SharedSecret::new("docs-password"));
+        config.alerting.rules = vec![AlertRule {
+            name: "5xx".to_string(),
+            status_class: 5,
+            threshold: 10,
+            window_secs: 60,
+        }];
+
+        let summary = config.summary();
+        let rendered = format!("{summary}{}", summary.to_json());
+        for secret in [
+            "db-password",
+            "jwt-secret-value",
+            "redis-password",
+            "dsn-key",
+            "webhook-token",
+            "docs-password",
+        ] {
+            assert!(!rendered.contains(secret), "摘要泄露了 {secret}");
+        }
+
+        let keys: Vec<_> = summary.entries().iter().map(|(key, _)| *key).collect();
+        for key in [
+            "server.addr",
+            "database.max_connections",
+            "cors.allow_origins",
+            "rate_limit.global",
+            "docs",
+            "redis",
+            "telemetry.sentry",
+            "alerting",
+        ] {
+            assert!(keys.contains(&key), "摘要缺少 {key}");
+        }
+        let alerting = &summary
+            .entries()
+            .iter()
+            .find(|(k, _)| *k == "alerting")
+            .unwrap()
+            .1;
+        assert_eq!(alerting, "1 rules -> https://hooks.example.com");
+    }
+
+    #[test]
+    fn test_display_table_groups_sections_and_aligns_columns() {
+        let mut config = AppConfig::default();
+        config.database.url = "postgres://app:db-password@db/app".to_string();
+        config.server.static_dir = "静态文件".to_string();
+
+        let table = config.to_display_table();
+        assert!(!table.contains("db-password"));
+        let lines: Vec<_> = table.lines().collect();
+        assert!(lines[0].starts_with("+-") && lines[0].ends_with("-+"));
+        assert!(lines[1].starts_with("| SECTION ") && lines[1].contains("| FIELD "));
+        // 每行的显示宽度一致（中文按单个字符计）
+        let widths: Vec<_> = lines.iter().map(|line| line.chars().count()).collect();
+        assert!(widths.iter().all(|w| *w == widths[0]), "{table}");
+
+        // 段名只在每段第一行出现，字段名去掉段前缀
+        let row = |field: &str| {
+            lines
+                .iter()
+                .find(|line| line.contains(&format!("| {field} ")))
+                .unwrap_or_else(|| panic!("缺少 {field}：\n{table}"))
+                .to_string()
+        };
+        assert!(row("addr").starts_with("| server "));
+        assert!(row("public_url").starts_with("|        "));
+        assert!(row("static_dir").contains("| 静态文件 "));
+        assert!(row("max_connections").starts_with("|        "));
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.starts_with("| database "))
+                .count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+
+/// 允许协商的最低 TLS 版本
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TlsVersion {
+    /// TLS 1.2 及以上
+    #[default]
+    #[serde(rename = "1.2")]
+    Tls12,
+
+    /// 仅 TLS 1.3
+    #[serde(rename = "1.3")]
+    Tls13,
+}
+
+impl TlsVersion {
+    /// 配置中的版本号
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tls12 => "1.2",
+            Self::Tls13 => "1.3",
+        }
+    }
+
+    /// 解析配置中的版本号（`"1.2"` 或 `"1.3"`）
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "1.2" => Ok(Self::Tls12),
+            "1.3" => Ok(Self::Tls13),
+            other => Err(format!(
+                "min_tls_version 只能是 \"1.2\" 或 \"1.3\"：{other}"
+            )),
+        }
+    }
+}
+
+/// HTTPS 配置
+///
+/// 启用后服务器直接以 HTTPS 监听 `server.host:server.port`，需使用 `--features tls` 构建。
+/// 证书和私钥均为 PEM 格式，证书文件可包含完整证书链。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// 是否启用 HTTPS（默认：false）
+    pub enabled: bool,
+
+    /// PEM 证书（链）文件路径
+    pub cert_path: String,
+
+    /// PEM 私钥文件路径（PKCS#8、PKCS#1 或 SEC1）
+    pub key_path: String,
+
+    /// 允许协商的最低 TLS 版本（默认："1.2"）
+    pub min_tls_version: TlsVersion,
+}
+
+impl ConfigSection for TlsConfig {
+    fn section_name(&self) -> &str {
+        "tls"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(enabled) = obj.get("enabled").and_then(|v| v.as_bool()) {
+                self.enabled = enabled;
+            }
+            if let Some(path) = obj.get("cert_path").and_then(|v| v.as_str()) {
+                self.cert_path = path.to_string();
+            }
+            if let Some(path) = obj.get("key_path").and_then(|v| v.as_str()) {
+                self.key_path = path.to_string();
+            }
+            if let Some(version) = obj.get("min_tls_version").and_then(|v| v.as_str()) {
+                self.min_tls_version = TlsVersion::parse(version)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if !cfg!(feature = "tls") {
+            return Err("启用 HTTPS 需要使用 --features tls 构建".to_string());
+        }
+        if self.cert_path.is_empty() || self.key_path.is_empty() {
+            return Err("启用 HTTPS 时必须配置 cert_path 和 key_path".to_string());
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(path) = env::var("TLS_CERT_PATH") {
+            self.cert_path = path;
+        }
+        if let Ok(path) = env::var("TLS_KEY_PATH") {
+            self.key_path = path;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_load_min_tls_version() {
+        let mut config = TlsConfig::default();
+        config
+            .load_from_value(&json!({ "enabled": true, "min_tls_version": "1.3" }))
+            .unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.min_tls_version, TlsVersion::Tls13);
+
+        let err = config
+            .load_from_value(&json!({ "min_tls_version": "1.1" }))
+            .unwrap_err();
+        assert!(err.contains("1.1"));
+    }
+
+    #[test]
+    fn test_deserialize_min_tls_version() {
+        let config: TlsConfig =
+            serde_json::from_value(json!({ "min_tls_version": "1.3" })).unwrap();
+        assert_eq!(config.min_tls_version, TlsVersion::Tls13);
+        assert!(serde_json::from_value::<TlsConfig>(json!({ "min_tls_version": "1.0" })).is_err());
+    }
+
+    #[test]
+    fn test_validate_requires_paths_when_enabled() {
+        assert!(TlsConfig::default().validate().is_ok());
+
+        let config = TlsConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = TlsConfig {
+            enabled: true,
+            cert_path: "certs/server.crt".to_string(),
+            key_path: "certs/server.key".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.validate().is_ok(), cfg!(feature = "tls"));
+    }
+}
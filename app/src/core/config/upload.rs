@@ -0,0 +1,441 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+
+/// 分片上传时单个分片的最小大小（字节），保证第一个分片包含识别文件类型所需的文件头
+pub const MIN_CHUNK_SIZE_BYTES: usize = 64 * 1024;
+
+/// 下载链接有效期的上限（秒），与 S3 预签名地址允许的最长有效期一致
+pub const MAX_DOWNLOAD_URL_TTL_SECS: u64 = 7 * 24 * 3600;
+
+/// 缩略图边长的上限（像素）
+pub const MAX_THUMBNAIL_SIZE: u32 = 1024;
+
+/// 可携带脚本的 MIME 类型，无论配置如何都不能在浏览器中直接显示
+const SCRIPTABLE_MIME_TYPES: [&str; 7] = [
+    "text/html",
+    "application/xhtml+xml",
+    "image/svg+xml",
+    "text/xml",
+    "application/xml",
+    "text/javascript",
+    "application/javascript",
+];
+
+/// 文件上传配置
+///
+/// 约束 `POST /v1/files` 接收的单个文件大小和 MIME 类型，以及本地存储的根目录。
+/// MIME 类型按文件头识别出的真实类型校验，不只看客户端声明的 Content-Type。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadConfig {
+    /// 单个文件大小上限，单位字节（默认：10 MiB）
+    ///
+    /// 上传接口的请求体上限在此基础上预留少量 multipart 开销，不受 `server.body_limit_bytes` 约束
+    pub max_file_size_bytes: usize,
+
+    /// 允许的 MIME 类型，支持 `image/*` 形式的通配（默认：常见图片、PDF 和纯文本）
+    pub allowed_mime_types: Vec<String>,
+
+    /// 拒绝的 MIME 类型，优先于允许列表，同样支持通配
+    ///
+    /// 默认拒绝 SVG、HTML 等可携带脚本的格式，即使允许了 `image/*` 或 `text/*`
+    pub denied_mime_types: Vec<String>,
+
+    /// 可以按 `?disposition=inline` 在浏览器中直接显示的 MIME 类型，同样支持通配（默认：常见图片和 PDF）
+    ///
+    /// 其余类型始终作为附件下载；HTML、SVG 等可携带脚本的类型不能加入，启动时校验
+    pub inline_mime_types: Vec<String>,
+
+    /// 本地存储根目录（默认：uploads）
+    pub storage_dir: String,
+
+    /// 分片上传时单个分片的大小上限，单位字节，不小于 64 KiB（默认：8 MiB）
+    pub max_chunk_size_bytes: usize,
+
+    /// 分片上传会话的有效期，单位秒，过期后未完成的分片会被清理（默认：86400）
+    pub session_ttl_secs: u64,
+
+    /// 过期上传会话的清理间隔，单位秒，0 表示不启动清理任务（默认：3600）
+    pub session_cleanup_interval_secs: u64,
+
+    /// 文件内容引用计数的校验间隔，单位秒，0 表示不启动校验任务（默认：86400）
+    pub blob_integrity_interval_secs: u64,
+
+    /// 存储后端中没有元数据引用的对象保留多久后才由对账任务删除，单位秒（默认：86400）
+    ///
+    /// 上传时先写对象、后登记元数据，宽限期内的对象可能属于仍在进行的上传，必须远长于单次上传的耗时
+    pub orphan_grace_secs: u64,
+
+    /// 未指定有效期时签名下载链接的有效期，单位秒（默认：300）
+    pub download_url_ttl_secs: u64,
+
+    /// 请求可指定的签名下载链接有效期上限，单位秒，不超过 7 天（默认：3600）
+    pub max_download_url_ttl_secs: u64,
+
+    /// 未单独设置配额的用户可使用的存储空间，单位字节，0 表示不限制（默认：1 GiB）
+    pub default_storage_quota_bytes: u64,
+
+    /// 图片上传后生成的缩略图边长（像素），按比例缩放到不超过该边长的正方形内，为空时不生成（默认：64、256）
+    ///
+    /// 只对 PNG、JPEG、GIF 和 WebP 生成，每个值在 1 到 1024 之间
+    pub thumbnail_sizes: Vec<u32>,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size_bytes: 10 * 1024 * 1024,
+            allowed_mime_types: [
+                "image/png",
+                "image/jpeg",
+                "image/gif",
+                "image/webp",
+                "application/pdf",
+                "text/plain",
+            ]
+            .map(String::from)
+            .to_vec(),
+            denied_mime_types: ["image/svg+xml", "text/html", "application/xhtml+xml"]
+                .map(String::from)
+                .to_vec(),
+            inline_mime_types: [
+                "image/png",
+                "image/jpeg",
+                "image/gif",
+                "image/webp",
+                "application/pdf",
+            ]
+            .map(String::from)
+            .to_vec(),
+            storage_dir: "uploads".to_string(),
+            max_chunk_size_bytes: 8 * 1024 * 1024,
+            session_ttl_secs: 24 * 3600,
+            session_cleanup_interval_secs: 3600,
+            blob_integrity_interval_secs: 24 * 3600,
+            orphan_grace_secs: 24 * 3600,
+            download_url_ttl_secs: 300,
+            max_download_url_ttl_secs: 3600,
+            default_storage_quota_bytes: 1024 * 1024 * 1024,
+            thumbnail_sizes: vec![64, 256],
+        }
+    }
+}
+
+impl UploadConfig {
+    /// 判断 MIME 类型是否允许上传（不区分大小写，忽略 `;` 之后的参数）
+    ///
+    /// 命中拒绝列表的类型即使也在允许列表中仍然不允许。
+    pub fn allows(&self, mime: &str) -> bool {
+        let mime = normalize(mime);
+        matches_any(&self.allowed_mime_types, &mime) && !matches_any(&self.denied_mime_types, &mime)
+    }
+
+    /// 判断 MIME 类型的文件是否可以在浏览器中直接显示，规则同 [`UploadConfig::allows`]
+    pub fn allows_inline(&self, mime: &str) -> bool {
+        let mime = normalize(mime);
+        matches_any(&self.inline_mime_types, &mime) && !matches_any(&self.denied_mime_types, &mime)
+    }
+}
+
+/// 规范化 MIME 类型：转为小写，去掉 `;` 之后的参数
+fn normalize(mime: &str) -> String {
+    mime.split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// 判断已规范化的 MIME 类型是否匹配列表中任一项（支持 `image/*` 形式的通配）
+fn matches_any(patterns: &[String], mime: &str) -> bool {
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.to_ascii_lowercase();
+        match pattern.strip_suffix("/*") {
+            Some(kind) => mime
+                .split_once('/')
+                .is_some_and(|(mime_kind, _)| mime_kind == kind),
+            None => mime == pattern,
+        }
+    })
+}
+
+/// 读取字符串数组配置项
+fn string_list(value: &Value, key: &str) -> Result<Vec<String>, String> {
+    value
+        .as_array()
+        .ok_or_else(|| format!("{key} 必须是字符串数组"))?
+        .iter()
+        .map(|v| {
+            v.as_str()
+                .map(String::from)
+                .ok_or_else(|| format!("{key} 只能包含字符串：{v}"))
+        })
+        .collect()
+}
+
+impl ConfigSection for UploadConfig {
+    fn section_name(&self) -> &str {
+        "upload"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(size) = obj.get("max_file_size_bytes").and_then(|v| v.as_u64()) {
+                self.max_file_size_bytes = size as usize;
+            }
+            if let Some(types) = obj.get("allowed_mime_types") {
+                self.allowed_mime_types = string_list(types, "allowed_mime_types")?;
+            }
+            if let Some(types) = obj.get("denied_mime_types") {
+                self.denied_mime_types = string_list(types, "denied_mime_types")?;
+            }
+            if let Some(types) = obj.get("inline_mime_types") {
+                self.inline_mime_types = string_list(types, "inline_mime_types")?;
+            }
+            if let Some(dir) = obj.get("storage_dir").and_then(|v| v.as_str()) {
+                self.storage_dir = dir.to_string();
+            }
+            if let Some(size) = obj.get("max_chunk_size_bytes").and_then(|v| v.as_u64()) {
+                self.max_chunk_size_bytes = size as usize;
+            }
+            if let Some(ttl) = obj.get("session_ttl_secs").and_then(|v| v.as_u64()) {
+                self.session_ttl_secs = ttl;
+            }
+            if let Some(interval) = obj
+                .get("session_cleanup_interval_secs")
+                .and_then(|v| v.as_u64())
+            {
+                self.session_cleanup_interval_secs = interval;
+            }
+            if let Some(interval) = obj
+                .get("blob_integrity_interval_secs")
+                .and_then(|v| v.as_u64())
+            {
+                self.blob_integrity_interval_secs = interval;
+            }
+            if let Some(grace) = obj.get("orphan_grace_secs").and_then(|v| v.as_u64()) {
+                self.orphan_grace_secs = grace;
+            }
+            if let Some(ttl) = obj.get("download_url_ttl_secs").and_then(|v| v.as_u64()) {
+                self.download_url_ttl_secs = ttl;
+            }
+            if let Some(ttl) = obj
+                .get("max_download_url_ttl_secs")
+                .and_then(|v| v.as_u64())
+            {
+                self.max_download_url_ttl_secs = ttl;
+            }
+            if let Some(quota) = obj
+                .get("default_storage_quota_bytes")
+                .and_then(|v| v.as_u64())
+            {
+                self.default_storage_quota_bytes = quota;
+            }
+            if let Some(sizes) = obj.get("thumbnail_sizes") {
+                self.thumbnail_sizes = sizes
+                    .as_array()
+                    .ok_or("thumbnail_sizes 必须是整数数组")?
+                    .iter()
+                    .map(|v| {
+                        v.as_u64()
+                            .and_then(|size| u32::try_from(size).ok())
+                            .ok_or_else(|| format!("thumbnail_sizes 只能包含正整数：{v}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.max_file_size_bytes == 0 {
+            return Err("max_file_size_bytes 必须大于 0".to_string());
+        }
+        if self.allowed_mime_types.is_empty() {
+            return Err("allowed_mime_types 不能为空".to_string());
+        }
+        if let Some(mime) = self
+            .allowed_mime_types
+            .iter()
+            .chain(&self.denied_mime_types)
+            .chain(&self.inline_mime_types)
+            .find(|m| {
+                m.split_once('/')
+                    .is_none_or(|(a, b)| a.is_empty() || b.is_empty())
+            })
+        {
+            return Err(format!("MIME 类型格式无效：{mime}"));
+        }
+        if let Some(mime) = SCRIPTABLE_MIME_TYPES
+            .iter()
+            .find(|mime| matches_any(&self.inline_mime_types, mime))
+        {
+            return Err(format!(
+                "inline_mime_types 不能包含可携带脚本的类型：{mime}"
+            ));
+        }
+        if self.storage_dir.is_empty() {
+            return Err("上传存储目录不能为空".to_string());
+        }
+        if self.max_chunk_size_bytes < MIN_CHUNK_SIZE_BYTES {
+            return Err(format!(
+                "max_chunk_size_bytes 不能小于 {MIN_CHUNK_SIZE_BYTES}"
+            ));
+        }
+        if self.default_storage_quota_bytes > i64::MAX as u64 {
+            return Err("default_storage_quota_bytes 超出范围".to_string());
+        }
+        if self.session_ttl_secs == 0 {
+            return Err("session_ttl_secs 必须大于 0".to_string());
+        }
+        if self.orphan_grace_secs < 3600 {
+            return Err("orphan_grace_secs 不能小于 3600，以免删除仍在上传中的内容".to_string());
+        }
+        if !(1..=MAX_DOWNLOAD_URL_TTL_SECS).contains(&self.max_download_url_ttl_secs) {
+            return Err(format!(
+                "max_download_url_ttl_secs 必须在 1 到 {MAX_DOWNLOAD_URL_TTL_SECS} 之间"
+            ));
+        }
+        if !(1..=self.max_download_url_ttl_secs).contains(&self.download_url_ttl_secs) {
+            return Err(
+                "download_url_ttl_secs 必须大于 0 且不超过 max_download_url_ttl_secs".to_string(),
+            );
+        }
+        if let Some(size) = self
+            .thumbnail_sizes
+            .iter()
+            .find(|size| !(1..=MAX_THUMBNAIL_SIZE).contains(*size))
+        {
+            return Err(format!(
+                "thumbnail_sizes 中的边长必须在 1 到 {MAX_THUMBNAIL_SIZE} 之间：{size}"
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(dir) = env::var("UPLOAD_DIR") {
+            self.storage_dir = dir;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_exact_and_wildcard_mime_types() {
+        let config = UploadConfig {
+            allowed_mime_types: vec!["image/*".to_string(), "application/pdf".to_string()],
+            ..Default::default()
+        };
+        assert!(config.allows("image/png"));
+        assert!(config.allows("Application/PDF"));
+        assert!(config.allows("application/pdf; charset=binary"));
+        assert!(!config.allows("application/zip"));
+        assert!(!config.allows("imagex/png"));
+    }
+
+    #[test]
+    fn test_denied_mime_types_take_precedence() {
+        let config = UploadConfig {
+            allowed_mime_types: vec!["image/*".to_string(), "text/*".to_string()],
+            ..Default::default()
+        };
+        assert!(config.allows("image/png"));
+        assert!(config.allows("text/csv"));
+        assert!(!config.allows("image/svg+xml"));
+        assert!(!config.allows("Text/HTML; charset=utf-8"));
+
+        let config = UploadConfig {
+            denied_mime_types: Vec::new(),
+            ..config
+        };
+        assert!(config.allows("image/svg+xml"));
+    }
+
+    #[test]
+    fn test_inline_only_for_listed_safe_types() {
+        let config = UploadConfig::default();
+        assert!(config.allows_inline("image/png"));
+        assert!(config.allows_inline("Application/PDF; charset=binary"));
+        assert!(!config.allows_inline("text/plain"));
+        assert!(!config.allows_inline("text/html"));
+
+        // 拒绝列表同样优先
+        let config = UploadConfig {
+            inline_mime_types: vec!["image/*".to_string()],
+            ..Default::default()
+        };
+        assert!(config.allows_inline("image/webp"));
+        assert!(!config.allows_inline("image/svg+xml"));
+    }
+
+    #[test]
+    fn test_validate_rejects_scriptable_inline_types() {
+        for types in [
+            vec!["text/html"],
+            vec!["image/png", "image/*"],
+            vec!["text/*"],
+        ] {
+            let mut config = UploadConfig::default();
+            config
+                .load_from_value(&serde_json::json!({ "inline_mime_types": types }))
+                .unwrap();
+            assert!(config.validate().is_err(), "{types:?}");
+        }
+    }
+
+    #[test]
+    fn test_validate_download_url_ttl() {
+        assert!(UploadConfig::default().validate().is_ok());
+
+        for (ttl, max) in [(0, 3600), (7200, 3600), (300, 0), (300, 8 * 24 * 3600)] {
+            let config = UploadConfig {
+                download_url_ttl_secs: ttl,
+                max_download_url_ttl_secs: max,
+                ..Default::default()
+            };
+            assert!(config.validate().is_err(), "{ttl} / {max}");
+        }
+    }
+
+    #[test]
+    fn test_validate_orphan_grace() {
+        let mut config = UploadConfig::default();
+        config
+            .load_from_value(&serde_json::json!({ "orphan_grace_secs": 7200 }))
+            .unwrap();
+        assert_eq!(config.orphan_grace_secs, 7200);
+        assert!(config.validate().is_ok());
+
+        config.orphan_grace_secs = 60;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_thumbnail_sizes() {
+        let mut config = UploadConfig::default();
+        config
+            .load_from_value(&serde_json::json!({ "thumbnail_sizes": [128, 512] }))
+            .unwrap();
+        assert_eq!(config.thumbnail_sizes, [128, 512]);
+        assert!(config.validate().is_ok());
+
+        for sizes in [vec![0], vec![64, MAX_THUMBNAIL_SIZE + 1]] {
+            config.thumbnail_sizes = sizes;
+            assert!(config.validate().is_err(), "{:?}", config.thumbnail_sizes);
+        }
+        assert!(
+            config
+                .load_from_value(&serde_json::json!({ "thumbnail_sizes": [-1] }))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::section::ConfigSection;
+
+/// 根路径 `/` 的欢迎响应配置
+///
+/// API 客户端得到 JSON（`message` 和 `links`）；开启 `html` 后，`Accept` 偏好 `text/html` 的请求
+/// （浏览器直接打开）得到按 `templates/welcome.html` 渲染的页面，便于部署时展示品牌信息。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WelcomeConfig {
+    /// 欢迎消息（默认：Hello, World!）
+    pub message: String,
+
+    /// HTML 页面标题（默认：DropBuddy）
+    pub title: String,
+
+    /// 浏览器访问时是否返回 HTML 页面（默认：false，始终返回 JSON）
+    pub html: bool,
+
+    /// 附加的链接：名称 → 地址，如 `{ "文档" = "/docs" }`，JSON 和 HTML 中都会列出（默认：为空）
+    pub links: BTreeMap<String, String>,
+}
+
+impl Default for WelcomeConfig {
+    fn default() -> Self {
+        Self {
+            message: "Hello, World!".to_string(),
+            title: "DropBuddy".to_string(),
+            html: false,
+            links: BTreeMap::new(),
+        }
+    }
+}
+
+impl ConfigSection for WelcomeConfig {
+    fn section_name(&self) -> &str {
+        "welcome"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(message) = obj.get("message").and_then(|v| v.as_str()) {
+                self.message = message.to_string();
+            }
+            if let Some(title) = obj.get("title").and_then(|v| v.as_str()) {
+                self.title = title.to_string();
+            }
+            if let Some(html) = obj.get("html").and_then(|v| v.as_bool()) {
+                self.html = html;
+            }
+            if let Some(links) = obj.get("links").and_then(|v| v.as_object()) {
+                self.links = links
+                    .iter()
+                    .map(|(name, url)| {
+                        url.as_str()
+                            .map(|url| (name.clone(), url.to_string()))
+                            .ok_or_else(|| format!("links 的地址必须是字符串：{name}"))
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.message.trim().is_empty() {
+            return Err("message 不能为空".to_string());
+        }
+        if self.html && self.title.trim().is_empty() {
+            return Err("启用 html 时 title 不能为空".to_string());
+        }
+        Ok(())
+    }
+
+    fn apply_env_overrides(&mut self) -> Result<(), String> {
+        if let Ok(message) = env::var("WELCOME_MESSAGE") {
+            self.message = message;
+        }
+        Ok(())
+    }
+}
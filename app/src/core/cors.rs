@@ -1,5 +1,5 @@
 use axum::http::HeaderValue;
-use axum::http::header::HeaderName;
+use axum::http::header::{self, HeaderName};
 use axum::http::method::Method;
 use std::time::Duration;
 use tower_http::cors::{Any, CorsLayer};
@@ -15,6 +15,7 @@ use crate::error::ConfigError;
 /// - 允许的请求头
 /// - 暴露的响应头
 /// - 凭证和缓存时间设置
+/// - `Vary` 响应头（按源动态返回时包含 `Origin`，防止缓存污染）
 ///
 /// # 参数
 ///
@@ -27,7 +28,7 @@ use crate::error::ConfigError;
 /// # 示例
 ///
 /// ```ignore
-/// let config = AppConfig::load()?;
+/// let config = AppConfig::load(&ConfigOverrides::default())?;
 /// let cors_layer = build_cors_layer(&config.cors)?;
 /// ```
 pub fn build_cors_layer(cors_config: &CorsConfig) -> Result<CorsLayer, ConfigError> {
@@ -56,7 +57,8 @@ pub fn build_cors_layer(cors_config: &CorsConfig) -> Result<CorsLayer, ConfigErr
     }
 
     // 处理允许的源
-    if cors_config.allow_origins.contains(&"*".to_string()) {
+    let wildcard_origin = cors_config.allow_origins.contains(&"*".to_string());
+    if wildcard_origin {
         cors = cors.allow_origin(Any);
     } else {
         let origins: Vec<HeaderValue> = cors_config
@@ -94,7 +96,115 @@ pub fn build_cors_layer(cors_config: &CorsConfig) -> Result<CorsLayer, ConfigErr
         cors = cors.allow_credentials(true);
     }
 
-    cors = cors.max_age(Duration::from_secs(cors_config.max_age));
+    // 未配置时不发送 Access-Control-Max-Age；显式的 0 原样发送，禁用预检缓存
+    if let Some(max_age) = cors_config.max_age {
+        cors = cors.max_age(Duration::from_secs(max_age));
+    }
+
+    cors = cors.vary(vary_headers(cors_config, wildcard_origin));
 
     Ok(cors)
 }
+
+/// 计算 `Vary` 响应头
+///
+/// 指定具体源时，`Access-Control-Allow-Origin` 会随请求的 `Origin` 变化，
+/// 必须声明 `Vary: Origin`，否则共享缓存可能把某个源的响应返回给其他源。
+/// 通配源的响应与请求源无关，只需保留预检请求相关的头。
+/// 配置中的 `vary_headers` 会追加在后面。
+fn vary_headers(cors_config: &CorsConfig, wildcard_origin: bool) -> Vec<HeaderName> {
+    let mut vary = Vec::new();
+    if !wildcard_origin {
+        vary.push(header::ORIGIN);
+    }
+    vary.push(header::ACCESS_CONTROL_REQUEST_METHOD);
+    vary.push(header::ACCESS_CONTROL_REQUEST_HEADERS);
+
+    for name in cors_config
+        .vary_headers
+        .iter()
+        .filter_map(|h| h.parse::<HeaderName>().ok())
+    {
+        if !vary.contains(&name) {
+            vary.push(name);
+        }
+    }
+    vary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, extract::Request, routing::get};
+    use tower::ServiceExt;
+
+    async fn vary_for(cors_config: CorsConfig) -> String {
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(build_cors_layer(&cors_config).unwrap());
+        let response = app
+            .oneshot(
+                Request::get("/")
+                    .header(header::ORIGIN, "https://example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        response.headers()[header::VARY]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_vary_origin_for_specific_origins() {
+        let vary = vary_for(CorsConfig {
+            allow_origins: vec!["https://example.com".to_string()],
+            vary_headers: vec!["Accept-Encoding".to_string()],
+            ..Default::default()
+        })
+        .await;
+
+        assert!(vary.starts_with("origin"));
+        assert!(vary.ends_with("accept-encoding"));
+    }
+
+    async fn preflight_max_age(max_age: Option<u64>) -> Option<String> {
+        let cors_config = CorsConfig {
+            max_age,
+            ..Default::default()
+        };
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(build_cors_layer(&cors_config).unwrap());
+        let response = app
+            .oneshot(
+                Request::options("/")
+                    .header(header::ORIGIN, "https://example.com")
+                    .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_MAX_AGE)
+            .map(|v| v.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_preflight_max_age_unset_vs_zero() {
+        assert_eq!(preflight_max_age(None).await, None);
+        assert_eq!(preflight_max_age(Some(0)).await.as_deref(), Some("0"));
+        assert_eq!(preflight_max_age(Some(600)).await.as_deref(), Some("600"));
+    }
+
+    #[tokio::test]
+    async fn test_no_vary_origin_for_wildcard_origin() {
+        let vary = vary_for(CorsConfig::default()).await;
+        assert!(!vary.contains("origin,"));
+        assert!(vary.contains("access-control-request-method"));
+    }
+}
@@ -0,0 +1,71 @@
+//! Sentry 错误上报（需启用 `sentry` cargo feature）
+//!
+//! - 启动时根据 [`SentryConfig`] 初始化客户端，默认集成会挂载 panic 钩子
+//! - [`sentry_context_middleware`] 为每个请求创建独立的 Hub，并写入 `request_id`、`route` 标签
+//! - 认证中间件通过 [`set_user_id`] 追加 `user_id` 标签
+//! - `AppError` 转换为 5xx 响应时通过 [`capture_server_error`] 上报
+//!
+//! 出于隐私考虑，不上报请求体，也不启用 `send_default_pii`。
+
+use std::sync::Arc;
+
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use entity::user::UserId;
+use sentry::{ClientInitGuard, ClientOptions, Hub, SentryFutureExt};
+
+use crate::core::config::SentryConfig;
+
+/// 初始化 Sentry 客户端，未配置 DSN 时返回 `None`
+///
+/// 返回的守卫需在进程生命周期内保持存活，被丢弃时会刷新未发送的事件。
+pub fn init(config: &SentryConfig) -> Option<ClientInitGuard> {
+    let dsn = config.dsn.as_deref()?;
+
+    let mut options = ClientOptions::new()
+        .dsn(dsn)
+        .maybe_release(sentry::release_name!())
+        .sample_rate(config.sample_rate)
+        .traces_sample_rate(config.traces_sample_rate)
+        .send_default_pii(false);
+    if let Some(environment) = &config.environment {
+        options = options.environment(environment.clone());
+    }
+
+    let guard = sentry::init(options);
+
+    guard.is_enabled().then_some(guard)
+}
+
+/// 为每个请求绑定独立的 Hub 并写入请求标签
+///
+/// 需位于请求 ID 中间件内层，以便读取 `x-request-id`。
+pub async fn sentry_context_middleware(request: Request, next: Next) -> Response {
+    let hub = Arc::new(Hub::new_from_top(Hub::current()));
+    hub.configure_scope(|scope| {
+        if let Some(request_id) = request
+            .headers()
+            .get("x-request-id")
+            .and_then(|v| v.to_str().ok())
+        {
+            scope.set_tag("request_id", request_id);
+        }
+        scope.set_tag(
+            "route",
+            format!("{} {}", request.method(), request.uri().path()),
+        );
+    });
+
+    next.run(request).bind_hub(hub).await
+}
+
+/// 记录当前请求的用户 ID
+pub fn set_user_id(user_id: UserId) {
+    sentry::configure_scope(|scope| scope.set_tag("user_id", user_id));
+}
+
+/// 上报服务端错误
+pub fn capture_server_error(error: &(dyn std::error::Error + 'static)) {
+    sentry::capture_error(error);
+}
@@ -0,0 +1,99 @@
+//! 功能开关
+//!
+//! 控制可选功能模块是否注册路由，未启用的模块不会出现在路由表和 API 文档中。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::config::ConfigSection;
+
+/// 可选功能模块开关
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeatureFlags {
+    /// 是否启用 WebSocket 端点（默认：false）
+    pub websocket_enabled: bool,
+
+    /// 是否启用 SSE（Server-Sent Events）端点（默认：false）
+    pub sse_enabled: bool,
+
+    /// 是否启用文件相关端点（默认：true）
+    pub file_upload_enabled: bool,
+
+    /// 是否启用管理员运维端点（默认：true）
+    pub admin_panel_enabled: bool,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        Self {
+            websocket_enabled: false,
+            sse_enabled: false,
+            file_upload_enabled: true,
+            admin_panel_enabled: true,
+        }
+    }
+}
+
+impl FeatureFlags {
+    /// 已启用的功能名称列表，用于启动日志
+    pub fn enabled(&self) -> Vec<&'static str> {
+        [
+            ("websocket", self.websocket_enabled),
+            ("sse", self.sse_enabled),
+            ("file_upload", self.file_upload_enabled),
+            ("admin_panel", self.admin_panel_enabled),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect()
+    }
+}
+
+impl ConfigSection for FeatureFlags {
+    fn section_name(&self) -> &str {
+        "features"
+    }
+
+    fn load_from_value(&mut self, value: &Value) -> Result<(), String> {
+        if let Some(obj) = value.as_object() {
+            if let Some(enabled) = obj.get("websocket_enabled").and_then(|v| v.as_bool()) {
+                self.websocket_enabled = enabled;
+            }
+            if let Some(enabled) = obj.get("sse_enabled").and_then(|v| v.as_bool()) {
+                self.sse_enabled = enabled;
+            }
+            if let Some(enabled) = obj.get("file_upload_enabled").and_then(|v| v.as_bool()) {
+                self.file_upload_enabled = enabled;
+            }
+            if let Some(enabled) = obj.get("admin_panel_enabled").and_then(|v| v.as_bool()) {
+                self.admin_panel_enabled = enabled;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        // 开关之间相互独立，无需额外验证
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_and_list_enabled() {
+        let mut flags = FeatureFlags::default();
+        assert_eq!(flags.enabled(), vec!["file_upload", "admin_panel"]);
+
+        flags
+            .load_from_value(&serde_json::json!({
+                "sse_enabled": true,
+                "admin_panel_enabled": false,
+            }))
+            .unwrap();
+        assert_eq!(flags.enabled(), vec!["sse", "file_upload"]);
+    }
+}
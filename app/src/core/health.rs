@@ -0,0 +1,351 @@
+//! 就绪检查
+//!
+//! `/health/ready` 需要探测数据库和 Redis，负载均衡器高频轮询时会给依赖带来压力。
+//! [`ReadinessCache`] 在 TTL 内复用最近一次的检查结果；检查期间持有锁，
+//! 并发到达的探测会等待并复用同一次结果，而不是各自探测一遍。
+//!
+//! 就绪检查的结果会暴露依赖的可用性。按 `health` 配置启用 [`HealthGuard`] 后，
+//! 只有携带有效 API 密钥或来源 IP 在白名单内的调用方能看到详情。
+
+use std::future::Future;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::core::config::HealthConfig;
+use crate::core::middleware::ApiKeys;
+use crate::core::response::ApiResponse;
+
+/// 依赖就绪状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Readiness {
+    /// 所有依赖均可用
+    pub ready: bool,
+
+    /// 数据库是否可用
+    pub database: bool,
+
+    /// Redis 是否可用，未配置 Redis 时为空
+    pub redis: Option<bool>,
+
+    /// 可选依赖不可用，服务仍可处理请求但部分功能受影响（如上传的文件暂时无法扫描）
+    pub degraded: bool,
+
+    /// 恶意软件扫描器是否可用，未启用扫描时为空；不可用时只标记降级，不影响 `ready`
+    pub scanner: Option<bool>,
+}
+
+impl Readiness {
+    /// 根据各依赖的探测结果构建就绪状态
+    pub fn new(database: bool, redis: Option<bool>) -> Self {
+        Self {
+            ready: database && redis.unwrap_or(true),
+            database,
+            redis,
+            degraded: false,
+            scanner: None,
+        }
+    }
+
+    /// 附加恶意软件扫描器的探测结果
+    pub fn with_scanner(mut self, scanner: Option<bool>) -> Self {
+        self.scanner = scanner;
+        self.degraded = scanner == Some(false);
+        self
+    }
+}
+
+/// 就绪检查结果缓存
+///
+/// 克隆后的实例共享同一份缓存；TTL 为 0 时不缓存，每次都重新探测。
+#[derive(Debug, Clone)]
+pub struct ReadinessCache {
+    ttl: Duration,
+    last: Arc<Mutex<Option<(Instant, Readiness)>>>,
+}
+
+impl ReadinessCache {
+    /// 创建缓存，`ttl` 为检查结果的有效期
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            last: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 返回有效期内的缓存结果，过期时调用 `probe` 重新探测并缓存
+    pub async fn get_or_check<F, Fut>(&self, probe: F) -> Readiness
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Readiness>,
+    {
+        let mut last = self.last.lock().await;
+        if let Some((checked_at, readiness)) = last.as_ref()
+            && checked_at.elapsed() < self.ttl
+        {
+            return readiness.clone();
+        }
+
+        let readiness = probe().await;
+        *last = Some((Instant::now(), readiness.clone()));
+        readiness
+    }
+}
+
+/// IP 地址或 CIDR 网段（如 `10.0.0.0/8`、`::1`），单个地址视为前缀长度取满的网段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    /// 地址是否属于该网段，IPv4 与 IPv6 地址互不匹配
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| "不是有效的 IP 地址".to_string())?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= max)
+                .ok_or_else(|| format!("前缀长度必须在 0-{max} 之间"))?,
+            None => max,
+        };
+        Ok(Self { addr, prefix })
+    }
+}
+
+/// 就绪检查详情的访问控制（由 `health` 配置创建）
+///
+/// 未启用时所有调用方都能看到详情。
+#[derive(Debug, Clone, Default)]
+pub struct HealthGuard {
+    require_api_key: bool,
+    allowed_networks: Arc<[IpNetwork]>,
+    api_keys: ApiKeys,
+}
+
+impl HealthGuard {
+    /// 创建访问控制，`config` 已通过校验，无效的网段会被忽略
+    pub fn new(config: &HealthConfig, api_keys: ApiKeys) -> Self {
+        Self {
+            require_api_key: config.require_api_key,
+            allowed_networks: config
+                .allowed_ips
+                .iter()
+                .filter_map(|ip| ip.parse().ok())
+                .collect(),
+            api_keys,
+        }
+    }
+
+    /// 调用方是否可以查看详情：携带有效的 API 密钥，或来源 IP 在白名单内
+    pub fn allows(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> bool {
+        if !self.require_api_key && self.allowed_networks.is_empty() {
+            return true;
+        }
+        if self.require_api_key && self.api_keys.authenticate(headers).is_some() {
+            return true;
+        }
+        peer.is_some_and(|ip| self.allowed_networks.iter().any(|net| net.contains(ip)))
+    }
+
+    /// 构建就绪检查响应：依赖全部可用时为 200，否则为 503；无权查看详情时不带响应体
+    pub fn readiness_response(
+        &self,
+        readiness: Readiness,
+        headers: &HeaderMap,
+        peer: Option<IpAddr>,
+    ) -> Response {
+        let status = if readiness.ready {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+        if !self.allows(headers, peer) {
+            return status.into_response();
+        }
+        (status, ApiResponse::success(readiness)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{ApiKeyEntry, ApiKeysConfig};
+    use crate::core::middleware::api_key::API_KEY_HEADER;
+    use crate::shared::SharedSecret;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_rapid_checks_probe_once() {
+        let cache = ReadinessCache::new(Duration::from_secs(1));
+        let probes = AtomicUsize::new(0);
+        let probe = || async {
+            probes.fetch_add(1, Ordering::SeqCst);
+            Readiness::new(true, None)
+        };
+
+        let first = cache.get_or_check(probe).await;
+        let second = cache.get_or_check(probe).await;
+
+        assert_eq!(probes.load(Ordering::SeqCst), 1);
+        assert_eq!(first, second);
+        assert!(second.ready);
+    }
+
+    #[test]
+    fn test_unavailable_scanner_degrades_without_failing_readiness() {
+        let readiness = Readiness::new(true, None).with_scanner(Some(false));
+        assert!(readiness.ready);
+        assert!(readiness.degraded);
+
+        assert!(!Readiness::new(true, None).with_scanner(Some(true)).degraded);
+        assert!(!Readiness::new(true, None).with_scanner(None).degraded);
+    }
+
+    #[tokio::test]
+    async fn test_zero_ttl_always_probes() {
+        let cache = ReadinessCache::new(Duration::ZERO);
+        let probes = AtomicUsize::new(0);
+        let probe = || async {
+            probes.fetch_add(1, Ordering::SeqCst);
+            Readiness::new(true, Some(false))
+        };
+
+        let readiness = cache.get_or_check(probe).await;
+        cache.get_or_check(probe).await;
+
+        assert_eq!(probes.load(Ordering::SeqCst), 2);
+        assert!(!readiness.ready);
+    }
+
+    #[test]
+    fn test_ip_network_contains() {
+        let net: IpNetwork = "10.1.0.0/16".parse().unwrap();
+        assert!(net.contains("10.1.255.7".parse().unwrap()));
+        assert!(!net.contains("10.2.0.1".parse().unwrap()));
+        // IPv4 映射的 IPv6 地址按 IPv4 匹配
+        assert!(net.contains("::ffff:10.1.0.1".parse().unwrap()));
+        assert!(!net.contains("::1".parse().unwrap()));
+
+        let host: IpNetwork = "::1".parse().unwrap();
+        assert!(host.contains("::1".parse().unwrap()));
+        assert!(!host.contains("::2".parse().unwrap()));
+
+        let any: IpNetwork = "0.0.0.0/0".parse().unwrap();
+        assert!(any.contains("192.168.1.1".parse().unwrap()));
+
+        for invalid in ["10.0.0.0/33", "::/129", "10.0.0.1/", "localhost", ""] {
+            assert!(invalid.parse::<IpNetwork>().is_err(), "{invalid}");
+        }
+    }
+
+    const PROBE_KEY: &str = "probe-key-0123456789abcdef0123456789abcdef";
+
+    fn guard(require_api_key: bool, allowed_ips: &[&str]) -> HealthGuard {
+        let api_keys = ApiKeys::new(&ApiKeysConfig {
+            keys: vec![ApiKeyEntry {
+                label: "probe".to_string(),
+                key: SharedSecret::new(PROBE_KEY),
+                scopes: Vec::new(),
+            }],
+        });
+        let config = HealthConfig {
+            require_api_key,
+            allowed_ips: allowed_ips.iter().map(|ip| ip.to_string()).collect(),
+        };
+        HealthGuard::new(&config, api_keys)
+    }
+
+    /// 返回状态码和响应体
+    async fn ready(
+        guard: &HealthGuard,
+        readiness: Readiness,
+        key: Option<&str>,
+        peer: &str,
+    ) -> (StatusCode, Vec<u8>) {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = key {
+            headers.insert(API_KEY_HEADER, key.parse().unwrap());
+        }
+        let response = guard.readiness_response(readiness, &headers, Some(peer.parse().unwrap()));
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, body.to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_authorized_callers_get_details() {
+        let down = Readiness::new(true, Some(false));
+        for (guard, key, peer) in [
+            (HealthGuard::default(), None, "203.0.113.9"),
+            (guard(true, &[]), Some(PROBE_KEY), "203.0.113.9"),
+            (guard(false, &["10.0.0.0/8"]), None, "10.3.2.1"),
+            (guard(true, &["10.0.0.0/8"]), None, "10.3.2.1"),
+        ] {
+            let (status, body) = ready(&guard, down.clone(), key, peer).await;
+            assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(body["data"]["database"], true);
+            assert_eq!(body["data"]["redis"], false);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_callers_get_bare_status() {
+        for (guard, key, peer) in [
+            (guard(true, &[]), None, "10.3.2.1"),
+            (guard(true, &[]), Some("wrong-key"), "10.3.2.1"),
+            (
+                guard(false, &["10.0.0.0/8"]),
+                Some(PROBE_KEY),
+                "203.0.113.9",
+            ),
+        ] {
+            let (status, body) = ready(&guard, Readiness::new(true, None), key, peer).await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(body.is_empty());
+
+            let (status, body) = ready(&guard, Readiness::new(false, None), key, peer).await;
+            assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+            assert!(body.is_empty());
+        }
+    }
+}
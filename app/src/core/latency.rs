@@ -0,0 +1,179 @@
+//! 按路由统计请求耗时分位数
+//!
+//! 每条路由（`方法 路由模板`，如 `GET /v1/user/me`）保留最近 [`WINDOW_SIZE`] 个请求的耗时，
+//! 查询时计算 p50 / p90 / p99。数据只在内存中，重启后清空，适合不想部署 Prometheus 的小型部署。
+//!
+//! [`latency_middleware`] 挂在 v1 的各个子路由上（见 [`crate::v1::routes`]），
+//! 此时才能读到包含挂载前缀的完整路由模板。未匹配到路由的请求（404）不计入，
+//! 避免扫描器制造大量不同的路径。
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// 每条路由保留的最近样本数
+pub const WINDOW_SIZE: usize = 1024;
+
+/// 单条路由的耗时分位数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RouteLatency {
+    /// 路由（`方法 路由模板`）
+    pub route: String,
+
+    /// 启动以来的请求总数
+    pub count: u64,
+
+    /// 参与分位数计算的样本数（最多 [`WINDOW_SIZE`] 个）
+    pub samples: usize,
+
+    /// 中位数耗时（毫秒）
+    pub p50_ms: f64,
+
+    /// 90 分位耗时（毫秒）
+    pub p90_ms: f64,
+
+    /// 99 分位耗时（毫秒）
+    pub p99_ms: f64,
+}
+
+#[derive(Debug, Default)]
+struct RouteWindow {
+    count: u64,
+    samples: VecDeque<Duration>,
+}
+
+/// 请求耗时统计（克隆后共享同一份数据）
+#[derive(Debug, Clone, Default)]
+pub struct LatencyStats {
+    routes: Arc<Mutex<HashMap<String, RouteWindow>>>,
+}
+
+impl LatencyStats {
+    /// 记录一次请求耗时，超出窗口时丢弃最旧的样本
+    pub fn record(&self, route: &str, latency: Duration) {
+        let mut routes = self.routes.lock().unwrap();
+        let window = routes.entry(route.to_string()).or_default();
+        window.count += 1;
+        if window.samples.len() == WINDOW_SIZE {
+            window.samples.pop_front();
+        }
+        window.samples.push_back(latency);
+    }
+
+    /// 各路由的耗时分位数，按路由排序
+    pub fn snapshot(&self) -> Vec<RouteLatency> {
+        let routes = self.routes.lock().unwrap();
+        let mut result: Vec<_> = routes
+            .iter()
+            .map(|(route, window)| {
+                let mut sorted: Vec<_> = window.samples.iter().copied().collect();
+                sorted.sort_unstable();
+                RouteLatency {
+                    route: route.clone(),
+                    count: window.count,
+                    samples: sorted.len(),
+                    p50_ms: percentile_ms(&sorted, 50.0),
+                    p90_ms: percentile_ms(&sorted, 90.0),
+                    p99_ms: percentile_ms(&sorted, 99.0),
+                }
+            })
+            .collect();
+        result.sort_by(|a, b| a.route.cmp(&b.route));
+        result
+    }
+}
+
+/// 最近秩法计算分位数（毫秒），`sorted` 须已升序排列
+fn percentile_ms(sorted: &[Duration], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1].as_secs_f64() * 1000.0
+}
+
+/// 记录请求耗时的中间件
+pub async fn latency_middleware(
+    State(stats): State<LatencyStats>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| format!("{} {}", request.method(), path.as_str()));
+    let start = Instant::now();
+    let response = next.run(request).await;
+    if let Some(route) = route {
+        stats.record(&route, start.elapsed());
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::{Router, middleware, routing::get};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_percentiles_from_synthetic_latencies() {
+        let stats = LatencyStats::default();
+        for ms in 1..=100 {
+            stats.record("GET /items", Duration::from_millis(ms));
+        }
+
+        let [items] = stats.snapshot().try_into().unwrap();
+        assert_eq!(items.count, 100);
+        assert_eq!(items.p50_ms, 50.0);
+        assert_eq!(items.p90_ms, 90.0);
+        assert_eq!(items.p99_ms, 99.0);
+    }
+
+    #[test]
+    fn test_window_keeps_most_recent_samples() {
+        let stats = LatencyStats::default();
+        for _ in 0..WINDOW_SIZE {
+            stats.record("GET /slow", Duration::from_secs(1));
+        }
+        for _ in 0..WINDOW_SIZE {
+            stats.record("GET /slow", Duration::from_millis(10));
+        }
+
+        let [slow] = stats.snapshot().try_into().unwrap();
+        assert_eq!(slow.count, 2 * WINDOW_SIZE as u64);
+        assert_eq!(slow.samples, WINDOW_SIZE);
+        assert_eq!(slow.p99_ms, 10.0);
+    }
+
+    #[tokio::test]
+    async fn test_middleware_groups_by_route_template() {
+        let stats = LatencyStats::default();
+        // 与 v1 路由相同的结构：中间件挂在嵌套的模块路由上，才能拿到完整的路由模板
+        let items = Router::new().route("/{id}", get(|| async { "ok" })).layer(
+            middleware::from_fn_with_state(stats.clone(), latency_middleware),
+        );
+        let app = Router::new().nest_service("/v1", Router::new().nest_service("/items", items));
+
+        for uri in ["/v1/items/1", "/v1/items/2", "/v1/items/1/missing"] {
+            app.clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+        }
+
+        let routes: Vec<_> = stats
+            .snapshot()
+            .into_iter()
+            .map(|r| (r.route, r.count))
+            .collect();
+        assert_eq!(routes, [("GET /v1/items/{id}".to_string(), 2)]);
+    }
+}
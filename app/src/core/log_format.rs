@@ -0,0 +1,266 @@
+//! 扁平化 JSON 日志格式
+//!
+//! 内置的 JSON 格式只把事件字段放在顶层，所在 span 的字段被嵌套在 `span`/`spans` 下，
+//! 按请求检索日志时需要额外展开。这里的 [`FlattenedJson`] 将事件所在的所有 span 字段
+//! （如 `request_id`、`method`、`uri`、`user_id`）与事件字段合并为一层 JSON 对象输出。
+//!
+//! 日志时间戳按 [`LogTimezone`]（`logging.timezone`）输出，默认 UTC。
+
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::FormatTime;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// 日志时间戳的时区
+///
+/// 只影响日志输出，API 响应中的时间仍为 UTC。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogTimezone {
+    /// UTC（`Z` 后缀）
+    #[default]
+    Utc,
+
+    /// 服务器本地时区
+    Local,
+
+    /// 固定偏移，如 `+08:00`
+    Fixed(FixedOffset),
+}
+
+impl LogTimezone {
+    /// 解析 `utc`、`local` 或 `+08:00` / `-05:30` 形式的固定偏移（不区分大小写）
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "utc" => Ok(Self::Utc),
+            "local" => Ok(Self::Local),
+            offset => offset.parse::<FixedOffset>().map(Self::Fixed).map_err(|_| {
+                format!("无效的日志时区：{value}（可选 utc、local 或 +08:00 形式的偏移）")
+            }),
+        }
+    }
+
+    /// 按时区格式化为 RFC 3339（微秒精度）
+    pub fn format(&self, at: DateTime<Utc>) -> String {
+        match self {
+            Self::Utc => at.to_rfc3339_opts(SecondsFormat::Micros, true),
+            Self::Local => at
+                .with_timezone(&Local)
+                .to_rfc3339_opts(SecondsFormat::Micros, false),
+            Self::Fixed(offset) => at
+                .with_timezone(offset)
+                .to_rfc3339_opts(SecondsFormat::Micros, false),
+        }
+    }
+}
+
+impl FormatTime for LogTimezone {
+    fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+        write!(w, "{}", self.format(Utc::now()))
+    }
+}
+
+/// 扁平化 JSON 事件格式
+///
+/// 需配合 [`JsonFields`](tracing_subscriber::fmt::format::JsonFields) 字段格式化器使用，
+/// 以便从 span 扩展中读取 JSON 格式的字段。字段冲突时内层 span 覆盖外层，事件字段覆盖 span 字段。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlattenedJson {
+    /// 时间戳时区
+    pub timezone: LogTimezone,
+}
+
+impl<S, N> FormatEvent<S, N> for FlattenedJson
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'w> FormatFields<'w> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = Map::new();
+        fields.insert("timestamp".into(), self.timezone.format(Utc::now()).into());
+        fields.insert("level".into(), metadata.level().as_str().into());
+
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+                let Some(formatted) = extensions.get::<FormattedFields<N>>() else {
+                    continue;
+                };
+                if let Ok(Value::Object(span_fields)) = serde_json::from_str(formatted) {
+                    fields.extend(span_fields);
+                }
+            }
+        }
+
+        event.record(&mut JsonVisitor(&mut fields));
+
+        if let Some(file) = metadata.file() {
+            fields.insert("file".into(), file.into());
+        }
+        if let Some(line) = metadata.line() {
+            fields.insert("line".into(), line.into());
+        }
+
+        let json = serde_json::to_string(&fields).map_err(|_| fmt::Error)?;
+        writeln!(writer, "{json}")
+    }
+}
+
+/// 将事件字段写入 JSON 对象的访问器
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl JsonVisitor<'_> {
+    fn insert(&mut self, field: &Field, value: impl Into<Value>) {
+        // 跳过 tracing-log 桥接时附加的 log.* 元数据字段
+        if !field.name().starts_with("log.") {
+            self.0.insert(field.name().to_owned(), value.into());
+        }
+    }
+}
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value);
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value);
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value);
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value);
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value);
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.insert(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{value:?}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::middleware::trace::make_span;
+    use axum::body::Body;
+    use axum::http::Request;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::fmt::MakeWriter;
+    use tracing_subscriber::fmt::format::JsonFields;
+
+    /// 收集日志输出的内存写入器
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Capture {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn capture_lines(f: impl FnOnce()) -> Vec<Value> {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::fmt()
+            .fmt_fields(JsonFields::new())
+            .event_format(FlattenedJson::default())
+            .with_writer(capture.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, f);
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_request_span_fields_are_top_level() {
+        let request = Request::get("/v1/user/me?verbose=1")
+            .header("x-request-id", "req-123")
+            .body(Body::empty())
+            .unwrap();
+
+        let lines = capture_lines(|| {
+            let span = make_span(&request);
+            let _entered = span.enter();
+            span.record("user_id", 42);
+            tracing::info!(answer = 7, "处理请求");
+        });
+
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert_eq!(line["request_id"], "req-123");
+        assert_eq!(line["method"], "GET");
+        assert_eq!(line["uri"], "/v1/user/me?verbose=1");
+        assert_eq!(line["user_id"], 42);
+        assert_eq!(line["message"], "处理请求");
+        assert_eq!(line["answer"], 7);
+        assert_eq!(line["level"], "INFO");
+        assert!(line.get("span").is_none());
+    }
+
+    #[test]
+    fn test_event_outside_span_has_no_request_fields() {
+        let lines = capture_lines(|| tracing::warn!("启动完成"));
+
+        assert_eq!(lines[0]["message"], "启动完成");
+        assert!(lines[0].get("request_id").is_none());
+    }
+
+    #[test]
+    fn test_configured_timezone_shifts_timestamp() {
+        let at = DateTime::parse_from_rfc3339("2024-03-01T23:30:00.123456Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            LogTimezone::parse("UTC").unwrap().format(at),
+            "2024-03-01T23:30:00.123456Z"
+        );
+        assert_eq!(
+            LogTimezone::parse("+08:00").unwrap().format(at),
+            "2024-03-02T07:30:00.123456+08:00"
+        );
+        assert_eq!(
+            LogTimezone::parse("-05:30").unwrap().format(at),
+            "2024-03-01T18:00:00.123456-05:30"
+        );
+        assert!(LogTimezone::parse("Asia/Shanghai").is_err());
+    }
+}
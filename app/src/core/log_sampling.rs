@@ -0,0 +1,197 @@
+//! 按路由采样请求日志
+//!
+//! 健康检查等高频路由会淹没日志。[`SamplingLayer`] 在请求 span 创建时根据路径和
+//! `logging.sampling` 配置决定是否保留该请求的日志，被丢弃的请求内所有 INFO 及以下级别的
+//! 事件都不会输出，WARN 和 ERROR 始终保留。
+//!
+//! 采样结果由 `request_id` 的哈希值决定，同一请求的所有日志行要么全部保留、要么全部丢弃。
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+/// 请求 span 名称，与 [`make_span`](crate::core::middleware::trace::make_span) 保持一致
+const REQUEST_SPAN: &str = "request";
+
+/// 请求日志采样层
+///
+/// 通过 [`Layer::event_enabled`] 全局生效，对所有输出层（控制台、文件、Sentry）一致。
+#[derive(Debug, Clone)]
+pub struct SamplingLayer {
+    /// 路径 → 采样率（0.0 ~ 1.0）
+    rates: BTreeMap<String, f64>,
+}
+
+/// 写入请求 span 扩展的采样结果
+#[derive(Debug, Clone, Copy)]
+struct SampledOut;
+
+impl SamplingLayer {
+    /// 根据采样配置创建采样层，未配置任何路由时返回 `None`
+    pub fn new(rates: &BTreeMap<String, f64>) -> Option<Self> {
+        (!rates.is_empty()).then(|| Self {
+            rates: rates.clone(),
+        })
+    }
+
+    /// 判断请求是否保留日志
+    ///
+    /// 路径按精确匹配查找采样率（忽略查询字符串），未配置的路径始终保留。
+    fn keep(&self, uri: &str, request_id: &str) -> bool {
+        let path = uri.split('?').next().unwrap_or(uri);
+        match self.rates.get(path) {
+            Some(&rate) => sample_point(request_id) < rate,
+            None => true,
+        }
+    }
+}
+
+/// 将请求 ID 映射到 `[0, 1)` 区间（FNV-1a 哈希，跨进程稳定）
+fn sample_point(request_id: &str) -> f64 {
+    let hash = request_id
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        });
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+impl<S> Layer<S> for SamplingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != REQUEST_SPAN {
+            return;
+        }
+
+        let mut fields = RequestFields::default();
+        attrs.record(&mut fields);
+        if self.keep(&fields.uri, &fields.request_id) {
+            return;
+        }
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SampledOut);
+        }
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, ctx: Context<'_, S>) -> bool {
+        // WARN 和 ERROR 不受采样影响
+        if *event.metadata().level() <= Level::WARN {
+            return true;
+        }
+
+        match ctx.event_scope(event) {
+            Some(scope) => !scope
+                .from_root()
+                .any(|span| span.extensions().get::<SampledOut>().is_some()),
+            None => true,
+        }
+    }
+}
+
+/// 从请求 span 中读取采样所需的字段
+#[derive(Default)]
+struct RequestFields {
+    uri: String,
+    request_id: String,
+}
+
+impl Visit for RequestFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "uri" => self.uri = format!("{value:?}"),
+            "request_id" => self.request_id = format!("{value:?}"),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::middleware::trace::make_span;
+    use axum::body::Body;
+    use axum::http::Request;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// 记录实际输出的事件级别
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<Level>>>);
+
+    impl<S: Subscriber> Layer<S> for Recorder {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            self.0.lock().unwrap().push(*event.metadata().level());
+        }
+    }
+
+    /// 在指定路径和请求 ID 的请求 span 内输出 INFO 和 WARN 各一条日志
+    fn run(rates: &[(&str, f64)], uri: &str, request_id: &str) -> Vec<Level> {
+        let rates = rates
+            .iter()
+            .map(|(path, rate)| (path.to_string(), *rate))
+            .collect();
+        let recorder = Recorder::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(recorder.clone())
+            .with(SamplingLayer::new(&rates));
+
+        let request = Request::get(uri)
+            .header("x-request-id", request_id)
+            .body(Body::empty())
+            .unwrap();
+        tracing::subscriber::with_default(subscriber, || {
+            let span = make_span(&request);
+            let _entered = span.enter();
+            tracing::info!("请求日志");
+            tracing::warn!("请求警告");
+        });
+
+        recorder.0.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn test_sampled_out_request_keeps_only_warnings() {
+        let levels = run(&[("/health", 0.0)], "/health", "req-1");
+        assert_eq!(levels, vec![Level::WARN]);
+
+        let levels = run(&[("/health", 1.0)], "/health", "req-1");
+        assert_eq!(levels, vec![Level::INFO, Level::WARN]);
+    }
+
+    #[test]
+    fn test_unconfigured_routes_are_not_sampled() {
+        let levels = run(&[("/health", 0.0)], "/v1/user/me?x=1", "req-1");
+        assert_eq!(levels, vec![Level::INFO, Level::WARN]);
+
+        // 查询字符串不影响路由匹配
+        let levels = run(&[("/health", 0.0)], "/health?probe=1", "req-1");
+        assert_eq!(levels, vec![Level::WARN]);
+    }
+
+    #[test]
+    fn test_sampling_is_deterministic_per_request_id() {
+        let layer = SamplingLayer::new(&BTreeMap::from([("/health".to_string(), 0.5)])).unwrap();
+
+        let decisions: Vec<bool> = (0..200)
+            .map(|i| layer.keep("/health", &format!("req-{i}")))
+            .collect();
+        for (i, keep) in decisions.iter().enumerate() {
+            assert_eq!(layer.keep("/health", &format!("req-{i}")), *keep);
+        }
+
+        let kept = decisions.iter().filter(|&&keep| keep).count();
+        assert!((60..=140).contains(&kept), "kept {kept} of 200");
+    }
+}
@@ -1,167 +1,168 @@
 use crate::{
-    core::config::LoggingConfig,
+    core::{
+        config::{LoggingConfig, SentryConfig},
+        log_format::{FlattenedJson, LogTimezone},
+        log_sampling::SamplingLayer,
+        log_scrub::{Scrubber, ScrubbingMakeWriter},
+    },
     error::{AppError, ValidationError},
 };
-use std::fs;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+use tokio::task::JoinHandle;
 use tracing_appender::non_blocking;
-use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry,
+    fmt::{MakeWriter, format::JsonFields},
+    layer::{Layered, SubscriberExt},
+    reload,
+    util::SubscriberInitExt,
+};
+
+/// 类型擦除后的日志输出层，便于按配置组合控制台和文件输出
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// 日志过滤器所包裹的订阅者类型（注册表 + 输出层）
+type FilteredSubscriber = Layered<Vec<BoxedLayer>, Registry>;
+
+/// 日志系统守卫
+///
+/// 持有非阻塞写入器的后台线程守卫。守卫被丢弃时会刷新缓冲区并停止写入，
+/// 因此必须在整个进程生命周期内保持存活（通常在 `main` 中绑定到变量）。
+#[must_use = "日志守卫被丢弃后将停止写入日志"]
+pub struct LogGuard {
+    _guards: Vec<WorkerGuard>,
+
+    /// 运行时日志级别控制句柄
+    log_level: LogLevelHandle,
+
+    /// Sentry 客户端守卫，被丢弃时刷新未发送的事件
+    #[cfg(feature = "sentry")]
+    _sentry: Option<sentry::ClientInitGuard>,
+}
 
 /// 初始化日志系统
 ///
 /// 根据配置初始化 tracing 日志系统，支持以下功能：
-/// - 控制台输出（pretty 或 compact 格式）
-/// - 文件日志输出（JSON 格式）
-/// - 日志轮转（每日、每小时等）
+/// - 控制台输出（pretty、compact 或 json 格式）
+/// - 文件日志输出（默认 JSON 格式，可单独配置）
+/// - 日志轮转（每小时、每日、按大小或不轮转）
+/// - Sentry 上报（启用 `sentry` feature 且配置了 DSN 时）
+/// - 高频路由的请求日志采样（见 [`SamplingLayer`]）
+/// - 写入前脱敏密码、令牌等敏感值（`scrub = true` 时，见 [`Scrubber`]）
+///
+/// 控制台和文件各自使用独立的格式配置，写入均通过非阻塞写入器完成。
 ///
 /// # 参数
 /// * `config` - 日志配置对象
+/// * `sentry_config` - Sentry 配置
 ///
 /// # 返回
-/// 成功初始化返回 Ok(())，失败返回 AppError
-pub fn init_tracing(config: &LoggingConfig) -> Result<(), AppError> {
-    let env_filter =
-        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.level));
+/// 成功返回 [`LogGuard`]（需在进程生命周期内保持存活），失败返回 AppError
+pub fn init_tracing(
+    config: &LoggingConfig,
+    sentry_config: &SentryConfig,
+) -> Result<LogGuard, AppError> {
+    if !config.console && !config.file {
+        return Err(AppError::Validation(ValidationError::custom(
+            "至少需要启用控制台或文件日志输出",
+        )));
+    }
+
+    // 设置了 RUST_LOG 时以其为准，否则使用 level + 模块级别指令
+    let env_filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(config.filter_directives()));
+    let (filter_layer, log_level) = LogLevelHandle::new(env_filter);
+
+    let scrubber = config
+        .scrub
+        .then(|| Arc::new(Scrubber::new(&config.scrub_keys, &config.scrub_allowlist)));
+    let timezone = config
+        .log_timezone()
+        .map_err(|e| AppError::Validation(ValidationError::custom(e)))?;
+
+    let mut layers: Vec<BoxedLayer> = Vec::new();
+    let mut guards = Vec::new();
+
+    if config.console {
+        let (console_layer, console_guard) = console_layer(config, scrubber.clone(), timezone);
+        layers.push(console_layer);
+        guards.push(console_guard);
+    }
 
     if config.file {
         fs::create_dir_all(&config.file_dir).map_err(AppError::Io)?;
+        let (file_writer, file_guard) = create_file_appender(config)?;
+        layers.push(fmt_layer(
+            &config.file_format,
+            file_writer,
+            false,
+            scrubber,
+            timezone,
+        ));
+        guards.push(file_guard);
     }
 
-    match (config.console, config.file) {
-        (true, true) => {
-            // 同时输出到控制台和文件
-            let (file_writer, _file_guard) = create_file_appender(config)?;
-            let (console_writer, _console_guard) = non_blocking(std::io::stdout());
-
-            let registry = tracing_subscriber::registry().with(env_filter);
-
-            match config.console_format.as_str() {
-                "pretty" => {
-                    registry
-                        .with(
-                            tracing_subscriber::fmt::layer()
-                                .pretty() // 控制台用 pretty->易读
-                                .with_writer(console_writer)
-                                .with_ansi(true)
-                                .with_file(true)
-                                .with_line_number(true)
-                                .with_target(false),
-                        )
-                        .with(
-                            tracing_subscriber::fmt::layer()
-                                .json() // 文件永远用 JSON
-                                .with_writer(file_writer)
-                                .with_file(true)
-                                .with_line_number(true)
-                                .with_target(false)
-                                .with_ansi(false),
-                        )
-                        .init();
-                }
-                _ => {
-                    // 默认使用 compact 格式
-                    registry
-                        .with(
-                            tracing_subscriber::fmt::layer()
-                                .compact()
-                                .with_writer(console_writer)
-                                .with_ansi(true)
-                                .with_file(true)
-                                .with_line_number(true)
-                                .with_target(false),
-                        )
-                        .with(
-                            tracing_subscriber::fmt::layer()
-                                .json()
-                                .with_writer(file_writer)
-                                .with_file(true)
-                                .with_line_number(true)
-                                .with_target(false)
-                                .with_ansi(false),
-                        )
-                        .init();
-                }
-            }
+    #[cfg(feature = "sentry")]
+    let sentry_guard = crate::core::error_reporting::init(sentry_config);
+    #[cfg(feature = "sentry")]
+    if sentry_guard.is_some() {
+        layers.push(sentry::integrations::tracing::layer().boxed());
+    }
 
-            std::mem::forget(_file_guard);
-            std::mem::forget(_console_guard);
-        }
-        (true, false) => {
-            // 仅输出到控制台
-            let (console_writer, _console_guard) = non_blocking(std::io::stdout());
-
-            let registry = tracing_subscriber::registry().with(env_filter);
-
-            match config.console_format.as_str() {
-                "pretty" => {
-                    registry
-                        .with(
-                            tracing_subscriber::fmt::layer()
-                                .pretty()
-                                .with_writer(console_writer)
-                                .with_ansi(true)
-                                .with_file(true)
-                                .with_line_number(true)
-                                .with_target(false),
-                        )
-                        .init();
-                }
-                _ => {
-                    registry
-                        .with(
-                            tracing_subscriber::fmt::layer()
-                                .compact()
-                                .with_writer(console_writer)
-                                .with_ansi(true)
-                                .with_file(true)
-                                .with_line_number(true)
-                                .with_target(false),
-                        )
-                        .init();
-                }
-            }
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(filter_layer)
+        .with(SamplingLayer::new(&config.sampling))
+        .init();
 
-            std::mem::forget(_console_guard);
-        }
-        (false, true) => {
-            // 仅输出到文件
-            let (file_writer, _file_guard) = create_file_appender(config)?;
-
-            let registry = tracing_subscriber::registry().with(env_filter);
-
-            registry
-                .with(
-                    tracing_subscriber::fmt::layer()
-                        .json()
-                        .with_writer(file_writer)
-                        .with_file(true)
-                        .with_line_number(true)
-                        .with_target(false)
-                        .with_ansi(false),
-                )
-                .init();
-
-            std::mem::forget(_file_guard);
-        }
-        (false, false) => {
-            return Err(AppError::Validation(ValidationError::custom(
-                "至少需要启用控制台或文件日志输出",
-            )));
-        }
+    #[cfg(feature = "sentry")]
+    if sentry_guard.is_some() {
+        tracing::info!("Sentry 错误上报已启用");
+    }
+    #[cfg(not(feature = "sentry"))]
+    if sentry_config.dsn.is_some() {
+        tracing::warn!("已配置 Sentry DSN，但当前构建未启用 sentry feature，错误上报不会生效");
     }
 
     tracing::info!(
-        "日志系统初始化完成 - 级别: {}, 控制台格式: {}, 控制台: {}, 文件: {}",
+        "日志系统初始化完成 - 级别: {}, 控制台: {} ({}, {}), 文件: {} ({})",
         config.level,
-        config.console_format,
         config.console,
-        config.file
+        config.console_format,
+        config.target,
+        config.file,
+        config.file_format
     );
 
+    for (path, rate) in &config.sampling {
+        tracing::info!("日志采样: {} 保留 {}%", path, rate * 100.0);
+    }
+
     if config.file {
         tracing::info!("日志文件目录: {}", config.file_dir);
         tracing::info!("日志文件前缀: {}", config.get_file_prefix_with_env());
-        tracing::info!("日志轮转策略: {}", config.rotation);
-        tracing::info!("保留文件数量: {}", config.max_files);
+        if config.rotation == "size" {
+            tracing::info!(
+                "日志轮转策略: size（单个文件上限 {} MB）",
+                config.max_file_size_mb
+            );
+        } else {
+            tracing::info!("日志轮转策略: {}", config.rotation);
+        }
+        tracing::info!(
+            "日志保留策略: 最多 {} 个文件，{} 天，总大小 {} MB（0 表示不限制）",
+            config.max_files,
+            config.retention_days,
+            config.max_total_size_mb
+        );
 
         if config.cleanup_enabled {
             if config.cleanup_interval == 0 {
@@ -177,23 +178,209 @@ pub fn init_tracing(config: &LoggingConfig) -> Result<(), AppError> {
         }
     }
 
-    Ok(())
+    Ok(LogGuard {
+        _guards: guards,
+        log_level,
+        #[cfg(feature = "sentry")]
+        _sentry: sentry_guard,
+    })
+}
+
+impl LogGuard {
+    /// 获取运行时日志级别控制句柄
+    pub fn log_level(&self) -> LogLevelHandle {
+        self.log_level.clone()
+    }
+}
+
+/// 运行时日志级别控制句柄
+///
+/// 包装全局 [`EnvFilter`] 的 [`reload::Handle`]，允许在不重启进程的情况下调整过滤指令。
+/// 设置了有效期的调整会在到期后自动恢复为启动时的过滤指令，避免调试级别被长期遗留在生产环境。
+#[derive(Clone)]
+pub struct LogLevelHandle {
+    handle: reload::Handle<EnvFilter, FilteredSubscriber>,
+
+    /// 启动时生效的过滤指令
+    default_filter: Arc<str>,
+
+    /// 待执行的自动恢复任务
+    revert: Arc<Mutex<Option<RevertTask>>>,
+}
+
+impl std::fmt::Debug for LogLevelHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogLevelHandle")
+            .field("current", &self.current())
+            .field("default_filter", &self.default_filter)
+            .field("expires_at", &self.expires_at())
+            .finish()
+    }
+}
+
+/// 自动恢复任务
+#[derive(Debug)]
+struct RevertTask {
+    at: DateTime<Utc>,
+    task: JoinHandle<()>,
 }
 
-fn create_file_appender(
+impl LogLevelHandle {
+    /// 包装初始过滤器，返回可注册到订阅者的过滤层和对应的控制句柄
+    pub(crate) fn new(filter: EnvFilter) -> (reload::Layer<EnvFilter, FilteredSubscriber>, Self) {
+        let default_filter = filter.to_string().into();
+        let (layer, handle) = reload::Layer::new(filter);
+        let this = Self {
+            handle,
+            default_filter,
+            revert: Arc::default(),
+        };
+        (layer, this)
+    }
+
+    /// 当前生效的过滤指令
+    pub fn current(&self) -> String {
+        self.handle
+            .with_current(|filter| filter.to_string())
+            .unwrap_or_else(|_| self.default_filter.to_string())
+    }
+
+    /// 启动时配置的过滤指令
+    pub fn default_filter(&self) -> &str {
+        &self.default_filter
+    }
+
+    /// 当前调整自动恢复的时间，没有待恢复的调整时返回 `None`
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.revert.lock().unwrap().as_ref().map(|r| r.at)
+    }
+
+    /// 应用新的过滤指令
+    ///
+    /// 指定 `ttl` 时会在到期后自动恢复为启动时的过滤指令；
+    /// 新的调整会取消之前尚未执行的恢复任务。
+    ///
+    /// # 返回
+    /// 指令无法解析时返回解析错误信息
+    pub fn set(&self, directive: &str, ttl: Option<Duration>) -> Result<(), String> {
+        let filter = EnvFilter::try_new(directive).map_err(|e| e.to_string())?;
+        self.handle.reload(filter).map_err(|e| e.to_string())?;
+
+        let mut revert = self.revert.lock().unwrap();
+        if let Some(previous) = revert.take() {
+            previous.task.abort();
+        }
+        if let Some(ttl) = ttl {
+            let this = self.clone();
+            *revert = Some(RevertTask {
+                at: Utc::now() + ttl,
+                task: tokio::spawn(async move {
+                    tokio::time::sleep(ttl).await;
+                    this.restore_default();
+                }),
+            });
+        }
+        Ok(())
+    }
+
+    /// 到期后恢复启动时的过滤指令
+    fn restore_default(&self) {
+        self.revert.lock().unwrap().take();
+        match self.handle.reload(EnvFilter::new(&*self.default_filter)) {
+            Ok(()) => tracing::info!(
+                target: "audit",
+                filter = %self.default_filter,
+                "日志级别调整已到期，恢复为默认过滤指令"
+            ),
+            Err(e) => tracing::error!(error = %e, "恢复默认日志级别失败"),
+        }
+    }
+}
+
+/// 构建控制台日志输出层
+///
+/// 按 `target` 配置写入 stdout 或 stderr（未知目标按 stdout 处理，配置校验阶段已拦截）。
+fn console_layer(
     config: &LoggingConfig,
-) -> Result<(non_blocking::NonBlocking, non_blocking::WorkerGuard), AppError> {
-    use tracing_appender::rolling::{RollingFileAppender, Rotation};
+    scrubber: Option<Arc<Scrubber>>,
+    timezone: LogTimezone,
+) -> (BoxedLayer, WorkerGuard) {
+    let (writer, guard) = match config.target.as_str() {
+        "stderr" => non_blocking(io::stderr()),
+        _ => non_blocking(io::stdout()),
+    };
+    (
+        fmt_layer(&config.console_format, writer, true, scrubber, timezone),
+        guard,
+    )
+}
 
+/// 按格式名称构建日志输出层
+///
+/// 支持 `pretty`、`compact` 和 `json`，未知格式按 `compact` 处理（配置校验阶段已拦截）。
+/// 传入 `scrubber` 时在写入前对格式化后的日志脱敏；时间戳按 `timezone` 输出。
+fn fmt_layer<W>(
+    format: &str,
+    writer: W,
+    ansi: bool,
+    scrubber: Option<Arc<Scrubber>>,
+    timezone: LogTimezone,
+) -> BoxedLayer
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match scrubber {
+        Some(scrubber) => format_layer(
+            format,
+            ScrubbingMakeWriter::new(writer, scrubber),
+            ansi,
+            timezone,
+        ),
+        None => format_layer(format, writer, ansi, timezone),
+    }
+}
+
+fn format_layer<W>(format: &str, writer: W, ansi: bool, timezone: LogTimezone) -> BoxedLayer
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi)
+        .with_file(true)
+        .with_line_number(true)
+        .with_target(false)
+        .with_timer(timezone);
+
+    match format {
+        "pretty" => layer.pretty().boxed(),
+        // 请求 span 字段（request_id、user_id 等）展开到每行 JSON 的顶层
+        "json" => layer
+            .fmt_fields(JsonFields::new())
+            .event_format(FlattenedJson { timezone })
+            .boxed(),
+        _ => layer.compact().boxed(),
+    }
+}
+
+fn create_file_appender(config: &LoggingConfig) -> Result<(NonBlocking, WorkerGuard), AppError> {
     let file_prefix_with_env = config.get_file_prefix_with_env();
 
     let rotation = match config.rotation.as_str() {
         "daily" => Rotation::DAILY,
         "hourly" => Rotation::HOURLY,
         "never" => Rotation::NEVER,
+        "size" => {
+            let appender = SizeRollingAppender::new(
+                &config.file_dir,
+                &file_prefix_with_env,
+                config.max_file_size_mb * 1024 * 1024,
+            )?;
+            return Ok(non_blocking(appender));
+        }
         _ => {
             return Err(AppError::Validation(ValidationError::custom(format!(
-                "不支持的日志轮转策略: {}，支持的策略: daily, hourly, never",
+                "不支持的日志轮转策略: {}，支持的策略: daily, hourly, size, never",
                 config.rotation
             ))));
         }
@@ -209,10 +396,114 @@ fn create_file_appender(
     Ok(non_blocking(file_appender))
 }
 
+/// 按文件大小轮转的日志写入器
+///
+/// 始终写入 `{prefix}.log`，写满 `max_bytes` 后将其重命名为
+/// `{prefix}.{时间戳}.log` 并重新打开。轮转出的文件与按时间轮转的文件
+/// 使用相同的前缀和后缀，因此同样由 [`cleanup_old_logs`] 负责清理。
+struct SizeRollingAppender {
+    dir: PathBuf,
+    prefix: String,
+    max_bytes: u64,
+    file: File,
+    written: u64,
+}
+
+impl SizeRollingAppender {
+    fn new(dir: impl Into<PathBuf>, prefix: &str, max_bytes: u64) -> io::Result<Self> {
+        let dir = dir.into();
+        let path = dir.join(format!("{prefix}.log"));
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            dir,
+            prefix: prefix.to_string(),
+            max_bytes,
+            file,
+            written,
+        })
+    }
+
+    fn active_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.log", self.prefix))
+    }
+
+    fn roll(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S%.3f");
+        let rolled = self.dir.join(format!("{}.{}.log", self.prefix, timestamp));
+        fs::rename(self.active_path(), rolled)?;
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.active_path())?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRollingAppender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // 单条日志不拆分到两个文件中：写入前判断是否需要轮转
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.roll()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// 日志清理策略
+///
+/// 三个限制相互独立，任一项为 0 表示不启用该项限制。
+#[derive(Debug, Clone, Copy)]
+struct RetentionPolicy {
+    /// 最多保留的文件数量
+    max_files: usize,
+
+    /// 文件最长保留时间
+    max_age: Option<Duration>,
+
+    /// 所有日志文件的总大小上限（字节）
+    max_total_bytes: Option<u64>,
+}
+
+impl RetentionPolicy {
+    fn from_config(config: &LoggingConfig) -> Self {
+        Self {
+            max_files: config.max_files,
+            max_age: (config.retention_days > 0)
+                .then(|| Duration::from_secs(config.retention_days * 24 * 3600)),
+            max_total_bytes: (config.max_total_size_mb > 0)
+                .then(|| config.max_total_size_mb * 1024 * 1024),
+        }
+    }
+}
+
+/// 待清理的日志文件
+#[derive(Debug)]
+struct LogFile {
+    path: PathBuf,
+    modified: SystemTime,
+    size: u64,
+}
+
 /// 清理旧日志文件
 ///
-/// 根据配置删除超过最大文件数量限制的最旧的日志文件。
-/// 如果 max_files 设置为 0，则不进行任何清理。
+/// 扫描日志目录中带当前前缀的 `.log` 文件，按以下规则删除（从最旧的文件开始）：
+/// - 修改时间早于 `retention_days` 天的文件
+/// - 超出 `max_files` 数量限制的文件
+/// - 使总大小超过 `max_total_size_mb` 的文件
+///
+/// 最新的文件视为正在写入的文件，始终保留。符号链接一律跳过，
+/// 不会跟随链接删除目录以外的文件。每个被删除的文件都会记录日志。
 ///
 /// # 参数
 /// * `config` - 日志配置对象
@@ -220,45 +511,292 @@ fn create_file_appender(
 /// # 返回
 /// 成功返回 Ok(())，失败返回 AppError
 pub fn cleanup_old_logs(config: &LoggingConfig) -> Result<(), AppError> {
-    if config.max_files == 0 {
-        return Ok(()); // 0 表示不限制文件数量
+    let log_dir = Path::new(&config.file_dir);
+    if !log_dir.is_dir() {
+        return Ok(());
     }
 
-    let log_dir = std::path::Path::new(&config.file_dir);
-    if !log_dir.exists() {
-        return Ok(());
+    let policy = RetentionPolicy::from_config(config);
+    let prefix = config.get_file_prefix_with_env();
+    let deleted = cleanup_dir(log_dir, &prefix, policy, SystemTime::now())?;
+
+    if !deleted.is_empty() {
+        tracing::info!("日志清理完成，共删除 {} 个文件", deleted.len());
     }
 
-    let file_prefix_with_env = config.get_file_prefix_with_env();
+    Ok(())
+}
+
+/// 按清理策略处理指定目录，返回已删除的文件路径
+fn cleanup_dir(
+    dir: &Path,
+    prefix: &str,
+    policy: RetentionPolicy,
+    now: SystemTime,
+) -> io::Result<Vec<PathBuf>> {
+    let mut log_files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        // file_type() 不跟随符号链接，只处理目录内的普通文件
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+        if !(name.starts_with(prefix) && name.ends_with(".log")) {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        log_files.push(LogFile {
+            path: entry.path(),
+            modified: metadata.modified()?,
+            size: metadata.len(),
+        });
+    }
 
-    let mut log_files: Vec<_> = fs::read_dir(log_dir)?
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            let path = entry.path();
-            if path.is_file() {
-                let file_name = path.file_name()?.to_str()?;
-                // 匹配带环境后缀的日志文件
-                if file_name.starts_with(&file_prefix_with_env) && file_name.ends_with(".log") {
-                    let metadata = entry.metadata().ok()?;
-                    let modified = metadata.modified().ok()?;
-                    return Some((path, modified));
+    // 从新到旧排序，第一个为正在写入的文件
+    log_files.sort_by_key(|file| std::cmp::Reverse(file.modified));
+    let Some((active, older)) = log_files.split_first() else {
+        return Ok(Vec::new());
+    };
+
+    let mut kept_files = 1;
+    let mut kept_bytes = active.size;
+    let mut over_budget = false;
+    let mut deleted = Vec::new();
+
+    for file in older {
+        let age = now.duration_since(file.modified).unwrap_or_default();
+        let reason = if policy.max_age.is_some_and(|max_age| age > max_age) {
+            Some("超过保留天数")
+        } else if policy.max_files > 0 && kept_files >= policy.max_files {
+            Some("超过文件数量上限")
+        } else if over_budget
+            || policy
+                .max_total_bytes
+                .is_some_and(|max| kept_bytes + file.size > max)
+        {
+            // 一旦超出总大小，更旧的文件全部删除，保证从最旧的开始清理
+            over_budget = true;
+            Some("超过总大小上限")
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) => match fs::remove_file(&file.path) {
+                Ok(()) => {
+                    tracing::info!(
+                        "已删除旧日志文件（{}）: {}，大小 {} 字节",
+                        reason,
+                        file.path.display(),
+                        file.size
+                    );
+                    deleted.push(file.path.clone());
+                }
+                Err(e) => {
+                    tracing::warn!("删除旧日志文件失败 {}: {}", file.path.display(), e);
                 }
+            },
+            None => {
+                kept_files += 1;
+                kept_bytes += file.size;
             }
-            None
-        })
-        .collect();
+        }
+    }
 
-    log_files.sort_by(|a, b| b.1.cmp(&a.1));
+    Ok(deleted)
+}
 
-    if log_files.len() > config.max_files {
-        for (path, _) in log_files.iter().skip(config.max_files) {
-            if let Err(e) = fs::remove_file(path) {
-                tracing::warn!("删除旧日志文件失败 {}: {}", path.display(), e);
-            } else {
-                tracing::info!("已删除旧日志文件: {}", path.display());
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_console_layer_targets() {
+        for target in ["stdout", "stderr"] {
+            let config = LoggingConfig {
+                target: target.to_string(),
+                console_format: "json".to_string(),
+                ..Default::default()
+            };
+            let (layer, _guard) = console_layer(&config, None, LogTimezone::Utc);
+            let subscriber = tracing_subscriber::registry().with(vec![layer]);
+            tracing::subscriber::with_default(subscriber, || {
+                tracing::info!(target, "控制台输出目标测试");
+            });
         }
     }
 
-    Ok(())
+    #[tokio::test]
+    async fn test_log_level_handle_set_and_report() {
+        let (_layer, handle) = LogLevelHandle::new(EnvFilter::new("info"));
+        assert_eq!(handle.current(), "info");
+
+        handle.set("warn,app=debug", None).unwrap();
+        assert_eq!(handle.current(), "app=debug,warn");
+        assert_eq!(handle.default_filter(), "info");
+        assert!(handle.expires_at().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_log_level_handle_rejects_invalid_directive() {
+        let (_layer, handle) = LogLevelHandle::new(EnvFilter::new("info"));
+
+        assert!(handle.set("app=notalevel", None).is_err());
+        assert_eq!(handle.current(), "info");
+    }
+
+    #[tokio::test]
+    async fn test_log_level_handle_reverts_after_ttl() {
+        let (_layer, handle) = LogLevelHandle::new(EnvFilter::new("info"));
+
+        handle
+            .set("trace", Some(Duration::from_millis(50)))
+            .unwrap();
+        assert_eq!(handle.current(), "trace");
+        assert!(handle.expires_at().is_some());
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(handle.current(), "info");
+        assert!(handle.expires_at().is_none());
+    }
+
+    #[test]
+    fn test_size_rolling_appender_rolls_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut appender = SizeRollingAppender::new(dir.path(), "app-prod", 16).unwrap();
+
+        appender.write_all(b"0123456789\n").unwrap();
+        appender.write_all(b"abcdefghij\n").unwrap();
+        appender.flush().unwrap();
+
+        let mut names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+
+        assert_eq!(names.len(), 2);
+        assert!(
+            names
+                .iter()
+                .all(|n| n.starts_with("app-prod") && n.ends_with(".log"))
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("app-prod.log")).unwrap(),
+            "abcdefghij\n"
+        );
+    }
+
+    /// 在目录中创建指定大小和修改时间（距 `now` 的天数）的日志文件
+    fn fabricate(dir: &Path, name: &str, size: usize, days_ago: u64, now: SystemTime) {
+        let path = dir.join(name);
+        fs::write(&path, vec![b'x'; size]).unwrap();
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(now - Duration::from_secs(days_ago * 24 * 3600))
+            .unwrap();
+    }
+
+    fn remaining(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    const UNLIMITED: RetentionPolicy = RetentionPolicy {
+        max_files: 0,
+        max_age: None,
+        max_total_bytes: None,
+    };
+
+    #[test]
+    fn test_cleanup_deletes_files_older_than_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        fabricate(dir.path(), "app-prod.2024-01-03.log", 10, 1, now);
+        fabricate(dir.path(), "app-prod.2024-01-02.log", 10, 5, now);
+        fabricate(dir.path(), "app-prod.2024-01-01.log", 10, 10, now);
+        fabricate(dir.path(), "other.log", 10, 100, now);
+
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::from_secs(7 * 24 * 3600)),
+            ..UNLIMITED
+        };
+        let deleted = cleanup_dir(dir.path(), "app-prod", policy, now).unwrap();
+
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(
+            remaining(dir.path()),
+            [
+                "app-prod.2024-01-02.log",
+                "app-prod.2024-01-03.log",
+                "other.log"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cleanup_enforces_total_size_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        fabricate(dir.path(), "app-prod.d.log", 40, 0, now);
+        fabricate(dir.path(), "app-prod.c.log", 40, 1, now);
+        fabricate(dir.path(), "app-prod.b.log", 40, 2, now);
+        fabricate(dir.path(), "app-prod.a.log", 10, 3, now);
+
+        let policy = RetentionPolicy {
+            max_total_bytes: Some(100),
+            ..UNLIMITED
+        };
+        cleanup_dir(dir.path(), "app-prod", policy, now).unwrap();
+
+        // b 使总大小超限，比它更旧的 a 即使很小也一并删除
+        assert_eq!(remaining(dir.path()), ["app-prod.c.log", "app-prod.d.log"]);
+    }
+
+    #[test]
+    fn test_cleanup_keeps_active_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        fabricate(dir.path(), "app-prod.log", 500, 30, now);
+
+        let policy = RetentionPolicy {
+            max_files: 1,
+            max_age: Some(Duration::from_secs(24 * 3600)),
+            max_total_bytes: Some(100),
+        };
+        let deleted = cleanup_dir(dir.path(), "app-prod", policy, now).unwrap();
+
+        assert!(deleted.is_empty());
+        assert_eq!(remaining(dir.path()), ["app-prod.log"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cleanup_skips_symlinks() {
+        let outside = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        fabricate(outside.path(), "precious.log", 10, 100, now);
+        fabricate(dir.path(), "app-prod.new.log", 10, 0, now);
+        std::os::unix::fs::symlink(
+            outside.path().join("precious.log"),
+            dir.path().join("app-prod.old.log"),
+        )
+        .unwrap();
+
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::from_secs(24 * 3600)),
+            ..UNLIMITED
+        };
+        let deleted = cleanup_dir(dir.path(), "app-prod", policy, now).unwrap();
+
+        assert!(deleted.is_empty());
+        assert!(outside.path().join("precious.log").exists());
+    }
 }
@@ -0,0 +1,226 @@
+//! 路由访问级别
+//!
+//! 路由通过 [`AccessRouter::api_route_access`] 声明 [`Access`]，同一个声明同时决定
+//! 挂载的认证中间件和 OpenAPI 文档中各操作的 `security`，文档与实际校验不会不一致：
+//!
+//! ```ignore
+//! ApiRouter::new()
+//!     .api_route_access("/me", Access::Authenticated, &state, get_with(me, me_docs))
+//!     .with_state(state)
+//! ```
+
+use std::sync::Arc;
+
+use aide::axum::ApiRouter;
+use aide::axum::routing::ApiMethodRouter;
+use aide::openapi::{PathItem, SecurityRequirement};
+use axum::middleware::from_fn_with_state;
+
+use super::api_key::API_KEY_SCHEME;
+use super::auth::{require_admin, require_admin_or_api_key, require_auth};
+use crate::AppState;
+
+/// OpenAPI 中声明的 JWT 安全方案名称
+pub const BEARER_SCHEME: &str = "BearerAuth";
+
+/// 路由的访问级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// 无需认证
+    Public,
+
+    /// 需要登录（[`require_auth`]），未携带有效 JWT 时返回 401
+    Authenticated,
+
+    /// 需要管理员权限（[`require_auth`] 加 [`require_admin`]），非管理员返回 403
+    Admin,
+
+    /// 管理员，或拥有指定授权范围的 API 密钥（[`require_admin_or_api_key`]），供监控等机器客户端调用
+    AdminOrApiKey(&'static str),
+}
+
+impl Access {
+    /// 按访问级别为路由挂载认证中间件
+    ///
+    /// 中间件位于 `method_router` 已有的层之外，先认证再执行限速等路由自身的层。
+    pub fn layer(
+        self,
+        state: &Arc<AppState>,
+        method_router: ApiMethodRouter<Arc<AppState>>,
+    ) -> ApiMethodRouter<Arc<AppState>> {
+        let method_router = match self {
+            Access::Admin => method_router.layer(from_fn_with_state(state.clone(), require_admin)),
+            Access::AdminOrApiKey(scope) => method_router.layer(from_fn_with_state(
+                (state.clone(), scope),
+                require_admin_or_api_key,
+            )),
+            Access::Public | Access::Authenticated => method_router,
+        };
+        match self {
+            Access::Public | Access::AdminOrApiKey(_) => method_router,
+            Access::Authenticated | Access::Admin => {
+                method_router.layer(from_fn_with_state(state.clone(), require_auth))
+            }
+        }
+    }
+
+    /// 按访问级别为路径下的所有操作写入 `security`
+    pub fn document(self, path_item: &mut PathItem) {
+        if self == Access::Public {
+            return;
+        }
+        // 多个安全要求之间为“或”的关系，满足其一即可
+        let mut requirements = vec![SecurityRequirement::from_iter([(
+            BEARER_SCHEME.to_string(),
+            Vec::new(),
+        )])];
+        if let Access::AdminOrApiKey(scope) = self {
+            requirements.push(SecurityRequirement::from_iter([(
+                API_KEY_SCHEME.to_string(),
+                vec![scope.to_string()],
+            )]));
+        }
+        let operations = [
+            &mut path_item.get,
+            &mut path_item.put,
+            &mut path_item.post,
+            &mut path_item.delete,
+            &mut path_item.options,
+            &mut path_item.head,
+            &mut path_item.patch,
+            &mut path_item.trace,
+        ];
+        for operation in operations.into_iter().flatten() {
+            for requirement in &requirements {
+                if !operation.security.contains(requirement) {
+                    operation.security.push(requirement.clone());
+                }
+            }
+        }
+    }
+}
+
+/// 按 [`Access`] 注册路由
+pub trait AccessRouter {
+    /// 注册路由，同时挂载访问级别对应的中间件并在文档中标注
+    fn api_route_access(
+        self,
+        path: &str,
+        access: Access,
+        state: &Arc<AppState>,
+        method_router: ApiMethodRouter<Arc<AppState>>,
+    ) -> Self;
+}
+
+impl AccessRouter for ApiRouter<Arc<AppState>> {
+    fn api_route_access(
+        self,
+        path: &str,
+        access: Access,
+        state: &Arc<AppState>,
+        method_router: ApiMethodRouter<Arc<AppState>>,
+    ) -> Self {
+        self.api_route_with(path, access.layer(state, method_router), |mut p| {
+            access.document(p.inner_mut());
+            p
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{ApiKeyEntry, AppConfig};
+    use crate::core::middleware::api_key::API_KEY_HEADER;
+    use crate::core::response::ApiResponse;
+    use crate::shared::SharedSecret;
+    use crate::test_support::test_state;
+    use aide::axum::routing::get;
+    use aide::openapi::OpenApi;
+    use axum::body::Body;
+    use axum::extract::Request;
+    use axum::http::StatusCode;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_protected_route_is_documented_and_enforced() {
+        let state = Arc::new(test_state(&AppConfig::default()));
+        let handler = || async { ApiResponse::success("ok".to_string()) };
+
+        let mut api = OpenApi::default();
+        let app = ApiRouter::new()
+            .api_route_access("/open", Access::Public, &state, get(handler))
+            .api_route_access("/private", Access::Authenticated, &state, get(handler))
+            .with_state(state.clone())
+            .finish_api(&mut api);
+
+        let spec = serde_json::to_value(&api).unwrap();
+        assert!(spec["paths"]["/open"]["get"].get("security").is_none());
+        assert_eq!(
+            spec["paths"]["/private"]["get"]["security"],
+            serde_json::json!([{ BEARER_SCHEME: [] }])
+        );
+
+        let status = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                app.oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap()
+                    .status()
+            }
+        };
+        assert_eq!(status("/open").await, StatusCode::OK);
+        assert_eq!(status("/private").await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_admin_or_api_key_route_checks_key_scope() {
+        let mut config = AppConfig::default();
+        config.api_keys.keys = [("monitor", "stats:read"), ("ci", "users:export")]
+            .map(|(label, scope)| ApiKeyEntry {
+                label: label.to_string(),
+                key: SharedSecret::new(format!("{label}-key-0123456789abcdef0123456789abcdef")),
+                scopes: vec![scope.to_string()],
+            })
+            .to_vec();
+        let state = Arc::new(test_state(&config));
+        let handler = || async { ApiResponse::success("ok".to_string()) };
+
+        let mut api = OpenApi::default();
+        let app = ApiRouter::new()
+            .api_route_access(
+                "/stats",
+                Access::AdminOrApiKey("stats:read"),
+                &state,
+                get(handler),
+            )
+            .with_state(state.clone())
+            .finish_api(&mut api);
+
+        let spec = serde_json::to_value(&api).unwrap();
+        assert_eq!(
+            spec["paths"]["/stats"]["get"]["security"],
+            serde_json::json!([{ BEARER_SCHEME: [] }, { API_KEY_SCHEME: ["stats:read"] }])
+        );
+
+        let status = |key: Option<&'static str>| {
+            let app = app.clone();
+            async move {
+                let mut request = Request::get("/stats");
+                if let Some(label) = key {
+                    let key = format!("{label}-key-0123456789abcdef0123456789abcdef");
+                    request = request.header(API_KEY_HEADER, key);
+                }
+                app.oneshot(request.body(Body::empty()).unwrap())
+                    .await
+                    .unwrap()
+                    .status()
+            }
+        };
+        assert_eq!(status(Some("monitor")).await, StatusCode::OK);
+        assert_eq!(status(Some("ci")).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(Some("unknown")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+    }
+}
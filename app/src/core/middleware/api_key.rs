@@ -0,0 +1,194 @@
+//! API 密钥认证
+//!
+//! 机器客户端通过 `X-Auth-Key` 请求头携带 `api_keys` 中配置的密钥，作为 JWT 之外的认证方式。
+//! 管理员和机器客户端都需要调用的路由声明 [`Access::AdminOrApiKey`](super::access::Access::AdminOrApiKey)，
+//! 由中间件检查授权范围；只供机器客户端调用的处理器声明 [`ApiKeyAuth`] 参数即要求 API 密钥认证，
+//! 缺失或无效时返回 401：
+//!
+//! ```ignore
+//! async fn sync(key: ApiKeyAuth) -> Result<ApiResponse<()>, AppError> {
+//!     key.require_scope("users:sync")?;
+//!     // ...
+//! }
+//! ```
+
+use std::sync::Arc;
+
+use aide::OperationInput;
+use aide::generate::GenContext;
+use aide::openapi::{Operation, SecurityRequirement};
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::HeaderMap;
+use axum::http::request::Parts;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use tracing::warn;
+
+use crate::AppState;
+use crate::core::config::ApiKeysConfig;
+use crate::error::{AppError, AuthError};
+
+/// 携带 API 密钥的请求头
+pub const API_KEY_HEADER: &str = "x-auth-key";
+
+/// OpenAPI 中声明的安全方案名称
+pub const API_KEY_SCHEME: &str = "ApiKey";
+
+/// 已配置的 API 密钥集合
+///
+/// 只保存密钥的 SHA-256 摘要，比较时使用常量时间比较，避免通过响应耗时猜测密钥。
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeys {
+    entries: Arc<[ApiKeyEntry]>,
+}
+
+#[derive(Debug)]
+struct ApiKeyEntry {
+    digest: [u8; 32],
+    label: String,
+    scopes: Vec<String>,
+}
+
+impl ApiKeys {
+    pub fn new(config: &ApiKeysConfig) -> Self {
+        Self {
+            entries: config
+                .keys
+                .iter()
+                .map(|entry| ApiKeyEntry {
+                    digest: Sha256::digest(entry.key.as_bytes()).into(),
+                    label: entry.label.clone(),
+                    scopes: entry.scopes.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// 校验请求头中的 API 密钥，未携带或不匹配时返回 `None`
+    pub fn authenticate(&self, headers: &HeaderMap) -> Option<ApiKeyAuth> {
+        let key = headers.get(API_KEY_HEADER)?.as_bytes();
+        let digest: [u8; 32] = Sha256::digest(key).into();
+        self.entries
+            .iter()
+            .find(|entry| bool::from(entry.digest.ct_eq(&digest)))
+            .map(|entry| ApiKeyAuth {
+                label: entry.label.clone(),
+                scopes: entry.scopes.clone(),
+            })
+    }
+
+    /// 校验请求头中的 API 密钥，未携带或不匹配时返回 401
+    ///
+    /// 通过后把密钥标识记录到请求 span，使后续日志都能区分调用方。
+    pub fn require(&self, headers: &HeaderMap) -> Result<ApiKeyAuth, AppError> {
+        let auth = self.authenticate(headers).ok_or_else(|| {
+            warn!("Missing or invalid API key");
+            AppError::Auth(AuthError::InvalidApiKey)
+        })?;
+        tracing::Span::current().record("api_key", auth.label.as_str());
+        Ok(auth)
+    }
+}
+
+/// 通过 API 密钥认证的调用方
+#[derive(Debug, Clone)]
+pub struct ApiKeyAuth {
+    /// 密钥标识
+    pub label: String,
+
+    /// 授权范围
+    pub scopes: Vec<String>,
+}
+
+impl ApiKeyAuth {
+    /// 要求调用方拥有指定授权范围，否则返回 403
+    pub fn require_scope(&self, scope: &str) -> Result<(), AppError> {
+        if self.scopes.iter().any(|s| s == scope) {
+            return Ok(());
+        }
+        warn!(api_key = %self.label, scope, "API 密钥缺少授权范围");
+        Err(AppError::Auth(AuthError::PermissionDenied))
+    }
+}
+
+impl FromRef<Arc<AppState>> for ApiKeys {
+    fn from_ref(state: &Arc<AppState>) -> Self {
+        state.api_keys.clone()
+    }
+}
+
+impl<S> FromRequestParts<S> for ApiKeyAuth
+where
+    ApiKeys: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        ApiKeys::from_ref(state).require(&parts.headers)
+    }
+}
+
+impl OperationInput for ApiKeyAuth {
+    fn operation_input(_ctx: &mut GenContext, operation: &mut Operation) {
+        operation.security.push(SecurityRequirement::from_iter([(
+            API_KEY_SCHEME.to_string(),
+            Vec::new(),
+        )]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::ApiKeyEntry;
+    use crate::shared::SharedSecret;
+    use axum::body::Body;
+    use axum::extract::Request;
+    use axum::http::StatusCode;
+    use axum::{Router, routing::get};
+    use tower::ServiceExt;
+
+    const CI_KEY: &str = "ci-key-0123456789abcdef0123456789abcdef";
+
+    fn app() -> Router {
+        let keys = ApiKeys::new(&ApiKeysConfig {
+            keys: vec![ApiKeyEntry {
+                label: "ci".to_string(),
+                key: SharedSecret::new(CI_KEY),
+                scopes: vec!["users:export".to_string()],
+            }],
+        });
+        Router::new()
+            .route(
+                "/machine",
+                get(|key: ApiKeyAuth| async move {
+                    key.require_scope("users:export").map(|()| key.label)
+                }),
+            )
+            .with_state(keys)
+    }
+
+    async fn call(key: Option<&str>) -> StatusCode {
+        let mut request = Request::get("/machine");
+        if let Some(key) = key {
+            request = request.header(API_KEY_HEADER, key);
+        }
+        app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_valid_key_is_accepted() {
+        assert_eq!(call(Some(CI_KEY)).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_missing_or_invalid_key_is_rejected() {
+        assert_eq!(call(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(call(Some("ci-key-wrong")).await, StatusCode::UNAUTHORIZED);
+    }
+}
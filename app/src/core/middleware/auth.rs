@@ -1,13 +1,24 @@
-use axum::{extract::Request, middleware::Next, response::Response};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
 use tracing::warn;
 
-use crate::{AppState, error::AppError};
+use super::api_key::API_KEY_HEADER;
+use crate::{
+    AppState,
+    error::{AppError, AuthError},
+};
+use entity::enums::UserRole;
+use entity::user::{self, UserId};
+use sea_orm::{DatabaseConnection, EntityTrait};
 use std::sync::Arc;
 
 /// 当前登录用户标识
 #[derive(Debug, Clone)]
 pub struct CurrentUser {
-    pub user_id: i32,
+    pub user_id: UserId,
 }
 
 /// 认证中间件 - 验证 JWT token
@@ -16,7 +27,13 @@ pub async fn require_auth(
     mut request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
-    // 从 Authorization header 中提取 token
+    let user_id = bearer_user_id(&state, &request)?;
+    set_current_user(&mut request, user_id);
+    Ok(next.run(request).await)
+}
+
+/// 从 Authorization header 中提取并验证 JWT，返回其中的用户 ID
+fn bearer_user_id(state: &AppState, request: &Request) -> Result<UserId, AppError> {
     let auth_header = request
         .headers()
         .get("authorization")
@@ -31,13 +48,76 @@ pub async fn require_auth(
     };
 
     // 验证 token
-    let user_id = state.jwt_service.extract_user_id(token).map_err(|_| {
+    state.jwt_service.extract_user_id(token).map_err(|_| {
         warn!("Invalid or expired token");
         AppError::Auth(crate::error::AuthError::InvalidPassword)
-    })?;
+    })
+}
+
+/// 将当前用户注入到请求扩展中
+fn set_current_user(request: &mut Request, user_id: UserId) {
+    // 记录到请求 span，使后续日志都携带 user_id
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    #[cfg(feature = "sentry")]
+    crate::core::error_reporting::set_user_id(user_id);
 
-    // 将当前用户注入到请求扩展中
     request.extensions_mut().insert(CurrentUser { user_id });
+}
+
+/// 管理员权限中间件 - 要求当前用户为管理员
+///
+/// 需位于 [`require_auth`] 内层，依赖其注入的 [`CurrentUser`]。
+/// 角色每次从数据库读取，降级后立即生效。
+pub async fn require_admin(
+    state: axum::extract::State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let user_id = request
+        .extensions()
+        .get::<CurrentUser>()
+        .map(|u| u.user_id)
+        .ok_or(AppError::Auth(AuthError::InvalidToken))?;
+
+    if !is_admin(&state.db, user_id).await? {
+        warn!(%user_id, "非管理员访问管理接口");
+        return Err(AppError::Auth(AuthError::PermissionDenied));
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// 用户当前是否为管理员（从数据库读取角色，用户不存在时为 false）
+pub async fn is_admin(db: &DatabaseConnection, user_id: UserId) -> Result<bool, AppError> {
+    let role = user::Entity::find_by_id(user_id)
+        .one(db)
+        .await?
+        .map(|u| u.role);
+    Ok(role == Some(UserRole::Admin.into()))
+}
+
+/// 管理员或 API 密钥中间件 - 要求管理员身份，或拥有 `scope` 授权范围的 API 密钥
+///
+/// 携带 `X-Auth-Key` 时按 API 密钥认证：密钥无效返回 401，缺少授权范围返回 403，通过后注入
+/// [`ApiKeyAuth`](super::ApiKeyAuth)；否则与 [`require_auth`] 加 [`require_admin`] 相同，注入 [`CurrentUser`]。
+pub async fn require_admin_or_api_key(
+    State((state, scope)): State<(Arc<AppState>, &'static str)>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if request.headers().contains_key(API_KEY_HEADER) {
+        let auth = state.api_keys.require(request.headers())?;
+        auth.require_scope(scope)?;
+        request.extensions_mut().insert(auth);
+        return Ok(next.run(request).await);
+    }
 
+    let user_id = bearer_user_id(&state, &request)?;
+    set_current_user(&mut request, user_id);
+    if !is_admin(&state.db, user_id).await? {
+        warn!(%user_id, "非管理员访问管理接口");
+        return Err(AppError::Auth(AuthError::PermissionDenied));
+    }
     Ok(next.run(request).await)
 }
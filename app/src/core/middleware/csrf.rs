@@ -0,0 +1,345 @@
+//! CSRF 防护中间件
+//!
+//! 面向服务端渲染的表单页面，采用签名的双重提交 Cookie 方案：
+//!
+//! 1. GET 请求的响应中下发 `csrf_token` Cookie（已有合法令牌时不重复下发），
+//!    令牌为 `随机数.HMAC-SHA256(session_secret, 随机数)`，均为 URL 安全的 Base64
+//! 2. POST / PUT / PATCH / DELETE 请求必须通过 `X-CSRF-Token` 请求头或 `_csrf` 表单字段
+//!    回传与 Cookie 相同的令牌，且签名有效，否则返回 403
+//!
+//! 携带有效 JWT `Authorization` 头或有效 `X-Auth-Key` API 密钥的请求（API 客户端）
+//! 不依赖 Cookie 认证，直接放行。
+//! 通过 `server.csrf_enabled` 开启，签名密钥为 `SESSION_SECRET`。
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::body::{Body, to_bytes};
+use axum::extract::Request;
+use axum::http::{HeaderMap, HeaderValue, Method, header};
+use axum::response::{IntoResponse, Response};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use futures_util::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tower::{Layer, Service};
+use tracing::warn;
+
+use super::api_key::ApiKeys;
+use crate::error::{AppError, AuthError};
+use crate::shared::crypto::{constant_time_compare, generate_token};
+use crate::shared::{SharedSecret, jwt::JwtService};
+
+/// 存放 CSRF 令牌的 Cookie 名
+pub const CSRF_COOKIE: &str = "csrf_token";
+
+/// 回传 CSRF 令牌的请求头
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// 回传 CSRF 令牌的表单字段
+pub const CSRF_FORM_FIELD: &str = "_csrf";
+
+/// 读取表单字段时请求体的大小上限
+const FORM_BODY_LIMIT: usize = 1024 * 1024;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// CSRF 防护层
+#[derive(Clone)]
+pub struct CsrfLayer {
+    csrf: Arc<Csrf>,
+}
+
+impl CsrfLayer {
+    /// 使用会话密钥签名令牌，`jwt_service` 和 `api_keys` 用于识别并放行 API 客户端
+    pub fn new(secret: &SharedSecret, jwt_service: JwtService, api_keys: ApiKeys) -> Self {
+        Self {
+            csrf: Arc::new(Csrf {
+                secret: secret.clone(),
+                jwt_service,
+                api_keys,
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for CsrfLayer {
+    type Service = CsrfService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CsrfService {
+            inner,
+            csrf: self.csrf.clone(),
+        }
+    }
+}
+
+/// [`CsrfLayer`] 生成的服务
+#[derive(Clone)]
+pub struct CsrfService<S> {
+    inner: S,
+    csrf: Arc<Csrf>,
+}
+
+impl<S> Service<Request> for CsrfService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        // 取出已就绪的服务，留下克隆体供下次 poll_ready
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+        let csrf = self.csrf.clone();
+        Box::pin(async move { Ok(csrf.handle(request, inner).await) })
+    }
+}
+
+struct Csrf {
+    secret: SharedSecret,
+    jwt_service: JwtService,
+    api_keys: ApiKeys,
+}
+
+impl Csrf {
+    async fn handle<S>(&self, request: Request, mut inner: S) -> Response
+    where
+        S: Service<Request, Response = Response, Error = Infallible>,
+    {
+        let cookie_token = cookie_value(request.headers(), CSRF_COOKIE)
+            .filter(|token| self.verify(token))
+            .map(str::to_string);
+
+        if request.method() == Method::GET {
+            let Ok(mut response) = inner.call(request).await;
+            if cookie_token.is_none()
+                && let Ok(cookie) = HeaderValue::from_str(&format!(
+                    "{CSRF_COOKIE}={}; Path=/; SameSite=Strict",
+                    self.issue()
+                ))
+            {
+                response.headers_mut().append(header::SET_COOKIE, cookie);
+            }
+            return response;
+        }
+
+        if !is_state_changing(request.method()) || self.is_api_client(request.headers()) {
+            let Ok(response) = inner.call(request).await;
+            return response;
+        }
+
+        let (request, submitted) = match submitted_token(request).await {
+            Ok(result) => result,
+            Err(response) => return response,
+        };
+        let valid = match (cookie_token, submitted) {
+            (Some(cookie), Some(submitted)) => {
+                constant_time_compare(cookie.as_bytes(), submitted.as_bytes())
+            }
+            _ => false,
+        };
+        if !valid {
+            warn!(method = %request.method(), uri = %request.uri(), "CSRF 令牌校验失败");
+            return AppError::Auth(AuthError::CsrfTokenInvalid).into_response();
+        }
+
+        let Ok(response) = inner.call(request).await;
+        response
+    }
+
+    /// 生成新的签名令牌
+    fn issue(&self) -> String {
+        let nonce = generate_token(32);
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(&nonce).finalize().into_bytes());
+        format!("{nonce}.{signature}")
+    }
+
+    /// 校验令牌签名
+    fn verify(&self, token: &str) -> bool {
+        let Some((nonce, signature)) = token.split_once('.') else {
+            return false;
+        };
+        let Ok(signature) = URL_SAFE_NO_PAD.decode(signature) else {
+            return false;
+        };
+        self.mac(nonce).verify_slice(&signature).is_ok()
+    }
+
+    fn mac(&self, nonce: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(self.secret.as_bytes()).expect("HMAC 接受任意长度的密钥");
+        mac.update(nonce.as_bytes());
+        mac
+    }
+
+    /// 是否为携带有效 JWT 或 API 密钥的 API 客户端
+    fn is_api_client(&self, headers: &HeaderMap) -> bool {
+        let valid_jwt = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .is_some_and(|token| self.jwt_service.extract_user_id(token).is_ok());
+        valid_jwt || self.api_keys.authenticate(headers).is_some()
+    }
+}
+
+fn is_state_changing(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    )
+}
+
+/// 从 `Cookie` 请求头中读取指定 Cookie 的值
+fn cookie_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find_map(|(key, value)| (key == name).then_some(value))
+}
+
+/// 读取请求回传的令牌：优先使用请求头，其次是 urlencoded 表单中的 `_csrf` 字段
+///
+/// 读取表单时会缓冲请求体并原样放回，后续处理器仍可正常提取表单。
+async fn submitted_token(request: Request) -> Result<(Request, Option<String>), Response> {
+    if let Some(token) = request
+        .headers()
+        .get(CSRF_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        let token = token.to_string();
+        return Ok((request, Some(token)));
+    }
+
+    let is_form = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/x-www-form-urlencoded"));
+    if !is_form {
+        return Ok((request, None));
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, FORM_BODY_LIMIT).await.map_err(|e| {
+        AppError::Validation(crate::ValidationError::custom(e.to_string())).into_response()
+    })?;
+    let token = form_urlencoded::parse(&bytes)
+        .find(|(key, _)| key == CSRF_FORM_FIELD)
+        .map(|(_, value)| value.into_owned());
+    Ok((Request::from_parts(parts, Body::from(bytes)), token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::user_id;
+    use axum::http::StatusCode;
+    use axum::{Router, routing::get};
+    use tower::ServiceExt;
+
+    const SECRET: &str = "session-secret-at-least-32-characters!";
+
+    fn jwt_service() -> JwtService {
+        JwtService::new("jwt-secret-at-least-32-characters-long".to_string())
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/form", get(|| async { "form" }).post(|| async { "saved" }))
+            .layer(CsrfLayer::new(
+                &SharedSecret::new(SECRET),
+                jwt_service(),
+                ApiKeys::default(),
+            ))
+    }
+
+    async fn issue_token(app: &Router) -> String {
+        let response = app
+            .clone()
+            .oneshot(Request::get("/form").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        let (pair, _) = cookie.split_once(';').unwrap();
+        pair.strip_prefix("csrf_token=").unwrap().to_string()
+    }
+
+    async fn post(app: &Router, request: axum::http::request::Builder, body: &str) -> StatusCode {
+        app.clone()
+            .oneshot(
+                request
+                    .method(Method::POST)
+                    .uri("/form")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_header_and_form_tokens_are_accepted() {
+        let app = app();
+        let token = issue_token(&app).await;
+        let cookie = format!("{CSRF_COOKIE}={token}");
+
+        let with_header = Request::builder()
+            .header(header::COOKIE, &cookie)
+            .header(CSRF_HEADER, &token);
+        assert_eq!(post(&app, with_header, "").await, StatusCode::OK);
+
+        let with_form = Request::builder()
+            .header(header::COOKIE, &cookie)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+        let body = format!("name=a&{CSRF_FORM_FIELD}={token}");
+        assert_eq!(post(&app, with_form, &body).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_missing_mismatched_or_forged_tokens_are_rejected() {
+        let app = app();
+        let token = issue_token(&app).await;
+        let other = issue_token(&app).await;
+
+        let missing = Request::builder().header(header::COOKIE, format!("{CSRF_COOKIE}={token}"));
+        assert_eq!(post(&app, missing, "").await, StatusCode::FORBIDDEN);
+
+        let mismatched = Request::builder()
+            .header(header::COOKIE, format!("{CSRF_COOKIE}={token}"))
+            .header(CSRF_HEADER, &other);
+        assert_eq!(post(&app, mismatched, "").await, StatusCode::FORBIDDEN);
+
+        let forged = "bm9uY2U.c2lnbmF0dXJl";
+        let unsigned = Request::builder()
+            .header(header::COOKIE, format!("{CSRF_COOKIE}={forged}"))
+            .header(CSRF_HEADER, forged);
+        assert_eq!(post(&app, unsigned, "").await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_valid_jwt_bypasses_csrf() {
+        let app = app();
+        let jwt = jwt_service().generate_token(user_id(1), 3600).unwrap();
+
+        let api_client = Request::builder().header(header::AUTHORIZATION, format!("Bearer {jwt}"));
+        assert_eq!(post(&app, api_client, "").await, StatusCode::OK);
+
+        let bad_jwt = Request::builder().header(header::AUTHORIZATION, "Bearer not-a-jwt");
+        assert_eq!(post(&app, bad_jwt, "").await, StatusCode::FORBIDDEN);
+    }
+}
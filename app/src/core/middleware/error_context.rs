@@ -0,0 +1,161 @@
+//! 内部错误的上下文链日志
+//!
+//! 服务层用 `anyhow::Context` 为内部错误逐层附加上下文（如「创建用户失败」←「数据库查询失败」←
+//! 驱动错误）。错误转换为 500 响应时只向客户端返回通用消息，完整的错误链通过 [`ErrorChain`]
+//! 写入响应扩展，由 [`error_context_middleware`] 记录为一条结构化日志，每一层上下文一个字段。
+
+use std::sync::Arc;
+
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::Response;
+
+/// 单独记录为字段的上下文层数，更深的层级合并到 `cause_rest`
+const MAX_CAUSE_FIELDS: usize = 4;
+
+/// anyhow 错误链，从最外层上下文到根因依次排列
+#[derive(Debug, Clone)]
+pub struct ErrorChain(Arc<[String]>);
+
+impl ErrorChain {
+    /// 展开错误链中每一层的消息
+    pub fn new(error: &anyhow::Error) -> Self {
+        Self(error.chain().map(ToString::to_string).collect())
+    }
+
+    /// 将错误链写入响应扩展
+    pub fn attach(error: &anyhow::Error, mut response: Response) -> Response {
+        response.extensions_mut().insert(Self::new(error));
+        response
+    }
+
+    /// 从最外层上下文到根因的各层消息
+    pub fn messages(&self) -> &[String] {
+        &self.0
+    }
+
+    /// 记录错误链：`error` 为最外层上下文，`cause_1`..`cause_4` 依次为内层原因，
+    /// 超出的层级以 ` <- ` 连接写入 `cause_rest`，`root_cause` 始终为最内层
+    fn log(&self, status: StatusCode) {
+        let level = |i: usize| self.0.get(i).map(String::as_str);
+        let rest = (self.0.len() > MAX_CAUSE_FIELDS + 1)
+            .then(|| self.0[MAX_CAUSE_FIELDS + 1..].join(" <- "));
+        tracing::error!(
+            status = status.as_u16(),
+            depth = self.0.len(),
+            error = level(0),
+            cause_1 = level(1),
+            cause_2 = level(2),
+            cause_3 = level(3),
+            cause_4 = level(4),
+            cause_rest = rest,
+            root_cause = self.0.last().map(String::as_str),
+            "internal error"
+        );
+    }
+}
+
+/// 记录 5xx 响应携带的错误链
+///
+/// 放在追踪 span 内层，日志会带上 `request_id` 等请求字段。
+pub async fn error_context_middleware(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    if response.status().is_server_error()
+        && let Some(chain) = response.extensions().get::<ErrorChain>()
+    {
+        chain.log(response.status());
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::Mutex;
+
+    use anyhow::Context;
+    use axum::{Router, body::Body, response::IntoResponse, routing::get};
+    use tower::ServiceExt;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::*;
+    use crate::error::AppError;
+
+    /// 收集日志输出的内存写入器
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Capture {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    async fn failing() -> Result<(), AppError> {
+        Err(io::Error::other("connection reset"))
+            .context("数据库查询失败")
+            .context("创建用户失败")?
+    }
+
+    #[tokio::test]
+    async fn test_logs_each_context_level_as_a_field() {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(capture.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let router = Router::new()
+            .route("/", get(failing))
+            .layer(axum::middleware::from_fn(error_context_middleware));
+        let response = router
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(!String::from_utf8_lossy(&body).contains("connection reset"));
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let log: serde_json::Value = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .find(|log| log["fields"]["message"] == "internal error")
+            .unwrap();
+        let fields = &log["fields"];
+        assert_eq!(fields["status"], 500);
+        assert_eq!(fields["depth"], 3);
+        assert_eq!(fields["error"], "创建用户失败");
+        assert_eq!(fields["cause_1"], "数据库查询失败");
+        assert_eq!(fields["cause_2"], "connection reset");
+        assert_eq!(fields["root_cause"], "connection reset");
+        assert!(fields.get("cause_3").is_none());
+    }
+
+    #[test]
+    fn test_chain_is_attached_to_response_extensions() {
+        let error = anyhow::anyhow!("root").context("outer");
+        let response =
+            ErrorChain::attach(&error, StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        let chain = response.extensions().get::<ErrorChain>().unwrap();
+        assert_eq!(chain.messages(), ["outer", "root"]);
+    }
+}
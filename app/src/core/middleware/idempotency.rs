@@ -0,0 +1,792 @@
+//! 幂等请求中间件
+//!
+//! 客户端在 POST / PATCH 请求中携带 `Idempotency-Key` 请求头后，网络超时等原因导致的重试不会重复执行：
+//! 首次请求的完整响应（状态码、响应头、响应体）以 MessagePack 序列化后存入 Redis，
+//! 键为 `idem:{method}:{path}:{caller}:{key}`（`key` 为客户端幂等键的 [`stable_hash`]，Redis 键的长度与客户端
+//! 传入的键无关），保留 24 小时；之后相同键的请求直接返回保存的响应，
+//! 并带上 `Idempotent-Replayed: true`。
+//!
+//! 本层位于路由认证之外，`caller` 是 `Authorization` 和 `X-Auth-Key` 请求头的 SHA-256（未携带凭据时为
+//! `anonymous`），不同调用方使用相同的键互不影响。保存响应时同时记录请求体的 SHA-256，相同键的请求
+//! 携带不同的请求体时返回 422，不重放也不再次执行。
+//!
+//! 相同键的并发请求通过 Redis 分布式锁（`SET NX PX`）串行化：持锁的请求执行处理器并保存响应，
+//! 其他请求返回 409，客户端稍后重试即可拿到保存的响应。锁在保存响应后释放，最长保留
+//! [`LOCK_TTL`]，持锁实例崩溃也不会永久阻塞该键。
+//!
+//! 以下情况不保存响应，重试会再次执行处理器：5xx 响应（通常是暂时性故障）、401 / 403 响应、
+//! 大小未知的流式响应、超过 [`MAX_CACHED_BODY_BYTES`] 的响应，以及处理器没有读完请求体
+//! （无法得到请求体摘要）的请求。Redis 不可用时记录警告后直接执行处理器，不影响正常请求。
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+
+use axum::body::{Body, Bytes, HttpBody, to_bytes};
+use axum::extract::Request;
+use axum::http::{HeaderName, HeaderValue, Method, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use deadpool_redis::Pool as RedisPool;
+use deadpool_redis::redis;
+use futures_util::StreamExt;
+use futures_util::future::BoxFuture;
+use http_body::{Frame, SizeHint};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tower::{Layer, Service};
+use tracing::{debug, warn};
+
+use super::api_key::API_KEY_HEADER;
+use crate::error::{AppError, RedisError, ValidationError};
+use crate::shared::crypto::generate_token;
+use crate::shared::hashing::stable_hash;
+
+/// 客户端传入幂等键的请求头
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// 标记响应为重放结果的响应头
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// 保存响应的有效期
+pub const RESPONSE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 分布式锁的最长保留时间
+pub const LOCK_TTL: Duration = Duration::from_secs(60);
+
+/// 保存的响应体大小上限，超过时不保存
+pub const MAX_CACHED_BODY_BYTES: usize = 1024 * 1024;
+
+/// 幂等键的最大长度
+const MAX_KEY_LEN: usize = 255;
+
+/// 只在锁仍属于自己时删除，避免误删锁过期后其他请求获得的锁
+const UNLOCK_SCRIPT: &str = "if redis.call('GET', KEYS[1]) == ARGV[1] then return redis.call('DEL', KEYS[1]) else return 0 end";
+
+/// 保存在 Redis 中的响应
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdempotencyCachedResponse {
+    /// 状态码
+    pub status: u16,
+
+    /// 响应头（同名头按出现顺序保存多项）
+    pub headers: Vec<(String, String)>,
+
+    /// 响应体
+    pub body: Vec<u8>,
+
+    /// 首次请求的请求体 SHA-256（十六进制）
+    pub request_digest: String,
+
+    /// 首次请求的请求体长度
+    pub request_len: u64,
+}
+
+impl IdempotencyCachedResponse {
+    /// 重建响应，无法还原的响应头被丢弃
+    fn into_response(self) -> Response {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() =
+            StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let headers = response.headers_mut();
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) =
+                (HeaderName::try_from(name), HeaderValue::try_from(value))
+            {
+                headers.append(name, value);
+            }
+        }
+        headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+/// 保存响应和分布式锁的存储
+pub trait IdempotencyStore: Send + Sync + 'static {
+    /// 读取保存的响应
+    fn get(
+        &self,
+        key: &str,
+    ) -> impl Future<Output = Result<Option<IdempotencyCachedResponse>, RedisError>> + Send;
+
+    /// 保存响应，`ttl` 后过期
+    fn put(
+        &self,
+        key: &str,
+        response: &IdempotencyCachedResponse,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<(), RedisError>> + Send;
+
+    /// 尝试加锁，锁已被持有时返回 `false`
+    fn try_lock(
+        &self,
+        key: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<bool, RedisError>> + Send;
+
+    /// 释放自己持有的锁
+    fn unlock(&self, key: &str, token: &str)
+    -> impl Future<Output = Result<(), RedisError>> + Send;
+}
+
+/// 基于 Redis 的存储
+#[derive(Clone)]
+pub struct RedisIdempotencyStore {
+    pool: RedisPool,
+}
+
+impl RedisIdempotencyStore {
+    pub fn new(pool: RedisPool) -> Self {
+        Self { pool }
+    }
+
+    async fn connection(&self) -> Result<deadpool_redis::Connection, RedisError> {
+        self.pool
+            .get()
+            .await
+            .map_err(|e| RedisError::Connection(e.to_string()))
+    }
+}
+
+impl IdempotencyStore for RedisIdempotencyStore {
+    async fn get(&self, key: &str) -> Result<Option<IdempotencyCachedResponse>, RedisError> {
+        let mut conn = self.connection().await?;
+        let bytes: Option<Vec<u8>> = redis::cmd("GET")
+            .arg(key)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| RedisError::Operation(e.to_string()))?;
+        bytes
+            .map(|b| rmp_serde::from_slice(&b).map_err(|e| RedisError::Operation(e.to_string())))
+            .transpose()
+    }
+
+    async fn put(
+        &self,
+        key: &str,
+        response: &IdempotencyCachedResponse,
+        ttl: Duration,
+    ) -> Result<(), RedisError> {
+        let bytes =
+            rmp_serde::to_vec(response).map_err(|e| RedisError::Operation(e.to_string()))?;
+        let mut conn = self.connection().await?;
+        redis::cmd("SET")
+            .arg(key)
+            .arg(bytes)
+            .arg("PX")
+            .arg(ttl.as_millis() as u64)
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| RedisError::Operation(e.to_string()))
+    }
+
+    async fn try_lock(&self, key: &str, token: &str, ttl: Duration) -> Result<bool, RedisError> {
+        let mut conn = self.connection().await?;
+        let reply: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(token)
+            .arg("NX")
+            .arg("PX")
+            .arg(ttl.as_millis() as u64)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| RedisError::Operation(e.to_string()))?;
+        Ok(reply.is_some())
+    }
+
+    async fn unlock(&self, key: &str, token: &str) -> Result<(), RedisError> {
+        let mut conn = self.connection().await?;
+        redis::cmd("EVAL")
+            .arg(UNLOCK_SCRIPT)
+            .arg(1)
+            .arg(key)
+            .arg(token)
+            .query_async::<i64>(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| RedisError::Operation(e.to_string()))
+    }
+}
+
+/// 幂等请求层
+pub struct IdempotencyLayer<T = RedisIdempotencyStore> {
+    store: Arc<T>,
+}
+
+impl<T> Clone for IdempotencyLayer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl IdempotencyLayer {
+    /// 使用 Redis 连接池保存响应和加锁
+    pub fn new(pool: RedisPool) -> Self {
+        Self::with_store(RedisIdempotencyStore::new(pool))
+    }
+}
+
+impl<T> IdempotencyLayer<T> {
+    /// 使用自定义存储
+    pub fn with_store(store: T) -> Self {
+        Self {
+            store: Arc::new(store),
+        }
+    }
+}
+
+impl<S, T> Layer<S> for IdempotencyLayer<T> {
+    type Service = IdempotencyService<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IdempotencyService {
+            inner,
+            store: self.store.clone(),
+        }
+    }
+}
+
+/// [`IdempotencyLayer`] 生成的服务
+pub struct IdempotencyService<S, T = RedisIdempotencyStore> {
+    inner: S,
+    store: Arc<T>,
+}
+
+impl<S: Clone, T> Clone for IdempotencyService<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<S, T> Service<Request> for IdempotencyService<S, T>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    T: IdempotencyStore,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        if !matches!(*request.method(), Method::POST | Method::PATCH)
+            || !request.headers().contains_key(IDEMPOTENCY_KEY_HEADER)
+        {
+            return Box::pin(self.inner.call(request));
+        }
+
+        // 取出已就绪的服务，留下克隆体供下次 poll_ready
+        let clone = self.inner.clone();
+        let inner = std::mem::replace(&mut self.inner, clone);
+        let store = self.store.clone();
+        Box::pin(async move { Ok(handle(store.as_ref(), request, inner).await) })
+    }
+}
+
+/// 请求体指纹
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fingerprint {
+    /// SHA-256（十六进制）
+    digest: String,
+
+    /// 长度
+    len: u64,
+}
+
+/// 转发给处理器的同时计算指纹的请求体，读到结尾后指纹写入 `fingerprint`
+struct FingerprintBody {
+    inner: Body,
+    hasher: Option<(Sha256, u64)>,
+    fingerprint: Arc<OnceLock<Fingerprint>>,
+}
+
+impl FingerprintBody {
+    fn new(inner: Body) -> (Self, Arc<OnceLock<Fingerprint>>) {
+        let fingerprint = Arc::new(OnceLock::new());
+        let mut body = Self {
+            inner,
+            hasher: Some((Sha256::new(), 0)),
+            fingerprint: fingerprint.clone(),
+        };
+        // 空请求体不会被读取，直接得到指纹
+        if body.inner.is_end_stream() {
+            body.finish();
+        }
+        (body, fingerprint)
+    }
+
+    fn finish(&mut self) {
+        if let Some((hasher, len)) = self.hasher.take() {
+            let _ = self.fingerprint.set(Fingerprint {
+                digest: format!("{:x}", hasher.finalize()),
+                len,
+            });
+        }
+    }
+}
+
+impl HttpBody for FingerprintBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, axum::Error>>> {
+        let this = self.get_mut();
+        let frame = ready!(Pin::new(&mut this.inner).poll_frame(cx));
+        match &frame {
+            Some(Ok(frame)) => {
+                if let (Some(data), Some((hasher, len))) = (frame.data_ref(), &mut this.hasher) {
+                    hasher.update(data);
+                    *len += data.len() as u64;
+                }
+                // 按 Content-Length 读取的处理器读完最后一块后不会再轮询到结尾
+                if this.inner.is_end_stream() {
+                    this.finish();
+                }
+            }
+            // 读取失败的请求体没有完整指纹
+            Some(Err(_)) => this.hasher = None,
+            None => this.finish(),
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// 读取请求体并计算指纹，长度超过 `max_len` 时停止读取并返回 `None`
+async fn read_fingerprint(body: Body, max_len: u64) -> Result<Option<Fingerprint>, axum::Error> {
+    let mut stream = body.into_data_stream();
+    let (mut hasher, mut len) = (Sha256::new(), 0);
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        len += chunk.len() as u64;
+        if len > max_len {
+            return Ok(None);
+        }
+        hasher.update(&chunk);
+    }
+    Ok(Some(Fingerprint {
+        digest: format!("{:x}", hasher.finalize()),
+        len,
+    }))
+}
+
+/// 调用方标识：`Authorization` 和 `X-Auth-Key` 请求头的 SHA-256，未携带凭据时为 `anonymous`
+fn caller_scope(request: &Request) -> String {
+    let credentials =
+        [header::AUTHORIZATION.as_str(), API_KEY_HEADER].map(|name| request.headers().get(name));
+    if credentials.iter().all(Option::is_none) {
+        return "anonymous".to_string();
+    }
+
+    let mut hasher = Sha256::new();
+    for value in credentials {
+        hasher.update(value.map_or(&[][..], HeaderValue::as_bytes));
+        // 请求头的值不含 NUL，作为分隔符避免两个值拼接后混淆
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// 校验客户端传入的幂等键：1-255 个可见 ASCII 字符
+fn idempotency_key(request: &Request) -> Result<&str, AppError> {
+    request
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|key| {
+            !key.is_empty() && key.len() <= MAX_KEY_LEN && key.bytes().all(|b| b.is_ascii_graphic())
+        })
+        .ok_or_else(|| {
+            AppError::Validation(ValidationError::custom(format!(
+                "Idempotency-Key 必须为 1-{MAX_KEY_LEN} 个可见 ASCII 字符"
+            )))
+        })
+}
+
+async fn handle<S, T>(store: &T, request: Request, mut inner: S) -> Response
+where
+    S: Service<Request, Response = Response, Error = Infallible>,
+    T: IdempotencyStore,
+{
+    let cache_key = match idempotency_key(&request) {
+        Ok(key) => format!(
+            "idem:{}:{}:{}:{}",
+            request.method(),
+            request.uri().path(),
+            caller_scope(&request),
+            stable_hash(&key)
+        ),
+        Err(e) => return e.into_response(),
+    };
+
+    match store.get(&cache_key).await {
+        Ok(Some(cached)) => return replay(cached, request).await,
+        Ok(None) => {}
+        Err(e) => {
+            warn!(error = %e, "读取幂等响应失败，直接处理请求");
+            return run(&mut inner, request).await;
+        }
+    }
+
+    let lock_key = format!("{cache_key}:lock");
+    let token = generate_token(16);
+    match store.try_lock(&lock_key, &token, LOCK_TTL).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return AppError::Conflict(
+                "A request with the same Idempotency-Key is still being processed".to_string(),
+            )
+            .into_response();
+        }
+        Err(e) => {
+            warn!(error = %e, "获取幂等锁失败，直接处理请求");
+            return run(&mut inner, request).await;
+        }
+    }
+
+    // 检查缓存和加锁之间，持锁的请求可能刚好保存了响应并释放锁
+    let response = match store.get(&cache_key).await {
+        Ok(Some(cached)) => replay(cached, request).await,
+        _ => {
+            let (parts, body) = request.into_parts();
+            let (body, fingerprint) = FingerprintBody::new(body);
+            let response = run(&mut inner, Request::from_parts(parts, Body::new(body))).await;
+            match fingerprint.get() {
+                Some(fingerprint) => save(store, &cache_key, fingerprint, response).await,
+                None => {
+                    debug!("处理器没有读完请求体，不保存响应");
+                    response
+                }
+            }
+        }
+    };
+
+    if let Err(e) = store.unlock(&lock_key, &token).await {
+        warn!(error = %e, "释放幂等锁失败，锁将在超时后自动释放");
+    }
+    response
+}
+
+/// 请求体与首次请求相同时重放保存的响应，不同时返回 422
+async fn replay(cached: IdempotencyCachedResponse, request: Request) -> Response {
+    match read_fingerprint(request.into_body(), cached.request_len).await {
+        Ok(Some(fingerprint)) if fingerprint.digest == cached.request_digest => {
+            cached.into_response()
+        }
+        Ok(_) => AppError::from_status(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Idempotency-Key 已被请求体不同的请求使用",
+        )
+        .into_response(),
+        Err(e) => AppError::from_status(StatusCode::BAD_REQUEST, &format!("读取请求体失败：{e}"))
+            .into_response(),
+    }
+}
+
+async fn run<S>(inner: &mut S, request: Request) -> Response
+where
+    S: Service<Request, Response = Response, Error = Infallible>,
+{
+    match inner.call(request).await {
+        Ok(response) => response,
+        Err(infallible) => match infallible {},
+    }
+}
+
+/// 保存可重放的响应，返回内容相同的响应
+///
+/// 401 / 403 与调用方当时的凭据和权限有关，不保存，重试时重新认证。
+async fn save<T: IdempotencyStore>(
+    store: &T,
+    cache_key: &str,
+    fingerprint: &Fingerprint,
+    response: Response,
+) -> Response {
+    let status = response.status();
+    let cacheable = !status.is_server_error()
+        && status != StatusCode::UNAUTHORIZED
+        && status != StatusCode::FORBIDDEN
+        && response
+            .body()
+            .size_hint()
+            .exact()
+            .is_some_and(|len| len <= MAX_CACHED_BODY_BYTES as u64);
+    if !cacheable {
+        debug!(status = %response.status(), "响应不可重放，不保存");
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, MAX_CACHED_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(error = %e, "读取响应体失败");
+            return AppError::from(anyhow::anyhow!("读取响应体失败：{e}")).into_response();
+        }
+    };
+
+    let cached = IdempotencyCachedResponse {
+        status: parts.status.as_u16(),
+        headers: parts
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect(),
+        body: body.to_vec(),
+        request_digest: fingerprint.digest.clone(),
+        request_len: fingerprint.len,
+    };
+    if let Err(e) = store.put(cache_key, &cached, RESPONSE_TTL).await {
+        warn!(error = %e, "保存幂等响应失败");
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::routing::post;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    /// 内存存储，以序列化后的字节保存响应，与 Redis 的行为一致
+    #[derive(Default)]
+    struct MemoryStore {
+        entries: Mutex<HashMap<String, Vec<u8>>>,
+        locks: Mutex<HashMap<String, String>>,
+    }
+
+    impl IdempotencyStore for MemoryStore {
+        async fn get(&self, key: &str) -> Result<Option<IdempotencyCachedResponse>, RedisError> {
+            Ok(self
+                .entries
+                .lock()
+                .unwrap()
+                .get(key)
+                .map(|b| rmp_serde::from_slice(b).unwrap()))
+        }
+
+        async fn put(
+            &self,
+            key: &str,
+            response: &IdempotencyCachedResponse,
+            _ttl: Duration,
+        ) -> Result<(), RedisError> {
+            self.entries
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), rmp_serde::to_vec(response).unwrap());
+            Ok(())
+        }
+
+        async fn try_lock(
+            &self,
+            key: &str,
+            token: &str,
+            _ttl: Duration,
+        ) -> Result<bool, RedisError> {
+            let mut locks = self.locks.lock().unwrap();
+            if locks.contains_key(key) {
+                return Ok(false);
+            }
+            locks.insert(key.to_string(), token.to_string());
+            Ok(true)
+        }
+
+        async fn unlock(&self, key: &str, token: &str) -> Result<(), RedisError> {
+            let mut locks = self.locks.lock().unwrap();
+            if locks.get(key).is_some_and(|t| t == token) {
+                locks.remove(key);
+            }
+            Ok(())
+        }
+    }
+
+    fn app(store: Arc<MemoryStore>, calls: Arc<AtomicUsize>) -> Router {
+        let denied_calls = calls.clone();
+        Router::new()
+            .route(
+                "/v1/orders",
+                post(move |body: String| async move {
+                    let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                    (
+                        StatusCode::CREATED,
+                        [("x-order", n.to_string())],
+                        format!("order {n}{body}"),
+                    )
+                }),
+            )
+            .route(
+                "/v1/denied",
+                post(move || async move {
+                    denied_calls.fetch_add(1, Ordering::SeqCst);
+                    StatusCode::FORBIDDEN
+                }),
+            )
+            .layer(IdempotencyLayer { store })
+    }
+
+    async fn send(app: &Router, key: Option<&str>) -> Response {
+        let mut request = Request::post("/v1/orders");
+        if let Some(key) = key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        app.clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn send_as(app: &Router, uri: &str, token: &str, body: &'static str) -> Response {
+        let request = Request::post(uri)
+            .header(IDEMPOTENCY_KEY_HEADER, "abc")
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .body(Body::from(body))
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap()
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replays_saved_response_for_same_key() {
+        let store = Arc::new(MemoryStore::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(store.clone(), calls.clone());
+
+        let first = send(&app, Some("abc")).await;
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert!(!first.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER));
+        assert_eq!(body(first).await, "order 1");
+
+        let replay = send(&app, Some("abc")).await;
+        assert_eq!(replay.status(), StatusCode::CREATED);
+        assert_eq!(replay.headers()["x-order"], "1");
+        assert_eq!(replay.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert_eq!(body(replay).await, "order 1");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(store.entries.lock().unwrap().contains_key(&format!(
+            "idem:POST:/v1/orders:anonymous:{}",
+            stable_hash(&"abc")
+        )));
+        assert!(store.locks.lock().unwrap().is_empty());
+
+        // 不同的键和不带键的请求照常执行
+        assert_eq!(body(send(&app, Some("other")).await).await, "order 2");
+        assert_eq!(body(send(&app, None).await).await, "order 3");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_request_with_locked_key_conflicts() {
+        let store = Arc::new(MemoryStore::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(store.clone(), calls.clone());
+        store.locks.lock().unwrap().insert(
+            format!(
+                "idem:POST:/v1/orders:anonymous:{}:lock",
+                stable_hash(&"abc")
+            ),
+            "other".to_string(),
+        );
+
+        let response = send(&app, Some("abc")).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_key_is_scoped_to_caller() {
+        let store = Arc::new(MemoryStore::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(store.clone(), calls.clone());
+
+        let alice = send_as(&app, "/v1/orders", "alice", "").await;
+        assert_eq!(body(alice).await, "order 1");
+
+        // 其他调用方使用相同的键不会拿到 alice 的响应
+        let bob = send_as(&app, "/v1/orders", "bob", "").await;
+        assert!(!bob.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER));
+        assert_eq!(body(bob).await, "order 2");
+
+        let replay = send_as(&app, "/v1/orders", "alice", "").await;
+        assert_eq!(replay.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        assert_eq!(body(replay).await, "order 1");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(store.entries.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_same_key_with_different_body_is_rejected() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(Arc::default(), calls.clone());
+
+        let first = send_as(&app, "/v1/orders", "alice", ": 2 apples").await;
+        assert_eq!(body(first).await, "order 1: 2 apples");
+
+        for other in [": 3 apples", ": 2 apples and more", ""] {
+            let response = send_as(&app, "/v1/orders", "alice", other).await;
+            assert_eq!(
+                response.status(),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "{other:?}"
+            );
+        }
+
+        let replay = send_as(&app, "/v1/orders", "alice", ": 2 apples").await;
+        assert_eq!(body(replay).await, "order 1: 2 apples");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_forbidden_response_is_not_saved() {
+        let store = Arc::new(MemoryStore::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let app = app(store.clone(), calls.clone());
+
+        for _ in 0..2 {
+            let response = send_as(&app, "/v1/denied", "alice", "").await;
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+            assert!(!response.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(store.entries.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_key_is_rejected() {
+        let app = app(Arc::default(), Arc::default());
+        let long = "k".repeat(MAX_KEY_LEN + 1);
+        for key in ["", "has space", long.as_str()] {
+            let response = send(&app, Some(key)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{key:?}");
+        }
+    }
+}
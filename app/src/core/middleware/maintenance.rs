@@ -0,0 +1,162 @@
+//! 维护模式中间件
+//!
+//! 执行数据库结构变更等部署操作时，短暂地对外返回 503，避免客户端看到变更到一半的状态。
+//! 开关为进程内的 [`MaintenanceMode`]，由管理员通过 `POST /v1/admin/maintenance/enable|disable` 切换，
+//! 重启后恢复为关闭；多实例部署时需要逐个实例切换。
+//!
+//! 维护期间健康检查（`/health`、`/health/ready`）照常响应，供负载均衡器判断实例存活；
+//! 维护开关接口本身也不受影响，否则开启后将无法关闭。
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll};
+
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use futures_util::future::BoxFuture;
+use tower::{Layer, Service};
+
+use crate::core::response::{ApiResponse, Domain, Reason};
+
+/// 维护期间返回的消息
+pub const MAINTENANCE_MESSAGE: &str = "Service temporarily unavailable for maintenance";
+
+/// 维护开关接口的路径前缀
+const MAINTENANCE_ROUTE_PREFIX: &str = "/v1/admin/maintenance/";
+
+/// 维护模式开关
+///
+/// 克隆后的实例共享同一个标志。
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceMode {
+    enabled: Arc<AtomicBool>,
+}
+
+impl MaintenanceMode {
+    /// 是否处于维护模式
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// 切换维护模式，返回切换前的状态
+    pub fn set(&self, enabled: bool) -> bool {
+        self.enabled.swap(enabled, Ordering::Relaxed)
+    }
+}
+
+/// 维护模式层，开启维护模式时对健康检查以外的请求返回 503
+#[derive(Debug, Clone)]
+pub struct MaintenanceModeLayer {
+    mode: MaintenanceMode,
+}
+
+impl MaintenanceModeLayer {
+    /// 使用共享的维护模式开关创建
+    pub fn new(mode: MaintenanceMode) -> Self {
+        Self { mode }
+    }
+}
+
+impl<S> Layer<S> for MaintenanceModeLayer {
+    type Service = MaintenanceModeService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MaintenanceModeService {
+            inner,
+            mode: self.mode.clone(),
+        }
+    }
+}
+
+/// [`MaintenanceModeLayer`] 生成的服务
+#[derive(Debug, Clone)]
+pub struct MaintenanceModeService<S> {
+    inner: S,
+    mode: MaintenanceMode,
+}
+
+impl<S> Service<Request> for MaintenanceModeService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        if self.mode.is_enabled() && !is_exempt(request.uri().path()) {
+            return Box::pin(async { Ok(maintenance_response()) });
+        }
+        Box::pin(self.inner.call(request))
+    }
+}
+
+/// 维护期间仍然放行的请求：健康检查和维护开关接口
+fn is_exempt(path: &str) -> bool {
+    path == "/health" || path.starts_with("/health/") || path.starts_with(MAINTENANCE_ROUTE_PREFIX)
+}
+
+fn maintenance_response() -> Response {
+    ApiResponse::<()>::fail_with_message(
+        StatusCode::SERVICE_UNAVAILABLE,
+        Domain::GLOBAL,
+        Reason::ServiceUnavailable,
+        MAINTENANCE_MESSAGE,
+    )
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn status(router: &Router, path: &str) -> StatusCode {
+        router
+            .clone()
+            .oneshot(Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_blocks_all_but_health_checks() {
+        let mode = MaintenanceMode::default();
+        let router = Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .route("/health/ready", get(|| async { "ok" }))
+            .route("/v1/users", get(|| async { "users" }))
+            .route("/v1/admin/maintenance/disable", get(|| async { "off" }))
+            .layer(MaintenanceModeLayer::new(mode.clone()));
+
+        assert_eq!(status(&router, "/v1/users").await, StatusCode::OK);
+
+        assert!(!mode.set(true));
+        let response = router
+            .clone()
+            .oneshot(Request::get("/v1/users").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&body).contains(MAINTENANCE_MESSAGE));
+        for path in ["/health", "/health/ready", "/v1/admin/maintenance/disable"] {
+            assert_eq!(status(&router, path).await, StatusCode::OK, "{path}");
+        }
+
+        assert!(mode.set(false));
+        assert_eq!(status(&router, "/v1/users").await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,148 @@
+//! 全局 HTTP 方法白名单
+//!
+//! 路由层只对已注册路径上未声明的方法返回 405，未匹配的路径则进入 404 回退，`TRACE`、`CONNECT`
+//! 等少见方法会一路走到静态文件处理。本中间件位于路由之前，不在 `server.allowed_methods` 中的方法
+//! 直接返回 405 和 `Allow` 响应头，与各路由自身的方法匹配互为补充。
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::extract::Request;
+use axum::http::{HeaderValue, Method, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use futures_util::future::BoxFuture;
+use tower::{Layer, Service};
+
+use crate::core::response::{ApiResponse, Domain, Reason};
+
+/// 方法白名单层
+#[derive(Debug, Clone)]
+pub struct MethodFilterLayer {
+    allowed: Arc<[Method]>,
+    allow_header: HeaderValue,
+}
+
+impl MethodFilterLayer {
+    /// 只放行 `allowed` 中的方法
+    pub fn new(allowed: Vec<Method>) -> Self {
+        let allow_header = allowed
+            .iter()
+            .map(Method::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        Self {
+            allowed: allowed.into(),
+            allow_header: HeaderValue::from_str(&allow_header)
+                .expect("HTTP 方法名均为合法的响应头字符"),
+        }
+    }
+}
+
+impl<S> Layer<S> for MethodFilterLayer {
+    type Service = MethodFilterService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MethodFilterService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// [`MethodFilterLayer`] 生成的服务
+#[derive(Debug, Clone)]
+pub struct MethodFilterService<S> {
+    inner: S,
+    layer: MethodFilterLayer,
+}
+
+impl<S> Service<Request> for MethodFilterService<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        if !self.layer.allowed.contains(request.method()) {
+            let response = method_not_allowed(request.method(), self.layer.allow_header.clone());
+            return Box::pin(async { Ok(response) });
+        }
+        Box::pin(self.inner.call(request))
+    }
+}
+
+fn method_not_allowed(method: &Method, allow: HeaderValue) -> Response {
+    let mut response = ApiResponse::<()>::fail_with_message(
+        StatusCode::METHOD_NOT_ALLOWED,
+        Domain::GLOBAL,
+        Reason::MethodNotAllowed,
+        format!("Method {method} is not allowed"),
+    )
+    .into_response();
+    response.headers_mut().insert(header::ALLOW, allow);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, routing::any};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn router() -> Router {
+        Router::new()
+            .route("/v1/users", any(|| async { "users" }))
+            .layer(MethodFilterLayer::new(vec![
+                Method::GET,
+                Method::POST,
+                Method::OPTIONS,
+            ]))
+    }
+
+    async fn send(method: Method) -> Response {
+        router()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri("/v1/users")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_trace_is_rejected_with_allow_header() {
+        let response = send(Method::TRACE).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "GET, POST, OPTIONS");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], 405);
+        assert_eq!(body["error"]["errors"][0]["reason"], "METHOD_NOT_ALLOWED");
+    }
+
+    #[tokio::test]
+    async fn test_allowed_methods_reach_routes() {
+        assert_eq!(send(Method::GET).await.status(), StatusCode::OK);
+        assert_eq!(send(Method::OPTIONS).await.status(), StatusCode::OK);
+        // 路由接受任意方法，但不在白名单中的方法不会到达路由
+        assert_eq!(
+            send(Method::CONNECT).await.status(),
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+    }
+}
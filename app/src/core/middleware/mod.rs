@@ -2,10 +2,33 @@
 //!
 //! 提供 HTTP 请求的拦截和处理功能，包括认证、请求追踪等。
 
+/// 路由访问级别（认证中间件与文档标注）
+pub mod access;
+/// API 密钥认证
+pub mod api_key;
 /// JWT 认证中间件
 pub mod auth;
+/// 表单请求的 CSRF 防护
+pub mod csrf;
+/// 5xx 响应的错误上下文链日志
+pub mod error_context;
+/// 幂等请求（相同 Idempotency-Key 的重试重放首次响应）
+pub mod idempotency;
+/// 维护模式（维护期间返回 503）
+pub mod maintenance;
+/// 全局 HTTP 方法白名单（其他方法返回 405）
+pub mod method_filter;
 /// 请求 ID 生成和追踪中间件
 pub mod request_id;
+/// 请求追踪 span 与访问日志
+pub mod trace;
 
+pub use access::{Access, AccessRouter};
+pub use api_key::{ApiKeyAuth, ApiKeys};
 pub use auth::*;
+pub use csrf::CsrfLayer;
+pub use error_context::{ErrorChain, error_context_middleware};
+pub use idempotency::IdempotencyLayer;
+pub use maintenance::{MaintenanceMode, MaintenanceModeLayer};
+pub use method_filter::MethodFilterLayer;
 pub use request_id::*;
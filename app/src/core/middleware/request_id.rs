@@ -1,23 +1,208 @@
-use axum::extract::Request;
-use axum::http::HeaderValue;
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderValue, StatusCode};
 use axum::middleware::Next;
-use axum::response::Response;
+use axum::response::{IntoResponse, Response};
+use tracing::warn;
 use uuid::Uuid;
 
-pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
-    let request_id = Uuid::new_v4().to_string();
+use crate::AppError;
+use crate::core::config::RequestIdConfig;
+
+/// 请求 ID 请求头
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// 上游传入的请求 ID 的最大长度
+const MAX_INCOMING_ID_LEN: usize = 128;
+
+/// 请求 ID 的生成与沿用策略（由 `request_id` 配置创建）
+#[derive(Debug, Clone, Default)]
+pub struct RequestIdPolicy {
+    strict: bool,
+
+    /// 去掉末尾 `/` 的内部路由前缀
+    internal_prefixes: Arc<[String]>,
+}
+
+impl RequestIdPolicy {
+    pub fn new(config: &RequestIdConfig) -> Self {
+        Self {
+            strict: config.strict,
+            internal_prefixes: config
+                .internal_route_prefixes
+                .iter()
+                .map(|p| p.trim_end_matches('/').to_string())
+                .collect(),
+        }
+    }
+
+    /// 路径是否属于内部路由，按路径段匹配前缀
+    fn is_internal(&self, path: &str) -> bool {
+        self.internal_prefixes.iter().any(|prefix| {
+            path.strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+/// 请求 ID 中间件
+///
+/// 公开路由总是生成新的请求 ID，忽略客户端传入的值；内部路由沿用上游传入的有效请求 ID，
+/// 缺少时按策略生成（宽松模式）或返回 400（严格模式）。请求 ID 写入请求头供后续中间件和日志使用，
+/// 并在响应头中返回（包括被拒绝的请求）。
+pub async fn request_id_middleware(
+    State(policy): State<RequestIdPolicy>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path();
+    let incoming = if policy.is_internal(path) {
+        let incoming = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .filter(|id| is_valid_incoming(id))
+            .map(String::from);
+        if incoming.is_none() && policy.strict {
+            warn!(path, "内部路由的请求缺少有效的 x-request-id，已拒绝");
+            let response = AppError::from_status(
+                StatusCode::BAD_REQUEST,
+                "内部路由的请求必须携带有效的 x-request-id 请求头",
+            )
+            .into_response();
+            return with_request_id(response, &Uuid::new_v4().to_string());
+        }
+        incoming
+    } else {
+        None
+    };
+    let request_id = incoming.unwrap_or_else(|| Uuid::new_v4().to_string());
 
     // 将请求ID添加到请求头中
     if let Ok(header_value) = HeaderValue::from_str(&request_id) {
-        request.headers_mut().insert("x-request-id", header_value);
+        request
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER, header_value);
     }
 
-    let mut response = next.run(request).await;
+    let response = next.run(request).await;
 
     // 将请求ID添加到响应头中
-    if let Ok(header_value) = HeaderValue::from_str(&request_id) {
-        response.headers_mut().insert("x-request-id", header_value);
-    }
+    with_request_id(response, &request_id)
+}
 
+fn with_request_id(mut response: Response, request_id: &str) -> Response {
+    if let Ok(header_value) = HeaderValue::from_str(request_id) {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER, header_value);
+    }
     response
 }
+
+/// 上游传入的请求 ID 只接受不超过 128 个可见 ASCII 字符，避免日志注入
+fn is_valid_incoming(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_INCOMING_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::HeaderMap;
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    /// 返回处理器看到的请求 ID
+    async fn echo(headers: HeaderMap) -> String {
+        headers[REQUEST_ID_HEADER].to_str().unwrap().to_string()
+    }
+
+    fn app(strict: bool) -> Router {
+        let policy = RequestIdPolicy::new(&RequestIdConfig {
+            strict,
+            internal_route_prefixes: vec!["/v1/internal/".to_string()],
+        });
+        Router::new()
+            .route("/v1/internal/jobs", get(echo))
+            .route("/v1/internals", get(echo))
+            .route("/v1/users", get(echo))
+            .layer(axum::middleware::from_fn_with_state(
+                policy,
+                request_id_middleware,
+            ))
+    }
+
+    async fn send(
+        app: Router,
+        path: &str,
+        request_id: Option<&str>,
+    ) -> (StatusCode, String, String) {
+        let mut request = Request::builder().uri(path);
+        if let Some(id) = request_id {
+            request = request.header(REQUEST_ID_HEADER, id);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let header = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, header, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_internal_routes_keep_incoming_id() {
+        for strict in [false, true] {
+            let (status, header, seen) =
+                send(app(strict), "/v1/internal/jobs", Some("upstream-42")).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(header, "upstream-42");
+            assert_eq!(seen, "upstream-42");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_public_routes_always_generate_id() {
+        for strict in [false, true] {
+            for path in ["/v1/users", "/v1/internals"] {
+                let (status, header, seen) = send(app(strict), path, Some("client-id")).await;
+                assert_eq!(status, StatusCode::OK);
+                assert_ne!(header, "client-id");
+                assert_eq!(header, seen);
+                assert!(Uuid::parse_str(&header).is_ok());
+
+                let (status, _, _) = send(app(strict), path, None).await;
+                assert_eq!(status, StatusCode::OK);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lenient_mode_generates_missing_internal_id() {
+        for request_id in [None, Some("has space"), Some(&*"x".repeat(129))] {
+            let (status, header, seen) = send(app(false), "/v1/internal/jobs", request_id).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(header, seen);
+            assert!(Uuid::parse_str(&header).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strict_mode_rejects_missing_internal_id() {
+        for request_id in [None, Some("has space")] {
+            let (status, header, body) = send(app(true), "/v1/internal/jobs", request_id).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(Uuid::parse_str(&header).is_ok());
+            assert!(body.contains("x-request-id"), "{body}");
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! HTTP 请求追踪
+//!
+//! 为 `TraceLayer` 提供 span 构建和响应日志钩子：每个请求结束时在 span 上记录
+//! 状态码、响应体大小和耗时，并输出一行 `GET /v1/user/me → 200 (3ms)` 形式的访问日志。
+
+use std::time::Duration;
+
+use axum::body::{Body, HttpBody};
+use axum::extract::Request;
+use axum::http::{Method, Response, header::CONTENT_LENGTH};
+use axum::middleware::Next;
+use tracing::{Level, Span, field};
+
+/// 请求行信息（方法和路径）
+///
+/// `TraceLayer` 的响应钩子拿不到原始请求，由 [`request_line_middleware`]
+/// 在内层把请求行写入响应扩展，供 [`on_response`] 读取。
+#[derive(Debug, Clone)]
+pub struct RequestLine {
+    pub method: Method,
+    pub path: String,
+}
+
+/// 将请求方法和路径写入响应扩展
+///
+/// 必须放在 `TraceLayer` 内层（ServiceBuilder 中位于其后）。
+pub async fn request_line_middleware(request: Request, next: Next) -> Response<Body> {
+    let line = RequestLine {
+        method: request.method().clone(),
+        path: request.uri().path().to_string(),
+    };
+
+    let mut response = next.run(request).await;
+    response.extensions_mut().insert(line);
+    response
+}
+
+/// 为每个请求创建追踪 span
+///
+/// span 使用 INFO 级别，确保默认日志级别下请求内的日志也能携带 `request_id` 等字段。
+/// 响应相关字段先声明为空，由 [`on_response`] 在请求结束时填充；
+/// `user_id` 由认证中间件在识别用户后填充，`api_key` 由 API 密钥认证填充。
+pub fn make_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("unknown");
+    tracing::span!(
+        Level::INFO,
+        "request",
+        method = display(request.method()),
+        uri = display(request.uri()),
+        version = debug(request.version()),
+        request_id = request_id,
+        user_id = field::Empty,
+        api_key = field::Empty,
+        http.status_code = field::Empty,
+        http.response_content_length = field::Empty,
+        latency_ms = field::Empty,
+    )
+}
+
+/// 响应钩子：记录状态码、响应体大小和耗时，并输出访问日志
+pub fn on_response(response: &Response<Body>, latency: Duration, span: &Span) {
+    let status = response.status().as_u16();
+    let latency_ms = latency.as_millis() as u64;
+
+    span.record("http.status_code", status);
+    span.record("latency_ms", latency_ms);
+    if let Some(length) = content_length(response) {
+        span.record("http.response_content_length", length);
+    }
+
+    match response.extensions().get::<RequestLine>() {
+        Some(line) => tracing::info!(
+            "{} {} → {} ({}ms)",
+            line.method,
+            line.path,
+            status,
+            latency_ms
+        ),
+        None => tracing::info!("→ {} ({}ms)", status, latency_ms),
+    }
+}
+
+/// 响应体大小：优先读取 `Content-Length` 响应头，否则使用响应体的确切长度（流式响应为空）
+fn content_length(response: &Response<Body>) -> Option<u64> {
+    response
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .or_else(|| response.body().size_hint().exact())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_length_from_body_size_hint() {
+        let response = Response::new(Body::from("hello"));
+        assert_eq!(content_length(&response), Some(5));
+
+        let response = Response::builder()
+            .header(CONTENT_LENGTH, "42")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(content_length(&response), Some(42));
+    }
+}
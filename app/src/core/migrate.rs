@@ -0,0 +1,213 @@
+//! 启动时的数据库迁移
+//!
+//! 多个实例同时启动时都会执行 `Migrator::up`，并发执行同一个迁移可能重复建表，或在记录迁移版本时冲突。
+//! 连接 PostgreSQL 且开启 `database.migration_lock`（默认开启）时，迁移前在一个专用连接上获取会话级
+//! advisory lock（`pg_advisory_lock`）：同一时间只有一个实例执行迁移，其他实例阻塞等待，拿到锁后再执行
+//! `Migrator::up` 时已没有待执行的迁移，直接继续启动。持有锁的实例崩溃时连接断开，锁随会话自动释放。
+//!
+//! 单实例部署可以关闭 `migration_lock`；SQLite 等其他数据库不加锁。
+
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend, DbErr, Statement,
+};
+use tracing::{info, warn};
+
+use crate::core::config::DatabaseConfig;
+
+/// 迁移使用的 advisory lock 键（"dropbudd" 的 ASCII 编码），与同一数据库上的其他 advisory lock 区分
+pub const MIGRATION_LOCK_KEY: i64 = 0x6472_6f70_6275_6464;
+
+/// 执行所有待执行的迁移
+///
+/// 按 `database.migration_lock` 配置在多个实例之间加锁，见[模块文档](self)。
+pub async fn run_migrations(db: &DatabaseConnection, config: &DatabaseConfig) -> Result<(), DbErr> {
+    if !config.migration_lock || db.get_database_backend() != DbBackend::Postgres {
+        return Migrator::up(db, None).await;
+    }
+
+    // advisory lock 属于会话，加锁和解锁必须在同一个连接上，因此单独建立只有一个连接的连接池
+    let mut options = ConnectOptions::new(config.connection_url());
+    options
+        .max_connections(1)
+        .min_connections(1)
+        .sqlx_logging(false);
+    let lock = AdvisoryLock {
+        conn: Database::connect(options).await?,
+    };
+    let result = migrate_locked(db, &lock).await;
+    if let Err(e) = lock.conn.close().await {
+        warn!(error = %e, "关闭迁移锁连接失败");
+    }
+    result
+}
+
+/// 迁移期间持有的跨实例互斥锁
+trait MigrationLock {
+    /// 尝试立即获取锁，已被其他实例持有时返回 `false`
+    async fn try_lock(&self) -> Result<bool, DbErr>;
+
+    /// 等待直到获取锁
+    async fn lock(&self) -> Result<(), DbErr>;
+
+    /// 释放锁
+    async fn unlock(&self) -> Result<(), DbErr>;
+}
+
+/// 持有 `lock` 执行迁移，迁移失败时同样释放锁
+async fn migrate_locked<L: MigrationLock>(db: &DatabaseConnection, lock: &L) -> Result<(), DbErr> {
+    if !lock.try_lock().await? {
+        info!("其他实例正在执行数据库迁移，等待其完成");
+        lock.lock().await?;
+    }
+    let result = Migrator::up(db, None).await;
+    let unlocked = lock.unlock().await;
+    result.and(unlocked)
+}
+
+/// PostgreSQL 会话级 advisory lock
+struct AdvisoryLock {
+    /// 只有一个连接的连接池，保证加锁和解锁在同一个会话中
+    conn: DatabaseConnection,
+}
+
+impl AdvisoryLock {
+    fn statement(sql: &str) -> Statement {
+        Statement::from_sql_and_values(DbBackend::Postgres, sql, [MIGRATION_LOCK_KEY.into()])
+    }
+}
+
+impl MigrationLock for AdvisoryLock {
+    async fn try_lock(&self) -> Result<bool, DbErr> {
+        let row = self
+            .conn
+            .query_one(Self::statement("SELECT pg_try_advisory_lock($1) AS locked"))
+            .await?;
+        match row {
+            Some(row) => row.try_get("", "locked"),
+            None => Ok(false),
+        }
+    }
+
+    async fn lock(&self) -> Result<(), DbErr> {
+        self.conn
+            .execute(Self::statement("SELECT pg_advisory_lock($1)"))
+            .await?;
+        Ok(())
+    }
+
+    async fn unlock(&self) -> Result<(), DbErr> {
+        self.conn
+            .execute(Self::statement("SELECT pg_advisory_unlock($1)"))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use tokio::sync::Semaphore;
+
+    /// 进程内的互斥锁，模拟多个实例共享的 advisory lock
+    struct LocalLock {
+        permits: Semaphore,
+        held: AtomicBool,
+        waited: AtomicUsize,
+    }
+
+    impl LocalLock {
+        fn new() -> Self {
+            Self {
+                permits: Semaphore::new(1),
+                held: AtomicBool::new(false),
+                waited: AtomicUsize::new(0),
+            }
+        }
+
+        fn acquired(&self) {
+            assert!(!self.held.swap(true, Ordering::SeqCst), "锁被同时持有");
+        }
+    }
+
+    impl MigrationLock for LocalLock {
+        async fn try_lock(&self) -> Result<bool, DbErr> {
+            match self.permits.try_acquire() {
+                Ok(permit) => {
+                    permit.forget();
+                    self.acquired();
+                    Ok(true)
+                }
+                Err(_) => {
+                    self.waited.fetch_add(1, Ordering::SeqCst);
+                    Ok(false)
+                }
+            }
+        }
+
+        async fn lock(&self) -> Result<(), DbErr> {
+            self.permits.acquire().await.unwrap().forget();
+            self.acquired();
+            Ok(())
+        }
+
+        async fn unlock(&self) -> Result<(), DbErr> {
+            self.held.store(false, Ordering::SeqCst);
+            self.permits.add_permits(1);
+            Ok(())
+        }
+    }
+
+    async fn connect(path: &std::path::Path) -> DatabaseConnection {
+        let mut options = ConnectOptions::new(format!("sqlite://{}?mode=rwc", path.display()));
+        options.sqlx_logging(false);
+        Database::connect(options).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_migrations_run_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        // 两个实例各自的连接池，指向同一个数据库
+        let (first, second) = (connect(&path).await, connect(&path).await);
+        let lock = LocalLock::new();
+
+        let (a, b) = tokio::join!(
+            migrate_locked(&first, &lock),
+            migrate_locked(&second, &lock)
+        );
+        a.unwrap();
+        b.unwrap();
+        assert_eq!(lock.waited.load(Ordering::SeqCst), 1, "后启动的实例应等待");
+        assert!(!lock.held.load(Ordering::SeqCst), "迁移结束后应释放锁");
+
+        let applied = Migrator::get_applied_migrations(&second).await.unwrap();
+        assert_eq!(applied.len(), Migrator::migrations().len());
+        assert!(
+            Migrator::get_pending_migrations(&first)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrations_without_lock_on_sqlite() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = connect(&dir.path().join("app.db")).await;
+        let config = DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            ..Default::default()
+        };
+        // SQLite 不加锁，重复执行时没有待执行的迁移
+        run_migrations(&db, &config).await.unwrap();
+        run_migrations(&db, &config).await.unwrap();
+        assert!(
+            Migrator::get_pending_migrations(&db)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
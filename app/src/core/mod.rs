@@ -2,23 +2,58 @@
 //!
 //! 包含配置、日志、中间件、应用状态等核心功能。
 
+pub mod alerting;
+mod body_limit;
 pub mod config;
 mod cors;
+#[cfg(feature = "sentry")]
+pub mod error_reporting;
+pub mod feature_flags;
+mod health;
+pub mod latency;
+mod log_format;
+mod log_sampling;
+mod log_scrub;
 mod logging;
 pub mod middleware;
+pub mod migrate;
 mod rate_limit;
+pub mod read_retry;
 pub mod response;
 pub mod state;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod tx;
+pub mod validated;
 
-/// 应用全局配置
-pub use config::AppConfig;
+/// 路由级请求体大小限制
+pub use body_limit::RouteBodyLimits;
+/// 应用全局配置及命令行覆盖项
+pub use config::{AppConfig, ConfigOverrides};
 /// CORS 跨域配置构建函数
 pub use cors::build_cors_layer;
+/// 可选功能模块开关
+pub use feature_flags::FeatureFlags;
+/// 就绪检查结果及其缓存
+pub use health::{HealthGuard, IpNetwork, Readiness, ReadinessCache};
+/// 日志时间戳时区
+pub use log_format::LogTimezone;
+/// 运行时日志级别控制句柄
+pub use logging::LogLevelHandle;
 /// 旧日志文件清理函数
 pub use logging::cleanup_old_logs;
-/// 速率限制错误处理函数
-pub use rate_limit::handle_rate_limit_error;
+/// 速率限制错误处理函数、全局限流中间件和配额状态
+pub use rate_limit::{
+    GLOBAL_RATE_LIMIT_BURST, GLOBAL_RATE_LIMIT_PERIOD_SECS, GlobalRateLimit, RateLimitStatus,
+    handle_rate_limit_error, rate_limit_middleware,
+};
+/// 只读操作的数据库瞬时错误重试
+pub use read_retry::ReadRetry;
 /// 标准 API 响应格式
 pub use response::{API_VERSION, ApiResponse, Domain, ErrorDetail};
 /// 应用状态（包含数据库、Redis等）
 pub use state::AppState;
+/// 请求级数据库事务提取器
+pub use tx::Tx;
+/// 带校验的 JSON 请求体提取器
+pub use validated::Validated;
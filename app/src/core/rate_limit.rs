@@ -1,6 +1,104 @@
-use axum::http::StatusCode;
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
+use governor::clock::{Clock, DefaultClock};
+use governor::middleware::{StateInformationMiddleware, StateSnapshot};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use tower_governor::GovernorError;
+use tower_governor::governor::GovernorConfig;
+use tower_governor::key_extractor::{KeyExtractor, PeerIpKeyExtractor};
+
+/// 全局限流的令牌补充间隔（秒），每个间隔为客户端补充一个请求配额
+pub const GLOBAL_RATE_LIMIT_PERIOD_SECS: u64 = 10;
+
+/// 全局限流的令牌桶容量（突发请求上限）
+pub const GLOBAL_RATE_LIMIT_BURST: u32 = 20;
+
+/// 全局限流配置（按客户端 IP 限流，记录配额状态）
+pub type GlobalRateLimit = GovernorConfig<PeerIpKeyExtractor, StateInformationMiddleware>;
+
+/// 当前客户端的限流配额状态
+///
+/// 由 [`rate_limit_middleware`] 在放行请求时写入请求扩展，处理器可直接提取。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct RateLimitStatus {
+    /// 令牌桶容量（突发请求上限）
+    pub limit: u32,
+
+    /// 本次请求后剩余的可用请求数
+    pub remaining: u32,
+
+    /// 令牌桶完全恢复所需的秒数
+    pub reset_after_secs: u64,
+}
+
+impl RateLimitStatus {
+    fn from_snapshot(snapshot: &StateSnapshot) -> Self {
+        let quota = snapshot.quota();
+        let limit = quota.burst_size().get();
+        let remaining = snapshot.remaining_burst_capacity();
+        let reset_after = quota.replenish_interval() * (limit - remaining);
+        Self {
+            limit,
+            remaining,
+            reset_after_secs: reset_after.as_secs_f64().ceil() as u64,
+        }
+    }
+}
+
+/// 全局限流中间件
+///
+/// 与 `tower_governor::GovernorLayer` 行为一致（按 IP 计数、超限返回 429 并附带
+/// `retry-after` 等响应头），额外把本次检查得到的 [`RateLimitStatus`] 写入请求扩展，
+/// 使配额查询接口无需再次消耗令牌。
+pub async fn rate_limit_middleware(
+    State(config): State<Arc<GlobalRateLimit>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let key = match PeerIpKeyExtractor.extract(&request) {
+        Ok(key) => key,
+        Err(err) => return handle_rate_limit_error(err).await,
+    };
+
+    match config.limiter().check_key(&key) {
+        Ok(snapshot) => {
+            let status = RateLimitStatus::from_snapshot(&snapshot);
+            request.extensions_mut().insert(status);
+
+            let mut response = next.run(request).await;
+            let headers = response.headers_mut();
+            headers.insert("x-ratelimit-limit", HeaderValue::from(status.limit));
+            headers.insert("x-ratelimit-remaining", HeaderValue::from(status.remaining));
+            response
+        }
+        Err(not_until) => {
+            let wait_time = not_until
+                .wait_time_from(DefaultClock::default().now())
+                .as_secs();
+            tracing::info!(ip = %key, wait_time, "请求超出速率限制");
+
+            let mut headers = HeaderMap::new();
+            headers.insert("x-ratelimit-after", wait_time.into());
+            headers.insert("retry-after", wait_time.into());
+            headers.insert(
+                "x-ratelimit-limit",
+                not_until.quota().burst_size().get().into(),
+            );
+            headers.insert("x-ratelimit-remaining", 0.into());
+
+            handle_rate_limit_error(GovernorError::TooManyRequests {
+                wait_time,
+                headers: Some(headers),
+            })
+            .await
+        }
+    }
+}
 
 /// 速率限制错误处理
 ///
@@ -47,3 +145,67 @@ pub async fn handle_rate_limit_error(err: GovernorError) -> Response {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension, Json, Router, body::Body, extract::ConnectInfo, middleware, routing::get,
+    };
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+    use tower_governor::governor::GovernorConfigBuilder;
+
+    async fn status(Extension(status): Extension<RateLimitStatus>) -> Json<RateLimitStatus> {
+        Json(status)
+    }
+
+    fn router() -> Router {
+        let config = Arc::new(
+            GovernorConfigBuilder::default()
+                .per_second(60)
+                .burst_size(3)
+                .use_headers()
+                .finish()
+                .unwrap(),
+        );
+        Router::new()
+            .route("/status", get(status))
+            .layer(middleware::from_fn_with_state(
+                config,
+                rate_limit_middleware,
+            ))
+    }
+
+    async fn call(router: &Router) -> Response {
+        let mut request = Request::get("/status").body(Body::empty()).unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))));
+        router.clone().oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_remaining_decreases_across_requests() {
+        let router = router();
+
+        let mut remaining = Vec::new();
+        for _ in 0..3 {
+            let response = call(&router).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()["x-ratelimit-limit"], "3");
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let status: RateLimitStatus = serde_json::from_slice(&body).unwrap();
+            assert_eq!(status.limit, 3);
+            assert!(status.reset_after_secs > 0);
+            remaining.push(status.remaining);
+        }
+        assert_eq!(remaining, vec![2, 1, 0]);
+
+        let response = call(&router).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("retry-after"));
+    }
+}
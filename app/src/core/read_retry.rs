@@ -0,0 +1,181 @@
+//! 只读操作的数据库瞬时错误重试
+//!
+//! 连接被重置等瞬时错误通常在换一个连接后即可恢复。GET 处理器可以用 [`ReadRetry::run`]
+//! 包裹只读的服务调用，命中 `database.read_retry_on` 中的错误时等待 `database.read_retry_delay_ms`
+//! 后重试一次：
+//!
+//! ```ignore
+//! let user = state
+//!     .read_retry
+//!     .run(|| user_service.get_user(current_user.user_id))
+//!     .await?;
+//! ```
+//!
+//! 只用于没有副作用的读操作：写操作失败时可能已经在数据库中生效，重试会重复执行。
+
+use std::future::Future;
+use std::time::Duration;
+
+use sea_orm::{ConnAcquireErr, DbErr, RuntimeErr, sqlx};
+use tracing::warn;
+
+use crate::AppError;
+use crate::core::config::{DatabaseConfig, TransientDbError};
+
+/// 可以从中取出数据库错误的错误类型
+pub trait AsDbErr {
+    /// 数据库错误，其他错误返回 `None`
+    fn as_db_err(&self) -> Option<&DbErr>;
+}
+
+impl AsDbErr for DbErr {
+    fn as_db_err(&self) -> Option<&DbErr> {
+        Some(self)
+    }
+}
+
+impl AsDbErr for AppError {
+    fn as_db_err(&self) -> Option<&DbErr> {
+        match self {
+            AppError::Database(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl TransientDbError {
+    /// 数据库错误是否属于这一类瞬时错误
+    pub fn matches(self, err: &DbErr) -> bool {
+        let sqlx_err = match err {
+            DbErr::Conn(RuntimeErr::SqlxError(e))
+            | DbErr::Exec(RuntimeErr::SqlxError(e))
+            | DbErr::Query(RuntimeErr::SqlxError(e)) => Some(e),
+            _ => None,
+        };
+        match self {
+            Self::AcquireTimeout => {
+                matches!(err, DbErr::ConnectionAcquire(ConnAcquireErr::Timeout))
+                    || matches!(sqlx_err, Some(sqlx::Error::PoolTimedOut))
+            }
+            Self::ConnectionClosed => {
+                matches!(
+                    err,
+                    DbErr::ConnectionAcquire(ConnAcquireErr::ConnectionClosed)
+                ) || matches!(sqlx_err, Some(sqlx::Error::WorkerCrashed))
+            }
+            Self::Io => matches!(sqlx_err, Some(sqlx::Error::Io(_))),
+        }
+    }
+}
+
+/// 只读操作的重试策略（由 `database` 配置创建，启动后不可变）
+#[derive(Debug, Clone, Default)]
+pub struct ReadRetry {
+    /// 需要重试的瞬时错误，为空时不重试
+    errors: Vec<TransientDbError>,
+
+    /// 重试前的等待时间
+    delay: Duration,
+}
+
+impl ReadRetry {
+    /// 按数据库配置创建
+    pub fn new(config: &DatabaseConfig) -> Self {
+        Self {
+            errors: config.read_retry_on.clone(),
+            delay: Duration::from_millis(config.read_retry_delay_ms),
+        }
+    }
+
+    /// 错误是否为配置中的瞬时数据库错误
+    pub fn is_transient<E: AsDbErr>(&self, err: &E) -> bool {
+        err.as_db_err()
+            .is_some_and(|e| self.errors.iter().any(|kind| kind.matches(e)))
+    }
+
+    /// 执行只读操作，遇到瞬时数据库错误时重试一次
+    pub async fn run<T, E, F, Fut>(&self, mut op: F) -> Result<T, E>
+    where
+        E: AsDbErr + std::fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        match op().await {
+            Err(e) if self.is_transient(&e) => {
+                warn!(error = %e, "只读操作遇到瞬时数据库错误，重试一次");
+                tokio::time::sleep(self.delay).await;
+                op().await
+            }
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn retry() -> ReadRetry {
+        ReadRetry::new(&DatabaseConfig {
+            read_retry_delay_ms: 0,
+            ..Default::default()
+        })
+    }
+
+    fn connection_reset() -> DbErr {
+        DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Io(
+            std::io::ErrorKind::ConnectionReset.into(),
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_retries_once_after_transient_error() {
+        let calls = AtomicUsize::new(0);
+        let result: Result<&str, AppError> = retry()
+            .run(|| async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(AppError::Database(connection_reset())),
+                    _ => Ok("alice"),
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), "alice");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // 重试仍然失败时返回第二次的错误，不再继续重试
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<(), DbErr> = retry()
+            .run(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(connection_reset())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_are_not_retried() {
+        let calls = AtomicUsize::new(0);
+        let result: Result<(), AppError> = retry()
+            .run(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(AppError::Database(DbErr::RecordNotFound(
+                    "user".to_string(),
+                )))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // 未配置的瞬时错误也不重试
+        let retry = ReadRetry::new(&DatabaseConfig {
+            read_retry_on: vec![TransientDbError::AcquireTimeout],
+            read_retry_delay_ms: 0,
+            ..Default::default()
+        });
+        assert!(!retry.is_transient(&connection_reset()));
+        assert!(retry.is_transient(&DbErr::ConnectionAcquire(ConnAcquireErr::Timeout)));
+    }
+}
@@ -2,11 +2,14 @@
 //!
 //! 遵循 Google JSON Style Guide 的响应格式。
 
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use aide::OperationOutput;
 use aide::generate::GenContext;
 use aide::openapi::Operation;
 use axum::Json;
-use axum::http::StatusCode;
+use axum::http::{StatusCode, header};
 use axum::response::{IntoResponse, Response};
 use indexmap::IndexMap;
 use schemars::JsonSchema;
@@ -17,6 +20,16 @@ use super::{ApiError, Domain, ErrorDetail, Reason};
 /// API 版本号
 pub const API_VERSION: &str = "1.0";
 
+/// 是否格式化输出 JSON 响应，由 [`set_pretty_json`] 在应用启动时设置
+static PRETTY_JSON: AtomicBool = AtomicBool::new(false);
+
+/// 设置 JSON 响应是否格式化（缩进）输出
+///
+/// 开发环境下便于手动调试；默认关闭，保持紧凑输出。
+pub fn set_pretty_json(enabled: bool) {
+    PRETTY_JSON.store(enabled, Ordering::Relaxed);
+}
+
 /// API 响应
 ///
 /// 遵循 Google JSON Style Guide，响应要么包含 `data`，要么包含 `error`。
@@ -39,6 +52,10 @@ pub struct ApiResponse<T: Serialize> {
     /// 错误信息
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<ApiError>,
+
+    /// 按字段分组的校验错误（仅请求参数校验失败时存在），便于表单逐字段展示
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<BTreeMap<String, Vec<String>>>,
 }
 
 /// Data 对象包装器
@@ -168,6 +185,7 @@ impl<T: Serialize> ApiResponse<T> {
                 content: DataContent::Single(data),
             }),
             error: None,
+            errors: None,
         }
     }
 
@@ -220,6 +238,7 @@ impl<T: Serialize> ApiResponse<T> {
                 })),
             }),
             error: None,
+            errors: None,
         }
     }
 
@@ -259,6 +278,7 @@ impl<T: Serialize> ApiResponse<T> {
                 })),
             }),
             error: None,
+            errors: None,
         }
     }
 
@@ -431,6 +451,33 @@ impl ApiResponse<()> {
             api_version: API_VERSION.to_string(),
             data: None,
             error: Some(error),
+            errors: None,
+        }
+    }
+
+    /// 创建按字段分组的校验错误响应（400）
+    ///
+    /// `errors` 原样输出到响应顶层，同时为每条消息生成带 `location` 的错误详情。
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let errors = BTreeMap::from([("email".to_string(), vec!["is invalid".to_string()])]);
+    /// let response = ApiResponse::validation_failed(errors);
+    /// ```ignore
+    pub fn validation_failed(errors: BTreeMap<String, Vec<String>>) -> Self {
+        let details = errors.iter().flat_map(|(field, messages)| {
+            messages.iter().map(move |message| {
+                ErrorDetail::with_message(Domain::VALIDATION, Reason::InvalidFormat, message)
+                    .at(field, "body")
+            })
+        });
+        let error =
+            ApiError::new(StatusCode::BAD_REQUEST, "请求参数校验失败").with_details(details);
+
+        Self {
+            errors: Some(errors),
+            ..Self::error(error)
         }
     }
 
@@ -587,10 +634,26 @@ impl ApiResponse<()> {
     }
 }
 
+impl<T: Serialize> ApiResponse<T> {
+    /// 序列化为 HTTP 响应，`pretty` 为 true 时输出缩进格式的 JSON
+    fn render(self, pretty: bool) -> Response {
+        let status = self.status_code();
+        if !pretty {
+            return (status, Json(self)).into_response();
+        }
+        match serde_json::to_vec_pretty(&self) {
+            Ok(body) => {
+                (status, [(header::CONTENT_TYPE, "application/json")], body).into_response()
+            }
+            // 与 `Json` 的失败行为保持一致
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+}
+
 impl<T: Serialize> IntoResponse for ApiResponse<T> {
     fn into_response(self) -> Response {
-        let status = self.status_code();
-        (status, Json(self)).into_response()
+        self.render(PRETTY_JSON.load(Ordering::Relaxed))
     }
 }
 
@@ -631,3 +694,33 @@ impl<T: Serialize + JsonSchema> OperationOutput for ApiResponse<T> {
         Vec::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_of(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_render_pretty_json() {
+        let response = ApiResponse::success(serde_json::json!({ "name": "alice" })).render(true);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        let body = body_of(response).await;
+        assert!(body.contains("\n  \"api_version\": \"1.0\""), "{body}");
+        assert!(body.contains("\"name\": \"alice\""), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_render_compact_json() {
+        let response = ApiResponse::success(serde_json::json!({ "name": "alice" })).render(false);
+        let body = body_of(response).await;
+        assert!(!body.contains('\n'), "{body}");
+        assert!(body.contains("\"name\":\"alice\""), "{body}");
+    }
+}
@@ -63,9 +63,40 @@
 //! }
 //! ```
 //!
+//! ### 表单校验错误
+//!
+//! 请求参数校验失败时（见 [`Validated`](crate::core::validated::Validated)），
+//! 顶层额外附带按字段分组的 `errors`：
+//!
+//! ```json
+//! {
+//!   "api_version": "1.0",
+//!   "error": {
+//!     "code": 400,
+//!     "message": "请求参数校验失败",
+//!     "errors": [{
+//!       "domain": "validation",
+//!       "reason": "INVALID_FORMAT",
+//!       "message": "is invalid",
+//!       "location": "email",
+//!       "location_type": "body"
+//!     }]
+//!   },
+//!   "errors": {
+//!     "email": ["is invalid"],
+//!     "password": ["is too short"]
+//!   }
+//! }
+//! ```
+//!
 //! ## 核心类型
 //!
 //! - [`ApiResponse`] - 统一响应包装器
+//! - [`PaginatedResponse`] - 分页响应（附带 `X-Total-Count` 响应头）
+//! - [`CursorPage`] - 游标分页响应（无限滚动，翻页期间插入数据不会重复或遗漏）
+//! - [`StreamingListResponse`] - 流式列表响应（大列表边读边写，不缓冲整个列表）
+//! - [`ItemResult`] - 批量操作中单个数据项的结果（[`ApiResponse::multi_status`] 返回 207）
+//! - [`NoContent`] - 不带响应体的 204 响应（如删除操作）
 //! - [`DataWrapper`] - Data 对象包装器（支持 Google 保留属性）
 //! - [`DataContent`] - 数据内容（单个资源或列表）
 //! - [`ApiError`] - 错误对象
@@ -112,9 +143,20 @@
 mod api_response;
 mod domain;
 mod error;
+mod multi_status;
+mod no_content;
+mod pagination;
 mod reason;
+mod stream;
 
-pub use api_response::{API_VERSION, ApiResponse, DataContent, DataWrapper};
+pub use api_response::{API_VERSION, ApiResponse, DataContent, DataWrapper, set_pretty_json};
 pub use domain::Domain;
 pub use error::{ApiError, ErrorDetail};
+pub use multi_status::ItemResult;
+pub use no_content::NoContent;
+pub use pagination::{
+    Cursor, CursorPage, CursorParams, MAX_PER_PAGE, PageParams, PaginatedResponse,
+    TOTAL_COUNT_HEADER, TOTAL_COUNT_HEADER_COMPONENT, register_total_count_header,
+};
 pub use reason::Reason;
+pub use stream::StreamingListResponse;
@@ -0,0 +1,109 @@
+//! 批量操作的逐项结果
+//!
+//! 批量接口部分数据项失败时返回 207 Multi-Status，`items` 中按请求顺序给出每一项的结果：
+//!
+//! ```json
+//! {"api_version":"1.0","data":{"items":[
+//!   {"index":0,"status":424,"error":{"code":424,"message":"..."}},
+//!   {"index":1,"status":409,"error":{"code":409,"message":"用户已存在","errors":[...]}}
+//! ],"current_item_count":2}}
+//! ```
+
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{ApiError, ApiResponse};
+
+/// 批量操作中单个数据项的结果
+///
+/// 成功时包含 `data`，失败时包含 `error`，`status` 为该项单独处理时对应的 HTTP 状态码。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ItemResult<T> {
+    /// 数据项在请求中的位置（从 0 开始）
+    pub index: usize,
+
+    /// 该项的 HTTP 状态码
+    pub status: u16,
+
+    /// 成功时的数据
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+
+    /// 失败时的错误
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiError>,
+}
+
+impl<T> ItemResult<T> {
+    /// 成功的数据项
+    pub fn ok(index: usize, status: StatusCode, data: T) -> Self {
+        Self {
+            index,
+            status: status.as_u16(),
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    /// 失败的数据项，状态码取自错误
+    pub fn err(index: usize, error: ApiError) -> Self {
+        Self {
+            index,
+            status: error.code,
+            data: None,
+            error: Some(error),
+        }
+    }
+
+    /// 该项是否成功
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+impl<T: Serialize> ApiResponse<ItemResult<T>> {
+    /// 创建 207 Multi-Status 响应，`results` 以列表形式放在 `data.items` 中
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let response = ApiResponse::multi_status(vec![
+    ///     ItemResult::ok(0, StatusCode::CREATED, user),
+    ///     ItemResult::err(1, ApiError::new(StatusCode::CONFLICT, "用户已存在")),
+    /// ]);
+    /// ```ignore
+    pub fn multi_status(results: Vec<ItemResult<T>>) -> impl IntoResponse {
+        (StatusCode::MULTI_STATUS, Self::simple_list(results))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    async fn test_multi_status_lists_each_item_result() {
+        let response = ApiResponse::multi_status(vec![
+            ItemResult::ok(0, StatusCode::CREATED, json!({ "id": 1 })),
+            ItemResult::err(1, ApiError::new(StatusCode::CONFLICT, "用户已存在")),
+        ])
+        .into_response();
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["data"]["current_item_count"], 2);
+        assert_eq!(
+            body["data"]["items"],
+            json!([
+                { "index": 0, "status": 201, "data": { "id": 1 } },
+                { "index": 1, "status": 409, "error": { "code": 409, "message": "用户已存在" } }
+            ])
+        );
+    }
+}
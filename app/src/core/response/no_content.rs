@@ -0,0 +1,100 @@
+//! 无内容响应
+//!
+//! 成功但没有数据可返回的操作（如删除）使用 [`NoContent`]，返回不带响应体的 204，
+//! 而不是只含 `api_version` 的空信封。
+
+use aide::OperationOutput;
+use aide::generate::GenContext;
+use aide::openapi::Operation;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+/// 204 No Content 响应
+///
+/// 没有响应体，也不设置 `Content-Type`。生成 API 文档时自动登记为 204 响应。
+///
+/// # Examples
+///
+/// ```ignore
+/// async fn delete_item() -> Result<NoContent, AppError> {
+///     // ...
+///     Ok(NoContent)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoContent;
+
+impl IntoResponse for NoContent {
+    fn into_response(self) -> Response {
+        StatusCode::NO_CONTENT.into_response()
+    }
+}
+
+impl OperationOutput for NoContent {
+    type Inner = ();
+
+    fn operation_response(
+        _ctx: &mut GenContext,
+        _operation: &mut Operation,
+    ) -> Option<aide::openapi::Response> {
+        Some(aide::openapi::Response {
+            description: "操作成功，无响应体".to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn inferred_responses(
+        ctx: &mut GenContext,
+        operation: &mut Operation,
+    ) -> Vec<(Option<u16>, aide::openapi::Response)> {
+        Self::operation_response(ctx, operation)
+            .map(|response| (Some(StatusCode::NO_CONTENT.as_u16()), response))
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aide::axum::ApiRouter;
+    use aide::axum::routing::delete;
+    use aide::openapi::{OpenApi, StatusCode as DocStatusCode};
+    use axum::body::Body;
+    use axum::http::{Request, header};
+    use tower::ServiceExt;
+
+    fn router() -> ApiRouter {
+        ApiRouter::new().api_route("/items/{id}", delete(|| async { NoContent }))
+    }
+
+    #[tokio::test]
+    async fn test_delete_returns_204_without_body() {
+        let request = Request::delete("/items/1").body(Body::empty()).unwrap();
+        let app: axum::Router = router().into();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(response.headers().get(header::CONTENT_TYPE).is_none());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+    }
+
+    #[test]
+    fn test_documents_204_response() {
+        let mut api = OpenApi::default();
+        let _ = router().finish_api(&mut api);
+
+        let operation = api.paths.unwrap().paths["/items/{id}"]
+            .as_item()
+            .unwrap()
+            .delete
+            .clone()
+            .unwrap();
+        let responses = operation.responses.unwrap().responses;
+        let response = responses[&DocStatusCode::Code(204)].as_item().unwrap();
+        assert!(response.content.is_empty());
+    }
+}
@@ -0,0 +1,384 @@
+//! 分页参数与分页响应
+//!
+//! 列表接口统一使用 [`PageParams`] 解析 `?page=&per_page=`，
+//! 并返回 [`PaginatedResponse`]：响应体沿用 [`ApiResponse::list`] 的格式，
+//! 同时在 `X-Total-Count` 响应头中给出总数，便于前端表格组件直接读取。
+//!
+//! 翻页期间有数据插入时，偏移分页会出现重复或遗漏。面向无限滚动的接口改用游标分页：
+//! 使用 [`CursorParams`] 解析 `?cursor=&per_page=` 并返回 [`CursorPage`]，
+//! 游标 [`Cursor`] 为上一页最后一条数据的 `(created_at, id)`，下一页从它之后开始。
+
+use aide::OperationOutput;
+use aide::generate::GenContext;
+use aide::openapi::{Header, Operation, ParameterSchemaOrContent, ReferenceOr, SchemaObject};
+use aide::transform::TransformOpenApi;
+use axum::http::HeaderValue;
+use axum::response::{IntoResponse, Response};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, FixedOffset};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::ApiResponse;
+
+/// 总数响应头名称
+pub const TOTAL_COUNT_HEADER: &str = "x-total-count";
+
+/// 总数响应头在 OpenAPI 文档 `components/headers` 中的名称
+pub const TOTAL_COUNT_HEADER_COMPONENT: &str = "X-Total-Count";
+
+/// 默认每页数据量
+const DEFAULT_PER_PAGE: u64 = 20;
+
+/// 每页数据量上限，也用作批量接口单次请求的数据项上限
+pub const MAX_PER_PAGE: u64 = 100;
+
+/// 分页查询参数
+///
+/// 缺省时取第 1 页、每页 20 条；`page` 最小为 1，`per_page` 限制在 1-100 之间。
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct PageParams {
+    /// 页码（从 1 开始）
+    pub page: u64,
+
+    /// 每页数据量（1-100）
+    pub per_page: u64,
+}
+
+impl Default for PageParams {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            per_page: DEFAULT_PER_PAGE,
+        }
+    }
+}
+
+impl PageParams {
+    /// 将参数修正到合法范围内
+    pub fn normalized(self) -> Self {
+        Self {
+            page: self.page.max(1),
+            per_page: self.per_page.clamp(1, MAX_PER_PAGE),
+        }
+    }
+
+    /// 当前页第一条数据的偏移量
+    pub fn offset(&self) -> u64 {
+        (self.page.max(1) - 1) * self.per_page
+    }
+}
+
+/// 分页响应
+///
+/// 包装 [`ApiResponse::list`]，输出时额外设置 `X-Total-Count` 响应头。
+#[derive(Debug, Clone)]
+pub struct PaginatedResponse<T: Serialize> {
+    /// 响应体
+    body: ApiResponse<T>,
+
+    /// 总数据量
+    total: u64,
+}
+
+impl<T: Serialize> PaginatedResponse<T> {
+    /// 根据当前页数据、总数和分页参数创建分页响应
+    pub fn new(items: Vec<T>, total: u64, params: PageParams) -> Self {
+        Self {
+            body: ApiResponse::list(
+                items,
+                total as i64,
+                params.page as i64,
+                params.per_page as i64,
+            ),
+            total,
+        }
+    }
+
+    /// 设置资源类型（如 "FileList"）
+    pub fn with_kind(mut self, kind: impl Into<String>) -> Self {
+        self.body = self.body.with_kind(kind);
+        self
+    }
+
+    /// 总数据量
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+impl<T: Serialize> IntoResponse for PaginatedResponse<T> {
+    fn into_response(self) -> Response {
+        let mut response = self.body.into_response();
+        response
+            .headers_mut()
+            .insert(TOTAL_COUNT_HEADER, HeaderValue::from(self.total));
+        response
+    }
+}
+
+impl<T: Serialize + JsonSchema> OperationOutput for PaginatedResponse<T> {
+    type Inner = T;
+
+    fn operation_response(
+        ctx: &mut GenContext,
+        operation: &mut Operation,
+    ) -> Option<aide::openapi::Response> {
+        let mut response = ApiResponse::<T>::operation_response(ctx, operation)?;
+        response.headers.insert(
+            TOTAL_COUNT_HEADER_COMPONENT.to_string(),
+            ReferenceOr::ref_(&format!(
+                "#/components/headers/{TOTAL_COUNT_HEADER_COMPONENT}"
+            )),
+        );
+        Some(response)
+    }
+
+    fn inferred_responses(
+        ctx: &mut GenContext,
+        operation: &mut Operation,
+    ) -> Vec<(Option<u16>, aide::openapi::Response)> {
+        ApiResponse::<T>::inferred_responses(ctx, operation)
+    }
+}
+
+/// 在 OpenAPI 文档的 `components/headers` 中登记 `X-Total-Count`
+///
+/// [`PaginatedResponse`] 的响应文档通过 `$ref` 引用该组件，生成文档时需要调用一次，
+/// 例如 `api.with(register_total_count_header)`。
+pub fn register_total_count_header(mut api: TransformOpenApi) -> TransformOpenApi {
+    let header = Header {
+        description: Some("符合查询条件的总数据量".to_string()),
+        style: Default::default(),
+        required: true,
+        deprecated: None,
+        format: ParameterSchemaOrContent::Schema(SchemaObject {
+            json_schema: schemars::json_schema!({ "type": "integer", "format": "uint64", "minimum": 0 }),
+            external_docs: None,
+            example: None,
+        }),
+        example: None,
+        examples: Default::default(),
+        extensions: Default::default(),
+    };
+    api.inner_mut()
+        .components
+        .get_or_insert_with(Default::default)
+        .headers
+        .insert(
+            TOTAL_COUNT_HEADER_COMPONENT.to_string(),
+            ReferenceOr::Item(header),
+        );
+    api
+}
+
+/// 游标分页的位置：上一页最后一条数据的创建时间和 ID
+///
+/// 数据按 `(created_at, id)` 升序排列，`id` 保证创建时间相同的数据也有确定的顺序。
+/// 对客户端是不透明的字符串（URL 安全的 Base64 编码），客户端只应原样回传。
+/// `id` 的类型随数据的主键类型而定，例如用户列表使用 [`entity::user::UserId`]。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor<I = i64> {
+    /// 创建时间
+    pub created_at: DateTime<FixedOffset>,
+
+    /// ID
+    pub id: I,
+}
+
+impl<I: Serialize + DeserializeOwned> Cursor<I> {
+    /// 编码为不透明的游标字符串
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(&(self.created_at, &self.id)).expect("游标总能序列化"))
+    }
+
+    /// 解析客户端回传的游标字符串
+    pub fn decode(value: &str) -> Result<Self, String> {
+        let invalid = || "cursor 无效".to_string();
+        let bytes = URL_SAFE_NO_PAD.decode(value).map_err(|_| invalid())?;
+        let (created_at, id) = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        Ok(Self { created_at, id })
+    }
+}
+
+/// 游标分页查询参数
+///
+/// 缺省时从头开始、每页 20 条；`per_page` 限制在 1-100 之间。
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CursorParams {
+    /// 上一页响应中的 `next_cursor`，不指定时从第一条开始
+    pub cursor: Option<String>,
+
+    /// 每页数据量（1-100）
+    pub per_page: u32,
+}
+
+impl Default for CursorParams {
+    fn default() -> Self {
+        Self {
+            cursor: None,
+            per_page: DEFAULT_PER_PAGE as u32,
+        }
+    }
+}
+
+impl CursorParams {
+    /// 修正到合法范围内的每页数据量
+    pub fn per_page(&self) -> u32 {
+        self.per_page.clamp(1, MAX_PER_PAGE as u32)
+    }
+
+    /// 解析游标，格式无效时返回错误消息
+    pub fn cursor<I: Serialize + DeserializeOwned>(&self) -> Result<Option<Cursor<I>>, String> {
+        self.cursor.as_deref().map(Cursor::decode).transpose()
+    }
+}
+
+/// 游标分页响应
+///
+/// 响应体为 [`ApiResponse::success`] 包装的 `{ items, next_cursor }`，
+/// `next_cursor` 为 `null` 表示没有更多数据。不返回总数，避免每次翻页都统计全表。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CursorPage<T> {
+    /// 当前页数据
+    pub items: Vec<T>,
+
+    /// 下一页的游标
+    pub next_cursor: Option<String>,
+}
+
+impl<T> CursorPage<T> {
+    /// 由多查询一条的结果创建分页：`rows` 最多包含 `per_page + 1` 条，多出的一条只用于判断是否还有下一页
+    ///
+    /// `cursor_of` 取出数据的游标位置，`map` 将数据转换为响应中的数据项。
+    pub fn from_rows<R, I: Serialize + DeserializeOwned>(
+        mut rows: Vec<R>,
+        per_page: u32,
+        cursor_of: impl Fn(&R) -> Cursor<I>,
+        map: impl FnMut(R) -> T,
+    ) -> Self {
+        let has_more = rows.len() > per_page as usize;
+        rows.truncate(per_page as usize);
+        let next_cursor = rows
+            .last()
+            .filter(|_| has_more)
+            .map(|last| cursor_of(last).encode());
+        Self {
+            items: rows.into_iter().map(map).collect(),
+            next_cursor,
+        }
+    }
+}
+
+impl<T: Serialize> IntoResponse for CursorPage<T> {
+    fn into_response(self) -> Response {
+        ApiResponse::success(self).into_response()
+    }
+}
+
+impl<T: Serialize + JsonSchema> OperationOutput for CursorPage<T> {
+    type Inner = Self;
+
+    fn operation_response(
+        ctx: &mut GenContext,
+        operation: &mut Operation,
+    ) -> Option<aide::openapi::Response> {
+        ApiResponse::<Self>::operation_response(ctx, operation)
+    }
+
+    fn inferred_responses(
+        ctx: &mut GenContext,
+        operation: &mut Operation,
+    ) -> Vec<(Option<u16>, aide::openapi::Response)> {
+        ApiResponse::<Self>::inferred_responses(ctx, operation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_params_normalized() {
+        let params = PageParams {
+            page: 0,
+            per_page: 1000,
+        }
+        .normalized();
+        assert_eq!(params.page, 1);
+        assert_eq!(params.per_page, MAX_PER_PAGE);
+        assert_eq!(params.offset(), 0);
+
+        let params = PageParams {
+            page: 3,
+            per_page: 10,
+        };
+        assert_eq!(params.offset(), 20);
+    }
+
+    #[test]
+    fn test_paginated_response_sets_total_header() {
+        let response =
+            PaginatedResponse::new(vec![1, 2], 42, PageParams::default()).into_response();
+        assert_eq!(response.headers()[TOTAL_COUNT_HEADER], "42");
+    }
+
+    #[test]
+    fn test_paginated_response_documents_total_header() {
+        let response = aide::generate::in_context(|ctx| {
+            PaginatedResponse::<i64>::operation_response(ctx, &mut Operation::default())
+        })
+        .unwrap();
+        assert!(matches!(
+            &response.headers[TOTAL_COUNT_HEADER_COMPONENT],
+            ReferenceOr::Reference { reference, .. }
+                if reference == "#/components/headers/X-Total-Count"
+        ));
+
+        let mut api = aide::openapi::OpenApi::default();
+        let _ = register_total_count_header(TransformOpenApi::new(&mut api));
+        let components = api.components.unwrap();
+        let ReferenceOr::Item(header) = &components.headers[TOTAL_COUNT_HEADER_COMPONENT] else {
+            panic!("X-Total-Count 应登记为组件本身");
+        };
+        assert!(header.required);
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = Cursor {
+            created_at: DateTime::parse_from_rfc3339("2024-05-01T08:30:00.123456+08:00").unwrap(),
+            id: 42,
+        };
+        let encoded = cursor.encode();
+        assert!(!encoded.contains(['+', '/', '=']), "{encoded}");
+        assert_eq!(Cursor::decode(&encoded).unwrap(), cursor);
+
+        for invalid in ["", "not base64!", &URL_SAFE_NO_PAD.encode("[1,2]")] {
+            assert!(Cursor::<i64>::decode(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_cursor_page_from_rows() {
+        let at = DateTime::parse_from_rfc3339("2024-05-01T00:00:00Z").unwrap();
+        let cursor_of = |id: &i64| Cursor {
+            created_at: at,
+            id: *id,
+        };
+
+        let page = CursorPage::from_rows(vec![1, 2, 3], 2, cursor_of, |id| id * 10);
+        assert_eq!(page.items, [10, 20]);
+        let next = Cursor::<i64>::decode(page.next_cursor.as_deref().unwrap()).unwrap();
+        assert_eq!(next.id, 2);
+
+        let page = CursorPage::from_rows(vec![1, 2], 2, cursor_of, |id| id);
+        assert_eq!(page.items, [1, 2]);
+        assert!(page.next_cursor.is_none());
+    }
+}
@@ -32,6 +32,8 @@ pub enum Reason {
     MissingCredentials,
     /// 认证失败（通用）
     AuthenticationFailed,
+    /// 登录失败次数过多，账户被临时锁定
+    AccountLocked,
 
     // ==================== 验证 (validation) ====================
     /// 格式无效
@@ -64,6 +66,8 @@ pub enum Reason {
     // ==================== 权限 ====================
     /// 权限不足
     PermissionDenied,
+    /// CSRF 令牌缺失或无效（前端需重新获取令牌后重试）
+    CsrfTokenInvalid,
 
     // ==================== 文件 (file) ====================
     /// 文件大小超出限制
@@ -72,6 +76,10 @@ pub enum Reason {
     FileTypeNotAllowed,
     /// 上传失败
     UploadFailed,
+    /// 文件未通过恶意软件扫描（待扫描或已隔离）
+    FileNotClean,
+    /// 用户的存储空间配额已用尽
+    StorageQuotaExceeded,
 
     // ==================== 限流 (rate_limit) ====================
     /// 请求频率超限
@@ -84,6 +92,8 @@ pub enum Reason {
     ServiceUnavailable,
     /// 功能未实现
     NotImplemented,
+    /// 请求方法不被允许
+    MethodNotAllowed,
     /// 请求超时
     Timeout,
     /// 未知错误
@@ -100,6 +110,7 @@ impl std::fmt::Display for Reason {
             Self::TokenExpired => "TOKEN_EXPIRED",
             Self::MissingCredentials => "MISSING_CREDENTIALS",
             Self::AuthenticationFailed => "AUTHENTICATION_FAILED",
+            Self::AccountLocked => "ACCOUNT_LOCKED",
             Self::InvalidFormat => "INVALID_FORMAT",
             Self::RequiredFieldMissing => "REQUIRED_FIELD_MISSING",
             Self::ValueOutOfRange => "VALUE_OUT_OF_RANGE",
@@ -113,13 +124,17 @@ impl std::fmt::Display for Reason {
             Self::Conflict => "CONFLICT",
             Self::UsageLimitReached => "USAGE_LIMIT_REACHED",
             Self::PermissionDenied => "PERMISSION_DENIED",
+            Self::CsrfTokenInvalid => "CSRF_TOKEN_INVALID",
             Self::FileTooLarge => "FILE_TOO_LARGE",
             Self::FileTypeNotAllowed => "FILE_TYPE_NOT_ALLOWED",
             Self::UploadFailed => "UPLOAD_FAILED",
+            Self::FileNotClean => "FILE_NOT_CLEAN",
+            Self::StorageQuotaExceeded => "STORAGE_QUOTA_EXCEEDED",
             Self::RateLimitExceeded => "RATE_LIMIT_EXCEEDED",
             Self::InternalError => "INTERNAL_ERROR",
             Self::ServiceUnavailable => "SERVICE_UNAVAILABLE",
             Self::NotImplemented => "NOT_IMPLEMENTED",
+            Self::MethodNotAllowed => "METHOD_NOT_ALLOWED",
             Self::Timeout => "TIMEOUT",
             Self::Unknown => "UNKNOWN",
         };
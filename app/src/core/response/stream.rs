@@ -0,0 +1,215 @@
+//! 流式列表响应
+//!
+//! 数据量很大的列表（如全量导出）一次性收集到 `Vec` 再放进 [`ApiResponse`] 会占用大量内存。
+//! [`StreamingListResponse`] 从数据流中边读边写，输出与 [`ApiResponse::simple_list`] 相同的信封：
+//!
+//! ```json
+//! {"api_version":"1.0","data":{"kind":"UserList","total_items":2,"items":[{...},{...}],"current_item_count":2}}
+//! ```
+//!
+//! 与缓冲响应相比有以下取舍：
+//!
+//! - `total_items` 只在调用方事先知道总数时（[`StreamingListResponse::with_total`]）输出，否则省略；
+//!   `current_item_count` 在所有数据项写完后才知道，因此位于 `items` 之后
+//! - 响应头在读取第一条数据前发出，状态码固定为 200；中途读取失败时只能记录日志并中断连接，
+//!   客户端会收到不完整的 JSON，而不是错误信封
+//! - 不受 `server.pretty_json` 影响，始终输出紧凑格式
+//!
+//! [`ApiResponse`]: super::ApiResponse
+//! [`ApiResponse::simple_list`]: super::ApiResponse::simple_list
+
+use std::io;
+use std::mem;
+
+use axum::body::{Body, Bytes};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use serde::Serialize;
+use tracing::error;
+
+use super::API_VERSION;
+use crate::AppError;
+
+/// 累计到该大小（字节）再发送一个响应体数据块，避免每条数据单独发送
+const CHUNK_BYTES: usize = 16 * 1024;
+
+/// 流式列表响应
+///
+/// 数据项逐条序列化写入响应体，内存占用只与单个数据块大小有关，与列表长度无关。
+pub struct StreamingListResponse<T> {
+    items: BoxStream<'static, Result<T, AppError>>,
+    kind: Option<String>,
+    total: Option<u64>,
+}
+
+impl<T: Serialize + Send + 'static> StreamingListResponse<T> {
+    /// 由数据流创建响应，数据流按需拉取（如分批查询数据库）
+    pub fn new(items: impl Stream<Item = Result<T, AppError>> + Send + 'static) -> Self {
+        Self {
+            items: items.boxed(),
+            kind: None,
+            total: None,
+        }
+    }
+
+    /// 设置资源类型（如 "UserList"）
+    pub fn with_kind(mut self, kind: impl Into<String>) -> Self {
+        self.kind = Some(kind.into());
+        self
+    }
+
+    /// 设置总数据量，输出为 `total_items`
+    pub fn with_total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// 信封中 `items` 之前的部分
+    fn head(&self) -> Vec<u8> {
+        let mut head = format!("{{\"api_version\":\"{API_VERSION}\",\"data\":{{");
+        if let Some(kind) = &self.kind {
+            let kind = serde_json::to_string(kind).expect("字符串总能序列化");
+            head.push_str(&format!("\"kind\":{kind},"));
+        }
+        if let Some(total) = self.total {
+            head.push_str(&format!("\"total_items\":{total},"));
+        }
+        head.push_str("\"items\":[");
+        head.into_bytes()
+    }
+}
+
+/// 响应体编码状态
+struct Encoder<T> {
+    items: BoxStream<'static, Result<T, AppError>>,
+    buf: Vec<u8>,
+    count: u64,
+}
+
+impl<T: Serialize> Encoder<T> {
+    /// 读取数据项直到攒满一个数据块或数据流结束，返回数据块和是否还有后续数据
+    async fn next_chunk(&mut self) -> io::Result<(Bytes, bool)> {
+        while let Some(item) = self.items.next().await {
+            let item = item.map_err(|e| {
+                error!(error = %e, count = self.count, "流式列表读取数据失败，响应已中断");
+                io::Error::other(e.to_string())
+            })?;
+            if self.count > 0 {
+                self.buf.push(b',');
+            }
+            serde_json::to_writer(&mut self.buf, &item).map_err(|e| {
+                error!(error = %e, count = self.count, "流式列表序列化数据失败，响应已中断");
+                io::Error::other(e)
+            })?;
+            self.count += 1;
+            if self.buf.len() >= CHUNK_BYTES {
+                return Ok((Bytes::from(mem::take(&mut self.buf)), true));
+            }
+        }
+        self.buf
+            .extend(format!("],\"current_item_count\":{}}}}}", self.count).bytes());
+        Ok((Bytes::from(mem::take(&mut self.buf)), false))
+    }
+}
+
+impl<T: Serialize + Send + 'static> IntoResponse for StreamingListResponse<T> {
+    fn into_response(self) -> Response {
+        let encoder = Encoder {
+            buf: self.head(),
+            items: self.items,
+            count: 0,
+        };
+        let body = stream::unfold(Some(encoder), |encoder| async move {
+            let mut encoder = encoder?;
+            match encoder.next_chunk().await {
+                Ok((chunk, true)) => Some((Ok(chunk), Some(encoder))),
+                Ok((chunk, false)) => Some((Ok(chunk), None)),
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+        (
+            [(header::CONTENT_TYPE, "application/json")],
+            Body::from_stream(body),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use serde_json::{Value, json};
+
+    #[derive(Serialize)]
+    struct Row {
+        id: u64,
+        name: String,
+    }
+
+    fn rows(count: u64) -> impl Stream<Item = Result<Row, AppError>> + Send + 'static {
+        stream::iter(0..count).map(|id| {
+            Ok(Row {
+                id,
+                name: format!("user-{id}"),
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_streams_large_list_as_well_formed_json() {
+        let response = StreamingListResponse::new(rows(50_000))
+            .with_kind("UserList")
+            .with_total(50_000)
+            .into_response();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+
+        let mut body = response.into_body().into_data_stream();
+        let mut bytes = Vec::new();
+        let mut chunks = 0;
+        while let Some(chunk) = body.next().await {
+            bytes.extend_from_slice(&chunk.unwrap());
+            chunks += 1;
+        }
+        assert!(chunks > 1, "大列表应分多个数据块发送");
+
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["api_version"], API_VERSION);
+        assert_eq!(body["data"]["kind"], "UserList");
+        assert_eq!(body["data"]["total_items"], 50_000);
+        assert_eq!(body["data"]["current_item_count"], 50_000);
+        let items = body["data"]["items"].as_array().unwrap();
+        assert_eq!(items.len(), 50_000);
+        assert_eq!(items[49_999], json!({ "id": 49_999, "name": "user-49999" }));
+    }
+
+    #[tokio::test]
+    async fn test_empty_list_omits_unknown_total() {
+        let response = StreamingListResponse::new(rows(0)).into_response();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "api_version": API_VERSION,
+                "data": { "items": [], "current_item_count": 0 }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_aborts_body() {
+        let items = rows(3).chain(stream::once(async {
+            Err(AppError::Anyhow(anyhow!("数据库连接断开")))
+        }));
+        let response = StreamingListResponse::new(items).into_response();
+        assert!(
+            axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -2,28 +2,131 @@ mod runtime;
 
 pub use runtime::AppStateConfig;
 
-use crate::{AppConfig, AppError, ValidationError, shared::jwt::JwtService};
+use crate::{
+    AppConfig, AppError, FeatureFlags, HealthGuard, LogLevelHandle, OpenApiCache, Readiness,
+    ReadinessCache, RouteBodyLimits, ValidationError,
+    core::{
+        config::{FileScanConfig, ImportConfig, ProfilingConfig, UploadConfig},
+        latency::LatencyStats,
+        middleware::{ApiKeys, MaintenanceMode},
+        read_retry::ReadRetry,
+        response,
+    },
+    shared::{
+        SharedSecret, encryption,
+        ids::{self, IdGenerator},
+        jobs::JobQueue,
+        jwt::JwtService,
+        scanner::{AnyScanner, FileScanner, ScanQueue},
+        storage::{AnyStorage, Storages},
+    },
+};
 use deadpool_redis::Pool as RedisPool;
 use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::{RwLock, RwLockReadGuard};
+
+/// 共享 HTTP 客户端的请求超时时间（秒）
+const HTTP_CLIENT_TIMEOUT_SECS: u64 = 10;
 
 /// 应用程序运行时状态
 ///
 /// 包含应用程序在运行时需要的所有共享资源，如数据库连接、Redis 连接池和配置。
 /// 通过 Axum 的 State 提取器传递给每个处理器。
+///
+/// 克隆后的实例共享同一份运行时可变数据：
+/// - `db`、`redis` 为连接池句柄，内部自行同步，启动后不再替换
+/// - `jwt_service`、`jwt_secret`、`id_generator`、`features`、`body_limits`、`upload`、`import`、`profiling`、`storage`、`file_scan`、`scanner`、`health_guard`、`read_retry` 启动后不可变
+/// - `scan_queue`、`blob_queue`、`thumbnail_queue` 为共享的后台任务队列
+/// - `log_level` 内部通过 reload 句柄同步
+/// - `readiness` 为共享的就绪检查结果缓存，内部加锁
+/// - `maintenance` 为共享的维护模式开关，内部为原子变量
+/// - `http_client` 内部为 `Arc`，克隆后共享连接池
+/// - `openapi` 为共享的 OpenAPI 文档缓存，只写入一次
+/// - `config` 可能被配置热重载替换，使用 [`RwLock`] 保护，通过 [`AppState::config`] 读取
 #[derive(Debug, Clone)]
 pub struct AppState {
     /// 数据库连接
     pub db: DatabaseConnection,
 
+    /// 只读操作的瞬时数据库错误重试策略（由处理器按需使用）
+    pub read_retry: ReadRetry,
+
     /// Redis 连接池（可选）
     pub redis: Option<RedisPool>,
 
     /// JWT 服务
     pub jwt_service: JwtService,
 
-    /// 应用状态配置
-    pub config: AppStateConfig,
+    /// JWT 签名密钥，同时用于签名文件下载链接
+    ///
+    /// 启动后不可变，不放入可被热重载替换的 [`AppStateConfig`]：`jwt_service` 在启动时由它构建，
+    /// 运行中替换密钥会让令牌和下载链接的签名与校验使用不同的密钥。
+    pub jwt_secret: SharedSecret,
+
+    /// 已配置的 API 密钥（机器客户端认证）
+    pub api_keys: ApiKeys,
+
+    /// Snowflake ID 生成器（同时注册为全局生成器，见 [`ids::next_id`]）
+    pub id_generator: IdGenerator,
+
+    /// 运行时日志级别控制句柄
+    pub log_level: LogLevelHandle,
+
+    /// 功能开关（决定注册哪些可选路由）
+    pub features: FeatureFlags,
+
+    /// 按挂载路径覆盖的请求体大小上限（构建路由时使用）
+    pub body_limits: RouteBodyLimits,
+
+    /// 文件上传限制（大小、MIME 类型）
+    pub upload: UploadConfig,
+
+    /// CSV 批量导入用户的限制（行数、失败比例）
+    pub import: ImportConfig,
+
+    /// CPU 性能剖析的访问限制和时长上限（需要启用 `profiling` feature 的 debug 构建）
+    pub profiling: ProfilingConfig,
+
+    /// 上传文件的内容存储（按 `object_storage` 配置的后端集合）
+    pub storage: Storages<AnyStorage>,
+
+    /// 上传文件的恶意软件扫描配置
+    pub file_scan: FileScanConfig,
+
+    /// 恶意软件扫描器（按 `file_scan.backend` 选择）
+    pub scanner: AnyScanner,
+
+    /// 待扫描文件队列，由后台扫描任务消费
+    pub scan_queue: ScanQueue,
+
+    /// 待回收的文件内容（SHA-256）队列，由后台回收任务消费
+    pub blob_queue: JobQueue<String>,
+
+    /// 等待生成缩略图的文件 ID 队列，由后台缩略图任务消费
+    pub thumbnail_queue: JobQueue<i64>,
+
+    /// 就绪检查结果缓存
+    pub readiness: ReadinessCache,
+
+    /// 就绪检查详情的访问控制
+    pub health_guard: HealthGuard,
+
+    /// 维护模式开关（由管理接口切换）
+    pub maintenance: MaintenanceMode,
+
+    /// 按路由统计的请求耗时（重启后清空）
+    pub latency: LatencyStats,
+
+    /// 共享 HTTP 客户端（连接池复用），用于调用外部服务，如告警 webhook
+    pub http_client: reqwest::Client,
+
+    /// 序列化后的 OpenAPI 文档（构建路由后预先生成或在首次请求时生成）
+    pub openapi: OpenApiCache,
+
+    /// 应用状态配置（运行时可替换）
+    pub config: Arc<RwLock<AppStateConfig>>,
 }
 
 impl AppState {
@@ -34,6 +137,7 @@ impl AppState {
     /// # 参数
     ///
     /// * `app_config` - 应用配置对象
+    /// * `log_level` - 日志系统初始化时返回的日志级别控制句柄
     ///
     /// # 返回值
     ///
@@ -42,24 +146,156 @@ impl AppState {
     /// # 异步
     ///
     /// 此方法是异步的，因为建立数据库连接是 I/O 操作
-    pub async fn init(app_config: &AppConfig) -> Result<Self, AppError> {
+    pub async fn init(app_config: &AppConfig, log_level: LogLevelHandle) -> Result<Self, AppError> {
         let db = Self::create_db_connection(app_config).await?;
         let redis = Self::create_redis_pool(app_config).await?;
-        let jwt_service = JwtService::new(app_config.clone().secrets.jwt_secret.clone());
+        Self::with_connections(app_config, log_level, db, redis)
+    }
+
+    /// 使用已建立的数据库连接和 Redis 连接池创建应用状态
+    ///
+    /// 不发起任何连接，测试中可以传入未连接的 [`DatabaseConnection`] 来构建完整的路由。
+    ///
+    /// # 参数
+    ///
+    /// * `app_config` - 应用配置对象
+    /// * `log_level` - 日志系统初始化时返回的日志级别控制句柄
+    /// * `db` - 数据库连接
+    /// * `redis` - Redis 连接池（可选）
+    ///
+    /// # 返回值
+    ///
+    /// 成功返回应用状态，加密密钥、机器 ID 或存储后端配置无效时返回应用错误
+    pub fn with_connections(
+        app_config: &AppConfig,
+        log_level: LogLevelHandle,
+        db: DatabaseConnection,
+        redis: Option<RedisPool>,
+    ) -> Result<Self, AppError> {
+        let jwt_service = JwtService::new((*app_config.secrets.jwt_secret).to_owned());
+        let id_generator = IdGenerator::new(app_config.server.machine_id)?;
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(HTTP_CLIENT_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| anyhow::anyhow!("HTTP 客户端创建失败：{e}"))?;
+        ids::install(id_generator.clone());
+
+        let pretty_json = app_config.server.pretty_json || app_config.logging.level == "debug";
+        response::set_pretty_json(pretty_json);
+
+        if let Some(key) = &app_config.secrets.encryption_key {
+            let key = encryption::parse_key(key)
+                .map_err(|e| AppError::Validation(ValidationError::custom(e)))?;
+            encryption::install_key(key);
+            tracing::info!("数据库列加密密钥已加载");
+        }
+
+        let storage =
+            Storages::from_config(&app_config.object_storage, &app_config.upload.storage_dir)
+                .map_err(|e| anyhow::anyhow!("存储后端初始化失败：{e}"))?;
+
+        let api_keys = ApiKeys::new(&app_config.api_keys);
 
         Ok(AppState {
             db,
+            read_retry: ReadRetry::new(&app_config.database),
             redis,
             jwt_service,
-            config: AppStateConfig {
-                jwt_secret: app_config.clone().secrets.jwt_secret,
-            },
+            jwt_secret: app_config.secrets.jwt_secret.clone(),
+            health_guard: HealthGuard::new(&app_config.health, api_keys.clone()),
+            maintenance: MaintenanceMode::default(),
+            api_keys,
+            id_generator,
+            log_level,
+            features: app_config.features,
+            body_limits: RouteBodyLimits::new(app_config.server.route_body_limits.clone()),
+            upload: app_config.upload.clone(),
+            import: app_config.import.clone(),
+            profiling: app_config.profiling.clone(),
+            storage,
+            file_scan: app_config.file_scan.clone(),
+            scanner: AnyScanner::from_config(&app_config.file_scan),
+            scan_queue: ScanQueue::default(),
+            blob_queue: JobQueue::default(),
+            thumbnail_queue: JobQueue::default(),
+            http_client,
+            latency: LatencyStats::default(),
+            openapi: OpenApiCache::default(),
+            readiness: ReadinessCache::new(Duration::from_millis(
+                app_config.server.readiness_cache_ttl_ms,
+            )),
+            config: Arc::new(RwLock::new(AppStateConfig {
+                public_url: app_config.server.resolved_public_url(),
+                audit_redact_fields: app_config.audit.redact_fields.clone(),
+            })),
         })
     }
 
+    /// 读取当前的应用状态配置
+    ///
+    /// 返回的读守卫会阻塞配置替换，不要跨越耗时操作持有。
+    pub async fn config(&self) -> RwLockReadGuard<'_, AppStateConfig> {
+        self.config.read().await
+    }
+
+    /// 对外访问的基础 URL（不带末尾 `/`）
+    ///
+    /// 用于在响应中拼接绝对地址，如文件下载链接。
+    pub async fn public_url(&self) -> String {
+        self.config().await.public_url.clone()
+    }
+
+    /// 检查数据库、Redis 和恶意软件扫描器是否可用
+    ///
+    /// 结果在 `server.readiness_cache_ttl_ms` 内被缓存，高频探测不会每次都访问依赖。
+    /// 扫描器不可用只标记为降级，不影响就绪状态。
+    pub async fn readiness(&self) -> Readiness {
+        self.readiness
+            .get_or_check(|| async {
+                let database = self
+                    .db
+                    .ping()
+                    .await
+                    .inspect_err(|e| tracing::warn!(error = %e, "就绪检查：数据库不可用"))
+                    .is_ok();
+                let redis = match &self.redis {
+                    Some(pool) => Some(Self::ping_redis(pool).await),
+                    None => None,
+                };
+                let scanner = if self.scanner.is_external() {
+                    Some(
+                        self.scanner
+                            .ping()
+                            .await
+                            .inspect_err(|e| tracing::warn!(error = %e, "就绪检查：扫描器不可用"))
+                            .is_ok(),
+                    )
+                } else {
+                    None
+                };
+                Readiness::new(database, redis).with_scanner(scanner)
+            })
+            .await
+    }
+
+    async fn ping_redis(pool: &RedisPool) -> bool {
+        let result = async {
+            let mut conn = pool.get().await.map_err(|e| e.to_string())?;
+            deadpool_redis::redis::cmd("PING")
+                .query_async::<String>(&mut conn)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        .await;
+        result
+            .inspect_err(|e| tracing::warn!(error = %e, "就绪检查：Redis 不可用"))
+            .is_ok()
+    }
+
     /// 创建数据库连接
     ///
-    /// 根据应用配置创建连接池并连接到数据库。
+    /// 根据应用配置创建连接池并连接到数据库。连接 PostgreSQL 时按配置为每个连接设置
+    /// `statement_timeout` 和 `idle_in_transaction_session_timeout`。
     ///
     /// # 参数
     ///
@@ -69,7 +305,7 @@ impl AppState {
     ///
     /// 成功返回数据库连接，失败返回应用错误
     async fn create_db_connection(app_config: &AppConfig) -> Result<DatabaseConnection, AppError> {
-        let mut opt = ConnectOptions::new(app_config.database.url.as_str());
+        let mut opt = ConnectOptions::new(app_config.database.connection_url());
         opt.max_connections(app_config.database.max_connections)
             .min_connections(5)
             .connect_timeout(Duration::from_secs(app_config.database.pool_timeout))
@@ -84,6 +320,18 @@ impl AppState {
                 )))
             })?);
 
+        let session_options = app_config.database.session_options();
+        if !session_options.is_empty() {
+            if app_config.database.is_postgres() {
+                // 作为连接启动参数发送，每个新连接都会生效，等同于建立连接后执行 SET
+                opt.map_sqlx_postgres_opts(move |pg| pg.options(session_options.clone()));
+            } else {
+                tracing::warn!(
+                    "statement_timeout_ms / idle_transaction_timeout_ms 只对 PostgreSQL 生效，已忽略"
+                );
+            }
+        }
+
         Database::connect(opt).await.map_err(AppError::Database)
     }
 
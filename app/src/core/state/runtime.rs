@@ -2,9 +2,12 @@ use serde::{Deserialize, Serialize};
 
 /// 应用状态运行时配置
 ///
-/// 存储应用在运行时需要的敏感配置信息和秘密。
+/// 存储可以被配置热重载替换的设置。JWT 密钥启动后不可变，见 [`AppState::jwt_secret`](super::AppState::jwt_secret)。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppStateConfig {
-    /// JWT 签名密钥，用于生成和验证令牌
-    pub jwt_secret: String,
+    /// 对外访问的基础 URL（不带末尾 `/`）
+    pub public_url: String,
+
+    /// 管理操作审计时需要脱敏的请求体字段名
+    pub audit_redact_fields: Vec<String>,
 }
@@ -0,0 +1,313 @@
+//! HTTPS 监听与 HTTP 重定向
+//!
+//! 基于 rustls 在 TCP 连接上完成 TLS 握手，再交给 `axum::serve` 处理请求。
+//! 握手在独立任务中进行并带有超时，慢速或恶意客户端不会阻塞后续连接的接入。
+
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use axum::extract::State;
+use axum::http::uri::{Authority, Scheme};
+use axum::http::{HeaderMap, StatusCode, Uri, header};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::serve::Listener;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::server::TlsStream;
+use tracing::debug;
+
+use crate::core::config::{TlsConfig, TlsVersion};
+use crate::error::ConfigError;
+
+/// 单个连接完成 TLS 握手的最长时间
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 已完成握手、等待 `axum::serve` 接收的连接数上限
+const ACCEPT_QUEUE: usize = 128;
+
+/// 读取证书和私钥，构建 rustls 服务端配置
+///
+/// # 返回
+/// 文件不存在、格式错误或证书与私钥不匹配时返回 ConfigError::Invalid
+pub fn load_server_config(config: &TlsConfig) -> Result<Arc<rustls::ServerConfig>, ConfigError> {
+    let certs = CertificateDer::pem_file_iter(&config.cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| {
+            ConfigError::Invalid(format!("无法读取 TLS 证书 {}：{e}", config.cert_path))
+        })?;
+    if certs.is_empty() {
+        return Err(ConfigError::Invalid(format!(
+            "TLS 证书文件中没有证书：{}",
+            config.cert_path
+        )));
+    }
+    let key = PrivateKeyDer::from_pem_file(&config.key_path)
+        .map_err(|e| ConfigError::Invalid(format!("无法读取 TLS 私钥 {}：{e}", config.key_path)))?;
+
+    let versions: &[&'static rustls::SupportedProtocolVersion] = match config.min_tls_version {
+        TlsVersion::Tls12 => &[&rustls::version::TLS13, &rustls::version::TLS12],
+        TlsVersion::Tls13 => &[&rustls::version::TLS13],
+    };
+    let invalid = |e: rustls::Error| ConfigError::Invalid(format!("TLS 配置无效：{e}"));
+    let mut server_config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_protocol_versions(versions)
+    .map_err(invalid)?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)
+    .map_err(invalid)?;
+    // axum 只启用了 HTTP/1.1
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Arc::new(server_config))
+}
+
+/// 接收 HTTPS 连接的监听器
+///
+/// 后台任务负责接收 TCP 连接并逐个在独立任务中握手，握手成功的连接通过通道交给
+/// `axum::serve`。监听器被丢弃（如优雅关闭开始）后后台任务随之退出。
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    connections: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    /// 在已绑定的 TCP 监听器上启用 TLS
+    ///
+    /// `listener` 可以是经 [`ListenerExt::tap_io`](axum::serve::ListenerExt::tap_io) 包装、
+    /// 接入时调整连接选项的监听器。
+    pub fn new<L>(listener: L, config: Arc<rustls::ServerConfig>) -> io::Result<Self>
+    where
+        L: Listener<Io = TcpStream, Addr = SocketAddr>,
+    {
+        let local_addr = listener.local_addr()?;
+        let (tx, rx) = mpsc::channel(ACCEPT_QUEUE);
+        tokio::spawn(accept_loop(listener, TlsAcceptor::from(config), tx));
+        Ok(Self {
+            local_addr,
+            connections: rx,
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+            // 后台任务只在监听器被丢弃后退出，此处不会发生
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+/// 接收 TCP 连接并在独立任务中完成 TLS 握手
+async fn accept_loop<L>(
+    mut listener: L,
+    acceptor: TlsAcceptor,
+    tx: mpsc::Sender<(TlsStream<TcpStream>, SocketAddr)>,
+) where
+    L: Listener<Io = TcpStream, Addr = SocketAddr>,
+{
+    loop {
+        let (stream, addr) = tokio::select! {
+            _ = tx.closed() => return,
+            accepted = Listener::accept(&mut listener) => accepted,
+        };
+        let acceptor = acceptor.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => {
+                    let _ = tx.send((stream, addr)).await;
+                }
+                Ok(Err(e)) => debug!(%addr, error = %e, "TLS 握手失败"),
+                Err(_) => debug!(%addr, "TLS 握手超时"),
+            }
+        });
+    }
+}
+
+/// HTTP → HTTPS 重定向路由，所有请求以 308 重定向到同一路径的 HTTPS 地址
+pub fn redirect_router(https_port: u16) -> Router {
+    Router::new()
+        .fallback(redirect_to_https)
+        .with_state(https_port)
+}
+
+async fn redirect_to_https(
+    State(https_port): State<u16>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Response {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok());
+    match host.and_then(|host| https_location(host, https_port, &uri)) {
+        Some(location) => Redirect::permanent(&location).into_response(),
+        None => (StatusCode::BAD_REQUEST, "缺少有效的 Host 请求头").into_response(),
+    }
+}
+
+/// 根据 Host 请求头拼接 HTTPS 地址，非 443 端口时保留端口号
+fn https_location(host: &str, https_port: u16, uri: &Uri) -> Option<String> {
+    let host = host.parse::<Authority>().ok()?;
+    let authority = match https_port {
+        443 => host.host().to_string(),
+        port => format!("{}:{port}", host.host()),
+    };
+    let path = uri.path_and_query().map_or("/", |p| p.as_str());
+    Some(format!("{}://{authority}{path}", Scheme::HTTPS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::pki_types::ServerName;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsConnector;
+
+    const CERT_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/tls/localhost.crt"
+    );
+    const KEY_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/tls/localhost.key"
+    );
+
+    fn tls_config(min_tls_version: TlsVersion) -> TlsConfig {
+        TlsConfig {
+            enabled: true,
+            cert_path: CERT_PATH.to_string(),
+            key_path: KEY_PATH.to_string(),
+            min_tls_version,
+        }
+    }
+
+    /// 启动只返回 `ok` 的 HTTPS 服务，返回监听地址
+    async fn serve(min_tls_version: TlsVersion) -> SocketAddr {
+        let server_config = load_server_config(&tls_config(min_tls_version)).unwrap();
+        let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let listener = TlsListener::new(tcp, server_config).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/", axum::routing::get(|| async { "ok" }));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        addr
+    }
+
+    /// 以指定的 TLS 版本发起 HTTPS 请求，返回响应原文
+    async fn get(
+        addr: SocketAddr,
+        versions: &[&'static rustls::SupportedProtocolVersion],
+    ) -> io::Result<String> {
+        let mut roots = rustls::RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter(CERT_PATH).unwrap() {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let client_config = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_protocol_versions(versions)
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+        let tcp = TcpStream::connect(addr).await?;
+        let mut stream = TlsConnector::from(Arc::new(client_config))
+            .connect(ServerName::try_from("localhost").unwrap(), tcp)
+            .await?;
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    #[tokio::test]
+    async fn test_serves_https() {
+        let addr = serve(TlsVersion::Tls12).await;
+        for version in [&rustls::version::TLS12, &rustls::version::TLS13] {
+            let response = get(addr, &[version]).await.unwrap();
+            assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+            assert!(response.ends_with("ok"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_min_tls_version_rejects_older_clients() {
+        let addr = serve(TlsVersion::Tls13).await;
+        assert!(get(addr, &[&rustls::version::TLS12]).await.is_err());
+        // 失败的握手不影响后续连接
+        assert!(get(addr, &[&rustls::version::TLS13]).await.is_ok());
+    }
+
+    #[test]
+    fn test_load_server_config_reports_missing_files() {
+        let mut config = tls_config(TlsVersion::Tls12);
+        config.key_path = "missing.key".to_string();
+        let err = load_server_config(&config).unwrap_err();
+        assert!(err.to_string().contains("missing.key"), "{err}");
+
+        // 私钥文件不是证书
+        config.cert_path = KEY_PATH.to_string();
+        let err = load_server_config(&config).unwrap_err();
+        assert!(err.to_string().contains("没有证书"), "{err}");
+    }
+
+    #[test]
+    fn test_https_location() {
+        let uri: Uri = "/v1/users?page=2".parse().unwrap();
+        assert_eq!(
+            https_location("api.example.com", 443, &uri).as_deref(),
+            Some("https://api.example.com/v1/users?page=2")
+        );
+        assert_eq!(
+            https_location("api.example.com:80", 8443, &uri).as_deref(),
+            Some("https://api.example.com:8443/v1/users?page=2")
+        );
+        assert_eq!(
+            https_location("[::1]:80", 443, &"/".parse().unwrap()).as_deref(),
+            Some("https://[::1]/")
+        );
+        assert_eq!(https_location("bad host", 443, &uri), None);
+    }
+
+    #[tokio::test]
+    async fn test_redirect_router() {
+        use tower::ServiceExt;
+
+        let request = axum::http::Request::builder()
+            .uri("/health?verbose=1")
+            .header(header::HOST, "api.example.com")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = redirect_router(443).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://api.example.com/health?verbose=1"
+        );
+
+        let request = axum::http::Request::builder()
+            .uri("/")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        let response = redirect_router(443).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
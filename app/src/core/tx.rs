@@ -0,0 +1,309 @@
+//! 请求级数据库事务
+//!
+//! [`Tx`] 提取器为每个请求开启一个事务，并生成简短的事务 ID。
+//! 事务内执行的所有语句都在 `db_tx{tx_id=...}` span 中运行，
+//! 因此 sqlx 输出的 SQL 日志会带上事务 ID，并发请求交错执行时也能区分来源。
+//!
+//! ```ignore
+//! async fn handler(tx: Tx) -> Result<ApiResponse<()>, AppError> {
+//!     user::Entity::find().all(&tx).await?;
+//!     tx.commit().await?;
+//!     Ok(ApiResponse::success(()))
+//! }
+//! ```
+//!
+//! 未调用 [`Tx::commit`] 就被丢弃的事务会自动回滚。
+//!
+//! 不需要在处理器签名中持有事务时，可以用 [`Transactional::transaction`] 包裹一段操作，
+//! 闭包返回 `Ok` 时提交，返回 `Err` 时回滚：
+//!
+//! ```ignore
+//! let user = state
+//!     .transaction(async |txn| {
+//!         file::Entity::delete_many().filter(...).exec(txn).await?;
+//!         Ok(user::Entity::delete_by_id(id).exec(txn).await?)
+//!     })
+//!     .await?;
+//! ```
+
+use std::sync::Arc;
+
+use aide::OperationInput;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use sea_orm::prelude::async_trait::async_trait;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, DbErr, ExecResult,
+    QueryResult, Statement, TransactionTrait,
+};
+use tracing::{Instrument, Span};
+
+use crate::shared::crypto::generate_hex_token;
+use crate::{AppError, AppState};
+
+/// 带事务 ID 追踪的数据库事务
+#[derive(Debug)]
+pub struct Tx {
+    inner: DatabaseTransaction,
+    id: String,
+    span: Span,
+}
+
+impl Tx {
+    /// 开启事务并生成事务 ID
+    pub async fn begin(db: &DatabaseConnection) -> Result<Self, DbErr> {
+        let id = new_tx_id();
+        let span = tracing::info_span!("db_tx", tx_id = %id);
+
+        let inner = db.begin().instrument(span.clone()).await?;
+        span.in_scope(|| tracing::debug!("事务开始"));
+
+        Ok(Self { inner, id, span })
+    }
+
+    /// 事务 ID
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// 在事务 span 中运行任意异步代码，使其中的日志带上事务 ID
+    pub async fn scoped<F: Future>(&self, fut: F) -> F::Output {
+        fut.instrument(self.span.clone()).await
+    }
+
+    /// 提交事务
+    pub async fn commit(self) -> Result<(), DbErr> {
+        let span = self.span;
+        async move {
+            self.inner.commit().await?;
+            tracing::debug!("事务已提交");
+            Ok(())
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// 回滚事务
+    pub async fn rollback(self) -> Result<(), DbErr> {
+        let span = self.span;
+        async move {
+            self.inner.rollback().await?;
+            tracing::debug!("事务已回滚");
+            Ok(())
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+/// 可以用闭包执行数据库事务的类型
+///
+/// 应用状态和直接持有数据库连接的仓储都实现该 trait，跨多张表的写入统一通过 [`transaction`](Self::transaction)
+/// 提交或回滚，不再手动调用 [`Tx::commit`]。
+pub(crate) trait Transactional {
+    /// 开启事务使用的数据库连接
+    fn connection(&self) -> &DatabaseConnection;
+
+    /// 在数据库事务中执行 `f`，返回 `Ok` 时提交，返回 `Err` 时回滚并原样返回错误
+    ///
+    /// 闭包中的日志和 SQL 都在 `db_tx` span 中执行；该 span 是请求 span 的子 span，
+    /// 因此事务的开始、提交和回滚（DEBUG 级别）会同时带上 `request_id` 和 `tx_id`。
+    ///
+    /// 使用异步闭包（`async |txn| { ... }`）而不是返回 `Future` 的普通闭包，
+    /// 这样返回的 future 才能借用 `txn`。
+    async fn transaction<F, R, E>(&self, f: F) -> Result<R, E>
+    where
+        F: AsyncFnOnce(&DatabaseTransaction) -> Result<R, E>,
+        E: From<DbErr>,
+    {
+        run_in_transaction(self.connection(), f).await
+    }
+}
+
+impl Transactional for AppState {
+    fn connection(&self) -> &DatabaseConnection {
+        &self.db
+    }
+}
+
+/// [`Transactional::transaction`] 的实现，单独拆出以便在测试中直接传入数据库连接
+async fn run_in_transaction<F, R, E>(db: &DatabaseConnection, f: F) -> Result<R, E>
+where
+    F: AsyncFnOnce(&DatabaseTransaction) -> Result<R, E>,
+    E: From<DbErr>,
+{
+    let tx = Tx::begin(db).await?;
+    match tx.scoped(f(&tx.inner)).await {
+        Ok(value) => {
+            tx.commit().await?;
+            Ok(value)
+        }
+        Err(e) => {
+            let span = tx.span.clone();
+            if let Err(rollback) = tx.rollback().await {
+                span.in_scope(|| tracing::warn!(error = %rollback, "事务回滚失败"));
+            }
+            Err(e)
+        }
+    }
+}
+
+/// 生成 8 位十六进制的事务 ID
+fn new_tx_id() -> String {
+    generate_hex_token(4)
+}
+
+#[async_trait]
+impl ConnectionTrait for Tx {
+    fn get_database_backend(&self) -> DbBackend {
+        self.inner.get_database_backend()
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        self.scoped(self.inner.execute(stmt)).await
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        self.scoped(self.inner.execute_unprepared(sql)).await
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        self.scoped(self.inner.query_one(stmt)).await
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        self.scoped(self.inner.query_all(stmt)).await
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for Tx {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Tx::begin(&state.db).await?)
+    }
+}
+
+impl OperationInput for Tx {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::Mutex;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    /// 收集日志输出的内存写入器
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Capture {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_transactions_log_distinct_tx_ids() {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_current_span(true)
+            .with_writer(capture.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let db = sea_orm::Database::connect("sqlite::memory:").await.unwrap();
+
+        let run = |label: &'static str| {
+            let db = db.clone();
+            async move {
+                let tx = Tx::begin(&db).await.unwrap();
+                tx.execute_unprepared("SELECT 1").await.unwrap();
+                tx.scoped(async { tracing::info!(label, "事务内日志") })
+                    .await;
+                let id = tx.id().to_string();
+                tx.commit().await.unwrap();
+                id
+            }
+        };
+        let (a, b) = tokio::join!(run("a"), run("b"));
+        assert_ne!(a, b);
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let tx_ids: Vec<(String, String)> = output
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter_map(|log| {
+                let label = log["fields"]["label"].as_str()?.to_string();
+                let tx_id = log["span"]["tx_id"].as_str()?.to_string();
+                Some((label, tx_id))
+            })
+            .collect();
+
+        assert!(tx_ids.contains(&("a".to_string(), a)));
+        assert!(tx_ids.contains(&("b".to_string(), b)));
+    }
+
+    #[tokio::test]
+    async fn test_run_in_transaction_commits_on_ok_and_rolls_back_on_err() {
+        // 内存 SQLite 每个连接是独立的数据库，限制为单连接才能在事务外看到结果
+        let mut options = sea_orm::ConnectOptions::new("sqlite::memory:");
+        options.max_connections(1);
+        let db = sea_orm::Database::connect(options).await.unwrap();
+        db.execute_unprepared("CREATE TABLE item (id INTEGER PRIMARY KEY)")
+            .await
+            .unwrap();
+        let count = async || {
+            db.query_one(Statement::from_string(
+                DbBackend::Sqlite,
+                "SELECT COUNT(*) AS n FROM item",
+            ))
+            .await
+            .unwrap()
+            .unwrap()
+            .try_get::<i64>("", "n")
+            .unwrap()
+        };
+
+        let inserted = run_in_transaction(&db, async |txn| {
+            txn.execute_unprepared("INSERT INTO item (id) VALUES (1)")
+                .await?;
+            Ok::<_, AppError>(1)
+        })
+        .await
+        .unwrap();
+        assert_eq!(inserted, 1);
+        assert_eq!(count().await, 1);
+
+        let err = run_in_transaction(&db, async |txn| {
+            txn.execute_unprepared("INSERT INTO item (id) VALUES (2)")
+                .await?;
+            Err::<(), _>(AppError::Auth(crate::error::AuthError::PermissionDenied))
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::Auth(crate::error::AuthError::PermissionDenied)
+        ));
+        assert_eq!(count().await, 1);
+    }
+}
@@ -0,0 +1,128 @@
+//! 带校验的 JSON 请求体提取器
+//!
+//! [`Validated<T>`] 先按 [`Json`] 反序列化请求体，再执行 `T` 上 `#[derive(Validate)]` 声明的规则。
+//! 校验失败时返回 [`AppError::ValidationMap`]，响应中按字段列出全部错误：
+//!
+//! ```ignore
+//! #[derive(Deserialize, JsonSchema, Validate)]
+//! struct SignupForm {
+//!     #[validate(email(message = "is invalid"))]
+//!     email: String,
+//! }
+//!
+//! async fn signup(Validated(form): Validated<SignupForm>) -> Result<ApiResponse<()>, AppError> {
+//!     // form 已通过校验
+//! }
+//! ```
+//!
+//! 请求体不是合法 JSON 或缺少字段时返回单条消息的 [`ValidationError`]。
+
+use aide::OperationInput;
+use aide::generate::GenContext;
+use aide::openapi::Operation;
+use axum::Json;
+use axum::extract::{FromRequest, Request};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::error::{AppError, ValidationError};
+
+/// 反序列化后执行 `validator` 规则的 JSON 请求体
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Validated<T>(pub T);
+
+impl<T, S> FromRequest<S> for Validated<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(|e| ValidationError::custom(e.body_text()))?;
+        value.validate()?;
+        Ok(Self(value))
+    }
+}
+
+impl<T: JsonSchema> OperationInput for Validated<T> {
+    fn operation_input(ctx: &mut GenContext, operation: &mut Operation) {
+        Json::<T>::operation_input(ctx, operation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{Body, to_bytes};
+    use axum::http::{StatusCode, header};
+    use axum::{Router, routing::post};
+    use serde::Deserialize;
+    use serde_json::{Value, json};
+    use tower::ServiceExt;
+
+    #[derive(Deserialize, Validate)]
+    struct SignupForm {
+        #[validate(email(message = "is invalid"))]
+        email: String,
+
+        #[validate(length(min = 8, message = "is too short"))]
+        password: String,
+    }
+
+    async fn signup(Validated(form): Validated<SignupForm>) -> String {
+        form.email
+    }
+
+    async fn post_json(body: &str) -> (StatusCode, Value) {
+        let app = Router::new().route("/signup", post(signup));
+        let response = app
+            .oneshot(
+                Request::post("/signup")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_invalid_fields_return_errors_map() {
+        let (status, body) = post_json(r#"{"email": "nope", "password": "short"}"#).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body["errors"],
+            json!({ "email": ["is invalid"], "password": ["is too short"] })
+        );
+        assert_eq!(body["error"]["code"], 400);
+        assert_eq!(body["error"]["errors"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_valid_and_malformed_bodies() {
+        let (status, _) =
+            post_json(r#"{"email": "a@example.com", "password": "long enough"}"#).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = post_json(r#"{"email": "a@example.com"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.get("errors").is_none());
+        assert!(
+            body["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("password")
+        );
+    }
+}
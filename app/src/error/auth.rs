@@ -4,6 +4,7 @@ use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use thiserror::Error;
 
+use crate::core::middleware::ErrorChain;
 use crate::response::{ApiError, ApiResponse, Domain, ErrorDetail, Reason};
 
 #[derive(Debug, Error)]
@@ -29,16 +30,32 @@ pub enum AuthError {
     #[error("用户已被停用")]
     UserInactive,
 
+    #[error("登录失败次数过多，账户已被临时锁定")]
+    AccountLocked,
+
     #[error("无效的访问令牌")]
     InvalidToken,
 
+    #[error("无效的 API 密钥")]
+    InvalidApiKey,
+
+    #[error("无权访问该资源")]
+    PermissionDenied,
+
+    #[error("CSRF 令牌缺失或无效")]
+    CsrfTokenInvalid,
+
+    /// 内部错误，携带 `anyhow::Context` 附加的上下文链，不向客户端暴露
     #[error("内部错误: {0}")]
-    Internal(String),
+    Internal(#[from] anyhow::Error),
 }
 
-impl IntoResponse for AuthError {
-    fn into_response(self) -> Response {
-        let api_error = match self {
+impl AuthError {
+    /// 错误对应的 API 错误对象，内部错误不暴露详情
+    ///
+    /// 批量接口用它为失败的数据项生成逐项的错误信息。
+    pub fn api_error(&self) -> ApiError {
+        match self {
             Self::UserAlreadyExists => ApiError::new(StatusCode::CONFLICT, self.to_string())
                 .with_detail(ErrorDetail::new(Domain::AUTH, Reason::AlreadyExists)),
 
@@ -60,14 +77,35 @@ impl IntoResponse for AuthError {
             Self::UserInactive => ApiError::new(StatusCode::FORBIDDEN, self.to_string())
                 .with_detail(ErrorDetail::new(Domain::AUTH, Reason::AuthenticationFailed)),
 
+            Self::AccountLocked => ApiError::new(StatusCode::LOCKED, self.to_string())
+                .with_detail(ErrorDetail::new(Domain::AUTH, Reason::AccountLocked)),
+
             Self::InvalidToken => ApiError::new(StatusCode::UNAUTHORIZED, self.to_string())
                 .with_detail(ErrorDetail::new(Domain::AUTH, Reason::InvalidToken)),
 
-            Self::Internal(ref msg) => {
-                tracing::error!(error = %msg, "auth internal error");
+            Self::InvalidApiKey => ApiError::new(StatusCode::UNAUTHORIZED, self.to_string())
+                .with_detail(ErrorDetail::new(Domain::AUTH, Reason::InvalidToken)),
+
+            Self::PermissionDenied => ApiError::new(StatusCode::FORBIDDEN, self.to_string())
+                .with_detail(ErrorDetail::new(Domain::AUTH, Reason::PermissionDenied)),
+
+            Self::CsrfTokenInvalid => ApiError::new(StatusCode::FORBIDDEN, self.to_string())
+                .with_detail(ErrorDetail::new(Domain::AUTH, Reason::CsrfTokenInvalid)),
+
+            Self::Internal(_) => {
                 ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
             }
-        };
-        ApiResponse::error(api_error).into_response()
+        }
+    }
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        let response = ApiResponse::error(self.api_error()).into_response();
+        match self {
+            // 完整的上下文链由 error_context 中间件记录
+            Self::Internal(ref e) => ErrorChain::attach(e, response),
+            _ => response,
+        }
     }
 }
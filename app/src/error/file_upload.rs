@@ -1,7 +1,8 @@
-//! 文件上传相关错误
+//! 文件上传和下载相关错误
 
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
+use entity::enums::ScanStatus;
 use thiserror::Error;
 
 use crate::response::{ApiError, ApiResponse, Domain, ErrorDetail, Reason};
@@ -17,23 +18,40 @@ pub enum FileUploadError {
     #[error("文件类型不允许: {0}")]
     TypeNotAllowed(String),
 
+    #[error("文件类型与内容不符: 声明为 {claimed}，实际为 {detected}")]
+    TypeMismatch { claimed: String, detected: String },
+
     #[error("上传失败: {0}")]
     Failed(String),
 
     #[error("缺少必需字段: {0}")]
     MissingField(String),
+
+    /// 文件尚未扫描或扫描发现恶意内容，不允许下载
+    #[error("文件未通过安全扫描，暂不可下载: {0}")]
+    NotClean(ScanStatus),
+
+    /// 文件登记后所有者的已用空间将超过配额
+    #[error("存储空间不足: 已使用 {used} 字节，配额 {limit} 字节")]
+    QuotaExceeded { used: i64, limit: i64 },
 }
 
 impl IntoResponse for FileUploadError {
     fn into_response(self) -> Response {
         let api_error = match self {
+            // 请求体超过 DefaultBodyLimit 时，multipart 解析错误本身携带 413 状态
+            Self::Multipart(ref e) if e.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, self.to_string())
+                    .with_detail(ErrorDetail::new(Domain::FILE, Reason::FileTooLarge))
+            }
+
             Self::Multipart(_) => ApiError::new(StatusCode::BAD_REQUEST, self.to_string())
                 .with_detail(ErrorDetail::new(Domain::FILE, Reason::InvalidFormat)),
 
             Self::TooLarge(_) => ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, self.to_string())
                 .with_detail(ErrorDetail::new(Domain::FILE, Reason::FileTooLarge)),
 
-            Self::TypeNotAllowed(_) => {
+            Self::TypeNotAllowed(_) | Self::TypeMismatch { .. } => {
                 ApiError::new(StatusCode::UNSUPPORTED_MEDIA_TYPE, self.to_string())
                     .with_detail(ErrorDetail::new(Domain::FILE, Reason::FileTypeNotAllowed))
             }
@@ -45,7 +63,94 @@ impl IntoResponse for FileUploadError {
 
             Self::MissingField(_) => ApiError::new(StatusCode::BAD_REQUEST, self.to_string())
                 .with_detail(ErrorDetail::new(Domain::FILE, Reason::RequiredFieldMissing)),
+
+            Self::NotClean(_) => ApiError::new(StatusCode::FORBIDDEN, self.to_string())
+                .with_detail(ErrorDetail::new(Domain::FILE, Reason::FileNotClean)),
+
+            Self::QuotaExceeded { .. } => ApiError::new(StatusCode::FORBIDDEN, self.to_string())
+                .with_detail(ErrorDetail::new(Domain::FILE, Reason::StorageQuotaExceeded)),
         };
         ApiResponse::error(api_error).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        extract::{DefaultBodyLimit, Multipart},
+        http::Request,
+        routing::post,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::error::AppError;
+
+    /// 测试用的单文件大小上限（字节）
+    const LIMIT: usize = 1024;
+
+    /// 逐个读取字段，超过 [`LIMIT`] 时返回 `TooLarge`
+    async fn upload(mut multipart: Multipart) -> Result<StatusCode, AppError> {
+        while let Some(field) = multipart.next_field().await? {
+            let data = field.bytes().await?;
+            if data.len() > LIMIT {
+                return Err(FileUploadError::TooLarge(LIMIT).into());
+            }
+        }
+        Ok(StatusCode::CREATED)
+    }
+
+    fn multipart_request(size: usize) -> Request<Body> {
+        let boundary = "test-boundary";
+        let mut body = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.bin\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n"
+        )
+        .into_bytes();
+        body.extend(std::iter::repeat_n(b'x', size));
+        body.extend(format!("\r\n--{boundary}--\r\n").into_bytes());
+
+        Request::post("/upload")
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn send(router: Router, request: Request<Body>) -> (StatusCode, String) {
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    #[tokio::test]
+    async fn test_file_over_limit_returns_413_with_limit() {
+        let router = Router::new().route("/upload", post(upload));
+
+        let (status, _) = send(router.clone(), multipart_request(LIMIT)).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, body) = send(router, multipart_request(LIMIT + 1)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(body.contains(&LIMIT.to_string()));
+        assert!(body.contains("FILE_TOO_LARGE"));
+    }
+
+    #[tokio::test]
+    async fn test_body_limit_exceeded_returns_413() {
+        let router = Router::new()
+            .route("/upload", post(upload))
+            .layer(DefaultBodyLimit::max(LIMIT));
+
+        let (status, body) = send(router, multipart_request(LIMIT * 4)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(body.contains("FILE_TOO_LARGE"));
+    }
+}
@@ -8,12 +8,16 @@ mod file_upload;
 mod redis;
 mod validation;
 
+use std::collections::HashMap;
+
 use aide::OperationOutput;
-use axum::http::StatusCode;
+use axum::http::header::RETRY_AFTER;
+use axum::http::{HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use thiserror::Error;
 
-use crate::response::{ApiError, ApiResponse};
+use crate::core::middleware::ErrorChain;
+use crate::response::{ApiError, ApiResponse, Domain, Reason};
 
 pub use auth::AuthError;
 pub use config::ConfigError;
@@ -30,6 +34,10 @@ pub enum AppError {
     #[error(transparent)]
     Validation(#[from] ValidationError),
 
+    /// 按字段分组的校验错误，用于表单等需要逐字段展示错误的场景
+    #[error("请求参数校验失败")]
+    ValidationMap(HashMap<String, Vec<String>>),
+
     #[error(transparent)]
     Config(#[from] ConfigError),
 
@@ -39,6 +47,34 @@ pub enum AppError {
     #[error(transparent)]
     Redis(#[from] RedisError),
 
+    /// 通用的资源不存在错误，消息直接返回给客户端
+    #[error("{0}")]
+    NotFound(String),
+
+    /// 通用的未认证错误（401），消息直接返回给客户端
+    #[error("{0}")]
+    Unauthorized(String),
+
+    /// 通用的无权访问错误（403），消息直接返回给客户端
+    #[error("{0}")]
+    Forbidden(String),
+
+    /// 通用的资源冲突错误（409），消息直接返回给客户端
+    #[error("{0}")]
+    Conflict(String),
+
+    /// 没有对应专用变体的 HTTP 错误，消息直接返回给客户端
+    #[error("{message}")]
+    Http { status: StatusCode, message: String },
+
+    /// 依赖的服务（如数据库）暂时不可用，消息直接返回给客户端
+    #[error("{0}")]
+    ServiceUnavailable(String),
+
+    /// 调用外部服务超时（504），响应带 `Retry-After`，`service` 为返回给客户端的服务名称
+    #[error("{service} 响应超时")]
+    GatewayTimeout { service: String },
+
     #[error("数据库错误: {0}")]
     Database(#[from] sea_orm::DbErr),
 
@@ -52,9 +88,103 @@ pub enum AppError {
     Anyhow(#[from] anyhow::Error),
 }
 
+impl AppError {
+    /// 按状态码构造带消息的错误
+    ///
+    /// 404、401、403、409 分别对应 [`Self::NotFound`]、[`Self::Unauthorized`]、[`Self::Forbidden`]、
+    /// [`Self::Conflict`]，其他状态码构造 [`Self::Http`]。
+    ///
+    /// ```ignore
+    /// return Err(AppError::from_status(StatusCode::NOT_FOUND, "用户不存在"));
+    /// ```
+    pub fn from_status(status: StatusCode, message: &str) -> Self {
+        let message = message.to_string();
+        match status {
+            StatusCode::NOT_FOUND => Self::NotFound(message),
+            StatusCode::UNAUTHORIZED => Self::Unauthorized(message),
+            StatusCode::FORBIDDEN => Self::Forbidden(message),
+            StatusCode::CONFLICT => Self::Conflict(message),
+            status => Self::Http { status, message },
+        }
+    }
+
+    /// 转换调用外部服务时的 HTTP 客户端错误
+    ///
+    /// 超时（[`reqwest::Error::is_timeout`]）转换为 [`Self::GatewayTimeout`]，其他错误作为内部错误。
+    ///
+    /// ```ignore
+    /// let response = state
+    ///     .http_client
+    ///     .get(url)
+    ///     .send()
+    ///     .await
+    ///     .map_err(|e| AppError::from_reqwest("支付服务", e))?;
+    /// ```
+    pub fn from_reqwest(service: &str, err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            Self::GatewayTimeout {
+                service: service.to_string(),
+            }
+        } else {
+            Self::Anyhow(anyhow::Error::new(err).context(format!("调用 {service} 失败")))
+        }
+    }
+}
+
+/// 外部服务超时（504）响应的 `Retry-After` 秒数
+const GATEWAY_TIMEOUT_RETRY_AFTER_SECS: u32 = 5;
+
+/// PostgreSQL 因 `statement_timeout` 取消语句时的 SQLSTATE（query_canceled）
+const PG_QUERY_CANCELED: &str = "57014";
+
+/// PostgreSQL 因 `idle_in_transaction_session_timeout` 关闭会话时的 SQLSTATE
+const PG_IDLE_IN_TRANSACTION_TIMEOUT: &str = "25P03";
+
+/// 识别连接池获取超时和数据库侧的超时，返回用于日志的说明
+///
+/// 这类错误说明连接池已被慢查询或长事务占满，单独记录 WARN 便于在 500 错误日志之外发现趋势。
+fn db_timeout(err: &sea_orm::DbErr) -> Option<&'static str> {
+    use sea_orm::{ConnAcquireErr, DbErr, RuntimeErr, sqlx};
+
+    match err {
+        DbErr::ConnectionAcquire(ConnAcquireErr::Timeout) => {
+            Some("获取数据库连接超时，连接池已耗尽")
+        }
+        DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Database(e)))
+        | DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Database(e))) => {
+            match e.code().as_deref() {
+                Some(PG_QUERY_CANCELED) => {
+                    Some("SQL 语句执行超时（statement_timeout），已被数据库取消")
+                }
+                Some(PG_IDLE_IN_TRANSACTION_TIMEOUT) => {
+                    Some("事务空闲超时（idle_in_transaction_session_timeout），会话已被数据库关闭")
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 错误（或其原因链中的数据库错误）是超时时记录 WARN，返回是否记录
+fn warn_on_db_timeout(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(reason) = err.downcast_ref::<sea_orm::DbErr>().and_then(db_timeout) {
+            tracing::warn!(error = %err, "{reason}");
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        match self {
+        #[cfg(feature = "sentry")]
+        let event = sentry::event_from_error(&self);
+
+        let response = match self {
             // 委托给具体错误类型
             Self::Auth(e) => e.into_response(),
             Self::Validation(e) => e.into_response(),
@@ -62,7 +192,78 @@ impl IntoResponse for AppError {
             Self::FileUpload(e) => e.into_response(),
             Self::Redis(e) => e.into_response(),
 
+            Self::NotFound(message) => ApiResponse::fail_with_message(
+                StatusCode::NOT_FOUND,
+                Domain::GLOBAL,
+                Reason::NotFound,
+                message,
+            )
+            .into_response(),
+
+            Self::Unauthorized(message) => ApiResponse::fail_with_message(
+                StatusCode::UNAUTHORIZED,
+                Domain::AUTH,
+                Reason::AuthenticationFailed,
+                message,
+            )
+            .into_response(),
+
+            Self::Forbidden(message) => ApiResponse::fail_with_message(
+                StatusCode::FORBIDDEN,
+                Domain::AUTH,
+                Reason::PermissionDenied,
+                message,
+            )
+            .into_response(),
+
+            Self::Conflict(message) => ApiResponse::fail_with_message(
+                StatusCode::CONFLICT,
+                Domain::GLOBAL,
+                Reason::Conflict,
+                message,
+            )
+            .into_response(),
+
+            Self::Http { status, message } => {
+                if status.is_server_error() {
+                    tracing::error!(%status, error = %message, "http error");
+                }
+                ApiResponse::error(ApiError::new(status, message)).into_response()
+            }
+
+            Self::ServiceUnavailable(message) => {
+                tracing::warn!(error = %message, "service unavailable");
+                ApiResponse::fail_with_message(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Domain::GLOBAL,
+                    Reason::ServiceUnavailable,
+                    message,
+                )
+                .into_response()
+            }
+
+            Self::GatewayTimeout { service } => {
+                tracing::warn!(%service, "upstream service timed out");
+                let mut response = ApiResponse::fail_with_message(
+                    StatusCode::GATEWAY_TIMEOUT,
+                    Domain::GLOBAL,
+                    Reason::Timeout,
+                    format!("{service} 响应超时，请稍后重试"),
+                )
+                .into_response();
+                response.headers_mut().insert(
+                    RETRY_AFTER,
+                    HeaderValue::from(GATEWAY_TIMEOUT_RETRY_AFTER_SECS),
+                );
+                response
+            }
+
+            Self::ValidationMap(errors) => {
+                ApiResponse::validation_failed(errors.into_iter().collect()).into_response()
+            }
+
             Self::Database(e) => {
+                let _ = warn_on_db_timeout(&e);
                 tracing::error!(error = %e, "database error");
                 ApiResponse::error(ApiError::new(
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -89,15 +290,26 @@ impl IntoResponse for AppError {
                 .into_response()
             }
 
+            // 完整的上下文链由 error_context 中间件记录
             Self::Anyhow(e) => {
-                tracing::error!(error = %e, "internal error");
-                ApiResponse::error(ApiError::new(
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error",
-                ))
-                .into_response()
+                let _ = warn_on_db_timeout(e.as_ref());
+                ErrorChain::attach(
+                    &e,
+                    ApiResponse::error(ApiError::new(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Internal server error",
+                    ))
+                    .into_response(),
+                )
             }
+        };
+
+        #[cfg(feature = "sentry")]
+        if response.status().is_server_error() {
+            sentry::capture_event(event);
         }
+
+        response
     }
 }
 
@@ -115,10 +327,152 @@ impl From<deadpool_redis::CreatePoolError> for AppError {
 
 impl From<validator::ValidationErrors> for AppError {
     fn from(e: validator::ValidationErrors) -> Self {
-        Self::Validation(ValidationError::from_validator(e))
+        Self::ValidationMap(validation::field_messages(&e))
     }
 }
 
 impl OperationOutput for AppError {
     type Inner = ();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::assert_api_error;
+    use anyhow::Context;
+    use sea_orm::{ConnAcquireErr, DbErr, RuntimeErr, sqlx};
+    use std::borrow::Cow;
+
+    /// 只带 SQLSTATE 的数据库错误
+    #[derive(Debug)]
+    struct PgError(&'static str);
+
+    impl std::fmt::Display for PgError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+
+    impl std::error::Error for PgError {}
+
+    impl sqlx::error::DatabaseError for PgError {
+        fn message(&self) -> &str {
+            "canceling statement"
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+
+    fn query_error(code: &'static str) -> DbErr {
+        DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::Database(Box::new(
+            PgError(code),
+        ))))
+    }
+
+    #[test]
+    fn test_db_timeouts_are_recognized() {
+        assert!(db_timeout(&DbErr::ConnectionAcquire(ConnAcquireErr::Timeout)).is_some());
+        assert!(db_timeout(&query_error(PG_QUERY_CANCELED)).is_some());
+        assert!(db_timeout(&query_error(PG_IDLE_IN_TRANSACTION_TIMEOUT)).is_some());
+        assert!(db_timeout(&query_error("23505")).is_none());
+        assert!(db_timeout(&DbErr::ConnectionAcquire(ConnAcquireErr::ConnectionClosed)).is_none());
+
+        // 经 anyhow 附加上下文后仍能识别
+        let err = Err::<(), _>(query_error(PG_QUERY_CANCELED))
+            .context("查询用户失败")
+            .unwrap_err();
+        assert!(warn_on_db_timeout(err.as_ref()));
+        let err = anyhow::anyhow!("其他错误");
+        assert!(!warn_on_db_timeout(err.as_ref()));
+    }
+
+    #[tokio::test]
+    async fn test_from_status_picks_specific_variant() {
+        let cases = [
+            (StatusCode::NOT_FOUND, "NOT_FOUND"),
+            (StatusCode::UNAUTHORIZED, "AUTHENTICATION_FAILED"),
+            (StatusCode::FORBIDDEN, "PERMISSION_DENIED"),
+            (StatusCode::CONFLICT, "CONFLICT"),
+        ];
+        for (status, reason) in cases {
+            let err = AppError::from_status(status, "用户不存在");
+            assert!(!matches!(err, AppError::Http { .. }), "{status}");
+            assert_api_error(err.into_response(), status, reason, "用户不存在").await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reqwest_timeout_maps_to_gateway_timeout() {
+        // 接受连接但从不响应的上游服务
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_millis(50))
+            .build()
+            .unwrap();
+        let err = client
+            .get(format!("http://{addr}/"))
+            .send()
+            .await
+            .unwrap_err();
+        server.abort();
+
+        let err = AppError::from_reqwest("支付服务", err);
+        assert!(matches!(&err, AppError::GatewayTimeout { service } if service == "支付服务"));
+
+        let response = err.into_response();
+        assert_eq!(response.headers()[RETRY_AFTER], "5");
+        assert_api_error(
+            response,
+            StatusCode::GATEWAY_TIMEOUT,
+            "TIMEOUT",
+            "支付服务 响应超时，请稍后重试",
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_from_status_falls_back_to_http() {
+        let err = AppError::from_status(StatusCode::GONE, "链接已失效");
+        assert!(matches!(
+            err,
+            AppError::Http {
+                status: StatusCode::GONE,
+                ..
+            }
+        ));
+
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::GONE);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: ApiResponse<serde_json::Value> = serde_json::from_slice(&bytes).unwrap();
+        let error = body.error.unwrap();
+        assert_eq!(error.code, 410);
+        assert_eq!(error.message, "链接已失效");
+    }
+}
@@ -1,5 +1,7 @@
 //! 验证相关错误
 
+use std::collections::HashMap;
+
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use thiserror::Error;
@@ -8,45 +10,37 @@ use crate::response::{ApiError, ApiResponse, Domain, ErrorDetail, Reason};
 
 #[derive(Debug, Error)]
 pub enum ValidationError {
-    #[error("{0}")]
-    Fields(String),
-
     #[error("{0}")]
     Custom(String),
 }
 
 impl ValidationError {
-    /// 从 validator::ValidationErrors 创建
-    pub fn from_validator(errors: validator::ValidationErrors) -> Self {
-        let messages: Vec<String> = errors
-            .field_errors()
-            .iter()
-            .map(|(field, errs)| {
-                let msgs = errs
-                    .iter()
-                    .filter_map(|e| e.message.as_ref())
-                    .map(|m| m.to_string())
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!(
-                    "{}: {}",
-                    field,
-                    if msgs.is_empty() {
-                        "验证失败"
-                    } else {
-                        &msgs
-                    }
-                )
-            })
-            .collect();
-        Self::Fields(messages.join("; "))
-    }
-
     pub fn custom(msg: impl Into<String>) -> Self {
         Self::Custom(msg.into())
     }
 }
 
+/// 将 validator::ValidationErrors 按字段整理为 `字段 → 错误消息列表`
+///
+/// 未设置 `message` 的规则使用其错误码（如 `length`、`email`）作为消息。
+pub fn field_messages(errors: &validator::ValidationErrors) -> HashMap<String, Vec<String>> {
+    errors
+        .field_errors()
+        .into_iter()
+        .map(|(field, errs)| {
+            let messages = errs
+                .iter()
+                .map(|e| {
+                    e.message
+                        .as_ref()
+                        .map_or_else(|| e.code.to_string(), |m| m.to_string())
+                })
+                .collect();
+            (field.to_string(), messages)
+        })
+        .collect()
+}
+
 impl IntoResponse for ValidationError {
     fn into_response(self) -> Response {
         let api_error = ApiError::new(StatusCode::BAD_REQUEST, self.to_string()).with_detail(
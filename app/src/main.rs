@@ -8,6 +8,9 @@ mod modules;
 mod routes;
 /// 共享工具模块（JWT、密码等）
 mod shared;
+/// 测试辅助工具
+#[cfg(test)]
+mod test_support;
 
 pub use core::*;
 pub use error::*;
@@ -15,25 +18,31 @@ pub use modules::*;
 pub use routes::v1;
 
 use aide::axum::{ApiRouter, IntoApiResponse};
-use aide::openapi::{OpenApi, Tag};
+use aide::openapi::OpenApi;
 use aide::transform::TransformOpenApi;
-use axum::body::Body;
 use axum::error_handling::HandleErrorLayer;
-use axum::http::Request;
-use axum::http::StatusCode;
+use axum::extract::ConnectInfo;
+use axum::extract::DefaultBodyLimit;
 use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
+use axum::serve::ListenerExt;
 use axum::{BoxError, Extension, routing::get};
-use migration::{Migrator, MigratorTrait};
+use clap::Parser;
+use futures_util::future::BoxFuture;
 use serde_json::{Value, json};
+use std::future::IntoFuture;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::signal;
 use tower::ServiceBuilder;
 use tower::buffer::BufferLayer;
-use tower_governor::{GovernorLayer, governor::GovernorConfigBuilder};
+use tower_governor::governor::GovernorConfigBuilder;
 use tower_http::compression::CompressionLayer;
-use tower_http::services::ServeDir;
 use tower_http::trace::TraceLayer;
-use tracing::{Level, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
 
 /// 健康检查端点
 ///
@@ -46,21 +55,122 @@ async fn health_check() -> ApiResponse<Value> {
     }))
 }
 
-/// Hello World 测试端点
+/// 就绪检查端点
 ///
-/// 返回一条简单的问候消息，用于测试服务器是否正常响应。
-#[instrument]
-async fn hello_world() -> ApiResponse<Value> {
-    info!("Hello World 请求");
-    ApiResponse::success(json!({
-        "message": "Hello, World!"
-    }))
+/// 探测数据库和 Redis，全部可用时返回 200，否则返回 503。
+/// 结果按 `server.readiness_cache_ttl_ms` 缓存，供负载均衡器高频轮询。
+/// 按 `health` 配置限制访问时，未通过校验的调用方只得到不带响应体的状态码。
+#[instrument(skip(state, headers))]
+async fn readiness_check(
+    axum::extract::State(state): axum::extract::State<Arc<AppState>>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+) -> Response {
+    let readiness = state.readiness().await;
+    let peer = connect_info.map(|Extension(ConnectInfo(addr))| addr.ip());
+    state
+        .health_guard
+        .readiness_response(readiness, &headers, peer)
+}
+
+/// 命令行参数
+///
+/// 优先级高于配置文件和 `APP_*` 环境变量（见 [`AppConfig::load`]），
+/// 便于 Docker 等部署方式直接通过启动参数覆盖配置，如 `app --port 8080`。
+#[derive(Debug, Parser)]
+#[command(version, about = "Axum + SeaORM 应用服务器", long_about = None)]
+struct Cli {
+    /// 配置文件路径，指定后只加载该文件，替代 config/ 目录下的分层配置文件
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "HOST",
+        help = format!("监听地址 [默认: {}]", AppConfig::default().server.host)
+    )]
+    host: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PORT",
+        help = format!("监听端口 [默认: {}]", AppConfig::default().server.port)
+    )]
+    port: Option<u16>,
+
+    #[arg(
+        long,
+        value_name = "LEVEL",
+        value_parser = ["trace", "debug", "info", "warn", "error"],
+        help = format!("日志级别 [默认: {}]", AppConfig::default().logging.level)
+    )]
+    log_level: Option<String>,
+
+    /// 生成 OpenAPI 文档写入该文件后退出，不启动服务器，也不连接数据库和 Redis
+    #[arg(long, value_name = "PATH")]
+    export_openapi: Option<PathBuf>,
+
+    /// 导出文档的格式
+    #[arg(long, value_enum, default_value_t, requires = "export_openapi")]
+    format: OpenApiFormat,
+}
+
+impl From<Cli> for ConfigOverrides {
+    fn from(cli: Cli) -> Self {
+        Self {
+            config_file: cli.config,
+            host: cli.host,
+            port: cli.port,
+            log_level: cli.log_level,
+            offline: cli.export_openapi.is_some(),
+        }
+    }
 }
 
 /// 应用程序主入口点
 ///
+/// 解析命令行参数并加载、验证配置，再按 `performance` 配置构建 Tokio 运行时，在其中运行 [`run`]。
+///
+/// # 返回
+/// 正常退出返回 Ok(())，发生错误返回 AppError
+fn main() -> Result<(), AppError> {
+    // 解析命令行参数并加载配置（--help / --version 在此输出后退出）
+    let cli = Cli::parse();
+    let export = cli.export_openapi.clone().map(|path| (path, cli.format));
+    let config = AppConfig::load(&cli.into())?;
+    if let Some((path, format)) = export {
+        return export_openapi(&config, &path, format);
+    }
+    let runtime = config.performance.runtime_builder().build()?;
+    runtime.block_on(run(config))
+}
+
+/// 生成 OpenAPI 文档并写入 `path`（`--export-openapi`）
+///
+/// 路由由未连接数据库、不使用 Redis 的应用状态构建，文档与服务启动时生成的一致，
+/// 只是不包含文档页面本身的路由，输出不随 `docs.enabled` 变化。
+fn export_openapi(config: &AppConfig, path: &Path, format: OpenApiFormat) -> Result<(), AppError> {
+    let mut config = config.clone();
+    config.docs.enabled = Some(false);
+
+    let (_layer, log_level) = LogLevelHandle::new(tracing_subscriber::EnvFilter::new("off"));
+    let app_state = Arc::new(AppState::with_connections(
+        &config,
+        log_level,
+        sea_orm::DatabaseConnection::Disconnected,
+        None,
+    )?);
+
+    aide::generate::on_error(|error| eprintln!("{error}"));
+    let (_, api) = build_api(&config, &app_state);
+    let content = render_openapi(&api, format).map_err(|e| anyhow::anyhow!(e))?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// 启动服务
+///
 /// 负责以下初始化工作：
-/// - 加载和验证配置
 /// - 初始化日志系统
 /// - 建立数据库连接并执行迁移
 /// - 初始化应用状态（包括Redis连接）
@@ -69,29 +179,43 @@ async fn hello_world() -> ApiResponse<Value> {
 ///
 /// # 返回
 /// 正常退出返回 Ok(())，发生错误返回 AppError
-#[tokio::main]
-async fn main() -> Result<(), AppError> {
-    // 加载配置
-    let config = AppConfig::load()?;
-    // 初始化 tracing 日志系统
-    config.init_tracing()?;
+async fn run(config: AppConfig) -> Result<(), AppError> {
+    // 初始化 tracing 日志系统（守卫需保持到进程退出，否则缓冲中的日志会丢失）
+    let log_guard = config.init_tracing()?;
 
-    // sea-orm 数据库连接和自动迁移
-    let connection = sea_orm::Database::connect(&config.database.url).await?;
-    Migrator::up(&connection, None).await?;
+    // 输出连接目标（不含账号和密码），URL 无法解析时交给驱动报告连接错误
+    match config.database.target() {
+        Ok(target) => info!(
+            scheme = %target.scheme,
+            host = target.host.as_deref().unwrap_or("-"),
+            port = target.port,
+            database = target.database.as_deref().unwrap_or("-"),
+            max_connections = config.database.max_connections,
+            "连接数据库 {target}"
+        ),
+        Err(e) => warn!(error = %e, "无法解析数据库 URL"),
+    }
 
-    // 初始化 API 文档生成
-    aide::generate::on_error(|error| println!("{error}"));
-    aide::generate::extract_schemas(true);
+    // sea-orm 数据库连接和自动迁移
+    let connection = sea_orm::Database::connect(config.database.connection_url()).await?;
+    core::migrate::run_migrations(&connection, &config.database).await?;
 
-    // 输出启动信息
+    // 输出启动信息与脱敏后的配置摘要
     info!("🚀 应用启动");
-    info!("服务器地址: {}", config.server_addr());
-    info!("数据库连接池: {} 个连接", config.database.max_connections);
-    info!("日志级别: {}", config.logging.level);
+    info!(
+        workers = tokio::runtime::Handle::current().metrics().num_workers(),
+        max_blocking_threads = config.performance.blocking_threads,
+        "Tokio 运行时已启动"
+    );
+    // 交互式终端输出对齐的表格；重定向到文件、管道时输出结构化字段，便于日志系统解析
+    if config.logging.console_is_terminal() {
+        debug!("启动配置摘要:\n{}", config.to_display_table());
+    } else {
+        debug!(config = %config.summary().to_json(), "启动配置摘要");
+    }
 
     // 初始化应用状态（包含数据库连接、Redis 连接池等）
-    let app_state = Arc::new(AppState::init(&config).await?);
+    let app_state = Arc::new(AppState::init(&config, log_guard.log_level()).await?);
 
     // 输出 Redis 连接状态
     if app_state.redis.is_some() {
@@ -120,53 +244,95 @@ async fn main() -> Result<(), AppError> {
         }
     }
 
-    // 构建路由
-    let mut api = OpenApi::default();
-
-    // 构建基础路由
-    let mut app = ApiRouter::new()
-        .nest_service("/static", ServeDir::new("app/assets"))
-        .route("/health", get(health_check))
-        .route("/", get(hello_world))
-        .route("/favicon.ico", get(favicon))
-        .nest_api_service("/v1", v1::routes(app_state.clone()));
-
-    // 只在 debug 模式下添加 API 文档路由
-    if config.logging.level == "debug" {
-        app = app.nest_api_service("/docs", docs_routes(&app_state));
+    // 启动过期分片上传会话的清理任务和上传文件的扫描任务
+    if config.features.file_upload_enabled {
+        modules::file::spawn_session_cleanup(app_state.clone());
+        modules::file::spawn_scan_worker(app_state.clone());
+        modules::file::spawn_blob_worker(app_state.clone());
+        modules::file::spawn_thumbnail_worker(app_state.clone());
     }
 
+    // 构建路由并生成 API 文档
+    aide::generate::on_error(|error| println!("{error}"));
+    let (app, api) = build_api(&config, &app_state);
+
     // 配置 CORS
     let cors_layer = build_cors_layer(&config.cors)?;
-    info!(
-        "🌐 CORS 配置：允许源 {:?}，允许凭证 {}",
-        config.cors.allow_origins, config.cors.allow_credentials
-    );
 
     // 配置速率限制
     // 注意：在本地开发环境中，SmartIpKeyExtractor 可能无法正确提取 IP 地址
     // 生产环境中，确保配置了正确的 ConnectInfo 中间件
-    let general_limiter = Arc::new(
+    let general_limiter: Arc<GlobalRateLimit> = Arc::new(
         GovernorConfigBuilder::default()
-            .per_second(10)
-            .burst_size(20)
+            .per_second(GLOBAL_RATE_LIMIT_PERIOD_SECS)
+            .burst_size(GLOBAL_RATE_LIMIT_BURST)
             .use_headers()
             .finish()
             .unwrap(),
     );
-    info!("⚡ 速率限制已启用: 每秒10个请求，突发20个请求");
+
+    // 错误突增告警（未配置 webhook 时为空操作）
+    let error_metrics =
+        core::alerting::spawn_watcher(&config.alerting, app_state.http_client.clone());
 
     // 应用所有中间件
     let app = app
-        .finish_api_with(&mut api, api_docs)
-        .fallback(handle_404)
+        // 静态文件与回退处理（404 或单页应用 index.html）
+        .merge(static_files::routes(&config))
+        // 全局请求体大小上限，server.route_body_limits 中的路由覆盖位于其内侧，优先生效
+        .layer(DefaultBodyLimit::max(config.server.body_limit_bytes));
+
+    // 携带 Idempotency-Key 的 POST / PATCH 请求重放首次响应（需要 Redis）
+    let app = match &app_state.redis {
+        Some(pool) => app.layer(middleware::IdempotencyLayer::new(pool.clone())),
+        None => app,
+    };
+
+    // 表单请求的 CSRF 防护（携带有效 JWT 或 API 密钥的请求不受影响）
+    let app = match &config.secrets.session_secret {
+        Some(secret) if config.server.csrf_enabled => app.layer(middleware::CsrfLayer::new(
+            secret,
+            app_state.jwt_service.clone(),
+            app_state.api_keys.clone(),
+        )),
+        _ => app,
+    };
+
+    // 维护模式：开启后健康检查和维护开关接口以外的请求返回 503
+    let app = app.layer(middleware::MaintenanceModeLayer::new(
+        app_state.maintenance.clone(),
+    ));
+
+    // 全局方法白名单：TRACE、CONNECT 等未允许的方法在路由之前返回 405
+    let app = app.layer(middleware::MethodFilterLayer::new(
+        config
+            .server
+            .allowed_methods()
+            .map_err(ConfigError::Invalid)?,
+    ));
+
+    // Sentry 请求上下文（request_id、route 标签），位于所有全局中间件内层
+    #[cfg(feature = "sentry")]
+    let app = app.layer(axum::middleware::from_fn(
+        core::error_reporting::sentry_context_middleware,
+    ));
+
+    let app = app
         .layer(
             ServiceBuilder::new()
                 // CORS 跨域配置
                 .layer(cors_layer)
                 // 基于 IP 的速率限制
-                .layer(GovernorLayer::new(general_limiter))
-                // 错误处理层（处理 GovernorLayer 和其他中间件的错误）
+                .layer(axum::middleware::from_fn_with_state(
+                    general_limiter,
+                    rate_limit_middleware,
+                ))
+                // 错误指标采集（供告警使用，位于错误处理层外侧以统计所有 5xx）
+                .layer(axum::middleware::from_fn_with_state(
+                    error_metrics,
+                    core::alerting::error_metrics_middleware,
+                ))
+                // 错误处理层（处理缓冲层等中间件的错误）
                 .layer(HandleErrorLayer::new(|err: BoxError| async move {
                     (
                         StatusCode::INTERNAL_SERVER_ERROR,
@@ -177,56 +343,165 @@ async fn main() -> Result<(), AppError> {
                 .layer(BufferLayer::new(1024))
                 // HTTP 响应压缩（gzip/deflate/brotli）
                 .layer(CompressionLayer::new())
-                // 请求 ID 中间件（用于追踪）
-                .layer(axum::middleware::from_fn(middleware::request_id_middleware))
-                // 请求追踪和日志
+                // 请求 ID 中间件（用于追踪，内部路由沿用上游传入的请求 ID）
+                .layer(axum::middleware::from_fn_with_state(
+                    middleware::RequestIdPolicy::new(&config.request_id),
+                    middleware::request_id_middleware,
+                ))
+                // 请求追踪和访问日志（状态码、响应大小、耗时）
                 .layer(
-                    TraceLayer::new_for_http().make_span_with(|request: &Request<Body>| {
-                        let request_id = request
-                            .headers()
-                            .get("x-request-id")
-                            .and_then(|v| v.to_str().ok())
-                            .unwrap_or("unknown");
-                        tracing::span!(
-                            Level::DEBUG,
-                            "request",
-                            method = display(request.method()),
-                            uri = display(request.uri()),
-                            version = debug(request.version()),
-                            request_id = request_id
-                        )
-                    }),
-                ),
+                    TraceLayer::new_for_http()
+                        .make_span_with(middleware::trace::make_span)
+                        .on_response(middleware::trace::on_response),
+                )
+                // 记录请求行供访问日志使用（需位于 TraceLayer 内层）
+                .layer(axum::middleware::from_fn(
+                    middleware::trace::request_line_middleware,
+                ))
+                // 记录内部错误的上下文链（需位于 TraceLayer 内层以携带请求字段）
+                .layer(axum::middleware::from_fn(
+                    middleware::error_context_middleware,
+                )),
         )
         .layer(Extension(Arc::new(api)))
         .with_state(app_state);
 
     // 绑定监听地址
-    let listener = tokio::net::TcpListener::bind(&config.server_addr()).await?;
-    info!("🎯 服务器启动在: http://{}", config.server_addr());
+    let listener = bind(&config.server_addr()).await?;
 
-    // 优雅关闭处理
-    axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
-    )
-    .with_graceful_shutdown(shutdown_signal())
-    .await
-    .map_err(|e| {
+    // 优雅关闭处理：收到信号后最多等待 graceful_shutdown_timeout_secs 排空在途请求
+    let (signal_tx, signal_rx) = tokio::sync::oneshot::channel();
+    let mut server = serve(&config, listener, app, async move {
+        let reason = shutdown_signal().await;
+        let _ = signal_tx.send(reason);
+    })
+    .await?;
+
+    let drain_timeout = Duration::from_secs(config.server.graceful_shutdown_timeout_secs);
+    let (result, reason) = tokio::select! {
+        result = &mut server => (result, None),
+        Ok(reason) = signal_rx => match tokio::time::timeout(drain_timeout, &mut server).await {
+            Ok(result) => (result, Some(reason)),
+            Err(_) => {
+                warn!(
+                    reason = reason.code(),
+                    timeout_secs = drain_timeout.as_secs(),
+                    "优雅关闭超时，强制退出"
+                );
+                // process::exit 不会执行析构函数，先释放日志守卫以刷新缓冲中的日志
+                drop(log_guard);
+                std::process::exit(1);
+            }
+        },
+    };
+    result.map_err(|e| {
         error!("服务器错误: {}", e);
         AppError::Io(e)
     })?;
-    info!("🛑 服务器已优雅关闭");
+    match reason {
+        Some(reason) => info!(
+            reason = reason.code(),
+            "🛑 服务器已优雅关闭（{}）",
+            reason.message()
+        ),
+        None => warn!("🛑 服务器在未收到关闭信号的情况下退出"),
+    }
     Ok(())
 }
 
+/// 绑定 TCP 监听地址，失败时记录带排查提示的错误日志
+async fn bind(addr: &str) -> std::io::Result<tokio::net::TcpListener> {
+    tokio::net::TcpListener::bind(addr)
+        .await
+        .inspect_err(|e| error!("{}", bind_error_message(addr, e)))
+}
+
+/// 按 `tls.enabled` 以 HTTP 或 HTTPS 提供服务，`shutdown` 完成后开始优雅关闭
+///
+/// 接入的每个连接按 `performance` 配置设置 `TCP_NODELAY` 和 keepalive。
+/// 启用 HTTPS 且配置了 `server.redirect_http` 时，额外监听 HTTP 端口并重定向到 HTTPS。
+async fn serve(
+    config: &AppConfig,
+    listener: tokio::net::TcpListener,
+    app: axum::Router,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<BoxFuture<'static, std::io::Result<()>>, AppError> {
+    let make_service = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+    let performance = config.performance.clone();
+    let tune = move |tcp: &mut tokio::net::TcpStream| {
+        if let Err(e) = performance.tune_stream(tcp) {
+            debug!(error = %e, "设置 TCP 连接选项失败");
+        }
+    };
+
+    #[cfg(feature = "tls")]
+    if config.tls.enabled {
+        let tls_config = core::tls::load_server_config(&config.tls)?;
+        if config.server.redirect_http {
+            let addr = format!(
+                "{}:{}",
+                config.server.host, config.server.redirect_http_port
+            );
+            let redirect = bind(&addr).await?;
+            let router = core::tls::redirect_router(config.server.port);
+            tokio::spawn(async move { axum::serve(redirect, router).await });
+            info!("↪️ HTTP 重定向监听在: http://{addr}");
+        }
+        // tap_io 包装后 ConnectInfo<SocketAddr> 才能用于自定义监听器
+        let listener =
+            core::tls::TlsListener::new(listener.tap_io(tune), tls_config)?.tap_io(|_| {});
+        info!("🎯 服务器启动在: https://{}", config.server_addr());
+        return Ok(Box::pin(
+            axum::serve(listener, make_service)
+                .with_graceful_shutdown(shutdown)
+                .into_future(),
+        ));
+    }
+
+    info!("🎯 服务器启动在: http://{}", config.server_addr());
+    Ok(Box::pin(
+        axum::serve(listener.tap_io(tune), make_service)
+            .with_graceful_shutdown(shutdown)
+            .into_future(),
+    ))
+}
+
+/// 服务器关闭原因
+///
+/// 由 [`shutdown_signal`] 返回并在 `main` 结束时记录，便于事后排查进程为何退出。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShutdownReason {
+    /// 收到 Ctrl+C (SIGINT)
+    Interrupt,
+    /// 收到 SIGTERM（如 `docker stop`、Kubernetes 滚动更新）
+    Terminate,
+}
+
+impl ShutdownReason {
+    /// 机器可读的原因标识，作为日志的 `reason` 字段
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Interrupt => "sigint",
+            Self::Terminate => "sigterm",
+        }
+    }
+
+    /// 面向运维人员的原因描述
+    fn message(&self) -> &'static str {
+        match self {
+            Self::Interrupt => "收到 Ctrl+C (SIGINT) 信号",
+            Self::Terminate => "收到 SIGTERM 信号",
+        }
+    }
+}
+
 /// 监听系统关闭信号
 ///
-/// 等待 Ctrl+C (SIGINT) 或 SIGTERM 信号，触发时返回。
+/// 等待 Ctrl+C (SIGINT) 或 SIGTERM 信号，触发时返回对应的 [`ShutdownReason`]。
 /// 支持跨平台：
 /// - Unix系统：监听 SIGTERM 和 SIGINT
 /// - Windows系统：仅监听 Ctrl+C (SIGINT)
-async fn shutdown_signal() {
+async fn shutdown_signal() -> ShutdownReason {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -244,14 +519,31 @@ async fn shutdown_signal() {
     #[cfg(not(unix))]
     let terminate = std::future::pending::<()>();
 
-    tokio::select! {
-        _ = ctrl_c => {
-            info!("🔄 收到 Ctrl+C 信号，开始优雅关闭...");
-        },
-        _ = terminate => {
-            info!("🔄 收到 SIGTERM 信号，开始优雅关闭...");
-        },
+    let reason = tokio::select! {
+        _ = ctrl_c => ShutdownReason::Interrupt,
+        _ = terminate => ShutdownReason::Terminate,
+    };
+    info!(
+        reason = reason.code(),
+        "🔄 {}，开始优雅关闭...",
+        reason.message()
+    );
+    reason
+}
+
+/// 生成监听地址绑定失败时的日志消息
+///
+/// 端口被占用（`AddrInUse`）时给出排查提示，其他错误原样输出。
+fn bind_error_message(addr: &str, err: &std::io::Error) -> String {
+    if err.kind() != std::io::ErrorKind::AddrInUse {
+        return format!("无法绑定监听地址 {addr}: {err}");
     }
+
+    let port = addr.rsplit_once(':').map_or(addr, |(_, port)| port);
+    format!(
+        "无法绑定监听地址 {addr}: 端口 {port} 可能已被其他进程占用，\
+         请停止占用该端口的进程（如 `lsof -i :{port}`）或通过 APP_SERVER_PORT 更换端口"
+    )
 }
 
 /// 获取网站图标
@@ -268,6 +560,46 @@ async fn favicon() -> impl IntoApiResponse {
 //     ([(CONTENT_TYPE, "text/plain")], robots.as_bytes())
 // }
 
+/// 构建业务路由并生成 OpenAPI 文档
+///
+/// 包括健康检查、V1 API，以及启用文档时挂载在 `docs.path` 下的文档路由；不含静态文件回退和全局中间件。
+/// 启用文档且开启 `docs.preload` 时，同时把序列化后的文档写入 [`AppState::openapi`]。
+///
+/// # 参数
+/// * `config` - 应用配置，决定是否挂载文档路由
+/// * `app_state` - 应用状态
+///
+/// # 返回
+/// 尚未绑定状态的路由和生成的 OpenAPI 文档
+fn build_api(
+    config: &AppConfig,
+    app_state: &Arc<AppState>,
+) -> (axum::Router<Arc<AppState>>, OpenApi) {
+    aide::generate::extract_schemas(true);
+
+    let mut api = OpenApi::default();
+
+    // 构建基础路由
+    let mut app = ApiRouter::new()
+        .route("/health", get(health_check))
+        .route("/health/ready", get(readiness_check))
+        .route("/", welcome_route(&config.welcome))
+        .route("/favicon.ico", get(favicon))
+        .nest_api_service(v1::PREFIX, v1::routes(app_state.clone()));
+
+    // 按 docs 配置挂载 API 文档路由（未配置时仅 debug 日志级别启用）
+    if config.docs_enabled() {
+        app = app.nest_api_service(&config.docs.path, docs_routes(app_state, &config.docs));
+    }
+
+    let app = app.finish_api_with(&mut api, api_docs);
+    if config.docs_enabled() && config.docs.preload {
+        let json = app_state.openapi.get_or_render(&api);
+        debug!(bytes = json.len(), "OpenAPI 文档已预先生成");
+    }
+    (app, api)
+}
+
 /// 配置 OpenAPI 文档
 ///
 /// # 参数
@@ -278,19 +610,445 @@ async fn favicon() -> impl IntoApiResponse {
 fn api_docs(api: TransformOpenApi) -> TransformOpenApi {
     api.title("DropBuddy API Documentation")
         .summary("API for the DropBuddy platform")
-        // .description(include_str!("README.md")) 
-        .tag(Tag {
-            name: "❤️💕".into(),
-            description: Some("Endpoints related to community features and content.".into()),
-            ..Default::default()
-        })
+        // .description(include_str!("README.md"))
+        .security_scheme(
+            middleware::access::BEARER_SCHEME,
+            aide::openapi::SecurityScheme::Http {
+                scheme: "bearer".into(),
+                bearer_format: Some("JWT".into()),
+                description: Some("登录接口返回的访问令牌".into()),
+                extensions: Default::default(),
+            },
+        )
         .security_scheme(
-            "ApiKey",
+            middleware::api_key::API_KEY_SCHEME,
             aide::openapi::SecurityScheme::ApiKey {
                 location: aide::openapi::ApiKeyLocation::Header,
                 name: "X-Auth-Key".into(),
-                description: Some("API Key for authentication (Note: This might be a placeholder and needs proper implementation description).".into()), // 更谨慎的描述
+                description: Some(
+                    "机器客户端的 API 密钥，在 api_keys 配置或 API_KEYS 环境变量中设置".into(),
+                ),
                 extensions: Default::default(),
             },
         )
+        .with(response::register_total_count_header)
+        .with(modules::register_default_headers)
+        .with(modules::register_module_tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_state;
+    use std::cell::RefCell;
+    use std::collections::BTreeSet;
+    use std::io;
+    use std::rc::Rc;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_bind_error_message_addr_in_use() {
+        let err = io::Error::from(io::ErrorKind::AddrInUse);
+        let message = bind_error_message("0.0.0.0:3000", &err);
+        assert!(message.contains("0.0.0.0:3000"));
+        assert!(message.contains("端口 3000 可能已被其他进程占用"));
+        assert!(message.contains("lsof -i :3000"));
+    }
+
+    #[test]
+    fn test_shutdown_reason_code_and_message() {
+        assert_eq!(ShutdownReason::Interrupt.code(), "sigint");
+        assert_eq!(
+            ShutdownReason::Interrupt.message(),
+            "收到 Ctrl+C (SIGINT) 信号"
+        );
+        assert_eq!(ShutdownReason::Terminate.code(), "sigterm");
+        assert_eq!(ShutdownReason::Terminate.message(), "收到 SIGTERM 信号");
+    }
+
+    #[test]
+    fn test_cli_overrides_and_help_defaults() {
+        use clap::CommandFactory;
+
+        Cli::command().debug_assert();
+
+        let overrides: ConfigOverrides = Cli::try_parse_from([
+            "app",
+            "--port",
+            "8080",
+            "--log-level",
+            "debug",
+            "--config",
+            "/etc/app.toml",
+        ])
+        .unwrap()
+        .into();
+        assert_eq!(overrides.port, Some(8080));
+        assert_eq!(overrides.log_level.as_deref(), Some("debug"));
+        assert_eq!(overrides.config_file, Some(PathBuf::from("/etc/app.toml")));
+        assert!(overrides.host.is_none());
+
+        assert!(Cli::try_parse_from(["app", "--log-level", "loud"]).is_err());
+
+        let help = Cli::command().render_help().to_string();
+        assert!(help.contains("[默认: 3001]"), "{help}");
+        assert!(help.contains("[默认: 127.0.0.1]"), "{help}");
+    }
+
+    // 开启 docs.preload 时构建路由即缓存序列化后的文档，/docs/openapi.json 直接返回缓存；关闭时不缓存
+    #[tokio::test]
+    async fn test_openapi_json_is_cached_at_startup() {
+        let mut config = AppConfig::default();
+        config.docs.enabled = Some(true);
+        let state = Arc::new(test_state(&config));
+
+        let (app, api) = build_api(&config, &state);
+        let cached = state.openapi.get().expect("启动后应已缓存文档");
+        assert!(!cached.is_empty());
+
+        // 文档接口直接返回缓存的 JSON，不重新生成
+        let response = app
+            .layer(Extension(Arc::new(api)))
+            .with_state(state.clone())
+            .oneshot(
+                axum::http::Request::get("/docs/openapi.json")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, cached);
+        let spec: Value = serde_json::from_slice(&body).unwrap();
+        assert!(spec["paths"].get("/v1/user/login").is_some());
+
+        config.docs.preload = false;
+        let state = Arc::new(test_state(&config));
+        let _ = build_api(&config, &state);
+        assert!(state.openapi.get().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_docs_page_is_embedded_and_follows_configured_path() {
+        let mut config = AppConfig::default();
+        config.docs.enabled = Some(true);
+        config.docs.path = "/api-docs".to_string();
+        let state = Arc::new(test_state(&config));
+        let (app, api) = build_api(&config, &state);
+        let app = app.layer(Extension(Arc::new(api))).with_state(state);
+
+        let get = |uri: &str| {
+            app.clone().oneshot(
+                axum::http::Request::get(uri)
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let response = get("/api-docs").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let html = String::from_utf8_lossy(&body);
+        assert!(html.contains("/api-docs/openapi.json"));
+        // 页面脚本内嵌，不从 CDN 加载
+        assert!(!html.contains("<script src="), "页面引用了外部脚本");
+
+        let response = get("/api-docs/openapi.json").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(get("/docs").await.unwrap().status(), StatusCode::NOT_FOUND);
+    }
+
+    /// 收集 OpenAPI 文档中所有请求体和响应体的 schema，附带 `METHOD path 位置` 描述
+    ///
+    /// 没有声明任何响应的接口记录为 `null` schema，同样视为未解析。
+    fn body_schemas(spec: &Value) -> Vec<(String, Value)> {
+        let mut schemas = Vec::new();
+        for (path, item) in spec["paths"].as_object().expect("文档缺少 paths") {
+            for (method, op) in item.as_object().into_iter().flatten() {
+                let at = format!("{} {path}", method.to_uppercase());
+                let mut contents = Vec::new();
+                if let Some(content) = op.pointer("/requestBody/content") {
+                    contents.push((format!("{at} 请求体"), content));
+                }
+                let responses = op["responses"].as_object().filter(|r| !r.is_empty());
+                if responses.is_none() {
+                    schemas.push((format!("{at} 未声明响应"), Value::Null));
+                }
+                for (status, response) in responses.into_iter().flatten() {
+                    if let Some(content) = response.get("content") {
+                        contents.push((format!("{at} {status} 响应"), content));
+                    }
+                }
+                for (at, content) in contents {
+                    for (media_type, media) in content.as_object().into_iter().flatten() {
+                        schemas.push((
+                            format!("{at} {media_type}"),
+                            media.get("schema").cloned().unwrap_or(Value::Null),
+                        ));
+                    }
+                }
+            }
+        }
+        schemas
+    }
+
+    /// 收集 schema 中所有 `$ref` 的引用目标
+    fn refs(schema: &Value, out: &mut Vec<String>) {
+        match schema {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    match (key.as_str(), value) {
+                        ("$ref", Value::String(target)) => out.push(target.clone()),
+                        _ => refs(value, out),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| refs(item, out)),
+            _ => {}
+        }
+    }
+
+    /// 所有文档化接口的请求体和响应体都要有具体的 schema，且引用的组件都存在
+    ///
+    /// DTO 缺少 `JsonSchema` 或处理器的请求体、响应类型无法推断时，aide 会生成 `{}` 或 `true`，
+    /// 文档中只显示任意类型。
+    #[tokio::test]
+    async fn test_openapi_bodies_have_resolved_schemas() {
+        let config = AppConfig::default();
+        let state = test_state(&config);
+
+        let errors = Rc::new(RefCell::new(Vec::new()));
+        let sink = errors.clone();
+        aide::generate::on_error(move |error| sink.borrow_mut().push(error.to_string()));
+        let (_, api) = build_api(&config, &Arc::new(state));
+        assert!(
+            errors.borrow().is_empty(),
+            "文档生成出错：{:?}",
+            errors.borrow()
+        );
+        let spec = serde_json::to_value(&api).unwrap();
+
+        let schemas = body_schemas(&spec);
+        assert!(
+            schemas
+                .iter()
+                .any(|(at, _)| at.starts_with("POST /v1/files 请求体")),
+            "文档缺少上传接口：{:?}",
+            schemas.iter().map(|(at, _)| at).collect::<Vec<_>>()
+        );
+
+        let components = &spec["components"]["schemas"];
+        let mut unresolved = Vec::new();
+        for (at, schema) in &schemas {
+            let fallback = match schema {
+                Value::Object(map) => map.is_empty(),
+                _ => true,
+            };
+            if fallback {
+                unresolved.push(format!("{at}: {schema}"));
+                continue;
+            }
+            let mut targets = Vec::new();
+            refs(schema, &mut targets);
+            for target in targets {
+                let name = target.strip_prefix("#/components/schemas/");
+                if name.is_none_or(|name| components.get(name).is_none()) {
+                    unresolved.push(format!("{at}: 无法解析的引用 {target}"));
+                }
+            }
+        }
+        assert!(unresolved.is_empty(), "{}", unresolved.join("\n"));
+
+        // 分页接口的响应头引用 components/headers 中登记的 X-Total-Count
+        let list = &spec["paths"]["/v1/files"]["get"]["responses"]["200"];
+        let reference = list["headers"][response::TOTAL_COUNT_HEADER_COMPONENT]["$ref"]
+            .as_str()
+            .expect("分页接口文档缺少 X-Total-Count 响应头");
+        assert!(
+            spec.pointer(reference.trim_start_matches('#')).is_some(),
+            "无法解析的引用 {reference}"
+        );
+    }
+
+    #[test]
+    fn test_export_openapi_without_connections() {
+        // 默认配置没有数据库 URL 和 JWT 密钥，导出不依赖它们
+        let dir = tempfile::tempdir().unwrap();
+        let json_path = dir.path().join("openapi.json");
+        let yaml_path = dir.path().join("openapi.yaml");
+        export_openapi(&AppConfig::default(), &json_path, OpenApiFormat::Json).unwrap();
+        export_openapi(&AppConfig::default(), &yaml_path, OpenApiFormat::Yaml).unwrap();
+
+        let spec: Value = serde_json::from_slice(&std::fs::read(&json_path).unwrap()).unwrap();
+        assert!(spec["paths"]["/v1/files"]["get"].is_object());
+        assert!(spec["paths"].get("/docs").is_none());
+
+        let yaml = std::fs::read_to_string(&yaml_path).unwrap();
+        assert!(yaml.contains("/v1/files"));
+        assert!(yaml.contains(&format!("openapi: {}", spec["openapi"].as_str().unwrap())));
+    }
+
+    #[test]
+    fn test_register_docs_include_default_responses() {
+        let config = AppConfig::default();
+        let (_, api) = build_api(&config, &Arc::new(test_state(&config)));
+        let spec = serde_json::to_value(&api).unwrap();
+
+        let responses = &spec["paths"]["/v1/user/register"]["post"]["responses"];
+        let summary: Value = responses
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(code, response)| {
+                let headers: Vec<_> = response["headers"]
+                    .as_object()
+                    .map(|h| h.keys().cloned().collect())
+                    .unwrap_or_default();
+                (
+                    code.clone(),
+                    json!({ "description": response["description"], "headers": headers }),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            json!({
+                "201": {
+                    "description": "API 响应",
+                    "headers": ["X-RateLimit-Limit", "X-RateLimit-Remaining", "X-Request-ID"],
+                },
+                "400": {
+                    "description": "请求参数无效",
+                    "headers": ["X-RateLimit-Limit", "X-RateLimit-Remaining", "X-Request-ID"],
+                },
+                "401": {
+                    "description": "未认证或令牌无效",
+                    "headers": ["X-RateLimit-Limit", "X-RateLimit-Remaining", "X-Request-ID"],
+                },
+                "429": {
+                    "description": "请求频率超限",
+                    "headers": [
+                        "Retry-After",
+                        "X-RateLimit-Limit",
+                        "X-RateLimit-Remaining",
+                        "X-Request-ID",
+                    ],
+                },
+                "500": {
+                    "description": "内部服务器错误",
+                    "headers": ["X-RateLimit-Limit", "X-RateLimit-Remaining", "X-Request-ID"],
+                },
+            })
+        );
+        assert_eq!(
+            responses["400"]["content"]["application/json"]["schema"],
+            responses["500"]["content"]["application/json"]["schema"]
+        );
+
+        // 所有响应头引用都能解析到 components/headers
+        for path in spec["paths"].as_object().unwrap().values() {
+            for operation in path.as_object().unwrap().values() {
+                let Some(responses) = operation["responses"].as_object() else {
+                    continue;
+                };
+                for response in responses.values() {
+                    for header in response["headers"]
+                        .as_object()
+                        .into_iter()
+                        .flat_map(|h| h.values())
+                    {
+                        let target = header["$ref"].as_str().unwrap().trim_start_matches('#');
+                        assert!(spec.pointer(target).is_some(), "未登记的响应头：{target}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_protected_routes_declare_security() {
+        let config = AppConfig::default();
+        let (_, api) = build_api(&config, &Arc::new(test_state(&config)));
+        let spec = serde_json::to_value(&api).unwrap();
+        let security = json!([{ middleware::access::BEARER_SCHEME: [] }]);
+
+        for path in [
+            "/v1/user/me",
+            "/v1/files",
+            "/v1/files/{id}",
+            "/v1/files/uploads/{id}/chunks/{n}",
+            "/v1/users/{id}/files",
+            "/v1/users/me/storage",
+            "/v1/admin/logging/level",
+            "/v1/admin/maintenance/enable",
+        ] {
+            let item = spec["paths"][path].as_object().expect(path);
+            for (method, operation) in item {
+                assert_eq!(operation["security"], security, "{method} {path}");
+            }
+        }
+        // 监控系统也可以凭授权范围为 stats:read 的 API 密钥调用
+        assert_eq!(
+            spec["paths"]["/v1/stats/latency"]["get"]["security"],
+            json!([
+                { middleware::access::BEARER_SCHEME: [] },
+                { middleware::api_key::API_KEY_SCHEME: ["stats:read"] }
+            ])
+        );
+        for path in ["/v1/user/login", "/v1/files/{id}/download"] {
+            for (method, operation) in spec["paths"][path].as_object().expect(path) {
+                assert!(operation.get("security").is_none(), "{method} {path}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_register_docs_tag_operations_by_module() {
+        fn tags(config: &AppConfig) -> (Vec<String>, BTreeSet<String>) {
+            let (_, api) = build_api(config, &Arc::new(test_state(config)));
+            let spec = serde_json::to_value(&api).unwrap();
+            let declared = spec["tags"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|tag| tag["name"].as_str().unwrap().to_string())
+                .collect();
+            let mut used = BTreeSet::new();
+            for (path, item) in spec["paths"].as_object().unwrap() {
+                for (method, operation) in item.as_object().unwrap() {
+                    let names = operation["tags"].as_array().unwrap();
+                    assert_eq!(names.len(), 1, "{method} {path} 应只有一个模块标签");
+                    used.insert(names[0].as_str().unwrap().to_string());
+                }
+            }
+            (declared, used)
+        }
+
+        let mut config = AppConfig::default();
+        config.docs.enabled = Some(true);
+        let (declared, used) = tags(&config);
+        assert_eq!(declared, ["用户", "文件", "管理", "限流", "统计", "文档"]);
+        assert_eq!(used, declared.iter().cloned().collect());
+
+        // 关闭的模块不留下孤立标签
+        config.docs.enabled = Some(false);
+        config.features.file_upload_enabled = false;
+        let (declared, used) = tags(&config);
+        assert_eq!(declared, ["用户", "管理", "限流", "统计"]);
+        assert_eq!(used, declared.iter().cloned().collect());
+    }
+
+    #[test]
+    fn test_bind_error_message_other_error() {
+        let err = io::Error::from(io::ErrorKind::PermissionDenied);
+        let message = bind_error_message("0.0.0.0:80", &err);
+        assert!(message.starts_with("无法绑定监听地址 0.0.0.0:80"));
+        assert!(!message.contains("占用"));
+    }
 }
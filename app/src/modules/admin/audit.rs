@@ -0,0 +1,146 @@
+//! 管理操作审计中间件
+//!
+//! 挂载在管理路由上，记录每次写操作（非 GET/HEAD/OPTIONS）的操作者、路由、方法、
+//! 脱敏后的请求体和响应状态码。审计写入在后台任务中进行，不阻塞响应。
+
+use std::sync::Arc;
+
+use axum::body::{Body, to_bytes};
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::Method;
+use axum::middleware::Next;
+use axum::response::Response;
+use serde_json::Value;
+use tracing::error;
+
+use crate::{
+    AppError, AppState, AuthError, ValidationError, core::middleware::CurrentUser,
+    shared::FromState,
+};
+
+use super::repo::NewAdminAudit;
+use super::service::AuditLogService;
+
+/// 审计时读取的请求体上限（字节），管理接口的请求体都很小
+const MAX_AUDIT_BODY_BYTES: usize = 1024 * 1024;
+
+/// 脱敏字段的替换值
+const REDACTED: &str = "[REDACTED]";
+
+/// 管理操作审计中间件
+///
+/// 需位于 [`require_auth`](crate::core::middleware::require_auth) 内层，依赖其注入的 [`CurrentUser`]。
+pub async fn audit_admin_mutations(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        return Ok(next.run(request).await);
+    }
+
+    let actor_id = request
+        .extensions()
+        .get::<CurrentUser>()
+        .map(|u| u.user_id)
+        .ok_or(AppError::Auth(AuthError::InvalidToken))?;
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_owned())
+        .unwrap_or_else(|| request.uri().path().to_owned());
+
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, MAX_AUDIT_BODY_BYTES)
+        .await
+        .map_err(|_| ValidationError::custom("管理接口请求体过大"))?;
+    let redact_fields = state.config().await.audit_redact_fields.clone();
+    let request_body = redact_body(&bytes, &redact_fields);
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+
+    let entry = NewAdminAudit {
+        actor_id,
+        method,
+        route,
+        request_body,
+        status: response.status().as_u16(),
+    };
+    let service = AuditLogService::from_state(&state);
+    tokio::spawn(async move {
+        if let Err(e) = service.record(entry).await {
+            error!(error = %e, "写入管理操作审计记录失败");
+        }
+    });
+
+    Ok(response)
+}
+
+/// 生成脱敏后的请求体
+///
+/// 空请求体返回 `None`；JSON 请求体中字段名（不区分大小写）命中 `fields` 的值
+/// 在任意层级都会被替换为 `[REDACTED]`；非 JSON 请求体只记录长度，不保存内容。
+pub fn redact_body(body: &[u8], fields: &[String]) -> Option<String> {
+    if body.is_empty() {
+        return None;
+    }
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value, fields);
+            Some(value.to_string())
+        }
+        Err(_) => Some(format!("<{} 字节的非 JSON 请求体>", body.len())),
+    }
+}
+
+fn redact_value(value: &mut Value, fields: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if fields.iter().any(|f| f.eq_ignore_ascii_case(key)) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(value, fields);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| redact_value(v, fields)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> Vec<String> {
+        vec!["password".to_string(), "token".to_string()]
+    }
+
+    #[test]
+    fn test_redact_nested_fields_case_insensitive() {
+        let body =
+            br#"{"username":"alice","Password":"hunter2","sessions":[{"token":"abc","id":1}]}"#;
+        let redacted: Value = serde_json::from_str(&redact_body(body, &fields()).unwrap()).unwrap();
+
+        assert_eq!(redacted["username"], "alice");
+        assert_eq!(redacted["Password"], REDACTED);
+        assert_eq!(redacted["sessions"][0]["token"], REDACTED);
+        assert_eq!(redacted["sessions"][0]["id"], 1);
+    }
+
+    #[test]
+    fn test_redact_empty_and_non_json_bodies() {
+        assert_eq!(redact_body(b"", &fields()), None);
+        assert_eq!(
+            redact_body(b"password=hunter2", &fields()).unwrap(),
+            "<16 字节的非 JSON 请求体>"
+        );
+    }
+}
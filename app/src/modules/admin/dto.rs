@@ -0,0 +1,169 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::LogLevelHandle;
+use entity::admin_audit;
+use entity::enums::StorageBackend;
+use entity::user::UserId;
+
+/// 调整日志级别请求
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct SetLogLevelRequest {
+    /// 过滤指令，语法与 `RUST_LOG` 相同（如 `info,app=debug`）
+    #[validate(custom(function = "not_blank", message = "日志过滤指令不能为空"))]
+    pub filter: String,
+
+    /// 有效期（秒，1 ~ 7 天），到期后自动恢复为启动时的过滤指令；不设置则一直生效
+    #[validate(range(min = 1, max = 604800, message = "有效期必须在 1 秒到 7 天之间"))]
+    pub ttl_secs: Option<u64>,
+}
+
+fn not_blank(value: &str) -> Result<(), validator::ValidationError> {
+    if value.trim().is_empty() {
+        return Err(validator::ValidationError::new("blank"));
+    }
+    Ok(())
+}
+
+/// 日志级别响应
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LogLevelResponse {
+    /// 当前生效的过滤指令
+    pub filter: String,
+
+    /// 启动时配置的过滤指令
+    pub default_filter: String,
+
+    /// 自动恢复时间，没有待恢复的调整时为空
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl LogLevelResponse {
+    /// 根据日志级别控制句柄的当前状态构建响应
+    pub fn from_handle(handle: &LogLevelHandle) -> Self {
+        Self {
+            filter: handle.current(),
+            default_filter: handle.default_filter().to_owned(),
+            expires_at: handle.expires_at(),
+        }
+    }
+}
+
+/// 维护模式状态
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MaintenanceResponse {
+    /// 是否处于维护模式
+    pub enabled: bool,
+}
+
+/// 管理操作审计查询参数
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct AdminActionQuery {
+    /// 操作者用户 ID
+    pub actor_id: Option<UserId>,
+
+    /// 起始时间（包含，RFC 3339）
+    pub from: Option<DateTime<Utc>>,
+
+    /// 结束时间（不包含，RFC 3339）
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// 审计日志查询参数
+///
+/// `user_id`、`path`、`from`+`to` 每次只能选择一种，均不设置时返回全部记录。
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct AuditLogQuery {
+    /// 操作者用户 ID
+    pub user_id: Option<UserId>,
+
+    /// 路由前缀，如 `/v1/admin/logging`
+    pub path: Option<String>,
+
+    /// 起始时间（包含，RFC 3339），需与 `to` 同时设置
+    pub from: Option<DateTime<Utc>>,
+
+    /// 结束时间（不包含，RFC 3339），需与 `from` 同时设置
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// 管理操作审计记录
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditLogDto {
+    /// 记录 ID
+    pub id: i64,
+
+    /// 执行操作的管理员用户 ID
+    pub actor_id: UserId,
+
+    /// HTTP 方法
+    pub method: String,
+
+    /// 匹配的路由模板
+    pub route: String,
+
+    /// 脱敏后的请求体
+    pub request_body: Option<String>,
+
+    /// 响应状态码
+    pub status: i16,
+
+    /// 操作时间
+    pub created_at: DateTime<FixedOffset>,
+}
+
+impl From<admin_audit::Model> for AuditLogDto {
+    fn from(model: admin_audit::Model) -> Self {
+        Self {
+            id: model.id,
+            actor_id: model.actor_id,
+            method: model.method,
+            route: model.route,
+            request_body: model.request_body,
+            status: model.status,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// 已执行的数据库迁移
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AppliedMigrationDto {
+    /// 迁移名称，如 `m20220101_000001_create_user_table`
+    pub name: String,
+
+    /// 执行时间
+    pub applied_at: DateTime<Utc>,
+}
+
+/// 数据库迁移状态
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MigrationStatusResponse {
+    /// 已执行的迁移，按执行顺序排列
+    pub applied: Vec<AppliedMigrationDto>,
+
+    /// 尚未执行的迁移名称，按定义顺序排列
+    pub pending: Vec<String>,
+}
+
+/// 迁移文件存储后端请求
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct MigrateFileStorageRequest {
+    /// 目标存储后端，必须已在 `object_storage` 中配置
+    pub backend: StorageBackend,
+}
+
+/// 文件存储后端迁移结果
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FileStorageMigrationResponse {
+    /// 文件ID
+    pub file_id: i64,
+
+    /// 迁移前的存储后端
+    pub from: StorageBackend,
+
+    /// 当前的存储后端，与 `from` 相同表示文件原本就在目标后端
+    pub to: StorageBackend,
+}
@@ -0,0 +1,394 @@
+use crate::modules::docs::with_defaults;
+use crate::{
+    ApiResponse, AppError, AppState, ValidationError,
+    core::Validated,
+    core::middleware::CurrentUser,
+    core::response::{PageParams, PaginatedResponse},
+    modules::file::{FileService, dto::CorruptFileDto},
+    modules::user::{ImportOutcome, UserService, dto::ImportReport},
+    shared::FromState,
+};
+use aide::openapi::{MediaType, ReferenceOr, RequestBody, SchemaObject};
+use aide::transform::TransformOperation;
+use axum::extract::{Extension, Multipart, Path, Query, State};
+use axum::http::StatusCode;
+use entity::user::UserId;
+use indexmap::IndexMap;
+use schemars::json_schema;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, instrument, warn};
+
+use super::dto::{
+    AdminActionQuery, AuditLogDto, AuditLogQuery, FileStorageMigrationResponse, LogLevelResponse,
+    MaintenanceResponse, MigrateFileStorageRequest, MigrationStatusResponse, SetLogLevelRequest,
+};
+use super::service::{AuditLogService, MigrationService};
+
+/// 查看日志级别处理器
+///
+/// 返回当前生效的过滤指令、启动时的默认指令以及自动恢复时间。
+#[instrument(skip(state))]
+pub async fn get_log_level(State(state): State<Arc<AppState>>) -> ApiResponse<LogLevelResponse> {
+    ApiResponse::success(LogLevelResponse::from_handle(&state.log_level))
+}
+
+/// 查看日志级别 API 文档
+pub fn get_log_level_docs(op: TransformOperation) -> TransformOperation {
+    op.description("查看当前生效的日志过滤指令（仅管理员）")
+        .response::<200, ApiResponse<LogLevelResponse>>()
+        .with(with_defaults)
+}
+
+/// 调整日志级别处理器
+///
+/// 立即应用新的过滤指令，每次调整都会记录审计日志。
+///
+/// # 参数
+/// * `state` - 应用状态（包含日志级别控制句柄）
+/// * `current_user` - 当前登录的管理员（由认证中间件注入）
+/// * `req` - 过滤指令和可选的有效期
+///
+/// # 返回
+/// 成功返回调整后的日志级别状态，参数校验失败或指令无法解析时返回 400
+#[instrument(skip(state, current_user))]
+pub async fn set_log_level(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Validated(req): Validated<SetLogLevelRequest>,
+) -> Result<ApiResponse<LogLevelResponse>, AppError> {
+    let filter = req.filter.trim();
+
+    let previous = state.log_level.current();
+    state
+        .log_level
+        .set(filter, req.ttl_secs.map(Duration::from_secs))
+        .map_err(|e| ValidationError::custom(format!("无效的日志过滤指令: {e}")))?;
+
+    warn!(
+        target: "audit",
+        user_id = %current_user.user_id,
+        previous = %previous,
+        filter = %filter,
+        ttl_secs = ?req.ttl_secs,
+        "日志级别已调整"
+    );
+
+    Ok(ApiResponse::success(LogLevelResponse::from_handle(
+        &state.log_level,
+    )))
+}
+
+/// 调整日志级别 API 文档
+pub fn set_log_level_docs(op: TransformOperation) -> TransformOperation {
+    op.description("调整日志过滤指令，可指定到期自动恢复（仅管理员）")
+        .response::<200, ApiResponse<LogLevelResponse>>()
+        .with(with_defaults)
+}
+
+/// 开启维护模式处理器
+///
+/// 开启后健康检查和维护开关接口以外的请求均返回 503，只影响当前实例，重启后恢复为关闭。
+#[instrument(skip(state, current_user))]
+pub async fn enable_maintenance(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> ApiResponse<MaintenanceResponse> {
+    set_maintenance(&state, current_user.user_id, true)
+}
+
+/// 开启维护模式 API 文档
+pub fn enable_maintenance_docs(op: TransformOperation) -> TransformOperation {
+    op.description("开启维护模式，健康检查以外的请求返回 503（仅管理员）")
+        .response::<200, ApiResponse<MaintenanceResponse>>()
+        .with(with_defaults)
+}
+
+/// 关闭维护模式处理器
+#[instrument(skip(state, current_user))]
+pub async fn disable_maintenance(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> ApiResponse<MaintenanceResponse> {
+    set_maintenance(&state, current_user.user_id, false)
+}
+
+/// 关闭维护模式 API 文档
+pub fn disable_maintenance_docs(op: TransformOperation) -> TransformOperation {
+    op.description("关闭维护模式，恢复正常服务（仅管理员）")
+        .response::<200, ApiResponse<MaintenanceResponse>>()
+        .with(with_defaults)
+}
+
+/// 切换维护模式并记录审计日志，重复切换视为成功
+fn set_maintenance(
+    state: &AppState,
+    user_id: UserId,
+    enabled: bool,
+) -> ApiResponse<MaintenanceResponse> {
+    let previous = state.maintenance.set(enabled);
+    if previous != enabled {
+        warn!(target: "audit", %user_id, enabled, "维护模式已切换");
+    }
+    ApiResponse::success(MaintenanceResponse { enabled })
+}
+
+/// 查询管理操作审计记录处理器
+///
+/// 按操作者和时间范围分页返回管理接口的写操作记录，按时间倒序。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接）
+/// * `query` - 过滤条件（actor_id、from、to）
+/// * `params` - 分页参数（page、per_page）
+///
+/// # 返回
+/// 成功返回审计记录分页列表，响应头 `X-Total-Count` 为记录总数
+#[instrument(skip(state))]
+pub async fn list_admin_actions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AdminActionQuery>,
+    Query(params): Query<PageParams>,
+) -> Result<PaginatedResponse<AuditLogDto>, AppError> {
+    AuditLogService::from_state(&state)
+        .list_admin_actions(query, params)
+        .await
+}
+
+/// 查询管理操作审计记录 API 文档
+pub fn list_admin_actions_docs(op: TransformOperation) -> TransformOperation {
+    op.description("按操作者和时间范围分页查询管理操作审计记录（仅管理员）")
+        .response::<200, PaginatedResponse<AuditLogDto>>()
+        .with(with_defaults)
+}
+
+/// 查询审计日志处理器
+///
+/// 按单一维度（用户、路由前缀或时间范围）分页查询审计记录，按时间倒序。
+/// 需要组合操作者和时间范围时使用 `GET /audit/admin-actions`。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接）
+/// * `query` - 过滤条件（user_id、path、from+to 三选一）
+/// * `params` - 分页参数（page、per_page）
+///
+/// # 返回
+/// 成功返回审计记录分页列表；同时指定多种条件或只给出 `from`/`to` 之一时返回 400
+#[instrument(skip(state))]
+pub async fn list_audit_logs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuditLogQuery>,
+    Query(params): Query<PageParams>,
+) -> Result<PaginatedResponse<AuditLogDto>, AppError> {
+    let service = AuditLogService::from_state(&state);
+    match query {
+        AuditLogQuery {
+            user_id: Some(user_id),
+            path: None,
+            from: None,
+            to: None,
+        } => service.find_by_user(user_id, params).await,
+        AuditLogQuery {
+            user_id: None,
+            path: Some(path),
+            from: None,
+            to: None,
+        } => service.find_by_path(&path, params).await,
+        AuditLogQuery {
+            user_id: None,
+            path: None,
+            from: Some(from),
+            to: Some(to),
+        } => service.find_by_date_range(from, to, params).await,
+        AuditLogQuery {
+            user_id: None,
+            path: None,
+            from: None,
+            to: None,
+        } => {
+            service
+                .list_admin_actions(AdminActionQuery::default(), params)
+                .await
+        }
+        _ => Err(ValidationError::custom(
+            "user_id、path、from+to 只能选择一种过滤条件，组合查询请使用 /v1/admin/audit/admin-actions",
+        )
+        .into()),
+    }
+}
+
+/// 查询审计日志 API 文档
+pub fn list_audit_logs_docs(op: TransformOperation) -> TransformOperation {
+    op.description("按用户、路由前缀或时间范围分页查询审计日志（仅管理员）")
+        .response::<200, PaginatedResponse<AuditLogDto>>()
+        .with(with_defaults)
+}
+
+/// 查看数据库迁移状态处理器
+///
+/// 返回已执行的迁移（含执行时间）和尚未执行的迁移名称。
+///
+/// # 返回
+/// 成功返回迁移状态，数据库无法连接时返回 503
+#[instrument(skip(state))]
+pub async fn get_migrations(
+    State(state): State<Arc<AppState>>,
+) -> Result<ApiResponse<MigrationStatusResponse>, AppError> {
+    let status = MigrationService::from_state(&state).status().await?;
+    Ok(ApiResponse::success(status))
+}
+
+/// 查看数据库迁移状态 API 文档
+pub fn get_migrations_docs(op: TransformOperation) -> TransformOperation {
+    op.description("查看已执行和待执行的数据库迁移，数据库无法连接时返回 503（仅管理员）")
+        .response::<200, ApiResponse<MigrationStatusResponse>>()
+        .with(with_defaults)
+}
+
+/// 迁移文件存储后端处理器
+///
+/// 将文件内容复制到目标后端并校验大小和 SHA-256，成功后更新元数据并删除原后端中的内容。
+/// 用于在本地磁盘和 S3 并存期间逐个迁移已有文件。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接和存储后端）
+/// * `file_id` - 文件ID
+/// * `req` - 目标存储后端
+///
+/// # 返回
+/// 成功返回迁移前后的存储后端，目标后端未配置返回 400，文件不存在返回 404
+#[instrument(skip(state))]
+pub async fn migrate_file_storage(
+    State(state): State<Arc<AppState>>,
+    Path(file_id): Path<i64>,
+    Validated(req): Validated<MigrateFileStorageRequest>,
+) -> Result<ApiResponse<FileStorageMigrationResponse>, AppError> {
+    let from = FileService::from_state(&state)
+        .migrate_storage(file_id, req.backend)
+        .await?;
+    Ok(ApiResponse::success(FileStorageMigrationResponse {
+        file_id,
+        from,
+        to: req.backend,
+    }))
+}
+
+/// 迁移文件存储后端 API 文档
+pub fn migrate_file_storage_docs(op: TransformOperation) -> TransformOperation {
+    op.description("将文件内容迁移到另一个存储后端（仅管理员）")
+        .response::<200, ApiResponse<FileStorageMigrationResponse>>()
+        .with(with_defaults)
+}
+
+/// 列出损坏文件处理器
+///
+/// 定期对账任务发现内容在存储后端中缺失时，会把内容标记为损坏；这里分页列出引用了这些内容的文件，
+/// 供管理员从备份恢复对象或通知用户。对象恢复后下一轮对账会自动清除标记。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接）
+/// * `params` - 分页参数（page、per_page）
+///
+/// # 返回
+/// 成功返回损坏文件分页列表，按文件 ID 升序，响应头 `X-Total-Count` 为总数
+#[instrument(skip(state))]
+pub async fn list_corrupt_files(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PageParams>,
+) -> Result<PaginatedResponse<CorruptFileDto>, AppError> {
+    FileService::from_state(&state)
+        .list_corrupt_files(params)
+        .await
+}
+
+/// 列出损坏文件 API 文档
+pub fn list_corrupt_files_docs(op: TransformOperation) -> TransformOperation {
+    op.description("分页列出内容在存储后端中缺失的文件（仅管理员）")
+        .response::<200, PaginatedResponse<CorruptFileDto>>()
+        .with(with_defaults)
+}
+
+/// 导入用户处理器
+///
+/// 接收 `multipart/form-data` 请求，`csv` 字段为 UTF-8 编码的 CSV 文件：第一行为表头，
+/// 必须包含 `username`、`email`、`password` 列。每行按注册规则校验，有效的行每 100 个一个事务写入，
+/// 失败的行连同行号、出错的列和原因返回。管理操作审计只记录请求体长度，不保存 CSV 内容；
+/// 审计最多读取 1 MiB 的请求体，更大的文件需要拆分后分别导入。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接和导入限制）
+/// * `multipart` - multipart 表单
+///
+/// # 返回
+/// 成功返回 200 和导入结果；失败行占比超过 `import.max_error_rate_percent` 时返回 422 和导入结果，
+/// 此时未导入任何用户；缺少 `csv` 字段、缺少必需的列、没有数据行或超过 `import.max_rows` 返回 400
+#[instrument(skip(state, multipart))]
+pub async fn import_users(
+    State(state): State<Arc<AppState>>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, ApiResponse<ImportReport>), AppError> {
+    let mut data = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() == Some("csv") {
+            data = Some(field.bytes().await?);
+        }
+    }
+    let data = data.ok_or_else(|| ValidationError::custom("缺少 csv 字段"))?;
+
+    let outcome = UserService::from_state(&state)
+        .import_csv(&data, &state.import)
+        .await?;
+    Ok(match outcome {
+        ImportOutcome::Completed(report) => {
+            info!(
+                total = report.total,
+                succeeded = report.succeeded,
+                failed = report.failed,
+                "CSV 导入用户完成"
+            );
+            (StatusCode::OK, ApiResponse::success(report))
+        }
+        ImportOutcome::Rejected(report) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            ApiResponse::success(report),
+        ),
+    })
+}
+
+/// 导入用户 API 文档
+///
+/// `Multipart` 提取器只生成不带字段的请求体描述，这里替换为带 `csv` 字段的表单结构。
+pub fn import_users_docs(mut op: TransformOperation) -> TransformOperation {
+    let schema = json_schema!({
+        "type": "object",
+        "required": ["csv"],
+        "properties": {
+            "csv": {
+                "type": "string",
+                "format": "binary",
+                "description": "UTF-8 编码的 CSV 文件，表头须包含 username、email、password 列"
+            }
+        }
+    });
+    op.inner_mut().request_body = Some(ReferenceOr::Item(RequestBody {
+        description: Some("multipart/form-data 表单".to_string()),
+        content: IndexMap::from_iter([(
+            "multipart/form-data".to_string(),
+            MediaType {
+                schema: Some(SchemaObject {
+                    json_schema: schema,
+                    external_docs: None,
+                    example: None,
+                }),
+                ..Default::default()
+            },
+        )]),
+        required: true,
+        extensions: IndexMap::default(),
+    }));
+
+    op.description("从 CSV 批量导入用户，失败行过多时整体拒绝（仅管理员）")
+        .response::<200, ApiResponse<ImportReport>>()
+        .response::<422, ApiResponse<ImportReport>>()
+        .with(with_defaults)
+}
@@ -0,0 +1,154 @@
+//! 管理模块
+//!
+//! 提供仅管理员可用的运维接口，如运行时调整日志级别、迁移文件存储后端、切换维护模式。
+//! 所有写操作都会经 [`audit::audit_admin_mutations`] 记录到 `admin_audit` 表。
+
+use crate::AppState;
+use crate::core::middleware::{Access, AccessRouter};
+use crate::modules::docs::{ModuleTag, ModuleTagRouter};
+use aide::axum::ApiRouter;
+use aide::axum::routing::{ApiMethodRouter, get_with, post_with};
+use std::sync::Arc;
+
+mod audit;
+pub mod dto;
+mod handler;
+#[cfg(all(feature = "profiling", debug_assertions))]
+mod profile;
+mod repo;
+mod service;
+
+/// 管理模块在 OpenAPI 文档中的标签
+pub const TAG: ModuleTag = ModuleTag::new(
+    "管理",
+    "管理员运维接口：日志级别、审计记录、数据库迁移、存储迁移与维护模式",
+);
+
+/// 构建管理模块的路由
+///
+/// 配置以下端点（均需要管理员权限）：
+/// - GET /logging/level - 查看当前生效的日志过滤指令
+/// - PUT /logging/level - 调整日志过滤指令，可指定自动恢复时间
+/// - GET /audit/admin-actions - 按操作者和时间范围查询管理操作审计记录
+/// - GET /audit-logs - 按用户、路由前缀或时间范围之一查询审计记录
+/// - GET /migrations - 查看已执行和待执行的数据库迁移
+/// - GET /files/corrupt - 分页列出内容在存储后端中缺失的文件
+/// - POST /files/{id}/storage - 将文件内容迁移到另一个存储后端
+/// - POST /users/import - 从 CSV 批量导入用户
+/// - POST /maintenance/enable - 开启维护模式（健康检查以外的请求返回 503）
+/// - POST /maintenance/disable - 关闭维护模式
+/// - GET /profile/cpu - CPU 性能剖析，返回火焰图或 pprof 文件（仅 `profiling` feature 的 debug 构建，
+///   且来源 IP 在 `profiling.allowed_ips` 内）
+///
+/// # 参数
+/// * `state` - 应用状态，包含数据库和日志级别控制句柄
+///
+/// # 返回
+/// 返回配置好的路由器
+pub fn routes(state: Arc<AppState>) -> ApiRouter {
+    let router = ApiRouter::new()
+        .api_route_access(
+            "/logging/level",
+            Access::Admin,
+            &state,
+            audited(
+                &state,
+                get_with(handler::get_log_level, handler::get_log_level_docs)
+                    .put_with(handler::set_log_level, handler::set_log_level_docs),
+            ),
+        )
+        .api_route_access(
+            "/audit/admin-actions",
+            Access::Admin,
+            &state,
+            get_with(
+                handler::list_admin_actions,
+                handler::list_admin_actions_docs,
+            ),
+        )
+        .api_route_access(
+            "/audit-logs",
+            Access::Admin,
+            &state,
+            get_with(handler::list_audit_logs, handler::list_audit_logs_docs),
+        )
+        .api_route_access(
+            "/migrations",
+            Access::Admin,
+            &state,
+            get_with(handler::get_migrations, handler::get_migrations_docs),
+        )
+        .api_route_access(
+            "/files/corrupt",
+            Access::Admin,
+            &state,
+            get_with(
+                handler::list_corrupt_files,
+                handler::list_corrupt_files_docs,
+            ),
+        )
+        .api_route_access(
+            "/files/{id}/storage",
+            Access::Admin,
+            &state,
+            audited(
+                &state,
+                post_with(
+                    handler::migrate_file_storage,
+                    handler::migrate_file_storage_docs,
+                ),
+            ),
+        )
+        .api_route_access(
+            "/users/import",
+            Access::Admin,
+            &state,
+            audited(
+                &state,
+                post_with(handler::import_users, handler::import_users_docs),
+            ),
+        )
+        .api_route_access(
+            "/maintenance/enable",
+            Access::Admin,
+            &state,
+            audited(
+                &state,
+                post_with(
+                    handler::enable_maintenance,
+                    handler::enable_maintenance_docs,
+                ),
+            ),
+        )
+        .api_route_access(
+            "/maintenance/disable",
+            Access::Admin,
+            &state,
+            audited(
+                &state,
+                post_with(
+                    handler::disable_maintenance,
+                    handler::disable_maintenance_docs,
+                ),
+            ),
+        );
+    #[cfg(all(feature = "profiling", debug_assertions))]
+    let router = router.api_route_access(
+        "/profile/cpu",
+        Access::Admin,
+        &state,
+        get_with(profile::cpu_profile, profile::cpu_profile_docs),
+    );
+    router.with_module_tag(&TAG).with_state(state)
+}
+
+/// 为有写操作的管理接口挂载审计中间件，位于 [`Access::Admin`] 的认证中间件内层
+fn audited(
+    state: &Arc<AppState>,
+    method_router: ApiMethodRouter<Arc<AppState>>,
+) -> ApiMethodRouter<Arc<AppState>> {
+    method_router.layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        audit::audit_admin_mutations,
+    ))
+}
@@ -0,0 +1,235 @@
+//! CPU 性能剖析接口
+//!
+//! 只在启用 `profiling` feature 的 debug 构建中编译和注册。`GET /v1/admin/profile/cpu` 用 pprof
+//! 对整个进程采样指定的秒数，返回火焰图（SVG）或 pprof protobuf（可用 `go tool pprof` 查看）。
+//! 除管理员身份外，来源 IP 还必须在 `profiling.allowed_ips` 内；同一时间只允许一次剖析。
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aide::openapi::SchemaObject;
+use aide::transform::{TransformOperation, TransformResponse};
+use axum::extract::{ConnectInfo, Extension, Query, State};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use pprof::protos::Message;
+use schemars::{JsonSchema, json_schema};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tracing::{instrument, warn};
+
+use crate::core::IpNetwork;
+use crate::core::middleware::CurrentUser;
+use crate::modules::docs::with_defaults;
+use crate::{ApiResponse, AppError, AppState};
+
+/// 默认剖析时长（秒）
+const DEFAULT_SECONDS: u64 = 10;
+
+/// 采样时跳过的系统库，避免在信号处理中展开这些库的栈时死锁
+const BLOCKLIST: [&str; 4] = ["libc", "libgcc", "pthread", "vdso"];
+
+/// 进程内同一时间只能有一个 pprof 采样器
+static PROFILE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// 剖析结果格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileFormat {
+    /// 火焰图（`image/svg+xml`），可直接在浏览器中打开
+    #[default]
+    Svg,
+
+    /// pprof protobuf（`application/octet-stream`），用 `go tool pprof` 分析
+    Pb,
+}
+
+/// CPU 剖析参数
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct CpuProfileQuery {
+    /// 采样时长（秒），默认 10，超过 `profiling.max_seconds` 时按上限截断
+    pub seconds: Option<u64>,
+
+    /// 结果格式，默认 `svg`
+    #[serde(default)]
+    pub format: ProfileFormat,
+}
+
+/// CPU 剖析处理器
+///
+/// 在阻塞线程中采样 `seconds` 秒后返回结果，请求期间连接保持打开。
+///
+/// # 参数
+/// * `state` - 应用状态（包含剖析配置）
+/// * `current_user` - 当前登录的管理员（由认证中间件注入）
+/// * `connect_info` - TCP 连接的对端地址，用于校验 `profiling.allowed_ips`
+/// * `query` - 采样时长和结果格式
+///
+/// # 返回
+/// 成功返回火焰图或 pprof 文件；来源 IP 不在允许列表时返回 403，已有剖析进行中时返回 409
+#[instrument(skip(state, current_user, connect_info))]
+pub async fn cpu_profile(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Query(query): Query<CpuProfileQuery>,
+) -> Result<Response, AppError> {
+    let config = &state.profiling;
+    let peer = connect_info.map(|Extension(ConnectInfo(addr))| addr.ip());
+    let allowed = peer.is_some_and(|ip| {
+        config
+            .allowed_ips
+            .iter()
+            .filter_map(|network| network.parse::<IpNetwork>().ok())
+            .any(|network| network.contains(ip))
+    });
+    if !allowed {
+        return Err(AppError::from_status(
+            StatusCode::FORBIDDEN,
+            "来源地址不允许进行性能剖析",
+        ));
+    }
+
+    let Ok(_guard) = PROFILE_LOCK.try_lock() else {
+        return Err(AppError::from_status(
+            StatusCode::CONFLICT,
+            "已有性能剖析正在进行",
+        ));
+    };
+
+    let seconds = query
+        .seconds
+        .unwrap_or(DEFAULT_SECONDS)
+        .clamp(1, config.max_seconds);
+    warn!(
+        target: "audit",
+        user_id = %current_user.user_id,
+        peer = ?peer,
+        seconds,
+        format = ?query.format,
+        "开始 CPU 性能剖析"
+    );
+
+    let frequency = config.frequency_hz;
+    let format = query.format;
+    tokio::task::spawn_blocking(move || sample(frequency, Duration::from_secs(seconds), format))
+        .await
+        .map_err(|e| anyhow::anyhow!("性能剖析任务异常退出: {e}"))?
+}
+
+/// 采样 `duration` 后按 `format` 生成响应，在阻塞线程中执行
+fn sample(frequency: i32, duration: Duration, format: ProfileFormat) -> Result<Response, AppError> {
+    let internal = |e: pprof::Error| AppError::from(anyhow::anyhow!("性能剖析失败: {e}"));
+
+    let profiler = pprof::ProfilerGuardBuilder::default()
+        .frequency(frequency)
+        .blocklist(&BLOCKLIST)
+        .build()
+        .map_err(internal)?;
+    std::thread::sleep(duration);
+    let report = profiler.report().build().map_err(internal)?;
+
+    let response = match format {
+        ProfileFormat::Svg => {
+            let mut svg = Vec::new();
+            report.flamegraph(&mut svg).map_err(internal)?;
+            ([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()
+        }
+        ProfileFormat::Pb => {
+            let profile = report.pprof().map_err(internal)?;
+            (
+                [
+                    (header::CONTENT_TYPE, "application/octet-stream"),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        "attachment; filename=\"cpu.pb\"",
+                    ),
+                ],
+                profile.encode_to_vec(),
+            )
+                .into_response()
+        }
+    };
+    Ok(response)
+}
+
+/// CPU 剖析 API 文档
+pub fn cpu_profile_docs(op: TransformOperation) -> TransformOperation {
+    op.description(
+        "对进程采样指定秒数，返回 CPU 火焰图（`format=svg`）或 pprof protobuf（`format=pb`）。\
+         仅管理员且来源 IP 在 `profiling.allowed_ips` 内可用，同一时间只允许一次剖析",
+    )
+    .response_with::<200, Vec<u8>, _>(|res| profile_response(res, "火焰图或 pprof 文件"))
+    .response_with::<403, ApiResponse<()>, _>(|res| {
+        res.description("不是管理员，或来源 IP 不在允许列表中")
+    })
+    .response_with::<409, ApiResponse<()>, _>(|res| res.description("已有性能剖析正在进行"))
+    .with(with_defaults)
+}
+
+/// 剖析结果的响应文档：两种格式都是二进制内容
+fn profile_response<'t>(
+    mut res: TransformResponse<'t, Vec<u8>>,
+    description: &str,
+) -> TransformResponse<'t, Vec<u8>> {
+    let content = &mut res.inner().content;
+    let media = content
+        .shift_remove("application/octet-stream")
+        .unwrap_or_default();
+    for media_type in ["image/svg+xml", "application/octet-stream"] {
+        let mut media = media.clone();
+        media.schema = Some(SchemaObject {
+            json_schema: json_schema!({ "type": "string", "format": "binary" }),
+            external_docs: None,
+            example: None,
+        });
+        content.insert(media_type.to_string(), media);
+    }
+    res.description(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::AppConfig;
+    use crate::test_support::{assert_api_error, test_state, user_id};
+
+    async fn call(state: &Arc<AppState>, peer: &str) -> Response {
+        let addr: SocketAddr = peer.parse().unwrap();
+        cpu_profile(
+            State(state.clone()),
+            Extension(CurrentUser {
+                user_id: user_id(1),
+            }),
+            Some(Extension(ConnectInfo(addr))),
+            Query(CpuProfileQuery::default()),
+        )
+        .await
+        .into_response()
+    }
+
+    #[tokio::test]
+    async fn test_rejects_disallowed_peer_and_concurrent_profile() {
+        let state = Arc::new(test_state(&AppConfig::default()));
+
+        let response = call(&state, "192.0.2.1:4000").await;
+        assert_api_error(
+            response,
+            StatusCode::FORBIDDEN,
+            "PERMISSION_DENIED",
+            "来源地址不允许进行性能剖析",
+        )
+        .await;
+
+        let _running = PROFILE_LOCK.lock().await;
+        let response = call(&state, "127.0.0.1:4000").await;
+        assert_api_error(
+            response,
+            StatusCode::CONFLICT,
+            "CONFLICT",
+            "已有性能剖析正在进行",
+        )
+        .await;
+    }
+}
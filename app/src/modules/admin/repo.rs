@@ -0,0 +1,236 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, sea_query::LikeExpr,
+};
+
+use crate::{
+    AppState,
+    core::response::PageParams,
+    shared::{FromState, ids},
+};
+use entity::admin_audit;
+use entity::user::UserId;
+
+/// 待写入的管理操作审计记录
+#[derive(Debug, Clone)]
+pub struct NewAdminAudit {
+    /// 执行操作的管理员用户 ID
+    pub actor_id: UserId,
+
+    /// HTTP 方法
+    pub method: String,
+
+    /// 匹配的路由模板
+    pub route: String,
+
+    /// 脱敏后的请求体
+    pub request_body: Option<String>,
+
+    /// 响应状态码
+    pub status: u16,
+}
+
+/// 管理操作审计查询条件，未设置的条件不参与过滤
+#[derive(Debug, Clone, Default)]
+pub struct AdminAuditFilter {
+    /// 操作者用户 ID
+    pub actor_id: Option<UserId>,
+
+    /// 路由前缀，如 `/v1/admin/logging`
+    pub route_prefix: Option<String>,
+
+    /// 起始时间（包含）
+    pub from: Option<DateTime<Utc>>,
+
+    /// 结束时间（不包含）
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// 管理操作审计数据访问接口
+pub trait AdminAuditRepo: Send + Sync {
+    /// 写入一条审计记录
+    async fn insert(&self, entry: NewAdminAudit) -> Result<(), DbErr>;
+
+    /// 按条件分页查询审计记录，按时间倒序，返回当前页数据和总数
+    async fn list(
+        &self,
+        filter: &AdminAuditFilter,
+        params: PageParams,
+    ) -> Result<(Vec<admin_audit::Model>, u64), DbErr>;
+}
+
+/// 基于 SeaORM 的管理操作审计数据访问实现
+#[derive(Debug, Clone)]
+pub struct SeaOrmAdminAuditRepo {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmAdminAuditRepo {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+impl FromState for SeaOrmAdminAuditRepo {
+    fn from_state(app: &AppState) -> Self {
+        Self::new(app.db.clone())
+    }
+}
+
+impl AdminAuditRepo for SeaOrmAdminAuditRepo {
+    async fn insert(&self, entry: NewAdminAudit) -> Result<(), DbErr> {
+        admin_audit::ActiveModel {
+            // 多个实例同时写入时由各自的 ID 生成器分配主键，不依赖数据库自增序列
+            id: Set(ids::next_id()),
+            actor_id: Set(entry.actor_id),
+            method: Set(entry.method),
+            route: Set(entry.route),
+            request_body: Set(entry.request_body),
+            status: Set(entry.status as i16),
+            created_at: Set(Utc::now().fixed_offset()),
+        }
+        .insert(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        filter: &AdminAuditFilter,
+        params: PageParams,
+    ) -> Result<(Vec<admin_audit::Model>, u64), DbErr> {
+        let mut query = admin_audit::Entity::find();
+        if let Some(actor_id) = filter.actor_id {
+            query = query.filter(admin_audit::Column::ActorId.eq(actor_id));
+        }
+        if let Some(prefix) = &filter.route_prefix {
+            // 转义 LIKE 通配符，只做字面前缀匹配
+            let escaped = prefix
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            query = query.filter(
+                admin_audit::Column::Route.like(LikeExpr::new(format!("{escaped}%")).escape('\\')),
+            );
+        }
+        if let Some(from) = filter.from {
+            query = query.filter(admin_audit::Column::CreatedAt.gte(from));
+        }
+        if let Some(to) = filter.to {
+            query = query.filter(admin_audit::Column::CreatedAt.lt(to));
+        }
+
+        let paginator = query
+            .order_by_desc(admin_audit::Column::CreatedAt)
+            .order_by_desc(admin_audit::Column::Id)
+            .paginate(&self.db, params.per_page);
+
+        let total = paginator.num_items().await?;
+        let items = paginator.fetch_page(params.page - 1).await?;
+        Ok((items, total))
+    }
+}
+
+/// 内存管理操作审计数据访问实现（仅用于测试）
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct InMemoryAdminAuditRepo {
+    entries: std::sync::Mutex<Vec<admin_audit::Model>>,
+}
+
+#[cfg(test)]
+impl InMemoryAdminAuditRepo {
+    /// 预置一条审计记录
+    pub fn seed(&self, model: admin_audit::Model) {
+        self.entries.lock().unwrap().push(model);
+    }
+}
+
+#[cfg(test)]
+impl AdminAuditRepo for InMemoryAdminAuditRepo {
+    async fn insert(&self, entry: NewAdminAudit) -> Result<(), DbErr> {
+        let mut entries = self.entries.lock().unwrap();
+        let id = entries.len() as i64 + 1;
+        entries.push(admin_audit::Model {
+            id,
+            actor_id: entry.actor_id,
+            method: entry.method,
+            route: entry.route,
+            request_body: entry.request_body,
+            status: entry.status as i16,
+            created_at: Utc::now().fixed_offset(),
+        });
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        filter: &AdminAuditFilter,
+        params: PageParams,
+    ) -> Result<(Vec<admin_audit::Model>, u64), DbErr> {
+        let mut matched: Vec<_> = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|m| {
+                filter.actor_id.is_none_or(|id| m.actor_id == id)
+                    && filter
+                        .route_prefix
+                        .as_ref()
+                        .is_none_or(|prefix| m.route.starts_with(prefix.as_str()))
+                    && filter.from.is_none_or(|from| m.created_at >= from)
+                    && filter.to.is_none_or(|to| m.created_at < to)
+            })
+            .cloned()
+            .collect();
+        matched.sort_by_key(|m| std::cmp::Reverse((m.created_at, m.id)));
+
+        let total = matched.len() as u64;
+        let items = matched
+            .into_iter()
+            .skip(params.offset() as usize)
+            .take(params.per_page as usize)
+            .collect();
+        Ok((items, total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::user_id;
+    use migration::{Migrator, MigratorTrait};
+
+    #[tokio::test]
+    async fn test_insert_assigns_snowflake_ids() {
+        // 内存 SQLite 每个连接是独立的数据库，限制为单连接才能看到迁移结果
+        let mut options = sea_orm::ConnectOptions::new("sqlite::memory:");
+        options.max_connections(1);
+        let db = sea_orm::Database::connect(options).await.unwrap();
+        Migrator::up(&db, None).await.unwrap();
+        let repo = SeaOrmAdminAuditRepo::new(db);
+
+        for route in ["/v1/admin/a", "/v1/admin/b"] {
+            repo.insert(NewAdminAudit {
+                actor_id: user_id(1),
+                method: "POST".to_string(),
+                route: route.to_string(),
+                request_body: None,
+                status: 200,
+            })
+            .await
+            .unwrap();
+        }
+
+        let (items, total) = repo
+            .list(&AdminAuditFilter::default(), PageParams::default())
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+        // 主键来自 ID 生成器而不是自增序列，后写入的记录 ID 更大
+        let ids: Vec<i64> = items.iter().map(|m| m.id).collect();
+        assert!(ids[0] > ids[1] && ids[1] > 2, "{ids:?}");
+    }
+}
@@ -0,0 +1,361 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use entity::user::UserId;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{DatabaseConnection, DbErr};
+use tracing::instrument;
+
+use crate::{
+    AppState, ValidationError,
+    core::response::{PageParams, PaginatedResponse},
+    error::AppError,
+    shared::FromState,
+};
+
+use super::dto::{AdminActionQuery, AppliedMigrationDto, AuditLogDto, MigrationStatusResponse};
+use super::repo::{AdminAuditFilter, AdminAuditRepo, NewAdminAudit, SeaOrmAdminAuditRepo};
+
+/// 审计日志服务
+///
+/// 负责写入和查询管理操作审计记录。审计表增长很快，查询方法均分页，
+/// 且只按已建索引的列过滤：`(actor_id, created_at)`、`(route, created_at)`、`created_at`。
+#[derive(Debug, Clone)]
+pub struct AuditLogService<R = SeaOrmAdminAuditRepo> {
+    repo: R,
+}
+
+impl FromState for AuditLogService<SeaOrmAdminAuditRepo> {
+    fn from_state(app: &AppState) -> Self {
+        Self::new(SeaOrmAdminAuditRepo::from_state(app))
+    }
+}
+
+impl<R: AdminAuditRepo> AuditLogService<R> {
+    /// 使用指定的数据访问实现创建审计日志服务
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    /// 写入一条管理操作审计记录
+    #[instrument(skip(self, entry), fields(actor_id = %entry.actor_id, route = %entry.route))]
+    pub async fn record(&self, entry: NewAdminAudit) -> Result<(), AppError> {
+        self.repo
+            .insert(entry)
+            .await
+            .context("写入管理操作审计记录失败")?;
+        Ok(())
+    }
+
+    /// 按操作者和时间范围分页查询管理操作，按时间倒序
+    ///
+    /// # 返回
+    /// 成功返回审计记录分页列表，`from` 不早于 `to` 时返回验证错误
+    #[instrument(skip(self))]
+    pub async fn list_admin_actions(
+        &self,
+        query: AdminActionQuery,
+        params: PageParams,
+    ) -> Result<PaginatedResponse<AuditLogDto>, AppError> {
+        let filter = AdminAuditFilter {
+            actor_id: query.actor_id,
+            from: query.from,
+            to: query.to,
+            ..Default::default()
+        };
+        self.search(filter, params).await
+    }
+
+    /// 分页查询指定用户执行的操作，按时间倒序
+    #[instrument(skip(self))]
+    pub async fn find_by_user(
+        &self,
+        user_id: UserId,
+        params: PageParams,
+    ) -> Result<PaginatedResponse<AuditLogDto>, AppError> {
+        let filter = AdminAuditFilter {
+            actor_id: Some(user_id),
+            ..Default::default()
+        };
+        self.search(filter, params).await
+    }
+
+    /// 分页查询路由以 `path_prefix` 开头的操作，按时间倒序
+    ///
+    /// 前缀按字面匹配，`%`、`_` 不作为通配符。
+    #[instrument(skip(self))]
+    pub async fn find_by_path(
+        &self,
+        path_prefix: &str,
+        params: PageParams,
+    ) -> Result<PaginatedResponse<AuditLogDto>, AppError> {
+        if path_prefix.is_empty() {
+            return Err(ValidationError::custom("路由前缀不能为空").into());
+        }
+        let filter = AdminAuditFilter {
+            route_prefix: Some(path_prefix.to_owned()),
+            ..Default::default()
+        };
+        self.search(filter, params).await
+    }
+
+    /// 分页查询 `[from, to)` 时间范围内的操作，按时间倒序
+    #[instrument(skip(self))]
+    pub async fn find_by_date_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        params: PageParams,
+    ) -> Result<PaginatedResponse<AuditLogDto>, AppError> {
+        let filter = AdminAuditFilter {
+            from: Some(from),
+            to: Some(to),
+            ..Default::default()
+        };
+        self.search(filter, params).await
+    }
+
+    async fn search(
+        &self,
+        filter: AdminAuditFilter,
+        params: PageParams,
+    ) -> Result<PaginatedResponse<AuditLogDto>, AppError> {
+        if let (Some(from), Some(to)) = (filter.from, filter.to)
+            && from >= to
+        {
+            return Err(ValidationError::custom("起始时间必须早于结束时间").into());
+        }
+
+        let params = params.normalized();
+        let (entries, total) = self
+            .repo
+            .list(&filter, params)
+            .await
+            .context("查询管理操作审计记录失败")?;
+        let items = entries.into_iter().map(AuditLogDto::from).collect();
+
+        Ok(PaginatedResponse::new(items, total, params).with_kind("AuditLogList"))
+    }
+}
+
+/// 数据库迁移状态服务
+///
+/// 读取 `seaql_migrations` 表并与代码中定义的迁移比对，无需在生产容器中运行 `sea-orm-cli`。
+#[derive(Debug, Clone)]
+pub struct MigrationService {
+    db: DatabaseConnection,
+}
+
+impl FromState for MigrationService {
+    fn from_state(app: &AppState) -> Self {
+        Self::new(app.db.clone())
+    }
+}
+
+impl MigrationService {
+    /// 使用指定的数据库连接创建迁移状态服务
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 查询已执行和待执行的迁移
+    ///
+    /// # 返回
+    /// 成功返回迁移状态，数据库无法连接时返回 AppError::ServiceUnavailable
+    #[instrument(skip(self))]
+    pub async fn status(&self) -> Result<MigrationStatusResponse, AppError> {
+        let pending = Migrator::get_pending_migrations(&self.db)
+            .await
+            .map_err(unavailable_or_internal)?
+            .iter()
+            .map(|m| m.name().to_string())
+            .collect();
+        let applied = Migrator::get_migration_models(&self.db)
+            .await
+            .map_err(unavailable_or_internal)?
+            .into_iter()
+            .map(|m| AppliedMigrationDto {
+                name: m.version,
+                applied_at: DateTime::from_timestamp(m.applied_at, 0).unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(MigrationStatusResponse { applied, pending })
+    }
+}
+
+/// 连接类错误转换为 503，其他数据库错误附加上下文后按内部错误处理
+fn unavailable_or_internal(e: DbErr) -> AppError {
+    match e {
+        DbErr::Conn(_) | DbErr::ConnectionAcquire(_) => {
+            AppError::ServiceUnavailable(format!("数据库不可用：{e}"))
+        }
+        e => anyhow::Error::new(e).context("读取迁移状态失败").into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::admin::repo::InMemoryAdminAuditRepo;
+    use crate::test_support::user_id;
+    use chrono::{Duration, Utc};
+    use entity::admin_audit;
+
+    fn service() -> AuditLogService<InMemoryAdminAuditRepo> {
+        let repo = InMemoryAdminAuditRepo::default();
+        let start = Utc::now().fixed_offset() - Duration::hours(10);
+        for id in 1..=6 {
+            repo.seed(admin_audit::Model {
+                id,
+                actor_id: if id % 2 == 0 { user_id(2) } else { user_id(1) },
+                method: "PUT".to_string(),
+                route: if id <= 4 {
+                    "/v1/admin/logging/level".to_string()
+                } else {
+                    "/v1/admin/users/1/disable".to_string()
+                },
+                request_body: None,
+                status: 200,
+                created_at: start + Duration::hours(id),
+            });
+        }
+        AuditLogService::new(repo)
+    }
+
+    fn page(page: u64, per_page: u64) -> PageParams {
+        PageParams { page, per_page }
+    }
+
+    #[tokio::test]
+    async fn test_list_filters_by_actor_and_date_range() {
+        let service = service();
+        let now = Utc::now();
+
+        let by_actor = AdminActionQuery {
+            actor_id: Some(user_id(2)),
+            ..Default::default()
+        };
+        let response = service
+            .list_admin_actions(by_actor, PageParams::default())
+            .await
+            .unwrap();
+        assert_eq!(response.total(), 3);
+
+        // 第 3-4 小时的记录（id 3、4）
+        let in_range = AdminActionQuery {
+            actor_id: None,
+            from: Some(now - Duration::hours(7) - Duration::minutes(1)),
+            to: Some(now - Duration::hours(5) - Duration::minutes(1)),
+        };
+        let response = service
+            .list_admin_actions(in_range, PageParams::default())
+            .await
+            .unwrap();
+        assert_eq!(response.total(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_finders() {
+        let service = service();
+        let now = Utc::now();
+
+        let response = service
+            .find_by_user(user_id(1), PageParams::default())
+            .await
+            .unwrap();
+        assert_eq!(response.total(), 3);
+
+        let response = service
+            .find_by_path("/v1/admin/users", PageParams::default())
+            .await
+            .unwrap();
+        assert_eq!(response.total(), 2);
+        let response = service
+            .find_by_path("/v1/admin/%", PageParams::default())
+            .await
+            .unwrap();
+        assert_eq!(response.total(), 0);
+
+        let response = service
+            .find_by_date_range(now - Duration::hours(6), now, page(1, 2))
+            .await
+            .unwrap();
+        assert_eq!(response.total(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_record_then_list() {
+        let service = AuditLogService::new(InMemoryAdminAuditRepo::default());
+        service
+            .record(NewAdminAudit {
+                actor_id: user_id(7),
+                method: "PUT".to_string(),
+                route: "/v1/admin/logging/level".to_string(),
+                request_body: Some(r#"{"filter":"debug"}"#.to_string()),
+                status: 200,
+            })
+            .await
+            .unwrap();
+
+        let query = AdminActionQuery {
+            actor_id: Some(user_id(7)),
+            ..Default::default()
+        };
+        let response = service
+            .list_admin_actions(query, PageParams::default())
+            .await
+            .unwrap();
+        assert_eq!(response.total(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_inverted_date_range_is_rejected() {
+        let now = Utc::now();
+        let query = AdminActionQuery {
+            actor_id: None,
+            from: Some(now),
+            to: Some(now - Duration::hours(1)),
+        };
+        let result = service()
+            .list_admin_actions(query, PageParams::default())
+            .await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
+    async fn migrated_db(steps: u32) -> DatabaseConnection {
+        // 内存 SQLite 每个连接是独立的数据库，限制为单连接才能看到迁移结果
+        let mut options = sea_orm::ConnectOptions::new("sqlite::memory:");
+        options.max_connections(1);
+        let db = sea_orm::Database::connect(options).await.unwrap();
+        Migrator::up(&db, Some(steps)).await.unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_migration_status_lists_applied_and_pending() {
+        let status = MigrationService::new(migrated_db(3).await)
+            .status()
+            .await
+            .unwrap();
+
+        let total = Migrator::migrations().len();
+        assert_eq!(status.applied.len(), 3);
+        assert_eq!(status.pending.len(), total - 3);
+        assert_eq!(status.applied[0].name, "m20220101_000001_create_user_table");
+        assert!(status.applied[0].applied_at > Utc::now() - Duration::minutes(1));
+        assert_eq!(status.pending[0], "m20220101_000004_create_file_table");
+    }
+
+    #[tokio::test]
+    async fn test_migration_status_unavailable_without_database() {
+        // 延迟连接到没有监听的端口，首次查询时获取连接失败
+        let mut options = sea_orm::ConnectOptions::new("postgres://app@127.0.0.1:1/app");
+        options
+            .connect_lazy(true)
+            .acquire_timeout(std::time::Duration::from_millis(200));
+        let db = sea_orm::Database::connect(options).await.unwrap();
+
+        let err = MigrationService::new(db).status().await.unwrap_err();
+        assert!(matches!(err, AppError::ServiceUnavailable(_)));
+    }
+}
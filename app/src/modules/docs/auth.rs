@@ -0,0 +1,192 @@
+//! 文档路由的访问认证
+//!
+//! `docs.username` / `docs.password` 启用 HTTP Basic 认证，`docs.allow_admin_token` 允许管理员
+//! 携带 JWT 访问。认证失败返回 401 并带上 `WWW-Authenticate`，浏览器会弹出原生的登录框。
+
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::http::{HeaderMap, HeaderValue};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use subtle::ConstantTimeEq;
+use tracing::warn;
+
+use crate::AppState;
+use crate::core::config::DocsConfig;
+use crate::core::middleware::auth::is_admin;
+use crate::error::{AppError, AuthError};
+use crate::shared::SharedSecret;
+
+/// 质询中的认证域，浏览器在登录框中显示
+const REALM: &str = "API Docs";
+
+/// 文档访问认证（由 `docs` 配置创建）
+#[derive(Debug)]
+pub(super) struct DocsGuard {
+    /// Basic 认证的用户名和密码
+    credentials: Option<(String, SharedSecret)>,
+
+    /// 是否接受管理员的 JWT
+    allow_admin_token: bool,
+}
+
+impl DocsGuard {
+    /// 创建访问认证，配置中未启用任何认证方式时返回 `None`
+    pub(super) fn new(config: &DocsConfig) -> Option<Self> {
+        if !config.is_protected() {
+            return None;
+        }
+        Some(Self {
+            credentials: config.username.clone().zip(config.password.clone()),
+            allow_admin_token: config.allow_admin_token,
+        })
+    }
+
+    /// 请求是否携带有效的 Basic 凭据或管理员 JWT
+    async fn authorize(&self, state: &AppState, headers: &HeaderMap) -> Result<bool, AppError> {
+        let Some((scheme, param)) = headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split_once(' '))
+        else {
+            return Ok(false);
+        };
+
+        if scheme.eq_ignore_ascii_case("basic")
+            && let Some((username, password)) = &self.credentials
+        {
+            let Ok(decoded) = STANDARD.decode(param.trim()) else {
+                return Ok(false);
+            };
+            let Some((user, pass)) = std::str::from_utf8(&decoded)
+                .ok()
+                .and_then(|s| s.split_once(':'))
+            else {
+                return Ok(false);
+            };
+            // 两项都比较完再合并结果，耗时不随哪一项不匹配而变化
+            let user_ok = user.as_bytes().ct_eq(username.as_bytes());
+            let pass_ok = pass.as_bytes().ct_eq(password.as_bytes());
+            return Ok(bool::from(user_ok & pass_ok));
+        }
+
+        if scheme.eq_ignore_ascii_case("bearer") && self.allow_admin_token {
+            let Ok(user_id) = state.jwt_service.extract_user_id(param.trim()) else {
+                return Ok(false);
+            };
+            return is_admin(&state.db, user_id).await;
+        }
+
+        Ok(false)
+    }
+
+    /// 认证失败的 401 响应，配置了 Basic 认证时质询 Basic，否则质询 Bearer
+    fn challenge(&self) -> Response {
+        let scheme = if self.credentials.is_some() {
+            "Basic"
+        } else {
+            "Bearer"
+        };
+        let mut response = AppError::Auth(AuthError::InvalidToken).into_response();
+        response.headers_mut().insert(
+            WWW_AUTHENTICATE,
+            HeaderValue::from_str(&format!(r#"{scheme} realm="{REALM}", charset="UTF-8""#))
+                .expect("质询头只包含 ASCII 字符"),
+        );
+        response
+    }
+}
+
+/// 文档路由的认证中间件，未通过认证时返回 401 质询
+pub(super) async fn require_docs_auth(
+    State((guard, state)): State<(Arc<DocsGuard>, AppState)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match guard.authorize(&state, request.headers()).await {
+        Ok(true) => next.run(request).await,
+        Ok(false) => {
+            warn!(path = %request.uri().path(), "文档访问认证失败");
+            guard.challenge()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::AppConfig;
+    use crate::test_support::test_state;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    fn app(config: &DocsConfig) -> axum::Router {
+        let guard = Arc::new(DocsGuard::new(config).unwrap());
+        let state = test_state(&AppConfig::default());
+        axum::Router::new()
+            .route("/docs", get(|| async { "docs" }))
+            .layer(axum::middleware::from_fn_with_state(
+                (guard, state),
+                require_docs_auth,
+            ))
+    }
+
+    async fn call(app: axum::Router, authorization: Option<&str>) -> Response {
+        let mut request = Request::get("/docs");
+        if let Some(value) = authorization {
+            request = request.header(AUTHORIZATION, value);
+        }
+        app.oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_challenges_and_accepts_credentials() {
+        let config = DocsConfig {
+            username: Some("docs".to_string()),
+            password: Some(SharedSecret::new("hunter2")),
+            ..Default::default()
+        };
+        let basic = |creds: &str| format!("Basic {}", STANDARD.encode(creds));
+
+        let response = call(app(&config), None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            r#"Basic realm="API Docs", charset="UTF-8""#
+        );
+
+        for wrong in ["docs:wrong", "other:hunter2", "docs"] {
+            let response = call(app(&config), Some(&basic(wrong))).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{wrong}");
+        }
+        // 未开启 allow_admin_token 时不接受 JWT
+        let response = call(app(&config), Some("Bearer some.jwt.token")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = call(app(&config), Some(&basic("docs:hunter2"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_admin_token_only_challenges_bearer() {
+        let config = DocsConfig {
+            allow_admin_token: true,
+            ..Default::default()
+        };
+        let response = call(app(&config), Some("Bearer not-a-jwt")).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers()[WWW_AUTHENTICATE],
+            r#"Bearer realm="API Docs", charset="UTF-8""#
+        );
+    }
+}
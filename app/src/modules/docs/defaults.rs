@@ -0,0 +1,212 @@
+//! 所有接口共有的响应文档
+//!
+//! 各接口的 `*_docs` 函数只描述成功响应，[`with_defaults`] 补充每个接口都可能返回的标准错误响应
+//! （400 / 401 / 429 / 500，响应体为 `ApiResponse<()>`），并为所有响应标注全局中间件添加的响应头：
+//! 请求 ID（`x-request-id`）和限流配额（`x-ratelimit-limit` / `x-ratelimit-remaining`，429 响应另有
+//! `retry-after`）。响应头以 `$ref` 引用 `components/headers`，生成文档时需要调用一次
+//! [`register_default_headers`]。
+//!
+//! 没有单独 `*_docs` 函数的路由可以通过 [`DefaultResponses::with_default_responses`] 为整个路由器补充。
+
+use aide::OperationOutput;
+use aide::axum::ApiRouter;
+use aide::generate::in_context;
+use aide::openapi::{
+    Header, Operation, ParameterSchemaOrContent, PathItem, ReferenceOr, SchemaObject, StatusCode,
+};
+use aide::transform::{TransformOpenApi, TransformOperation};
+use serde_json::Value;
+
+use crate::ApiResponse;
+
+/// 请求 ID 响应头组件名
+pub const REQUEST_ID_HEADER_COMPONENT: &str = "X-Request-ID";
+
+/// 限流容量响应头组件名
+pub const RATE_LIMIT_LIMIT_HEADER_COMPONENT: &str = "X-RateLimit-Limit";
+
+/// 限流剩余配额响应头组件名
+pub const RATE_LIMIT_REMAINING_HEADER_COMPONENT: &str = "X-RateLimit-Remaining";
+
+/// 限流重试等待时间响应头组件名
+pub const RETRY_AFTER_HEADER_COMPONENT: &str = "Retry-After";
+
+/// 标准错误响应的状态码和说明
+const DEFAULT_ERRORS: [(u16, &str); 4] = [
+    (400, "请求参数无效"),
+    (401, "未认证或令牌无效"),
+    (429, "请求频率超限"),
+    (500, "内部服务器错误"),
+];
+
+/// 为操作补充标准错误响应和公共响应头，已声明的同状态码响应保持不变
+pub fn with_defaults(mut op: TransformOperation) -> TransformOperation {
+    apply_defaults(op.inner_mut());
+    op
+}
+
+fn apply_defaults(operation: &mut Operation) {
+    let missing: Vec<_> = DEFAULT_ERRORS
+        .into_iter()
+        .filter(|(code, _)| {
+            operation
+                .responses
+                .as_ref()
+                .is_none_or(|r| !r.responses.contains_key(&StatusCode::Code(*code)))
+        })
+        .collect();
+    let generated: Vec<_> = in_context(|ctx| {
+        missing
+            .into_iter()
+            .filter_map(|(code, description)| {
+                let mut response = ApiResponse::<()>::operation_response(ctx, operation)?;
+                response.description = description.to_string();
+                Some((code, response))
+            })
+            .collect()
+    });
+
+    let responses = operation.responses.get_or_insert_with(Default::default);
+    for (code, response) in generated {
+        responses
+            .responses
+            .insert(StatusCode::Code(code), ReferenceOr::Item(response));
+    }
+
+    for (code, response) in &mut responses.responses {
+        let ReferenceOr::Item(response) = response else {
+            continue;
+        };
+        let mut names = vec![
+            REQUEST_ID_HEADER_COMPONENT,
+            RATE_LIMIT_LIMIT_HEADER_COMPONENT,
+            RATE_LIMIT_REMAINING_HEADER_COMPONENT,
+        ];
+        if *code == StatusCode::Code(429) {
+            names.push(RETRY_AFTER_HEADER_COMPONENT);
+        }
+        for name in names {
+            response
+                .headers
+                .entry(name.to_string())
+                .or_insert_with(|| ReferenceOr::ref_(&format!("#/components/headers/{name}")));
+        }
+    }
+}
+
+/// 在 OpenAPI 文档的 `components/headers` 中登记 [`with_defaults`] 引用的响应头
+pub fn register_default_headers(mut api: TransformOpenApi) -> TransformOpenApi {
+    let headers = &mut api
+        .inner_mut()
+        .components
+        .get_or_insert_with(Default::default)
+        .headers;
+    let integer = serde_json::json!({ "type": "integer", "format": "uint32", "minimum": 0 });
+    for (name, description, schema) in [
+        (
+            REQUEST_ID_HEADER_COMPONENT,
+            "请求 ID，排查问题时提供给服务端",
+            serde_json::json!({ "type": "string" }),
+        ),
+        (
+            RATE_LIMIT_LIMIT_HEADER_COMPONENT,
+            "当前客户端的限流容量（突发请求上限）",
+            integer.clone(),
+        ),
+        (
+            RATE_LIMIT_REMAINING_HEADER_COMPONENT,
+            "当前客户端剩余的可用请求数",
+            integer.clone(),
+        ),
+        (
+            RETRY_AFTER_HEADER_COMPONENT,
+            "距离可以再次请求的秒数",
+            integer,
+        ),
+    ] {
+        headers.insert(
+            name.to_string(),
+            ReferenceOr::Item(header(description, schema)),
+        );
+    }
+    api
+}
+
+fn header(description: &str, schema: Value) -> Header {
+    Header {
+        description: Some(description.to_string()),
+        style: Default::default(),
+        required: false,
+        deprecated: None,
+        format: ParameterSchemaOrContent::Schema(SchemaObject {
+            json_schema: serde_json::from_value(schema).expect("响应头 schema 有效"),
+            external_docs: None,
+            example: None,
+        }),
+        example: None,
+        examples: Default::default(),
+        extensions: Default::default(),
+    }
+}
+
+/// 为路由器中的所有操作补充标准响应
+pub trait DefaultResponses {
+    /// 对路由器中已注册的每个操作应用 [`with_defaults`]
+    fn with_default_responses(self) -> Self;
+}
+
+impl<S> DefaultResponses for ApiRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn with_default_responses(self) -> Self {
+        self.with_path_items(|mut item| {
+            apply_to_path_item(item.inner_mut());
+            item
+        })
+    }
+}
+
+fn apply_to_path_item(path_item: &mut PathItem) {
+    let operations = [
+        &mut path_item.get,
+        &mut path_item.put,
+        &mut path_item.post,
+        &mut path_item.delete,
+        &mut path_item.options,
+        &mut path_item.head,
+        &mut path_item.patch,
+        &mut path_item.trace,
+    ];
+    for operation in operations.into_iter().flatten() {
+        apply_defaults(operation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_defaults_keeps_declared_responses() {
+        let mut operation = Operation::default();
+        let _ = TransformOperation::new(&mut operation)
+            .response_with::<401, ApiResponse<()>, _>(|r| r.description("令牌已过期"))
+            .with(with_defaults)
+            // 重复应用不会重复添加
+            .with(with_defaults);
+
+        let responses = &operation.responses.unwrap().responses;
+        let codes: Vec<_> = responses.keys().cloned().collect();
+        assert_eq!(codes, [401, 400, 429, 500].map(StatusCode::Code).to_vec());
+        let ReferenceOr::Item(unauthorized) = &responses[&StatusCode::Code(401)] else {
+            panic!("401 应为内联响应");
+        };
+        assert_eq!(unauthorized.description, "令牌已过期");
+        assert_eq!(unauthorized.headers.len(), 3);
+        let ReferenceOr::Item(limited) = &responses[&StatusCode::Code(429)] else {
+            panic!("429 应为内联响应");
+        };
+        assert!(limited.headers.contains_key(RETRY_AFTER_HEADER_COMPONENT));
+    }
+}
@@ -0,0 +1,91 @@
+//! OpenAPI 文档导出（`--export-openapi`）
+//!
+//! CI 比较接口契约时只需要文档本身，不启动服务器，也不连接数据库和 Redis。
+
+use aide::openapi::OpenApi;
+use clap::ValueEnum;
+use serde_json::Value;
+use yaml_rust2::{Yaml, YamlEmitter, yaml};
+
+/// 导出文档的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OpenApiFormat {
+    /// 格式化的 JSON
+    #[default]
+    Json,
+
+    /// YAML
+    Yaml,
+}
+
+/// 将 OpenAPI 文档序列化为指定格式的文本
+pub fn render_openapi(api: &OpenApi, format: OpenApiFormat) -> Result<String, String> {
+    match format {
+        OpenApiFormat::Json => {
+            serde_json::to_string_pretty(api).map_err(|e| format!("JSON 序列化失败：{e}"))
+        }
+        OpenApiFormat::Yaml => {
+            let value = serde_json::to_value(api).map_err(|e| format!("文档序列化失败：{e}"))?;
+            let mut out = String::new();
+            YamlEmitter::new(&mut out)
+                .dump(&to_yaml(value))
+                .map_err(|e| format!("YAML 序列化失败：{e}"))?;
+            out.push('\n');
+            Ok(out)
+        }
+    }
+}
+
+/// JSON 值转换为 YAML 节点，对象保持原有的键顺序
+fn to_yaml(value: Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        Value::String(s) => Yaml::String(s),
+        Value::Array(items) => Yaml::Array(items.into_iter().map(to_yaml).collect()),
+        Value::Object(map) => Yaml::Hash(
+            map.into_iter()
+                .map(|(k, v)| (Yaml::String(k), to_yaml(v)))
+                .collect::<yaml::Hash>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use yaml_rust2::YamlLoader;
+
+    #[test]
+    fn test_yaml_roundtrip_keeps_scalars() {
+        let value = json!({
+            "openapi": "3.1.0",
+            "paths": { "/v1/files": { "get": { "deprecated": false } } },
+            "minimum": 0,
+            "ratio": 0.5,
+            "version": "1.0",
+            "empty": null,
+            "tags": ["文件", "true"],
+        });
+        let mut out = String::new();
+        YamlEmitter::new(&mut out).dump(&to_yaml(value)).unwrap();
+        let doc = &YamlLoader::load_from_str(&out).unwrap()[0];
+
+        assert_eq!(doc["openapi"].as_str(), Some("3.1.0"));
+        assert_eq!(
+            doc["paths"]["/v1/files"]["get"]["deprecated"].as_bool(),
+            Some(false)
+        );
+        assert_eq!(doc["minimum"].as_i64(), Some(0));
+        assert_eq!(doc["ratio"].as_f64(), Some(0.5));
+        // 看起来像数字或布尔值的字符串仍然是字符串
+        assert_eq!(doc["version"].as_str(), Some("1.0"));
+        assert_eq!(doc["tags"][1].as_str(), Some("true"));
+        assert!(doc["empty"].is_null());
+    }
+}
@@ -1,6 +1,28 @@
-use std::sync::Arc;
+//! API 文档路由
+//!
+//! 挂载在 `docs.path`（默认 `/docs`）下：
+//! - GET / - Scalar 文档页面，脚本和样式由 aide 在编译时嵌入，离线环境也能打开
+//! - GET /openapi.json - OpenAPI 文档 JSON，序列化结果由 [`OpenApiCache`] 缓存
+//!
+//! 配置了 `docs.username` / `docs.password` 或 `docs.allow_admin_token` 时，以上路由都需要认证，
+//! 见 [`auth`]。
+//!
+//! 不启动服务器时可通过 `--export-openapi` 导出文档，见 [`render_openapi`]。
+//! 各接口共有的错误响应和响应头由 [`with_defaults`] 补充，按模块划分的标签见 [`tags`]。
+
+mod auth;
+mod defaults;
+mod export;
+mod tags;
+
+pub use defaults::{DefaultResponses, register_default_headers, with_defaults};
+pub use export::{OpenApiFormat, render_openapi};
+pub use tags::{ModuleTag, ModuleTagRouter, register_module_tags};
+
+use std::sync::{Arc, OnceLock};
 
 use crate::AppState;
+use crate::core::config::DocsConfig;
 use aide::{
     axum::{
         ApiRouter, IntoApiResponse,
@@ -9,58 +31,85 @@ use aide::{
     openapi::OpenApi,
     scalar::Scalar,
 };
-use axum::{Extension, Json, response::IntoResponse};
+use axum::{
+    Extension, body::Bytes, extract::State, http::header::CONTENT_TYPE, response::IntoResponse,
+};
+
+/// 文档模块在 OpenAPI 文档中的标签
+pub(super) const TAG: ModuleTag = ModuleTag::new("文档", "API 文档页面");
+
+/// 序列化后的 OpenAPI 文档缓存
+///
+/// 文档在构建路由时生成，序列化结果只计算一次：`docs.preload` 开启时在启动阶段写入，
+/// 否则在第一次请求时写入，之后的请求直接返回缓存的 JSON。
+#[derive(Debug, Clone, Default)]
+pub struct OpenApiCache {
+    json: Arc<OnceLock<Bytes>>,
+}
+
+impl OpenApiCache {
+    /// 返回缓存的 JSON，尚未缓存时序列化 `api` 并写入
+    pub fn get_or_render(&self, api: &OpenApi) -> Bytes {
+        self.json
+            .get_or_init(|| {
+                serde_json::to_vec(api)
+                    .expect("OpenAPI 文档可以序列化为 JSON")
+                    .into()
+            })
+            .clone()
+    }
+
+    /// 已缓存的 JSON
+    pub fn get(&self) -> Option<Bytes> {
+        self.json.get().cloned()
+    }
+}
 
-pub fn docs_routes(state: &AppState) -> ApiRouter {
-    // We infer the return types for these routes
-    // as an example.
-    //
-    // As a result, the `serve_redoc` route will
-    // have the `text/html` content-type correctly set
-    // with a 200 status.
+/// 构建文档路由，挂载在 `config.path` 下
+///
+/// 页面从 `config.spec_path()` 加载 OpenAPI JSON，该接口读取应用上的 `Extension<Arc<OpenApi>>`。
+///
+/// # 参数
+/// * `state` - 应用状态，包含 OpenAPI 文档缓存
+/// * `config` - 文档配置
+///
+/// # 返回
+/// 返回配置好的路由器
+pub fn docs_routes(state: &AppState, config: &DocsConfig) -> ApiRouter {
+    // 文档页面的响应类型（text/html）由处理器推断，生成后关闭推断，避免影响其他路由
     aide::generate::infer_responses(true);
 
     let router: ApiRouter = ApiRouter::new()
-        .api_route_with(
+        .api_route(
             "/",
             get_with(
-                Scalar::new("/docs/private/api.json")
+                Scalar::new(config.spec_path())
                     .with_title("DropBuddy Docs")
                     .axum_handler(),
-                |op| op.description("This documentation page."),
+                |op| op.description("API 文档页面"),
             ),
-            |p| p.security_requirement("ApiKey"),
         )
-        // .api_route_with(
-        //     "/redoc",
-        //     get_with(
-        //         Redoc::new("/docs/private/api.json")
-        //             .with_title("Aide Axum")
-        //             .axum_handler(),
-        //         |op| op.description("This documentation page."),
-        //     ),
-        //     |p| p.security_requirement("ApiKey"),
-        // )
-        // .api_route_with(
-        //     "/swagger",
-        //     get_with(
-        //         Swagger::new("/docs/private/api.json")
-        //             .with_title("Aide Axum")
-        //             .axum_handler(),
-        //         |op| op.description("This documentation page."),
-        //     ),
-        //     |p| p.security_requirement("ApiKey"),
-        // )
-        .route("/private/api.json", get(serve_docs))
+        .route("/openapi.json", get(serve_docs))
+        .with_default_responses()
+        .with_module_tag(&TAG)
         .with_state(state.clone());
 
-    // Afterwards we disable response inference because
-    // it might be incorrect for other routes.
     aide::generate::infer_responses(false);
 
-    router
+    match auth::DocsGuard::new(config) {
+        Some(guard) => router.layer(axum::middleware::from_fn_with_state(
+            (Arc::new(guard), state.clone()),
+            auth::require_docs_auth,
+        )),
+        None => router,
+    }
 }
 
-async fn serve_docs(Extension(api): Extension<Arc<OpenApi>>) -> impl IntoApiResponse {
-    Json(api).into_response()
+/// 返回 OpenAPI 文档 JSON，首次请求时序列化并缓存
+async fn serve_docs(
+    State(state): State<AppState>,
+    Extension(api): Extension<Arc<OpenApi>>,
+) -> impl IntoApiResponse {
+    let json = state.openapi.get_or_render(&api);
+    ([(CONTENT_TYPE, "application/json")], json).into_response()
 }
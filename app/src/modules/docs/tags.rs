@@ -0,0 +1,101 @@
+//! 按模块划分的 OpenAPI 标签
+//!
+//! 每个业务模块声明一个 [`ModuleTag`]，在 `routes()` 中通过 [`ModuleTagRouter::with_module_tag`]
+//! 为其下所有操作打上标签，接口的 `*_docs` 函数不再单独调用 `.tag(...)`：
+//!
+//! ```ignore
+//! pub const TAG: ModuleTag = ModuleTag::new("限流", "查询当前客户端的限流配额");
+//!
+//! ApiRouter::new()
+//!     .api_route("/status", get_with(handler::status, handler::status_docs))
+//!     .with_module_tag(&TAG)
+//!     .with_state(state)
+//! ```
+//!
+//! 标签的名称和说明由 [`register_module_tags`] 从 [`MODULE_TAGS`](crate::modules::MODULE_TAGS)
+//! 收集到文档的 `tags` 中，只登记实际有操作使用的标签（如关闭文件上传后不出现“文件”）。
+
+use std::collections::BTreeSet;
+
+use aide::axum::ApiRouter;
+use aide::openapi::{Operation, PathItem, ReferenceOr, Tag};
+use aide::transform::TransformOpenApi;
+
+use crate::modules::MODULE_TAGS;
+
+/// 模块在 OpenAPI 文档中的标签
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleTag {
+    /// 标签名称，文档按名称分组
+    pub name: &'static str,
+
+    /// 标签说明
+    pub description: &'static str,
+}
+
+impl ModuleTag {
+    /// 创建模块标签
+    pub const fn new(name: &'static str, description: &'static str) -> Self {
+        Self { name, description }
+    }
+}
+
+/// 为路由器中的所有操作打上模块标签
+pub trait ModuleTagRouter {
+    /// 将路由器中已注册的每个操作的标签设为 `tag`，覆盖原有标签
+    fn with_module_tag(self, tag: &ModuleTag) -> Self;
+}
+
+impl<S> ModuleTagRouter for ApiRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn with_module_tag(self, tag: &ModuleTag) -> Self {
+        let name = tag.name;
+        self.with_path_items(move |mut item| {
+            for operation in operations(item.inner_mut()) {
+                operation.tags = vec![name.to_string()];
+            }
+            item
+        })
+    }
+}
+
+fn operations(path_item: &mut PathItem) -> impl Iterator<Item = &mut Operation> {
+    [
+        &mut path_item.get,
+        &mut path_item.put,
+        &mut path_item.post,
+        &mut path_item.delete,
+        &mut path_item.options,
+        &mut path_item.head,
+        &mut path_item.patch,
+        &mut path_item.trace,
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// 在 OpenAPI 文档的 `tags` 中登记有操作使用的模块标签，按 [`MODULE_TAGS`] 的顺序排列
+pub fn register_module_tags(mut api: TransformOpenApi) -> TransformOpenApi {
+    let mut used = BTreeSet::new();
+    let paths = api
+        .inner_mut()
+        .paths
+        .iter_mut()
+        .flat_map(|p| p.paths.values_mut());
+    for item in paths {
+        if let ReferenceOr::Item(item) = item {
+            used.extend(operations(item).flat_map(|op| op.tags.clone()));
+        }
+    }
+
+    for tag in MODULE_TAGS.iter().filter(|tag| used.contains(tag.name)) {
+        api = api.tag(Tag {
+            name: tag.name.into(),
+            description: Some(tag.description.into()),
+            ..Default::default()
+        });
+    }
+    api
+}
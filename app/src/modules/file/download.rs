@@ -0,0 +1,306 @@
+//! 文件下载的辅助函数
+//!
+//! 包括 `Content-Disposition` 的构建、`Range` 请求的解析和应用签名的下载链接。
+//!
+//! 存储后端不支持预签名时（如本地磁盘），下载链接形如 `/v1/files/{id}/download?expires=...&sig=...`：
+//! `expires` 为过期时间的 Unix 秒数，`sig` 为 `HMAC-SHA256(jwt_secret, "file-download:{id}:{expires}")`
+//! 的 URL 安全 Base64 编码。持有链接即可在过期前免登录下载，修改任一参数都会使签名失效。
+
+use std::ops::Range;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::dto::Disposition;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 为文件下载链接签名
+pub(super) fn sign(secret: &[u8], file_id: i64, expires: i64) -> String {
+    URL_SAFE_NO_PAD.encode(mac(secret, file_id, expires).finalize().into_bytes())
+}
+
+/// 校验下载链接的签名，已过期（`expires` 不晚于 `now`）时同样返回 false
+pub(super) fn verify(secret: &[u8], file_id: i64, expires: i64, sig: &str, now: i64) -> bool {
+    if expires <= now {
+        return false;
+    }
+    let Ok(sig) = URL_SAFE_NO_PAD.decode(sig) else {
+        return false;
+    };
+    mac(secret, file_id, expires).verify_slice(&sig).is_ok()
+}
+
+fn mac(secret: &[u8], file_id: i64, expires: i64) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC 接受任意长度的密钥");
+    mac.update(format!("file-download:{file_id}:{expires}").as_bytes());
+    mac
+}
+
+/// 文件的 ETag：内容的 SHA-256 摘要，文件内容不可修改，因此是强校验器
+pub(super) fn etag(checksum: &str) -> String {
+    format!("\"{checksum}\"")
+}
+
+/// 下载请求应返回的内容区间
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ByteRange {
+    /// 整个文件（200）
+    Full,
+
+    /// 单个区间（206）
+    Partial(Range<u64>),
+
+    /// 区间无法满足（416）
+    Unsatisfiable,
+}
+
+/// 按 RFC 9110 解析 `Range` 和 `If-Range` 请求头
+///
+/// - 未携带 `Range`、单位不是 `bytes` 或格式无法识别时忽略，返回整个文件
+/// - 携带 `If-Range` 但与 `etag` 不一致（包括日期形式和弱校验器）时返回整个文件，避免拼接出不同版本的内容
+/// - 支持 `start-end`、`start-`（到末尾）和 `-n`（最后 n 个字节），`end` 超出文件末尾时截断到末尾
+/// - 起点超出文件末尾、`-0` 以及请求多个区间时视为无法满足
+pub(super) fn parse_range(
+    range: Option<&str>,
+    if_range: Option<&str>,
+    etag: &str,
+    size: u64,
+) -> ByteRange {
+    let Some(range) = range else {
+        return ByteRange::Full;
+    };
+    if if_range.is_some_and(|validator| validator.trim() != etag) {
+        return ByteRange::Full;
+    }
+    let Some((unit, specs)) = range.split_once('=') else {
+        return ByteRange::Full;
+    };
+    if !unit.trim().eq_ignore_ascii_case("bytes") {
+        return ByteRange::Full;
+    }
+    let specs: Vec<_> = specs
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let [spec] = specs[..] else {
+        return if specs.is_empty() {
+            ByteRange::Full
+        } else {
+            ByteRange::Unsatisfiable
+        };
+    };
+    let Some((first, last)) = spec.split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (first, last) = (first.trim(), last.trim());
+    let parse = |value: &str| {
+        value
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| value.parse::<u64>().ok())
+            .flatten()
+    };
+
+    if first.is_empty() {
+        // 最后 n 个字节
+        return match parse(last) {
+            Some(0) => ByteRange::Unsatisfiable,
+            Some(_) if size == 0 => ByteRange::Unsatisfiable,
+            Some(n) => ByteRange::Partial(size.saturating_sub(n)..size),
+            None => ByteRange::Full,
+        };
+    }
+    let Some(start) = parse(first) else {
+        return ByteRange::Full;
+    };
+    let end = if last.is_empty() {
+        size
+    } else {
+        match parse(last) {
+            Some(last) if last >= start => last.saturating_add(1).min(size),
+            _ => return ByteRange::Full,
+        }
+    };
+    if start >= size {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial(start..end)
+}
+
+/// 构建 `Content-Disposition`
+///
+/// `filename` 只保留可打印 ASCII 字符（其余替换为 `_`），供不支持 RFC 6266 的客户端使用；
+/// `filename*` 按 RFC 5987 以 UTF-8 百分号编码保留原始文件名。
+/// 是否允许 `inline` 由调用方按文件类型决定，见 [`super::service::FileService::content_disposition`]。
+pub(super) fn content_disposition(filename: &str, disposition: Disposition) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' => c,
+            _ => '_',
+        })
+        .collect();
+    let mut encoded = String::with_capacity(filename.len());
+    for byte in filename.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    format!(
+        "{}; filename=\"{fallback}\"; filename*=UTF-8''{encoded}",
+        disposition.as_str()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"test-secret";
+
+    #[test]
+    fn test_signature_roundtrip() {
+        let sig = sign(SECRET, 7, 1_000);
+        assert!(verify(SECRET, 7, 1_000, &sig, 999));
+    }
+
+    #[test]
+    fn test_tampered_or_expired_signature_is_rejected() {
+        let sig = sign(SECRET, 7, 1_000);
+        assert!(!verify(SECRET, 8, 1_000, &sig, 999));
+        assert!(!verify(SECRET, 7, 2_000, &sig, 999));
+        assert!(!verify(b"other-secret", 7, 1_000, &sig, 999));
+        assert!(!verify(SECRET, 7, 1_000, &sig[1..], 999));
+        assert!(!verify(SECRET, 7, 1_000, "not base64!", 999));
+        assert!(!verify(SECRET, 7, 1_000, &sig, 1_000));
+    }
+
+    const ETAG: &str = "\"abc\"";
+
+    fn range(header: &str) -> ByteRange {
+        parse_range(Some(header), None, ETAG, 1000)
+    }
+
+    #[test]
+    fn test_parse_single_ranges() {
+        assert_eq!(parse_range(None, None, ETAG, 1000), ByteRange::Full);
+        assert_eq!(range("bytes=0-499"), ByteRange::Partial(0..500));
+        assert_eq!(range("bytes=500-"), ByteRange::Partial(500..1000));
+        assert_eq!(range("bytes=-200"), ByteRange::Partial(800..1000));
+        assert_eq!(range("Bytes = 10 - 19"), ByteRange::Partial(10..20));
+        // 终点或后缀长度超出文件大小时截断到文件范围内
+        assert_eq!(range("bytes=900-5000"), ByteRange::Partial(900..1000));
+        assert_eq!(range("bytes=-5000"), ByteRange::Partial(0..1000));
+        assert_eq!(range("bytes=999-999"), ByteRange::Partial(999..1000));
+    }
+
+    #[test]
+    fn test_parse_unsatisfiable_ranges() {
+        assert_eq!(range("bytes=1000-"), ByteRange::Unsatisfiable);
+        assert_eq!(range("bytes=1000-1005"), ByteRange::Unsatisfiable);
+        assert_eq!(range("bytes=-0"), ByteRange::Unsatisfiable);
+        assert_eq!(range("bytes=0-1,5-6"), ByteRange::Unsatisfiable);
+        assert_eq!(
+            parse_range(Some("bytes=0-"), None, ETAG, 0),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(
+            parse_range(Some("bytes=-1"), None, ETAG, 0),
+            ByteRange::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn test_invalid_ranges_are_ignored() {
+        for header in [
+            "bytes=abc",
+            "bytes=5-1",
+            "bytes=-",
+            "bytes=+1-2",
+            "bytes=1-2-3",
+            "items=0-1",
+            "bytes",
+            "bytes=",
+        ] {
+            assert_eq!(range(header), ByteRange::Full, "{header}");
+        }
+    }
+
+    #[test]
+    fn test_if_range_requires_matching_etag() {
+        let partial = ByteRange::Partial(0..10);
+        assert_eq!(
+            parse_range(Some("bytes=0-9"), Some(ETAG), ETAG, 1000),
+            partial
+        );
+        for validator in ["\"other\"", "W/\"abc\"", "Wed, 21 Oct 2015 07:28:00 GMT"] {
+            assert_eq!(
+                parse_range(Some("bytes=0-9"), Some(validator), ETAG, 1000),
+                ByteRange::Full,
+                "{validator}"
+            );
+        }
+    }
+
+    /// 按 RFC 5987 解码 `filename*` 参数
+    fn decode_ext_filename(header: &str) -> String {
+        let encoded = header.split_once("filename*=UTF-8''").unwrap().1;
+        let mut bytes = Vec::new();
+        let mut rest = encoded.as_bytes();
+        while let [first, tail @ ..] = rest {
+            if *first == b'%' {
+                let hex = std::str::from_utf8(&tail[..2]).unwrap();
+                bytes.push(u8::from_str_radix(hex, 16).unwrap());
+                rest = &tail[2..];
+            } else {
+                bytes.push(*first);
+                rest = tail;
+            }
+        }
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_content_disposition_keeps_original_name_encoded() {
+        assert_eq!(
+            content_disposition("report.pdf", Disposition::Attachment),
+            "attachment; filename=\"report.pdf\"; filename*=UTF-8''report.pdf"
+        );
+        assert_eq!(
+            content_disposition("季度 \"报告\".pdf", Disposition::Attachment),
+            "attachment; filename=\"__ ____.pdf\"; \
+             filename*=UTF-8''%E5%AD%A3%E5%BA%A6%20%22%E6%8A%A5%E5%91%8A%22.pdf"
+        );
+    }
+
+    #[test]
+    fn test_content_disposition_round_trips_unicode_names() {
+        for name in [
+            "annual report 2024.pdf",
+            "季度报告.pdf",
+            "he said \"hi\".png",
+            "résumé 履歴書 \"最終\" (2).pdf",
+            "semi;colon,comma'quote%.pdf",
+        ] {
+            let header = content_disposition(name, Disposition::Inline);
+            assert!(header.starts_with("inline; filename=\""), "{header}");
+            assert!(header.is_ascii(), "{header}");
+            assert_eq!(decode_ext_filename(&header), name);
+
+            // 兼容参数中的引号和反斜杠被替换，不会提前结束带引号的字符串
+            let fallback = header
+                .strip_prefix("inline; filename=\"")
+                .and_then(|rest| rest.split_once("\"; filename*="))
+                .unwrap()
+                .0;
+            assert!(!fallback.contains(['"', '\\']), "{fallback}");
+        }
+    }
+}
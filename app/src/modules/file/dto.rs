@@ -0,0 +1,373 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use entity::{file, upload_chunk, upload_session, user::UserId};
+
+use super::thumbnail;
+
+/// 文件元数据（不包含文件内容）
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FileMetadataDto {
+    /// 文件ID
+    pub id: i64,
+
+    /// 原始文件名
+    pub filename: String,
+
+    /// MIME 类型
+    pub content_type: String,
+
+    /// 文件大小（字节）
+    pub size_bytes: i64,
+
+    /// 上传时间
+    pub created_at: DateTime<FixedOffset>,
+
+    /// 恶意软件扫描状态：pending_scan / clean / infected，只有 clean 的文件可以下载
+    pub scan_status: String,
+
+    /// 缩略图状态：pending（等待生成）/ ready，不生成缩略图的文件没有此字段
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_status: Option<String>,
+
+    /// 已生成的缩略图边长（像素），通过 `GET /v1/files/{id}/thumbnail?size=` 获取
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thumbnail_sizes: Vec<u32>,
+
+    /// 下载地址
+    pub download_url: String,
+}
+
+impl FileMetadataDto {
+    /// 由文件实体构建元数据，下载地址基于 `public_url` 拼接
+    pub fn from_model(model: file::Model, public_url: &str) -> Self {
+        Self {
+            download_url: format!("{public_url}/v1/files/{}/download", model.id),
+            thumbnail_sizes: thumbnail::sizes_of(&model),
+            id: model.id,
+            filename: model.filename,
+            content_type: model.content_type,
+            size_bytes: model.size_bytes,
+            created_at: model.created_at,
+            thumbnail_status: model.thumbnail_status,
+            scan_status: model.scan_status,
+        }
+    }
+}
+
+/// 内容在存储后端中缺失的文件（管理员对账报告）
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CorruptFileDto {
+    /// 文件ID
+    pub id: i64,
+
+    /// 所有者用户ID
+    pub owner_id: UserId,
+
+    /// 原始文件名
+    pub filename: String,
+
+    /// 文件大小（字节）
+    pub size_bytes: i64,
+
+    /// 文件内容的 SHA-256 摘要，引用同一内容的文件一起缺失
+    pub checksum: String,
+
+    /// 记录的存储后端
+    pub storage_backend: String,
+
+    /// 记录的对象键
+    pub storage_key: String,
+
+    /// 上传时间
+    pub created_at: DateTime<FixedOffset>,
+
+    /// 对账任务首次发现内容缺失的时间
+    pub corrupt_at: DateTime<FixedOffset>,
+}
+
+impl CorruptFileDto {
+    /// 由文件实体和内容被标记损坏的时间构建
+    pub fn from_model(model: file::Model, corrupt_at: DateTime<FixedOffset>) -> Self {
+        Self {
+            id: model.id,
+            owner_id: model.owner_id,
+            filename: model.filename,
+            size_bytes: model.size_bytes,
+            checksum: model.checksum,
+            storage_backend: model.storage_backend,
+            storage_key: model.storage_key,
+            created_at: model.created_at,
+            corrupt_at,
+        }
+    }
+}
+
+/// 上传成功后返回的文件信息
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FileResponse {
+    /// 文件ID
+    pub id: i64,
+
+    /// 所有者用户ID
+    pub owner_id: UserId,
+
+    /// 原始文件名
+    pub filename: String,
+
+    /// MIME 类型
+    pub content_type: String,
+
+    /// 文件大小（字节）
+    pub size_bytes: i64,
+
+    /// 文件内容的 SHA-256 摘要（十六进制小写）
+    pub checksum: String,
+
+    /// 文件描述
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// 上传时间
+    pub created_at: DateTime<FixedOffset>,
+
+    /// 恶意软件扫描状态：pending_scan / clean / infected，只有 clean 的文件可以下载
+    pub scan_status: String,
+
+    /// 缩略图状态：pending（等待生成）/ ready，不生成缩略图的文件没有此字段
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_status: Option<String>,
+
+    /// 已生成的缩略图边长（像素），通过 `GET /v1/files/{id}/thumbnail?size=` 获取
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thumbnail_sizes: Vec<u32>,
+
+    /// 下载地址
+    pub download_url: String,
+}
+
+impl FileResponse {
+    /// 由文件实体构建响应，下载地址基于 `public_url` 拼接
+    pub fn from_model(model: file::Model, public_url: &str) -> Self {
+        Self {
+            download_url: format!("{public_url}/v1/files/{}/download", model.id),
+            thumbnail_sizes: thumbnail::sizes_of(&model),
+            id: model.id,
+            owner_id: model.owner_id,
+            filename: model.filename,
+            content_type: model.content_type,
+            size_bytes: model.size_bytes,
+            checksum: model.checksum,
+            description: model.description,
+            created_at: model.created_at,
+            thumbnail_status: model.thumbnail_status,
+            scan_status: model.scan_status,
+        }
+    }
+}
+
+/// 缩略图查询参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ThumbnailQuery {
+    /// 期望的边长（像素），返回已生成的尺寸中最接近的一个；未指定时返回最大的
+    pub size: Option<u32>,
+}
+
+/// 文件列表查询参数，未设置的条件不参与过滤
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FileListQuery {
+    /// 文件所有者ID，默认为当前用户；查询他人文件需要管理员权限
+    pub owner_id: Option<UserId>,
+
+    /// MIME 类型，支持 `image/*` 形式的通配
+    pub mime: Option<String>,
+
+    /// 上传时间起点（包含，RFC 3339）
+    pub from: Option<DateTime<Utc>>,
+
+    /// 上传时间终点（不包含，RFC 3339）
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// 更新文件元数据请求，未提供的字段保持不变
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct UpdateFileRequest {
+    /// 新文件名（路径部分会被去除）
+    #[validate(length(min = 1, max = 255, message = "文件名长度必须在 1-255 个字符之间"))]
+    pub filename: Option<String>,
+
+    /// 新描述，空字符串表示清除描述
+    #[validate(length(max = 500, message = "文件描述不能超过 500 个字符"))]
+    pub description: Option<String>,
+}
+
+/// 浏览器处理下载内容的方式（`Content-Disposition`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Disposition {
+    /// 作为附件保存
+    #[default]
+    Attachment,
+
+    /// 在浏览器中直接显示，只对 `upload.inline_mime_types` 中的类型生效，其余类型仍作为附件
+    Inline,
+}
+
+impl Disposition {
+    /// `Content-Disposition` 中的类型名
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Attachment => "attachment",
+            Self::Inline => "inline",
+        }
+    }
+}
+
+/// 获取下载地址的查询参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct DownloadUrlQuery {
+    /// 有效期（秒），默认 `upload.download_url_ttl_secs`，不超过 `upload.max_download_url_ttl_secs`
+    pub ttl_secs: Option<u64>,
+
+    /// 通过该地址下载时浏览器的处理方式，默认作为附件
+    #[serde(default)]
+    pub disposition: Disposition,
+}
+
+/// 有时效的下载地址
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DownloadUrlResponse {
+    /// 下载地址，过期前无需登录即可访问
+    pub url: String,
+
+    /// 过期时间
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 当前用户的存储空间用量
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StorageUsageResponse {
+    /// 已登记文件的大小合计（字节）
+    pub used_bytes: i64,
+
+    /// 生效的配额（字节），不限制时为空
+    pub quota_bytes: Option<i64>,
+
+    /// 剩余可用空间（字节），不限制时为空
+    pub remaining_bytes: Option<i64>,
+}
+
+/// 签名下载链接携带的查询参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SignedDownloadQuery {
+    /// 过期时间（Unix 秒）
+    pub expires: Option<i64>,
+
+    /// 签名
+    pub sig: Option<String>,
+
+    /// 浏览器的处理方式，默认作为附件
+    #[serde(default)]
+    pub disposition: Disposition,
+}
+
+impl SignedDownloadQuery {
+    /// 同时携带过期时间和签名时返回两者
+    pub fn signature(&self) -> Option<(i64, &str)> {
+        Some((self.expires?, self.sig.as_deref()?))
+    }
+}
+
+/// 创建分片上传会话请求
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct CreateUploadSessionRequest {
+    /// 原始文件名
+    #[validate(length(min = 1, max = 255, message = "文件名长度必须在 1-255 个字符之间"))]
+    pub filename: String,
+
+    /// 声明的 MIME 类型，完成时按文件头识别的真实类型校验
+    pub content_type: String,
+
+    /// 文件大小（字节），不超过 `upload.max_file_size_bytes`
+    #[validate(range(min = 1, message = "文件大小必须大于 0"))]
+    pub size_bytes: u64,
+
+    /// 分片大小（字节），最后一个分片可以更小；不小于 64 KiB，不超过 `upload.max_chunk_size_bytes`
+    pub chunk_size: u64,
+
+    /// 完整文件内容的 SHA-256 摘要（十六进制）
+    #[validate(custom(
+        function = "sha256_hex",
+        message = "checksum 必须是 64 位十六进制 SHA-256 摘要"
+    ))]
+    pub checksum: String,
+
+    /// 文件描述（可选）
+    #[validate(length(max = 500, message = "文件描述不能超过 500 个字符"))]
+    pub description: Option<String>,
+}
+
+fn sha256_hex(value: &str) -> Result<(), validator::ValidationError> {
+    if value.len() != 64 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(validator::ValidationError::new("sha256"));
+    }
+    Ok(())
+}
+
+/// 分片上传会话状态
+///
+/// 断点续传时先查询会话，只重新上传 `received_chunks` 之外的分片。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadSessionResponse {
+    /// 会话 ID
+    pub id: String,
+
+    /// 原始文件名
+    pub filename: String,
+
+    /// 声明的 MIME 类型
+    pub content_type: String,
+
+    /// 文件大小（字节）
+    pub size_bytes: i64,
+
+    /// 分片大小（字节）
+    pub chunk_size: i64,
+
+    /// 分片总数，分片序号为 `0..chunk_count`
+    pub chunk_count: i64,
+
+    /// 已接收的分片序号，升序
+    pub received_chunks: Vec<i32>,
+
+    /// 会话过期时间，过期后未完成的分片会被清理
+    pub expires_at: DateTime<FixedOffset>,
+}
+
+impl UploadSessionResponse {
+    /// 由会话及其已接收的分片构建响应
+    pub fn from_model(model: upload_session::Model, chunks: &[upload_chunk::Model]) -> Self {
+        Self {
+            chunk_count: chunk_count(&model),
+            received_chunks: chunks.iter().map(|c| c.chunk_index).collect(),
+            id: model.id,
+            filename: model.filename,
+            content_type: model.content_type,
+            size_bytes: model.size_bytes,
+            chunk_size: model.chunk_size,
+            expires_at: model.expires_at,
+        }
+    }
+}
+
+/// 会话的分片总数
+pub fn chunk_count(session: &upload_session::Model) -> i64 {
+    (session.size_bytes as u64).div_ceil(session.chunk_size as u64) as i64
+}
+
+/// 指定分片应有的大小：除最后一个分片外都等于 `chunk_size`
+pub fn chunk_len(session: &upload_session::Model, index: i64) -> i64 {
+    (session.size_bytes - index * session.chunk_size).min(session.chunk_size)
+}
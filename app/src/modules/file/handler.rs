@@ -0,0 +1,715 @@
+use crate::modules::docs::with_defaults;
+use crate::{
+    AppError, AppState,
+    core::Validated,
+    core::middleware::{CurrentUser, auth::require_auth},
+    core::response::{ApiResponse, NoContent, PageParams, PaginatedResponse},
+    error::AuthError,
+    shared::FromState,
+};
+use aide::openapi::{MediaType, ReferenceOr, RequestBody, SchemaObject};
+use aide::transform::{TransformOperation, TransformResponse};
+use axum::body::Body;
+use axum::extract::{Extension, Multipart, Path, Query, Request, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use entity::enums::ThumbnailStatus;
+use entity::user::UserId;
+use indexmap::IndexMap;
+use schemars::json_schema;
+use std::sync::Arc;
+use tokio_util::io::ReaderStream;
+use tracing::{info, instrument};
+
+use super::download::{self, ByteRange};
+use super::dto::{
+    CreateUploadSessionRequest, DownloadUrlQuery, DownloadUrlResponse, FileListQuery,
+    FileMetadataDto, FileResponse, SignedDownloadQuery, StorageUsageResponse, ThumbnailQuery,
+    UpdateFileRequest, UploadSessionResponse,
+};
+use super::repo::FileFilter;
+use super::service::FileService;
+use super::thumbnail::THUMBNAIL_CONTENT_TYPE;
+
+/// 文件内容响应的 `X-Content-Type-Options`，禁止浏览器猜测内容类型
+const NOSNIFF: HeaderValue = HeaderValue::from_static("nosniff");
+
+/// 缩略图尚未生成时返回的消息
+const THUMBNAIL_PENDING: &str = "缩略图尚未生成，请稍后重试";
+
+/// 上传文件处理器
+///
+/// 接收 `multipart/form-data` 请求：`file` 字段为文件内容（必需），
+/// `description` 字段为文件描述（可选）。文件大小和 MIME 类型按 `upload` 配置校验，
+/// 内容逐块写入存储后端（不在内存中缓存整个文件），元数据（含 SHA-256 摘要）写入数据库。
+/// 新文件的扫描状态为 `pending_scan`，投递给后台扫描任务，扫描通过前不能下载；
+/// 图片另外投递给后台缩略图任务。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接、存储后端和上传限制）
+/// * `current_user` - 当前登录用户，即文件所有者（由认证中间件注入）
+/// * `multipart` - multipart 表单
+///
+/// # 返回
+/// 成功返回 201 和文件信息，缺少 `file` 字段返回 400，超出大小返回 413，类型不允许返回 415
+#[instrument(skip(state, current_user, multipart))]
+pub async fn upload_file(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    multipart: Multipart,
+) -> Result<(StatusCode, ApiResponse<FileResponse>), AppError> {
+    info!("处理文件上传请求，用户ID: {}", current_user.user_id);
+
+    let public_url = state.public_url().await;
+    let file_service = FileService::from_state(&state);
+    let response = file_service
+        .upload(current_user.user_id, multipart, &public_url)
+        .await?;
+    state.scan_queue.enqueue(response.id);
+    enqueue_thumbnails(&state, &response);
+
+    Ok((
+        StatusCode::CREATED,
+        ApiResponse::success(response).with_kind("File"),
+    ))
+}
+
+/// 上传文件 API 文档
+///
+/// `Multipart` 提取器只生成不带字段的请求体描述，这里替换为带 `file`、`description` 字段的表单结构。
+pub fn upload_file_docs(mut op: TransformOperation) -> TransformOperation {
+    let schema = json_schema!({
+        "type": "object",
+        "required": ["file"],
+        "properties": {
+            "file": {
+                "type": "string",
+                "format": "binary",
+                "description": "文件内容，大小和 MIME 类型受 upload 配置限制"
+            },
+            "description": {
+                "type": "string",
+                "maxLength": 500,
+                "description": "文件描述（可选）"
+            }
+        }
+    });
+    op.inner_mut().request_body = Some(ReferenceOr::Item(RequestBody {
+        description: Some("multipart/form-data 表单".to_string()),
+        content: IndexMap::from_iter([(
+            "multipart/form-data".to_string(),
+            MediaType {
+                schema: Some(SchemaObject {
+                    json_schema: schema,
+                    external_docs: None,
+                    example: None,
+                }),
+                ..Default::default()
+            },
+        )]),
+        required: true,
+        extensions: IndexMap::default(),
+    }));
+
+    op.description("上传文件")
+        .response::<201, ApiResponse<FileResponse>>()
+        .with(with_defaults)
+}
+
+/// 列出文件处理器
+///
+/// 分页返回文件元数据，按上传时间倒序，可按 MIME 类型（支持 `image/*`）和上传时间范围过滤。
+/// 默认列出当前用户的文件，管理员可以通过 `owner_id` 查询其他用户。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接）
+/// * `current_user` - 当前登录用户（由认证中间件注入）
+/// * `query` - 过滤条件（owner_id、mime、from、to）
+/// * `params` - 分页参数（page、per_page）
+///
+/// # 返回
+/// 成功返回文件元数据分页列表，响应头 `X-Total-Count` 为文件总数；
+/// 过滤条件无效返回 400，查询他人文件但不是管理员返回 403
+#[instrument(skip(state, current_user))]
+pub async fn list_files(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(query): Query<FileListQuery>,
+    Query(params): Query<PageParams>,
+) -> Result<PaginatedResponse<FileMetadataDto>, AppError> {
+    let public_url = state.public_url().await;
+    FileService::from_state(&state)
+        .list_files(current_user.user_id, query, params, &public_url)
+        .await
+}
+
+/// 列出文件 API 文档
+pub fn list_files_docs(op: TransformOperation) -> TransformOperation {
+    op.description("按类型和上传时间分页列出文件")
+        .response::<200, PaginatedResponse<FileMetadataDto>>()
+        .with(with_defaults)
+}
+
+/// 列出用户文件处理器
+///
+/// 分页返回指定用户拥有的文件元数据，不包含文件内容。
+/// 普通用户只能查询自己的文件，管理员可以查询任意用户。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接）
+/// * `current_user` - 当前登录用户（由认证中间件注入）
+/// * `owner_id` - 文件所有者ID
+/// * `params` - 分页参数（page、per_page）
+///
+/// # 返回
+/// 成功返回文件元数据分页列表，响应头 `X-Total-Count` 为文件总数
+#[instrument(skip(state, current_user))]
+pub async fn list_user_files(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(owner_id): Path<UserId>,
+    Query(params): Query<PageParams>,
+) -> Result<PaginatedResponse<FileMetadataDto>, AppError> {
+    info!(
+        "查询用户文件列表，当前用户ID: {}，所有者ID: {}",
+        current_user.user_id, owner_id
+    );
+
+    let public_url = state.public_url().await;
+    let file_service = FileService::from_state(&state);
+    let response = file_service
+        .list_user_files(
+            current_user.user_id,
+            owner_id,
+            &FileFilter::default(),
+            params,
+            &public_url,
+        )
+        .await?;
+
+    Ok(response)
+}
+
+/// 列出用户文件 API 文档
+pub fn list_user_files_docs(op: TransformOperation) -> TransformOperation {
+    op.description("分页列出指定用户的文件")
+        .response::<200, PaginatedResponse<FileMetadataDto>>()
+        .with(with_defaults)
+}
+
+/// 查询存储空间用量处理器
+///
+/// 返回当前用户已用的存储空间、生效的配额和剩余空间，配额为用户单独设置的值，
+/// 未设置时为 `upload.default_storage_quota_bytes`。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接和默认配额）
+/// * `current_user` - 当前登录用户（由认证中间件注入）
+///
+/// # 返回
+/// 成功返回存储空间用量，不限制时 `quota_bytes` 和 `remaining_bytes` 为空
+#[instrument(skip(state, current_user))]
+pub async fn get_storage_usage(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<ApiResponse<StorageUsageResponse>, AppError> {
+    let usage = FileService::from_state(&state)
+        .storage_usage(current_user.user_id)
+        .await?;
+
+    Ok(ApiResponse::success(usage))
+}
+
+/// 查询存储空间用量 API 文档
+pub fn get_storage_usage_docs(op: TransformOperation) -> TransformOperation {
+    op.description("查询当前用户的存储空间用量")
+        .response::<200, ApiResponse<StorageUsageResponse>>()
+        .with(with_defaults)
+}
+
+/// 下载文件处理器
+///
+/// 从文件记录的存储后端流式读取内容，不在内存中缓存整个文件。
+/// 携带 `expires` 和 `sig` 时按签名下载链接校验，无需登录；否则只有文件所有者和管理员可以下载。
+///
+/// 支持单个区间的 `Range` 请求（返回 206）和 `If-Range` 条件，便于视频拖动和断点续传；
+/// 请求多个区间或区间超出文件末尾时返回 416，无法识别的 `Range` 按普通下载处理。
+///
+/// 默认作为附件下载，`?disposition=inline` 只对 `upload.inline_mime_types` 中的类型生效；
+/// 响应始终带有 `X-Content-Type-Options: nosniff`，浏览器不会把内容猜测为 HTML 等其他类型。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接和存储后端）
+/// * `current_user` - 当前登录用户（未携带签名时由认证中间件注入）
+/// * `file_id` - 文件ID
+/// * `query` - 签名下载链接的过期时间和签名，以及浏览器的处理方式
+/// * `headers` - 请求头（`Range`、`If-Range`）
+///
+/// # 返回
+/// 成功返回文件内容（附件形式）或请求的区间，签名无效或已过期、无权访问返回 403，
+/// 文件待扫描或已被隔离返回 403（`FILE_NOT_CLEAN`），文件不存在返回 404，区间无法满足返回 416
+#[instrument(skip(state, current_user, query, headers))]
+pub async fn download_file(
+    State(state): State<Arc<AppState>>,
+    current_user: Option<Extension<CurrentUser>>,
+    Path(file_id): Path<i64>,
+    Query(query): Query<SignedDownloadQuery>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let file_service = FileService::from_state(&state);
+    let file = match (query.signature(), current_user) {
+        (Some((expires, sig)), _) => {
+            file_service
+                .signed_file(file_id, expires, sig, state.jwt_secret.as_bytes())
+                .await?
+        }
+        (None, Some(Extension(current_user))) => {
+            file_service
+                .downloadable_file(current_user.user_id, file_id)
+                .await?
+        }
+        // 未携带签名的请求已由认证中间件拦截，这里只是兜底
+        (None, None) => return Err(AuthError::InvalidToken.into()),
+    };
+
+    let etag = download::etag(&file.checksum);
+    let size = file.size_bytes as u64;
+    let header_str = |name| {
+        headers
+            .get(name)
+            .and_then(|v: &HeaderValue| v.to_str().ok())
+    };
+    let range = download::parse_range(
+        header_str(header::RANGE),
+        header_str(header::IF_RANGE),
+        &etag,
+        size,
+    );
+
+    let (status, range) = match range {
+        ByteRange::Full => (StatusCode::OK, None),
+        ByteRange::Partial(range) => (StatusCode::PARTIAL_CONTENT, Some(range)),
+        ByteRange::Unsatisfiable => {
+            let mut response =
+                AppError::from_status(StatusCode::RANGE_NOT_SATISFIABLE, "请求的区间无法满足")
+                    .into_response();
+            let headers = response.headers_mut();
+            headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{size}")).expect("数字总是合法的请求头"),
+            );
+            headers.insert(header::X_CONTENT_TYPE_OPTIONS, NOSNIFF);
+            return Ok(response);
+        }
+    };
+    let reader = file_service.open_content(&file, range.clone()).await?;
+
+    let mut response = (
+        status,
+        [
+            (header::CONTENT_TYPE, file.content_type.clone()),
+            (header::ETAG, etag),
+            (header::ACCEPT_RANGES, "bytes".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                file_service.content_disposition(&file, query.disposition),
+            ),
+        ],
+        Body::from_stream(ReaderStream::new(reader)),
+    )
+        .into_response();
+    let (length, content_range) = match range {
+        Some(range) => (
+            range.end - range.start,
+            Some(format!("bytes {}-{}/{size}", range.start, range.end - 1)),
+        ),
+        None => (size, None),
+    };
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    headers.insert(header::X_CONTENT_TYPE_OPTIONS, NOSNIFF);
+    if let Some(content_range) = content_range {
+        headers.insert(
+            header::CONTENT_RANGE,
+            HeaderValue::from_str(&content_range).expect("数字总是合法的请求头"),
+        );
+    }
+    Ok(response)
+}
+
+/// 下载文件 API 文档
+pub fn download_file_docs(op: TransformOperation) -> TransformOperation {
+    op.description(
+        "下载文件内容，支持单个区间的 `Range` 请求；携带签名下载链接的 `expires` 和 `sig` 时无需登录。\
+         默认作为附件下载，`disposition=inline` 只对允许在浏览器中显示的类型（图片、PDF 等）生效",
+    )
+    .response_with::<200, Vec<u8>, _>(|res| binary_response(res, "文件内容"))
+    .response_with::<206, Vec<u8>, _>(|res| binary_response(res, "`Range` 请求的区间内容"))
+    .with(with_defaults)
+}
+
+/// 获取缩略图处理器
+///
+/// 返回已生成的尺寸中最接近 `size` 的缩略图（PNG）。缩略图尚未生成（或对象已丢失）时重新投递给
+/// 后台缩略图任务并返回 404，稍后重试即可。
+///
+/// # 返回
+/// 成功返回 PNG 内容，文件不存在、不生成缩略图或尚未生成返回 404，无权访问返回 403，未通过扫描返回 403
+#[instrument(skip(state, current_user))]
+pub async fn get_thumbnail(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(id): Path<i64>,
+    Query(query): Query<ThumbnailQuery>,
+) -> Result<Response, AppError> {
+    let thumbnail = FileService::from_state(&state)
+        .thumbnail(current_user.user_id, id, query.size)
+        .await?;
+    let Some(thumbnail) = thumbnail else {
+        state.thumbnail_queue.enqueue(id);
+        return Err(AppError::NotFound(THUMBNAIL_PENDING.to_string()));
+    };
+
+    let mut response = (
+        [(header::CONTENT_TYPE, THUMBNAIL_CONTENT_TYPE)],
+        thumbnail.content,
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert(header::X_CONTENT_TYPE_OPTIONS, NOSNIFF);
+    Ok(response)
+}
+
+/// 获取缩略图 API 文档
+pub fn get_thumbnail_docs(op: TransformOperation) -> TransformOperation {
+    op.description(
+        "获取图片的缩略图（PNG），返回已生成的尺寸中最接近 `size` 的一个，未指定时返回最大的。\
+         缩略图在上传后由后台生成，尚未生成时返回 404，稍后重试即可",
+    )
+    .response_with::<200, Vec<u8>, _>(|res| {
+        let mut res = binary_response(res, "缩略图内容");
+        let content = &mut res.inner().content;
+        if let Some(media) = content.shift_remove("application/octet-stream") {
+            content.insert(THUMBNAIL_CONTENT_TYPE.to_string(), media);
+        }
+        res
+    })
+    .with(with_defaults)
+}
+
+/// 新文件等待生成缩略图时投递给后台缩略图任务
+fn enqueue_thumbnails(state: &AppState, response: &FileResponse) {
+    if response.thumbnail_status.as_deref() == Some(ThumbnailStatus::Pending.to_string().as_str()) {
+        state.thumbnail_queue.enqueue(response.id);
+    }
+}
+
+/// 二进制响应的文档：aide 对 `Vec<u8>` 只登记媒体类型，这里补上 `string/binary` schema
+fn binary_response<'t>(
+    mut res: TransformResponse<'t, Vec<u8>>,
+    description: &str,
+) -> TransformResponse<'t, Vec<u8>> {
+    for media in res.inner().content.values_mut() {
+        media.schema = Some(SchemaObject {
+            json_schema: json_schema!({ "type": "string", "format": "binary" }),
+            external_docs: None,
+            example: None,
+        });
+    }
+    res.description(description)
+}
+
+/// 下载接口的认证中间件：携带 `expires` 和 `sig` 的请求交给处理器校验签名，其余请求要求登录
+pub async fn require_auth_unless_signed(
+    state: State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let signed = Query::<SignedDownloadQuery>::try_from_uri(request.uri())
+        .is_ok_and(|Query(query)| query.signature().is_some());
+    if signed {
+        return Ok(next.run(request).await);
+    }
+    require_auth(state, request, next).await
+}
+
+/// 获取下载地址处理器
+///
+/// 返回有时效的下载地址：文件在 S3 时为预签名地址，在本地磁盘时为应用签名的下载链接，
+/// 过期前无需登录即可下载。文件所有者和管理员可以获取。
+///
+/// # 参数
+/// * `state` - 应用状态（包含签名密钥、存储后端和有效期限制）
+/// * `current_user` - 当前登录用户（由认证中间件注入）
+/// * `file_id` - 文件ID
+/// * `query` - 有效期（秒），不超过 `upload.max_download_url_ttl_secs`；以及浏览器的处理方式
+///
+/// # 返回
+/// 成功返回下载地址和过期时间，有效期无效返回 400，无权访问或文件未通过扫描返回 403，文件不存在返回 404
+#[instrument(skip(state, current_user))]
+pub async fn get_download_url(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(file_id): Path<i64>,
+    Query(query): Query<DownloadUrlQuery>,
+) -> Result<ApiResponse<DownloadUrlResponse>, AppError> {
+    let public_url = state.public_url().await;
+    let response = FileService::from_state(&state)
+        .download_url(
+            current_user.user_id,
+            file_id,
+            query.ttl_secs,
+            query.disposition,
+            state.jwt_secret.as_bytes(),
+            &public_url,
+        )
+        .await?;
+
+    Ok(ApiResponse::success(response))
+}
+
+/// 获取下载地址 API 文档
+pub fn get_download_url_docs(op: TransformOperation) -> TransformOperation {
+    op.description("获取有时效的文件下载地址")
+        .response::<200, ApiResponse<DownloadUrlResponse>>()
+        .with(with_defaults)
+}
+
+/// 查询文件元数据处理器
+///
+/// 文件所有者和管理员可以查看，未通过扫描的文件也可以查看元数据。
+///
+/// # 返回
+/// 成功返回文件信息，无权访问返回 403，文件不存在返回 404
+#[instrument(skip(state, current_user))]
+pub async fn get_file(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(file_id): Path<i64>,
+) -> Result<ApiResponse<FileResponse>, AppError> {
+    let public_url = state.public_url().await;
+    let file = FileService::from_state(&state)
+        .get_file(current_user.user_id, file_id, &public_url)
+        .await?;
+    Ok(ApiResponse::success(file).with_kind("File"))
+}
+
+/// 查询文件元数据 API 文档
+pub fn get_file_docs(op: TransformOperation) -> TransformOperation {
+    op.description("查询文件元数据")
+        .response::<200, ApiResponse<FileResponse>>()
+        .with(with_defaults)
+}
+
+/// 修改文件元数据处理器
+///
+/// 修改文件名或描述，未提供的字段保持不变，空描述表示清除。文件所有者和管理员可以修改。
+///
+/// # 返回
+/// 成功返回更新后的文件信息，参数无效返回 400，无权访问返回 403，文件不存在返回 404
+#[instrument(skip(state, current_user))]
+pub async fn update_file(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(file_id): Path<i64>,
+    Validated(req): Validated<UpdateFileRequest>,
+) -> Result<ApiResponse<FileResponse>, AppError> {
+    info!(
+        "修改文件元数据，当前用户ID: {}，文件ID: {}",
+        current_user.user_id, file_id
+    );
+
+    let public_url = state.public_url().await;
+    let file = FileService::from_state(&state)
+        .update_file(current_user.user_id, file_id, req, &public_url)
+        .await?;
+    Ok(ApiResponse::success(file).with_kind("File"))
+}
+
+/// 修改文件元数据 API 文档
+pub fn update_file_docs(op: TransformOperation) -> TransformOperation {
+    op.description("修改文件名或描述")
+        .response::<200, ApiResponse<FileResponse>>()
+        .with(with_defaults)
+}
+
+/// 删除文件处理器
+///
+/// 删除元数据并释放配额，文件内容交给后台回收任务：没有其他文件引用相同内容时从存储后端删除。
+/// 文件所有者和管理员可以删除。
+///
+/// # 返回
+/// 成功返回 204（无响应体），无权访问返回 403，文件不存在返回 404
+#[instrument(skip(state, current_user))]
+pub async fn delete_file(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(file_id): Path<i64>,
+) -> Result<NoContent, AppError> {
+    info!(
+        "删除文件，当前用户ID: {}，文件ID: {}",
+        current_user.user_id, file_id
+    );
+
+    let file = FileService::from_state(&state)
+        .delete_file(current_user.user_id, file_id)
+        .await?;
+    // 队列已满时由定期的完整性检查回收
+    if !file.checksum.is_empty() {
+        state.blob_queue.enqueue(file.checksum);
+    }
+
+    Ok(NoContent)
+}
+
+/// 删除文件 API 文档
+pub fn delete_file_docs(op: TransformOperation) -> TransformOperation {
+    op.description("删除文件").with(with_defaults)
+}
+
+/// 创建分片上传会话处理器
+///
+/// 大文件可先创建会话，再逐个上传分片，网络中断后只需重传缺失的分片。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接和上传限制）
+/// * `current_user` - 当前登录用户，即文件所有者（由认证中间件注入）
+/// * `req` - 文件名、类型、大小、分片大小和 SHA-256 摘要
+///
+/// # 返回
+/// 成功返回 201 和会话状态，参数无效返回 400，超出大小返回 413，类型不允许返回 415
+#[instrument(skip(state, current_user, req))]
+pub async fn create_upload_session(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Validated(req): Validated<CreateUploadSessionRequest>,
+) -> Result<(StatusCode, ApiResponse<UploadSessionResponse>), AppError> {
+    let response = FileService::from_state(&state)
+        .create_session(current_user.user_id, req)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        ApiResponse::success(response).with_kind("UploadSession"),
+    ))
+}
+
+/// 创建分片上传会话 API 文档
+pub fn create_upload_session_docs(op: TransformOperation) -> TransformOperation {
+    op.description("创建分片上传会话（断点续传）")
+        .response::<201, ApiResponse<UploadSessionResponse>>()
+        .with(with_defaults)
+}
+
+/// 查询分片上传会话处理器
+///
+/// 返回已接收的分片序号，断点续传时只需上传其余分片。
+#[instrument(skip(state, current_user))]
+pub async fn get_upload_session(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(session_id): Path<String>,
+) -> Result<ApiResponse<UploadSessionResponse>, AppError> {
+    let response = FileService::from_state(&state)
+        .get_session(current_user.user_id, &session_id)
+        .await?;
+
+    Ok(ApiResponse::success(response).with_kind("UploadSession"))
+}
+
+/// 查询分片上传会话 API 文档
+pub fn get_upload_session_docs(op: TransformOperation) -> TransformOperation {
+    op.description("查询分片上传会话及已接收的分片")
+        .response::<200, ApiResponse<UploadSessionResponse>>()
+        .with(with_defaults)
+}
+
+/// 上传分片处理器
+///
+/// 请求体为分片的原始内容，逐块写入存储后端。同一序号可以重复上传，以最后一次为准。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接和存储后端）
+/// * `current_user` - 当前登录用户（由认证中间件注入）
+/// * `session_id` - 会话ID
+/// * `index` - 分片序号，从 0 开始
+/// * `body` - 分片内容
+///
+/// # 返回
+/// 成功返回会话状态，会话无效返回 404，序号或大小不符返回 400，超出分片大小返回 413
+#[instrument(skip(state, current_user, body))]
+pub async fn put_upload_chunk(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path((session_id, index)): Path<(String, i64)>,
+    body: Body,
+) -> Result<ApiResponse<UploadSessionResponse>, AppError> {
+    let response = FileService::from_state(&state)
+        .put_chunk(
+            current_user.user_id,
+            &session_id,
+            index,
+            body.into_data_stream(),
+        )
+        .await?;
+
+    Ok(ApiResponse::success(response).with_kind("UploadSession"))
+}
+
+/// 上传分片 API 文档
+pub fn put_upload_chunk_docs(mut op: TransformOperation) -> TransformOperation {
+    op.inner_mut().request_body = Some(ReferenceOr::Item(RequestBody {
+        description: Some("分片的原始内容，除最后一个分片外大小必须等于 chunk_size".to_string()),
+        content: IndexMap::from_iter([(
+            "application/octet-stream".to_string(),
+            MediaType {
+                schema: Some(SchemaObject {
+                    json_schema: json_schema!({ "type": "string", "format": "binary" }),
+                    external_docs: None,
+                    example: None,
+                }),
+                ..Default::default()
+            },
+        )]),
+        required: true,
+        extensions: IndexMap::default(),
+    }));
+
+    op.description("上传单个分片（可重复上传）")
+        .response::<200, ApiResponse<UploadSessionResponse>>()
+        .with(with_defaults)
+}
+
+/// 完成分片上传处理器
+///
+/// 校验分片完整性、文件类型和 SHA-256 后合并为最终文件，与普通上传一样投递给后台扫描任务。
+///
+/// # 返回
+/// 成功返回 201 和文件信息，会话无效返回 404，分片不完整或校验和不符返回 400，类型不符返回 415
+#[instrument(skip(state, current_user))]
+pub async fn complete_upload_session(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Path(session_id): Path<String>,
+) -> Result<(StatusCode, ApiResponse<FileResponse>), AppError> {
+    let public_url = state.public_url().await;
+    let response = FileService::from_state(&state)
+        .complete_session(current_user.user_id, &session_id, &public_url)
+        .await?;
+    state.scan_queue.enqueue(response.id);
+    enqueue_thumbnails(&state, &response);
+
+    Ok((
+        StatusCode::CREATED,
+        ApiResponse::success(response).with_kind("File"),
+    ))
+}
+
+/// 完成分片上传 API 文档
+pub fn complete_upload_session_docs(op: TransformOperation) -> TransformOperation {
+    op.description("校验并合并分片，生成最终文件")
+        .response::<201, ApiResponse<FileResponse>>()
+        .with(with_defaults)
+}
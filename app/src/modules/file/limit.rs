@@ -0,0 +1,139 @@
+//! 上传请求体大小的提前检查
+//!
+//! 上传接口的请求体上限由 `upload` 配置推导。声明了 `Content-Length` 的请求在读取请求体之前比较，
+//! 超出上限直接返回 413；分块传输等没有 `Content-Length` 的请求仍会被 `DefaultBodyLimit`
+//! 和写入存储时的大小校验在读到上限时中止。
+
+use axum::extract::{Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::error::{AppError, FileUploadError};
+
+/// 上传接口在单个文件上限之外为 multipart 边界、字段头和描述字段预留的请求体大小（字节）
+const MULTIPART_OVERHEAD_BYTES: usize = 64 * 1024;
+
+/// 上传接口的请求体上限
+#[derive(Debug, Clone, Copy)]
+pub(super) struct UploadLimit {
+    /// 允许的请求体大小（字节）
+    pub(super) body_bytes: usize,
+    /// 错误响应中提示的文件大小上限（字节）
+    file_bytes: usize,
+}
+
+impl UploadLimit {
+    /// multipart 表单上传：文件上限加上 [`MULTIPART_OVERHEAD_BYTES`]
+    pub(super) fn multipart(max_file_size: usize) -> Self {
+        Self {
+            body_bytes: max_file_size + MULTIPART_OVERHEAD_BYTES,
+            file_bytes: max_file_size,
+        }
+    }
+
+    /// 请求体即文件内容的上传（如分片）
+    pub(super) fn raw(max_size: usize) -> Self {
+        Self {
+            body_bytes: max_size,
+            file_bytes: max_size,
+        }
+    }
+}
+
+/// 声明的 `Content-Length` 超出 [`UploadLimit`] 时返回 FileUploadError::TooLarge，不读取请求体
+pub(super) async fn reject_oversized(
+    State(limit): State<UploadLimit>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let declared = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared.is_some_and(|len| len > limit.body_bytes as u64) {
+        return Err(FileUploadError::TooLarge(limit.file_bytes).into());
+    }
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::{Body, Bytes};
+    use axum::extract::DefaultBodyLimit;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use futures_util::stream;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use tower::ServiceExt;
+
+    const LIMIT: usize = 1024;
+
+    /// 与上传路由相同的层：`DefaultBodyLimit` 加上 `Content-Length` 检查，记录处理器是否执行
+    fn app(reached: Arc<AtomicBool>) -> Router {
+        let limit = UploadLimit::raw(LIMIT);
+        Router::new().route(
+            "/upload",
+            post(move |body: Bytes| async move {
+                reached.store(true, Ordering::SeqCst);
+                body.len().to_string()
+            })
+            .layer(DefaultBodyLimit::max(limit.body_bytes))
+            .layer(axum::middleware::from_fn_with_state(
+                limit,
+                reject_oversized,
+            )),
+        )
+    }
+
+    async fn send(request: Request) -> (StatusCode, String, bool) {
+        let reached = Arc::new(AtomicBool::new(false));
+        let response = app(reached.clone()).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            String::from_utf8_lossy(&body).into_owned(),
+            reached.load(Ordering::SeqCst),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_declared_oversize_is_rejected_before_reading_body() {
+        // 声明的长度超出上限，请求体本身为空：不读取请求体就返回 413
+        let request = Request::post("/upload")
+            .header(header::CONTENT_LENGTH, LIMIT + 1)
+            .body(Body::empty())
+            .unwrap();
+        let (status, body, reached) = send(request).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(body.contains("FILE_TOO_LARGE"));
+        assert!(body.contains(&LIMIT.to_string()));
+        assert!(!reached);
+
+        let request = Request::post("/upload")
+            .header(header::CONTENT_LENGTH, LIMIT)
+            .body(Body::from(vec![b'x'; LIMIT]))
+            .unwrap();
+        let (status, body, reached) = send(request).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, LIMIT.to_string());
+        assert!(reached);
+    }
+
+    #[tokio::test]
+    async fn test_chunked_body_is_cut_off_at_limit() {
+        let chunks = (0..4).map(|_| Ok::<_, std::io::Error>(Bytes::from(vec![b'x'; LIMIT / 2])));
+        let request = Request::post("/upload")
+            .body(Body::from_stream(stream::iter(chunks)))
+            .unwrap();
+        let (status, _, _) = send(request).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,364 @@
+//! 文件管理模块
+//!
+//! 提供文件上传、下载、删除、元数据查询等功能，文件内容按配置写入本地磁盘或 S3。
+
+use crate::AppState;
+use crate::core::middleware::{Access, AccessRouter};
+use crate::modules::docs::{ModuleTag, ModuleTagRouter};
+use crate::shared::FromState;
+use crate::shared::lock::JobLock;
+use aide::axum::ApiRouter;
+use aide::axum::routing::{get_with, post_with, put_with};
+use axum::extract::DefaultBodyLimit;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+mod download;
+pub mod dto;
+mod handler;
+mod limit;
+mod repo;
+mod service;
+
+use limit::UploadLimit;
+pub(crate) use service::FileService;
+mod sniff;
+mod thumbnail;
+
+/// 每轮重新投递的待扫描文件数上限，不超过扫描队列容量
+const RESCAN_BATCH_SIZE: u64 = 500;
+
+/// 存储对账任务的锁名称，多实例部署时同一时间只有一个实例运行
+const BLOB_RECONCILE_LOCK: &str = "file.blob_reconcile";
+
+/// 文件模块在 OpenAPI 文档中的标签
+pub const TAG: ModuleTag = ModuleTag::new("文件", "文件上传、下载、元数据查询与分享");
+
+/// 构建文件路由，挂载在 `/files` 下
+///
+/// 配置以下端点（除携带签名的下载请求外均需要认证）：
+/// - POST / - 上传文件（multipart）
+/// - GET / - 分页列出文件，可按类型和上传时间过滤（默认当前用户，管理员可指定 `owner_id`）
+/// - GET /{id} - 查询文件元数据（所有者或管理员）
+/// - PATCH /{id} - 修改文件名或描述（所有者或管理员）
+/// - GET /{id}/url - 获取有时效的下载地址（所有者或管理员）
+/// - GET /{id}/download - 下载文件内容（所有者或管理员，或凭签名下载链接免登录）
+/// - GET /{id}/thumbnail - 获取图片的缩略图，按 `size` 选择最接近的尺寸（所有者或管理员）
+/// - DELETE /{id} - 删除文件元数据，内容由后台任务回收（所有者或管理员）
+/// - POST /uploads - 创建分片上传会话
+/// - GET /uploads/{id} - 查询会话及已接收的分片
+/// - PUT /uploads/{id}/chunks/{n} - 上传第 n 个分片（可重复上传）
+/// - POST /uploads/{id}/complete - 校验并合并分片
+///
+/// 上传接口的请求体上限为 `upload.max_file_size_bytes` 加上 multipart 开销，不受全局 `body_limit_bytes`
+/// 约束；分片接口的上限为 `upload.max_chunk_size_bytes`，实际大小由会话约定并在写入时校验。
+/// 两者声明的 `Content-Length` 超出上限时都在读取请求体之前返回 413。
+///
+/// # 参数
+/// * `state` - 应用状态，包含数据库、存储后端和上传限制
+///
+/// # 返回
+/// 返回配置好的路由器
+pub fn routes(state: Arc<AppState>) -> ApiRouter {
+    let file_limit = UploadLimit::multipart(state.upload.max_file_size_bytes);
+    let chunk_limit = UploadLimit::raw(state.upload.max_chunk_size_bytes);
+    ApiRouter::new()
+        .api_route_access(
+            "/",
+            Access::Authenticated,
+            &state,
+            post_with(handler::upload_file, handler::upload_file_docs)
+                .layer(DefaultBodyLimit::max(file_limit.body_bytes))
+                .layer(axum::middleware::from_fn_with_state(
+                    file_limit,
+                    limit::reject_oversized,
+                ))
+                .get_with(handler::list_files, handler::list_files_docs),
+        )
+        .api_route_access(
+            "/{id}/url",
+            Access::Authenticated,
+            &state,
+            get_with(handler::get_download_url, handler::get_download_url_docs),
+        )
+        .api_route_access(
+            "/{id}",
+            Access::Authenticated,
+            &state,
+            get_with(handler::get_file, handler::get_file_docs)
+                .patch_with(handler::update_file, handler::update_file_docs)
+                .delete_with(handler::delete_file, handler::delete_file_docs),
+        )
+        .api_route_access(
+            "/{id}/thumbnail",
+            Access::Authenticated,
+            &state,
+            get_with(handler::get_thumbnail, handler::get_thumbnail_docs),
+        )
+        .api_route_access(
+            "/uploads",
+            Access::Authenticated,
+            &state,
+            post_with(
+                handler::create_upload_session,
+                handler::create_upload_session_docs,
+            ),
+        )
+        .api_route_access(
+            "/uploads/{id}",
+            Access::Authenticated,
+            &state,
+            get_with(
+                handler::get_upload_session,
+                handler::get_upload_session_docs,
+            ),
+        )
+        .api_route_access(
+            "/uploads/{id}/chunks/{n}",
+            Access::Authenticated,
+            &state,
+            put_with(handler::put_upload_chunk, handler::put_upload_chunk_docs).layer(
+                axum::middleware::from_fn_with_state(chunk_limit, limit::reject_oversized),
+            ),
+        )
+        .api_route_access(
+            "/uploads/{id}/complete",
+            Access::Authenticated,
+            &state,
+            post_with(
+                handler::complete_upload_session,
+                handler::complete_upload_session_docs,
+            ),
+        )
+        // 携带签名的下载请求免登录，由处理器自己的中间件判断是否需要认证
+        .api_route(
+            "/{id}/download",
+            get_with(handler::download_file, handler::download_file_docs).layer(
+                axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    handler::require_auth_unless_signed,
+                ),
+            ),
+        )
+        .with_module_tag(&TAG)
+        .with_state(state)
+}
+
+/// 启动过期上传会话的清理任务
+///
+/// 每隔 `upload.session_cleanup_interval_secs` 秒删除过期会话的分片内容和记录，间隔为 0 时不启动。
+pub fn spawn_session_cleanup(state: Arc<AppState>) {
+    let interval = state.upload.session_cleanup_interval_secs;
+    if interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match service::FileService::from_state(&state)
+                .purge_expired_sessions()
+                .await
+            {
+                Ok(0) => {}
+                Ok(count) => info!(count, "已清理过期的上传会话"),
+                Err(e) => warn!(error = %e, "清理过期的上传会话失败"),
+            }
+        }
+    });
+}
+
+/// 启动文件内容的回收和对账任务
+///
+/// 逐个处理 [`AppState::blob_queue`] 中投递的内容摘要，回收不再被任何文件引用的内容；回收失败只记录日志。
+/// `upload.blob_integrity_interval_secs` 不为 0 时另外启动定期对账任务：先把去重之前保存的内容移动到
+/// 由摘要决定的对象键并删除重复的副本，再从元数据一侧修正引用计数、
+/// 回收队列漏掉的内容并标记对象缺失的内容，再从存储一侧删除超过宽限期仍没有元数据引用的孤立对象。
+/// 多实例部署时对账任务持有 [`JobLock`]，同一时间只在一个实例上运行，其他实例本轮跳过；
+/// 租约为一个间隔，持有锁的实例崩溃后下一轮即可由其他实例接管。
+///
+/// [`AppState::blob_queue`]: crate::AppState::blob_queue
+pub fn spawn_blob_worker(state: Arc<AppState>) {
+    let Some(mut rx) = state.blob_queue.take_receiver() else {
+        warn!("内容回收任务已在运行，忽略重复启动");
+        return;
+    };
+    let worker = state.clone();
+    tokio::spawn(async move {
+        while let Some(hash) = rx.recv().await {
+            let service = service::FileService::from_state(&worker);
+            if let Err(e) = service.reclaim_blob(&hash).await {
+                warn!(hash, error = %e, "回收文件内容失败，等待完整性检查重试");
+            }
+        }
+    });
+
+    let interval = state.upload.blob_integrity_interval_secs;
+    if interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let lock = JobLock::from_state(&state);
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let guard = match lock
+                .try_acquire(BLOB_RECONCILE_LOCK, Duration::from_secs(interval))
+                .await
+            {
+                Ok(Some(guard)) => guard,
+                Ok(None) => {
+                    debug!("其他实例正在对账文件内容，本轮跳过");
+                    continue;
+                }
+                Err(e) => {
+                    warn!(error = %e, "获取文件内容对账锁失败");
+                    continue;
+                }
+            };
+
+            let service = service::FileService::from_state(&state);
+            match service.relocate_legacy_blobs().await {
+                Ok(report) => info!(
+                    relocated = report.relocated,
+                    copies_deleted = report.copies_deleted,
+                    "旧内容迁移完成"
+                ),
+                Err(e) => warn!(error = %e, "旧内容迁移失败"),
+            }
+            match service.check_blob_integrity().await {
+                Ok(report) => info!(
+                    checked = report.checked,
+                    fixed = report.fixed,
+                    removed = report.removed,
+                    missing = report.missing,
+                    recovered = report.recovered,
+                    "文件内容完整性检查完成"
+                ),
+                Err(e) => warn!(error = %e, "文件内容完整性检查失败"),
+            }
+            match service.sweep_orphaned_objects().await {
+                Ok(report) => info!(
+                    scanned = report.scanned,
+                    deleted = report.deleted,
+                    "孤立对象清理完成"
+                ),
+                Err(e) => warn!(error = %e, "孤立对象清理失败"),
+            }
+
+            if let Err(e) = guard.release().await {
+                warn!(error = %e, "释放文件内容对账锁失败，等待租约到期");
+            }
+        }
+    });
+}
+
+/// 启动上传文件的恶意软件扫描任务
+///
+/// 逐个处理 [`AppState::scan_queue`] 中投递的文件 ID，按 `file_scan` 配置扫描并隔离被感染的文件；
+/// 扫描失败（如 clamd 不可用）只记录日志，文件保持待扫描状态。`file_scan.rescan_interval_secs`
+/// 不为 0 时另外启动定期任务，把仍在等待扫描的文件重新投递，覆盖队列已满、扫描器故障和进程重启的情况。
+///
+/// [`AppState::scan_queue`]: crate::AppState::scan_queue
+pub fn spawn_scan_worker(state: Arc<AppState>) {
+    let Some(mut rx) = state.scan_queue.take_receiver() else {
+        warn!("扫描任务已在运行，忽略重复启动");
+        return;
+    };
+    let worker = state.clone();
+    tokio::spawn(async move {
+        while let Some(file_id) = rx.recv().await {
+            let service = service::FileService::from_state(&worker);
+            match service
+                .scan_file(file_id, &worker.scanner, worker.file_scan.quarantine)
+                .await
+            {
+                Ok(Some(status)) => info!(file_id, %status, "文件扫描完成"),
+                Ok(None) => {}
+                Err(e) => warn!(file_id, error = %e, "文件扫描失败，稍后重试"),
+            }
+        }
+    });
+
+    let interval = state.file_scan.rescan_interval_secs;
+    if interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match service::FileService::from_state(&state)
+                .pending_scan_files(RESCAN_BATCH_SIZE)
+                .await
+            {
+                Ok(ids) => {
+                    let queued = ids
+                        .into_iter()
+                        .filter(|&id| state.scan_queue.enqueue(id))
+                        .count();
+                    if queued > 0 {
+                        info!(count = queued, "已重新投递待扫描的文件");
+                    }
+                }
+                Err(e) => warn!(error = %e, "查询待扫描的文件失败"),
+            }
+        }
+    });
+}
+
+/// 启动缩略图生成任务
+///
+/// 逐个处理 [`AppState::thumbnail_queue`] 中投递的文件 ID，为等待生成缩略图的图片生成全部尺寸；
+/// 失败（如图片无法解码、存储不可用）只记录日志，文件保持 `pending`，下次请求缩略图时重新投递。
+///
+/// [`AppState::thumbnail_queue`]: crate::AppState::thumbnail_queue
+pub fn spawn_thumbnail_worker(state: Arc<AppState>) {
+    let Some(mut rx) = state.thumbnail_queue.take_receiver() else {
+        warn!("缩略图任务已在运行，忽略重复启动");
+        return;
+    };
+    tokio::spawn(async move {
+        while let Some(file_id) = rx.recv().await {
+            let service = service::FileService::from_state(&state);
+            match service.generate_thumbnails(file_id).await {
+                Ok(Some(sizes)) => info!(file_id, ?sizes, "缩略图生成完成"),
+                Ok(None) => {}
+                Err(e) => warn!(file_id, error = %e, "缩略图生成失败，保持待生成状态"),
+            }
+        }
+    });
+}
+
+/// 构建按所有者查询文件的路由，挂载在 `/users` 下
+///
+/// 配置以下端点：
+/// - GET /{id}/files - 分页列出指定用户的文件（需要认证）
+/// - GET /me/storage - 查询当前用户的存储空间用量（需要认证）
+///
+/// # 参数
+/// * `state` - 应用状态，包含数据库和服务实例
+///
+/// # 返回
+/// 返回配置好的路由器
+pub fn owner_routes(state: Arc<AppState>) -> ApiRouter {
+    ApiRouter::new()
+        .api_route_access(
+            "/{id}/files",
+            Access::Authenticated,
+            &state,
+            get_with(handler::list_user_files, handler::list_user_files_docs),
+        )
+        .api_route_access(
+            "/me/storage",
+            Access::Authenticated,
+            &state,
+            get_with(handler::get_storage_usage, handler::get_storage_usage_docs),
+        )
+        .with_module_tag(&TAG)
+        .with_state(state)
+}
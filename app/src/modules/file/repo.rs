@@ -0,0 +1,1778 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, JoinType,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, RelationTrait, Set,
+};
+
+use crate::core::tx::{Transactional, Tx};
+use crate::{AppState, core::response::PageParams, shared::FromState};
+use entity::user::{self, UserId};
+use entity::{
+    blob,
+    enums::{ScanStatus, ThumbnailStatus},
+    file, upload_chunk, upload_session,
+};
+
+/// 用户的存储空间配额和已用量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageUsage {
+    /// 用户单独设置的配额（字节），`None` 表示使用默认配额
+    pub quota_bytes: Option<i64>,
+
+    /// 已登记文件的大小合计（字节）
+    pub used_bytes: i64,
+}
+
+/// 文件列表的过滤条件，未设置的条件不参与过滤
+#[derive(Debug, Clone, Default)]
+pub struct FileFilter {
+    /// MIME 类型（小写），以 `/*` 结尾时匹配该主类型下的所有类型
+    pub content_type: Option<String>,
+
+    /// 上传时间起点（包含）
+    pub from: Option<DateTime<Utc>>,
+
+    /// 上传时间终点（不包含）
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl FileFilter {
+    /// `image/*` 形式的通配返回 `image/`，精确类型返回 `None`
+    fn type_prefix(&self) -> Option<&str> {
+        self.content_type.as_deref()?.strip_suffix('*')
+    }
+
+    /// 内存中判断文件是否满足条件（与数据库查询的语义一致）
+    #[cfg(test)]
+    fn matches(&self, file: &file::Model) -> bool {
+        let type_matches = match (self.type_prefix(), &self.content_type) {
+            (Some(prefix), _) => file.content_type.starts_with(prefix),
+            (None, Some(content_type)) => file.content_type == *content_type,
+            (None, None) => true,
+        };
+        type_matches
+            && self.from.is_none_or(|from| file.created_at >= from)
+            && self.to.is_none_or(|to| file.created_at < to)
+    }
+}
+
+/// 登记文件元数据的结果
+#[derive(Debug)]
+pub enum InsertOutcome {
+    /// 已登记；相同内容已存在时记录中的 `storage_key` / `storage_backend` 指向已有内容的位置
+    Inserted(Box<file::Model>),
+
+    /// 计入后所有者的已用量将超过配额，未写入
+    QuotaExceeded,
+
+    /// 要求复用已有内容（未提供 `storage_key`），但该内容已不存在，未写入
+    BlobMissing,
+}
+
+/// 未参与去重的早期文件删除后，需要从存储后端删除的内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnreferencedContent {
+    /// 对象键
+    pub storage_key: String,
+
+    /// 所在的存储后端
+    pub storage_backend: String,
+}
+
+/// 内容改用 `backend` 上的 `to` 后被替换的对象：`from` 和文件记录中的 `(storage_key, storage_backend)`，
+/// 去掉重复的和 `to` 本身
+fn replaced_copies(
+    from: &str,
+    to: &str,
+    backend: &str,
+    copies: impl IntoIterator<Item = (String, String)>,
+) -> Vec<UnreferencedContent> {
+    let mut replaced: Vec<UnreferencedContent> = Vec::new();
+    let from = (from.to_string(), backend.to_string());
+    for (storage_key, storage_backend) in std::iter::once(from).chain(copies) {
+        let content = UnreferencedContent {
+            storage_key,
+            storage_backend,
+        };
+        let is_target = content.storage_key == to && content.storage_backend == backend;
+        if !is_target && !replaced.contains(&content) {
+            replaced.push(content);
+        }
+    }
+    replaced
+}
+
+/// 文件数据访问接口
+///
+/// 文件内容按 SHA-256（`file.checksum`）去重：相同内容只在存储后端保存一份，对象键由摘要决定
+/// （`blobs/{hash}`），由 `blob` 记录对象位置和引用计数，文件记录的 `storage_key` / `storage_backend`
+/// 与所引用的 blob 保持一致。去重之前上传的内容仍在旧的对象键下，由 [`FileRepo::relocate_blob`] 逐个迁移。
+/// 校验和为空的早期文件不参与去重，独占自己的内容。
+pub trait FileRepo: Send + Sync {
+    /// 查询用户角色，用户不存在时返回 `None`
+    async fn find_user_role(&self, user_id: UserId) -> Result<Option<i16>, DbErr>;
+
+    /// 按条件分页查询指定用户拥有的文件，按上传时间倒序，返回当前页数据和总数
+    async fn list_by_owner(
+        &self,
+        owner_id: UserId,
+        filter: &FileFilter,
+        params: PageParams,
+    ) -> Result<(Vec<file::Model>, u64), DbErr>;
+
+    /// 查询用户的存储配额和已用量，用户不存在时返回 `None`
+    async fn find_storage_usage(&self, user_id: UserId) -> Result<Option<StorageUsage>, DbErr>;
+
+    /// 插入文件元数据，计入所有者的已用量并引用内容
+    ///
+    /// 相同校验和的内容已存在时引用计数加一，记录改用已有内容的位置；不存在时以传入的 `storage_key`
+    /// 登记新内容。已有文件引用同一内容时，扫描状态与这些文件一致，
+    /// 已为同一内容生成缩略图时直接沿用。未提供 `storage_key` 时只复用已有内容，不存在则返回
+    /// [`InsertOutcome::BlobMissing`]。计入后的已用量超过 `quota_bytes` 时返回 [`InsertOutcome::QuotaExceeded`]，
+    /// `quota_bytes` 为 `None` 时不限制。已用量、引用计数和元数据在同一事务中更新，并发上传不会超出配额。
+    async fn insert(
+        &self,
+        model: file::ActiveModel,
+        quota_bytes: Option<i64>,
+    ) -> Result<InsertOutcome, DbErr>;
+
+    /// 按 ID 查询文件元数据
+    async fn find_by_id(&self, id: i64) -> Result<Option<file::Model>, DbErr>;
+
+    /// 更新文件名和描述，返回更新后的记录，记录不存在时返回 `None`
+    async fn update_metadata(
+        &self,
+        id: i64,
+        filename: &str,
+        description: Option<&str>,
+    ) -> Result<Option<file::Model>, DbErr>;
+
+    /// 删除文件元数据，从所有者的已用量中扣除并释放对内容的引用，记录不存在时视为成功
+    ///
+    /// 引用计数降为 0 的 blob 记录保留，由回收任务删除对象后再删除记录；
+    /// 只有校验和为空的早期文件返回需要立即从存储后端删除的内容。
+    async fn delete(&self, id: i64) -> Result<Option<UnreferencedContent>, DbErr>;
+
+    /// 更新文件内容所在的存储后端（引用同一内容的文件一起更新）
+    async fn update_storage_backend(&self, id: i64, backend: &str) -> Result<(), DbErr>;
+
+    /// 更新文件内容的对象键（内容被隔离时使用，引用同一内容的文件一起更新）
+    async fn update_storage_key(&self, id: i64, storage_key: &str) -> Result<(), DbErr>;
+
+    /// 更新文件的恶意软件扫描状态（引用同一内容的文件一起更新）
+    async fn update_scan_status(&self, id: i64, status: &str) -> Result<(), DbErr>;
+
+    /// 更新文件的缩略图状态和已生成的边长（引用同一内容的文件一起更新）
+    async fn update_thumbnails(&self, id: i64, status: &str, sizes: &[u32]) -> Result<(), DbErr>;
+
+    /// 按 SHA-256 查询已登记的内容
+    async fn find_blob(&self, hash: &str) -> Result<Option<blob::Model>, DbErr>;
+
+    /// 按摘要升序列出 `after` 之后的内容，最多 `limit` 个（供完整性检查分批遍历）
+    async fn list_blobs(&self, after: Option<&str>, limit: u64) -> Result<Vec<blob::Model>, DbErr>;
+
+    /// 统计实际引用该内容的文件记录数
+    async fn count_blob_refs(&self, hash: &str) -> Result<u64, DbErr>;
+
+    /// 引用计数仍为 `expected` 时修正为 `actual`，`actual` 为 0 时删除 blob 记录
+    ///
+    /// 期间有文件登记或删除（引用计数已变化）时不修改，返回 `false`。
+    async fn reconcile_blob_refs(
+        &self,
+        hash: &str,
+        expected: i64,
+        actual: u64,
+    ) -> Result<bool, DbErr>;
+
+    /// 设置或清除内容的损坏标记（对象在存储后端中缺失），已标记的内容保留最初的标记时间
+    async fn set_blob_corrupt(
+        &self,
+        hash: &str,
+        corrupt_at: Option<DateTimeWithTimeZone>,
+    ) -> Result<(), DbErr>;
+
+    /// 分页查询引用了损坏内容的文件，按 ID 升序，返回文件及内容被标记损坏的时间和总数
+    async fn list_corrupt_files(
+        &self,
+        params: PageParams,
+    ) -> Result<(Vec<(file::Model, DateTimeWithTimeZone)>, u64), DbErr>;
+
+    /// 内容的对象键仍为 `from` 时，把内容及引用它的文件改为使用同一后端上的 `to`
+    ///
+    /// 返回被替换、可以从存储后端删除的对象（`from` 以及文件各自保存的旧副本，不含 `to`）；
+    /// 期间内容已被隔离、迁移或回收（对象键不再是 `from`）时不修改，返回 `None`。
+    async fn relocate_blob(
+        &self,
+        hash: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Option<Vec<UnreferencedContent>>, DbErr>;
+
+    /// 从 `keys` 中找出 `backend` 上仍被内容或文件记录引用的对象键
+    async fn referenced_storage_keys(
+        &self,
+        backend: &str,
+        keys: &[String],
+    ) -> Result<HashSet<String>, DbErr>;
+
+    /// 列出等待扫描的文件 ID，按 ID 升序，最多 `limit` 个
+    async fn list_pending_scan(&self, limit: u64) -> Result<Vec<i64>, DbErr>;
+
+    /// 创建分片上传会话
+    async fn insert_session(
+        &self,
+        model: upload_session::Model,
+    ) -> Result<upload_session::Model, DbErr>;
+
+    /// 按 ID 查询分片上传会话
+    async fn find_session(&self, id: &str) -> Result<Option<upload_session::Model>, DbErr>;
+
+    /// 列出会话已接收的分片，按序号升序
+    async fn list_chunks(&self, session_id: &str) -> Result<Vec<upload_chunk::Model>, DbErr>;
+
+    /// 记录已接收的分片，同一序号重复上传时覆盖
+    async fn save_chunk(
+        &self,
+        session_id: &str,
+        chunk_index: i32,
+        size_bytes: i64,
+    ) -> Result<(), DbErr>;
+
+    /// 删除单个分片记录，记录不存在时视为成功
+    async fn delete_chunk(&self, session_id: &str, chunk_index: i32) -> Result<(), DbErr>;
+
+    /// 删除会话及其分片记录
+    async fn delete_session(&self, id: &str) -> Result<(), DbErr>;
+
+    /// 列出在 `now` 之前过期的会话
+    async fn list_expired_sessions(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<upload_session::Model>, DbErr>;
+}
+
+/// 基于 SeaORM 的文件数据访问实现
+#[derive(Debug, Clone)]
+pub struct SeaOrmFileRepo {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmFileRepo {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+impl Transactional for SeaOrmFileRepo {
+    fn connection(&self) -> &DatabaseConnection {
+        &self.db
+    }
+}
+
+impl SeaOrmFileRepo {
+    /// 更新文件内容相关的列：引用同一内容的文件一起更新，对象位置同时写入 blob 记录
+    async fn update_content(
+        &self,
+        id: i64,
+        column: file::Column,
+        value: &str,
+    ) -> Result<(), DbErr> {
+        let tx = Tx::begin(&self.db).await?;
+        let file = file::Entity::find_by_id(id)
+            .one(&tx)
+            .await?
+            .ok_or(DbErr::RecordNotUpdated)?;
+        let same_content = if file.checksum.is_empty() {
+            file::Column::Id.eq(id)
+        } else {
+            file::Column::Checksum.eq(&file.checksum)
+        };
+        file::Entity::update_many()
+            .col_expr(column, Expr::value(value))
+            .filter(same_content)
+            .exec(&tx)
+            .await?;
+
+        let blob_column = match column {
+            file::Column::StorageKey => Some(blob::Column::StorageKey),
+            file::Column::StorageBackend => Some(blob::Column::StorageBackend),
+            _ => None,
+        };
+        if let Some(blob_column) = blob_column.filter(|_| !file.checksum.is_empty()) {
+            blob::Entity::update_many()
+                .col_expr(blob_column, Expr::value(value))
+                .filter(blob::Column::Hash.eq(&file.checksum))
+                .exec(&tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+}
+
+impl FromState for SeaOrmFileRepo {
+    fn from_state(app: &AppState) -> Self {
+        Self::new(app.db.clone())
+    }
+}
+
+impl FileRepo for SeaOrmFileRepo {
+    async fn find_user_role(&self, user_id: UserId) -> Result<Option<i16>, DbErr> {
+        user::Entity::find_by_id(user_id)
+            .select_only()
+            .column(user::Column::Role)
+            .into_tuple()
+            .one(&self.db)
+            .await
+    }
+
+    async fn list_by_owner(
+        &self,
+        owner_id: UserId,
+        filter: &FileFilter,
+        params: PageParams,
+    ) -> Result<(Vec<file::Model>, u64), DbErr> {
+        let mut query = file::Entity::find()
+            .join(JoinType::InnerJoin, file::Relation::User.def())
+            .filter(user::Column::Id.eq(owner_id));
+        match (filter.type_prefix(), &filter.content_type) {
+            (Some(prefix), _) => {
+                query = query.filter(file::Column::ContentType.starts_with(prefix));
+            }
+            (None, Some(content_type)) => {
+                query = query.filter(file::Column::ContentType.eq(content_type));
+            }
+            (None, None) => {}
+        }
+        if let Some(from) = filter.from {
+            query = query.filter(file::Column::CreatedAt.gte(from));
+        }
+        if let Some(to) = filter.to {
+            query = query.filter(file::Column::CreatedAt.lt(to));
+        }
+        let paginator = query
+            .order_by_desc(file::Column::CreatedAt)
+            .order_by_desc(file::Column::Id)
+            .paginate(&self.db, params.per_page);
+
+        let total = paginator.num_items().await?;
+        let items = paginator.fetch_page(params.page - 1).await?;
+        Ok((items, total))
+    }
+
+    async fn find_storage_usage(&self, user_id: UserId) -> Result<Option<StorageUsage>, DbErr> {
+        let usage: Option<(Option<i64>, i64)> = user::Entity::find_by_id(user_id)
+            .select_only()
+            .column(user::Column::StorageQuotaBytes)
+            .column(user::Column::StorageUsedBytes)
+            .into_tuple()
+            .one(&self.db)
+            .await?;
+        Ok(usage.map(|(quota_bytes, used_bytes)| StorageUsage {
+            quota_bytes,
+            used_bytes,
+        }))
+    }
+
+    async fn insert(
+        &self,
+        model: file::ActiveModel,
+        quota_bytes: Option<i64>,
+    ) -> Result<InsertOutcome, DbErr> {
+        let (Some(owner_id), Some(size)) = (
+            model.owner_id.clone().take(),
+            model.size_bytes.clone().take(),
+        ) else {
+            return Err(DbErr::Custom("缺少字段 owner_id 或 size_bytes".to_string()));
+        };
+
+        let mut model = model;
+        let checksum = model.checksum.clone().take().unwrap_or_default();
+
+        // 带条件的 UPDATE 在行锁下判断并占用配额，未命中说明空间不足；出错或未提交时事务在丢弃时回滚
+        let tx = Tx::begin(&self.db).await?;
+        let used = Expr::col(user::Column::StorageUsedBytes);
+        let mut reserve = user::Entity::update_many()
+            .col_expr(user::Column::StorageUsedBytes, used.clone().add(size))
+            .filter(user::Column::Id.eq(owner_id));
+        if let Some(quota) = quota_bytes {
+            reserve = reserve.filter(used.lte(quota - size));
+        }
+        if reserve.exec(&tx).await?.rows_affected == 0 {
+            return Ok(InsertOutcome::QuotaExceeded);
+        }
+
+        if !checksum.is_empty() {
+            match (
+                model.storage_key.clone().take(),
+                model.storage_backend.clone().take(),
+            ) {
+                // 内容已存在时只增加引用计数，主键冲突在数据库内原子处理，并发上传相同内容不会重复登记
+                (Some(storage_key), Some(storage_backend)) => {
+                    blob::Entity::insert(blob::ActiveModel {
+                        hash: Set(checksum.clone()),
+                        size_bytes: Set(size),
+                        ref_count: Set(1),
+                        storage_key: Set(storage_key),
+                        storage_backend: Set(storage_backend),
+                        created_at: Set(chrono::Utc::now().fixed_offset()),
+                        corrupt_at: Set(None),
+                    })
+                    .on_conflict(
+                        OnConflict::column(blob::Column::Hash)
+                            .value(
+                                blob::Column::RefCount,
+                                Expr::col((blob::Entity, blob::Column::RefCount)).add(1),
+                            )
+                            .to_owned(),
+                    )
+                    .exec_without_returning(&tx)
+                    .await?;
+                }
+                _ => {
+                    let updated = blob::Entity::update_many()
+                        .col_expr(
+                            blob::Column::RefCount,
+                            Expr::col(blob::Column::RefCount).add(1),
+                        )
+                        .filter(blob::Column::Hash.eq(&checksum))
+                        .exec(&tx)
+                        .await?;
+                    if updated.rows_affected == 0 {
+                        return Ok(InsertOutcome::BlobMissing);
+                    }
+                }
+            }
+            let blob = blob::Entity::find_by_id(&checksum)
+                .one(&tx)
+                .await?
+                .ok_or_else(|| DbErr::RecordNotFound(format!("blob {checksum}")))?;
+            // 复用已有内容：扫描结论与引用同一内容的文件一致
+            let scan_status: Option<String> = file::Entity::find()
+                .select_only()
+                .column(file::Column::ScanStatus)
+                .filter(file::Column::Checksum.eq(&checksum))
+                .into_tuple()
+                .one(&tx)
+                .await?;
+            if let Some(status) = scan_status {
+                model.scan_status = Set(status);
+            }
+            let thumbnails = file::Entity::find()
+                .filter(file::Column::Checksum.eq(&checksum))
+                .filter(file::Column::ThumbnailStatus.eq(ThumbnailStatus::Ready.to_string()))
+                .one(&tx)
+                .await?;
+            if let Some(sibling) = thumbnails {
+                model.thumbnail_status = Set(sibling.thumbnail_status);
+                model.thumbnail_sizes = Set(sibling.thumbnail_sizes);
+            }
+            model.storage_key = Set(blob.storage_key);
+            model.storage_backend = Set(blob.storage_backend);
+        }
+
+        let model = model.insert(&tx).await?;
+        tx.commit().await?;
+        Ok(InsertOutcome::Inserted(Box::new(model)))
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<file::Model>, DbErr> {
+        file::Entity::find_by_id(id).one(&self.db).await
+    }
+
+    async fn update_metadata(
+        &self,
+        id: i64,
+        filename: &str,
+        description: Option<&str>,
+    ) -> Result<Option<file::Model>, DbErr> {
+        let Some(model) = file::Entity::find_by_id(id).one(&self.db).await? else {
+            return Ok(None);
+        };
+        let mut model: file::ActiveModel = model.into();
+        model.filename = Set(filename.to_string());
+        model.description = Set(description.map(str::to_string));
+        model.update(&self.db).await.map(Some)
+    }
+
+    async fn delete(&self, id: i64) -> Result<Option<UnreferencedContent>, DbErr> {
+        // 文件记录、所有者的已用量和 blob 引用计数在同一事务中更新
+        self.transaction(async |txn| {
+            let Some(file) = file::Entity::find_by_id(id).one(txn).await? else {
+                return Ok(None);
+            };
+            // 并发删除同一文件时只有实际删除了记录的一方扣除已用量和引用计数
+            if file::Entity::delete_by_id(id)
+                .exec(txn)
+                .await?
+                .rows_affected
+                == 0
+            {
+                return Ok(None);
+            }
+            user::Entity::update_many()
+                .col_expr(
+                    user::Column::StorageUsedBytes,
+                    Expr::col(user::Column::StorageUsedBytes).sub(file.size_bytes),
+                )
+                .filter(user::Column::Id.eq(file.owner_id))
+                .exec(txn)
+                .await?;
+
+            if file.checksum.is_empty() {
+                return Ok(Some(UnreferencedContent {
+                    storage_key: file.storage_key,
+                    storage_backend: file.storage_backend,
+                }));
+            }
+            blob::Entity::update_many()
+                .col_expr(
+                    blob::Column::RefCount,
+                    Expr::col(blob::Column::RefCount).sub(1),
+                )
+                .filter(blob::Column::Hash.eq(&file.checksum))
+                .exec(txn)
+                .await?;
+            Ok(None)
+        })
+        .await
+    }
+
+    async fn update_storage_backend(&self, id: i64, backend: &str) -> Result<(), DbErr> {
+        self.update_content(id, file::Column::StorageBackend, backend)
+            .await
+    }
+
+    async fn update_storage_key(&self, id: i64, storage_key: &str) -> Result<(), DbErr> {
+        self.update_content(id, file::Column::StorageKey, storage_key)
+            .await
+    }
+
+    async fn update_scan_status(&self, id: i64, status: &str) -> Result<(), DbErr> {
+        self.update_content(id, file::Column::ScanStatus, status)
+            .await
+    }
+
+    async fn update_thumbnails(&self, id: i64, status: &str, sizes: &[u32]) -> Result<(), DbErr> {
+        let Some(file) = file::Entity::find_by_id(id).one(&self.db).await? else {
+            return Err(DbErr::RecordNotUpdated);
+        };
+        let same_content = if file.checksum.is_empty() {
+            file::Column::Id.eq(id)
+        } else {
+            file::Column::Checksum.eq(&file.checksum)
+        };
+        file::Entity::update_many()
+            .col_expr(file::Column::ThumbnailStatus, Expr::value(status))
+            .col_expr(
+                file::Column::ThumbnailSizes,
+                Expr::value(serde_json::json!(sizes)),
+            )
+            .filter(same_content)
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn find_blob(&self, hash: &str) -> Result<Option<blob::Model>, DbErr> {
+        blob::Entity::find_by_id(hash).one(&self.db).await
+    }
+
+    async fn list_blobs(&self, after: Option<&str>, limit: u64) -> Result<Vec<blob::Model>, DbErr> {
+        let mut query = blob::Entity::find()
+            .order_by_asc(blob::Column::Hash)
+            .limit(limit);
+        if let Some(after) = after {
+            query = query.filter(blob::Column::Hash.gt(after));
+        }
+        query.all(&self.db).await
+    }
+
+    async fn count_blob_refs(&self, hash: &str) -> Result<u64, DbErr> {
+        file::Entity::find()
+            .filter(file::Column::Checksum.eq(hash))
+            .count(&self.db)
+            .await
+    }
+
+    async fn reconcile_blob_refs(
+        &self,
+        hash: &str,
+        expected: i64,
+        actual: u64,
+    ) -> Result<bool, DbErr> {
+        let unchanged = Condition::all()
+            .add(blob::Column::Hash.eq(hash))
+            .add(blob::Column::RefCount.eq(expected));
+        let result = if actual == 0 {
+            blob::Entity::delete_many()
+                .filter(unchanged)
+                .exec(&self.db)
+                .await?
+                .rows_affected
+        } else {
+            blob::Entity::update_many()
+                .col_expr(blob::Column::RefCount, Expr::value(actual as i64))
+                .filter(unchanged)
+                .exec(&self.db)
+                .await?
+                .rows_affected
+        };
+        Ok(result > 0)
+    }
+
+    async fn set_blob_corrupt(
+        &self,
+        hash: &str,
+        corrupt_at: Option<DateTimeWithTimeZone>,
+    ) -> Result<(), DbErr> {
+        let mut update = blob::Entity::update_many()
+            .col_expr(blob::Column::CorruptAt, Expr::value(corrupt_at))
+            .filter(blob::Column::Hash.eq(hash));
+        if corrupt_at.is_some() {
+            update = update.filter(blob::Column::CorruptAt.is_null());
+        }
+        update.exec(&self.db).await?;
+        Ok(())
+    }
+
+    async fn list_corrupt_files(
+        &self,
+        params: PageParams,
+    ) -> Result<(Vec<(file::Model, DateTimeWithTimeZone)>, u64), DbErr> {
+        let corrupt = blob::Entity::find()
+            .select_only()
+            .column(blob::Column::Hash)
+            .filter(blob::Column::CorruptAt.is_not_null())
+            .into_query();
+        let paginator = file::Entity::find()
+            .filter(file::Column::Checksum.in_subquery(corrupt))
+            .order_by_asc(file::Column::Id)
+            .paginate(&self.db, params.per_page);
+        let total = paginator.num_items().await?;
+        let files = paginator.fetch_page(params.page - 1).await?;
+
+        let hashes: HashSet<_> = files.iter().map(|f| f.checksum.clone()).collect();
+        let flagged: HashMap<String, Option<DateTimeWithTimeZone>> = blob::Entity::find()
+            .select_only()
+            .column(blob::Column::Hash)
+            .column(blob::Column::CorruptAt)
+            .filter(blob::Column::Hash.is_in(hashes))
+            .into_tuple()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .collect();
+        // 两次查询之间被清除标记的内容不再列出
+        let items = files
+            .into_iter()
+            .filter_map(|f| {
+                let corrupt_at = flagged.get(&f.checksum).copied().flatten()?;
+                Some((f, corrupt_at))
+            })
+            .collect();
+        Ok((items, total))
+    }
+
+    async fn relocate_blob(
+        &self,
+        hash: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Option<Vec<UnreferencedContent>>, DbErr> {
+        let tx = Tx::begin(&self.db).await?;
+        let Some(blob) = blob::Entity::find_by_id(hash)
+            .filter(blob::Column::StorageKey.eq(from))
+            .one(&tx)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let updated = blob::Entity::update_many()
+            .col_expr(blob::Column::StorageKey, Expr::value(to))
+            .filter(blob::Column::Hash.eq(hash))
+            .filter(blob::Column::StorageKey.eq(from))
+            .exec(&tx)
+            .await?;
+        if updated.rows_affected == 0 {
+            return Ok(None);
+        }
+
+        let copies: Vec<(String, String)> = file::Entity::find()
+            .select_only()
+            .column(file::Column::StorageKey)
+            .column(file::Column::StorageBackend)
+            .distinct()
+            .filter(file::Column::Checksum.eq(hash))
+            .into_tuple()
+            .all(&tx)
+            .await?;
+        file::Entity::update_many()
+            .col_expr(file::Column::StorageKey, Expr::value(to))
+            .col_expr(
+                file::Column::StorageBackend,
+                Expr::value(blob.storage_backend.clone()),
+            )
+            .filter(file::Column::Checksum.eq(hash))
+            .exec(&tx)
+            .await?;
+        tx.commit().await?;
+        Ok(Some(replaced_copies(
+            from,
+            to,
+            &blob.storage_backend,
+            copies,
+        )))
+    }
+
+    async fn referenced_storage_keys(
+        &self,
+        backend: &str,
+        keys: &[String],
+    ) -> Result<HashSet<String>, DbErr> {
+        let mut referenced: HashSet<String> = blob::Entity::find()
+            .select_only()
+            .column(blob::Column::StorageKey)
+            .filter(blob::Column::StorageBackend.eq(backend))
+            .filter(blob::Column::StorageKey.is_in(keys))
+            .into_tuple::<String>()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .collect();
+        // 校验和为空的早期文件没有 blob 记录，对象键只记录在文件上
+        let files: Vec<String> = file::Entity::find()
+            .select_only()
+            .column(file::Column::StorageKey)
+            .filter(file::Column::StorageBackend.eq(backend))
+            .filter(file::Column::StorageKey.is_in(keys))
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+        referenced.extend(files);
+        Ok(referenced)
+    }
+
+    async fn list_pending_scan(&self, limit: u64) -> Result<Vec<i64>, DbErr> {
+        file::Entity::find()
+            .select_only()
+            .column(file::Column::Id)
+            .filter(file::Column::ScanStatus.eq(ScanStatus::PendingScan.to_string()))
+            .order_by_asc(file::Column::Id)
+            .limit(limit)
+            .into_tuple()
+            .all(&self.db)
+            .await
+    }
+
+    async fn insert_session(
+        &self,
+        model: upload_session::Model,
+    ) -> Result<upload_session::Model, DbErr> {
+        upload_session::ActiveModel::from(model)
+            .insert(&self.db)
+            .await
+    }
+
+    async fn find_session(&self, id: &str) -> Result<Option<upload_session::Model>, DbErr> {
+        upload_session::Entity::find_by_id(id).one(&self.db).await
+    }
+
+    async fn list_chunks(&self, session_id: &str) -> Result<Vec<upload_chunk::Model>, DbErr> {
+        upload_chunk::Entity::find()
+            .filter(upload_chunk::Column::SessionId.eq(session_id))
+            .order_by_asc(upload_chunk::Column::ChunkIndex)
+            .all(&self.db)
+            .await
+    }
+
+    async fn save_chunk(
+        &self,
+        session_id: &str,
+        chunk_index: i32,
+        size_bytes: i64,
+    ) -> Result<(), DbErr> {
+        let chunk = upload_chunk::ActiveModel {
+            session_id: Set(session_id.to_string()),
+            chunk_index: Set(chunk_index),
+            size_bytes: Set(size_bytes),
+            uploaded_at: Set(chrono::Utc::now().fixed_offset()),
+        };
+        upload_chunk::Entity::insert(chunk)
+            .on_conflict(
+                OnConflict::columns([
+                    upload_chunk::Column::SessionId,
+                    upload_chunk::Column::ChunkIndex,
+                ])
+                .update_columns([
+                    upload_chunk::Column::SizeBytes,
+                    upload_chunk::Column::UploadedAt,
+                ])
+                .to_owned(),
+            )
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_chunk(&self, session_id: &str, chunk_index: i32) -> Result<(), DbErr> {
+        upload_chunk::Entity::delete_by_id((session_id.to_string(), chunk_index))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_session(&self, id: &str) -> Result<(), DbErr> {
+        // SQLite 默认不启用外键约束，不依赖级联删除
+        upload_chunk::Entity::delete_many()
+            .filter(upload_chunk::Column::SessionId.eq(id))
+            .exec(&self.db)
+            .await?;
+        upload_session::Entity::delete_by_id(id)
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    async fn list_expired_sessions(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<upload_session::Model>, DbErr> {
+        upload_session::Entity::find()
+            .filter(upload_session::Column::ExpiresAt.lt(now))
+            .all(&self.db)
+            .await
+    }
+}
+
+/// 内存文件数据访问实现（仅用于测试）
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct InMemoryFileRepo {
+    /// (用户 ID, 角色, 存储用量)
+    users: std::sync::Mutex<Vec<(UserId, i16, StorageUsage)>>,
+    files: std::sync::Mutex<Vec<file::Model>>,
+    blobs: std::sync::Mutex<Vec<blob::Model>>,
+    sessions: std::sync::Mutex<Vec<upload_session::Model>>,
+    chunks: std::sync::Mutex<Vec<upload_chunk::Model>>,
+}
+
+#[cfg(test)]
+impl InMemoryFileRepo {
+    /// 预置一个用户
+    pub fn seed_user(&self, id: UserId, role: i16) {
+        self.users
+            .lock()
+            .unwrap()
+            .push((id, role, StorageUsage::default()));
+    }
+
+    /// 设置用户单独的存储配额
+    pub fn set_storage_quota(&self, id: UserId, quota_bytes: Option<i64>) {
+        for (user_id, _, usage) in self.users.lock().unwrap().iter_mut() {
+            if *user_id == id {
+                usage.quota_bytes = quota_bytes;
+            }
+        }
+    }
+
+    /// 预置一个文件，计入所有者的已用量和内容的引用计数
+    pub fn seed_file(&self, model: file::Model) {
+        for (user_id, _, usage) in self.users.lock().unwrap().iter_mut() {
+            if *user_id == model.owner_id {
+                usage.used_bytes += model.size_bytes;
+            }
+        }
+        if !model.checksum.is_empty() {
+            let mut blobs = self.blobs.lock().unwrap();
+            match blobs.iter_mut().find(|b| b.hash == model.checksum) {
+                Some(blob) => blob.ref_count += 1,
+                None => blobs.push(blob::Model {
+                    hash: model.checksum.clone(),
+                    size_bytes: model.size_bytes,
+                    ref_count: 1,
+                    storage_key: model.storage_key.clone(),
+                    storage_backend: model.storage_backend.clone(),
+                    created_at: model.created_at,
+                    corrupt_at: None,
+                }),
+            }
+        }
+        self.files.lock().unwrap().push(model);
+    }
+
+    /// 预置一条内容记录（不关联文件）
+    pub fn seed_blob(&self, model: blob::Model) {
+        self.blobs.lock().unwrap().push(model);
+    }
+
+    /// 直接修改内容的引用计数（模拟与文件记录不一致的情况）
+    pub fn set_blob_ref_count(&self, hash: &str, ref_count: i64) {
+        for blob in self.blobs.lock().unwrap().iter_mut() {
+            if blob.hash == hash {
+                blob.ref_count = ref_count;
+            }
+        }
+    }
+
+    /// 更新文件内容相关的字段：引用同一内容的文件和 blob 一起更新
+    fn update_content(
+        &self,
+        id: i64,
+        update_file: impl Fn(&mut file::Model),
+        update_blob: impl Fn(&mut blob::Model),
+    ) -> Result<(), DbErr> {
+        let mut files = self.files.lock().unwrap();
+        let checksum = files
+            .iter()
+            .find(|f| f.id == id)
+            .ok_or(DbErr::RecordNotUpdated)?
+            .checksum
+            .clone();
+        for file in files.iter_mut() {
+            if file.id == id || (!checksum.is_empty() && file.checksum == checksum) {
+                update_file(file);
+            }
+        }
+        for blob in self.blobs.lock().unwrap().iter_mut() {
+            if blob.hash == checksum {
+                update_blob(blob);
+            }
+        }
+        Ok(())
+    }
+
+    /// 按 ID 查询已登记的文件
+    pub fn file(&self, id: i64) -> Option<file::Model> {
+        self.files
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|f| f.id == id)
+            .cloned()
+    }
+
+    /// 将会话的过期时间改为指定时间
+    pub fn set_session_expiry(&self, id: &str, expires_at: DateTimeWithTimeZone) {
+        for session in self.sessions.lock().unwrap().iter_mut() {
+            if session.id == id {
+                session.expires_at = expires_at;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl FileRepo for InMemoryFileRepo {
+    async fn find_user_role(&self, user_id: UserId) -> Result<Option<i16>, DbErr> {
+        Ok(self
+            .users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(id, _, _)| *id == user_id)
+            .map(|(_, role, _)| *role))
+    }
+
+    async fn find_storage_usage(&self, user_id: UserId) -> Result<Option<StorageUsage>, DbErr> {
+        Ok(self
+            .users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(id, _, _)| *id == user_id)
+            .map(|(_, _, usage)| *usage))
+    }
+
+    async fn list_by_owner(
+        &self,
+        owner_id: UserId,
+        filter: &FileFilter,
+        params: PageParams,
+    ) -> Result<(Vec<file::Model>, u64), DbErr> {
+        let mut owned: Vec<_> = self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|f| f.owner_id == owner_id && filter.matches(f))
+            .cloned()
+            .collect();
+        owned.sort_by_key(|f| std::cmp::Reverse((f.created_at, f.id)));
+
+        let total = owned.len() as u64;
+        let items = owned
+            .into_iter()
+            .skip(params.offset() as usize)
+            .take(params.per_page as usize)
+            .collect();
+        Ok((items, total))
+    }
+
+    async fn insert(
+        &self,
+        mut model: file::ActiveModel,
+        quota_bytes: Option<i64>,
+    ) -> Result<InsertOutcome, DbErr> {
+        let mut users = self.users.lock().unwrap();
+        let mut files = self.files.lock().unwrap();
+        let mut blobs = self.blobs.lock().unwrap();
+        let required = |name: &str| DbErr::Custom(format!("缺少字段 {name}"));
+        let storage_key = model.storage_key.take();
+        let mut model = file::Model {
+            id: files.iter().map(|f| f.id).max().unwrap_or_default() + 1,
+            owner_id: model.owner_id.take().ok_or_else(|| required("owner_id"))?,
+            filename: model.filename.take().ok_or_else(|| required("filename"))?,
+            content_type: model
+                .content_type
+                .take()
+                .ok_or_else(|| required("content_type"))?,
+            size_bytes: model
+                .size_bytes
+                .take()
+                .ok_or_else(|| required("size_bytes"))?,
+            storage_key: storage_key.clone().unwrap_or_default(),
+            created_at: chrono::Utc::now().fixed_offset(),
+            checksum: model.checksum.take().unwrap_or_default(),
+            description: model.description.take().flatten(),
+            storage_backend: model
+                .storage_backend
+                .take()
+                .unwrap_or_else(|| entity::enums::StorageBackend::Local.to_string()),
+            scan_status: model
+                .scan_status
+                .take()
+                .unwrap_or_else(|| ScanStatus::Clean.to_string()),
+            thumbnail_status: model.thumbnail_status.take().flatten(),
+            thumbnail_sizes: model
+                .thumbnail_sizes
+                .take()
+                .unwrap_or_else(|| serde_json::json!([])),
+        };
+        let Some((_, _, usage)) = users.iter_mut().find(|(id, _, _)| *id == model.owner_id) else {
+            return Ok(InsertOutcome::QuotaExceeded);
+        };
+        if quota_bytes.is_some_and(|quota| usage.used_bytes + model.size_bytes > quota) {
+            return Ok(InsertOutcome::QuotaExceeded);
+        }
+
+        if model.checksum.is_empty() {
+            if storage_key.is_none() {
+                return Err(required("storage_key"));
+            }
+        } else {
+            match blobs.iter_mut().find(|b| b.hash == model.checksum) {
+                Some(blob) => {
+                    blob.ref_count += 1;
+                    model.storage_key = blob.storage_key.clone();
+                    model.storage_backend = blob.storage_backend.clone();
+                    if let Some(sibling) = files.iter().find(|f| f.checksum == model.checksum) {
+                        model.scan_status = sibling.scan_status.clone();
+                    }
+                    let ready = Some(ThumbnailStatus::Ready.to_string());
+                    if let Some(sibling) = files
+                        .iter()
+                        .find(|f| f.checksum == model.checksum && f.thumbnail_status == ready)
+                    {
+                        model.thumbnail_status = sibling.thumbnail_status.clone();
+                        model.thumbnail_sizes = sibling.thumbnail_sizes.clone();
+                    }
+                }
+                None if storage_key.is_none() => return Ok(InsertOutcome::BlobMissing),
+                None => blobs.push(blob::Model {
+                    hash: model.checksum.clone(),
+                    size_bytes: model.size_bytes,
+                    ref_count: 1,
+                    storage_key: model.storage_key.clone(),
+                    storage_backend: model.storage_backend.clone(),
+                    created_at: model.created_at,
+                    corrupt_at: None,
+                }),
+            }
+        }
+        usage.used_bytes += model.size_bytes;
+        files.push(model.clone());
+        Ok(InsertOutcome::Inserted(Box::new(model)))
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<file::Model>, DbErr> {
+        Ok(self.file(id))
+    }
+
+    async fn update_metadata(
+        &self,
+        id: i64,
+        filename: &str,
+        description: Option<&str>,
+    ) -> Result<Option<file::Model>, DbErr> {
+        let mut files = self.files.lock().unwrap();
+        let Some(file) = files.iter_mut().find(|f| f.id == id) else {
+            return Ok(None);
+        };
+        file.filename = filename.to_string();
+        file.description = description.map(str::to_string);
+        Ok(Some(file.clone()))
+    }
+
+    async fn delete(&self, id: i64) -> Result<Option<UnreferencedContent>, DbErr> {
+        let mut users = self.users.lock().unwrap();
+        let mut files = self.files.lock().unwrap();
+        let mut blobs = self.blobs.lock().unwrap();
+        let Some(index) = files.iter().position(|f| f.id == id) else {
+            return Ok(None);
+        };
+        let file = files.remove(index);
+        for (user_id, _, usage) in users.iter_mut() {
+            if *user_id == file.owner_id {
+                usage.used_bytes -= file.size_bytes;
+            }
+        }
+        if file.checksum.is_empty() {
+            return Ok(Some(UnreferencedContent {
+                storage_key: file.storage_key,
+                storage_backend: file.storage_backend,
+            }));
+        }
+        for blob in blobs.iter_mut() {
+            if blob.hash == file.checksum {
+                blob.ref_count -= 1;
+            }
+        }
+        Ok(None)
+    }
+
+    async fn update_storage_backend(&self, id: i64, backend: &str) -> Result<(), DbErr> {
+        self.update_content(
+            id,
+            |f| f.storage_backend = backend.to_string(),
+            |b| b.storage_backend = backend.to_string(),
+        )
+    }
+
+    async fn update_storage_key(&self, id: i64, storage_key: &str) -> Result<(), DbErr> {
+        self.update_content(
+            id,
+            |f| f.storage_key = storage_key.to_string(),
+            |b| b.storage_key = storage_key.to_string(),
+        )
+    }
+
+    async fn update_scan_status(&self, id: i64, status: &str) -> Result<(), DbErr> {
+        self.update_content(id, |f| f.scan_status = status.to_string(), |_| {})
+    }
+
+    async fn update_thumbnails(&self, id: i64, status: &str, sizes: &[u32]) -> Result<(), DbErr> {
+        self.update_content(
+            id,
+            |f| {
+                f.thumbnail_status = Some(status.to_string());
+                f.thumbnail_sizes = serde_json::json!(sizes);
+            },
+            |_| {},
+        )
+    }
+
+    async fn find_blob(&self, hash: &str) -> Result<Option<blob::Model>, DbErr> {
+        Ok(self
+            .blobs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|b| b.hash == hash)
+            .cloned())
+    }
+
+    async fn list_blobs(&self, after: Option<&str>, limit: u64) -> Result<Vec<blob::Model>, DbErr> {
+        let mut blobs: Vec<_> = self
+            .blobs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|b| after.is_none_or(|after| b.hash.as_str() > after))
+            .cloned()
+            .collect();
+        blobs.sort_by(|a, b| a.hash.cmp(&b.hash));
+        blobs.truncate(limit as usize);
+        Ok(blobs)
+    }
+
+    async fn count_blob_refs(&self, hash: &str) -> Result<u64, DbErr> {
+        Ok(self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|f| f.checksum == hash)
+            .count() as u64)
+    }
+
+    async fn reconcile_blob_refs(
+        &self,
+        hash: &str,
+        expected: i64,
+        actual: u64,
+    ) -> Result<bool, DbErr> {
+        let mut blobs = self.blobs.lock().unwrap();
+        let Some(index) = blobs
+            .iter()
+            .position(|b| b.hash == hash && b.ref_count == expected)
+        else {
+            return Ok(false);
+        };
+        if actual == 0 {
+            blobs.remove(index);
+        } else {
+            blobs[index].ref_count = actual as i64;
+        }
+        Ok(true)
+    }
+
+    async fn set_blob_corrupt(
+        &self,
+        hash: &str,
+        corrupt_at: Option<DateTimeWithTimeZone>,
+    ) -> Result<(), DbErr> {
+        for blob in self.blobs.lock().unwrap().iter_mut() {
+            if blob.hash == hash && (corrupt_at.is_none() || blob.corrupt_at.is_none()) {
+                blob.corrupt_at = corrupt_at;
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_corrupt_files(
+        &self,
+        params: PageParams,
+    ) -> Result<(Vec<(file::Model, DateTimeWithTimeZone)>, u64), DbErr> {
+        let blobs = self.blobs.lock().unwrap();
+        let mut files: Vec<_> = self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|f| {
+                let blob = blobs.iter().find(|b| b.hash == f.checksum)?;
+                Some((f.clone(), blob.corrupt_at?))
+            })
+            .collect();
+        files.sort_by_key(|(f, _)| f.id);
+        let total = files.len() as u64;
+        let items = files
+            .into_iter()
+            .skip(((params.page - 1) * params.per_page) as usize)
+            .take(params.per_page as usize)
+            .collect();
+        Ok((items, total))
+    }
+
+    async fn relocate_blob(
+        &self,
+        hash: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Option<Vec<UnreferencedContent>>, DbErr> {
+        let mut files = self.files.lock().unwrap();
+        let mut blobs = self.blobs.lock().unwrap();
+        let Some(blob) = blobs
+            .iter_mut()
+            .find(|b| b.hash == hash && b.storage_key == from)
+        else {
+            return Ok(None);
+        };
+        blob.storage_key = to.to_string();
+
+        let mut copies = Vec::new();
+        for file in files.iter_mut().filter(|f| f.checksum == hash) {
+            copies.push((file.storage_key.clone(), file.storage_backend.clone()));
+            file.storage_key = to.to_string();
+            file.storage_backend = blob.storage_backend.clone();
+        }
+        Ok(Some(replaced_copies(
+            from,
+            to,
+            &blob.storage_backend,
+            copies,
+        )))
+    }
+
+    async fn referenced_storage_keys(
+        &self,
+        backend: &str,
+        keys: &[String],
+    ) -> Result<HashSet<String>, DbErr> {
+        let blobs = self.blobs.lock().unwrap();
+        let files = self.files.lock().unwrap();
+        let locations = blobs
+            .iter()
+            .map(|b| (&b.storage_backend, &b.storage_key))
+            .chain(files.iter().map(|f| (&f.storage_backend, &f.storage_key)));
+        Ok(locations
+            .filter(|(b, key)| *b == backend && keys.contains(key))
+            .map(|(_, key)| key.clone())
+            .collect())
+    }
+
+    async fn list_pending_scan(&self, limit: u64) -> Result<Vec<i64>, DbErr> {
+        let pending = ScanStatus::PendingScan.to_string();
+        let mut ids: Vec<_> = self
+            .files
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|f| f.scan_status == pending)
+            .map(|f| f.id)
+            .collect();
+        ids.sort_unstable();
+        ids.truncate(limit as usize);
+        Ok(ids)
+    }
+
+    async fn insert_session(
+        &self,
+        model: upload_session::Model,
+    ) -> Result<upload_session::Model, DbErr> {
+        self.sessions.lock().unwrap().push(model.clone());
+        Ok(model)
+    }
+
+    async fn find_session(&self, id: &str) -> Result<Option<upload_session::Model>, DbErr> {
+        Ok(self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|s| s.id == id)
+            .cloned())
+    }
+
+    async fn list_chunks(&self, session_id: &str) -> Result<Vec<upload_chunk::Model>, DbErr> {
+        let mut chunks: Vec<_> = self
+            .chunks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c.session_id == session_id)
+            .cloned()
+            .collect();
+        chunks.sort_by_key(|c| c.chunk_index);
+        Ok(chunks)
+    }
+
+    async fn save_chunk(
+        &self,
+        session_id: &str,
+        chunk_index: i32,
+        size_bytes: i64,
+    ) -> Result<(), DbErr> {
+        let mut chunks = self.chunks.lock().unwrap();
+        chunks.retain(|c| !(c.session_id == session_id && c.chunk_index == chunk_index));
+        chunks.push(upload_chunk::Model {
+            session_id: session_id.to_string(),
+            chunk_index,
+            size_bytes,
+            uploaded_at: chrono::Utc::now().fixed_offset(),
+        });
+        Ok(())
+    }
+
+    async fn delete_chunk(&self, session_id: &str, chunk_index: i32) -> Result<(), DbErr> {
+        self.chunks
+            .lock()
+            .unwrap()
+            .retain(|c| !(c.session_id == session_id && c.chunk_index == chunk_index));
+        Ok(())
+    }
+
+    async fn delete_session(&self, id: &str) -> Result<(), DbErr> {
+        self.chunks.lock().unwrap().retain(|c| c.session_id != id);
+        self.sessions.lock().unwrap().retain(|s| s.id != id);
+        Ok(())
+    }
+
+    async fn list_expired_sessions(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<upload_session::Model>, DbErr> {
+        Ok(self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|s| s.expires_at < now)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::user_id;
+    use migration::{Migrator, MigratorTrait};
+
+    /// 执行前 `steps` 个迁移（`None` 为全部）并创建用户 alice（ID 为 `user_id(1)`）
+    async fn migrated_db(steps: Option<u32>) -> DatabaseConnection {
+        // 内存 SQLite 每个连接是独立的数据库，限制为单连接才能看到迁移结果
+        let mut options = sea_orm::ConnectOptions::new("sqlite::memory:");
+        options.max_connections(1);
+        let db = sea_orm::Database::connect(options).await.unwrap();
+        Migrator::up(&db, steps).await.unwrap();
+        // 只迁移了一部分时用户表还没有后续新增的列，不能读回整行
+        user::Entity::insert(user::ActiveModel {
+            id: Set(user_id(1)),
+            username: Set("alice".to_string()),
+            email: Set("alice@example.com".to_string()),
+            password_hash: Set("not-used".to_string()),
+            ..Default::default()
+        })
+        .exec_without_returning(&db)
+        .await
+        .unwrap();
+        db
+    }
+
+    async fn migrated_repo() -> SeaOrmFileRepo {
+        SeaOrmFileRepo::new(migrated_db(None).await)
+    }
+
+    fn new_file(owner_id: UserId, size_bytes: i64) -> file::ActiveModel {
+        file::ActiveModel {
+            owner_id: Set(owner_id),
+            filename: Set("notes.txt".to_string()),
+            content_type: Set("text/plain".to_string()),
+            size_bytes: Set(size_bytes),
+            storage_key: Set(uuid::Uuid::new_v4().to_string()),
+            created_at: Set(chrono::Utc::now().fixed_offset()),
+            checksum: Set(String::new()),
+            storage_backend: Set("local".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// 内容摘要为 `checksum` 的文件，`storage_key` 为 `None` 时只复用已有内容
+    fn file_with_content(checksum: &str, storage_key: Option<&str>) -> file::ActiveModel {
+        let mut model = new_file(user_id(1), 10);
+        model.checksum = Set(checksum.to_string());
+        model.storage_key = storage_key.map_or(sea_orm::NotSet, |key| Set(key.to_string()));
+        model
+    }
+
+    fn inserted(outcome: InsertOutcome) -> file::Model {
+        match outcome {
+            InsertOutcome::Inserted(model) => *model,
+            other => panic!("应登记成功，实际为 {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_delete_keep_storage_usage_in_sync() {
+        let repo = migrated_repo().await;
+        let used = async || {
+            repo.find_storage_usage(user_id(1))
+                .await
+                .unwrap()
+                .unwrap()
+                .used_bytes
+        };
+        assert_eq!(used().await, 0);
+
+        let first = inserted(
+            repo.insert(new_file(user_id(1), 60), Some(100))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(used().await, 60);
+
+        // 超出配额时元数据和已用量都不变
+        assert!(matches!(
+            repo.insert(new_file(user_id(1), 41), Some(100))
+                .await
+                .unwrap(),
+            InsertOutcome::QuotaExceeded
+        ));
+        assert_eq!(used().await, 60);
+        let (_, total) = repo
+            .list_by_owner(user_id(1), &FileFilter::default(), PageParams::default())
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+
+        inserted(
+            repo.insert(new_file(user_id(1), 40), Some(100))
+                .await
+                .unwrap(),
+        );
+        inserted(repo.insert(new_file(user_id(1), 500), None).await.unwrap());
+        assert_eq!(used().await, 600);
+
+        repo.delete(first.id).await.unwrap();
+        repo.delete(first.id).await.unwrap();
+        assert_eq!(used().await, 540);
+        assert!(repo.find_storage_usage(user_id(2)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_by_owner_filters_type_and_upload_time() {
+        let repo = migrated_repo().await;
+        let start = chrono::Utc::now();
+        for (content_type, minutes) in [("image/png", 0), ("image/jpeg", 10), ("text/plain", 20)] {
+            let mut model = new_file(user_id(1), 1);
+            model.content_type = Set(content_type.to_string());
+            model.created_at = Set((start + chrono::Duration::minutes(minutes)).fixed_offset());
+            model.insert(&repo.db).await.unwrap();
+        }
+        let list = async |filter: FileFilter| {
+            let (files, total) = repo
+                .list_by_owner(user_id(1), &filter, PageParams::default())
+                .await
+                .unwrap();
+            assert_eq!(files.len() as u64, total);
+            files
+                .into_iter()
+                .map(|f| f.content_type)
+                .collect::<Vec<_>>()
+        };
+
+        let images = FileFilter {
+            content_type: Some("image/*".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(list(images.clone()).await, ["image/jpeg", "image/png"]);
+        let exact = FileFilter {
+            content_type: Some("text/plain".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(list(exact).await, ["text/plain"]);
+        // 起点包含、终点不包含
+        let window = FileFilter {
+            from: Some(start + chrono::Duration::minutes(10)),
+            to: Some(start + chrono::Duration::minutes(20)),
+            ..images
+        };
+        assert_eq!(list(window).await, ["image/jpeg"]);
+    }
+
+    #[tokio::test]
+    async fn test_files_with_same_content_share_one_blob() {
+        let repo = migrated_repo().await;
+        let first = inserted(
+            repo.insert(file_with_content("abc", Some("1/first")), None)
+                .await
+                .unwrap(),
+        );
+        let second = inserted(
+            repo.insert(file_with_content("abc", Some("1/second")), None)
+                .await
+                .unwrap(),
+        );
+        let third = inserted(
+            repo.insert(file_with_content("abc", None), None)
+                .await
+                .unwrap(),
+        );
+        assert_eq!(second.storage_key, "1/first");
+        assert_eq!(third.storage_key, "1/first");
+        assert_eq!(repo.find_blob("abc").await.unwrap().unwrap().ref_count, 3);
+        assert_eq!(repo.count_blob_refs("abc").await.unwrap(), 3);
+
+        // 只复用已有内容时，内容不存在则不登记，也不占用配额
+        assert!(matches!(
+            repo.insert(file_with_content("def", None), None)
+                .await
+                .unwrap(),
+            InsertOutcome::BlobMissing
+        ));
+        assert_eq!(
+            repo.find_storage_usage(user_id(1))
+                .await
+                .unwrap()
+                .unwrap()
+                .used_bytes,
+            30
+        );
+
+        // 扫描状态和对象位置按内容更新
+        repo.update_scan_status(first.id, "clean").await.unwrap();
+        repo.update_storage_key(first.id, "quarantine/1/first")
+            .await
+            .unwrap();
+        let third = repo.find_by_id(third.id).await.unwrap().unwrap();
+        assert_eq!(third.scan_status, "clean");
+        assert_eq!(third.storage_key, "quarantine/1/first");
+
+        // 缩略图按内容更新，之后登记的相同内容直接沿用
+        repo.update_thumbnails(first.id, "ready", &[64, 256])
+            .await
+            .unwrap();
+        let fourth = inserted(
+            repo.insert(file_with_content("abc", None), None)
+                .await
+                .unwrap(),
+        );
+        for model in [
+            repo.find_by_id(second.id).await.unwrap().unwrap(),
+            fourth.clone(),
+        ] {
+            assert_eq!(model.thumbnail_status.as_deref(), Some("ready"));
+            assert_eq!(model.thumbnail_sizes, serde_json::json!([64, 256]));
+        }
+
+        for id in [first.id, second.id, third.id, fourth.id] {
+            assert_eq!(repo.delete(id).await.unwrap(), None);
+        }
+        // 记录保留到回收任务删除对象之后
+        let blob = repo.find_blob("abc").await.unwrap().unwrap();
+        assert_eq!(blob.ref_count, 0);
+        assert_eq!(blob.storage_key, "quarantine/1/first");
+
+        // 早期文件不参与去重，删除时直接返回自己的内容
+        let legacy = inserted(repo.insert(new_file(user_id(1), 5), None).await.unwrap());
+        assert_eq!(
+            repo.delete(legacy.id).await.unwrap(),
+            Some(UnreferencedContent {
+                storage_key: legacy.storage_key,
+                storage_backend: "local".to_string(),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_blob_refs_is_optimistic() {
+        let repo = migrated_repo().await;
+        let file = inserted(
+            repo.insert(file_with_content("abc", Some("1/abc")), None)
+                .await
+                .unwrap(),
+        );
+        // 计数已变化时不修改
+        assert!(!repo.reconcile_blob_refs("abc", 5, 3).await.unwrap());
+        assert!(repo.reconcile_blob_refs("abc", 1, 4).await.unwrap());
+        assert_eq!(repo.find_blob("abc").await.unwrap().unwrap().ref_count, 4);
+
+        file::Entity::delete_by_id(file.id)
+            .exec(&repo.db)
+            .await
+            .unwrap();
+        assert_eq!(repo.count_blob_refs("abc").await.unwrap(), 0);
+        assert!(repo.reconcile_blob_refs("abc", 4, 0).await.unwrap());
+        assert!(repo.list_blobs(None, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_blobs_and_referenced_keys() {
+        let repo = migrated_repo().await;
+        let shared = inserted(
+            repo.insert(file_with_content("abc", Some("1/abc")), None)
+                .await
+                .unwrap(),
+        );
+        let copy = inserted(
+            repo.insert(file_with_content("abc", None), None)
+                .await
+                .unwrap(),
+        );
+        inserted(
+            repo.insert(file_with_content("def", Some("1/def")), None)
+                .await
+                .unwrap(),
+        );
+        let legacy = inserted(repo.insert(new_file(user_id(1), 10), None).await.unwrap());
+
+        // 已标记的内容保留最初的标记时间
+        let first = chrono::Utc::now().fixed_offset() - chrono::Duration::hours(1);
+        repo.set_blob_corrupt("abc", Some(first)).await.unwrap();
+        repo.set_blob_corrupt("abc", Some(chrono::Utc::now().fixed_offset()))
+            .await
+            .unwrap();
+        let (files, total) = repo
+            .list_corrupt_files(PageParams::default())
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+        let flagged: Vec<_> = files.iter().map(|(f, at)| (f.id, *at)).collect();
+        assert_eq!(flagged, [(shared.id, first), (copy.id, first)]);
+
+        repo.set_blob_corrupt("abc", None).await.unwrap();
+        let (_, total) = repo
+            .list_corrupt_files(PageParams::default())
+            .await
+            .unwrap();
+        assert_eq!(total, 0);
+
+        // 内容和早期文件的对象键都算被引用，后端不同则不算
+        let keys = ["1/abc", "1/def", legacy.storage_key.as_str(), "1/orphan"].map(String::from);
+        let mut referenced: Vec<_> = repo
+            .referenced_storage_keys("local", &keys)
+            .await
+            .unwrap()
+            .into_iter()
+            .collect();
+        referenced.sort();
+        let mut expected = keys[..3].to_vec();
+        expected.sort();
+        assert_eq!(referenced, expected);
+        assert!(
+            repo.referenced_storage_keys("s3", &keys)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blob_migration_backfills_existing_files() {
+        // 先停在 blob 表之前的迁移，模拟升级前的数据
+        let db = migrated_db(Some(11)).await;
+        let mut keys = Vec::new();
+        for (checksum, key) in [("abc", "1/a"), ("abc", "1/b"), ("", "1/legacy")] {
+            let mut model = new_file(user_id(1), 10);
+            model.checksum = Set(checksum.to_string());
+            model.storage_key = Set(key.to_string());
+            // 文件表还没有后续新增的列，不能读回整行
+            let inserted = file::Entity::insert(model).exec(&db).await.unwrap();
+            keys.push(inserted.last_insert_id);
+        }
+        Migrator::up(&db, None).await.unwrap();
+
+        let repo = SeaOrmFileRepo::new(db);
+        let blob = repo.find_blob("abc").await.unwrap().unwrap();
+        assert_eq!((blob.ref_count, blob.storage_key.as_str()), (2, "1/a"));
+        // 重复的副本仍由文件记录引用，等待迁移到内容的对象键后再删除
+        let second = repo.find_by_id(keys[1]).await.unwrap().unwrap();
+        assert_eq!(second.storage_key, "1/b");
+        let legacy = repo.find_by_id(keys[2]).await.unwrap().unwrap();
+        assert_eq!(legacy.storage_key, "1/legacy");
+        assert_eq!(repo.list_blobs(None, 10).await.unwrap().len(), 1);
+
+        let replaced: Vec<_> = repo
+            .relocate_blob("abc", "1/a", "blobs/abc")
+            .await
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.storage_key, c.storage_backend))
+            .collect();
+        assert_eq!(
+            replaced,
+            [("1/a", "local"), ("1/b", "local")].map(|(k, b)| (k.to_string(), b.to_string()))
+        );
+        assert_eq!(
+            repo.find_blob("abc").await.unwrap().unwrap().storage_key,
+            "blobs/abc"
+        );
+        for id in &keys[..2] {
+            let file = repo.find_by_id(*id).await.unwrap().unwrap();
+            assert_eq!(file.storage_key, "blobs/abc");
+        }
+        // 对象键已不是 `from` 时不再修改
+        assert!(
+            repo.relocate_blob("abc", "1/a", "blobs/other")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
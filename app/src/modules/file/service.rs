@@ -0,0 +1,3745 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use anyhow::Context;
+use axum::body::Bytes;
+use axum::extract::Multipart;
+use axum::extract::multipart::Field;
+use axum::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
+use futures_util::{Stream, StreamExt};
+use sea_orm::Set;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    core::config::{MIN_CHUNK_SIZE_BYTES, QuarantineAction, UploadConfig},
+    core::response::{PageParams, PaginatedResponse},
+    error::{AppError, AuthError, FileUploadError, ValidationError},
+    shared::{
+        FromState, OrNotFound,
+        scanner::{FileScanner, ScanVerdict},
+        storage::{AnyStorage, Storage, StorageBackend, Storages},
+    },
+};
+use entity::{
+    blob,
+    enums::{ScanStatus, ThumbnailStatus, UserRole},
+    file, upload_session,
+    user::UserId,
+};
+
+use super::download::{self, content_disposition};
+use super::dto::{
+    CorruptFileDto, CreateUploadSessionRequest, Disposition, DownloadUrlResponse, FileListQuery,
+    FileMetadataDto, FileResponse, StorageUsageResponse, UpdateFileRequest, UploadSessionResponse,
+    chunk_count, chunk_len,
+};
+use super::repo::{
+    FileFilter, FileRepo, InsertOutcome, SeaOrmFileRepo, StorageUsage, UnreferencedContent,
+};
+use super::sniff;
+use super::thumbnail::{self, thumbnail_key};
+
+/// 未声明 Content-Type 的文件字段按二进制流处理
+const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// 文件描述的最大长度（字符）
+const MAX_DESCRIPTION_CHARS: usize = 500;
+
+/// 合并分片时的读取缓冲大小（字节）
+const COPY_BUFFER_BYTES: usize = 64 * 1024;
+
+/// 会话不存在、不属于当前用户或已过期时统一返回的消息
+const SESSION_NOT_FOUND: &str = "上传会话不存在或已过期";
+
+/// 文件不存在时返回的消息
+const FILE_NOT_FOUND: &str = "文件不存在";
+
+/// 下载链接签名无效或已过期时返回的消息
+const INVALID_DOWNLOAD_LINK: &str = "下载链接无效或已过期";
+
+/// 被感染文件隔离后的对象键前缀
+const QUARANTINE_PREFIX: &str = "quarantine/";
+
+/// 用户不存在时返回的消息
+const USER_NOT_FOUND: &str = "用户不存在";
+
+/// 文件不生成缩略图（不是支持的图片类型或启用缩略图之前上传）时返回的消息
+const NO_THUMBNAIL: &str = "该文件没有缩略图";
+
+/// 完整性检查每批读取的内容记录数
+const BLOB_CHECK_BATCH_SIZE: u64 = 500;
+
+/// 孤立对象清理每批列出的对象数
+const ORPHAN_SCAN_BATCH_SIZE: usize = 500;
+
+/// 分片的对象键前缀，分片由上传会话记录，随过期会话一起清理，不参与孤立对象清理
+const CHUNK_KEY_PREFIX: &str = "chunks/";
+
+/// 内容的对象键前缀，内容保存在由 SHA-256 决定的 `blobs/{hash}` 下（见 [`blob_key`]）
+const BLOB_KEY_PREFIX: &str = "blobs/";
+
+/// 写入中的上传内容的对象键前缀，写完算出摘要后移动到 [`blob_key`]
+const TEMP_KEY_PREFIX: &str = "tmp/";
+
+/// 存储后端请求超时时返回给客户端的服务名称
+const STORAGE_SERVICE: &str = "存储服务";
+
+/// 存储后端请求超时（如 S3 无响应）转换为 504，其他错误交给 `otherwise` 转换
+fn storage_error(
+    e: std::io::Error,
+    otherwise: impl FnOnce(std::io::Error) -> AppError,
+) -> AppError {
+    if e.kind() == std::io::ErrorKind::TimedOut {
+        AppError::GatewayTimeout {
+            service: STORAGE_SERVICE.to_string(),
+        }
+    } else {
+        otherwise(e)
+    }
+}
+
+/// 写入存储失败
+fn write_failed(e: std::io::Error) -> AppError {
+    storage_error(e, |e| {
+        FileUploadError::Failed(format!("写入存储失败：{e}")).into()
+    })
+}
+
+/// 已写入存储后端、尚未登记元数据的文件
+#[derive(Debug)]
+struct StoredFile {
+    /// 写入的临时对象键，登记前移动到内容的对象键
+    temp_key: String,
+
+    /// 原始文件名（已去除路径部分）
+    filename: String,
+
+    /// MIME 类型
+    content_type: String,
+
+    /// 文件大小（字节）
+    size_bytes: u64,
+
+    /// 文件内容的 SHA-256 摘要（十六进制小写）
+    checksum: String,
+}
+
+/// 上传前查询到的存储配额
+#[derive(Debug, Clone, Copy)]
+struct Quota {
+    /// 已用空间（字节）
+    used: i64,
+
+    /// 生效的配额（字节），`None` 表示不限制
+    limit: Option<i64>,
+}
+
+impl Quota {
+    /// 再登记 `size` 字节后是否仍在配额内，超出时返回 FileUploadError::QuotaExceeded
+    fn check(&self, size: u64) -> Result<(), FileUploadError> {
+        match self.limit {
+            Some(limit) if self.used.saturating_add(size as i64) > limit => {
+                Err(FileUploadError::QuotaExceeded {
+                    used: self.used,
+                    limit,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 一次内容完整性检查的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlobIntegrityReport {
+    /// 检查的内容记录数
+    pub checked: u64,
+
+    /// 修正了引用计数的内容数
+    pub fixed: u64,
+
+    /// 不再被引用、已删除的内容数
+    pub removed: u64,
+
+    /// 存储后端中找不到对象的内容数，这些内容会被标记为损坏
+    pub missing: u64,
+
+    /// 之前标记为损坏、本轮重新找到对象而清除标记的内容数
+    pub recovered: u64,
+}
+
+/// 一次孤立对象清理的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrphanSweepReport {
+    /// 列出的对象数（所有后端合计）
+    pub scanned: u64,
+
+    /// 没有元数据引用、超过宽限期而删除的对象数
+    pub deleted: u64,
+}
+
+/// 一次旧内容迁移的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlobRelocationReport {
+    /// 移动到由摘要决定的对象键的内容数
+    pub relocated: u64,
+
+    /// 删除的旧副本数（原对象键和文件各自保存的重复副本）
+    pub copies_deleted: u64,
+}
+
+/// 读取到的缩略图
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    /// 实际返回的边长（像素）
+    pub size: u32,
+
+    /// PNG 内容
+    pub content: Vec<u8>,
+}
+
+/// 文件服务
+///
+/// 处理文件上传、下载、删除、元数据查询及访问控制。新文件（包括分片）写入默认存储后端，
+/// 已登记的文件按元数据中的 `storage_backend` 访问。
+pub struct FileService<R = SeaOrmFileRepo, S = AnyStorage> {
+    repo: R,
+    storages: Storages<S>,
+    upload: UploadConfig,
+}
+
+impl FromState for FileService<SeaOrmFileRepo, AnyStorage> {
+    fn from_state(app: &AppState) -> Self {
+        Self::new(
+            SeaOrmFileRepo::from_state(app),
+            app.storage.clone(),
+            app.upload.clone(),
+        )
+    }
+}
+
+impl<R: FileRepo, S: Storage> FileService<R, S> {
+    /// 使用指定的数据访问实现、存储后端集合和上传限制创建文件服务
+    pub fn new(repo: R, storages: Storages<S>, upload: UploadConfig) -> Self {
+        Self {
+            repo,
+            storages,
+            upload,
+        }
+    }
+
+    /// 接收并保存上传的文件
+    ///
+    /// 读取 multipart 表单中的 `file` 和 `description` 字段，字段顺序不限：
+    /// `description` 可以出现在 `file` 之前或之后，非文件字段先暂存，读完整个请求后再与文件关联，
+    /// 其他字段会被忽略。
+    ///
+    /// 文件内容逐块写入存储后端的临时对象，同时累计大小并计算 SHA-256，内存占用只与块大小有关，
+    /// 与文件大小无关；读完整个表单后移动到由摘要决定的 `blobs/{hash}`，相同内容只保存一份。MIME 类型
+    /// 按文件头识别的真实类型校验，确认之前不写入存储；累计大小超过 `upload.max_file_size_bytes` 或剩余的
+    /// 存储配额时立即中止写入。移动之前的失败都会删除临时对象；移动之后的失败（如元数据写入失败）
+    /// 不删除内容对象，它可能已被并发上传的相同内容引用，没有引用时由孤立对象清理删除。
+    ///
+    /// # 参数
+    /// * `owner_id` - 文件所有者（当前登录用户）ID
+    /// * `multipart` - multipart 表单
+    /// * `public_url` - 对外访问的基础 URL，用于拼接下载链接
+    ///
+    /// # 返回
+    /// 成功返回文件信息（含 SHA-256 摘要）
+    /// 读完整个请求仍没有 `file` 字段时返回 FileUploadError::MissingField，
+    /// 超出大小返回 FileUploadError::TooLarge，类型不允许返回 FileUploadError::TypeNotAllowed，
+    /// 声明类型与内容不符返回 FileUploadError::TypeMismatch，
+    /// 超出存储配额返回 FileUploadError::QuotaExceeded，存储写入失败返回 FileUploadError::Failed
+    #[instrument(skip(self, multipart))]
+    pub async fn upload(
+        &self,
+        owner_id: UserId,
+        multipart: Multipart,
+        public_url: &str,
+    ) -> Result<FileResponse, AppError> {
+        let quota = self.quota(owner_id).await?;
+        quota.check(0)?;
+
+        let mut stored = None;
+        let result = async {
+            let description = self.receive(quota, multipart, &mut stored).await?;
+            let file = stored
+                .take()
+                .ok_or_else(|| FileUploadError::MissingField("file".to_string()))?;
+            let storage_key = self.commit_blob(&file.temp_key, &file.checksum).await?;
+            let thumbnail_status = self.initial_thumbnail_status(&file.content_type);
+            let model = file::ActiveModel {
+                owner_id: Set(owner_id),
+                filename: Set(file.filename.clone()),
+                content_type: Set(file.content_type.clone()),
+                size_bytes: Set(file.size_bytes as i64),
+                storage_key: Set(storage_key),
+                checksum: Set(file.checksum.clone()),
+                description: Set(description),
+                storage_backend: Set(self.storages.default_backend().to_string()),
+                scan_status: Set(ScanStatus::PendingScan.to_string()),
+                thumbnail_status: Set(thumbnail_status),
+                ..Default::default()
+            };
+            self.register(owner_id, model, quota)
+                .await?
+                .context("登记文件元数据失败：内容记录不存在")
+                .map_err(AppError::from)
+        }
+        .await
+        .map_err(|e| self.body_limit_error(e));
+        // 读完表单之前失败（如描述不合法）时文件仍在临时对象中
+        if let Some(file) = stored {
+            self.discard(&file.temp_key).await;
+        }
+
+        let model = result?;
+        info!(file_id = model.id, size = model.size_bytes, "文件上传成功");
+        Ok(FileResponse::from_model(model, public_url))
+    }
+
+    /// 请求体在读取中途超出路由的 `DefaultBodyLimit`（如不带 `Content-Length` 的分块请求）时，
+    /// multipart 解析错误转换为说明文件大小上限的 FileUploadError::TooLarge
+    fn body_limit_error(&self, e: AppError) -> AppError {
+        match e {
+            AppError::FileUpload(FileUploadError::Multipart(ref m))
+                if m.status() == StatusCode::PAYLOAD_TOO_LARGE =>
+            {
+                FileUploadError::TooLarge(self.upload.max_file_size_bytes).into()
+            }
+            e => e,
+        }
+    }
+
+    /// 读取整个表单，文件写入存储后放入 `stored`，返回暂存的描述
+    async fn receive(
+        &self,
+        quota: Quota,
+        mut multipart: Multipart,
+        stored: &mut Option<StoredFile>,
+    ) -> Result<Option<String>, AppError> {
+        let mut description = None;
+
+        while let Some(field) = multipart.next_field().await? {
+            match field.name() {
+                Some("file") => {
+                    if stored.is_some() {
+                        return Err(ValidationError::custom("每次只能上传一个文件").into());
+                    }
+                    *stored = Some(self.store(quota, field).await?);
+                }
+                Some("description") => {
+                    let text = field.text().await?;
+                    let text = text.trim();
+                    if text.chars().count() > MAX_DESCRIPTION_CHARS {
+                        return Err(ValidationError::custom(format!(
+                            "文件描述不能超过 {MAX_DESCRIPTION_CHARS} 个字符"
+                        ))
+                        .into());
+                    }
+                    description = Some(text.to_string()).filter(|t| !t.is_empty());
+                }
+                _ => {}
+            }
+        }
+
+        Ok(description)
+    }
+
+    /// 将文件字段逐块写入临时对象，写入失败时删除临时对象
+    ///
+    /// 先读取文件头并通过 [`Self::resolve_content_type`] 确定真实类型，类型不符或不允许时不会写入存储。
+    async fn store(&self, quota: Quota, mut field: Field<'_>) -> Result<StoredFile, AppError> {
+        let claimed = normalize_mime(field.content_type().unwrap_or(DEFAULT_CONTENT_TYPE));
+        if !self.upload.allows(&claimed) {
+            return Err(FileUploadError::TypeNotAllowed(claimed).into());
+        }
+        let filename = sanitize_filename(field.file_name());
+        let limit = self.upload.max_file_size_bytes as u64;
+
+        let mut head = Vec::new();
+        while head.len() < sniff::SNIFF_BYTES {
+            let Some(chunk) = field.chunk().await? else {
+                break;
+            };
+            if (head.len() + chunk.len()) as u64 > limit {
+                return Err(FileUploadError::TooLarge(limit as usize).into());
+            }
+            quota.check((head.len() + chunk.len()) as u64)?;
+            head.extend_from_slice(&chunk);
+        }
+        let content_type = self.resolve_content_type(claimed, &head)?;
+
+        let temp_key = temp_key();
+        let mut writer = self
+            .storages
+            .primary()
+            .create(&temp_key)
+            .await
+            .map_err(write_failed)?;
+        let result = async {
+            let mut hasher = Sha256::new();
+            let mut size = head.len() as u64;
+            hasher.update(&head);
+            writer.write_all(&head).await.map_err(write_failed)?;
+            while let Some(chunk) = field.chunk().await? {
+                size += chunk.len() as u64;
+                if size > limit {
+                    return Err(FileUploadError::TooLarge(limit as usize).into());
+                }
+                quota.check(size)?;
+                hasher.update(&chunk);
+                writer.write_all(&chunk).await.map_err(write_failed)?;
+            }
+            writer.shutdown().await.map_err(write_failed)?;
+            Ok::<_, AppError>((size, format!("{:x}", hasher.finalize())))
+        }
+        .await;
+        drop(writer);
+
+        match result {
+            Ok((size_bytes, checksum)) => Ok(StoredFile {
+                temp_key,
+                filename,
+                content_type,
+                size_bytes,
+                checksum,
+            }),
+            Err(e) => {
+                self.discard(&temp_key).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// 将写完的临时对象移动到内容的对象键 `blobs/{checksum}` 并返回该键，移动失败时删除临时对象
+    ///
+    /// 相同内容的对象键相同，目标已存在时直接覆盖（内容一致）。移动后的对象不再由上传流程删除：
+    /// 并发上传的相同内容可能已经登记了它，没有引用的对象由孤立对象清理删除。
+    async fn commit_blob(&self, temp_key: &str, checksum: &str) -> Result<String, AppError> {
+        let storage_key = blob_key(checksum);
+        if let Err(e) = self.storages.primary().rename(temp_key, &storage_key).await {
+            self.discard(temp_key).await;
+            return Err(write_failed(e));
+        }
+        Ok(storage_key)
+    }
+
+    /// 根据文件头确定登记的 MIME 类型
+    ///
+    /// 声明的类型与识别结果不符时返回 FileUploadError::TypeMismatch，
+    /// 识别出的类型不允许时返回 FileUploadError::TypeNotAllowed。
+    /// 纯文本无法进一步区分，保留声明的 `text/*` 类型，其他情况取识别结果。
+    fn resolve_content_type(&self, claimed: String, head: &[u8]) -> Result<String, AppError> {
+        let detected = sniff::detect(head);
+        if !sniff::is_consistent(&claimed, detected) {
+            return Err(FileUploadError::TypeMismatch {
+                claimed,
+                detected: detected.to_string(),
+            }
+            .into());
+        }
+        if !self.upload.allows(detected) {
+            return Err(FileUploadError::TypeNotAllowed(detected.to_string()).into());
+        }
+        Ok(if detected == sniff::PLAIN_TEXT {
+            claimed
+        } else {
+            detected.to_string()
+        })
+    }
+
+    /// 新文件的缩略图状态：支持的图片类型且配置了缩略图尺寸时为 `pending`，否则不生成（`None`）
+    fn initial_thumbnail_status(&self, content_type: &str) -> Option<String> {
+        (!self.upload.thumbnail_sizes.is_empty() && thumbnail::supports(content_type))
+            .then(|| ThumbnailStatus::Pending.to_string())
+    }
+
+    /// 删除未登记的文件内容，失败只记录日志
+    async fn discard(&self, storage_key: &str) {
+        if let Err(e) = self.storages.primary().delete(storage_key).await {
+            warn!(storage_key, error = %e, "清理未登记的上传文件失败");
+        }
+    }
+
+    /// 查询用户的已用空间和生效的配额，用户不存在返回 AppError::NotFound
+    async fn quota(&self, user_id: UserId) -> Result<Quota, AppError> {
+        let usage = self
+            .repo
+            .find_storage_usage(user_id)
+            .await
+            .or_not_found(USER_NOT_FOUND)?;
+        Ok(Quota {
+            used: usage.used_bytes,
+            limit: self.effective_limit(&usage),
+        })
+    }
+
+    /// 用户单独设置的配额优先，未设置时使用 `upload.default_storage_quota_bytes`（0 表示不限制）
+    fn effective_limit(&self, usage: &StorageUsage) -> Option<i64> {
+        let default = self.upload.default_storage_quota_bytes as i64;
+        usage.quota_bytes.or((default > 0).then_some(default))
+    }
+
+    /// 登记文件元数据并计入已用空间
+    ///
+    /// 相同内容已存在时返回的记录指向已有内容的位置（可能是尚未迁移的旧对象键或其他存储后端）。
+    /// 未提供 `storage_key`（只复用已有内容）而内容已不存在时返回 `None`。
+    /// 写入期间其他上传占用了剩余配额时返回 FileUploadError::QuotaExceeded（附带最新的用量）。
+    async fn register(
+        &self,
+        owner_id: UserId,
+        model: file::ActiveModel,
+        quota: Quota,
+    ) -> Result<Option<file::Model>, AppError> {
+        match self
+            .repo
+            .insert(model, quota.limit)
+            .await
+            .context("登记文件元数据失败")?
+        {
+            InsertOutcome::Inserted(model) => return Ok(Some(*model)),
+            InsertOutcome::BlobMissing => return Ok(None),
+            InsertOutcome::QuotaExceeded => {}
+        }
+        let latest = self.quota(owner_id).await?;
+        Err(FileUploadError::QuotaExceeded {
+            used: latest.used,
+            limit: latest.limit.or(quota.limit).unwrap_or_default(),
+        }
+        .into())
+    }
+
+    /// 查询当前用户的存储空间用量
+    ///
+    /// # 返回
+    /// 成功返回已用空间、生效的配额和剩余空间（不限制时配额和剩余空间为空），
+    /// 用户不存在返回 AppError::NotFound
+    #[instrument(skip(self))]
+    pub async fn storage_usage(&self, user_id: UserId) -> Result<StorageUsageResponse, AppError> {
+        let quota = self.quota(user_id).await?;
+        Ok(StorageUsageResponse {
+            used_bytes: quota.used,
+            quota_bytes: quota.limit,
+            remaining_bytes: quota.limit.map(|limit| (limit - quota.used).max(0)),
+        })
+    }
+
+    /// 创建分片上传会话
+    ///
+    /// 声明的类型、文件大小和分片大小在此校验，内容类型在完成时按文件头再次校验。
+    ///
+    /// # 参数
+    /// * `owner_id` - 文件所有者（当前登录用户）ID
+    /// * `req` - 文件名、类型、大小、分片大小和 SHA-256 摘要
+    ///
+    /// # 返回
+    /// 成功返回会话状态（尚无已接收的分片）
+    /// 类型不允许返回 FileUploadError::TypeNotAllowed，超出大小返回 FileUploadError::TooLarge，
+    /// 超出存储配额返回 FileUploadError::QuotaExceeded，分片大小不在允许范围内返回 ValidationError
+    #[instrument(skip(self, req))]
+    pub async fn create_session(
+        &self,
+        owner_id: UserId,
+        req: CreateUploadSessionRequest,
+    ) -> Result<UploadSessionResponse, AppError> {
+        let content_type = normalize_mime(&req.content_type);
+        if !self.upload.allows(&content_type) {
+            return Err(FileUploadError::TypeNotAllowed(content_type).into());
+        }
+        let limit = self.upload.max_file_size_bytes;
+        if req.size_bytes > limit as u64 {
+            return Err(FileUploadError::TooLarge(limit).into());
+        }
+        self.quota(owner_id).await?.check(req.size_bytes)?;
+        let max_chunk = self.upload.max_chunk_size_bytes as u64;
+        if !(MIN_CHUNK_SIZE_BYTES as u64..=max_chunk).contains(&req.chunk_size) {
+            return Err(ValidationError::custom(format!(
+                "分片大小必须在 {MIN_CHUNK_SIZE_BYTES} 到 {max_chunk} 字节之间"
+            ))
+            .into());
+        }
+
+        let now = Utc::now().fixed_offset();
+        let session = upload_session::Model {
+            id: Uuid::new_v4().to_string(),
+            owner_id,
+            filename: sanitize_filename(Some(&req.filename)),
+            content_type,
+            description: req
+                .description
+                .map(|d| d.trim().to_string())
+                .filter(|d| !d.is_empty()),
+            size_bytes: req.size_bytes as i64,
+            chunk_size: req.chunk_size as i64,
+            checksum: req.checksum.to_ascii_lowercase(),
+            expires_at: now + Duration::seconds(self.upload.session_ttl_secs as i64),
+            created_at: now,
+        };
+        let session = self
+            .repo
+            .insert_session(session)
+            .await
+            .context("创建上传会话失败")?;
+        info!(session_id = %session.id, size = session.size_bytes, "创建分片上传会话");
+        Ok(UploadSessionResponse::from_model(session, &[]))
+    }
+
+    /// 查询分片上传会话状态，用于断点续传时确定需要重新上传的分片
+    ///
+    /// # 返回
+    /// 成功返回会话状态，会话不存在、不属于当前用户或已过期返回 AppError::NotFound
+    #[instrument(skip(self))]
+    pub async fn get_session(
+        &self,
+        owner_id: UserId,
+        session_id: &str,
+    ) -> Result<UploadSessionResponse, AppError> {
+        let session = self.active_session(owner_id, session_id).await?;
+        let chunks = self
+            .repo
+            .list_chunks(session_id)
+            .await
+            .context("查询已接收的分片失败")?;
+        Ok(UploadSessionResponse::from_model(session, &chunks))
+    }
+
+    /// 接收单个分片并写入存储后端
+    ///
+    /// 同一序号重复上传时覆盖之前的内容，客户端可以放心重试。分片大小必须与会话约定一致
+    /// （最后一个分片为剩余大小），失败时删除该分片的内容和记录。
+    ///
+    /// # 参数
+    /// * `owner_id` - 当前登录用户ID
+    /// * `session_id` - 会话ID
+    /// * `index` - 分片序号，从 0 开始
+    /// * `body` - 分片内容
+    ///
+    /// # 返回
+    /// 成功返回会话状态，会话无效返回 AppError::NotFound，序号或大小不符返回 ValidationError，
+    /// 超出约定大小返回 FileUploadError::TooLarge
+    #[instrument(skip(self, body))]
+    pub async fn put_chunk<B, E>(
+        &self,
+        owner_id: UserId,
+        session_id: &str,
+        index: i64,
+        body: B,
+    ) -> Result<UploadSessionResponse, AppError>
+    where
+        B: Stream<Item = Result<Bytes, E>>,
+        E: std::fmt::Display,
+    {
+        let session = self.active_session(owner_id, session_id).await?;
+        let count = chunk_count(&session);
+        if !(0..count).contains(&index) {
+            return Err(
+                ValidationError::custom(format!("分片序号必须在 0 到 {} 之间", count - 1)).into(),
+            );
+        }
+        let expected = chunk_len(&session, index);
+        let key = chunk_key(session_id, index);
+
+        let mut writer = self
+            .storages
+            .primary()
+            .create(&key)
+            .await
+            .map_err(write_failed)?;
+        let result = async {
+            futures_util::pin_mut!(body);
+            let mut size = 0i64;
+            while let Some(chunk) = body.next().await {
+                let chunk =
+                    chunk.map_err(|e| ValidationError::custom(format!("读取分片内容失败：{e}")))?;
+                size += chunk.len() as i64;
+                if size > expected {
+                    return Err(FileUploadError::TooLarge(expected as usize).into());
+                }
+                writer.write_all(&chunk).await.map_err(write_failed)?;
+            }
+            if size != expected {
+                return Err(ValidationError::custom(format!(
+                    "分片 {index} 应为 {expected} 字节，实际收到 {size} 字节"
+                ))
+                .into());
+            }
+            writer.shutdown().await.map_err(write_failed)?;
+            Ok::<_, AppError>(())
+        }
+        .await;
+
+        // 覆盖写入失败时旧内容也已不完整，一并删除记录，完成时会提示重新上传
+        if let Err(e) = result {
+            drop(writer);
+            self.discard(&key).await;
+            if let Err(e) = self.repo.delete_chunk(session_id, index as i32).await {
+                warn!(session_id, index, error = %e, "删除分片记录失败");
+            }
+            return Err(e);
+        }
+
+        self.repo
+            .save_chunk(session_id, index as i32, expected)
+            .await
+            .context("记录已接收的分片失败")?;
+        self.get_session(owner_id, session_id).await
+    }
+
+    /// 合并全部分片为最终文件并登记元数据
+    ///
+    /// 依次校验：所有分片均已上传且大小正确、文件头识别出的类型与声明一致、合并后的 SHA-256
+    /// 与创建会话时声明的一致。校验通过后删除分片和会话；校验和不符时保留分片，
+    /// 客户端可以重新上传有问题的分片后再次完成。
+    ///
+    /// # 参数
+    /// * `owner_id` - 当前登录用户ID
+    /// * `session_id` - 会话ID
+    /// * `public_url` - 对外访问的基础 URL，用于拼接下载链接
+    ///
+    /// # 返回
+    /// 成功返回文件信息，会话无效返回 AppError::NotFound，分片不完整或校验和不符返回 ValidationError，
+    /// 类型不符返回 FileUploadError::TypeMismatch，超出存储配额返回 FileUploadError::QuotaExceeded
+    #[instrument(skip(self))]
+    pub async fn complete_session(
+        &self,
+        owner_id: UserId,
+        session_id: &str,
+        public_url: &str,
+    ) -> Result<FileResponse, AppError> {
+        let session = self.active_session(owner_id, session_id).await?;
+        let count = chunk_count(&session);
+        let received: HashMap<i64, i64> = self
+            .repo
+            .list_chunks(session_id)
+            .await
+            .context("查询已接收的分片失败")?
+            .into_iter()
+            .map(|c| (c.chunk_index as i64, c.size_bytes))
+            .collect();
+        let missing: Vec<String> = (0..count)
+            .filter(|&i| received.get(&i) != Some(&chunk_len(&session, i)))
+            .map(|i| i.to_string())
+            .collect();
+        if !missing.is_empty() {
+            return Err(ValidationError::custom(format!(
+                "分片未上传完整，缺少：{}",
+                missing.join(", ")
+            ))
+            .into());
+        }
+
+        // 分片不小于 MIN_CHUNK_SIZE_BYTES，识别类型所需的文件头都在第一个分片中
+        let read_failed = |e: std::io::Error| FileUploadError::Failed(format!("读取分片失败：{e}"));
+        let mut head = Vec::with_capacity(sniff::SNIFF_BYTES);
+        self.storages
+            .primary()
+            .open(&chunk_key(session_id, 0))
+            .await
+            .map_err(read_failed)?
+            .take(sniff::SNIFF_BYTES as u64)
+            .read_to_end(&mut head)
+            .await
+            .map_err(read_failed)?;
+        let content_type = self.resolve_content_type(session.content_type.clone(), &head)?;
+        // 创建会话之后可能已上传了其他文件，合并前再检查一次，登记时在事务中最终确认
+        let quota = self.quota(owner_id).await?;
+        quota.check(session.size_bytes as u64)?;
+
+        let mut model = file::ActiveModel {
+            owner_id: Set(owner_id),
+            filename: Set(session.filename.clone()),
+            thumbnail_status: Set(self.initial_thumbnail_status(&content_type)),
+            content_type: Set(content_type),
+            size_bytes: Set(session.size_bytes),
+            checksum: Set(session.checksum.clone()),
+            description: Set(session.description.clone()),
+            scan_status: Set(ScanStatus::PendingScan.to_string()),
+            ..Default::default()
+        };
+
+        // 相同内容已保存时只校验分片，不再合并写入；校验期间内容被删除则回到合并流程
+        let existing = self
+            .repo
+            .find_blob(&session.checksum)
+            .await
+            .context("查询已保存的文件内容失败")?;
+        if existing.is_some() {
+            self.merge_chunks(&session, None).await?;
+            if let Some(file) = self.register(owner_id, model.clone(), quota).await? {
+                self.drop_session(&session).await;
+                info!(
+                    file_id = file.id,
+                    session_id,
+                    size = file.size_bytes,
+                    "分片上传完成，复用已保存的内容"
+                );
+                return Ok(FileResponse::from_model(file, public_url));
+            }
+        }
+
+        let temp_key = temp_key();
+        let mut writer = self
+            .storages
+            .primary()
+            .create(&temp_key)
+            .await
+            .map_err(write_failed)?;
+        let merged = async {
+            self.merge_chunks(&session, Some(&mut writer)).await?;
+            writer.shutdown().await.map_err(write_failed)
+        }
+        .await;
+        drop(writer);
+        if let Err(e) = merged {
+            self.discard(&temp_key).await;
+            return Err(e);
+        }
+
+        model.storage_key = Set(self.commit_blob(&temp_key, &session.checksum).await?);
+        model.storage_backend = Set(self.storages.default_backend().to_string());
+        let model = self
+            .register(owner_id, model, quota)
+            .await?
+            .context("登记文件元数据失败：内容记录不存在")?;
+        self.drop_session(&session).await;
+        info!(
+            file_id = model.id,
+            session_id,
+            size = model.size_bytes,
+            "分片上传完成"
+        );
+        Ok(FileResponse::from_model(model, public_url))
+    }
+
+    /// 按序读取会话的全部分片并校验 SHA-256，提供 `writer` 时同时写入合并后的内容
+    ///
+    /// 校验和与会话声明的不一致时返回 ValidationError。
+    async fn merge_chunks(
+        &self,
+        session: &upload_session::Model,
+        mut writer: Option<&mut S::Writer>,
+    ) -> Result<(), AppError> {
+        let read_failed = |e: std::io::Error| FileUploadError::Failed(format!("读取分片失败：{e}"));
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0; COPY_BUFFER_BYTES];
+        for index in 0..chunk_count(session) {
+            let mut reader = self
+                .storages
+                .primary()
+                .open(&chunk_key(&session.id, index))
+                .await
+                .map_err(read_failed)?;
+            loop {
+                let n = reader.read(&mut buf).await.map_err(read_failed)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buf[..n]);
+                if let Some(writer) = writer.as_mut() {
+                    writer.write_all(&buf[..n]).await.map_err(write_failed)?;
+                }
+            }
+        }
+        let checksum = format!("{:x}", hasher.finalize());
+        if checksum != session.checksum {
+            return Err(ValidationError::custom(format!(
+                "文件校验和不匹配：声明为 {}，实际为 {checksum}",
+                session.checksum
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// 删除已过期的上传会话及其分片，返回清理的会话数
+    #[instrument(skip(self))]
+    pub async fn purge_expired_sessions(&self) -> Result<usize, AppError> {
+        let expired = self
+            .repo
+            .list_expired_sessions(Utc::now().fixed_offset())
+            .await
+            .context("查询过期的上传会话失败")?;
+        for session in &expired {
+            self.drop_session(session).await;
+        }
+        Ok(expired.len())
+    }
+
+    /// 查询属于当前用户且未过期的会话
+    ///
+    /// 不区分「不存在」「属于其他用户」和「已过期」，避免泄露会话 ID 是否有效。
+    async fn active_session(
+        &self,
+        owner_id: UserId,
+        session_id: &str,
+    ) -> Result<upload_session::Model, AppError> {
+        let session = self
+            .repo
+            .find_session(session_id)
+            .await
+            .or_not_found(SESSION_NOT_FOUND)?;
+        if session.owner_id != owner_id || session.expires_at <= Utc::now().fixed_offset() {
+            return Err(AppError::from_status(
+                StatusCode::NOT_FOUND,
+                SESSION_NOT_FOUND,
+            ));
+        }
+        Ok(session)
+    }
+
+    /// 删除会话的全部分片内容和记录，失败只记录日志，残留的会话由清理任务再次处理
+    async fn drop_session(&self, session: &upload_session::Model) {
+        for index in 0..chunk_count(session) {
+            self.discard(&chunk_key(&session.id, index)).await;
+        }
+        if let Err(e) = self.repo.delete_session(&session.id).await {
+            warn!(session_id = %session.id, error = %e, "删除上传会话失败");
+        }
+    }
+
+    /// 查询可以下载的文件
+    ///
+    /// 文件所有者和管理员可以下载，内容通过 [`FileService::open_content`] 读取。
+    ///
+    /// # 返回
+    /// 成功返回文件元数据
+    /// 文件不存在返回 AppError::NotFound，无权访问返回 AuthError::PermissionDenied，
+    /// 未通过恶意软件扫描返回 FileUploadError::NotClean
+    #[instrument(skip(self))]
+    pub async fn downloadable_file(
+        &self,
+        current_user_id: UserId,
+        file_id: i64,
+    ) -> Result<file::Model, AppError> {
+        let file = self.accessible_file(current_user_id, file_id).await?;
+        ensure_clean(&file)?;
+        Ok(file)
+    }
+
+    /// 凭签名下载链接查询文件
+    ///
+    /// 签名有效且未过期时不检查访问者身份，内容通过 [`FileService::open_content`] 读取。
+    ///
+    /// # 参数
+    /// * `expires`、`sig` - 下载链接中的过期时间（Unix 秒）和签名
+    /// * `secret` - 签名密钥（`JWT_SECRET`）
+    ///
+    /// # 返回
+    /// 成功返回文件元数据
+    /// 签名无效或已过期返回 403，文件不存在返回 AppError::NotFound，
+    /// 未通过恶意软件扫描返回 FileUploadError::NotClean
+    #[instrument(skip(self, sig, secret))]
+    pub async fn signed_file(
+        &self,
+        file_id: i64,
+        expires: i64,
+        sig: &str,
+        secret: &[u8],
+    ) -> Result<file::Model, AppError> {
+        if !download::verify(secret, file_id, expires, sig, Utc::now().timestamp()) {
+            warn!(file_id, expires, "下载链接签名无效或已过期");
+            return Err(AppError::from_status(
+                StatusCode::FORBIDDEN,
+                INVALID_DOWNLOAD_LINK,
+            ));
+        }
+        let file = self
+            .repo
+            .find_by_id(file_id)
+            .await
+            .or_not_found(FILE_NOT_FOUND)?;
+        ensure_clean(&file)?;
+        Ok(file)
+    }
+
+    /// 从记录的存储后端打开文件内容，`range` 为要读取的字节区间（已按文件大小校验），`None` 时读取全部
+    pub async fn open_content(
+        &self,
+        file: &file::Model,
+        range: Option<Range<u64>>,
+    ) -> Result<S::Reader, AppError> {
+        let (_, storage) = self.storage_of(file)?;
+        let reader = match range {
+            Some(range) => storage.open_range(&file.storage_key, range).await,
+            None => storage.open(&file.storage_key).await,
+        };
+        reader.map_err(|e| {
+            storage_error(e, |e| {
+                anyhow::Error::new(e)
+                    .context(format!("读取文件 {} 的内容失败", file.id))
+                    .into()
+            })
+        })
+    }
+
+    /// 下载 `file` 时使用的 `Content-Disposition`
+    ///
+    /// 只有 `upload.inline_mime_types` 中的类型才按请求在浏览器中直接显示，其余类型（如用户上传的 HTML）
+    /// 始终作为附件，避免上传的内容在本站域名下被浏览器渲染、执行脚本。
+    pub fn content_disposition(&self, file: &file::Model, requested: Disposition) -> String {
+        let disposition = match requested {
+            Disposition::Inline if self.upload.allows_inline(&file.content_type) => {
+                Disposition::Inline
+            }
+            _ => Disposition::Attachment,
+        };
+        content_disposition(&file.filename, disposition)
+    }
+
+    /// 生成有时效的下载地址
+    ///
+    /// 文件所有者和管理员可以获取。文件所在的存储后端支持预签名时（S3）返回后端的预签名地址，
+    /// 客户端直接从后端下载；否则返回应用签名的 `/v1/files/{id}/download` 地址，过期前免登录下载。
+    ///
+    /// # 参数
+    /// * `ttl_secs` - 有效期（秒），未指定时使用 `upload.download_url_ttl_secs`
+    /// * `disposition` - 通过该地址下载时浏览器的处理方式，规则同 [`FileService::content_disposition`]
+    /// * `secret` - 应用签名的密钥（`JWT_SECRET`）
+    /// * `public_url` - 对外访问的基础 URL，用于拼接应用签名的地址
+    ///
+    /// # 返回
+    /// 成功返回下载地址和过期时间
+    /// 有效期为 0 或超过 `upload.max_download_url_ttl_secs` 返回 ValidationError，
+    /// 文件不存在返回 AppError::NotFound，无权访问返回 AuthError::PermissionDenied，
+    /// 未通过恶意软件扫描返回 FileUploadError::NotClean
+    #[instrument(skip(self, secret))]
+    pub async fn download_url(
+        &self,
+        current_user_id: UserId,
+        file_id: i64,
+        ttl_secs: Option<u64>,
+        disposition: Disposition,
+        secret: &[u8],
+        public_url: &str,
+    ) -> Result<DownloadUrlResponse, AppError> {
+        let max_ttl = self.upload.max_download_url_ttl_secs;
+        let ttl = ttl_secs.unwrap_or(self.upload.download_url_ttl_secs);
+        if ttl == 0 || ttl > max_ttl {
+            return Err(
+                ValidationError::custom(format!("ttl_secs 必须在 1 到 {max_ttl} 之间")).into(),
+            );
+        }
+
+        let file = self.accessible_file(current_user_id, file_id).await?;
+        ensure_clean(&file)?;
+        let (_, storage) = self.storage_of(&file)?;
+        let expires = Utc::now().timestamp() + ttl as i64;
+        let url = storage
+            .presign_download(
+                &file.storage_key,
+                ttl,
+                &file.content_type,
+                &self.content_disposition(&file, disposition),
+            )
+            .unwrap_or_else(|| {
+                // 处理方式不参与签名，下载时仍按文件类型决定是否允许 inline
+                let disposition = match disposition {
+                    Disposition::Attachment => "",
+                    Disposition::Inline => "&disposition=inline",
+                };
+                format!(
+                    "{public_url}/v1/files/{file_id}/download?expires={expires}&sig={}{disposition}",
+                    download::sign(secret, file_id, expires)
+                )
+            });
+        Ok(DownloadUrlResponse {
+            url,
+            expires_at: DateTime::from_timestamp(expires, 0).expect("有效期不超过 7 天，不会越界"),
+        })
+    }
+
+    /// 查询文件元数据
+    ///
+    /// 文件所有者和管理员可以查看，不要求扫描结果为 `clean`。
+    ///
+    /// # 返回
+    /// 成功返回文件信息，文件不存在返回 AppError::NotFound，无权访问返回 AuthError::PermissionDenied
+    #[instrument(skip(self))]
+    pub async fn get_file(
+        &self,
+        current_user_id: UserId,
+        file_id: i64,
+        public_url: &str,
+    ) -> Result<FileResponse, AppError> {
+        let file = self.accessible_file(current_user_id, file_id).await?;
+        Ok(FileResponse::from_model(file, public_url))
+    }
+
+    /// 修改文件名或描述
+    ///
+    /// 文件所有者和管理员可以修改。文件名去除路径部分，描述去除首尾空白，空描述表示清除；
+    /// 未提供的字段保持不变。
+    ///
+    /// # 返回
+    /// 成功返回更新后的文件信息
+    /// 两个字段都未提供返回 ValidationError，文件不存在返回 AppError::NotFound，
+    /// 无权访问返回 AuthError::PermissionDenied
+    #[instrument(skip(self))]
+    pub async fn update_file(
+        &self,
+        current_user_id: UserId,
+        file_id: i64,
+        req: UpdateFileRequest,
+        public_url: &str,
+    ) -> Result<FileResponse, AppError> {
+        if req.filename.is_none() && req.description.is_none() {
+            return Err(ValidationError::custom("至少需要提供 filename 或 description").into());
+        }
+        let file = self.accessible_file(current_user_id, file_id).await?;
+        let filename = match req.filename {
+            Some(name) => sanitize_filename(Some(&name)),
+            None => file.filename,
+        };
+        let description = match req.description {
+            Some(text) => Some(text.trim().to_string()).filter(|t| !t.is_empty()),
+            None => file.description,
+        };
+
+        let file = self
+            .repo
+            .update_metadata(file_id, &filename, description.as_deref())
+            .await
+            .or_not_found(FILE_NOT_FOUND)?;
+        info!(file_id, "文件元数据已更新");
+        Ok(FileResponse::from_model(file, public_url))
+    }
+
+    /// 删除文件元数据
+    ///
+    /// 文件所有者和管理员可以删除。在事务中删除元数据、释放所有者的配额和对内容的引用；
+    /// 内容由调用方投递给回收任务，最后一个引用释放后才从存储后端删除（见 [`Self::reclaim_blob`]）。
+    /// 未参与去重的早期文件直接删除内容，失败只记录日志。
+    ///
+    /// # 返回
+    /// 成功返回已删除文件的元数据
+    /// 文件不存在返回 AppError::NotFound，无权访问返回 AuthError::PermissionDenied
+    #[instrument(skip(self))]
+    pub async fn delete_file(
+        &self,
+        current_user_id: UserId,
+        file_id: i64,
+    ) -> Result<file::Model, AppError> {
+        let file = self.accessible_file(current_user_id, file_id).await?;
+        let unreferenced = self
+            .repo
+            .delete(file_id)
+            .await
+            .with_context(|| format!("删除文件 {file_id} 的元数据失败"))?;
+        if let Some(content) = unreferenced {
+            self.delete_content(&content).await;
+        }
+        info!(file_id, "文件已删除");
+        Ok(file)
+    }
+
+    /// 从记录的存储后端删除不再被引用的内容，返回是否删除成功，失败只记录日志
+    async fn delete_content(&self, content: &UnreferencedContent) -> bool {
+        let result = match self.storage_named(&content.storage_backend) {
+            Ok(storage) => storage.delete(&content.storage_key).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!(
+                storage_key = content.storage_key,
+                backend = content.storage_backend,
+                error = %e,
+                "删除不再被引用的文件内容失败"
+            );
+            return false;
+        }
+        true
+    }
+
+    /// 按记录中的后端名称取存储后端，名称无效或未配置时返回错误
+    fn storage_named(&self, backend: &str) -> std::io::Result<&S> {
+        backend
+            .parse::<StorageBackend>()
+            .map_err(|e| std::io::Error::other(e.to_string()))
+            .and_then(|backend| self.storages.get(backend))
+    }
+
+    /// 将文件内容迁移到另一个存储后端（仅供管理员接口调用，不做权限检查）
+    ///
+    /// 从当前后端读取内容写入目标后端，大小和 SHA-256 与元数据一致后更新记录，再删除源后端中的内容
+    /// （删除失败只记录日志）。文件已在目标后端时直接返回。迁移失败时删除目标后端中写入的内容，
+    /// 记录保持不变。
+    ///
+    /// # 返回
+    /// 成功返回迁移前的存储后端
+    /// 文件不存在返回 AppError::NotFound，目标后端未配置返回 ValidationError
+    #[instrument(skip(self))]
+    pub async fn migrate_storage(
+        &self,
+        file_id: i64,
+        target: StorageBackend,
+    ) -> Result<StorageBackend, AppError> {
+        let file = self
+            .repo
+            .find_by_id(file_id)
+            .await
+            .or_not_found(FILE_NOT_FOUND)?;
+        let (from, source) = self.storage_of(&file)?;
+        if from == target {
+            return Ok(from);
+        }
+        let destination = self
+            .storages
+            .get(target)
+            .map_err(|e| ValidationError::custom(e.to_string()))?;
+
+        if let Err(e) = copy_verified(
+            (source, &file.storage_key),
+            (destination, &file.storage_key),
+            file.size_bytes,
+            &file.checksum,
+        )
+        .await
+        {
+            if let Err(e) = destination.delete(&file.storage_key).await {
+                warn!(file_id, error = %e, "清理迁移失败的文件内容失败");
+            }
+            return Err(e);
+        }
+        self.repo
+            .update_storage_backend(file_id, &target.to_string())
+            .await
+            .with_context(|| format!("更新文件 {file_id} 的存储后端失败"))?;
+        if let Err(e) = source.delete(&file.storage_key).await {
+            warn!(file_id, %from, error = %e, "删除迁移前的文件内容失败");
+        }
+
+        info!(file_id, %from, to = %target, "文件已迁移存储后端");
+        Ok(from)
+    }
+
+    /// 扫描等待扫描的文件并记录结果（由后台扫描任务调用，不做权限检查）
+    ///
+    /// 文件不存在或已不是 `pending_scan` 时跳过，返回 `None`。扫描器不可用时返回错误，
+    /// 文件保持 `pending_scan`，由定期的重新投递重试。发现恶意内容时先标记为 `infected`
+    /// （此后即无法下载），再按 `quarantine` 把内容移动到 `quarantine/` 前缀下或直接删除；
+    /// 隔离失败只记录日志，记录仍为 `infected`。
+    ///
+    /// # 返回
+    /// 成功返回扫描后的状态
+    #[instrument(skip(self, scanner))]
+    pub async fn scan_file<F: FileScanner>(
+        &self,
+        file_id: i64,
+        scanner: &F,
+        quarantine: QuarantineAction,
+    ) -> Result<Option<ScanStatus>, AppError> {
+        let Some(file) = self
+            .repo
+            .find_by_id(file_id)
+            .await
+            .with_context(|| format!("查询文件 {file_id} 失败"))?
+        else {
+            return Ok(None);
+        };
+        if file.scan_status != ScanStatus::PendingScan.to_string() {
+            return Ok(None);
+        }
+
+        let (_, storage) = self.storage_of(&file)?;
+        let reader = storage
+            .open(&file.storage_key)
+            .await
+            .with_context(|| format!("读取文件 {file_id} 的内容失败"))?;
+        let verdict = scanner
+            .scan(reader)
+            .await
+            .with_context(|| format!("扫描文件 {file_id} 失败"))?;
+
+        let status = match &verdict {
+            ScanVerdict::Clean => ScanStatus::Clean,
+            ScanVerdict::Infected(_) => ScanStatus::Infected,
+        };
+        self.repo
+            .update_scan_status(file_id, &status.to_string())
+            .await
+            .with_context(|| format!("更新文件 {file_id} 的扫描状态失败"))?;
+
+        if let ScanVerdict::Infected(signature) = verdict {
+            warn!(file_id, %signature, %quarantine, "文件包含恶意内容，已隔离");
+            self.quarantine(&file, storage, quarantine).await;
+        }
+        Ok(Some(status))
+    }
+
+    /// 等待扫描的文件 ID，最多 `limit` 个（供后台任务重新投递）
+    pub async fn pending_scan_files(&self, limit: u64) -> Result<Vec<i64>, AppError> {
+        Ok(self
+            .repo
+            .list_pending_scan(limit)
+            .await
+            .context("查询待扫描的文件失败")?)
+    }
+
+    /// 为等待生成缩略图的图片生成 `upload.thumbnail_sizes` 中的全部尺寸（由后台缩略图任务调用，不做权限检查）
+    ///
+    /// 文件不存在、不是 `pending` 或已被判定感染时跳过，返回 `None`。读取原图后在阻塞线程池中解码和缩放，
+    /// 写入内容所在的存储后端，全部写入后把引用同一内容的文件标记为 `ready`。失败时返回错误，
+    /// 文件保持 `pending`，请求缩略图时重新投递；已写入的缩略图由下次生成覆盖。
+    ///
+    /// # 返回
+    /// 成功返回生成的边长，升序
+    #[instrument(skip(self))]
+    pub async fn generate_thumbnails(&self, file_id: i64) -> Result<Option<Vec<u32>>, AppError> {
+        let Some(file) = self
+            .repo
+            .find_by_id(file_id)
+            .await
+            .with_context(|| format!("查询文件 {file_id} 失败"))?
+        else {
+            return Ok(None);
+        };
+        let mut sizes = self.upload.thumbnail_sizes.clone();
+        sizes.sort_unstable();
+        sizes.dedup();
+        if file.thumbnail_status != Some(ThumbnailStatus::Pending.to_string())
+            || file.scan_status == ScanStatus::Infected.to_string()
+            || file.checksum.is_empty()
+            || sizes.is_empty()
+        {
+            return Ok(None);
+        }
+
+        let (_, storage) = self.storage_of(&file)?;
+        let mut source = Vec::with_capacity(file.size_bytes as usize);
+        storage
+            .open(&file.storage_key)
+            .await
+            .with_context(|| format!("读取文件 {file_id} 的内容失败"))?
+            .read_to_end(&mut source)
+            .await
+            .with_context(|| format!("读取文件 {file_id} 的内容失败"))?;
+        let rendered = {
+            let sizes = sizes.clone();
+            tokio::task::spawn_blocking(move || thumbnail::render(&source, &sizes))
+                .await
+                .context("缩略图任务异常退出")?
+                .with_context(|| format!("解码文件 {file_id} 的图片失败"))?
+        };
+
+        for (size, png) in rendered {
+            let key = thumbnail_key(&file.checksum, size);
+            let mut writer = storage
+                .create(&key)
+                .await
+                .with_context(|| format!("写入缩略图 {key} 失败"))?;
+            writer
+                .write_all(&png)
+                .await
+                .with_context(|| format!("写入缩略图 {key} 失败"))?;
+            writer
+                .shutdown()
+                .await
+                .with_context(|| format!("写入缩略图 {key} 失败"))?;
+        }
+        self.repo
+            .update_thumbnails(file_id, &ThumbnailStatus::Ready.to_string(), &sizes)
+            .await
+            .with_context(|| format!("更新文件 {file_id} 的缩略图状态失败"))?;
+        Ok(Some(sizes))
+    }
+
+    /// 读取文件的缩略图
+    ///
+    /// 只有所有者或管理员可以读取，扫描结果为 `clean` 之前不能读取。从已生成的边长中选择最接近 `size` 的一个
+    /// （距离相同时取较大的，未指定时取最大的）。缩略图尚未生成时返回 `None`；记录为 `ready` 而对象已不存在时
+    /// （如内容迁移到了其他存储后端）把文件改回 `pending` 并同样返回 `None`，由调用方投递生成任务。
+    ///
+    /// # 返回
+    /// 成功返回实际边长和 PNG 内容
+    /// 文件不存在或不生成缩略图返回 AppError::NotFound，无权访问返回 AuthError::PermissionDenied，
+    /// 未通过扫描返回 FileUploadError::NotClean
+    #[instrument(skip(self))]
+    pub async fn thumbnail(
+        &self,
+        current_user_id: UserId,
+        file_id: i64,
+        size: Option<u32>,
+    ) -> Result<Option<Thumbnail>, AppError> {
+        let file = self.accessible_file(current_user_id, file_id).await?;
+        ensure_clean(&file)?;
+        let Some(status) = file.thumbnail_status.as_deref() else {
+            return Err(AppError::NotFound(NO_THUMBNAIL.to_string()));
+        };
+        if status != ThumbnailStatus::Ready.to_string() {
+            return Ok(None);
+        }
+        let Some(size) = thumbnail::closest(&thumbnail::sizes_of(&file), size) else {
+            return Ok(None);
+        };
+
+        let (_, storage) = self.storage_of(&file)?;
+        let key = thumbnail_key(&file.checksum, size);
+        let mut content = Vec::new();
+        let read = async { storage.open(&key).await?.read_to_end(&mut content).await }.await;
+        match read {
+            Ok(_) => Ok(Some(Thumbnail { size, content })),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                warn!(file_id, storage_key = key, "缩略图对象不存在，等待重新生成");
+                self.repo
+                    .update_thumbnails(file_id, &ThumbnailStatus::Pending.to_string(), &[])
+                    .await
+                    .with_context(|| format!("更新文件 {file_id} 的缩略图状态失败"))?;
+                Ok(None)
+            }
+            Err(e) => Err(storage_error(e, |e| {
+                anyhow::anyhow!("读取缩略图 {key} 失败：{e}").into()
+            })),
+        }
+    }
+
+    /// 校验所有内容的引用计数和对象是否存在（由后台任务调用，不做权限检查）
+    ///
+    /// 引用计数与实际引用的文件数不一致时修正（例如删除用户时级联删除的文件不会释放引用），
+    /// 已无文件引用的内容（包括回收任务漏掉的）从存储后端删除。对象缺失时记录错误日志并把内容标记为损坏，
+    /// 引用它的文件出现在管理员的损坏文件报告中；对象重新出现（如从备份恢复）后清除标记。
+    /// 检查期间有文件登记或删除的内容本轮跳过。
+    #[instrument(skip(self))]
+    pub async fn check_blob_integrity(&self) -> Result<BlobIntegrityReport, AppError> {
+        let mut report = BlobIntegrityReport::default();
+        let mut after = None;
+        loop {
+            let blobs = self
+                .repo
+                .list_blobs(after.as_deref(), BLOB_CHECK_BATCH_SIZE)
+                .await
+                .context("查询文件内容记录失败")?;
+            let Some(last) = blobs.last() else {
+                break;
+            };
+            after = Some(last.hash.clone());
+
+            for blob in blobs {
+                report.checked += 1;
+                let refs = self.count_refs(&blob.hash).await?;
+                if refs == 0 {
+                    if self.remove_blob(&blob).await? {
+                        report.removed += 1;
+                    }
+                    continue;
+                }
+                if refs as i64 != blob.ref_count {
+                    let reconciled = self
+                        .repo
+                        .reconcile_blob_refs(&blob.hash, blob.ref_count, refs)
+                        .await
+                        .with_context(|| format!("修正内容 {} 的引用计数失败", blob.hash))?;
+                    if !reconciled {
+                        continue;
+                    }
+                    warn!(
+                        hash = blob.hash,
+                        recorded = blob.ref_count,
+                        actual = refs,
+                        "内容的引用计数与文件记录不一致，已修正"
+                    );
+                    report.fixed += 1;
+                }
+
+                let exists = self.blob_exists(&blob).await;
+                if !exists {
+                    report.missing += 1;
+                    error!(
+                        hash = blob.hash,
+                        storage_key = blob.storage_key,
+                        backend = blob.storage_backend,
+                        "文件内容在存储后端中不存在"
+                    );
+                }
+                let corrupt_at = match (exists, blob.corrupt_at) {
+                    (false, None) => Some(Utc::now().fixed_offset()),
+                    (true, Some(_)) => None,
+                    _ => continue,
+                };
+                self.repo
+                    .set_blob_corrupt(&blob.hash, corrupt_at)
+                    .await
+                    .with_context(|| format!("更新内容 {} 的损坏标记失败", blob.hash))?;
+                if exists {
+                    report.recovered += 1;
+                    info!(hash = blob.hash, "文件内容已恢复，清除损坏标记");
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// 删除存储后端中没有元数据引用的孤立对象（由后台任务调用，不做权限检查）
+    ///
+    /// 上传先写对象再登记元数据，进程在两步之间崩溃会留下没有记录的对象。逐个后端分批列出对象，
+    /// 每批查询仍被内容或文件记录引用的键，删除其余写入时间早于 `upload.orphan_grace_secs` 的对象；
+    /// 宽限期内的对象可能属于仍在进行的上传，无法获取写入时间的对象和分片一律保留。
+    /// 缩略图 `thumbnails/{hash}/...` 随内容 `blobs/{hash}` 的引用保留，内容不再被引用后一起删除。
+    /// 内存占用只与批大小有关，删除失败只记录日志，留待下一轮。
+    #[instrument(skip(self))]
+    pub async fn sweep_orphaned_objects(&self) -> Result<OrphanSweepReport, AppError> {
+        let mut report = OrphanSweepReport::default();
+        let cutoff = Utc::now() - Duration::seconds(self.upload.orphan_grace_secs as i64);
+        for (backend, storage) in self.storages.iter() {
+            let mut after: Option<String> = None;
+            loop {
+                let objects = storage
+                    .list(after.as_deref(), ORPHAN_SCAN_BATCH_SIZE)
+                    .await
+                    .with_context(|| format!("列出存储后端 {backend} 中的对象失败"))?;
+                let Some(last) = objects.last() else {
+                    break;
+                };
+                after = Some(last.key.clone());
+                report.scanned += objects.len() as u64;
+
+                let candidates: Vec<String> = objects
+                    .into_iter()
+                    .filter(|o| !o.key.starts_with(CHUNK_KEY_PREFIX))
+                    .filter(|o| o.modified.is_some_and(|modified| modified < cutoff))
+                    .map(|o| o.key)
+                    .collect();
+                if candidates.is_empty() {
+                    continue;
+                }
+                // 缩略图按所属内容的对象键判断是否被引用
+                let owner_key = |key: &String| match thumbnail::checksum_of(key) {
+                    Some(checksum) => blob_key(checksum),
+                    None => key.clone(),
+                };
+                let owners: Vec<String> = candidates.iter().map(owner_key).collect();
+                let referenced = self
+                    .repo
+                    .referenced_storage_keys(&backend.to_string(), &owners)
+                    .await
+                    .context("查询被引用的对象键失败")?;
+                for key in candidates
+                    .iter()
+                    .filter(|key| !referenced.contains(&owner_key(key)))
+                {
+                    match storage.delete(key).await {
+                        Ok(()) => {
+                            report.deleted += 1;
+                            info!(storage_key = key, %backend, "已删除没有元数据引用的对象");
+                        }
+                        Err(e) => {
+                            warn!(storage_key = key, %backend, error = %e, "删除孤立对象失败")
+                        }
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// 分页列出内容已被标记为损坏的文件（仅供管理员接口调用，不做权限检查）
+    ///
+    /// # 返回
+    /// 成功返回文件分页列表，按文件 ID 升序
+    #[instrument(skip(self))]
+    pub async fn list_corrupt_files(
+        &self,
+        params: PageParams,
+    ) -> Result<PaginatedResponse<CorruptFileDto>, AppError> {
+        let params = params.normalized();
+        let (files, total) = self
+            .repo
+            .list_corrupt_files(params)
+            .await
+            .context("查询损坏的文件失败")?;
+        let items = files
+            .into_iter()
+            .map(|(file, corrupt_at)| CorruptFileDto::from_model(file, corrupt_at))
+            .collect();
+        Ok(PaginatedResponse::new(items, total, params).with_kind("CorruptFileList"))
+    }
+
+    /// 把去重之前保存的内容移动到由摘要决定的对象键，并删除被替换的副本（由后台任务调用，不做权限检查）
+    ///
+    /// 早期上传的内容保存在 `{owner_id}/{uuid}` 下，同一内容的多个文件各自保留了一份副本。逐个内容复制到
+    /// 同一后端的临时对象并校验大小和 SHA-256，移动到 `blobs/{hash}` 后在一个事务中把内容和引用它的文件
+    /// 改为新的对象键，再删除原对象和文件各自的副本。复制失败或期间内容已被隔离、迁移时本轮跳过，
+    /// 已写入的对象由孤立对象清理删除；已隔离或标记为损坏的内容不迁移。删除失败只记录日志。
+    #[instrument(skip(self))]
+    pub async fn relocate_legacy_blobs(&self) -> Result<BlobRelocationReport, AppError> {
+        let mut report = BlobRelocationReport::default();
+        let mut after = None;
+        loop {
+            let blobs = self
+                .repo
+                .list_blobs(after.as_deref(), BLOB_CHECK_BATCH_SIZE)
+                .await
+                .context("查询文件内容记录失败")?;
+            let Some(last) = blobs.last() else {
+                break;
+            };
+            after = Some(last.hash.clone());
+
+            for blob in blobs {
+                let target = blob_key(&blob.hash);
+                if blob.storage_key == target
+                    || blob.storage_key.starts_with(QUARANTINE_PREFIX)
+                    || blob.corrupt_at.is_some()
+                {
+                    continue;
+                }
+                let storage = match self.storage_named(&blob.storage_backend) {
+                    Ok(storage) => storage,
+                    Err(e) => {
+                        warn!(hash = blob.hash, error = %e, "内容所在的存储后端不可用，跳过迁移");
+                        continue;
+                    }
+                };
+
+                // 先写临时对象再移动，复制中途失败不会破坏目标位置上已被引用的内容
+                let temp = temp_key();
+                let copied = async {
+                    copy_verified(
+                        (storage, &blob.storage_key),
+                        (storage, &temp),
+                        blob.size_bytes,
+                        &blob.hash,
+                    )
+                    .await?;
+                    storage
+                        .rename(&temp, &target)
+                        .await
+                        .context("移动内容到新的对象键失败")?;
+                    Ok::<_, AppError>(())
+                }
+                .await;
+                if let Err(e) = copied {
+                    warn!(hash = blob.hash, storage_key = blob.storage_key, error = %e, "迁移内容的对象键失败");
+                    delete_quietly(storage, &temp).await;
+                    continue;
+                }
+
+                let Some(replaced) = self
+                    .repo
+                    .relocate_blob(&blob.hash, &blob.storage_key, &target)
+                    .await
+                    .with_context(|| format!("更新内容 {} 的对象键失败", blob.hash))?
+                else {
+                    continue;
+                };
+                report.relocated += 1;
+                info!(
+                    hash = blob.hash,
+                    from = blob.storage_key,
+                    to = target,
+                    "内容已移动到由摘要决定的对象键"
+                );
+                for content in &replaced {
+                    if self.delete_content(content).await {
+                        report.copies_deleted += 1;
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// 回收不再被引用的内容（由后台回收任务调用，不做权限检查）
+    ///
+    /// 仍有文件引用或内容记录已不存在时不做处理，返回 `false`；回收时先删除 blob 记录再删除对象，
+    /// 此后上传的相同内容会重新写入，不会引用即将删除的对象。对象删除失败只记录日志。
+    #[instrument(skip(self))]
+    pub async fn reclaim_blob(&self, hash: &str) -> Result<bool, AppError> {
+        let Some(blob) = self
+            .repo
+            .find_blob(hash)
+            .await
+            .with_context(|| format!("查询内容 {hash} 失败"))?
+        else {
+            return Ok(false);
+        };
+        if self.count_refs(hash).await? > 0 {
+            return Ok(false);
+        }
+        self.remove_blob(&blob).await
+    }
+
+    /// 实际引用内容的文件数
+    async fn count_refs(&self, hash: &str) -> Result<u64, AppError> {
+        Ok(self
+            .repo
+            .count_blob_refs(hash)
+            .await
+            .with_context(|| format!("统计内容 {hash} 的引用失败"))?)
+    }
+
+    /// 引用计数未变化时删除 blob 记录和对象，期间有新文件引用该内容时返回 `false`
+    async fn remove_blob(&self, blob: &blob::Model) -> Result<bool, AppError> {
+        let removed = self
+            .repo
+            .reconcile_blob_refs(&blob.hash, blob.ref_count, 0)
+            .await
+            .with_context(|| format!("删除内容 {} 的记录失败", blob.hash))?;
+        if removed {
+            self.delete_content(&UnreferencedContent {
+                storage_key: blob.storage_key.clone(),
+                storage_backend: blob.storage_backend.clone(),
+            })
+            .await;
+            info!(hash = blob.hash, "已回收不再被引用的文件内容");
+        }
+        Ok(removed)
+    }
+
+    /// 内容对应的对象是否存在；读取出错（而非不存在）时视为存在，留待下一轮检查
+    async fn blob_exists(&self, blob: &blob::Model) -> bool {
+        let Ok(storage) = self.storage_named(&blob.storage_backend) else {
+            return false;
+        };
+        let result = if blob.size_bytes > 0 {
+            storage.open_range(&blob.storage_key, 0..1).await
+        } else {
+            storage.open(&blob.storage_key).await
+        };
+        match result {
+            Ok(_) => true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+            Err(e) => {
+                warn!(hash = blob.hash, error = %e, "检查文件内容是否存在失败");
+                true
+            }
+        }
+    }
+
+    /// 按 `action` 隔离被感染文件的内容，失败只记录日志
+    async fn quarantine(&self, file: &file::Model, storage: &S, action: QuarantineAction) {
+        match action {
+            QuarantineAction::Delete => {
+                if let Err(e) = storage.delete(&file.storage_key).await {
+                    warn!(file_id = file.id, error = %e, "删除被感染的文件内容失败");
+                }
+            }
+            QuarantineAction::Move => {
+                let target = format!("{QUARANTINE_PREFIX}{}", file.storage_key);
+                let copied = copy_verified(
+                    (storage, &file.storage_key),
+                    (storage, &target),
+                    file.size_bytes,
+                    &file.checksum,
+                )
+                .await;
+                if let Err(e) = copied {
+                    warn!(file_id = file.id, error = %e, "移动被感染的文件内容失败");
+                    delete_quietly(storage, &target).await;
+                    return;
+                }
+                if let Err(e) = self.repo.update_storage_key(file.id, &target).await {
+                    warn!(file_id = file.id, error = %e, "更新隔离文件的对象键失败");
+                    delete_quietly(storage, &target).await;
+                    return;
+                }
+                delete_quietly(storage, &file.storage_key).await;
+            }
+        }
+    }
+
+    /// 查询文件并检查当前用户是否为所有者或管理员
+    async fn accessible_file(
+        &self,
+        current_user_id: UserId,
+        file_id: i64,
+    ) -> Result<file::Model, AppError> {
+        let file = self
+            .repo
+            .find_by_id(file_id)
+            .await
+            .or_not_found(FILE_NOT_FOUND)?;
+        if file.owner_id != current_user_id {
+            let role = self.repo.find_user_role(current_user_id).await?;
+            if role != Some(UserRole::Admin.into()) {
+                return Err(AuthError::PermissionDenied.into());
+            }
+        }
+        Ok(file)
+    }
+
+    /// 文件内容所在的存储后端，记录的后端无效或未配置时返回内部错误
+    fn storage_of(&self, file: &file::Model) -> Result<(StorageBackend, &S), AppError> {
+        let backend: StorageBackend = file.storage_backend.parse().with_context(|| {
+            format!("文件 {} 的存储后端无效：{}", file.id, file.storage_backend)
+        })?;
+        let storage = self
+            .storages
+            .get(backend)
+            .with_context(|| format!("无法访问文件 {} 的内容", file.id))?;
+        Ok((backend, storage))
+    }
+
+    /// 按查询参数分页列出文件，默认列出当前用户的文件
+    ///
+    /// # 参数
+    /// * `current_user_id` - 当前登录用户ID
+    /// * `query` - 所有者、MIME 类型（支持 `image/*`）和上传时间范围
+    /// * `params` - 分页参数
+    /// * `public_url` - 对外访问的基础 URL，用于拼接下载链接
+    ///
+    /// # 返回
+    /// 成功返回文件元数据分页列表
+    /// MIME 类型格式无效或时间范围为空返回 ValidationError，其余同 [`Self::list_user_files`]
+    #[instrument(skip(self))]
+    pub async fn list_files(
+        &self,
+        current_user_id: UserId,
+        query: FileListQuery,
+        params: PageParams,
+        public_url: &str,
+    ) -> Result<PaginatedResponse<FileMetadataDto>, AppError> {
+        let content_type = query.mime.as_deref().map(normalize_mime);
+        if let Some(mime) = &content_type
+            && mime
+                .split_once('/')
+                .is_none_or(|(a, b)| a.is_empty() || b.is_empty() || a == "*")
+        {
+            return Err(ValidationError::custom(format!("MIME 类型格式无效：{mime}")).into());
+        }
+        if let (Some(from), Some(to)) = (query.from, query.to)
+            && from >= to
+        {
+            return Err(ValidationError::custom("from 必须早于 to").into());
+        }
+
+        let filter = FileFilter {
+            content_type,
+            from: query.from,
+            to: query.to,
+        };
+        let owner_id = query.owner_id.unwrap_or(current_user_id);
+        self.list_user_files(current_user_id, owner_id, &filter, params, public_url)
+            .await
+    }
+
+    /// 分页列出指定用户拥有的文件
+    ///
+    /// 普通用户只能查询自己的文件，管理员可以查询任意用户的文件。
+    ///
+    /// # 参数
+    /// * `current_user_id` - 当前登录用户ID
+    /// * `owner_id` - 被查询的文件所有者ID
+    /// * `filter` - 过滤条件
+    /// * `params` - 分页参数
+    /// * `public_url` - 对外访问的基础 URL，用于拼接下载链接
+    ///
+    /// # 返回
+    /// 成功返回文件元数据分页列表
+    /// 无权访问返回 AuthError::PermissionDenied，所有者不存在返回 AppError::NotFound
+    #[instrument(skip(self))]
+    pub async fn list_user_files(
+        &self,
+        current_user_id: UserId,
+        owner_id: UserId,
+        filter: &FileFilter,
+        params: PageParams,
+        public_url: &str,
+    ) -> Result<PaginatedResponse<FileMetadataDto>, AppError> {
+        let params = params.normalized();
+
+        // 查询他人文件需要管理员权限
+        if current_user_id != owner_id {
+            let role = self.repo.find_user_role(current_user_id).await?;
+            if role != Some(UserRole::Admin.into()) {
+                return Err(AuthError::PermissionDenied.into());
+            }
+        }
+
+        self.repo
+            .find_user_role(owner_id)
+            .await
+            .or_not_found("用户不存在")?;
+
+        let (files, total) = self
+            .repo
+            .list_by_owner(owner_id, filter, params)
+            .await
+            .with_context(|| format!("查询用户 {owner_id} 的文件失败"))?;
+        let items = files
+            .into_iter()
+            .map(|model| FileMetadataDto::from_model(model, public_url))
+            .collect();
+
+        Ok(PaginatedResponse::new(items, total, params).with_kind("FileList"))
+    }
+}
+
+/// 删除存储中的对象，失败只记录日志
+async fn delete_quietly<S: Storage>(storage: &S, key: &str) {
+    if let Err(e) = storage.delete(key).await {
+        warn!(key, error = %e, "删除文件内容失败");
+    }
+}
+
+/// 只有扫描结果为 `clean` 的文件可以下载
+fn ensure_clean(file: &file::Model) -> Result<(), AppError> {
+    let status: ScanStatus = file
+        .scan_status
+        .parse()
+        .with_context(|| format!("文件 {} 的扫描状态无效：{}", file.id, file.scan_status))?;
+    if status != ScanStatus::Clean {
+        return Err(FileUploadError::NotClean(status).into());
+    }
+    Ok(())
+}
+
+/// 将对象从 `(source, source_key)` 复制到 `(destination, destination_key)`，并校验大小和 SHA-256
+/// 与 `size_bytes` / `checksum` 一致（`checksum` 为空时只校验大小）
+async fn copy_verified<S: Storage>(
+    (source, source_key): (&S, &str),
+    (destination, destination_key): (&S, &str),
+    size_bytes: i64,
+    checksum: &str,
+) -> Result<(), AppError> {
+    let mut reader = source
+        .open(source_key)
+        .await
+        .context("读取源文件内容失败")?;
+    let mut writer = destination
+        .create(destination_key)
+        .await
+        .context("创建目标文件失败")?;
+
+    let mut hasher = Sha256::new();
+    let mut size = 0i64;
+    let mut buf = vec![0; COPY_BUFFER_BYTES];
+    loop {
+        let n = reader.read(&mut buf).await.context("读取源文件内容失败")?;
+        if n == 0 {
+            break;
+        }
+        size += n as i64;
+        hasher.update(&buf[..n]);
+        writer
+            .write_all(&buf[..n])
+            .await
+            .context("写入目标文件失败")?;
+    }
+    writer.shutdown().await.context("写入目标文件失败")?;
+
+    let actual = format!("{:x}", hasher.finalize());
+    if size != size_bytes || (!checksum.is_empty() && actual != checksum) {
+        return Err(anyhow::anyhow!(
+            "对象 {source_key} 的内容与元数据不一致：大小 {size}（应为 {size_bytes}），SHA-256 {actual}"
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// 内容在存储后端中的对象键
+fn blob_key(checksum: &str) -> String {
+    format!("{BLOB_KEY_PREFIX}{checksum}")
+}
+
+/// 新的临时对象键
+fn temp_key() -> String {
+    format!("{TEMP_KEY_PREFIX}{}", Uuid::new_v4())
+}
+
+/// 分片在存储后端中的对象键
+fn chunk_key(session_id: &str, index: i64) -> String {
+    format!("{CHUNK_KEY_PREFIX}{session_id}/{index}")
+}
+
+/// 规范化 MIME 类型：去掉 `;` 之后的参数并转为小写
+fn normalize_mime(raw: &str) -> String {
+    raw.split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// 只保留文件名本身，去掉客户端可能附带的路径（如 `C:\fakepath\a.png`）
+fn sanitize_filename(raw: Option<&str>) -> String {
+    raw.and_then(|name| name.rsplit(['/', '\\']).next())
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or("unnamed")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::file::repo::InMemoryFileRepo;
+    use crate::shared::scanner::NoopScanner;
+    use crate::shared::storage::{InMemoryStorage, ObjectInfo};
+    use crate::test_support::user_id;
+    use axum::{
+        body::{Body, Bytes},
+        extract::{DefaultBodyLimit, FromRequest},
+        http::{Request, StatusCode},
+        response::IntoResponse,
+    };
+    use entity::file;
+    use futures_util::{StreamExt, stream};
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+    use std::task::{Context, Poll};
+    use tokio::io::AsyncWrite;
+    use tower::{Layer, ServiceExt};
+
+    const ALICE: UserId = user_id(1);
+    const BOB: UserId = user_id(2);
+    const ADMIN: UserId = user_id(3);
+    const PUBLIC_URL: &str = "https://files.example.com";
+    const BOUNDARY: &str = "test-boundary";
+
+    fn service() -> FileService<InMemoryFileRepo, InMemoryStorage> {
+        let repo = InMemoryFileRepo::default();
+        repo.seed_user(ALICE, UserRole::User.into());
+        repo.seed_user(BOB, UserRole::User.into());
+        repo.seed_user(ADMIN, UserRole::Admin.into());
+
+        // 文件 1、2 在本地磁盘，文件 3 在 S3，内容为 `size_bytes` 个字节的 id
+        let (local, s3) = (InMemoryStorage::default(), InMemoryStorage::default());
+        let now = Utc::now().fixed_offset();
+        for id in 1..=3 {
+            let backend = if id == 3 {
+                StorageBackend::S3
+            } else {
+                StorageBackend::Local
+            };
+            let content = seeded_content(id);
+            repo.seed_file(file::Model {
+                id,
+                owner_id: ALICE,
+                filename: format!("report-{id}.pdf"),
+                content_type: "application/pdf".to_string(),
+                size_bytes: content.len() as i64,
+                storage_key: format!("blobs/{id}"),
+                created_at: now + Duration::seconds(id),
+                checksum: format!("{:x}", Sha256::digest(&content)),
+                description: None,
+                storage_backend: backend.to_string(),
+                scan_status: ScanStatus::Clean.to_string(),
+                thumbnail_status: None,
+                thumbnail_sizes: serde_json::json!([]),
+            });
+            let storage = if backend == StorageBackend::S3 {
+                &s3
+            } else {
+                &local
+            };
+            storage.insert(&format!("blobs/{id}"), content);
+        }
+
+        let upload = UploadConfig {
+            max_file_size_bytes: 16,
+            ..Default::default()
+        };
+        let storages = Storages::single(StorageBackend::Local, local).with(StorageBackend::S3, s3);
+        FileService::new(repo, storages, upload)
+    }
+
+    /// [`service`] 预置的文件内容
+    fn seeded_content(id: i64) -> Vec<u8> {
+        vec![id as u8; 1024 * id as usize]
+    }
+
+    fn stored_in(
+        service: &FileService<InMemoryFileRepo, InMemoryStorage>,
+        backend: StorageBackend,
+    ) -> &InMemoryStorage {
+        service.storages.get(backend).unwrap()
+    }
+
+    /// multipart 表单字段
+    enum Part<'a> {
+        /// (字段名, 值)
+        Text(&'a str, &'a str),
+        /// `file` 字段：(文件名, MIME 类型, 内容)
+        File(&'a str, &'a str, &'a [u8]),
+    }
+
+    /// 按给定顺序构造 multipart 请求
+    async fn multipart(parts: &[Part<'_>]) -> Multipart {
+        let mut body = Vec::new();
+        for part in parts {
+            body.extend(format!("--{BOUNDARY}\r\nContent-Disposition: form-data; ").bytes());
+            match part {
+                Part::Text(name, value) => {
+                    body.extend(format!("name=\"{name}\"\r\n\r\n{value}\r\n").bytes());
+                }
+                Part::File(filename, content_type, content) => {
+                    body.extend(
+                        format!(
+                            "name=\"file\"; filename=\"{filename}\"\r\n\
+                             Content-Type: {content_type}\r\n\r\n"
+                        )
+                        .bytes(),
+                    );
+                    body.extend_from_slice(content);
+                    body.extend_from_slice(b"\r\n");
+                }
+            }
+        }
+        body.extend(format!("--{BOUNDARY}--\r\n").bytes());
+        multipart_body(Body::from(body)).await
+    }
+
+    /// 以 [`BOUNDARY`] 为分隔符，用给定请求体构造 multipart 提取器
+    ///
+    /// 与上传路由一样经过 [`DefaultBodyLimit`] 层，否则提取器会套用 axum 默认的 2 MB 上限。
+    async fn multipart_body(body: Body) -> Multipart {
+        multipart_limited(body, DefaultBodyLimit::disable()).await
+    }
+
+    /// 同 [`multipart_body`]，请求体大小受 `limit` 约束
+    async fn multipart_limited(body: Body, limit: DefaultBodyLimit) -> Multipart {
+        let request = Request::post("/v1/files")
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .body(body)
+            .unwrap();
+        let extract = tower::service_fn(|request: Request<Body>| async move {
+            Multipart::from_request(request, &()).await
+        });
+        limit.layer(extract).oneshot(request).await.unwrap()
+    }
+
+    fn page(page: u64, per_page: u64) -> PageParams {
+        PageParams { page, per_page }
+    }
+
+    fn status(err: AppError) -> StatusCode {
+        err.into_response().status()
+    }
+
+    #[tokio::test]
+    async fn test_owner_lists_own_files_newest_first() {
+        let response = service()
+            .list_user_files(ALICE, ALICE, &FileFilter::default(), page(1, 2), PUBLIC_URL)
+            .await
+            .unwrap();
+        assert_eq!(response.total(), 3);
+
+        let bytes = axum::body::to_bytes(response.into_response().into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let items = body["data"]["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["id"], 3);
+        assert_eq!(
+            items[0]["download_url"],
+            "https://files.example.com/v1/files/3/download"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_non_admin_cannot_list_other_users_files() {
+        let err = service()
+            .list_user_files(
+                BOB,
+                ALICE,
+                &FileFilter::default(),
+                PageParams::default(),
+                PUBLIC_URL,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_admin_can_list_any_users_files() {
+        let response = service()
+            .list_user_files(
+                ADMIN,
+                ALICE,
+                &FileFilter::default(),
+                PageParams::default(),
+                PUBLIC_URL,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.total(), 3);
+
+        let err = service()
+            .list_user_files(
+                ADMIN,
+                user_id(404),
+                &FileFilter::default(),
+                PageParams::default(),
+                PUBLIC_URL,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_list_files_filters_by_type_and_upload_time() {
+        let service = service();
+        let form = multipart(&[Part::File("notes.txt", "text/plain", b"hello")]).await;
+        service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+        let list = async |user_id, query: FileListQuery| {
+            service
+                .list_files(user_id, query, PageParams::default(), PUBLIC_URL)
+                .await
+                .map(|response| response.total())
+        };
+
+        assert_eq!(list(ALICE, FileListQuery::default()).await.unwrap(), 4);
+        assert_eq!(list(BOB, FileListQuery::default()).await.unwrap(), 0);
+        let text = FileListQuery {
+            mime: Some("Text/*".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(list(ALICE, text).await.unwrap(), 1);
+        let created_at = |id| service.repo.file(id).unwrap().created_at.to_utc();
+        let window = FileListQuery {
+            mime: Some("application/pdf".to_string()),
+            from: Some(created_at(2)),
+            to: Some(created_at(3)),
+            ..Default::default()
+        };
+        assert_eq!(list(ALICE, window).await.unwrap(), 1);
+
+        for query in [
+            FileListQuery {
+                mime: Some("pdf".to_string()),
+                ..Default::default()
+            },
+            FileListQuery {
+                from: Some(created_at(3)),
+                to: Some(created_at(2)),
+                ..Default::default()
+            },
+        ] {
+            let err = list(ALICE, query).await.unwrap_err();
+            assert_eq!(status(err), StatusCode::BAD_REQUEST);
+        }
+
+        // 指定他人需要管理员权限
+        let alice = || FileListQuery {
+            owner_id: Some(ALICE),
+            ..Default::default()
+        };
+        let err = list(BOB, alice()).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+        assert_eq!(list(ADMIN, alice()).await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_file_metadata_is_visible_only_to_owner_or_admin() {
+        let service = service();
+        let err = service.get_file(BOB, 1, PUBLIC_URL).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+        let rename = || UpdateFileRequest {
+            filename: Some("stolen.pdf".to_string()),
+            ..Default::default()
+        };
+        let err = service
+            .update_file(BOB, 1, rename(), PUBLIC_URL)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+        let err = service.delete_file(BOB, 1).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+        assert_eq!(service.repo.file(1).unwrap().filename, "report-1.pdf");
+
+        let file = service.get_file(ALICE, 1, PUBLIC_URL).await.unwrap();
+        assert_eq!((file.id, file.owner_id), (1, ALICE));
+        let file = service.get_file(ADMIN, 1, PUBLIC_URL).await.unwrap();
+        assert_eq!(file.filename, "report-1.pdf");
+        let err = service.get_file(ALICE, 404, PUBLIC_URL).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_update_file_renames_and_describes() {
+        let service = service();
+        let req = UpdateFileRequest {
+            filename: Some("C:\\docs\\summary.pdf".to_string()),
+            description: Some(" 年度总结 ".to_string()),
+        };
+        let file = service
+            .update_file(ALICE, 1, req, PUBLIC_URL)
+            .await
+            .unwrap();
+        assert_eq!(file.filename, "summary.pdf");
+        assert_eq!(file.description.as_deref(), Some("年度总结"));
+
+        // 未提供的字段保持不变，空描述表示清除
+        let req = UpdateFileRequest {
+            description: Some(" ".to_string()),
+            ..Default::default()
+        };
+        let file = service
+            .update_file(ADMIN, 1, req, PUBLIC_URL)
+            .await
+            .unwrap();
+        assert_eq!(file.filename, "summary.pdf");
+        assert_eq!(file.description, None);
+        assert_eq!(service.repo.file(1).unwrap().filename, "summary.pdf");
+
+        let err = service
+            .update_file(ALICE, 1, UpdateFileRequest::default(), PUBLIC_URL)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+    }
+
+    async fn read_all(mut reader: impl tokio::io::AsyncRead + Unpin) -> Vec<u8> {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).await.unwrap();
+        content
+    }
+
+    #[tokio::test]
+    async fn test_download_reads_from_recorded_backend() {
+        let service = service();
+        for id in [1, 3] {
+            let model = service.downloadable_file(ALICE, id).await.unwrap();
+            assert_eq!(model.id, id);
+            let reader = service.open_content(&model, None).await.unwrap();
+            assert_eq!(read_all(reader).await, seeded_content(id));
+
+            let model = service.downloadable_file(ADMIN, id).await.unwrap();
+            let reader = service.open_content(&model, None).await.unwrap();
+            assert_eq!(read_all(reader).await, seeded_content(id));
+        }
+
+        let err = service.downloadable_file(BOB, 3).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+        let err = service.downloadable_file(ALICE, 99).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_open_content_reads_requested_range() {
+        let service = service();
+        for id in [1, 3] {
+            let model = service.downloadable_file(ALICE, id).await.unwrap();
+            let reader = service.open_content(&model, Some(10..20)).await.unwrap();
+            assert_eq!(read_all(reader).await, seeded_content(id)[10..20]);
+
+            let size = model.size_bytes as u64;
+            let reader = service
+                .open_content(&model, Some(size - 1..size))
+                .await
+                .unwrap();
+            assert_eq!(read_all(reader).await, [id as u8]);
+        }
+    }
+
+    const SECRET: &[u8] = b"download-secret";
+
+    /// 从应用签名的下载地址中取出 `expires` 和 `sig`
+    fn signature_of(url: &str) -> (i64, String) {
+        let query = url.split_once('?').unwrap().1;
+        let params: HashMap<_, _> = form_urlencoded::parse(query.as_bytes()).collect();
+        (
+            params["expires"].parse().unwrap(),
+            params["sig"].to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_signed_download_url_roundtrip() {
+        let service = service();
+        let response = service
+            .download_url(ALICE, 1, None, Disposition::Attachment, SECRET, PUBLIC_URL)
+            .await
+            .unwrap();
+        assert!(
+            response
+                .url
+                .starts_with("https://files.example.com/v1/files/1/download?expires="),
+            "{}",
+            response.url
+        );
+        let ttl = response.expires_at - Utc::now();
+        assert!(ttl <= Duration::seconds(300) && ttl > Duration::seconds(290));
+
+        let (expires, sig) = signature_of(&response.url);
+        let model = service.signed_file(1, expires, &sig, SECRET).await.unwrap();
+        assert_eq!(model.id, 1);
+        let reader = service.open_content(&model, None).await.unwrap();
+        assert_eq!(read_all(reader).await, seeded_content(1));
+
+        // 管理员可以为他人的文件生成链接
+        let response = service
+            .download_url(
+                ADMIN,
+                3,
+                Some(3600),
+                Disposition::Attachment,
+                SECRET,
+                PUBLIC_URL,
+            )
+            .await
+            .unwrap();
+        let (expires, sig) = signature_of(&response.url);
+        assert!(service.signed_file(3, expires, &sig, SECRET).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_or_expired_download_link_is_forbidden() {
+        let service = service();
+        let response = service
+            .download_url(
+                ALICE,
+                1,
+                Some(60),
+                Disposition::Attachment,
+                SECRET,
+                PUBLIC_URL,
+            )
+            .await
+            .unwrap();
+        let (expires, sig) = signature_of(&response.url);
+
+        for (file_id, expires, sig) in [
+            (2, expires, sig.as_str()),
+            (1, expires + 60, sig.as_str()),
+            (1, expires, "forged"),
+        ] {
+            let err = service
+                .signed_file(file_id, expires, sig, SECRET)
+                .await
+                .unwrap_err();
+            assert_eq!(status(err), StatusCode::FORBIDDEN);
+        }
+
+        let expired = Utc::now().timestamp() - 1;
+        let sig = download::sign(SECRET, 1, expired);
+        let err = service
+            .signed_file(1, expired, &sig, SECRET)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_inline_disposition_only_for_safe_types() {
+        let service = service();
+        let mut file = service.downloadable_file(ALICE, 1).await.unwrap();
+        file.filename = "季度 \"报告\".pdf".to_string();
+        assert!(
+            service
+                .content_disposition(&file, Disposition::Inline)
+                .starts_with("inline; ")
+        );
+        assert!(
+            service
+                .content_disposition(&file, Disposition::Attachment)
+                .starts_with("attachment; ")
+        );
+
+        // 不在 inline 列表中的类型忽略请求，仍作为附件
+        for content_type in ["text/plain", "text/html", "image/svg+xml"] {
+            file.content_type = content_type.to_string();
+            assert!(
+                service
+                    .content_disposition(&file, Disposition::Inline)
+                    .starts_with("attachment; "),
+                "{content_type}"
+            );
+        }
+
+        let response = service
+            .download_url(ALICE, 1, None, Disposition::Inline, SECRET, PUBLIC_URL)
+            .await
+            .unwrap();
+        assert!(
+            response.url.ends_with("&disposition=inline"),
+            "{}",
+            response.url
+        );
+        let (expires, sig) = signature_of(&response.url);
+        assert!(service.signed_file(1, expires, &sig, SECRET).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_download_url_checks_ttl_and_access() {
+        let service = service();
+        for ttl in [0, 3601] {
+            let err = service
+                .download_url(
+                    ALICE,
+                    1,
+                    Some(ttl),
+                    Disposition::Attachment,
+                    SECRET,
+                    PUBLIC_URL,
+                )
+                .await
+                .unwrap_err();
+            assert_eq!(status(err), StatusCode::BAD_REQUEST);
+        }
+
+        let err = service
+            .download_url(BOB, 1, None, Disposition::Attachment, SECRET, PUBLIC_URL)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+        let err = service
+            .download_url(ALICE, 99, None, Disposition::Attachment, SECRET, PUBLIC_URL)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_delete_removes_content_from_recorded_backend() {
+        let service = service();
+        let err = service.delete_file(BOB, 1).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+
+        let deleted = service.delete_file(ALICE, 1).await.unwrap();
+        assert_eq!(deleted.filename, "report-1.pdf");
+        let other = service.delete_file(ADMIN, 3).await.unwrap();
+        // 元数据已删除，内容等待回收任务处理
+        assert_eq!(stored_in(&service, StorageBackend::Local).object_count(), 2);
+        for file in [deleted, other] {
+            assert!(service.reclaim_blob(&file.checksum).await.unwrap());
+            assert!(!service.reclaim_blob(&file.checksum).await.unwrap());
+        }
+        assert_eq!(stored_in(&service, StorageBackend::Local).object_count(), 1);
+        assert_eq!(stored_in(&service, StorageBackend::S3).object_count(), 0);
+        assert!(service.repo.file(1).is_none());
+        assert!(service.repo.file(3).is_none());
+
+        let err = service.downloadable_file(ALICE, 3).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+        let err = service.delete_file(ALICE, 3).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_identical_uploads_share_stored_content() {
+        let service = service();
+        let upload = async |user_id| {
+            let form = multipart(&[Part::File("notes.txt", "text/plain", b"hello")]).await;
+            service.upload(user_id, form, PUBLIC_URL).await.unwrap()
+        };
+        let first = upload(ALICE).await;
+        let second = upload(BOB).await;
+        assert_eq!(first.checksum, second.checksum);
+
+        // 两次上传写入同一个由摘要决定的对象，两条记录都指向它
+        let local = stored_in(&service, StorageBackend::Local);
+        assert_eq!(local.object_count(), 3);
+        let key = service.repo.file(first.id).unwrap().storage_key;
+        assert_eq!(service.repo.file(second.id).unwrap().storage_key, key);
+
+        // 仍被引用的内容不会回收
+        service.delete_file(ALICE, first.id).await.unwrap();
+        assert!(!service.reclaim_blob(&first.checksum).await.unwrap());
+        assert_eq!(local.get(&key).as_deref(), Some(&b"hello"[..]));
+        service.delete_file(BOB, second.id).await.unwrap();
+        assert!(service.reclaim_blob(&first.checksum).await.unwrap());
+        assert_eq!(local.get(&key), None);
+        assert!(
+            service
+                .repo
+                .find_blob(&first.checksum)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // 配额按文件计算，共享内容的每个所有者都计入
+        assert_eq!(service.storage_usage(BOB).await.unwrap().used_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn test_blob_integrity_check_repairs_reference_counts() {
+        let service = service();
+        let local = stored_in(&service, StorageBackend::Local);
+        let checksum = |id| service.repo.file(id).unwrap().checksum;
+        service.repo.set_blob_ref_count(&checksum(1), 5);
+        local.delete("blobs/2").await.unwrap();
+        // 删除用户时级联删除的文件不会释放引用，遗留的内容无人引用
+        service.repo.seed_blob(blob::Model {
+            hash: "orphan".to_string(),
+            size_bytes: 3,
+            ref_count: 1,
+            storage_key: "blobs/orphan".to_string(),
+            storage_backend: StorageBackend::Local.to_string(),
+            created_at: Utc::now().fixed_offset(),
+            corrupt_at: None,
+        });
+        local.insert("blobs/orphan", b"old".to_vec());
+
+        let report = service.check_blob_integrity().await.unwrap();
+        assert_eq!(
+            report,
+            BlobIntegrityReport {
+                checked: 4,
+                fixed: 1,
+                removed: 1,
+                missing: 1,
+                recovered: 0,
+            }
+        );
+        assert_eq!(local.get("blobs/orphan"), None);
+        let blob = service.repo.find_blob(&checksum(1)).await.unwrap().unwrap();
+        assert_eq!(blob.ref_count, 1);
+
+        let report = service.check_blob_integrity().await.unwrap();
+        assert_eq!((report.checked, report.fixed, report.missing), (3, 0, 1));
+    }
+
+    /// 损坏文件报告中的 (文件 ID, 标记时间)
+    async fn corrupt_files(
+        service: &FileService<InMemoryFileRepo, InMemoryStorage>,
+    ) -> Vec<(i64, String)> {
+        let response = service.list_corrupt_files(page(1, 20)).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_response().into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        body["data"]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| {
+                let corrupt_at = item["corrupt_at"].as_str().unwrap().to_string();
+                (item["id"].as_i64().unwrap(), corrupt_at)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_missing_content_is_flagged_until_restored() {
+        let service = service();
+        let local = stored_in(&service, StorageBackend::Local);
+        local.delete("blobs/2").await.unwrap();
+
+        assert_eq!(service.check_blob_integrity().await.unwrap().missing, 1);
+        let flagged = corrupt_files(&service).await;
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].0, 2);
+
+        // 再次检查不会刷新最初的标记时间
+        service.check_blob_integrity().await.unwrap();
+        assert_eq!(corrupt_files(&service).await, flagged);
+
+        // 对象恢复后清除标记
+        local.insert("blobs/2", seeded_content(2));
+        let report = service.check_blob_integrity().await.unwrap();
+        assert_eq!((report.missing, report.recovered), (0, 1));
+        assert!(corrupt_files(&service).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_deletes_only_old_unreferenced_objects() {
+        let service = service();
+        let local = stored_in(&service, StorageBackend::Local);
+        let s3 = stored_in(&service, StorageBackend::S3);
+        let old = Utc::now() - Duration::seconds(service.upload.orphan_grace_secs as i64 + 60);
+        // 已登记文件的对象即使很旧也保留
+        local.insert_at("blobs/1", seeded_content(1), old);
+        local.insert_at("1/crashed-upload", b"lost".to_vec(), old);
+        local.insert("1/uploading", b"new".to_vec());
+        local.insert_at("chunks/session/0", b"chunk".to_vec(), old);
+        // 缩略图随所属内容的对象（预置文件的内容保存在 `blobs/1`）保留
+        local.insert_at(&thumbnail_key("1", 64), b"png".to_vec(), old);
+        local.insert_at(&thumbnail_key("reclaimed", 64), b"png".to_vec(), old);
+        // 对象键相同但在另一个后端上，不算被引用
+        s3.insert_at("blobs/1", b"stale copy".to_vec(), old);
+        s3.insert_at("blobs/3", seeded_content(3), old);
+
+        let report = service.sweep_orphaned_objects().await.unwrap();
+        assert_eq!(
+            report,
+            OrphanSweepReport {
+                scanned: 9,
+                deleted: 3,
+            }
+        );
+        assert_eq!(local.get("1/crashed-upload"), None);
+        assert_eq!(local.get(&thumbnail_key("reclaimed", 64)), None);
+        assert_eq!(s3.get("blobs/1"), None);
+        for key in ["blobs/1", "blobs/2", "1/uploading", "chunks/session/0"] {
+            assert!(local.get(key).is_some(), "{key}");
+        }
+        assert!(local.get(&thumbnail_key("1", 64)).is_some());
+        assert!(s3.get("blobs/3").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_relocation_moves_legacy_content_and_deletes_duplicates() {
+        let service = service();
+        let local = stored_in(&service, StorageBackend::Local);
+        // 去重之前上传的两个相同文件，各自保存了一份副本
+        let content = b"legacy".to_vec();
+        let checksum = format!("{:x}", Sha256::digest(&content));
+        for (id, key) in [(4, "1/a"), (5, "2/b")] {
+            service.repo.seed_file(file::Model {
+                id,
+                owner_id: ALICE,
+                filename: "legacy.txt".to_string(),
+                content_type: "text/plain".to_string(),
+                size_bytes: content.len() as i64,
+                storage_key: key.to_string(),
+                created_at: Utc::now().fixed_offset(),
+                checksum: checksum.clone(),
+                description: None,
+                storage_backend: StorageBackend::Local.to_string(),
+                scan_status: ScanStatus::Clean.to_string(),
+                thumbnail_status: None,
+                thumbnail_sizes: serde_json::json!([]),
+            });
+            local.insert(key, content.clone());
+        }
+
+        // 预置的三个内容也不在由摘要决定的对象键下，一并迁移
+        let report = service.relocate_legacy_blobs().await.unwrap();
+        assert_eq!(
+            report,
+            BlobRelocationReport {
+                relocated: 4,
+                copies_deleted: 5,
+            }
+        );
+        let key = format!("blobs/{checksum}");
+        for id in [4, 5] {
+            assert_eq!(service.repo.file(id).unwrap().storage_key, key);
+        }
+        assert_eq!(
+            service
+                .repo
+                .find_blob(&checksum)
+                .await
+                .unwrap()
+                .unwrap()
+                .storage_key,
+            key
+        );
+        assert_eq!(local.get(&key), Some(content));
+        assert_eq!((local.get("1/a"), local.get("2/b")), (None, None));
+        assert_eq!(local.object_count(), 3);
+
+        // 迁移后的内容可以正常读取
+        let file = service.repo.file(3).unwrap();
+        assert_eq!(file.storage_key, format!("blobs/{}", file.checksum));
+        let s3 = stored_in(&service, StorageBackend::S3);
+        assert_eq!(s3.get(&file.storage_key), Some(seeded_content(3)));
+        assert_eq!(s3.object_count(), 1);
+
+        let report = service.relocate_legacy_blobs().await.unwrap();
+        assert_eq!(report, BlobRelocationReport::default());
+    }
+
+    #[tokio::test]
+    async fn test_relocation_skips_content_that_fails_verification() {
+        let service = service();
+        let local = stored_in(&service, StorageBackend::Local);
+        // 对象内容与记录的摘要不符时不迁移，记录和原对象保持不变
+        local.insert("blobs/1", b"tampered".to_vec());
+        service.relocate_legacy_blobs().await.unwrap();
+        let file = service.repo.file(1).unwrap();
+        assert_eq!(file.storage_key, "blobs/1");
+        assert_eq!(local.get("blobs/1").as_deref(), Some(&b"tampered"[..]));
+        assert_eq!(local.get(&format!("blobs/{}", file.checksum)), None);
+        // 临时对象已删除，文件 2 的内容照常迁移
+        assert_eq!(local.object_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_paginates_large_backends() {
+        let service = service();
+        let local = stored_in(&service, StorageBackend::Local);
+        let old = Utc::now() - Duration::seconds(service.upload.orphan_grace_secs as i64 + 60);
+        let orphans = ORPHAN_SCAN_BATCH_SIZE * 2 + 7;
+        for i in 0..orphans {
+            local.insert_at(&format!("9/orphan-{i:04}"), b"x".to_vec(), old);
+        }
+
+        let report = service.sweep_orphaned_objects().await.unwrap();
+        assert_eq!(report.deleted, orphans as u64);
+        // 只剩预置文件的对象
+        assert_eq!(local.object_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_storage_moves_content_and_updates_record() {
+        let service = service();
+        let from = service
+            .migrate_storage(1, StorageBackend::S3)
+            .await
+            .unwrap();
+        assert_eq!(from, StorageBackend::Local);
+        assert_eq!(service.repo.file(1).unwrap().storage_backend, "s3");
+        assert_eq!(
+            stored_in(&service, StorageBackend::Local).get("blobs/1"),
+            None
+        );
+        assert_eq!(
+            stored_in(&service, StorageBackend::S3).get("blobs/1"),
+            Some(seeded_content(1))
+        );
+        let model = service.downloadable_file(ALICE, 1).await.unwrap();
+        let reader = service.open_content(&model, None).await.unwrap();
+        assert_eq!(read_all(reader).await, seeded_content(1));
+
+        // 已在目标后端时不做任何事
+        let from = service
+            .migrate_storage(1, StorageBackend::S3)
+            .await
+            .unwrap();
+        assert_eq!(from, StorageBackend::S3);
+        assert_eq!(
+            stored_in(&service, StorageBackend::S3).get("blobs/1"),
+            Some(seeded_content(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_storage_keeps_record_when_content_is_corrupted() {
+        let service = service();
+        stored_in(&service, StorageBackend::Local).insert("blobs/2", b"tampered".to_vec());
+
+        let err = service
+            .migrate_storage(2, StorageBackend::S3)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(service.repo.file(2).unwrap().storage_backend, "local");
+        assert_eq!(stored_in(&service, StorageBackend::S3).get("blobs/2"), None);
+        assert!(
+            stored_in(&service, StorageBackend::Local)
+                .get("blobs/2")
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_storage_requires_configured_target() {
+        let service = service();
+        let service = FileService::new(
+            service.repo,
+            Storages::single(StorageBackend::Local, InMemoryStorage::default()),
+            UploadConfig::default(),
+        );
+        let err = service
+            .migrate_storage(1, StorageBackend::S3)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+
+        let err = service
+            .migrate_storage(99, StorageBackend::S3)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+    }
+
+    /// 返回固定结论的扫描器，`None` 表示扫描器不可用
+    struct StubScanner(Option<ScanVerdict>);
+
+    impl FileScanner for StubScanner {
+        async fn scan<R: tokio::io::AsyncRead + Unpin + Send>(
+            &self,
+            _reader: R,
+        ) -> std::io::Result<ScanVerdict> {
+            self.0
+                .clone()
+                .ok_or_else(|| std::io::Error::other("scanner unavailable"))
+        }
+
+        async fn ping(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn error_body(err: AppError) -> (StatusCode, String) {
+        let response = err.into_response();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    #[tokio::test]
+    async fn test_uploaded_file_is_downloadable_only_after_clean_scan() {
+        let service = service();
+        let form = multipart(&[Part::File("notes.txt", "text/plain", b"hello")]).await;
+        let response = service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+        assert_eq!(service.pending_scan_files(10).await.unwrap(), [response.id]);
+
+        let err = service
+            .downloadable_file(ALICE, response.id)
+            .await
+            .unwrap_err();
+        let (code, body) = error_body(err).await;
+        assert_eq!(code, StatusCode::FORBIDDEN);
+        assert!(body.contains("FILE_NOT_CLEAN"), "{body}");
+
+        // 扫描器不可用时保持待扫描状态
+        let err = service
+            .scan_file(response.id, &StubScanner(None), QuarantineAction::Move)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(service.pending_scan_files(10).await.unwrap(), [response.id]);
+
+        let scanned = service
+            .scan_file(response.id, &NoopScanner, QuarantineAction::Move)
+            .await
+            .unwrap();
+        assert_eq!(scanned, Some(ScanStatus::Clean));
+        assert!(service.pending_scan_files(10).await.unwrap().is_empty());
+        service.downloadable_file(ALICE, response.id).await.unwrap();
+
+        // 已扫描的文件和不存在的文件直接跳过
+        for id in [response.id, 99] {
+            let scanned = service
+                .scan_file(id, &NoopScanner, QuarantineAction::Move)
+                .await
+                .unwrap();
+            assert_eq!(scanned, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_infected_files_are_quarantined_and_blocked() {
+        let service = service();
+        let infected = StubScanner(Some(ScanVerdict::Infected("Eicar-Test".to_string())));
+        for id in [1, 3] {
+            service
+                .repo
+                .update_scan_status(id, &ScanStatus::PendingScan.to_string())
+                .await
+                .unwrap();
+        }
+
+        // move：内容移到 quarantine/ 前缀下，记录指向新的对象键
+        let scanned = service
+            .scan_file(1, &infected, QuarantineAction::Move)
+            .await
+            .unwrap();
+        assert_eq!(scanned, Some(ScanStatus::Infected));
+        let local = stored_in(&service, StorageBackend::Local);
+        assert_eq!(local.get("blobs/1"), None);
+        assert_eq!(local.get("quarantine/blobs/1"), Some(seeded_content(1)));
+        let model = service.repo.file(1).unwrap();
+        assert_eq!(model.storage_key, "quarantine/blobs/1");
+        assert_eq!(model.scan_status, "infected");
+
+        // delete：直接删除内容
+        service
+            .scan_file(3, &infected, QuarantineAction::Delete)
+            .await
+            .unwrap();
+        assert_eq!(stored_in(&service, StorageBackend::S3).object_count(), 0);
+
+        let secret = b"secret";
+        let expires = Utc::now().timestamp() + 60;
+        let sig = download::sign(secret, 1, expires);
+        let errors = [
+            service.downloadable_file(ADMIN, 3).await.unwrap_err(),
+            service
+                .signed_file(1, expires, &sig, secret)
+                .await
+                .unwrap_err(),
+            service
+                .download_url(ALICE, 1, None, Disposition::Attachment, secret, PUBLIC_URL)
+                .await
+                .unwrap_err(),
+        ];
+        for err in errors {
+            let (code, body) = error_body(err).await;
+            assert_eq!(code, StatusCode::FORBIDDEN);
+            assert!(body.contains("FILE_NOT_CLEAN"), "{body}");
+        }
+
+        // 被感染的文件仍可删除
+        let deleted = service.delete_file(ALICE, 1).await.unwrap();
+        assert!(service.reclaim_blob(&deleted.checksum).await.unwrap());
+        assert_eq!(local.get("quarantine/blobs/1"), None);
+    }
+
+    #[tokio::test]
+    async fn test_upload_stores_content_and_metadata() {
+        let service = service();
+        let form = multipart(&[
+            Part::Text("description", " 季度报告 "),
+            Part::File("C:\\fakepath\\notes.txt", "text/plain", b"hello"),
+        ])
+        .await;
+
+        let response = service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+        assert_eq!(response.id, 4);
+        assert_eq!(response.owner_id, ALICE);
+        assert_eq!(response.filename, "notes.txt");
+        assert_eq!(response.size_bytes, 5);
+        assert_eq!(response.content_type, "text/plain");
+        assert_eq!(response.description.as_deref(), Some("季度报告"));
+        assert_eq!(
+            response.checksum,
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+
+        let (files, _) = service
+            .repo
+            .list_by_owner(ALICE, &FileFilter::default(), page(1, 10))
+            .await
+            .unwrap();
+        let model = files.iter().find(|f| f.id == response.id).unwrap();
+        assert_eq!(model.storage_key, format!("blobs/{}", response.checksum));
+        let stored = service.storages.primary().get(&model.storage_key).unwrap();
+        assert_eq!(stored, b"hello");
+        assert_eq!(model.storage_backend, "local");
+        assert_eq!(response.scan_status, "pending_scan");
+        // 临时对象已移动到内容的对象键
+        assert_eq!(service.storages.primary().object_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_image_upload_generates_thumbnails() {
+        let service = FileService {
+            upload: UploadConfig {
+                max_file_size_bytes: 1024 * 1024,
+                ..Default::default()
+            },
+            ..service()
+        };
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgb8(image::RgbImage::new(400, 200))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        let form = multipart(&[Part::File("photo.png", "image/png", &png)]).await;
+        let response = service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+        assert_eq!(response.thumbnail_status.as_deref(), Some("pending"));
+        service
+            .repo
+            .update_scan_status(response.id, "clean")
+            .await
+            .unwrap();
+        assert_eq!(
+            service.thumbnail(ALICE, response.id, None).await.unwrap(),
+            None
+        );
+
+        let sizes = service.generate_thumbnails(response.id).await.unwrap();
+        assert_eq!(sizes, Some(vec![64, 256]));
+        // 已生成后重复投递直接跳过
+        assert_eq!(
+            service.generate_thumbnails(response.id).await.unwrap(),
+            None
+        );
+        let thumbnail = service
+            .thumbnail(ALICE, response.id, Some(100))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(thumbnail.size, 64);
+        let decoded = image::load_from_memory(&thumbnail.content).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 32));
+        let err = service.thumbnail(BOB, response.id, None).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::FORBIDDEN);
+
+        // 相同内容的新文件沿用已生成的缩略图
+        let form = multipart(&[Part::File("copy.png", "image/png", &png)]).await;
+        let copy = service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+        assert_eq!(copy.thumbnail_status.as_deref(), Some("ready"));
+        assert_eq!(copy.thumbnail_sizes, [64, 256]);
+
+        // 缩略图对象丢失时回到待生成状态
+        let local = stored_in(&service, StorageBackend::Local);
+        local
+            .delete(&thumbnail_key(&response.checksum, 256))
+            .await
+            .unwrap();
+        assert_eq!(
+            service.thumbnail(ALICE, response.id, None).await.unwrap(),
+            None
+        );
+        let model = service.repo.file(response.id).unwrap();
+        assert_eq!(model.thumbnail_status.as_deref(), Some("pending"));
+
+        // 不是图片的文件没有缩略图
+        let err = service.thumbnail(ALICE, 1, None).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_undecodable_image_keeps_thumbnails_pending() {
+        let service = FileService {
+            upload: UploadConfig {
+                max_file_size_bytes: 1024,
+                ..Default::default()
+            },
+            ..service()
+        };
+        let broken = b"\x89PNG\r\n\x1a\n truncated";
+        let form = multipart(&[Part::File("broken.png", "image/png", broken)]).await;
+        let response = service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+
+        // 生成失败不影响上传，文件保持待生成状态
+        assert!(service.generate_thumbnails(response.id).await.is_err());
+        let model = service.repo.file(response.id).unwrap();
+        assert_eq!(model.thumbnail_status.as_deref(), Some("pending"));
+        assert_eq!(
+            stored_in(&service, StorageBackend::Local).get(&model.storage_key),
+            Some(broken.to_vec())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_rejects_missing_oversized_and_disallowed_files() {
+        let service = service();
+
+        let form = multipart(&[Part::Text("description", "no file")]).await;
+        let err = service.upload(ALICE, form, PUBLIC_URL).await.unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::FileUpload(FileUploadError::MissingField(ref f)) if f == "file"
+        ));
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+
+        let form = multipart(&[Part::File("big.txt", "text/plain", &[b'x'; 17])]).await;
+        let err = service.upload(ALICE, form, PUBLIC_URL).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let form = multipart(&[Part::File("a.zip", "application/zip", b"PK")]).await;
+        let err = service.upload(ALICE, form, PUBLIC_URL).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        // 文件已写入存储，但后续字段不合法：已写入的内容需要被清理
+        let form = multipart(&[
+            Part::File("notes.txt", "text/plain", b"hello"),
+            Part::Text("description", &"长".repeat(MAX_DESCRIPTION_CHARS + 1)),
+        ])
+        .await;
+        let err = service.upload(ALICE, form, PUBLIC_URL).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+
+        // 只剩预置的两个本地文件
+        assert_eq!(service.storages.primary().object_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_upload_over_body_limit_reports_file_size_limit() {
+        let service = service();
+
+        // 不带 Content-Length 的分块请求体，读取字段头时就超出路由的请求体上限
+        let head = format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"big.txt\"\r\n\
+             Content-Type: text/plain\r\n\r\n"
+        );
+        let chunks = std::iter::once(Bytes::from(head))
+            .chain(std::iter::repeat_n(Bytes::from_static(&[b'x'; 64]), 4))
+            .map(Ok::<_, std::io::Error>);
+        let form = multipart_limited(
+            Body::from_stream(stream::iter(chunks)),
+            DefaultBodyLimit::max(32),
+        )
+        .await;
+        let err = service.upload(ALICE, form, PUBLIC_URL).await.unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::FileUpload(FileUploadError::TooLarge(16))
+        ));
+        assert_eq!(status(err), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(service.storages.primary().object_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_upload_respects_storage_quota() {
+        let service = service();
+        let seeded: i64 = (1..=3).map(|id| seeded_content(id).len() as i64).sum();
+        let usage = service.storage_usage(ALICE).await.unwrap();
+        assert_eq!(usage.used_bytes, seeded);
+        assert_eq!(usage.quota_bytes, Some(1024 * 1024 * 1024));
+
+        service.repo.set_storage_quota(ALICE, Some(seeded + 5));
+        let form = multipart(&[Part::File("a.txt", "text/plain", b"hello")]).await;
+        service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+        let usage = service.storage_usage(ALICE).await.unwrap();
+        assert_eq!(usage.used_bytes, seeded + 5);
+        assert_eq!(usage.remaining_bytes, Some(0));
+
+        // 配额已满：拒绝并说明当前用量和配额，不留下已写入的内容
+        let objects = service.storages.primary().object_count();
+        let form = multipart(&[Part::File("b.txt", "text/plain", b"!")]).await;
+        let err = service.upload(ALICE, form, PUBLIC_URL).await.unwrap_err();
+        let (code, body) = error_body(err).await;
+        assert_eq!(code, StatusCode::FORBIDDEN);
+        assert!(body.contains("STORAGE_QUOTA_EXCEEDED"), "{body}");
+        assert!(
+            body.contains(&format!(
+                "已使用 {} 字节，配额 {} 字节",
+                seeded + 5,
+                seeded + 5
+            )),
+            "{body}"
+        );
+        assert_eq!(service.storages.primary().object_count(), objects);
+
+        // 删除文件释放配额
+        service.delete_file(ALICE, 1).await.unwrap();
+        let usage = service.storage_usage(ALICE).await.unwrap();
+        assert_eq!(usage.remaining_bytes, Some(seeded_content(1).len() as i64));
+        let form = multipart(&[Part::File("b.txt", "text/plain", b"!")]).await;
+        service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+
+        // 其他用户不受影响，默认配额为 0 时不限制
+        service.repo.set_storage_quota(BOB, None);
+        let service = FileService {
+            upload: UploadConfig {
+                default_storage_quota_bytes: 0,
+                ..service.upload.clone()
+            },
+            ..service
+        };
+        let usage = service.storage_usage(BOB).await.unwrap();
+        assert_eq!((usage.quota_bytes, usage.remaining_bytes), (None, None));
+    }
+
+    #[tokio::test]
+    async fn test_register_rechecks_quota_at_insert() {
+        let service = service();
+        service.repo.set_storage_quota(BOB, Some(10));
+        // 模拟并发：检查时还有空间，登记前其他上传占用了配额
+        let quota = service.quota(BOB).await.unwrap();
+        quota.check(6).unwrap();
+        let model = |size: i64| file::ActiveModel {
+            owner_id: Set(BOB),
+            filename: Set("a.txt".to_string()),
+            content_type: Set("text/plain".to_string()),
+            size_bytes: Set(size),
+            storage_key: Set(format!("{BOB}/{size}")),
+            ..Default::default()
+        };
+        service.register(BOB, model(6), quota).await.unwrap();
+
+        let err = service.register(BOB, model(6), quota).await.unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::FileUpload(FileUploadError::QuotaExceeded { used: 6, limit: 10 })
+        ));
+        assert_eq!(service.storage_usage(BOB).await.unwrap().used_bytes, 6);
+    }
+
+    #[tokio::test]
+    async fn test_upload_accepts_metadata_after_file() {
+        let service = service();
+        let form = multipart(&[
+            Part::File("notes.txt", "text/plain", b"hello"),
+            Part::Text("tag", "ignored"),
+            Part::Text("description", "写在文件之后"),
+        ])
+        .await;
+
+        let response = service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+        assert_eq!(response.size_bytes, 5);
+        assert_eq!(response.description.as_deref(), Some("写在文件之后"));
+    }
+
+    /// PNG 文件头
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[tokio::test]
+    async fn test_upload_stores_detected_content_type() {
+        let service = service();
+        let form = multipart(&[Part::File("logo.png", "image/png", PNG)]).await;
+
+        let response = service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+        assert_eq!(response.content_type, "image/png");
+        assert_eq!(response.size_bytes, PNG.len() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_upload_rejects_content_that_does_not_match_claimed_type() {
+        let service = service();
+
+        let form = multipart(&[Part::File("logo.jpg", "image/jpeg", PNG)]).await;
+        let err = service.upload(ALICE, form, PUBLIC_URL).await.unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::FileUpload(FileUploadError::TypeMismatch { ref claimed, ref detected })
+                if claimed == "image/jpeg" && detected == "image/png"
+        ));
+        assert_eq!(status(err), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let form = multipart(&[Part::File("a.txt", "text/plain", b"<html><script>")]).await;
+        let err = service.upload(ALICE, form, PUBLIC_URL).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        assert_eq!(service.storages.primary().object_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_upload_refuses_denied_types_even_under_wildcard() {
+        let upload = UploadConfig {
+            max_file_size_bytes: 1024,
+            allowed_mime_types: vec!["image/*".to_string()],
+            ..Default::default()
+        };
+        let repo = InMemoryFileRepo::default();
+        repo.seed_user(ALICE, UserRole::User.into());
+        let service = FileService::new(
+            repo,
+            Storages::single(StorageBackend::Local, InMemoryStorage::default()),
+            upload,
+        );
+        let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\"><script/></svg>";
+
+        let form = multipart(&[Part::File("x.svg", "image/svg+xml", svg)]).await;
+        let err = service.upload(ALICE, form, PUBLIC_URL).await.unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::FileUpload(FileUploadError::TypeNotAllowed(_))
+        ));
+
+        // 冒充 PNG 的 SVG 同样被拒绝
+        let form = multipart(&[Part::File("x.png", "image/png", svg)]).await;
+        let err = service.upload(ALICE, form, PUBLIC_URL).await.unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::FileUpload(FileUploadError::TypeMismatch { .. })
+        ));
+        assert_eq!(service.storages.primary().object_count(), 0);
+    }
+
+    #[test]
+    fn test_storage_timeouts_map_to_gateway_timeout() {
+        let err = write_failed(std::io::ErrorKind::TimedOut.into());
+        assert!(matches!(err, AppError::GatewayTimeout { service } if service == STORAGE_SERVICE));
+
+        let err = write_failed(std::io::Error::other("磁盘已满"));
+        assert!(matches!(
+            err,
+            AppError::FileUpload(FileUploadError::Failed(_))
+        ));
+    }
+
+    /// 只统计写入量、不保留内容的存储
+    #[derive(Debug, Default)]
+    struct CountingStorage {
+        written: Arc<AtomicU64>,
+        largest_write: Arc<AtomicUsize>,
+    }
+
+    struct CountingWriter {
+        written: Arc<AtomicU64>,
+        largest_write: Arc<AtomicUsize>,
+    }
+
+    impl AsyncWrite for CountingWriter {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            data: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.written.fetch_add(data.len() as u64, Ordering::Relaxed);
+            self.largest_write.fetch_max(data.len(), Ordering::Relaxed);
+            Poll::Ready(Ok(data.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl Storage for CountingStorage {
+        type Writer = CountingWriter;
+        type Reader = tokio::io::Empty;
+
+        async fn create(&self, _key: &str) -> std::io::Result<Self::Writer> {
+            Ok(CountingWriter {
+                written: self.written.clone(),
+                largest_write: self.largest_write.clone(),
+            })
+        }
+
+        async fn open(&self, _key: &str) -> std::io::Result<Self::Reader> {
+            Ok(tokio::io::empty())
+        }
+
+        async fn open_range(
+            &self,
+            _key: &str,
+            _range: std::ops::Range<u64>,
+        ) -> std::io::Result<Self::Reader> {
+            Ok(tokio::io::empty())
+        }
+
+        async fn delete(&self, _key: &str) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        async fn rename(&self, _from: &str, _to: &str) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        async fn list(
+            &self,
+            _after: Option<&str>,
+            _limit: usize,
+        ) -> std::io::Result<Vec<ObjectInfo>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_large_upload_is_streamed_in_bounded_chunks() {
+        const CHUNK: usize = 64 * 1024;
+        const CHUNKS: usize = 256;
+
+        let chunk = |i: usize| Bytes::from(vec![b'a' + (i % 26) as u8; CHUNK]);
+        let head = format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; \
+             filename=\"big.txt\"\r\nContent-Type: text/plain\r\n\r\n"
+        );
+        let tail = format!("\r\n--{BOUNDARY}--\r\n");
+        // 每块之间让出一次，模拟数据从网络陆续到达；
+        // 全部立即就绪的流会被 multer 一次读入缓冲区，无法体现逐块写入
+        let body = stream::once(async move { Bytes::from(head) })
+            .chain(stream::iter((0..CHUNKS).map(chunk)))
+            .chain(stream::once(async move { Bytes::from(tail) }))
+            .then(|bytes| async move {
+                tokio::task::yield_now().await;
+                Ok::<_, std::io::Error>(bytes)
+            });
+
+        let storage = CountingStorage::default();
+        let (written, largest_write) = (storage.written.clone(), storage.largest_write.clone());
+        let upload = UploadConfig {
+            max_file_size_bytes: CHUNK * CHUNKS,
+            ..Default::default()
+        };
+        let repo = InMemoryFileRepo::default();
+        repo.seed_user(ALICE, UserRole::User.into());
+        let service = FileService::new(
+            repo,
+            Storages::single(StorageBackend::Local, storage),
+            upload,
+        );
+
+        let form = multipart_body(Body::from_stream(body)).await;
+        let response = service.upload(ALICE, form, PUBLIC_URL).await.unwrap();
+
+        let mut hasher = Sha256::new();
+        (0..CHUNKS).for_each(|i| hasher.update(chunk(i)));
+        assert_eq!(response.checksum, format!("{:x}", hasher.finalize()));
+        assert_eq!(response.size_bytes, (CHUNK * CHUNKS) as i64);
+        assert_eq!(written.load(Ordering::Relaxed), (CHUNK * CHUNKS) as u64);
+        // multer 会保留可能是分隔符前缀的尾部字节并与下一块合并，单次写入不超过两块
+        assert!(largest_write.load(Ordering::Relaxed) <= 2 * CHUNK);
+    }
+
+    /// 分片上传测试使用的分片大小
+    const CHUNK_SIZE: usize = MIN_CHUNK_SIZE_BYTES;
+
+    fn chunked_service() -> FileService<InMemoryFileRepo, InMemoryStorage> {
+        let upload = UploadConfig {
+            max_file_size_bytes: 4 * CHUNK_SIZE,
+            ..Default::default()
+        };
+        let repo = InMemoryFileRepo::default();
+        repo.seed_user(ALICE, UserRole::User.into());
+        repo.seed_user(BOB, UserRole::User.into());
+        FileService::new(
+            repo,
+            Storages::single(StorageBackend::Local, InMemoryStorage::default()),
+            upload,
+        )
+    }
+
+    /// 两个完整分片加一个不完整分片的文本内容
+    fn chunked_content() -> Vec<u8> {
+        (0..2 * CHUNK_SIZE + 1000)
+            .map(|i| b'a' + (i % 26) as u8)
+            .collect()
+    }
+
+    fn session_request(content: &[u8]) -> CreateUploadSessionRequest {
+        CreateUploadSessionRequest {
+            filename: "big.txt".to_string(),
+            content_type: "text/plain".to_string(),
+            size_bytes: content.len() as u64,
+            chunk_size: CHUNK_SIZE as u64,
+            checksum: format!("{:x}", Sha256::digest(content)),
+            description: None,
+        }
+    }
+
+    fn chunk_body(
+        content: &[u8],
+        index: usize,
+    ) -> impl Stream<Item = Result<Bytes, std::io::Error>> + use<> {
+        let end = ((index + 1) * CHUNK_SIZE).min(content.len());
+        let bytes = Bytes::copy_from_slice(&content[index * CHUNK_SIZE..end]);
+        stream::iter([Ok(bytes)])
+    }
+
+    #[tokio::test]
+    async fn test_chunked_upload_assembles_chunks_in_any_order() {
+        let service = chunked_service();
+        let content = chunked_content();
+        let session = service
+            .create_session(ALICE, session_request(&content))
+            .await
+            .unwrap();
+        assert_eq!(session.chunk_count, 3);
+        assert!(session.received_chunks.is_empty());
+
+        for index in [2, 0, 1, 0] {
+            service
+                .put_chunk(
+                    ALICE,
+                    &session.id,
+                    index as i64,
+                    chunk_body(&content, index),
+                )
+                .await
+                .unwrap();
+        }
+        let session_state = service.get_session(ALICE, &session.id).await.unwrap();
+        assert_eq!(session_state.received_chunks, vec![0, 1, 2]);
+
+        let response = service
+            .complete_session(ALICE, &session.id, PUBLIC_URL)
+            .await
+            .unwrap();
+        assert_eq!(response.size_bytes, content.len() as i64);
+        assert_eq!(response.content_type, "text/plain");
+        assert_eq!(response.checksum, format!("{:x}", Sha256::digest(&content)));
+
+        // 只剩合并后的文件，分片和会话均已删除
+        assert_eq!(service.storages.primary().object_count(), 1);
+        let stored = service.repo.file(response.id).unwrap();
+        assert_eq!(
+            service.storages.primary().get(&stored.storage_key),
+            Some(content)
+        );
+        let err = service.get_session(ALICE, &session.id).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_chunked_upload_reuses_existing_content() {
+        let service = chunked_service();
+        let content = chunked_content();
+        let mut files = Vec::new();
+        for user_id in [ALICE, BOB] {
+            let session = service
+                .create_session(user_id, session_request(&content))
+                .await
+                .unwrap();
+            for index in 0..3 {
+                service
+                    .put_chunk(
+                        user_id,
+                        &session.id,
+                        index as i64,
+                        chunk_body(&content, index),
+                    )
+                    .await
+                    .unwrap();
+            }
+            files.push(
+                service
+                    .complete_session(user_id, &session.id, PUBLIC_URL)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        // 第二个会话只校验分片，不再写入合并后的内容
+        assert_eq!(service.storages.primary().object_count(), 1);
+        let (first, second) = (
+            service.repo.file(files[0].id).unwrap(),
+            service.repo.file(files[1].id).unwrap(),
+        );
+        assert_eq!(first.storage_key, second.storage_key);
+        assert_eq!(second.owner_id, BOB);
+        let blob = service
+            .repo
+            .find_blob(&first.checksum)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(blob.ref_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_chunked_upload_rejects_incomplete_or_wrong_sized_chunks() {
+        let service = chunked_service();
+        let content = chunked_content();
+        let session = service
+            .create_session(ALICE, session_request(&content))
+            .await
+            .unwrap();
+        service
+            .put_chunk(ALICE, &session.id, 0, chunk_body(&content, 0))
+            .await
+            .unwrap();
+
+        // 最后一个分片只有 1000 字节，当作中间分片上传大小不符
+        let err = service
+            .put_chunk(ALICE, &session.id, 1, chunk_body(&content, 2))
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+        let err = service
+            .put_chunk(ALICE, &session.id, 3, chunk_body(&content, 2))
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+
+        let err = service
+            .complete_session(ALICE, &session.id, PUBLIC_URL)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("1, 2"), "{err}");
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+        assert_eq!(service.storages.primary().object_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_chunked_upload_checksum_mismatch_keeps_chunks() {
+        let service = chunked_service();
+        let content = chunked_content();
+        let session = service
+            .create_session(ALICE, session_request(&content))
+            .await
+            .unwrap();
+
+        let mut corrupted = content.clone();
+        corrupted[CHUNK_SIZE] ^= 1;
+        for index in 0..3 {
+            service
+                .put_chunk(
+                    ALICE,
+                    &session.id,
+                    index as i64,
+                    chunk_body(&corrupted, index),
+                )
+                .await
+                .unwrap();
+        }
+        let err = service
+            .complete_session(ALICE, &session.id, PUBLIC_URL)
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+        assert_eq!(service.storages.primary().object_count(), 3);
+
+        // 重新上传出错的分片后可以再次完成
+        service
+            .put_chunk(ALICE, &session.id, 1, chunk_body(&content, 1))
+            .await
+            .unwrap();
+        service
+            .complete_session(ALICE, &session.id, PUBLIC_URL)
+            .await
+            .unwrap();
+        assert_eq!(service.storages.primary().object_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_chunked_upload_session_is_private_to_owner() {
+        let service = chunked_service();
+        let content = chunked_content();
+        let session = service
+            .create_session(ALICE, session_request(&content))
+            .await
+            .unwrap();
+
+        let err = service.get_session(BOB, &session.id).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+        let err = service
+            .put_chunk(BOB, &session.id, 0, chunk_body(&content, 0))
+            .await
+            .unwrap_err();
+        assert_eq!(status(err), StatusCode::NOT_FOUND);
+        assert_eq!(service.storages.primary().object_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_create_session_validates_limits() {
+        let service = chunked_service();
+        let content = chunked_content();
+
+        let mut req = session_request(&content);
+        req.size_bytes = 5 * CHUNK_SIZE as u64;
+        let err = service.create_session(ALICE, req).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let mut req = session_request(&content);
+        req.chunk_size = CHUNK_SIZE as u64 - 1;
+        let err = service.create_session(ALICE, req).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::BAD_REQUEST);
+
+        let mut req = session_request(&content);
+        req.content_type = "text/html".to_string();
+        let err = service.create_session(ALICE, req).await.unwrap_err();
+        assert_eq!(status(err), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        service
+            .repo
+            .set_storage_quota(ALICE, Some(content.len() as i64 - 1));
+        let err = service
+            .create_session(ALICE, session_request(&content))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::FileUpload(FileUploadError::QuotaExceeded { used: 0, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_purge_expired_sessions_removes_chunks() {
+        let service = chunked_service();
+        let content = chunked_content();
+        let active = service
+            .create_session(ALICE, session_request(&content))
+            .await
+            .unwrap();
+        let expired = service
+            .create_session(ALICE, session_request(&content))
+            .await
+            .unwrap();
+        for id in [&active.id, &expired.id] {
+            service
+                .put_chunk(ALICE, id, 0, chunk_body(&content, 0))
+                .await
+                .unwrap();
+        }
+        service.repo.set_session_expiry(
+            &expired.id,
+            Utc::now().fixed_offset() - Duration::seconds(1),
+        );
+
+        assert_eq!(service.purge_expired_sessions().await.unwrap(), 1);
+        assert_eq!(service.storages.primary().object_count(), 1);
+        assert!(service.get_session(ALICE, &active.id).await.is_ok());
+        assert!(
+            service
+                .repo
+                .find_session(&expired.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(service.purge_expired_sessions().await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,134 @@
+//! 按文件头识别文件类型
+//!
+//! 客户端声明的 Content-Type 不可信，上传时根据前 [`SNIFF_BYTES`] 字节识别真实类型。
+//! 二进制格式按魔数匹配；文本内容再区分 SVG、HTML、XML 等可携带脚本的标记格式与纯文本。
+
+/// 识别类型所需的文件头长度（字节）
+pub const SNIFF_BYTES: usize = 512;
+
+/// 无法识别的二进制内容
+pub const UNKNOWN_BINARY: &str = "application/octet-stream";
+
+/// 无法进一步区分的文本内容
+pub const PLAIN_TEXT: &str = "text/plain";
+
+/// 二进制格式签名：(偏移, 魔数, MIME 类型)
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (8, b"WEBP", "image/webp"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"\x7fELF", "application/x-executable"),
+    (0, b"MZ", "application/x-msdownload"),
+];
+
+/// 根据文件头识别 MIME 类型
+///
+/// 无法识别的二进制内容返回 [`UNKNOWN_BINARY`]，不含标记的文本返回 [`PLAIN_TEXT`]。
+pub fn detect(head: &[u8]) -> &'static str {
+    let head = &head[..head.len().min(SNIFF_BYTES)];
+    let signature = SIGNATURES.iter().find(|(offset, magic, _)| {
+        head.get(*offset..offset + magic.len()) == Some(magic)
+            && (*offset == 0 || head.starts_with(b"RIFF"))
+    });
+    if let Some((_, _, mime)) = signature {
+        return mime;
+    }
+    if !is_text(head) {
+        return UNKNOWN_BINARY;
+    }
+    detect_markup(head).unwrap_or(PLAIN_TEXT)
+}
+
+/// 声明的类型与识别出的类型是否一致
+///
+/// 纯文本无法进一步区分，声明为任意 `text/*` 类型都视为一致；其他类型必须完全相同。
+pub fn is_consistent(claimed: &str, detected: &str) -> bool {
+    claimed == detected || (detected == PLAIN_TEXT && claimed.starts_with("text/"))
+}
+
+/// 合法 UTF-8 且不含除制表、换行、换页、转义以外的控制字符
+///
+/// 文件头可能在多字节字符中间截断，末尾不完整的字符不视为错误。
+fn is_text(head: &[u8]) -> bool {
+    let valid = match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    };
+    valid
+        && !head
+            .iter()
+            .any(|&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+}
+
+/// 识别可携带脚本的标记格式
+fn detect_markup(head: &[u8]) -> Option<&'static str> {
+    let text = String::from_utf8_lossy(head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head))
+        .trim_start()
+        .to_ascii_lowercase();
+    if text.contains("<svg") {
+        return Some("image/svg+xml");
+    }
+    if [
+        "<!doctype html",
+        "<html",
+        "<script",
+        "<head",
+        "<body",
+        "<iframe",
+    ]
+    .iter()
+    .any(|tag| text.contains(tag))
+    {
+        return Some("text/html");
+    }
+    if text.starts_with("<?xml") {
+        return Some("application/xml");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_binary_signatures_and_markup() {
+        assert_eq!(detect(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), "image/png");
+        assert_eq!(detect(b"\xff\xd8\xff\xe0\0\x10JFIF"), "image/jpeg");
+        assert_eq!(detect(b"RIFF\x24\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(detect(b"%PDF-1.7\n"), "application/pdf");
+        assert_eq!(detect(b"\0\x01\x02\x03"), UNKNOWN_BINARY);
+
+        assert_eq!(detect("季度报告\n第一行".as_bytes()), PLAIN_TEXT);
+        assert_eq!(
+            detect(
+                b"\xef\xbb\xbf<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\">"
+            ),
+            "image/svg+xml"
+        );
+        assert_eq!(detect(b"  <!DOCTYPE html><html>"), "text/html");
+        assert_eq!(detect(b"hello <script>alert(1)</script>"), "text/html");
+        assert_eq!(detect(b"<?xml version=\"1.0\"?><note/>"), "application/xml");
+    }
+
+    #[test]
+    fn test_truncated_utf8_head_is_still_text() {
+        let mut head = "a".repeat(SNIFF_BYTES - 1).into_bytes();
+        head.extend_from_slice("报".as_bytes());
+        assert_eq!(detect(&head), PLAIN_TEXT);
+    }
+
+    #[test]
+    fn test_plain_text_is_consistent_with_any_text_type() {
+        assert!(is_consistent("text/csv", PLAIN_TEXT));
+        assert!(is_consistent("image/png", "image/png"));
+        assert!(!is_consistent("image/png", "image/jpeg"));
+        assert!(!is_consistent("text/plain", "text/html"));
+        assert!(!is_consistent("application/json", PLAIN_TEXT));
+    }
+}
@@ -0,0 +1,124 @@
+//! 图片缩略图
+//!
+//! 图片上传后由后台任务按 `upload.thumbnail_sizes` 生成 PNG 缩略图：解码一次原图，按比例缩放到不超过
+//! 各边长的正方形内（原图更小时不放大）。缩略图属于内容而不是文件，保存在与内容相同的存储后端的
+//! `thumbnails/{checksum}/{size}.png` 下，引用同一内容的文件共用；内容不再被引用后由孤立对象清理删除。
+
+use std::io::Cursor;
+
+use image::{ImageFormat, ImageReader, Limits};
+
+use entity::file;
+
+/// 缩略图的对象键前缀
+pub const THUMBNAIL_KEY_PREFIX: &str = "thumbnails/";
+
+/// 缩略图的 MIME 类型
+pub const THUMBNAIL_CONTENT_TYPE: &str = "image/png";
+
+/// 可以生成缩略图的图片类型（上传时按文件头识别出的类型）
+const SUPPORTED_MIME_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// 解码原图时允许分配的内存上限（字节），防止小文件声明超大尺寸耗尽内存
+const MAX_DECODE_ALLOC_BYTES: u64 = 256 * 1024 * 1024;
+
+/// 该类型的文件是否生成缩略图
+pub fn supports(content_type: &str) -> bool {
+    SUPPORTED_MIME_TYPES.contains(&content_type)
+}
+
+/// 缩略图在存储后端中的对象键
+pub fn thumbnail_key(checksum: &str, size: u32) -> String {
+    format!("{THUMBNAIL_KEY_PREFIX}{checksum}/{size}.png")
+}
+
+/// 缩略图对象键对应的内容摘要，不是缩略图的对象键返回 `None`
+pub fn checksum_of(key: &str) -> Option<&str> {
+    key.strip_prefix(THUMBNAIL_KEY_PREFIX)?
+        .split_once('/')
+        .map(|(checksum, _)| checksum)
+}
+
+/// 文件记录中已生成的缩略图边长，升序
+pub fn sizes_of(model: &file::Model) -> Vec<u32> {
+    let mut sizes: Vec<u32> =
+        serde_json::from_value(model.thumbnail_sizes.clone()).unwrap_or_default();
+    sizes.sort_unstable();
+    sizes
+}
+
+/// 从已生成的边长中选择最接近 `requested` 的一个，距离相同时取较大的；未指定时取最大的
+pub fn closest(sizes: &[u32], requested: Option<u32>) -> Option<u32> {
+    let Some(requested) = requested else {
+        return sizes.iter().copied().max();
+    };
+    sizes
+        .iter()
+        .copied()
+        .min_by_key(|&size| (size.abs_diff(requested), std::cmp::Reverse(size)))
+}
+
+/// 解码图片并按每个边长生成 PNG 缩略图，返回 `(边长, PNG 内容)`
+///
+/// 解码和缩放占用 CPU，应在阻塞线程池中调用。
+pub fn render(source: &[u8], sizes: &[u32]) -> image::ImageResult<Vec<(u32, Vec<u8>)>> {
+    let mut limits = Limits::default();
+    limits.max_alloc = Some(MAX_DECODE_ALLOC_BYTES);
+    let mut reader = ImageReader::new(Cursor::new(source)).with_guessed_format()?;
+    reader.limits(limits);
+    let image = reader.decode()?;
+
+    sizes
+        .iter()
+        .map(|&size| {
+            let thumbnail = if image.width() > size || image.height() > size {
+                image.thumbnail(size, size)
+            } else {
+                image.clone()
+            };
+            let mut png = Vec::new();
+            thumbnail.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+            Ok((size, png))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbImage};
+
+    #[test]
+    fn test_render_scales_down_and_keeps_aspect_ratio() {
+        let mut source = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::new(400, 200))
+            .write_to(&mut Cursor::new(&mut source), ImageFormat::Jpeg)
+            .unwrap();
+
+        let rendered = render(&source, &[64, 1000]).unwrap();
+        let dimensions: Vec<_> = rendered
+            .iter()
+            .map(|(size, png)| {
+                let image = image::load_from_memory_with_format(png, ImageFormat::Png).unwrap();
+                (*size, image.width(), image.height())
+            })
+            .collect();
+        assert_eq!(dimensions, [(64, 64, 32), (1000, 400, 200)]);
+
+        assert!(render(b"not an image", &[64]).is_err());
+    }
+
+    #[test]
+    fn test_closest_size_and_key() {
+        assert_eq!(closest(&[64, 256], Some(100)), Some(64));
+        assert_eq!(closest(&[64, 256], Some(160)), Some(256));
+        assert_eq!(closest(&[64, 256], Some(1000)), Some(256));
+        assert_eq!(closest(&[64, 256], None), Some(256));
+        assert_eq!(closest(&[], Some(64)), None);
+
+        let key = thumbnail_key("abc", 64);
+        assert_eq!(key, "thumbnails/abc/64.png");
+        assert_eq!(checksum_of(&key), Some("abc"));
+        assert_eq!(checksum_of("blobs/abc"), None);
+    }
+}
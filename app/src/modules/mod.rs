@@ -2,12 +2,37 @@
 //!
 //! 包含应用的各项业务功能实现，如用户管理等。
 
+/// 管理模块（运行时日志级别等运维接口）
+pub mod admin;
 /// API 文档路由
 mod docs;
+/// 文件管理模块（文件元数据查询）
+pub mod file;
 /// 404 处理
 mod not_found;
+/// 限流状态模块（查询客户端限流配额）
+pub mod rate_limit;
+/// 静态文件服务与单页应用回退
+pub mod static_files;
+/// 运行统计模块（请求耗时分位数）
+pub mod stats;
 /// 用户管理模块（注册、登录、获取用户信息）
 pub mod user;
+/// 根路径欢迎响应
+mod welcome;
 
 pub use docs::*;
 pub use not_found::*;
+pub use welcome::*;
+
+/// 各模块在 OpenAPI 文档中的标签，文档按此顺序列出
+///
+/// 新增带文档的模块时，在这里登记它的 `TAG`，并在其 `routes()` 中调用 [`ModuleTagRouter::with_module_tag`]。
+pub const MODULE_TAGS: &[ModuleTag] = &[
+    user::TAG,
+    file::TAG,
+    admin::TAG,
+    rate_limit::TAG,
+    stats::TAG,
+    docs::TAG,
+];
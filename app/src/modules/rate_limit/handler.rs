@@ -0,0 +1,24 @@
+use crate::modules::docs::with_defaults;
+use crate::{ApiResponse, RateLimitStatus};
+use aide::transform::TransformOperation;
+use axum::extract::Extension;
+
+/// 查询限流配额处理器
+///
+/// 返回全局限流中间件对本次请求的检查结果（查询本身也计入配额）。
+///
+/// # 参数
+/// * `status` - 限流配额状态（由全局限流中间件注入）
+///
+/// # 返回
+/// 返回令牌桶容量、剩余请求数和完全恢复所需秒数
+pub async fn status(Extension(status): Extension<RateLimitStatus>) -> ApiResponse<RateLimitStatus> {
+    ApiResponse::success(status)
+}
+
+/// 查询限流配额 API 文档
+pub fn status_docs(op: TransformOperation) -> TransformOperation {
+    op.description("查询当前客户端的限流配额")
+        .response::<200, ApiResponse<RateLimitStatus>>()
+        .with(with_defaults)
+}
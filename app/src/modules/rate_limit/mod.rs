@@ -0,0 +1,31 @@
+//! 限流状态模块
+//!
+//! 提供客户端查询自身限流配额的接口。
+
+use crate::AppState;
+use crate::modules::docs::{ModuleTag, ModuleTagRouter};
+use aide::axum::ApiRouter;
+use aide::axum::routing::get_with;
+use std::sync::Arc;
+
+mod handler;
+
+/// 限流状态模块在 OpenAPI 文档中的标签
+pub const TAG: ModuleTag = ModuleTag::new("限流", "查询当前客户端的限流配额");
+
+/// 构建限流状态模块的路由
+///
+/// 配置以下端点：
+/// - GET /status - 查询当前客户端的限流配额
+///
+/// # 参数
+/// * `state` - 应用状态
+///
+/// # 返回
+/// 返回配置好的路由器
+pub fn routes(state: Arc<AppState>) -> ApiRouter {
+    ApiRouter::new()
+        .api_route("/status", get_with(handler::status, handler::status_docs))
+        .with_module_tag(&TAG)
+        .with_state(state)
+}
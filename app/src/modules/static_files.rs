@@ -0,0 +1,247 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    extract::Request,
+    http::Method,
+    response::{IntoResponse, Response},
+};
+use tower::ServiceExt;
+use tower_http::services::{ServeDir, ServeFile};
+use tracing::{info, warn};
+
+use super::handle_404;
+use crate::core::config::{AppConfig, ServerConfig};
+use crate::routes::v1;
+
+/// 不参与单页应用回退的路径前缀，这些路径未匹配时始终返回 404
+///
+/// 包括 API 前缀、`docs.path`（按配置）以及健康检查和静态文件路径。
+fn reserved_prefixes(config: &AppConfig) -> Vec<String> {
+    [v1::PREFIX, config.docs.path.as_str(), "/health", "/static"]
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+/// 构建静态文件路由和全局回退处理
+///
+/// - `/static/*` 映射到配置的静态文件目录
+/// - 设置 `spa_path` 时，该路径下的请求由 `spa_dir` 中的前端构建产物响应，见 [`spa_service`]
+/// - 启用 `spa_fallback` 时，未匹配的非 API 路径返回 `index.html`
+/// - 其余未匹配路径交给 [`handle_404`]
+///
+/// # 参数
+/// * `config` - 应用配置，使用 `server` 段以及决定保留前缀的 `docs.path`
+///
+/// # 返回
+/// 可直接 merge 到应用路由中的路由器
+pub fn routes<S>(config: &AppConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let reserved = reserved_prefixes(config);
+    let config = &config.server;
+    let router = Router::new().nest_service("/static", ServeDir::new(&config.static_dir));
+    let router = match spa_service(config, &reserved) {
+        Some((path, service)) => router.nest_service(path, service),
+        None => router,
+    };
+
+    if config.spa_fallback {
+        let index = Path::new(&config.static_dir).join("index.html");
+        let reserved = Arc::new(reserved);
+        router.fallback(move |request: Request| {
+            spa_fallback(index.clone(), reserved.clone(), request)
+        })
+    } else {
+        router.fallback(handle_404)
+    }
+}
+
+/// 挂载在 `spa_path` 下的前端单页应用
+///
+/// 存在的文件直接返回，不存在的路径返回 `spa_dir/index.html`，刷新或直接打开深层链接时由前端路由接管。
+/// 回退使用 `fallback` 而不是 `not_found_service`，深层链接的状态码为 200 而不是 404。
+/// 未设置 `spa_path`、`spa_dir` 不存在或路径与保留前缀（`reserved`）冲突时不挂载。
+fn spa_service<'a>(
+    config: &'a ServerConfig,
+    reserved: &[String],
+) -> Option<(&'a str, ServeDir<ServeFile>)> {
+    let path = config.spa_path.as_deref()?;
+    if reserved
+        .iter()
+        .any(|prefix| is_under_prefix(path, prefix) || is_under_prefix(prefix, path))
+    {
+        warn!(spa_path = path, "spa_path 与 API 路径冲突，未挂载前端应用");
+        return None;
+    }
+    let dir = Path::new(&config.spa_dir);
+    if !dir.is_dir() {
+        warn!(spa_dir = %dir.display(), "前端应用目录不存在，未挂载 {path}");
+        return None;
+    }
+
+    info!(spa_path = path, spa_dir = %dir.display(), "已挂载前端应用");
+    Some((
+        path,
+        ServeDir::new(dir).fallback(ServeFile::new(dir.join("index.html"))),
+    ))
+}
+
+/// 单页应用回退处理器
+///
+/// 仅对非 API 路径的 GET/HEAD 请求返回 `index.html`，由前端路由接管；
+/// API 路径和其他方法仍返回标准 404 响应。
+async fn spa_fallback(index: PathBuf, reserved: Arc<Vec<String>>, request: Request) -> Response {
+    let is_api_path = reserved
+        .iter()
+        .any(|prefix| is_under_prefix(request.uri().path(), prefix));
+    let is_read = matches!(*request.method(), Method::GET | Method::HEAD);
+
+    if is_api_path || !is_read {
+        return handle_404(request).await;
+    }
+
+    match ServeFile::new(index).oneshot(request).await {
+        Ok(response) => response.into_response(),
+        Err(never) => match never {},
+    }
+}
+
+/// 判断路径是否位于指定前缀之下（`/v1` 匹配 `/v1` 和 `/v1/...`，不匹配 `/v10`）
+fn is_under_prefix(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode};
+
+    async fn get(router: Router, uri: &str) -> (StatusCode, String) {
+        let response = router
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    fn config(dir: &Path, spa_fallback: bool) -> AppConfig {
+        std::fs::write(dir.join("app.js"), "console.log('hi');").unwrap();
+        std::fs::write(dir.join("index.html"), "<div id=\"app\"></div>").unwrap();
+        let mut config = AppConfig::default();
+        config.server.static_dir = dir.to_string_lossy().into_owned();
+        config.server.spa_fallback = spa_fallback;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_serves_static_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = routes(&config(dir.path(), false));
+
+        let (status, body) = get(router, "/static/app.js").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "console.log('hi');");
+    }
+
+    #[tokio::test]
+    async fn test_spa_fallback_serves_index_for_unknown_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = routes(&config(dir.path(), true));
+
+        let (status, body) = get(router, "/dashboard/settings").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "<div id=\"app\"></div>");
+    }
+
+    #[tokio::test]
+    async fn test_spa_fallback_keeps_api_404() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = routes(&config(dir.path(), true));
+
+        let (status, body) = get(router, "/v1/unknown").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(!body.contains("id=\"app\""));
+
+        // 按配置的文档路径排除，而不是固定的 /docs
+        let mut config = config(dir.path(), true);
+        config.docs.path = "/api-docs".to_string();
+        let (status, _) = get(routes(&config), "/api-docs/missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_path_404_without_spa_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = routes(&config(dir.path(), false));
+
+        let (status, _) = get(router, "/dashboard").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    fn spa_config(dir: &Path, spa_path: &str) -> AppConfig {
+        std::fs::write(dir.join("index.html"), "<div id=\"spa\"></div>").unwrap();
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        std::fs::write(dir.join("assets/main.js"), "boot();").unwrap();
+        let mut config = AppConfig::default();
+        config.server.spa_path = Some(spa_path.to_string());
+        config.server.spa_dir = dir.to_string_lossy().into_owned();
+        config
+    }
+
+    #[tokio::test]
+    async fn test_spa_path_serves_files_and_deep_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = spa_config(dir.path(), "/app");
+
+        let (status, body) = get(routes(&config), "/app/assets/main.js").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "boot();");
+
+        for uri in ["/app", "/app/", "/app/orders/42"] {
+            let (status, body) = get(routes(&config), uri).await;
+            assert_eq!(status, StatusCode::OK, "{uri}");
+            assert_eq!(body, "<div id=\"spa\"></div>", "{uri}");
+        }
+
+        // 挂载路径之外仍然返回 404
+        let (status, _) = get(routes(&config), "/dashboard").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_spa_path_skipped_when_missing_or_conflicting() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = spa_config(dir.path(), "/v1/app");
+        let reserved = reserved_prefixes(&config);
+        assert!(spa_service(&config.server, &reserved).is_none());
+
+        // 自定义的文档路径同样不能被前端应用占用
+        config.docs.path = "/api-docs".to_string();
+        config.server.spa_path = Some("/api-docs/app".to_string());
+        let reserved = reserved_prefixes(&config);
+        assert!(spa_service(&config.server, &reserved).is_none());
+
+        config.server.spa_path = Some("/app".to_string());
+        config.server.spa_dir = dir.path().join("missing").to_string_lossy().into_owned();
+        assert!(spa_service(&config.server, &reserved).is_none());
+        let (status, _) = get(routes(&config), "/app/orders").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_is_under_prefix() {
+        assert!(is_under_prefix("/v1", "/v1"));
+        assert!(is_under_prefix("/v1/user/me", "/v1"));
+        assert!(!is_under_prefix("/v10", "/v1"));
+        assert!(!is_under_prefix("/app", "/v1"));
+    }
+}
@@ -0,0 +1,27 @@
+use crate::modules::docs::with_defaults;
+use crate::{ApiResponse, AppState, core::latency::RouteLatency};
+use aide::transform::TransformOperation;
+use axum::extract::State;
+use std::sync::Arc;
+
+/// 请求耗时分位数处理器
+///
+/// 返回每条路由最近请求的 p50 / p90 / p99 耗时，数据在重启后清空。
+///
+/// # 参数
+/// * `state` - 应用状态（包含请求耗时统计）
+///
+/// # 返回
+/// 按路由排序的耗时分位数列表
+pub async fn latency(State(state): State<Arc<AppState>>) -> ApiResponse<RouteLatency> {
+    ApiResponse::simple_list(state.latency.snapshot()).with_kind("RouteLatencyList")
+}
+
+/// 请求耗时分位数 API 文档
+pub fn latency_docs(op: TransformOperation) -> TransformOperation {
+    op.description(
+        "查询各路由最近请求的耗时分位数（仅管理员，或拥有 `stats:read` 授权范围的 API 密钥）",
+    )
+    .response::<200, ApiResponse<RouteLatency>>()
+    .with(with_defaults)
+}
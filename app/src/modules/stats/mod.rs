@@ -0,0 +1,40 @@
+//! 运行统计模块
+//!
+//! 提供管理员和监控系统可用的轻量运行指标，如按路由统计的请求耗时分位数。
+
+use crate::AppState;
+use crate::core::middleware::{Access, AccessRouter};
+use crate::modules::docs::{ModuleTag, ModuleTagRouter};
+use aide::axum::ApiRouter;
+use aide::axum::routing::get_with;
+use std::sync::Arc;
+
+mod handler;
+
+/// 运行统计模块在 OpenAPI 文档中的标签
+pub const TAG: ModuleTag = ModuleTag::new("统计", "运行统计，如各路由的请求耗时分位数");
+
+/// 机器客户端读取运行统计所需的 API 密钥授权范围
+pub const STATS_SCOPE: &str = "stats:read";
+
+/// 构建运行统计模块的路由
+///
+/// 配置以下端点（均需要管理员权限，或拥有 [`STATS_SCOPE`] 授权范围的 API 密钥）：
+/// - GET /latency - 各路由最近请求的耗时分位数（p50/p90/p99）
+///
+/// # 参数
+/// * `state` - 应用状态，包含请求耗时统计
+///
+/// # 返回
+/// 返回配置好的路由器
+pub fn routes(state: Arc<AppState>) -> ApiRouter {
+    ApiRouter::new()
+        .api_route_access(
+            "/latency",
+            Access::AdminOrApiKey(STATS_SCOPE),
+            &state,
+            get_with(handler::latency, handler::latency_docs),
+        )
+        .with_module_tag(&TAG)
+        .with_state(state)
+}
@@ -1,5 +1,17 @@
+use anyhow::Context;
 use schemars::JsonSchema;
+use sea_orm::Set;
+use sea_orm::prelude::DateTimeWithTimeZone;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use validator::Validate;
+
+use crate::{
+    error::AuthError,
+    shared::{FromDto, password},
+};
+use entity::enums::UserRole;
+use entity::user::{self, UserId};
 
 /// 用户注册请求
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -17,17 +29,180 @@ pub struct RegisterRequest {
     pub password_confirm: String,
 }
 
+impl FromDto<RegisterRequest> for user::ActiveModel {
+    type Error = AuthError;
+
+    /// 校验用户名长度（3-20字符）、密码长度（至少8字符）以及两次密码是否一致
+    fn validate(req: &RegisterRequest) -> Result<(), AuthError> {
+        if req.username.len() < 3 || req.username.len() > 20 {
+            return Err(AuthError::InvalidUsername);
+        }
+
+        if req.password.len() < 8 {
+            return Err(AuthError::PasswordTooShort);
+        }
+
+        if req.password != req.password_confirm {
+            return Err(AuthError::PasswordMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// 使用 Argon2 哈希密码，新用户默认为激活状态的普通用户
+    fn from_dto(req: RegisterRequest) -> Result<Self, AuthError> {
+        let password_hash = password::hash_password(&req.password).context("构建新用户失败")?;
+
+        Ok(user::ActiveModel {
+            username: Set(req.username),
+            email: Set(req.email),
+            password_hash: Set(password_hash),
+            status: Set(0), // 激活状态
+            role: Set(UserRole::User.into()),
+            ..Default::default()
+        })
+    }
+}
+
 /// 用户注册响应
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RegisterResponse {
     /// 用户ID
-    pub id: i32,
+    pub id: UserId,
+
+    /// 用户名
+    pub username: String,
+
+    /// 邮箱
+    pub email: String,
+}
+
+/// 用户搜索结果中的用户信息
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UserDto {
+    /// 用户ID
+    pub id: UserId,
 
     /// 用户名
     pub username: String,
 
     /// 邮箱
     pub email: String,
+
+    /// 注册时间
+    pub created_at: DateTimeWithTimeZone,
+}
+
+impl From<user::Model> for UserDto {
+    fn from(model: user::Model) -> Self {
+        Self {
+            id: model.id,
+            username: model.username,
+            email: model.email,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// 用户偏好设置
+///
+/// 对应 `user.preferences` 列中的已知键。列中的其他键（如前端自行保存的设置）原样保留，
+/// 不出现在响应中，修改偏好时也不会被覆盖。作为修改请求时，未提供或为 `null` 的字段保持不变。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct UserPreferencesDto {
+    /// 时区（IANA 名称，如 `Asia/Shanghai`）
+    #[validate(length(min = 1, max = 64, message = "时区长度必须在 1-64 个字符之间"))]
+    pub timezone: Option<String>,
+
+    /// 界面主题（如 `light`、`dark`）
+    #[validate(length(min = 1, max = 32, message = "主题长度必须在 1-32 个字符之间"))]
+    pub theme: Option<String>,
+
+    /// 是否接收邮件通知
+    pub email_notifications: Option<bool>,
+}
+
+impl UserPreferencesDto {
+    /// 从 `preferences` 列读取已知键，类型不符的键视为未设置
+    pub fn from_column(preferences: &Value) -> Self {
+        let string = |key: &str| preferences.get(key)?.as_str().map(str::to_string);
+        Self {
+            timezone: string("timezone"),
+            theme: string("theme"),
+            email_notifications: preferences
+                .get("email_notifications")
+                .and_then(Value::as_bool),
+        }
+    }
+
+    /// 将提供的字段合并到 `preferences` 列中，其他键保持不变
+    ///
+    /// 列的值不是 JSON 对象时（如旧数据中的 `null`）先替换为空对象。
+    pub fn merge_into(self, preferences: &mut Value) {
+        if !preferences.is_object() {
+            *preferences = Value::Object(Default::default());
+        }
+        let map = preferences.as_object_mut().expect("已确保为对象");
+        if let Some(timezone) = self.timezone {
+            map.insert("timezone".to_string(), timezone.into());
+        }
+        if let Some(theme) = self.theme {
+            map.insert("theme".to_string(), theme.into());
+        }
+        if let Some(enabled) = self.email_notifications {
+            map.insert("email_notifications".to_string(), enabled.into());
+        }
+    }
+}
+
+/// CSV 导入文件中的一行，列名与字段名一致，多余的列会被忽略
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportRow {
+    /// 用户名（首尾空白会被去除）
+    pub username: String,
+
+    /// 邮箱地址（首尾空白会被去除）
+    pub email: String,
+
+    /// 密码（原样使用）
+    pub password: String,
+}
+
+/// CSV 导入中失败的一行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ImportRowError {
+    /// CSV 中的行号（表头为第 1 行）
+    pub row: u64,
+
+    /// 出错的列；整行无法解析或写入时为空
+    pub field: Option<String>,
+
+    /// 错误原因
+    pub message: String,
+}
+
+/// CSV 导入结果
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ImportReport {
+    /// 数据行数（不含表头）
+    pub total: usize,
+
+    /// 成功创建的用户数
+    pub succeeded: usize,
+
+    /// 失败的行数
+    pub failed: usize,
+
+    /// 每个失败行的错误，按行号排列
+    pub errors: Vec<ImportRowError>,
+}
+
+/// 用户搜索查询参数（分页参数见 [`CursorParams`](crate::core::response::CursorParams)）
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct SearchUsersQuery {
+    /// 搜索关键字，匹配用户名或邮箱的任意部分（不区分大小写）；不指定时列出全部用户
+    #[serde(default)]
+    pub q: String,
 }
 
 /// 用户登录请求
@@ -44,7 +219,7 @@ pub struct LoginRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct LoginResponse {
     /// 用户ID
-    pub id: i32,
+    pub id: UserId,
 
     /// 用户名
     pub username: String,
@@ -58,3 +233,282 @@ pub struct LoginResponse {
     /// Token 过期时间（秒）
     pub expires_in: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::user_id;
+
+    #[test]
+    fn test_export_field_parse_list() {
+        assert_eq!(
+            ExportField::parse_list("id, email,created_at").unwrap(),
+            [ExportField::Id, ExportField::Email, ExportField::CreatedAt]
+        );
+        assert!(
+            ExportField::parse_list("id,password_hash")
+                .unwrap_err()
+                .contains("password_hash")
+        );
+        assert!(ExportField::parse_list(" , ").is_err());
+    }
+
+    #[test]
+    fn test_export_field_json_row_keeps_types() {
+        let now = chrono::Utc::now().fixed_offset();
+        let user = user::Model {
+            id: user_id(7),
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            password_hash: "hash".to_string(),
+            status: 1,
+            role: 0,
+            last_login_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            created_at: now,
+            updated_at: now,
+            storage_quota_bytes: None,
+            storage_used_bytes: 0,
+            preferences: serde_json::json!({}),
+        };
+        let row = ExportField::json_row(
+            &[
+                ExportField::Id,
+                ExportField::Email,
+                ExportField::LastLoginAt,
+                ExportField::CreatedAt,
+            ],
+            &user,
+        );
+        assert_eq!(
+            Value::Object(row),
+            serde_json::json!({
+                "id": user_id(7),
+                "email": "alice@example.com",
+                "last_login_at": null,
+                "created_at": now.to_rfc3339(),
+            })
+        );
+    }
+    use crate::shared::IntoModel;
+    use sea_orm::ActiveValue;
+    use serde_json::json;
+
+    #[test]
+    fn test_preferences_merge_keeps_unknown_keys() {
+        let mut column = json!({
+            "theme": "light",
+            "email_notifications": "yes",
+            "sidebar": { "collapsed": true },
+        });
+        // 类型不符的键视为未设置
+        assert_eq!(
+            UserPreferencesDto::from_column(&column),
+            UserPreferencesDto {
+                theme: Some("light".to_string()),
+                ..Default::default()
+            }
+        );
+
+        UserPreferencesDto {
+            timezone: Some("Asia/Shanghai".to_string()),
+            email_notifications: Some(false),
+            ..Default::default()
+        }
+        .merge_into(&mut column);
+        assert_eq!(
+            column,
+            json!({
+                "theme": "light",
+                "timezone": "Asia/Shanghai",
+                "email_notifications": false,
+                "sidebar": { "collapsed": true },
+            })
+        );
+
+        let mut column = Value::Null;
+        UserPreferencesDto::default().merge_into(&mut column);
+        assert_eq!(column, json!({}));
+    }
+
+    fn register_req(username: &str, password: &str, confirm: &str) -> RegisterRequest {
+        RegisterRequest {
+            username: username.to_string(),
+            email: format!("{username}@example.com"),
+            password: password.to_string(),
+            password_confirm: confirm.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_register_request_into_model() {
+        let model: user::ActiveModel = register_req("alice", "password123", "password123")
+            .into_model()
+            .unwrap();
+
+        assert_eq!(model.username, ActiveValue::Set("alice".to_string()));
+        assert_eq!(
+            model.email,
+            ActiveValue::Set("alice@example.com".to_string())
+        );
+        let hash = model.password_hash.clone().unwrap();
+        assert_ne!(hash, "password123");
+        assert!(password::verify_password("password123", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_register_request_into_model_defaults() {
+        let model: user::ActiveModel = register_req("alice", "password123", "password123")
+            .into_model()
+            .unwrap();
+
+        assert_eq!(model.status, ActiveValue::Set(0));
+        assert_eq!(model.role, ActiveValue::Set(UserRole::User.into()));
+        // 由数据库生成或登录流程维护的字段不在转换中设置
+        assert!(model.id.is_not_set());
+        assert!(model.last_login_at.is_not_set());
+        assert!(model.failed_login_attempts.is_not_set());
+        assert!(model.locked_until.is_not_set());
+        assert!(model.created_at.is_not_set());
+    }
+
+    #[test]
+    fn test_register_request_validation() {
+        let into = |req: RegisterRequest| IntoModel::<user::ActiveModel>::into_model(req);
+
+        assert!(matches!(
+            into(register_req("ab", "password123", "password123")),
+            Err(AuthError::InvalidUsername)
+        ));
+        assert!(matches!(
+            into(register_req("bob", "short", "short")),
+            Err(AuthError::PasswordTooShort)
+        ));
+        assert!(matches!(
+            into(register_req("bob", "password123", "password456")),
+            Err(AuthError::PasswordMismatch)
+        ));
+    }
+}
+
+/// 用户导出可选列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportField {
+    Id,
+    Username,
+    Email,
+    Status,
+    Role,
+    LastLoginAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl ExportField {
+    /// 全部可导出列（不含密码哈希等敏感字段），未指定 `fields` 时使用
+    pub const ALL: [Self; 8] = [
+        Self::Id,
+        Self::Username,
+        Self::Email,
+        Self::Status,
+        Self::Role,
+        Self::LastLoginAt,
+        Self::CreatedAt,
+        Self::UpdatedAt,
+    ];
+
+    /// 列名，同时作为 CSV 表头和 `fields` 参数中的取值
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Username => "username",
+            Self::Email => "email",
+            Self::Status => "status",
+            Self::Role => "role",
+            Self::LastLoginAt => "last_login_at",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+        }
+    }
+
+    /// 解析逗号分隔的列名列表，如 `id,email,created_at`
+    pub fn parse_list(fields: &str) -> Result<Vec<Self>, String> {
+        let fields = fields
+            .split(',')
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(|name| {
+                Self::ALL
+                    .into_iter()
+                    .find(|f| f.name() == name)
+                    .ok_or_else(|| format!("不支持导出的字段：{name}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if fields.is_empty() {
+            return Err("fields 不能为空".to_string());
+        }
+        Ok(fields)
+    }
+
+    /// 按 `fields` 的顺序取出用户的各列，组成 JSON 对象
+    ///
+    /// 数值列保持为数字，时间使用 RFC 3339 字符串，空值为 `null`。
+    pub fn json_row(fields: &[Self], user: &user::Model) -> serde_json::Map<String, Value> {
+        fields
+            .iter()
+            .map(|field| {
+                let value = match field {
+                    Self::Id => serde_json::json!(user.id),
+                    Self::Status => user.status.into(),
+                    Self::Role => user.role.into(),
+                    Self::LastLoginAt => user
+                        .last_login_at
+                        .map_or(Value::Null, |t| t.to_rfc3339().into()),
+                    _ => field.value(user).into(),
+                };
+                (field.name().to_string(), value)
+            })
+            .collect()
+    }
+
+    /// 取出该列在 CSV 中的值，时间使用 RFC 3339，空值为空字符串
+    pub fn value(self, user: &user::Model) -> String {
+        match self {
+            Self::Id => user.id.to_string(),
+            Self::Username => user.username.clone(),
+            Self::Email => user.email.clone(),
+            Self::Status => user.status.to_string(),
+            Self::Role => user.role.to_string(),
+            Self::LastLoginAt => user
+                .last_login_at
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default(),
+            Self::CreatedAt => user.created_at.to_rfc3339(),
+            Self::UpdatedAt => user.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// 用户导出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// CSV 附件
+    #[default]
+    Csv,
+
+    /// 流式 JSON 列表（`UserList` 信封）
+    Json,
+}
+
+/// 用户导出查询参数
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct ExportQuery {
+    /// 逗号分隔的导出列，如 `id,email,created_at`；不指定时导出全部列
+    pub fields: Option<String>,
+
+    /// 导出格式：`csv`（默认）或 `json`
+    #[serde(default)]
+    pub format: ExportFormat,
+}
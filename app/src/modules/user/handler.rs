@@ -1,12 +1,32 @@
-use crate::{ApiResponse, AppError, AppState, core::middleware::CurrentUser, shared::FromState};
+use crate::modules::docs::with_defaults;
+use crate::{
+    ApiResponse, AppError, AppState, ValidationError,
+    core::{
+        Validated,
+        middleware::CurrentUser,
+        response::{CursorPage, CursorParams, ItemResult, StreamingListResponse},
+    },
+    shared::FromState,
+};
+use aide::openapi::{MediaType, SchemaObject};
 use aide::transform::TransformOperation;
 use axum::Json;
-use axum::extract::{Extension, State};
+use axum::body::Body;
+use axum::extract::{Extension, Query, State};
+use axum::http::{HeaderName, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use futures_util::TryStreamExt;
+use indexmap::IndexMap;
+use schemars::json_schema;
 use std::sync::Arc;
-use tracing::{info, instrument};
+use tokio_util::io::ReaderStream;
+use tracing::{error, info, instrument};
 
-use super::dto::{LoginRequest, LoginResponse, RegisterRequest, RegisterResponse};
-use super::service::UserService;
+use super::dto::{
+    ExportField, ExportFormat, ExportQuery, LoginRequest, LoginResponse, RegisterRequest,
+    RegisterResponse, SearchUsersQuery, UserDto, UserPreferencesDto,
+};
+use super::service::{BulkCreateOutcome, UserService};
 
 /// 用户注册处理器
 ///
@@ -35,8 +55,8 @@ pub async fn register(
 /// 用户注册 API 文档
 pub fn register_docs(op: TransformOperation) -> TransformOperation {
     op.description("用户注册")
-        .tag("认证")
         .response::<201, ApiResponse<RegisterResponse>>()
+        .with(with_defaults)
 }
 
 /// 用户登录处理器
@@ -66,13 +86,17 @@ pub async fn login(
 /// 用户登录 API 文档
 pub fn login_docs(op: TransformOperation) -> TransformOperation {
     op.description("用户登录")
-        .tag("认证")
         .response::<200, ApiResponse<LoginResponse>>()
+        .response_with::<423, ApiResponse<()>, _>(|res| {
+            res.description("连续登录失败次数过多，账户已被临时锁定")
+        })
+        .with(with_defaults)
 }
 
 /// 获取当前用户处理器
 ///
 /// 获取当前登录用户的信息。需要在 Authorization header 中提供有效的 JWT 令牌。
+/// 遇到瞬时数据库错误时重试一次。
 ///
 /// # 参数
 /// * `state` - 应用状态（包含数据库连接）
@@ -88,7 +112,10 @@ pub async fn me(
     info!("获取当前用户信息，用户ID: {}", current_user.user_id);
 
     let user_service = UserService::from_state(&state);
-    let response = user_service.get_user(current_user.user_id).await?;
+    let response = state
+        .read_retry
+        .run(|| user_service.get_user(current_user.user_id))
+        .await?;
 
     Ok(ApiResponse::success(response))
 }
@@ -96,6 +123,255 @@ pub async fn me(
 /// 获取当前用户 API 文档
 pub fn me_docs(op: TransformOperation) -> TransformOperation {
     op.description("获取当前登录用户信息")
-        .tag("用户")
         .response::<200, ApiResponse<RegisterResponse>>()
+        .with(with_defaults)
+}
+
+/// 获取偏好设置处理器
+///
+/// 获取当前登录用户的偏好设置（时区、主题、邮件通知），未设置的项为 `null`。
+/// 遇到瞬时数据库错误时重试一次。
+///
+/// # 返回
+/// 成功返回偏好设置，用户不存在返回 404
+#[instrument(skip(state, current_user))]
+pub async fn get_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+) -> Result<ApiResponse<UserPreferencesDto>, AppError> {
+    let user_service = UserService::from_state(&state);
+    let preferences = state
+        .read_retry
+        .run(|| user_service.get_preferences(current_user.user_id))
+        .await?;
+    Ok(ApiResponse::success(preferences).with_kind("UserPreferences"))
+}
+
+/// 获取偏好设置 API 文档
+pub fn get_preferences_docs(op: TransformOperation) -> TransformOperation {
+    op.description("获取当前登录用户的偏好设置")
+        .response::<200, ApiResponse<UserPreferencesDto>>()
+        .with(with_defaults)
+}
+
+/// 修改偏好设置处理器
+///
+/// 合并修改当前登录用户的偏好设置：请求中提供的字段覆盖原值，未提供或为 `null` 的字段保持不变。
+///
+/// # 返回
+/// 成功返回修改后的偏好设置，参数无效返回 400，用户不存在返回 404
+#[instrument(skip(state, current_user))]
+pub async fn update_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Validated(req): Validated<UserPreferencesDto>,
+) -> Result<ApiResponse<UserPreferencesDto>, AppError> {
+    info!("修改偏好设置，用户ID: {}", current_user.user_id);
+
+    let preferences = UserService::from_state(&state)
+        .update_preferences(current_user.user_id, req)
+        .await?;
+    Ok(ApiResponse::success(preferences).with_kind("UserPreferences"))
+}
+
+/// 修改偏好设置 API 文档
+pub fn update_preferences_docs(op: TransformOperation) -> TransformOperation {
+    op.description("合并修改当前登录用户的偏好设置，未提供的字段保持不变")
+        .response::<200, ApiResponse<UserPreferencesDto>>()
+        .with(with_defaults)
+}
+
+/// 批量创建用户处理器
+///
+/// 用于数据迁移和批量邀请，请求体为注册请求的数组（最多 100 项），校验规则与注册相同。
+/// 全部用户在同一个事务中创建：任一项失败时整批回滚，返回 207 Multi-Status，
+/// 按请求顺序给出每一项的结果（失败原因，或因其他项失败而未创建的 424）。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接）
+/// * `current_user` - 当前登录的管理员（由认证中间件注入）
+/// * `reqs` - 注册请求列表
+///
+/// # 返回
+/// 全部成功返回 201 和创建的用户列表，存在失败项返回 207，列表为空或超过 100 项返回 400
+#[instrument(skip(state, current_user, reqs), fields(count = reqs.len()))]
+pub async fn bulk_create_users(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Json(reqs): Json<Vec<RegisterRequest>>,
+) -> Result<Response, AppError> {
+    let outcome = UserService::from_state(&state).bulk_create(reqs).await?;
+
+    Ok(match outcome {
+        BulkCreateOutcome::Created(users) => {
+            info!(
+                target: "audit",
+                user_id = %current_user.user_id,
+                created = ?users.iter().map(|u| u.id).collect::<Vec<_>>(),
+                "批量创建用户"
+            );
+            (
+                StatusCode::CREATED,
+                ApiResponse::simple_list(users).with_kind("UserList"),
+            )
+                .into_response()
+        }
+        BulkCreateOutcome::Rejected(results) => ApiResponse::multi_status(results).into_response(),
+    })
+}
+
+/// 批量创建用户 API 文档
+pub fn bulk_create_users_docs(op: TransformOperation) -> TransformOperation {
+    op.description("在同一个事务中批量创建用户，任一项失败时整批回滚并返回逐项结果（仅管理员）")
+        .response::<201, ApiResponse<UserDto>>()
+        .response::<207, ApiResponse<ItemResult<UserDto>>>()
+        .with(with_defaults)
+}
+
+/// 搜索用户处理器
+///
+/// 按用户名或邮箱搜索用户，使用游标分页，适合无限滚动列表：首次请求不带 `cursor`，
+/// 之后把响应中的 `next_cursor` 原样传回，`next_cursor` 为 `null` 时没有更多数据。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接）
+/// * `search` - 搜索关键字
+/// * `page` - 游标和每页数据量（1-100，默认 20）
+///
+/// # 返回
+/// 成功返回当前页用户和下一页游标，游标无效或关键字过长返回 400
+#[instrument(skip(state))]
+pub async fn search_users(
+    State(state): State<Arc<AppState>>,
+    Query(search): Query<SearchUsersQuery>,
+    Query(page): Query<CursorParams>,
+) -> Result<CursorPage<UserDto>, AppError> {
+    let cursor = page.cursor().map_err(ValidationError::custom)?;
+    UserService::from_state(&state)
+        .search_cursor(&search.q, cursor, page.per_page())
+        .await
+}
+
+/// 搜索用户 API 文档
+pub fn search_users_docs(op: TransformOperation) -> TransformOperation {
+    op.description("按用户名或邮箱搜索用户，游标分页（仅管理员）")
+        .response::<200, CursorPage<UserDto>>()
+        .with(with_defaults)
+}
+
+/// 导出数据在内存管道中的缓冲大小（字节）
+const EXPORT_PIPE_BYTES: usize = 64 * 1024;
+
+/// 导出 CSV 中每个用户一行的平均字节数，用于估算响应总大小
+const EXPORT_AVG_ROW_BYTES: u64 = 128;
+
+/// 导出的记录总数响应头
+const TOTAL_RECORDS_HEADER: &str = "x-total-records";
+
+/// 导出响应的估算总字节数响应头
+///
+/// 不使用 `Content-Length`：估算值与实际长度不一致时，连接会在响应体写完前被中断，客户端收到的是损坏的文件。
+const ESTIMATED_LENGTH_HEADER: &str = "x-content-length";
+
+/// 导出用户处理器
+///
+/// 默认以 CSV 附件的形式流式返回全部用户：后台任务分批查询并写入内存管道，
+/// 响应体边读边发送，不会把完整文件加载到内存中。`format=json` 时以 [`StreamingListResponse`]
+/// 流式返回 `UserList` 列表，同样分批查询、边读边写。
+///
+/// 开始导出前先统计用户数，通过 `X-Total-Records` 返回记录总数（JSON 格式同时输出为 `total_items`），
+/// CSV 格式通过 `X-Content-Length` 返回按 [`EXPORT_AVG_ROW_BYTES`] 估算的总字节数，供客户端显示进度。
+///
+/// # 参数
+/// * `state` - 应用状态（包含数据库连接）
+/// * `current_user` - 当前登录的管理员（由认证中间件注入）
+/// * `query` - 导出列（`fields=id,email,created_at`，不指定时导出全部列）和导出格式
+///
+/// # 返回
+/// 成功返回 `text/csv` 附件或 JSON 列表，字段名无效时返回 400
+///
+/// 响应头发出后若查询失败，只能记录日志并提前结束响应体，客户端会收到截断的文件。
+#[instrument(skip(state, current_user))]
+pub async fn export_users(
+    State(state): State<Arc<AppState>>,
+    Extension(current_user): Extension<CurrentUser>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    let fields = match query.fields.as_deref() {
+        Some(fields) => ExportField::parse_list(fields).map_err(ValidationError::custom)?,
+        None => ExportField::ALL.to_vec(),
+    };
+    info!(
+        target: "audit",
+        user_id = %current_user.user_id,
+        fields = ?fields.iter().map(|f| f.name()).collect::<Vec<_>>(),
+        format = ?query.format,
+        "导出用户数据"
+    );
+
+    let user_service = UserService::from_state(&state);
+    let total = user_service.count_users().await?;
+
+    if query.format == ExportFormat::Json {
+        let rows = user_service
+            .stream_all()
+            .map_ok(move |user| ExportField::json_row(&fields, &user));
+        let mut response = StreamingListResponse::new(rows)
+            .with_kind("UserList")
+            .with_total(total)
+            .into_response();
+        response
+            .headers_mut()
+            .insert(HeaderName::from_static(TOTAL_RECORDS_HEADER), total.into());
+        return Ok(response);
+    }
+
+    let (reader, writer) = tokio::io::duplex(EXPORT_PIPE_BYTES);
+    tokio::spawn(async move {
+        match user_service.export_csv(&fields, writer).await {
+            Ok(count) => info!(count, "用户导出完成"),
+            Err(e) => error!(error = %e, "用户导出中断"),
+        }
+    });
+
+    let filename = format!("users-{}.csv", chrono::Utc::now().format("%Y-%m-%d"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+            (
+                HeaderName::from_static(TOTAL_RECORDS_HEADER),
+                total.to_string(),
+            ),
+            (
+                HeaderName::from_static(ESTIMATED_LENGTH_HEADER),
+                (total * EXPORT_AVG_ROW_BYTES).to_string(),
+            ),
+        ],
+        Body::from_stream(ReaderStream::new(reader)),
+    )
+        .into_response())
+}
+
+/// 导出用户 API 文档
+pub fn export_users_docs(op: TransformOperation) -> TransformOperation {
+    op.description("以 CSV 附件或 JSON 列表流式导出全部用户（仅管理员，每分钟限 1 次）")
+        .response_with::<200, String, _>(|mut res| {
+            res.inner().content = IndexMap::from_iter([(
+                "text/csv".to_string(),
+                MediaType {
+                    schema: Some(SchemaObject {
+                        json_schema: json_schema!({ "type": "string" }),
+                        external_docs: None,
+                        example: None,
+                    }),
+                    ..Default::default()
+                },
+            )]);
+            res.description("CSV 文件内容（`format=json` 时为 `UserList` 列表）")
+        })
+        .with(with_defaults)
 }
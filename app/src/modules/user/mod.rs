@@ -3,15 +3,27 @@
 //! 提供用户注册、登录、获取当前用户信息等功能。
 
 use crate::AppState;
+use crate::core::middleware::{Access, AccessRouter};
+use crate::modules::docs::{ModuleTag, ModuleTagRouter};
 use aide::axum::ApiRouter;
 use aide::axum::routing::{get_with, post_with};
 use std::sync::Arc;
+use std::time::Duration;
 use tower_governor::{GovernorLayer, governor::GovernorConfigBuilder};
 
 pub mod dto;
 mod handler;
+mod repo;
 mod service;
 
+pub(crate) use service::{ImportOutcome, UserService};
+
+/// 用户模块在 OpenAPI 文档中的标签
+pub const TAG: ModuleTag = ModuleTag::new(
+    "用户",
+    "用户注册、登录、个人信息与偏好设置，以及管理员的批量用户操作",
+);
+
 /// 构建用户模块的路由
 ///
 /// 配置以下端点：
@@ -52,12 +64,68 @@ pub fn routes(state: Arc<AppState>) -> ApiRouter {
             "/login",
             post_with(handler::login, handler::login_docs).layer(GovernorLayer::new(login_limiter)),
         )
-        .api_route(
+        .api_route_access(
             "/me",
-            get_with(handler::me, handler::me_docs).layer(axum::middleware::from_fn_with_state(
-                state.clone(),
-                crate::core::middleware::auth::require_auth,
-            )),
+            Access::Authenticated,
+            &state,
+            get_with(handler::me, handler::me_docs),
+        )
+        .with_module_tag(&TAG)
+        .with_state(state)
+}
+
+/// 构建用户批量操作的路由，挂载在 `/users` 下
+///
+/// 配置以下端点：
+/// - POST /bulk - 在同一个事务中批量创建用户（需要管理员权限）
+/// - GET /search - 按用户名或邮箱搜索用户，游标分页（需要管理员权限）
+/// - GET /export - 以 CSV 流式导出全部用户（需要管理员权限，限速1req/min）
+/// - GET /me/preferences - 获取当前用户的偏好设置（需要认证）
+/// - PATCH /me/preferences - 合并修改当前用户的偏好设置（需要认证）
+///
+/// # 参数
+/// * `state` - 应用状态，包含数据库和服务实例
+///
+/// # 返回
+/// 返回配置好的路由器
+pub fn collection_routes(state: Arc<AppState>) -> ApiRouter {
+    // 导出：全表扫描，每分钟 1 次
+    let export_limiter = GovernorConfigBuilder::default()
+        .period(Duration::from_secs(60))
+        .burst_size(1)
+        .use_headers()
+        .finish()
+        .unwrap();
+
+    ApiRouter::new()
+        .api_route_access(
+            "/bulk",
+            Access::Admin,
+            &state,
+            post_with(handler::bulk_create_users, handler::bulk_create_users_docs),
+        )
+        .api_route_access(
+            "/search",
+            Access::Admin,
+            &state,
+            get_with(handler::search_users, handler::search_users_docs),
+        )
+        .api_route_access(
+            "/export",
+            Access::Admin,
+            &state,
+            get_with(handler::export_users, handler::export_users_docs)
+                .layer(GovernorLayer::new(export_limiter)),
+        )
+        .api_route_access(
+            "/me/preferences",
+            Access::Authenticated,
+            &state,
+            get_with(handler::get_preferences, handler::get_preferences_docs).patch_with(
+                handler::update_preferences,
+                handler::update_preferences_docs,
+            ),
         )
+        .with_module_tag(&TAG)
         .with_state(state)
 }
@@ -0,0 +1,560 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::{Expr, Func, LikeExpr};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set,
+};
+use serde_json::Value;
+
+use crate::{AppState, core::tx::Tx, shared::FromState};
+use entity::user::{self, UserId};
+
+/// 用户数据访问接口
+///
+/// 将服务层与具体的数据库实现解耦：生产环境使用 [`SeaOrmUserRepo`]，
+/// 单元测试使用内存实现，无需启动数据库即可覆盖业务逻辑。
+pub trait UserRepo: Send + Sync {
+    /// 根据用户 ID 查询用户
+    async fn find_by_id(&self, id: UserId) -> Result<Option<user::Model>, DbErr>;
+
+    /// 根据用户名查询用户
+    async fn find_by_username(&self, username: &str) -> Result<Option<user::Model>, DbErr>;
+
+    /// 根据邮箱查询用户
+    async fn find_by_email(&self, email: &str) -> Result<Option<user::Model>, DbErr>;
+
+    /// 根据用户名或邮箱查询用户（用于登录）
+    async fn find_by_username_or_email(&self, value: &str) -> Result<Option<user::Model>, DbErr>;
+
+    /// 插入新用户，返回写入后的完整模型
+    async fn insert(&self, model: user::ActiveModel) -> Result<user::Model, DbErr>;
+
+    /// 在同一个事务中插入多个用户，按顺序返回写入后的模型；任一插入失败时全部回滚
+    async fn insert_all(&self, models: Vec<user::ActiveModel>) -> Result<Vec<user::Model>, DbErr>;
+
+    /// 记录登录成功：更新最近登录时间并清除失败计数和锁定状态
+    async fn update_last_login(&self, id: UserId, at: DateTimeWithTimeZone) -> Result<(), DbErr>;
+
+    /// 记录登录失败：更新连续失败次数和锁定截止时间
+    async fn record_failed_login(
+        &self,
+        id: UserId,
+        attempts: i32,
+        locked_until: Option<DateTimeWithTimeZone>,
+    ) -> Result<(), DbErr>;
+
+    /// 写入用户的偏好设置（整列替换，合并由调用方完成）
+    async fn update_preferences(&self, id: UserId, preferences: Value) -> Result<(), DbErr>;
+
+    /// 按 ID 升序查询 `after` 之后（`None` 为从头开始）的最多 `limit` 个用户（键集分页，用于批量导出）
+    async fn list_after(
+        &self,
+        after: Option<UserId>,
+        limit: u64,
+    ) -> Result<Vec<user::Model>, DbErr>;
+
+    /// 统计用户总数
+    async fn count(&self) -> Result<u64, DbErr>;
+
+    /// 按 `(created_at, id)` 升序查询用户名或邮箱包含 `query`（不区分大小写）的用户，
+    /// 从 `after` 之后开始，最多 `limit` 个（游标分页）；`query` 为空时不过滤
+    async fn search_after(
+        &self,
+        query: &str,
+        after: Option<(DateTimeWithTimeZone, UserId)>,
+        limit: u64,
+    ) -> Result<Vec<user::Model>, DbErr>;
+}
+
+/// 基于 SeaORM 的用户数据访问实现
+#[derive(Debug, Clone)]
+pub struct SeaOrmUserRepo {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmUserRepo {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+impl FromState for SeaOrmUserRepo {
+    fn from_state(app: &AppState) -> Self {
+        Self::new(app.db.clone())
+    }
+}
+
+impl UserRepo for SeaOrmUserRepo {
+    async fn find_by_id(&self, id: UserId) -> Result<Option<user::Model>, DbErr> {
+        user::Entity::find_by_id(id).one(&self.db).await
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<user::Model>, DbErr> {
+        user::Entity::find()
+            .filter(user::Column::Username.eq(username))
+            .one(&self.db)
+            .await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<user::Model>, DbErr> {
+        user::Entity::find()
+            .filter(user::Column::Email.eq(email))
+            .one(&self.db)
+            .await
+    }
+
+    async fn find_by_username_or_email(&self, value: &str) -> Result<Option<user::Model>, DbErr> {
+        user::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(user::Column::Username.eq(value))
+                    .add(user::Column::Email.eq(value)),
+            )
+            .one(&self.db)
+            .await
+    }
+
+    async fn insert(&self, model: user::ActiveModel) -> Result<user::Model, DbErr> {
+        model.insert(&self.db).await
+    }
+
+    async fn insert_all(&self, models: Vec<user::ActiveModel>) -> Result<Vec<user::Model>, DbErr> {
+        // 出错时提前返回，未提交的事务在丢弃时回滚
+        let tx = Tx::begin(&self.db).await?;
+        let mut inserted = Vec::with_capacity(models.len());
+        for model in models {
+            inserted.push(model.insert(&tx).await?);
+        }
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    async fn update_last_login(&self, id: UserId, at: DateTimeWithTimeZone) -> Result<(), DbErr> {
+        user::ActiveModel {
+            id: Set(id),
+            last_login_at: Set(Some(at)),
+            failed_login_attempts: Set(0),
+            locked_until: Set(None),
+            ..Default::default()
+        }
+        .update(&self.db)
+        .await
+        .map(|_| ())
+    }
+
+    async fn record_failed_login(
+        &self,
+        id: UserId,
+        attempts: i32,
+        locked_until: Option<DateTimeWithTimeZone>,
+    ) -> Result<(), DbErr> {
+        user::ActiveModel {
+            id: Set(id),
+            failed_login_attempts: Set(attempts),
+            locked_until: Set(locked_until),
+            ..Default::default()
+        }
+        .update(&self.db)
+        .await
+        .map(|_| ())
+    }
+
+    async fn update_preferences(&self, id: UserId, preferences: Value) -> Result<(), DbErr> {
+        user::ActiveModel {
+            id: Set(id),
+            preferences: Set(preferences),
+            updated_at: Set(chrono::Utc::now().fixed_offset()),
+            ..Default::default()
+        }
+        .update(&self.db)
+        .await
+        .map(|_| ())
+    }
+
+    async fn list_after(
+        &self,
+        after: Option<UserId>,
+        limit: u64,
+    ) -> Result<Vec<user::Model>, DbErr> {
+        user::Entity::find()
+            .apply_if(after, |query, id| query.filter(user::Column::Id.gt(id)))
+            .order_by_asc(user::Column::Id)
+            .limit(limit)
+            .all(&self.db)
+            .await
+    }
+
+    async fn count(&self) -> Result<u64, DbErr> {
+        user::Entity::find().count(&self.db).await
+    }
+
+    async fn search_after(
+        &self,
+        query: &str,
+        after: Option<(DateTimeWithTimeZone, UserId)>,
+        limit: u64,
+    ) -> Result<Vec<user::Model>, DbErr> {
+        let mut select = user::Entity::find();
+        if !query.is_empty() {
+            // 转义 LIKE 通配符，只做字面子串匹配
+            let escaped = query
+                .to_lowercase()
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            let contains = |column: user::Column| {
+                Expr::expr(Func::lower(Expr::col(column)))
+                    .like(LikeExpr::new(format!("%{escaped}%")).escape('\\'))
+            };
+            select = select.filter(
+                Condition::any()
+                    .add(contains(user::Column::Username))
+                    .add(contains(user::Column::Email)),
+            );
+        }
+        if let Some((created_at, id)) = after {
+            select = select.filter(
+                Expr::tuple([
+                    Expr::col(user::Column::CreatedAt).into(),
+                    Expr::col(user::Column::Id).into(),
+                ])
+                .gt(Expr::tuple([created_at.into(), id.into()])),
+            );
+        }
+        select
+            .order_by_asc(user::Column::CreatedAt)
+            .order_by_asc(user::Column::Id)
+            .limit(limit)
+            .all(&self.db)
+            .await
+    }
+}
+
+/// 内存用户数据访问实现（仅用于测试）
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct InMemoryUserRepo {
+    users: std::sync::Mutex<Vec<user::Model>>,
+}
+
+#[cfg(test)]
+impl InMemoryUserRepo {
+    /// 预置一个用户，返回其 ID
+    pub fn seed(&self, model: user::Model) -> UserId {
+        let id = model.id;
+        self.users.lock().unwrap().push(model);
+        id
+    }
+
+    /// 获取指定用户的快照
+    pub fn get(&self, id: UserId) -> Option<user::Model> {
+        self.users
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|u| u.id == id)
+            .cloned()
+    }
+
+    fn find(&self, pred: impl Fn(&user::Model) -> bool) -> Option<user::Model> {
+        self.users.lock().unwrap().iter().find(|u| pred(u)).cloned()
+    }
+
+    fn modify(&self, id: UserId, f: impl FnOnce(&mut user::Model)) -> Result<(), DbErr> {
+        let mut users = self.users.lock().unwrap();
+        let user = users
+            .iter_mut()
+            .find(|u| u.id == id)
+            .ok_or_else(|| DbErr::RecordNotFound(format!("user {id}")))?;
+        f(user);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl UserRepo for InMemoryUserRepo {
+    async fn find_by_id(&self, id: UserId) -> Result<Option<user::Model>, DbErr> {
+        Ok(self.find(|u| u.id == id))
+    }
+
+    async fn find_by_username(&self, username: &str) -> Result<Option<user::Model>, DbErr> {
+        Ok(self.find(|u| u.username == username))
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<user::Model>, DbErr> {
+        Ok(self.find(|u| u.email == email))
+    }
+
+    async fn find_by_username_or_email(&self, value: &str) -> Result<Option<user::Model>, DbErr> {
+        Ok(self.find(|u| u.username == value || u.email == value))
+    }
+
+    async fn insert(&self, mut model: user::ActiveModel) -> Result<user::Model, DbErr> {
+        let now = chrono::Utc::now().fixed_offset();
+        let mut users = self.users.lock().unwrap();
+        let required = |name: &str| DbErr::Custom(format!("缺少字段 {name}"));
+        let model = user::Model {
+            id: model
+                .id
+                .take()
+                .unwrap_or(crate::test_support::user_id(users.len() as u16 + 1)),
+            username: model.username.take().ok_or_else(|| required("username"))?,
+            email: model.email.take().ok_or_else(|| required("email"))?,
+            password_hash: model
+                .password_hash
+                .take()
+                .ok_or_else(|| required("password_hash"))?,
+            status: model.status.take().unwrap_or_default(),
+            role: model.role.take().unwrap_or_default(),
+            last_login_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            created_at: now,
+            updated_at: now,
+            storage_quota_bytes: None,
+            storage_used_bytes: 0,
+            preferences: model
+                .preferences
+                .take()
+                .unwrap_or_else(|| serde_json::json!({})),
+        };
+        users.push(model.clone());
+        Ok(model)
+    }
+
+    async fn insert_all(&self, models: Vec<user::ActiveModel>) -> Result<Vec<user::Model>, DbErr> {
+        let before = self.users.lock().unwrap().len();
+        let mut inserted = Vec::with_capacity(models.len());
+        for model in models {
+            match self.insert(model).await {
+                Ok(model) => inserted.push(model),
+                Err(e) => {
+                    self.users.lock().unwrap().truncate(before);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(inserted)
+    }
+
+    async fn update_last_login(&self, id: UserId, at: DateTimeWithTimeZone) -> Result<(), DbErr> {
+        self.modify(id, |u| {
+            u.last_login_at = Some(at);
+            u.failed_login_attempts = 0;
+            u.locked_until = None;
+        })
+    }
+
+    async fn record_failed_login(
+        &self,
+        id: UserId,
+        attempts: i32,
+        locked_until: Option<DateTimeWithTimeZone>,
+    ) -> Result<(), DbErr> {
+        self.modify(id, |u| {
+            u.failed_login_attempts = attempts;
+            u.locked_until = locked_until;
+        })
+    }
+
+    async fn update_preferences(&self, id: UserId, preferences: Value) -> Result<(), DbErr> {
+        self.modify(id, |u| u.preferences = preferences)
+    }
+
+    async fn list_after(
+        &self,
+        after: Option<UserId>,
+        limit: u64,
+    ) -> Result<Vec<user::Model>, DbErr> {
+        let mut users: Vec<_> = self
+            .users
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|u| after.is_none_or(|id| u.id > id))
+            .cloned()
+            .collect();
+        users.sort_by_key(|u| u.id);
+        users.truncate(limit as usize);
+        Ok(users)
+    }
+
+    async fn count(&self) -> Result<u64, DbErr> {
+        Ok(self.users.lock().unwrap().len() as u64)
+    }
+
+    async fn search_after(
+        &self,
+        query: &str,
+        after: Option<(DateTimeWithTimeZone, UserId)>,
+        limit: u64,
+    ) -> Result<Vec<user::Model>, DbErr> {
+        let query = query.to_lowercase();
+        let mut users: Vec<_> = self
+            .users
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|u| {
+                u.username.to_lowercase().contains(&query)
+                    || u.email.to_lowercase().contains(&query)
+            })
+            .filter(|u| after.is_none_or(|after| (u.created_at, u.id) > after))
+            .cloned()
+            .collect();
+        users.sort_by_key(|u| (u.created_at, u.id));
+        users.truncate(limit as usize);
+        Ok(users)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::user_id;
+    use chrono::{Duration, Utc};
+    use migration::{Migrator, MigratorTrait};
+
+    async fn migrated_repo() -> SeaOrmUserRepo {
+        // 内存 SQLite 每个连接是独立的数据库，限制为单连接才能看到迁移结果
+        let mut options = sea_orm::ConnectOptions::new("sqlite::memory:");
+        options.max_connections(1);
+        let db = sea_orm::Database::connect(options).await.unwrap();
+        Migrator::up(&db, None).await.unwrap();
+        SeaOrmUserRepo::new(db)
+    }
+
+    #[tokio::test]
+    async fn test_search_after_filters_and_orders_by_created_at_and_id() {
+        let repo = migrated_repo().await;
+        let base = Utc::now().fixed_offset();
+        for (n, username, minutes) in [
+            (1, "alice", 0),
+            (2, "Albert", 0),
+            (3, "bob", 1),
+            (4, "al_x", 2),
+        ] {
+            let at = base + Duration::minutes(minutes);
+            repo.insert(user::ActiveModel {
+                id: Set(user_id(n)),
+                username: Set(username.to_string()),
+                email: Set(format!("{username}@example.com")),
+                password_hash: Set("not-used".to_string()),
+                created_at: Set(at),
+                updated_at: Set(at),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        }
+        let names = |users: Vec<user::Model>| -> Vec<String> {
+            users.into_iter().map(|u| u.username).collect()
+        };
+
+        let all = repo.search_after("", None, 10).await.unwrap();
+        assert_eq!(names(all.clone()), ["alice", "Albert", "bob", "al_x"]);
+
+        // 不区分大小写，`_` 按字面匹配
+        let found = repo.search_after("AL", None, 10).await.unwrap();
+        assert_eq!(names(found), ["alice", "Albert", "al_x"]);
+        let found = repo.search_after("l_", None, 10).await.unwrap();
+        assert_eq!(names(found), ["al_x"]);
+
+        // 游标位置之后的数据，包括创建时间相同但 ID 更大的
+        let first = &all[0];
+        let after = repo
+            .search_after("", Some((first.created_at, first.id)), 2)
+            .await
+            .unwrap();
+        assert_eq!(names(after), ["Albert", "bob"]);
+    }
+
+    #[tokio::test]
+    async fn test_insert_assigns_id_for_lookup() {
+        let repo = migrated_repo().await;
+        let created = repo
+            .insert(user::ActiveModel {
+                username: Set("alice".to_string()),
+                email: Set("alice@example.com".to_string()),
+                password_hash: Set("not-used".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        // UUID 主键由应用生成随机 UUID，自增主键由数据库从 1 开始分配
+        #[cfg(feature = "uuid-user-id")]
+        assert_eq!(created.id.get_version(), Some(uuid::Version::Random));
+        #[cfg(not(feature = "uuid-user-id"))]
+        assert_eq!(created.id, 1);
+
+        let found = repo.find_by_id(created.id).await.unwrap().unwrap();
+        assert_eq!(found.username, "alice");
+        assert!(repo.find_by_id(user_id(999)).await.unwrap().is_none());
+
+        repo.update_last_login(created.id, Utc::now().fixed_offset())
+            .await
+            .unwrap();
+        let listed = repo.list_after(None, 10).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].last_login_at.is_some());
+        assert!(
+            repo.list_after(Some(created.id), 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preferences_default_and_roundtrip() {
+        let repo = migrated_repo().await;
+        let created = repo
+            .insert(user::ActiveModel {
+                username: Set("alice".to_string()),
+                email: Set("alice@example.com".to_string()),
+                password_hash: Set("not-used".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(created.preferences, serde_json::json!({}));
+
+        let preferences = serde_json::json!({ "theme": "dark", "beta": { "grid": true } });
+        repo.update_preferences(created.id, preferences.clone())
+            .await
+            .unwrap();
+        let found = repo.find_by_id(created.id).await.unwrap().unwrap();
+        assert_eq!(found.preferences, preferences);
+    }
+
+    #[tokio::test]
+    async fn test_insert_all_rolls_back_on_failure() {
+        let repo = migrated_repo().await;
+        let new_user = |username: &str, email: &str| user::ActiveModel {
+            username: Set(username.to_string()),
+            email: Set(email.to_string()),
+            password_hash: Set("not-used".to_string()),
+            ..Default::default()
+        };
+
+        let inserted = repo
+            .insert_all(vec![
+                new_user("alice", "alice@example.com"),
+                new_user("bob", "bob@example.com"),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(inserted.len(), 2);
+        assert_eq!(inserted[1].username, "bob");
+
+        // 第二项的邮箱与已有用户重复，第一项也不会写入
+        repo.insert_all(vec![
+            new_user("carol", "carol@example.com"),
+            new_user("dave", "alice@example.com"),
+        ])
+        .await
+        .unwrap_err();
+        assert_eq!(repo.count().await.unwrap(), 2);
+        assert!(repo.find_by_username("carol").await.unwrap().is_none());
+    }
+}
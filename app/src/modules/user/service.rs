@@ -1,43 +1,98 @@
-use sea_orm::{
-    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, Set,
-};
-use tracing::instrument;
+use std::collections::HashSet;
+
+use anyhow::Context;
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use csv::{Position, ReaderBuilder, Trim};
+use futures_util::{Stream, StreamExt, TryStreamExt, stream};
+use sea_orm::SqlErr;
+use tokio::io::AsyncWrite;
+use tracing::{instrument, warn};
+use validator::ValidateEmail;
 
 use crate::{
     AppState,
-    error::AuthError,
-    shared::{FromState, jwt::JwtService, password},
+    core::config::ImportConfig,
+    core::response::{ApiError, Cursor, CursorPage, ItemResult, MAX_PER_PAGE},
+    error::{AppError, AuthError, ValidationError},
+    shared::{
+        FromDto, FromState, IntoModel, OrNotFound, csv::CsvWriter, jwt::JwtService, password,
+    },
+};
+use entity::user::{self, UserId};
+
+use super::dto::{
+    ExportField, ImportReport, ImportRow, ImportRowError, LoginRequest, LoginResponse,
+    RegisterRequest, RegisterResponse, UserDto, UserPreferencesDto,
 };
-use entity::user;
+use super::repo::{SeaOrmUserRepo, UserRepo};
+
+/// 触发账户锁定的连续登录失败次数
+const MAX_FAILED_LOGIN_ATTEMPTS: i32 = 5;
+
+/// 账户锁定时长（分钟）
+const LOCKOUT_MINUTES: i64 = 15;
+
+/// 批量导出时每次从数据库读取的用户数
+const EXPORT_BATCH_SIZE: u64 = 500;
+
+/// 搜索关键字的最大长度（字符）
+const MAX_SEARCH_QUERY_CHARS: usize = 100;
 
-use super::dto::{LoginRequest, LoginResponse, RegisterRequest, RegisterResponse};
+/// CSV 导入时每个事务写入的用户数
+const IMPORT_BATCH_SIZE: usize = 100;
+
+/// CSV 导入文件必须包含的列
+const IMPORT_COLUMNS: [&str; 3] = ["username", "email", "password"];
+
+/// 批量创建用户的结果
+#[derive(Debug)]
+pub enum BulkCreateOutcome {
+    /// 全部创建成功，按请求顺序排列
+    Created(Vec<UserDto>),
+
+    /// 存在失败的数据项，整批均未创建；按请求顺序给出每一项的结果
+    Rejected(Vec<ItemResult<UserDto>>),
+}
+
+/// CSV 导入用户的结果
+#[derive(Debug)]
+pub enum ImportOutcome {
+    /// 失败比例未超过上限，有效的行已写入
+    Completed(ImportReport),
+
+    /// 失败比例超过上限，未写入任何用户
+    Rejected(ImportReport),
+}
 
 /// 用户服务
 ///
-/// 处理用户注册、登录等业务逻辑
-pub struct UserService {
-    db: DatabaseConnection,
+/// 处理用户注册、登录等业务逻辑。数据访问通过 [`UserRepo`] 完成，
+/// 默认使用 SeaORM 实现，测试时可替换为内存实现。
+pub struct UserService<R = SeaOrmUserRepo> {
+    repo: R,
     jwt_service: JwtService,
 }
 
-impl FromState for UserService {
+impl FromState for UserService<SeaOrmUserRepo> {
     fn from_state(app: &AppState) -> Self {
-        Self {
-            db: app.db.clone(),
-            jwt_service: app.jwt_service.clone(),
-        }
+        Self::new(SeaOrmUserRepo::from_state(app), app.jwt_service.clone())
     }
 }
 
-impl UserService {
+impl<R: UserRepo> UserService<R> {
+    /// 使用指定的数据访问实现创建用户服务
+    pub fn new(repo: R, jwt_service: JwtService) -> Self {
+        Self { repo, jwt_service }
+    }
+
     /// 用户注册业务逻辑
     ///
     /// 执行以下步骤：
-    /// 1. 验证用户名长度（3-20字符）和密码长度（至少8字符）
-    /// 2. 验证两次密码输入是否一致
-    /// 3. 检查用户名和邮箱是否已存在
-    /// 4. 使用Argon2算法哈希密码
-    /// 5. 创建新用户并保存到数据库
+    /// 1. 通过 [`IntoModel`] 校验请求（用户名长度、密码长度、两次密码一致）并构建新用户，
+    ///    密码使用 Argon2 算法哈希
+    /// 2. 检查用户名和邮箱是否已存在
+    /// 3. 保存新用户到数据库
     ///
     /// # 参数
     /// * `req` - 注册请求，包含用户名、邮箱、密码
@@ -47,60 +102,35 @@ impl UserService {
     /// 失败返回 AuthError（如果用户已存在、验证失败等）
     #[instrument(skip(self, req))]
     pub async fn register(&self, req: RegisterRequest) -> Result<RegisterResponse, AuthError> {
-        // 验证用户名
-        if req.username.is_empty() || req.username.len() < 3 || req.username.len() > 20 {
-            return Err(AuthError::InvalidUsername);
-        }
-
-        // 验证密码长度
-        if req.password.len() < 8 {
-            return Err(AuthError::PasswordTooShort);
-        }
-
-        // 验证两次密码是否一致
-        if req.password != req.password_confirm {
-            return Err(AuthError::PasswordMismatch);
-        }
+        // 校验请求并构建新用户（密码在此完成哈希）
+        let new_user: user::ActiveModel = req.into_model()?;
+        let username = new_user.username.as_ref();
+        let email = new_user.email.as_ref();
 
         // 检查用户名是否已存在
-        let existing_user = user::Entity::find()
-            .filter(user::Column::Username.eq(&req.username))
-            .one(&self.db)
+        let existing_user = self
+            .repo
+            .find_by_username(username)
             .await
-            .map_err(|_| AuthError::Internal("数据库查询失败".to_string()))?;
+            .context("数据库查询失败")?;
 
         if existing_user.is_some() {
             return Err(AuthError::UserAlreadyExists);
         }
 
         // 检查邮箱是否已存在
-        let existing_email = user::Entity::find()
-            .filter(user::Column::Email.eq(&req.email))
-            .one(&self.db)
+        let existing_email = self
+            .repo
+            .find_by_email(email)
             .await
-            .map_err(|_| AuthError::Internal("数据库查询失败".to_string()))?;
+            .context("数据库查询失败")?;
 
         if existing_email.is_some() {
             return Err(AuthError::UserAlreadyExists);
         }
 
-        // 密码加密
-        let password_hash = password::hash_password(&req.password)
-            .map_err(|e| AuthError::Internal(e.to_string()))?;
-
         // 保存到数据库
-        let new_user = user::ActiveModel {
-            username: Set(req.username.clone()),
-            email: Set(req.email.clone()),
-            password_hash: Set(password_hash),
-            status: Set(0), // 激活状态
-            ..Default::default()
-        };
-
-        let user_model = new_user
-            .insert(&self.db)
-            .await
-            .map_err(|_| AuthError::Internal("创建用户失败".to_string()))?;
+        let user_model = self.repo.insert(new_user).await.context("创建用户失败")?;
 
         Ok(RegisterResponse {
             id: user_model.id,
@@ -113,30 +143,33 @@ impl UserService {
     ///
     /// 执行以下步骤：
     /// 1. 根据用户名或邮箱查询用户
-    /// 2. 检查用户状态（必须是激活状态）
-    /// 3. 验证密码是否正确
-    /// 4. 生成有效期为7天的JWT令牌
+    /// 2. 检查账户是否处于锁定期
+    /// 3. 检查用户状态（必须是激活状态）
+    /// 4. 验证密码是否正确，连续失败 5 次将锁定账户 15 分钟
+    /// 5. 生成有效期为7天的JWT令牌并记录登录时间
     ///
     /// # 参数
     /// * `req` - 登录请求，包含用户名/邮箱和密码
     ///
     /// # 返回
     /// 成功返回 LoginResponse（用户信息和JWT令牌）
-    /// 失败返回 AuthError（如果用户不存在、密码错误、用户被停用等）
+    /// 失败返回 AuthError（如果用户不存在、密码错误、账户被锁定、用户被停用等）
     #[instrument(skip(self, req))]
     pub async fn login(&self, req: LoginRequest) -> Result<LoginResponse, AuthError> {
         // 根据用户名或邮箱查询用户
-        let user_model = user::Entity::find()
-            .filter(
-                Condition::any()
-                    .add(user::Column::Username.eq(&req.username_or_email))
-                    .add(user::Column::Email.eq(&req.username_or_email)),
-            )
-            .one(&self.db)
+        let user_model = self
+            .repo
+            .find_by_username_or_email(&req.username_or_email)
             .await
-            .map_err(|_| AuthError::Internal("数据库查询失败".to_string()))?
+            .context("数据库查询失败")?
             .ok_or(AuthError::UserNotFound)?;
 
+        // 检查账户锁定状态
+        let now = Utc::now().fixed_offset();
+        if user_model.locked_until.is_some_and(|until| until > now) {
+            return Err(AuthError::AccountLocked);
+        }
+
         // 检查用户状态
         if user_model.status != 0 {
             return Err(AuthError::UserInactive);
@@ -144,9 +177,24 @@ impl UserService {
 
         // 验证密码
         let password_valid = password::verify_password(&req.password, &user_model.password_hash)
-            .map_err(|e| AuthError::Internal(e.to_string()))?;
+            .context("校验密码失败")?;
 
         if !password_valid {
+            let attempts = user_model.failed_login_attempts + 1;
+            let locked_until = (attempts >= MAX_FAILED_LOGIN_ATTEMPTS)
+                .then(|| now + Duration::minutes(LOCKOUT_MINUTES));
+            if locked_until.is_some() {
+                warn!(
+                    user_id = %user_model.id,
+                    attempts, "连续登录失败，账户已锁定"
+                );
+            }
+
+            self.repo
+                .record_failed_login(user_model.id, attempts, locked_until)
+                .await
+                .context("记录登录失败次数失败")?;
+
             return Err(AuthError::InvalidPassword);
         }
 
@@ -154,7 +202,12 @@ impl UserService {
         let token = self
             .jwt_service
             .generate_token(user_model.id, 7 * 24 * 3600) // 7天过期
-            .map_err(|e| AuthError::Internal(e.to_string()))?;
+            .context("生成访问令牌失败")?;
+
+        self.repo
+            .update_last_login(user_model.id, now)
+            .await
+            .context("更新登录时间失败")?;
 
         Ok(LoginResponse {
             id: user_model.id,
@@ -165,6 +218,105 @@ impl UserService {
         })
     }
 
+    /// 批量创建用户
+    ///
+    /// 执行以下步骤：
+    /// 1. 逐项校验请求（与注册相同的规则），并检查用户名和邮箱是否与批次内的前序数据项或已有用户重复
+    /// 2. 任一项失败时不写入任何用户，返回每一项的结果：失败的项附带错误，
+    ///    其余项返回 424 Failed Dependency
+    /// 3. 全部通过后哈希密码，并在同一个事务中插入全部用户
+    ///
+    /// # 参数
+    /// * `reqs` - 注册请求列表，最多 [`MAX_PER_PAGE`] 项
+    ///
+    /// # 返回
+    /// 成功返回 [`BulkCreateOutcome`]；列表为空或超过上限返回 ValidationError，
+    /// 校验通过后插入时遇到并发写入的重复用户返回 AuthError::UserAlreadyExists（整批已回滚）
+    #[instrument(skip(self, reqs), fields(count = reqs.len()))]
+    pub async fn bulk_create(
+        &self,
+        reqs: Vec<RegisterRequest>,
+    ) -> Result<BulkCreateOutcome, AppError> {
+        if reqs.is_empty() || reqs.len() as u64 > MAX_PER_PAGE {
+            return Err(ValidationError::custom(format!(
+                "批量创建的用户数必须在 1-{MAX_PER_PAGE} 之间"
+            ))
+            .into());
+        }
+
+        let mut usernames = HashSet::new();
+        let mut emails = HashSet::new();
+        let mut errors = Vec::with_capacity(reqs.len());
+        for req in &reqs {
+            let error = if let Err(e) = <user::ActiveModel as FromDto<_>>::validate(req) {
+                Some(e)
+            } else if !usernames.insert(req.username.as_str())
+                || !emails.insert(req.email.as_str())
+                || self.is_taken(req).await?
+            {
+                Some(AuthError::UserAlreadyExists)
+            } else {
+                None
+            };
+            errors.push(error);
+        }
+
+        if errors.iter().any(Option::is_some) {
+            warn!(
+                failed = errors.iter().flatten().count(),
+                "批量创建用户存在失败的数据项，整批未创建"
+            );
+            let results = errors
+                .into_iter()
+                .enumerate()
+                .map(|(index, error)| {
+                    let error = error.map(|e| e.api_error()).unwrap_or_else(|| {
+                        ApiError::new(
+                            StatusCode::FAILED_DEPENDENCY,
+                            "批次中其他数据项失败，该项未创建",
+                        )
+                    });
+                    ItemResult::err(index, error)
+                })
+                .collect();
+            return Ok(BulkCreateOutcome::Rejected(results));
+        }
+
+        let models = reqs
+            .into_iter()
+            .map(IntoModel::into_model)
+            .collect::<Result<Vec<user::ActiveModel>, _>>()?;
+        let created = self.repo.insert_all(models).await.map_err(|e| {
+            if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) {
+                AppError::from(AuthError::UserAlreadyExists)
+            } else {
+                AppError::from(anyhow::Error::new(e).context("批量创建用户失败"))
+            }
+        })?;
+        Ok(BulkCreateOutcome::Created(
+            created.into_iter().map(UserDto::from).collect(),
+        ))
+    }
+
+    /// 用户名或邮箱是否已被已有用户占用
+    async fn is_taken(&self, req: &RegisterRequest) -> Result<bool, AppError> {
+        let username_taken = self
+            .repo
+            .find_by_username(&req.username)
+            .await
+            .context("数据库查询失败")?
+            .is_some();
+        if username_taken {
+            return Ok(true);
+        }
+        Ok(self
+            .repo
+            .find_by_email(&req.email)
+            .await
+            .context("数据库查询失败")?
+            .is_some())
+    }
+
     /// 根据用户ID获取用户信息
     ///
     /// 从数据库中查询指定ID的用户信息。
@@ -174,14 +326,14 @@ impl UserService {
     ///
     /// # 返回
     /// 成功返回 RegisterResponse（用户ID、用户名、邮箱）
-    /// 如果用户不存在返回 AuthError::UserNotFound
+    /// 如果用户不存在返回 AppError::NotFound
     #[instrument(skip(self))]
-    pub async fn get_user(&self, user_id: i32) -> Result<RegisterResponse, AuthError> {
-        let user_model = user::Entity::find_by_id(user_id)
-            .one(&self.db)
+    pub async fn get_user(&self, user_id: UserId) -> Result<RegisterResponse, AppError> {
+        let user_model = self
+            .repo
+            .find_by_id(user_id)
             .await
-            .map_err(|_| AuthError::Internal("数据库查询失败".to_string()))?
-            .ok_or(AuthError::UserNotFound)?;
+            .or_not_found("用户不存在")?;
 
         Ok(RegisterResponse {
             id: user_model.id,
@@ -189,4 +341,775 @@ impl UserService {
             email: user_model.email,
         })
     }
+
+    /// 获取用户的偏好设置
+    ///
+    /// # 返回
+    /// 成功返回偏好设置中的已知键，用户不存在返回 AppError::NotFound
+    #[instrument(skip(self))]
+    pub async fn get_preferences(&self, user_id: UserId) -> Result<UserPreferencesDto, AppError> {
+        let user = self
+            .repo
+            .find_by_id(user_id)
+            .await
+            .or_not_found("用户不存在")?;
+        Ok(UserPreferencesDto::from_column(&user.preferences))
+    }
+
+    /// 合并修改用户的偏好设置
+    ///
+    /// 请求中提供的字段写入 `preferences` 列，未提供的字段和列中的其他键保持不变。
+    ///
+    /// # 返回
+    /// 成功返回修改后的偏好设置，用户不存在返回 AppError::NotFound
+    #[instrument(skip(self))]
+    pub async fn update_preferences(
+        &self,
+        user_id: UserId,
+        patch: UserPreferencesDto,
+    ) -> Result<UserPreferencesDto, AppError> {
+        let mut preferences = self
+            .repo
+            .find_by_id(user_id)
+            .await
+            .or_not_found("用户不存在")?
+            .preferences;
+        patch.merge_into(&mut preferences);
+        self.repo
+            .update_preferences(user_id, preferences.clone())
+            .await
+            .context("保存偏好设置失败")?;
+        Ok(UserPreferencesDto::from_column(&preferences))
+    }
+
+    /// 统计用户总数，用于导出前估算响应大小
+    pub async fn count_users(&self) -> Result<u64, AppError> {
+        Ok(self.repo.count().await.context("统计用户总数失败")?)
+    }
+
+    /// 按用户名或邮箱搜索用户，游标分页
+    ///
+    /// 结果按 `(created_at, id)` 升序排列，从 `cursor` 之后开始取 `per_page` 个；
+    /// 多查询一条用于判断是否还有下一页，有则返回本页最后一个用户的游标。
+    /// 翻页期间新注册的用户排在末尾，已返回的页不会重复或遗漏数据。
+    ///
+    /// # 参数
+    /// * `query` - 搜索关键字（前后空白会被去掉），为空时列出全部用户
+    /// * `cursor` - 上一页返回的游标，`None` 表示第一页
+    /// * `per_page` - 每页数据量（调用方已限制范围）
+    ///
+    /// # 返回
+    /// 成功返回当前页用户和下一页游标，关键字过长时返回 ValidationError
+    #[instrument(skip(self))]
+    pub async fn search_cursor(
+        &self,
+        query: &str,
+        cursor: Option<Cursor<UserId>>,
+        per_page: u32,
+    ) -> Result<CursorPage<UserDto>, AppError> {
+        let query = query.trim();
+        if query.chars().count() > MAX_SEARCH_QUERY_CHARS {
+            return Err(ValidationError::custom(format!(
+                "搜索关键字不能超过 {MAX_SEARCH_QUERY_CHARS} 个字符"
+            ))
+            .into());
+        }
+        let after = cursor.map(|c| (c.created_at, c.id));
+
+        let rows = self
+            .repo
+            .search_after(query, after, u64::from(per_page) + 1)
+            .await
+            .context("搜索用户失败")?;
+        Ok(CursorPage::from_rows(
+            rows,
+            per_page,
+            |user| Cursor {
+                created_at: user.created_at,
+                id: user.id,
+            },
+            UserDto::from,
+        ))
+    }
+
+    /// 按 ID 升序逐个产出全部用户
+    ///
+    /// 内部按 [`EXPORT_BATCH_SIZE`] 分批做键集分页查询，内存中最多只保留一批数据。
+    pub fn stream_all(self) -> impl Stream<Item = Result<user::Model, AppError>> {
+        // 状态为下一批的起点：`Some(None)` 从第一个用户开始，`None` 表示已经取完
+        stream::try_unfold((self, Some(None)), |(service, cursor)| async move {
+            let Some(after) = cursor else {
+                return Ok(None);
+            };
+            let batch = service
+                .repo
+                .list_after(after, EXPORT_BATCH_SIZE)
+                .await
+                .with_context(|| match after {
+                    Some(id) => format!("查询 ID 大于 {id} 的用户失败"),
+                    None => "查询用户失败".to_string(),
+                })?;
+            let next = match batch.last() {
+                Some(last) if batch.len() as u64 == EXPORT_BATCH_SIZE => Some(Some(last.id)),
+                _ => None,
+            };
+            Ok::<_, AppError>(Some((stream::iter(batch).map(Ok), (service, next))))
+        })
+        .try_flatten()
+    }
+
+    /// 以 CSV 格式导出全部用户到 `out`，返回导出的用户数
+    ///
+    /// 第一行为表头，之后每个用户一行，只包含 `fields` 指定的列。
+    #[instrument(skip(self, out))]
+    pub async fn export_csv<W>(self, fields: &[ExportField], out: W) -> Result<u64, AppError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut writer = CsvWriter::new(out);
+        writer.write_record(fields.iter().map(|f| f.name())).await?;
+
+        let users = self.stream_all();
+        futures_util::pin_mut!(users);
+        let mut count = 0;
+        while let Some(user) = users.try_next().await? {
+            writer
+                .write_record(fields.iter().map(|f| f.value(&user)))
+                .await?;
+            count += 1;
+        }
+        writer.flush().await?;
+        Ok(count)
+    }
+
+    /// 从 CSV 批量导入用户
+    ///
+    /// 执行以下步骤：
+    /// 1. 读取表头，必须包含 `username`、`email`、`password` 列，多余的列会被忽略
+    /// 2. 逐行校验（与注册相同的规则，另外校验邮箱格式），并检查用户名和邮箱是否与前面的行或已有用户重复
+    /// 3. 失败行占比超过 `config.max_error_rate_percent` 时不写入任何用户
+    /// 4. 否则哈希密码，每 [`IMPORT_BATCH_SIZE`] 个用户一个事务写入；某个批次遇到并发写入的重复用户时
+    ///    只回滚该批次，其中的行计为失败
+    ///
+    /// # 参数
+    /// * `data` - CSV 文件内容（UTF-8，第一行为表头）
+    /// * `config` - 行数和失败比例上限
+    ///
+    /// # 返回
+    /// 成功返回 [`ImportOutcome`]；表头无法读取、缺少必需的列、没有数据行或超过 `config.max_rows`
+    /// 返回 ValidationError。写入时遇到其他数据库错误直接返回，之前的批次已提交
+    #[instrument(skip(self, data, config), fields(bytes = data.len()))]
+    pub async fn import_csv(
+        &self,
+        data: &[u8],
+        config: &ImportConfig,
+    ) -> Result<ImportOutcome, AppError> {
+        let mut reader = ReaderBuilder::new().trim(Trim::Headers).from_reader(data);
+        let headers = reader
+            .headers()
+            .map_err(|e| ValidationError::custom(format!("无法读取 CSV 表头：{e}")))?
+            .clone();
+        if let Some(missing) = IMPORT_COLUMNS
+            .iter()
+            .find(|column| !headers.iter().any(|h| h == **column))
+        {
+            return Err(ValidationError::custom(format!("CSV 缺少 {missing} 列")).into());
+        }
+
+        let mut usernames = HashSet::new();
+        let mut emails = HashSet::new();
+        let mut valid = Vec::new();
+        let mut errors = Vec::new();
+        let mut total = 0;
+        for record in reader.records() {
+            total += 1;
+            if total > config.max_rows {
+                return Err(ValidationError::custom(format!(
+                    "单次最多导入 {} 行",
+                    config.max_rows
+                ))
+                .into());
+            }
+            // 表头为第 1 行；无法定位时按数据行的序号推算
+            let fallback = total as u64 + 1;
+            let parsed = record.and_then(|record| {
+                let row = record.position().map_or(fallback, Position::line);
+                record
+                    .deserialize::<ImportRow>(Some(&headers))
+                    .map(|parsed| (row, parsed))
+            });
+            let (row, parsed) = match parsed {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    errors.push(ImportRowError {
+                        row: e.position().map_or(fallback, Position::line),
+                        field: None,
+                        message: format!("无法解析该行：{e}"),
+                    });
+                    continue;
+                }
+            };
+
+            let req = RegisterRequest {
+                username: parsed.username.trim().to_string(),
+                email: parsed.email.trim().to_string(),
+                password_confirm: parsed.password.clone(),
+                password: parsed.password,
+            };
+            match self
+                .check_import_row(&req, &mut usernames, &mut emails)
+                .await?
+            {
+                Some((field, message)) => errors.push(ImportRowError {
+                    row,
+                    field: Some(field.to_string()),
+                    message,
+                }),
+                None => valid.push((row, req)),
+            }
+        }
+        if total == 0 {
+            return Err(ValidationError::custom("CSV 中没有数据行").into());
+        }
+
+        let mut report = ImportReport {
+            total,
+            succeeded: 0,
+            failed: errors.len(),
+            errors,
+        };
+        if config.exceeds_error_rate(report.failed, total) {
+            warn!(
+                total,
+                failed = report.failed,
+                "CSV 导入的失败行超过上限，未导入任何用户"
+            );
+            return Ok(ImportOutcome::Rejected(report));
+        }
+
+        while !valid.is_empty() {
+            let batch = valid.drain(..valid.len().min(IMPORT_BATCH_SIZE));
+            let (rows, reqs): (Vec<u64>, Vec<RegisterRequest>) = batch.unzip();
+            let models = reqs
+                .into_iter()
+                .map(IntoModel::into_model)
+                .collect::<Result<Vec<user::ActiveModel>, _>>()?;
+            match self.repo.insert_all(models).await {
+                Ok(created) => report.succeeded += created.len(),
+                Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+                    warn!(
+                        rows = rows.len(),
+                        "导入批次中存在并发写入的重复用户，该批次已回滚"
+                    );
+                    report.failed += rows.len();
+                    report
+                        .errors
+                        .extend(rows.into_iter().map(|row| ImportRowError {
+                            row,
+                            field: None,
+                            message: "同批次中有用户名或邮箱已被占用，该批次未导入".to_string(),
+                        }));
+                }
+                Err(e) => return Err(anyhow::Error::new(e).context("导入用户失败").into()),
+            }
+        }
+        report.errors.sort_by_key(|e| e.row);
+        Ok(ImportOutcome::Completed(report))
+    }
+
+    /// 校验导入的一行，失败时返回出错的列和原因
+    ///
+    /// 通过校验的用户名和邮箱记入 `usernames`、`emails`，后面的行不能再使用。
+    async fn check_import_row(
+        &self,
+        req: &RegisterRequest,
+        usernames: &mut HashSet<String>,
+        emails: &mut HashSet<String>,
+    ) -> Result<Option<(&'static str, String)>, AppError> {
+        if let Err(e) = <user::ActiveModel as FromDto<_>>::validate(req) {
+            let field = match e {
+                AuthError::InvalidUsername => "username",
+                _ => "password",
+            };
+            return Ok(Some((field, e.to_string())));
+        }
+        if !req.email.validate_email() {
+            return Ok(Some(("email", "邮箱格式无效".to_string())));
+        }
+        if usernames.contains(&req.username) {
+            return Ok(Some(("username", "与前面的行重复".to_string())));
+        }
+        if emails.contains(&req.email) {
+            return Ok(Some(("email", "与前面的行重复".to_string())));
+        }
+        if self
+            .repo
+            .find_by_username(&req.username)
+            .await
+            .context("数据库查询失败")?
+            .is_some()
+        {
+            return Ok(Some(("username", AuthError::UserAlreadyExists.to_string())));
+        }
+        if self
+            .repo
+            .find_by_email(&req.email)
+            .await
+            .context("数据库查询失败")?
+            .is_some()
+        {
+            return Ok(Some(("email", "邮箱已被使用".to_string())));
+        }
+        usernames.insert(req.username.clone());
+        emails.insert(req.email.clone());
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::user::repo::InMemoryUserRepo;
+    use crate::test_support::user_id;
+
+    const PASSWORD: &str = "correct-horse";
+
+    fn service() -> UserService<InMemoryUserRepo> {
+        UserService::new(
+            InMemoryUserRepo::default(),
+            JwtService::new("test-secret-at-least-32-characters!!".to_string()),
+        )
+    }
+
+    fn seed_user(service: &UserService<InMemoryUserRepo>) -> UserId {
+        let now = Utc::now().fixed_offset();
+        service.repo.seed(user::Model {
+            id: user_id(1),
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            password_hash: password::hash_password(PASSWORD).unwrap(),
+            status: 0,
+            role: 0,
+            last_login_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            created_at: now,
+            updated_at: now,
+            storage_quota_bytes: None,
+            storage_used_bytes: 0,
+            preferences: serde_json::json!({}),
+        })
+    }
+
+    fn register_req(username: &str, password: &str, confirm: &str) -> RegisterRequest {
+        RegisterRequest {
+            username: username.to_string(),
+            email: format!("{username}@example.com"),
+            password: password.to_string(),
+            password_confirm: confirm.to_string(),
+        }
+    }
+
+    fn login_req(password: &str) -> LoginRequest {
+        LoginRequest {
+            username_or_email: "alice".to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_preferences_merges_and_keeps_unknown_keys() {
+        let service = service();
+        let id = seed_user(&service);
+        service
+            .repo
+            .update_preferences(
+                id,
+                serde_json::json!({ "theme": "light", "layout": "grid" }),
+            )
+            .await
+            .unwrap();
+
+        let updated = service
+            .update_preferences(
+                id,
+                UserPreferencesDto {
+                    timezone: Some("Asia/Shanghai".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.theme.as_deref(), Some("light"));
+        assert_eq!(updated.timezone.as_deref(), Some("Asia/Shanghai"));
+        assert_eq!(service.get_preferences(id).await.unwrap(), updated);
+        assert_eq!(
+            service.repo.get(id).unwrap().preferences["layout"],
+            serde_json::json!("grid")
+        );
+
+        let err = service.get_preferences(user_id(404)).await.unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_register_password_policy() {
+        let service = service();
+
+        let err = service
+            .register(register_req("ab", "password123", "password123"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthError::InvalidUsername));
+
+        let err = service
+            .register(register_req("bob", "short", "short"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthError::PasswordTooShort));
+
+        let err = service
+            .register(register_req("bob", "password123", "password456"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthError::PasswordMismatch));
+
+        let created = service
+            .register(register_req("bob", "password123", "password123"))
+            .await
+            .unwrap();
+        assert_eq!(created.username, "bob");
+
+        let err = service
+            .register(register_req("bob", "password123", "password123"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthError::UserAlreadyExists));
+    }
+
+    #[tokio::test]
+    async fn test_login_locks_account_after_repeated_failures() {
+        let service = service();
+        let id = seed_user(&service);
+
+        for _ in 0..MAX_FAILED_LOGIN_ATTEMPTS {
+            let err = service
+                .login(login_req("wrong-password"))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AuthError::InvalidPassword));
+        }
+
+        let user = service.repo.get(id).unwrap();
+        assert_eq!(user.failed_login_attempts, MAX_FAILED_LOGIN_ATTEMPTS);
+        assert!(user.locked_until.is_some());
+
+        // 锁定期内即使密码正确也拒绝登录
+        let err = service.login(login_req(PASSWORD)).await.unwrap_err();
+        assert!(matches!(err, AuthError::AccountLocked));
+    }
+
+    #[tokio::test]
+    async fn test_login_success_resets_failures() {
+        let service = service();
+        let id = seed_user(&service);
+
+        service
+            .login(login_req("wrong-password"))
+            .await
+            .unwrap_err();
+        assert_eq!(service.repo.get(id).unwrap().failed_login_attempts, 1);
+
+        let response = service.login(login_req(PASSWORD)).await.unwrap();
+        assert_eq!(response.id, id);
+
+        let user = service.repo.get(id).unwrap();
+        assert_eq!(user.failed_login_attempts, 0);
+        assert!(user.last_login_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_login_allowed_after_lockout_expires() {
+        let service = service();
+        let id = seed_user(&service);
+        let expired = Utc::now().fixed_offset() - Duration::minutes(1);
+        service
+            .repo
+            .record_failed_login(id, MAX_FAILED_LOGIN_ATTEMPTS, Some(expired))
+            .await
+            .unwrap();
+
+        assert!(service.login(login_req(PASSWORD)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_export_csv_streams_all_users_across_batches() {
+        let service = service();
+        let now = Utc::now().fixed_offset();
+        let total = EXPORT_BATCH_SIZE as u16 + 2;
+        for n in (1..=total).rev() {
+            service.repo.seed(user::Model {
+                id: user_id(n),
+                username: format!("user{n}"),
+                email: format!("user{n}@example.com"),
+                password_hash: "not-exported".to_string(),
+                status: 0,
+                role: 0,
+                last_login_at: None,
+                failed_login_attempts: 0,
+                locked_until: None,
+                created_at: now,
+                updated_at: now,
+                storage_quota_bytes: None,
+                storage_used_bytes: 0,
+                preferences: serde_json::json!({}),
+            });
+        }
+
+        assert_eq!(service.count_users().await.unwrap(), total as u64);
+
+        let mut out = Vec::new();
+        let count = service
+            .export_csv(&[ExportField::Id, ExportField::Email], &mut out)
+            .await
+            .unwrap();
+        assert_eq!(count, total as u64);
+
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), total as usize + 1);
+        assert_eq!(lines[0], "id,email");
+        assert_eq!(lines[1], format!("{},user1@example.com", user_id(1)));
+        assert_eq!(
+            lines[total as usize],
+            format!("{},user{total}@example.com", user_id(total))
+        );
+        assert!(!csv.contains("not-exported"));
+    }
+
+    /// 预置一个指定创建时间的用户
+    fn seed_at(service: &UserService<InMemoryUserRepo>, id: UserId, username: &str, minutes: i64) {
+        let at = chrono::DateTime::parse_from_rfc3339("2024-05-01T00:00:00Z").unwrap()
+            + Duration::minutes(minutes);
+        service.repo.seed(user::Model {
+            id,
+            username: username.to_string(),
+            email: format!("{username}@example.com"),
+            password_hash: "not-used".to_string(),
+            status: 0,
+            role: 0,
+            last_login_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            created_at: at,
+            updated_at: at,
+            storage_quota_bytes: None,
+            storage_used_bytes: 0,
+            preferences: serde_json::json!({}),
+        });
+    }
+
+    fn usernames(page: &CursorPage<UserDto>) -> Vec<&str> {
+        page.items.iter().map(|u| u.username.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_search_cursor_pages_without_duplicates() {
+        let service = service();
+        // 同一时间创建的用户按 ID 排序
+        seed_at(&service, user_id(3), "carol", 0);
+        seed_at(&service, user_id(1), "alice", 0);
+        seed_at(&service, user_id(2), "Albert", 1);
+        seed_at(&service, user_id(4), "bob", 2);
+
+        let page = service.search_cursor("", None, 2).await.unwrap();
+        assert_eq!(usernames(&page), ["alice", "carol"]);
+        let cursor = Cursor::decode(page.next_cursor.as_deref().unwrap()).unwrap();
+
+        // 翻页期间插入的数据不会让已返回的数据再次出现
+        seed_at(&service, user_id(5), "aaron", -10);
+        seed_at(&service, user_id(6), "dave", 10);
+
+        let page = service.search_cursor("", Some(cursor), 2).await.unwrap();
+        assert_eq!(usernames(&page), ["Albert", "bob"]);
+        let cursor = Cursor::decode(page.next_cursor.as_deref().unwrap()).unwrap();
+
+        let page = service.search_cursor("", Some(cursor), 2).await.unwrap();
+        assert_eq!(usernames(&page), ["dave"]);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_search_cursor_matches_username_or_email() {
+        let service = service();
+        seed_at(&service, user_id(1), "alice", 0);
+        seed_at(&service, user_id(2), "Albert", 1);
+        seed_at(&service, user_id(3), "bob", 2);
+
+        let page = service.search_cursor("  AL ", None, 20).await.unwrap();
+        assert_eq!(usernames(&page), ["alice", "Albert"]);
+        assert!(page.next_cursor.is_none());
+
+        let page = service
+            .search_cursor("example.com", None, 20)
+            .await
+            .unwrap();
+        assert_eq!(page.items.len(), 3);
+
+        let err = service
+            .search_cursor(&"x".repeat(101), None, 20)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+    }
+
+    fn bulk_reqs(usernames: &[&str]) -> Vec<RegisterRequest> {
+        usernames
+            .iter()
+            .map(|name| register_req(name, "password123", "password123"))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_bulk_create_inserts_all_users() {
+        let service = service();
+        let outcome = service
+            .bulk_create(bulk_reqs(&["alice", "bob", "carol"]))
+            .await
+            .unwrap();
+        let BulkCreateOutcome::Created(users) = outcome else {
+            panic!("全部有效时应创建成功：{outcome:?}");
+        };
+        let names: Vec<_> = users.iter().map(|u| u.username.as_str()).collect();
+        assert_eq!(names, ["alice", "bob", "carol"]);
+        assert_eq!(service.count_users().await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_create_rejects_whole_batch_on_any_failure() {
+        let service = service();
+        seed_user(&service);
+        let mut reqs = bulk_reqs(&["bob", "carol", "dave", "bob", "erin"]);
+        reqs[1].email = "alice@example.com".to_string();
+        reqs[2].password_confirm = "different".to_string();
+
+        let outcome = service.bulk_create(reqs).await.unwrap();
+        let BulkCreateOutcome::Rejected(results) = outcome else {
+            panic!("存在无效数据项时应整批拒绝：{outcome:?}");
+        };
+        let statuses: Vec<_> = results.iter().map(|r| (r.index, r.status)).collect();
+        assert_eq!(statuses, [(0, 424), (1, 409), (2, 400), (3, 409), (4, 424)]);
+        assert!(results.iter().all(|r| !r.is_success()));
+        assert_eq!(service.count_users().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_create_limits_batch_size() {
+        let service = service();
+        for count in [0, MAX_PER_PAGE as usize + 1] {
+            let names: Vec<String> = (0..count).map(|i| format!("user{i}")).collect();
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            let err = service.bulk_create(bulk_reqs(&names)).await.unwrap_err();
+            assert!(matches!(err, AppError::Validation(_)), "{count}");
+        }
+        assert_eq!(service.count_users().await.unwrap(), 0);
+    }
+
+    fn import_config(max_error_rate_percent: u8) -> ImportConfig {
+        ImportConfig {
+            max_error_rate_percent,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_reports_row_errors() {
+        let service = service();
+        seed_user(&service);
+        let csv = "\
+username, email ,password,note
+bob,bob@example.com,correct-horse,first
+al,al@example.com,correct-horse,
+carol,not-an-email,correct-horse,
+dave,dave@example.com,short,
+alice,alice2@example.com,correct-horse,
+erin,alice@example.com,correct-horse,
+\" frank \",BOB@example.com,correct-horse
+bob,bob2@example.com,correct-horse,
+";
+
+        let outcome = service
+            .import_csv(csv.as_bytes(), &import_config(100))
+            .await
+            .unwrap();
+        let ImportOutcome::Completed(report) = outcome else {
+            panic!("失败比例未超过上限时应导入有效的行：{outcome:?}");
+        };
+        assert_eq!((report.total, report.succeeded, report.failed), (8, 1, 7));
+        let errors: Vec<_> = report
+            .errors
+            .iter()
+            .map(|e| (e.row, e.field.as_deref()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (3, Some("username")),
+                (4, Some("email")),
+                (5, Some("password")),
+                (6, Some("username")),
+                (7, Some("email")),
+                (8, None),
+                (9, Some("username")),
+            ]
+        );
+        assert!(
+            service
+                .repo
+                .find_by_username("bob")
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(service.count_users().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_rejects_when_error_rate_exceeded() {
+        let service = service();
+        let csv =
+            "username,email,password\nbob,bob@example.com,correct-horse\nx,x@example.com,short\n";
+
+        let outcome = service
+            .import_csv(csv.as_bytes(), &import_config(10))
+            .await
+            .unwrap();
+        let ImportOutcome::Rejected(report) = outcome else {
+            panic!("失败比例超过上限时应整体拒绝：{outcome:?}");
+        };
+        assert_eq!((report.total, report.succeeded, report.failed), (2, 0, 1));
+        assert_eq!(report.errors[0].row, 3);
+        assert_eq!(service.count_users().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_validates_header_and_row_limit() {
+        let service = service();
+        let config = ImportConfig {
+            max_rows: 1,
+            ..Default::default()
+        };
+        for csv in [
+            "username,email\nbob,bob@example.com\n",
+            "username,email,password\n",
+            "username,email,password\nbob,bob@example.com,correct-horse\ncarol,carol@example.com,correct-horse\n",
+        ] {
+            let err = service
+                .import_csv(csv.as_bytes(), &config)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::Validation(_)), "{csv}");
+        }
+        assert_eq!(service.count_users().await.unwrap(), 0);
+    }
 }
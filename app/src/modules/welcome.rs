@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use askama::Template;
+use axum::{
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{Html, IntoResponse, Response},
+    routing::{MethodRouter, get},
+};
+use serde_json::json;
+use tracing::instrument;
+
+use crate::ApiResponse;
+use crate::core::config::WelcomeConfig;
+
+#[derive(Template)]
+#[template(path = "welcome.html")]
+pub struct WelcomeTemplate<'a> {
+    pub title: &'a str,
+    pub message: &'a str,
+    pub links: Vec<(&'a str, &'a str)>,
+}
+
+/// 根路径 `/` 的路由
+///
+/// 默认返回 JSON 欢迎消息；`welcome.html` 开启时，`Accept` 偏好 `text/html` 的请求返回 HTML 页面。
+pub fn welcome_route<S>(config: &WelcomeConfig) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let config = Arc::new(config.clone());
+    get(move |headers: HeaderMap| welcome(config.clone(), headers))
+}
+
+/// 欢迎响应处理器
+#[instrument(skip_all)]
+async fn welcome(config: Arc<WelcomeConfig>, headers: HeaderMap) -> Response {
+    if !config.html {
+        return json_response(&config);
+    }
+
+    let mut response = if prefers_html(&headers) {
+        html_response(&config)
+    } else {
+        json_response(&config)
+    };
+    // 同一地址按 Accept 返回不同内容，缓存需要区分
+    response
+        .headers_mut()
+        .insert(header::VARY, HeaderValue::from_static("accept"));
+    response
+}
+
+fn json_response(config: &WelcomeConfig) -> Response {
+    let mut body = json!({ "message": config.message });
+    if !config.links.is_empty() {
+        body["links"] = json!(config.links);
+    }
+    ApiResponse::success(body).into_response()
+}
+
+fn html_response(config: &WelcomeConfig) -> Response {
+    let template = WelcomeTemplate {
+        title: &config.title,
+        message: &config.message,
+        links: config
+            .links
+            .iter()
+            .map(|(name, url)| (name.as_str(), url.as_str()))
+            .collect(),
+    };
+    match template.render() {
+        Ok(html) => Html(html).into_response(),
+        Err(err) => {
+            tracing::error!("Failed to render welcome template: {}", err);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response()
+        }
+    }
+}
+
+/// `Accept` 中 `text/html` 的权重是否高于 JSON
+///
+/// 浏览器发送 `text/html,...,*/*;q=0.8`，偏好 HTML；`curl` 等工具默认发送 `*/*`，
+/// `*/*` 和 `application/*` 计入 JSON 的权重，因此仍然得到 JSON。
+fn prefers_html(headers: &HeaderMap) -> bool {
+    let Some(accept) = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+
+    let (mut html, mut json) = (0.0_f32, 0.0_f32);
+    for range in accept.split(',') {
+        let mut parts = range.split(';').map(str::trim);
+        let media = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let q = parts
+            .find_map(|p| p.strip_prefix("q="))
+            .and_then(|q| q.parse().ok())
+            .unwrap_or(1.0);
+        match media.as_str() {
+            "text/html" => html = html.max(q),
+            "application/json" | "application/*" | "*/*" => json = json.max(q),
+            _ => {}
+        }
+    }
+    html > 0.0 && html > json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, extract::Request};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    async fn call(config: &WelcomeConfig, accept: &str) -> Response {
+        Router::new()
+            .route("/", welcome_route(config))
+            .oneshot(
+                Request::get("/")
+                    .header(header::ACCEPT, accept)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    fn config() -> WelcomeConfig {
+        WelcomeConfig {
+            message: "欢迎使用 <Acme> 文件服务".to_string(),
+            title: "Acme".to_string(),
+            links: [("文档".to_string(), "/docs".to_string())].into(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_json_uses_configured_message() {
+        let response = call(&config(), "text/html").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::VARY).is_none());
+
+        let body: Value = serde_json::from_str(&body(response).await).unwrap();
+        assert_eq!(body["data"]["message"], "欢迎使用 <Acme> 文件服务");
+        assert_eq!(body["data"]["links"]["文档"], "/docs");
+    }
+
+    #[tokio::test]
+    async fn test_html_for_browsers_and_json_for_api_clients() {
+        let config = WelcomeConfig {
+            html: true,
+            ..config()
+        };
+
+        let response = call(
+            &config,
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response.headers()[header::CONTENT_TYPE]
+                .to_str()
+                .unwrap()
+                .starts_with("text/html")
+        );
+        assert_eq!(response.headers()[header::VARY], "accept");
+        let html = body(response).await;
+        assert!(html.contains("<title>Acme</title>"), "{html}");
+        assert!(html.contains("欢迎使用 &#60;Acme&#62; 文件服务"), "{html}");
+        assert!(html.contains(r#"<a href="/docs">文档</a>"#), "{html}");
+
+        for accept in [
+            "*/*",
+            "application/json",
+            "text/html;q=0.5, application/json",
+        ] {
+            let response = call(&config, accept).await;
+            let body: Value = serde_json::from_str(&body(response).await).unwrap();
+            assert_eq!(body["data"]["message"], config.message, "{accept}");
+        }
+    }
+}
@@ -2,22 +2,59 @@
 //!
 //! 包含 V1 版本所有的 API 端点。
 
-use crate::{AppState, user};
+use crate::{AppState, admin, core::latency::latency_middleware, file, rate_limit, stats, user};
 use aide::axum::ApiRouter;
 use std::sync::Arc;
 
+/// V1 API 的挂载路径
+pub const PREFIX: &str = "/v1";
+
 /// 构建 V1 版本的 API 路由
 ///
 /// 聚合所有 V1 版本的业务模块路由。目前包括：
 /// - /user - 用户管理相关的端点
+/// - /ratelimit - 限流配额查询
+/// - /users - 用户集合操作（如批量导出）及按用户查询的资源（用户文件列表受 `file_upload_enabled` 控制）
+/// - /files - 文件上传（受 `file_upload_enabled` 控制）
+/// - /admin - 管理员运维接口（如运行时日志级别，受 `admin_panel_enabled` 控制）
+/// - /stats - 运行统计（如请求耗时分位数，仅管理员）
+///
+/// 每个子路由按其完整挂载路径（如 `/v1/users`）应用 `server.route_body_limits` 中的覆盖，
+/// 并记录请求耗时（挂在子路由上才能拿到完整的路由模板）。
 ///
 /// # 参数
-/// * `state` - 应用状态，包含数据库连接等资源
+/// * `state` - 应用状态，包含数据库连接和功能开关等资源
 ///
 /// # 返回
 /// 返回配置好的 V1 API 路由器
 pub fn routes(state: Arc<AppState>) -> ApiRouter {
-    ApiRouter::new()
-        .nest_api_service("/user", user::routes(state.clone()))
-        .with_state(state)
+    let features = state.features;
+    let limits = state.body_limits.clone();
+    let latency = state.latency.clone();
+    let nest = |router: ApiRouter<Arc<AppState>>, path: &str, child: ApiRouter| {
+        let child = child.layer(axum::middleware::from_fn_with_state(
+            latency.clone(),
+            latency_middleware,
+        ));
+        router.nest_api_service(path, limits.apply(&format!("{PREFIX}{path}"), child))
+    };
+
+    let mut router = ApiRouter::new();
+    router = nest(router, "/user", user::routes(state.clone()));
+    router = nest(router, "/ratelimit", rate_limit::routes(state.clone()));
+
+    let mut users = user::collection_routes(state.clone());
+    if features.file_upload_enabled {
+        users = users.merge(file::owner_routes(state.clone()));
+    }
+    router = nest(router, "/users", users);
+    if features.file_upload_enabled {
+        router = nest(router, "/files", file::routes(state.clone()));
+    }
+    if features.admin_panel_enabled {
+        router = nest(router, "/admin", admin::routes(state.clone()));
+    }
+    router = nest(router, "/stats", stats::routes(state.clone()));
+
+    router.with_state(state)
 }
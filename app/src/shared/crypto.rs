@@ -0,0 +1,76 @@
+//! 安全随机令牌和常量时间比较
+//!
+//! 密码重置、邮箱验证、API 密钥等场景需要不可预测的令牌，统一由这里从操作系统的安全随机源
+//! （`getrandom`）生成，不使用普通的伪随机数生成器。比较令牌时使用 [`constant_time_compare`]，
+//! 耗时与两者在哪一位不同无关，避免通过响应耗时逐字节猜出令牌。
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use subtle::ConstantTimeEq;
+
+/// 读取 `length` 字节的安全随机数
+///
+/// 操作系统随机源不可用时无法安全地继续运行，直接 panic。
+fn random_bytes(length: usize) -> Vec<u8> {
+    let mut buf = vec![0; length];
+    getrandom::fill(&mut buf).expect("操作系统随机源不可用");
+    buf
+}
+
+/// 生成 `length` 字节随机数的令牌，编码为不带填充的 URL 安全 base64
+///
+/// 输出长度约为 `length` 的 4/3 倍，可以直接放在 URL、请求头和 Cookie 中。
+pub fn generate_token(length: usize) -> String {
+    URL_SAFE_NO_PAD.encode(random_bytes(length))
+}
+
+/// 生成 `length` 字节随机数的令牌，编码为小写十六进制（长度为 `2 * length`）
+pub fn generate_hex_token(length: usize) -> String {
+    random_bytes(length)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// 常量时间比较两个字节串是否相等
+///
+/// 长度不同时直接返回 `false`，长度本身不视为秘密。
+pub fn constant_time_compare(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_have_expected_encoding_and_are_unique() {
+        let token = generate_token(32);
+        assert_eq!(token.len(), 43);
+        assert!(
+            token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+        assert_eq!(URL_SAFE_NO_PAD.decode(&token).unwrap().len(), 32);
+        assert_ne!(token, generate_token(32));
+
+        let hex = generate_hex_token(16);
+        assert_eq!(hex.len(), 32);
+        assert!(
+            hex.chars()
+                .all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase())
+        );
+        assert_ne!(hex, generate_hex_token(16));
+
+        assert_eq!(generate_token(0), "");
+    }
+
+    #[test]
+    fn test_constant_time_compare() {
+        assert!(constant_time_compare(b"token", b"token"));
+        assert!(!constant_time_compare(b"token", b"tokem"));
+        assert!(!constant_time_compare(b"token", b"token2"));
+        assert!(constant_time_compare(b"", b""));
+    }
+}
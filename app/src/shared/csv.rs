@@ -0,0 +1,75 @@
+//! 流式 CSV 写入
+//!
+//! 按 RFC 4180 逐行写入任意 [`AsyncWrite`]，用于大数据量导出时边查询边输出，
+//! 不需要在内存中拼出完整文件。
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// 基于 [`AsyncWrite`] 的 CSV 写入器
+#[derive(Debug)]
+pub struct CsvWriter<W> {
+    inner: W,
+    line: String,
+}
+
+impl<W: AsyncWrite + Unpin> CsvWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            line: String::new(),
+        }
+    }
+
+    /// 写入一行记录，字段按需加引号转义，行尾为 `\r\n`
+    pub async fn write_record<I, S>(&mut self, fields: I) -> std::io::Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.line.clear();
+        for (i, field) in fields.into_iter().enumerate() {
+            if i > 0 {
+                self.line.push(',');
+            }
+            push_field(&mut self.line, field.as_ref());
+        }
+        self.line.push_str("\r\n");
+        self.inner.write_all(self.line.as_bytes()).await
+    }
+
+    /// 刷新底层写入器
+    pub async fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush().await
+    }
+}
+
+/// 追加一个字段：包含逗号、引号或换行时用双引号包裹，内部引号加倍
+fn push_field(line: &mut String, field: &str) {
+    if field.contains([',', '"', '\r', '\n']) {
+        line.push('"');
+        line.push_str(&field.replace('"', "\"\""));
+        line.push('"');
+    } else {
+        line.push_str(field);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_record_quotes_special_fields() {
+        let mut writer = CsvWriter::new(Vec::new());
+        writer.write_record(["id", "name"]).await.unwrap();
+        writer
+            .write_record(["1", "Smith, \"Jr\"\nline"])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.inner).unwrap(),
+            "id,name\r\n1,\"Smith, \"\"Jr\"\"\nline\"\r\n"
+        );
+    }
+}
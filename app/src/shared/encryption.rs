@@ -0,0 +1,224 @@
+//! 数据库敏感列加密
+//!
+//! 使用 AES-256-GCM 加密 TOTP 密钥、会话数据、OAuth 令牌等需要静态加密的字段。
+//! 密文格式为 `nonce(12 字节) || ciphertext || tag(16 字节)`，每次加密使用随机 nonce。
+//!
+//! 实体字段使用 [`EncryptedString`] 即可在写入时自动加密、读取时自动解密，
+//! 前提是启动时已通过 [`install_key`] 安装了 `secrets.encryption_key`。
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sea_orm::sea_query::{ArrayType, ColumnType, Nullable, Value, ValueType, ValueTypeErr};
+use sea_orm::{ColIdx, DbErr, QueryResult, TryGetError, TryGetable};
+
+use crate::error::AppError;
+
+/// AES-256 密钥长度（字节）
+pub const KEY_LEN: usize = 32;
+
+/// GCM nonce 长度（字节）
+const NONCE_LEN: usize = 12;
+
+/// 全局列加密密钥，由 [`install_key`] 在应用启动时设置
+static COLUMN_KEY: OnceLock<[u8; KEY_LEN]> = OnceLock::new();
+
+/// 解析 Base64 编码的 32 字节密钥（可用 `openssl rand -base64 32` 生成）
+pub fn parse_key(encoded: &str) -> Result<[u8; KEY_LEN], String> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("加密密钥不是有效的 Base64：{e}"))?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| format!("加密密钥必须为 {KEY_LEN} 字节，实际为 {}", bytes.len()))
+}
+
+/// 安装全局列加密密钥
+///
+/// 只有第一次调用生效，重复调用会被忽略并返回 `false`。
+pub fn install_key(key: [u8; KEY_LEN]) -> bool {
+    COLUMN_KEY.set(key).is_ok()
+}
+
+/// 使用 AES-256-GCM 加密，返回 `nonce || ciphertext`
+pub fn encrypt(plaintext: &[u8], key: &[u8]) -> Result<Vec<u8>, AppError> {
+    let cipher = cipher(key)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow::anyhow!("数据加密失败"))?;
+
+    let mut output = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+/// 解密 [`encrypt`] 生成的数据，密钥错误或数据被篡改时返回错误
+pub fn decrypt(ciphertext: &[u8], key: &[u8]) -> Result<Vec<u8>, AppError> {
+    let cipher = cipher(key)?;
+    if ciphertext.len() < NONCE_LEN {
+        return Err(anyhow::anyhow!("密文长度不足").into());
+    }
+    let (nonce, data) = ciphertext.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), data)
+        .map_err(|_| anyhow::anyhow!("数据解密失败：密钥错误或数据已损坏").into())
+}
+
+fn cipher(key: &[u8]) -> Result<Aes256Gcm, AppError> {
+    if key.len() != KEY_LEN {
+        return Err(anyhow::anyhow!("加密密钥必须为 {KEY_LEN} 字节，实际为 {}", key.len()).into());
+    }
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)))
+}
+
+fn column_key() -> Result<&'static [u8; KEY_LEN], AppError> {
+    COLUMN_KEY
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("未配置 secrets.encryption_key，无法读写加密列").into())
+}
+
+/// 加密存储的字符串列
+///
+/// 在内存中保存明文，写入数据库时加密为二进制（PostgreSQL `bytea`），读取时自动解密。
+/// `Debug` 输出会隐藏明文。
+///
+/// ```ignore
+/// pub struct Model {
+///     pub totp_secret: Option<EncryptedString>,
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptedString(pub String);
+
+impl EncryptedString {
+    /// 明文内容
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    fn from_ciphertext(ciphertext: &[u8]) -> Result<Self, AppError> {
+        let plaintext = decrypt(ciphertext, column_key()?)?;
+        String::from_utf8(plaintext)
+            .map(Self)
+            .map_err(|_| anyhow::anyhow!("解密结果不是有效的 UTF-8").into())
+    }
+}
+
+impl fmt::Debug for EncryptedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptedString(\"[REDACTED]\")")
+    }
+}
+
+impl From<String> for EncryptedString {
+    fn from(plaintext: String) -> Self {
+        Self(plaintext)
+    }
+}
+
+/// 写入数据库时加密
+///
+/// # Panics
+///
+/// 未安装列加密密钥时 panic：这是部署配置错误，不应静默写入明文。
+impl From<EncryptedString> for Value {
+    fn from(value: EncryptedString) -> Self {
+        let key = column_key().expect("写入加密列前必须配置 secrets.encryption_key");
+        let ciphertext = encrypt(value.0.as_bytes(), key).expect("AES-256-GCM 加密失败");
+        Value::Bytes(Some(Box::new(ciphertext)))
+    }
+}
+
+impl TryGetable for EncryptedString {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let ciphertext: Vec<u8> = Vec::<u8>::try_get_by(res, index)?;
+        Self::from_ciphertext(&ciphertext)
+            .map_err(|e| TryGetError::DbErr(DbErr::Type(e.to_string())))
+    }
+}
+
+impl ValueType for EncryptedString {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        match v {
+            Value::Bytes(Some(ciphertext)) => {
+                Self::from_ciphertext(&ciphertext).map_err(|_| ValueTypeErr)
+            }
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "EncryptedString".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::Bytes
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::VarBinary(sea_orm::sea_query::StringLen::None)
+    }
+}
+
+impl Nullable for EncryptedString {
+    fn null() -> Value {
+        Value::Bytes(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; KEY_LEN] = [7; KEY_LEN];
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let ciphertext = encrypt(b"totp-secret", &KEY).unwrap();
+        assert_eq!(ciphertext.len(), NONCE_LEN + b"totp-secret".len() + 16);
+        assert_eq!(decrypt(&ciphertext, &KEY).unwrap(), b"totp-secret");
+
+        // 随机 nonce：相同明文每次加密结果不同
+        assert_ne!(encrypt(b"totp-secret", &KEY).unwrap(), ciphertext);
+    }
+
+    #[test]
+    fn test_decrypt_rejects_wrong_key_and_tampering() {
+        let mut ciphertext = encrypt(b"oauth-token", &KEY).unwrap();
+        assert!(decrypt(&ciphertext, &[8; KEY_LEN]).is_err());
+
+        let last = ciphertext.len() - 1;
+        ciphertext[last] ^= 1;
+        assert!(decrypt(&ciphertext, &KEY).is_err());
+        assert!(decrypt(b"short", &KEY).is_err());
+        assert!(encrypt(b"data", b"too-short-key").is_err());
+    }
+
+    #[test]
+    fn test_encrypted_string_value_roundtrip() {
+        install_key(KEY);
+
+        let value: Value = EncryptedString("session-data".to_string()).into();
+        let Value::Bytes(Some(ref stored)) = value else {
+            panic!("加密列应存储为二进制");
+        };
+        assert!(!stored.windows(12).any(|w| w == b"session-data"));
+
+        let restored = <EncryptedString as ValueType>::try_from(value).unwrap();
+        assert_eq!(restored.expose(), "session-data");
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let encoded = STANDARD.encode(KEY);
+        assert_eq!(parse_key(&encoded).unwrap(), KEY);
+        assert!(parse_key("c2hvcnQ=").is_err());
+        assert!(parse_key("not base64!").is_err());
+    }
+}
@@ -0,0 +1,65 @@
+//! 缓存键用的稳定哈希
+//!
+//! ETag、幂等键、single-flight 等场景需要把请求属性映射为短小且稳定的键。[`stable_hash`] 使用非加密的
+//! XXH3（64 位，固定种子），同一输入在每次运行、每个实例上得到相同结果，可以写入 Redis 等共享存储。
+//!
+//! 这里的哈希不抗碰撞攻击，不能用于签名、令牌或密码等安全相关场景，这些场景使用 HMAC-SHA256
+//! （见 [`crate::core::middleware::csrf`]）或 Argon2（见 [`crate::shared::password`]）。
+//!
+//! 输入通过 [`Hash`] 写入哈希器，标准库对 `usize` 等类型按平台字长写入，跨平台或跨 Rust 版本
+//! 不保证结果一致；需要长期持久化的键应只由字符串和定长整数构成。
+
+use std::hash::{Hash, Hasher};
+
+use xxhash_rust::xxh3::Xxh3;
+
+/// 计算 `value` 的稳定哈希，返回 16 位十六进制小写字符串
+pub fn stable_hash(value: &impl Hash) -> String {
+    let mut hasher = Xxh3::new();
+    value.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Hash)]
+    struct CacheKey<'a> {
+        method: &'a str,
+        path: &'a str,
+        user_id: i32,
+    }
+
+    #[test]
+    fn test_stable_hash_is_deterministic() {
+        let key = CacheKey {
+            method: "GET",
+            path: "/v1/users",
+            user_id: 42,
+        };
+        let hash = stable_hash(&key);
+        assert_eq!(hash.len(), 16);
+        assert!(
+            hash.chars()
+                .all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase())
+        );
+        assert_eq!(hash, stable_hash(&key));
+
+        // 固定的期望值：输出随运行或实例变化时此处失败
+        assert_eq!(stable_hash(&"GET /v1/users"), "45f1cd36f6dbae25");
+        assert_eq!(stable_hash(&(42i64, "alice")), "4708c20834e96b67");
+    }
+
+    #[test]
+    fn test_stable_hash_distinguishes_inputs() {
+        let key = |user_id| CacheKey {
+            method: "GET",
+            path: "/v1/users",
+            user_id,
+        };
+        assert_ne!(stable_hash(&key(1)), stable_hash(&key(2)));
+        // 字符串哈希带有结束标记，拼接边界不同的输入不会相同
+        assert_ne!(stable_hash(&("ab", "c")), stable_hash(&("a", "bc")));
+    }
+}
@@ -0,0 +1,107 @@
+//! Snowflake 风格的分布式唯一 ID 生成器
+//!
+//! 基于 Sonyflake 算法：39 位时间（10ms 精度）+ 8 位序列号 + 16 位机器 ID，
+//! 生成的 ID 为正的 `i64`，在同一生成器内严格递增，可直接用作实体主键（如管理操作审计记录）。
+//! 多实例部署时需为每个实例配置不同的 `server.machine_id`。
+
+use std::sync::OnceLock;
+
+use sonyflake::Sonyflake;
+
+use crate::error::{AppError, ValidationError};
+
+/// 全局 ID 生成器，由 [`install`] 在应用启动时设置
+static GLOBAL: OnceLock<IdGenerator> = OnceLock::new();
+
+/// 线程安全的 ID 生成器
+///
+/// 内部状态由互斥锁保护，克隆后共享同一序列，可放入 `AppState` 或跨线程使用。
+#[derive(Debug, Clone)]
+pub struct IdGenerator {
+    inner: Sonyflake,
+}
+
+impl IdGenerator {
+    /// 使用指定机器 ID 创建生成器
+    pub fn new(machine_id: u16) -> Result<Self, AppError> {
+        let inner = Sonyflake::builder()
+            .machine_id(&move || Ok(machine_id))
+            .finalize()
+            .map_err(|e| {
+                AppError::Validation(ValidationError::custom(format!("ID 生成器初始化失败：{e}")))
+            })?;
+        Ok(Self { inner })
+    }
+
+    /// 生成下一个 ID
+    ///
+    /// 同一毫秒窗口内序列号耗尽时会短暂阻塞到下一个时间窗口，以保证单调递增。
+    ///
+    /// # Panics
+    ///
+    /// 仅在时间位溢出（约 174 年后）或内部锁中毒时 panic，正常运行不会发生。
+    pub fn next_id(&self) -> i64 {
+        let id = self.inner.next_id().expect("snowflake ID 生成失败");
+        // Sonyflake ID 最高位恒为 0，转换为 i64 不会溢出
+        id as i64
+    }
+}
+
+/// 安装全局 ID 生成器
+///
+/// 只有第一次调用生效，重复调用会被忽略并返回 `false`。
+pub fn install(generator: IdGenerator) -> bool {
+    GLOBAL.set(generator).is_ok()
+}
+
+/// 使用全局生成器生成下一个 ID
+///
+/// 未调用 [`install`] 时回退为机器 ID 0 的生成器（仅适用于单实例或测试场景）。
+pub fn next_id() -> i64 {
+    GLOBAL
+        .get_or_init(|| IdGenerator::new(0).expect("默认 ID 生成器初始化失败"))
+        .next_id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_ids_are_increasing() {
+        let generator = IdGenerator::new(7).unwrap();
+        let ids: Vec<i64> = (0..1000).map(|_| generator.next_id()).collect();
+
+        assert!(ids.iter().all(|id| *id > 0));
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_ids_are_unique_across_threads() {
+        let generator = IdGenerator::new(1).unwrap();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let generator = generator.clone();
+                std::thread::spawn(move || {
+                    (0..500).map(|_| generator.next_id()).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut seen = HashSet::new();
+        for handle in handles {
+            for id in handle.join().unwrap() {
+                assert!(seen.insert(id), "重复的 ID: {id}");
+            }
+        }
+        assert_eq!(seen.len(), 2000);
+    }
+
+    #[test]
+    fn test_global_next_id_is_increasing() {
+        let first = next_id();
+        let second = next_id();
+        assert!(second > first);
+    }
+}
@@ -0,0 +1,59 @@
+//! 进程内的后台任务队列
+//!
+//! 请求处理只把任务投递到有界通道，由后台任务逐个消费，请求不等待执行结果。
+//! 投递使用 `try_send`，队列满时直接丢弃；队列不持久化，调用方需要有定期任务补上丢失的投递。
+
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc;
+
+/// 队列容量
+const QUEUE_CAPACITY: usize = 1024;
+
+/// 后台任务队列
+///
+/// 克隆后的实例共享同一个通道；接收端只能由消费任务通过 [`JobQueue::take_receiver`] 取走一次。
+#[derive(Debug, Clone)]
+pub struct JobQueue<T> {
+    tx: mpsc::Sender<T>,
+    rx: Arc<Mutex<Option<mpsc::Receiver<T>>>>,
+}
+
+impl<T> Default for JobQueue<T> {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        Self {
+            tx,
+            rx: Arc::new(Mutex::new(Some(rx))),
+        }
+    }
+}
+
+impl<T> JobQueue<T> {
+    /// 投递任务，队列已满或消费任务已退出时返回 `false`
+    pub fn enqueue(&self, job: T) -> bool {
+        self.tx.try_send(job).is_ok()
+    }
+
+    /// 取走接收端，已被取走时返回 `None`
+    pub fn take_receiver(&self) -> Option<mpsc::Receiver<T>> {
+        self.rx.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_queue_delivers_to_single_receiver() {
+        let queue = JobQueue::default();
+        assert!(queue.enqueue(1));
+        assert!(queue.clone().enqueue(2));
+
+        let mut rx = queue.take_receiver().unwrap();
+        assert!(queue.take_receiver().is_none());
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, Some(2));
+    }
+}
@@ -1,4 +1,5 @@
 use chrono::Utc;
+use entity::user::UserId;
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, TokenData, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
 
@@ -6,7 +7,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     /// 用户 ID
-    pub sub: i32,
+    pub sub: UserId,
 
     /// 过期时间（Unix timestamp）
     pub exp: i64,
@@ -17,7 +18,7 @@ pub struct Claims {
 
 impl Claims {
     /// 创建新的 claims，默认过期时间为 7 天
-    pub fn new(user_id: i32, expires_in_secs: i64) -> Self {
+    pub fn new(user_id: UserId, expires_in_secs: i64) -> Self {
         let now = Utc::now().timestamp();
         Self {
             sub: user_id,
@@ -53,7 +54,7 @@ impl JwtService {
     /// 返回生成的 token 字符串
     pub fn generate_token(
         &self,
-        user_id: i32,
+        user_id: UserId,
         expires_in_secs: i64,
     ) -> Result<String, jsonwebtoken::errors::Error> {
         let claims = Claims::new(user_id, expires_in_secs);
@@ -81,7 +82,49 @@ impl JwtService {
     ///
     /// # 返回
     /// 返回用户 ID，如果 token 无效或过期则返回错误
-    pub fn extract_user_id(&self, token: &str) -> Result<i32, jsonwebtoken::errors::Error> {
+    pub fn extract_user_id(&self, token: &str) -> Result<UserId, jsonwebtoken::errors::Error> {
         self.verify_token(token).map(|data| data.claims.sub)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::user_id;
+
+    fn jwt() -> JwtService {
+        JwtService::new("test-secret-at-least-32-characters!!".to_string())
+    }
+
+    #[test]
+    fn test_token_round_trips_user_id() {
+        let jwt = jwt();
+        let token = jwt.generate_token(user_id(42), 3600).unwrap();
+        assert_eq!(jwt.extract_user_id(&token).unwrap(), user_id(42));
+
+        // 自增 ID 的 sub 为数字，UUID 的 sub 为带连字符的字符串
+        let claims = serde_json::to_value(jwt.verify_token(&token).unwrap().claims).unwrap();
+        #[cfg(feature = "uuid-user-id")]
+        assert_eq!(claims["sub"], "00000000-0000-0000-0000-00000000002a");
+        #[cfg(not(feature = "uuid-user-id"))]
+        assert_eq!(claims["sub"], 42);
+    }
+
+    #[test]
+    fn test_token_with_foreign_sub_is_rejected() {
+        #[derive(Serialize)]
+        struct ForeignClaims {
+            sub: &'static str,
+            exp: i64,
+        }
+
+        // 切换主键类型前签发的 token 或其他系统的 token 不能被解析为用户 ID
+        let jwt = jwt();
+        let claims = ForeignClaims {
+            sub: "alice",
+            exp: Utc::now().timestamp() + 3600,
+        };
+        let token = encode(&Header::default(), &claims, &jwt.encoding_key).unwrap();
+        assert!(jwt.extract_user_id(&token).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! 基于数据库的分布式任务锁
+//!
+//! 多实例部署时，定期任务（如存储对账）只应在一个实例上运行。锁以租约的形式记录在 `job_lock` 表中：
+//! 获取时写入随机的持有者标识和到期时间，只有记录不存在或租约已到期时才能获取成功；
+//! 持有者崩溃、未能释放时，租约到期后其他实例即可获取，不需要人工清理。
+
+use std::time::Duration;
+
+use chrono::Utc;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set};
+use uuid::Uuid;
+
+use crate::{AppState, shared::FromState};
+use entity::job_lock;
+
+/// 任务锁
+#[derive(Debug, Clone)]
+pub struct JobLock {
+    db: DatabaseConnection,
+}
+
+impl FromState for JobLock {
+    fn from_state(app: &AppState) -> Self {
+        Self::new(app.db.clone())
+    }
+}
+
+impl JobLock {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// 尝试获取名为 `name` 的锁，租约为 `ttl`
+    ///
+    /// 锁已被持有且租约未到期时返回 `None`，不等待。`ttl` 应长于任务的最长运行时间，
+    /// 否则任务尚未结束时锁就可能被其他实例取走。
+    pub async fn try_acquire(&self, name: &str, ttl: Duration) -> Result<Option<LockGuard>, DbErr> {
+        let holder = Uuid::new_v4().to_string();
+        let now = Utc::now();
+        let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        let expires_at = now
+            .checked_add_signed(ttl)
+            .unwrap_or(chrono::DateTime::<Utc>::MAX_UTC);
+
+        // 记录已存在时只接管已到期的租约，判断和写入在同一条语句中完成，并发获取只有一个成功
+        let acquired = job_lock::Entity::insert(job_lock::ActiveModel {
+            name: Set(name.to_string()),
+            holder: Set(holder.clone()),
+            expires_at: Set(expires_at.fixed_offset()),
+        })
+        .on_conflict(
+            OnConflict::column(job_lock::Column::Name)
+                .update_columns([job_lock::Column::Holder, job_lock::Column::ExpiresAt])
+                .action_and_where(
+                    Expr::col((job_lock::Entity, job_lock::Column::ExpiresAt))
+                        .lt(now.fixed_offset()),
+                )
+                .to_owned(),
+        )
+        .exec_without_returning(&self.db)
+        .await?;
+
+        Ok((acquired > 0).then(|| LockGuard {
+            db: self.db.clone(),
+            name: name.to_string(),
+            holder,
+        }))
+    }
+}
+
+/// 已获取的锁，需要显式调用 [`LockGuard::release`] 释放
+///
+/// 释放需要访问数据库，无法在 `Drop` 中完成；未释放的锁在租约到期后自动失效。
+#[derive(Debug)]
+#[must_use = "锁需要调用 release 释放，否则要等租约到期"]
+pub struct LockGuard {
+    db: DatabaseConnection,
+    name: String,
+    holder: String,
+}
+
+impl LockGuard {
+    /// 释放锁；租约已到期并被其他实例接管时不做处理
+    pub async fn release(self) -> Result<(), DbErr> {
+        job_lock::Entity::delete_many()
+            .filter(job_lock::Column::Name.eq(&self.name))
+            .filter(job_lock::Column::Holder.eq(&self.holder))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use migration::{Migrator, MigratorTrait};
+
+    async fn migrated_lock() -> JobLock {
+        // 内存 SQLite 每个连接是独立的数据库，限制为单连接才能看到迁移结果
+        let mut options = sea_orm::ConnectOptions::new("sqlite::memory:");
+        options.max_connections(1);
+        let db = sea_orm::Database::connect(options).await.unwrap();
+        Migrator::up(&db, None).await.unwrap();
+        JobLock::new(db)
+    }
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[tokio::test]
+    async fn test_lock_is_exclusive_until_released() {
+        let lock = migrated_lock().await;
+        let guard = lock.try_acquire("reconcile", TTL).await.unwrap().unwrap();
+        assert!(lock.try_acquire("reconcile", TTL).await.unwrap().is_none());
+        // 不同名称的锁互不影响
+        let other = lock.try_acquire("cleanup", TTL).await.unwrap().unwrap();
+
+        guard.release().await.unwrap();
+        let again = lock.try_acquire("reconcile", TTL).await.unwrap().unwrap();
+        again.release().await.unwrap();
+        other.release().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_expired_lock_can_be_taken_over() {
+        let lock = migrated_lock().await;
+        let stale = lock
+            .try_acquire("reconcile", Duration::ZERO)
+            .await
+            .unwrap()
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let current = lock.try_acquire("reconcile", TTL).await.unwrap().unwrap();
+        // 过期的持有者释放时不会删除接管者的锁
+        stale.release().await.unwrap();
+        assert!(lock.try_acquire("reconcile", TTL).await.unwrap().is_none());
+        current.release().await.unwrap();
+    }
+}
@@ -1,8 +1,35 @@
+/// 安全随机令牌生成和常量时间比较
+pub mod crypto;
+/// 流式 CSV 写入（RFC 4180）
+pub mod csv;
+/// 数据库敏感列加密（AES-256-GCM）
+pub mod encryption;
 /// 从应用状态中提取服务的 Trait
 mod from_state;
+/// 缓存键用的非加密稳定哈希（XXH3）
+pub mod hashing;
+/// Snowflake 风格的分布式唯一 ID 生成器
+pub mod ids;
+/// 进程内的后台任务队列
+pub mod jobs;
 /// JWT 令牌生成和验证服务
 pub mod jwt;
+/// 基于数据库租约的分布式任务锁
+pub mod lock;
+/// 请求 DTO 到数据库模型的转换 Trait
+mod model_mapping;
 /// 密码哈希和验证功能（使用 Argon2）
 pub mod password;
+/// `Result<Option<T>>` 到 404 的转换扩展
+mod result;
+/// 上传文件的恶意软件扫描（clamd）
+pub mod scanner;
+/// 防止意外打印的敏感字符串类型
+mod secret;
+/// 文件内容存储后端（本地磁盘）
+pub mod storage;
 
 pub use from_state::*;
+pub use model_mapping::{FromDto, IntoModel};
+pub use result::OrNotFound;
+pub use secret::SharedSecret;
@@ -0,0 +1,46 @@
+/// 从请求 DTO 构建数据库模型的 Trait
+///
+/// 统一请求 DTO 到 SeaORM `ActiveModel` 的转换流程：先执行 [`validate`](FromDto::validate)
+/// 校验钩子，再由 [`from_dto`](FromDto::from_dto) 完成字段映射和默认值填充。
+/// 调用方通常通过 [`IntoModel`] 使用，保持服务层代码简洁。
+pub trait FromDto<D>: Sized {
+    /// 校验或转换失败时返回的错误类型
+    type Error;
+
+    /// 转换前的校验钩子，默认不做任何校验
+    ///
+    /// # 参数
+    /// * `dto` - 待转换的请求 DTO
+    fn validate(_dto: &D) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// 将已通过校验的 DTO 转换为模型
+    ///
+    /// # 参数
+    /// * `dto` - 请求 DTO
+    ///
+    /// # 返回
+    /// 返回填充好字段的模型，未在 DTO 中出现的字段保持默认值
+    fn from_dto(dto: D) -> Result<Self, Self::Error>;
+}
+
+/// 将请求 DTO 转换为数据库模型的 Trait
+///
+/// 对所有实现了 [`FromDto`] 的模型自动实现，先校验再转换。
+pub trait IntoModel<M> {
+    /// 校验失败或转换失败时返回的错误类型
+    type Error;
+
+    /// 校验并转换为模型
+    fn into_model(self) -> Result<M, Self::Error>;
+}
+
+impl<D, M: FromDto<D>> IntoModel<M> for D {
+    type Error = M::Error;
+
+    fn into_model(self) -> Result<M, Self::Error> {
+        M::validate(&self)?;
+        M::from_dto(self)
+    }
+}
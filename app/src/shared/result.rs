@@ -0,0 +1,53 @@
+use crate::error::AppError;
+
+/// `Result<Option<T>, E>` 的扩展：把查询结果为空转换为 404
+///
+/// 数据访问层按主键或唯一键查询时返回 `Result<Option<Model>, DbErr>`，
+/// 服务层用 `.or_not_found("用户不存在")?` 代替重复的 `.ok_or(...)`。
+pub trait OrNotFound<T> {
+    /// `Ok(None)` 转换为 `AppError::NotFound(message)`，错误按 `Into<AppError>` 原样转换
+    fn or_not_found(self, message: impl Into<String>) -> Result<T, AppError>;
+}
+
+impl<T, E: Into<AppError>> OrNotFound<T> for Result<Option<T>, E> {
+    fn or_not_found(self, message: impl Into<String>) -> Result<T, AppError> {
+        self.map_err(Into::into)?
+            .ok_or_else(|| AppError::NotFound(message.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::assert_api_error;
+    use axum::{http::StatusCode, response::IntoResponse};
+    use sea_orm::DbErr;
+
+    #[test]
+    fn test_some_is_unwrapped() {
+        let found: Result<Option<i32>, DbErr> = Ok(Some(7));
+        assert_eq!(found.or_not_found("用户不存在").unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_none_becomes_not_found() {
+        let missing: Result<Option<i32>, DbErr> = Ok(None);
+        let err = missing.or_not_found("用户不存在").unwrap_err();
+        assert!(matches!(err, AppError::NotFound(ref m) if m == "用户不存在"));
+
+        assert_api_error(
+            err.into_response(),
+            StatusCode::NOT_FOUND,
+            "NOT_FOUND",
+            "用户不存在",
+        )
+        .await;
+    }
+
+    #[test]
+    fn test_err_is_propagated() {
+        let failed: Result<Option<i32>, DbErr> = Err(DbErr::Custom("boom".to_string()));
+        let err = failed.or_not_found("用户不存在").unwrap_err();
+        assert!(matches!(err, AppError::Database(DbErr::Custom(ref m)) if m == "boom"));
+    }
+}
@@ -0,0 +1,211 @@
+//! ClamAV 守护进程（clamd）扫描器
+//!
+//! 使用 clamd 的 TCP 协议：`zINSTREAM` 命令后按「4 字节大端长度 + 数据」分块发送内容，
+//! 以长度为 0 的块结束，clamd 回复 `stream: OK` 或 `stream: <特征名> FOUND`。
+//! 每次扫描使用独立的连接，整个过程受 `file_scan.timeout_secs` 限制。
+
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use super::{FileScanner, ScanVerdict};
+use crate::core::config::FileScanConfig;
+
+/// 单个数据块的大小（字节），需小于 clamd 的 `StreamMaxLength`
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// clamd 回复的最大长度（字节）
+const MAX_REPLY_BYTES: u64 = 4096;
+
+/// 通过 TCP 调用 clamd 的扫描器
+#[derive(Debug, Clone)]
+pub struct ClamdScanner {
+    host: String,
+    port: u16,
+    timeout: Duration,
+}
+
+impl ClamdScanner {
+    /// 使用 `file_scan` 中的 clamd 地址和超时时间创建扫描器
+    pub fn new(config: &FileScanConfig) -> Self {
+        Self {
+            host: config.clamd_host.clone(),
+            port: config.clamd_port,
+            timeout: Duration::from_secs(config.timeout_secs),
+        }
+    }
+
+    /// 在超时时间内执行与 clamd 的一次交互
+    async fn with_timeout<T>(&self, task: impl Future<Output = io::Result<T>>) -> io::Result<T> {
+        tokio::time::timeout(self.timeout, task)
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "clamd 响应超时"))?
+    }
+
+    async fn connect(&self) -> io::Result<TcpStream> {
+        TcpStream::connect((self.host.as_str(), self.port)).await
+    }
+}
+
+/// 读取以 `\0` 结尾的回复，去掉结尾的 `\0` 和空白
+async fn read_reply(stream: &mut TcpStream) -> io::Result<String> {
+    let mut reply = Vec::new();
+    stream.take(MAX_REPLY_BYTES).read_to_end(&mut reply).await?;
+    let reply = String::from_utf8_lossy(&reply);
+    Ok(reply.trim_end_matches(['\0', '\n', ' ']).to_string())
+}
+
+/// 解析 INSTREAM 的回复
+fn parse_scan_reply(reply: &str) -> io::Result<ScanVerdict> {
+    let result = reply.strip_prefix("stream: ").unwrap_or(reply);
+    if result == "OK" {
+        return Ok(ScanVerdict::Clean);
+    }
+    if let Some(signature) = result.strip_suffix(" FOUND") {
+        return Ok(ScanVerdict::Infected(signature.to_string()));
+    }
+    Err(io::Error::other(format!("clamd 扫描失败：{reply}")))
+}
+
+impl FileScanner for ClamdScanner {
+    async fn scan<R: AsyncRead + Unpin + Send>(&self, mut reader: R) -> io::Result<ScanVerdict> {
+        self.with_timeout(async {
+            let mut stream = self.connect().await?;
+            stream.write_all(b"zINSTREAM\0").await?;
+
+            let mut buf = vec![0; CHUNK_BYTES];
+            loop {
+                let n = reader.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                stream.write_all(&(n as u32).to_be_bytes()).await?;
+                stream.write_all(&buf[..n]).await?;
+            }
+            stream.write_all(&0u32.to_be_bytes()).await?;
+
+            parse_scan_reply(&read_reply(&mut stream).await?)
+        })
+        .await
+    }
+
+    async fn ping(&self) -> io::Result<()> {
+        self.with_timeout(async {
+            let mut stream = self.connect().await?;
+            stream.write_all(b"zPING\0").await?;
+            match read_reply(&mut stream).await?.as_str() {
+                "PONG" => Ok(()),
+                reply => Err(io::Error::other(format!("clamd 回复异常：{reply}"))),
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// 测试内容中出现该标记时，模拟的 clamd 报告感染
+    const MARKER: &[u8] = b"EICAR-TEST";
+
+    /// 启动按 clamd 协议应答的模拟服务，返回端口
+    async fn fake_clamd() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut command = Vec::new();
+                    loop {
+                        let byte = stream.read_u8().await.unwrap();
+                        if byte == 0 {
+                            break;
+                        }
+                        command.push(byte);
+                    }
+                    let reply: &[u8] = match command.as_slice() {
+                        b"zPING" => b"PONG\0",
+                        b"zINSTREAM" => {
+                            let mut content = Vec::new();
+                            loop {
+                                let len = stream.read_u32().await.unwrap() as usize;
+                                if len == 0 {
+                                    break;
+                                }
+                                let mut chunk = vec![0; len];
+                                stream.read_exact(&mut chunk).await.unwrap();
+                                content.extend(chunk);
+                            }
+                            if content.windows(MARKER.len()).any(|w| w == MARKER) {
+                                b"stream: Eicar-Test-Signature FOUND\0"
+                            } else {
+                                b"stream: OK\0"
+                            }
+                        }
+                        _ => b"UNKNOWN COMMAND\0",
+                    };
+                    stream.write_all(reply).await.unwrap();
+                });
+            }
+        });
+        port
+    }
+
+    fn scanner(port: u16) -> ClamdScanner {
+        ClamdScanner::new(&FileScanConfig {
+            clamd_port: port,
+            timeout_secs: 5,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_scan_reports_clean_and_infected() {
+        let scanner = scanner(fake_clamd().await);
+        assert!(scanner.ping().await.is_ok());
+
+        let clean = vec![b'a'; CHUNK_BYTES * 2 + 1];
+        assert_eq!(
+            scanner.scan(clean.as_slice()).await.unwrap(),
+            ScanVerdict::Clean
+        );
+
+        let mut infected = vec![b'a'; CHUNK_BYTES];
+        infected.extend_from_slice(MARKER);
+        assert_eq!(
+            scanner.scan(infected.as_slice()).await.unwrap(),
+            ScanVerdict::Infected("Eicar-Test-Signature".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_clamd_returns_error() {
+        // 绑定后立即释放，端口上没有服务监听
+        let port = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let scanner = scanner(port);
+        assert!(scanner.ping().await.is_err());
+        assert!(scanner.scan(&b"content"[..]).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_scan_reply() {
+        assert_eq!(parse_scan_reply("stream: OK").unwrap(), ScanVerdict::Clean);
+        assert_eq!(
+            parse_scan_reply("stream: Win.Test.EICAR_HDB-1 FOUND").unwrap(),
+            ScanVerdict::Infected("Win.Test.EICAR_HDB-1".to_string())
+        );
+        assert!(parse_scan_reply("INSTREAM size limit exceeded. ERROR").is_err());
+    }
+}
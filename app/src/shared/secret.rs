@@ -0,0 +1,79 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::Deref;
+
+/// 脱敏占位文本
+const REDACTED: &str = "[REDACTED]";
+
+/// 敏感字符串（密钥、令牌等）
+///
+/// `Debug`、`Display` 和序列化输出均为 `[REDACTED]`，避免随配置或状态结构体被意外打印到日志。
+/// 需要实际值时通过 `Deref<Target = str>` 访问，例如 `secret.as_bytes()`。
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SharedSecret(String);
+
+impl SharedSecret {
+    /// 包装一个敏感字符串
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self(secret.into())
+    }
+}
+
+impl Deref for SharedSecret {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SharedSecret {
+    fn from(secret: String) -> Self {
+        Self(secret)
+    }
+}
+
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedSecret").field(&REDACTED).finish()
+    }
+}
+
+impl fmt::Display for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedSecret").field(&REDACTED).finish()
+    }
+}
+
+impl Serialize for SharedSecret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl<'de> Deserialize<'de> for SharedSecret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_is_redacted() {
+        let secret = SharedSecret::new("super-secret-value");
+
+        assert_eq!(format!("{secret:?}"), r#"SharedSecret("[REDACTED]")"#);
+        assert_eq!(secret.to_string(), r#"SharedSecret("[REDACTED]")"#);
+        assert_eq!(serde_json::to_string(&secret).unwrap(), r#""[REDACTED]""#);
+        assert_eq!(secret.as_bytes(), b"super-secret-value");
+    }
+
+    #[test]
+    fn test_secret_deserializes_from_plain_string() {
+        let secret: SharedSecret = serde_json::from_str(r#""abc""#).unwrap();
+        assert_eq!(&*secret, "abc");
+    }
+}
@@ -0,0 +1,708 @@
+//! 文件内容存储
+//!
+//! 元数据写入数据库，文件内容通过 [`Storage`] 按对象键写入存储后端。
+//! 写入以流的方式进行，上传大文件时内存占用只与单次写入的块大小有关。
+//! 目前有本地磁盘 [`LocalStorage`] 和 S3 兼容对象存储 [`S3Storage`] 两种实现，
+//! 由 [`Storages`] 按 `object_storage.backend` 选择新文件写入的后端，
+//! 已有文件按元数据中记录的 [`StorageBackend`] 读取，两种后端可以并存以便逐步迁移。
+
+use std::io;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use chrono::{DateTime, Utc};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWrite, BufWriter, ReadBuf, Take};
+
+use crate::core::config::ObjectStorageConfig;
+
+mod s3;
+
+pub use entity::enums::StorageBackend;
+pub use s3::{S3Reader, S3Storage, S3Writer};
+
+/// 本地文件写入缓冲大小（字节），减少小块写入带来的系统调用次数
+const LOCAL_WRITE_BUFFER_BYTES: usize = 64 * 1024;
+
+/// 存储后端中的一个对象
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectInfo {
+    /// 对象键
+    pub key: String,
+
+    /// 最后修改时间，后端无法提供时为 `None`
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// 文件内容存储后端
+pub trait Storage: Send + Sync {
+    /// 对象写入器
+    type Writer: AsyncWrite + Unpin + Send;
+
+    /// 对象读取器
+    type Reader: AsyncRead + Unpin + Send;
+
+    /// 创建对象并返回写入器，已存在时覆盖
+    ///
+    /// 写入完成后必须调用 `shutdown` 提交内容；中途放弃时丢弃写入器并调用 [`Storage::delete`]。
+    async fn create(&self, key: &str) -> io::Result<Self::Writer>;
+
+    /// 打开已提交的对象用于读取，对象不存在时返回 `NotFound`
+    async fn open(&self, key: &str) -> io::Result<Self::Reader>;
+
+    /// 打开已提交的对象，只读取 `range` 区间内的字节（用于 HTTP Range 请求）
+    ///
+    /// `range` 由调用方按对象大小校验，必须非空且不超出对象末尾；对象不存在时返回 `NotFound`。
+    async fn open_range(&self, key: &str, range: Range<u64>) -> io::Result<Self::Reader>;
+
+    /// 删除对象，对象不存在时视为成功
+    async fn delete(&self, key: &str) -> io::Result<()>;
+
+    /// 将已提交的对象移动到 `to`，目标已存在时覆盖，源对象不存在时返回 `NotFound`
+    async fn rename(&self, from: &str, to: &str) -> io::Result<()>;
+
+    /// 按键的顺序列出排在 `after` 之后的至多 `limit` 个对象
+    ///
+    /// 用于分批遍历全部对象：下一批以上一批最后一个键作为 `after`，返回空列表时遍历结束。
+    /// 排序规则由后端决定，但同一后端的分批遍历不会遗漏或重复未变动的对象。
+    async fn list(&self, after: Option<&str>, limit: usize) -> io::Result<Vec<ObjectInfo>>;
+
+    /// 生成有效期为 `expires_in_secs` 秒的直接下载地址，下载时响应头使用指定的类型和 `Content-Disposition`
+    ///
+    /// 不支持预签名的后端返回 `None`，由调用方改用应用签名的下载地址。
+    fn presign_download(
+        &self,
+        _key: &str,
+        _expires_in_secs: u64,
+        _content_type: &str,
+        _content_disposition: &str,
+    ) -> Option<String> {
+        None
+    }
+}
+
+/// 已配置的存储后端集合
+///
+/// 新对象写入默认后端（[`Storages::primary`]），已有对象按记录的后端通过 [`Storages::get`] 访问。
+#[derive(Debug, Clone)]
+pub struct Storages<S> {
+    default: StorageBackend,
+    backends: Vec<(StorageBackend, S)>,
+}
+
+impl<S> Storages<S> {
+    /// 只包含一个后端的集合，新对象写入该后端
+    pub fn single(backend: StorageBackend, storage: S) -> Self {
+        Self {
+            default: backend,
+            backends: vec![(backend, storage)],
+        }
+    }
+
+    /// 增加一个可读写但不作为默认的后端，已存在同名后端时替换
+    pub fn with(mut self, backend: StorageBackend, storage: S) -> Self {
+        self.backends.retain(|(b, _)| *b != backend);
+        self.backends.push((backend, storage));
+        self
+    }
+
+    /// 新对象写入的后端
+    pub fn default_backend(&self) -> StorageBackend {
+        self.default
+    }
+
+    /// 默认后端的存储
+    pub fn primary(&self) -> &S {
+        self.get(self.default).expect("默认存储后端在构造时已注册")
+    }
+
+    /// 全部已配置的后端
+    pub fn iter(&self) -> impl Iterator<Item = (StorageBackend, &S)> {
+        self.backends
+            .iter()
+            .map(|(backend, storage)| (*backend, storage))
+    }
+
+    /// 指定后端的存储，未配置该后端时返回 `Unsupported`
+    pub fn get(&self, backend: StorageBackend) -> io::Result<&S> {
+        self.backends
+            .iter()
+            .find(|(b, _)| *b == backend)
+            .map(|(_, storage)| storage)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("未配置存储后端：{backend}"),
+                )
+            })
+    }
+}
+
+impl Storages<AnyStorage> {
+    /// 按配置创建存储后端集合
+    ///
+    /// 本地磁盘始终可用（根目录为 `upload.storage_dir`），配置了 `object_storage.s3.bucket` 时加入 S3，
+    /// 新文件写入 `object_storage.backend` 指定的后端。
+    pub fn from_config(config: &ObjectStorageConfig, local_root: &str) -> io::Result<Self> {
+        let local = AnyStorage::Local(LocalStorage::new(local_root));
+        let mut storages = Self::single(StorageBackend::Local, local);
+        if config.s3.is_configured() {
+            let s3 = S3Storage::new(&config.s3)?;
+            storages = storages.with(StorageBackend::S3, AnyStorage::S3(s3));
+        }
+        // 默认后端必须已注册，否则 primary() 无法取到存储
+        storages.get(config.backend)?;
+        storages.default = config.backend;
+        Ok(storages)
+    }
+}
+
+/// 运行时选择的存储后端
+#[derive(Debug, Clone)]
+pub enum AnyStorage {
+    /// 本地磁盘
+    Local(LocalStorage),
+
+    /// S3 兼容对象存储
+    S3(S3Storage),
+}
+
+/// [`AnyStorage`] 的写入器
+pub enum AnyWriter {
+    Local(<LocalStorage as Storage>::Writer),
+    S3(S3Writer),
+}
+
+/// [`AnyStorage`] 的读取器
+pub enum AnyReader {
+    Local(<LocalStorage as Storage>::Reader),
+    S3(S3Reader),
+}
+
+impl Storage for AnyStorage {
+    type Writer = AnyWriter;
+    type Reader = AnyReader;
+
+    async fn create(&self, key: &str) -> io::Result<Self::Writer> {
+        match self {
+            Self::Local(storage) => storage.create(key).await.map(AnyWriter::Local),
+            Self::S3(storage) => storage.create(key).await.map(AnyWriter::S3),
+        }
+    }
+
+    async fn open(&self, key: &str) -> io::Result<Self::Reader> {
+        match self {
+            Self::Local(storage) => storage.open(key).await.map(AnyReader::Local),
+            Self::S3(storage) => storage.open(key).await.map(AnyReader::S3),
+        }
+    }
+
+    async fn open_range(&self, key: &str, range: Range<u64>) -> io::Result<Self::Reader> {
+        match self {
+            Self::Local(storage) => storage.open_range(key, range).await.map(AnyReader::Local),
+            Self::S3(storage) => storage.open_range(key, range).await.map(AnyReader::S3),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        match self {
+            Self::Local(storage) => storage.delete(key).await,
+            Self::S3(storage) => storage.delete(key).await,
+        }
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        match self {
+            Self::Local(storage) => storage.rename(from, to).await,
+            Self::S3(storage) => storage.rename(from, to).await,
+        }
+    }
+
+    async fn list(&self, after: Option<&str>, limit: usize) -> io::Result<Vec<ObjectInfo>> {
+        match self {
+            Self::Local(storage) => storage.list(after, limit).await,
+            Self::S3(storage) => storage.list(after, limit).await,
+        }
+    }
+
+    fn presign_download(
+        &self,
+        key: &str,
+        expires_in_secs: u64,
+        content_type: &str,
+        content_disposition: &str,
+    ) -> Option<String> {
+        match self {
+            Self::Local(storage) => {
+                storage.presign_download(key, expires_in_secs, content_type, content_disposition)
+            }
+            Self::S3(storage) => {
+                storage.presign_download(key, expires_in_secs, content_type, content_disposition)
+            }
+        }
+    }
+}
+
+impl AsyncWrite for AnyWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Local(writer) => Pin::new(writer).poll_write(cx, data),
+            Self::S3(writer) => Pin::new(writer).poll_write(cx, data),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Local(writer) => Pin::new(writer).poll_flush(cx),
+            Self::S3(writer) => Pin::new(writer).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Local(writer) => Pin::new(writer).poll_shutdown(cx),
+            Self::S3(writer) => Pin::new(writer).poll_shutdown(cx),
+        }
+    }
+}
+
+impl AsyncRead for AnyReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Local(reader) => Pin::new(reader).poll_read(cx, buf),
+            Self::S3(reader) => Pin::new(reader).poll_read(cx, buf),
+        }
+    }
+}
+
+/// 本地磁盘存储，对象键映射为根目录下的相对路径
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// 解析对象键对应的文件路径，拒绝绝对路径和 `..` 等可能逃出根目录的键
+    fn path_for(&self, key: &str) -> io::Result<PathBuf> {
+        let relative = Path::new(key);
+        let safe = !key.is_empty()
+            && relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)));
+        if !safe {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("非法的对象键：{key}"),
+            ));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+impl Storage for LocalStorage {
+    type Writer = BufWriter<tokio::fs::File>;
+    type Reader = Take<tokio::fs::File>;
+
+    async fn create(&self, key: &str) -> io::Result<Self::Writer> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let file = tokio::fs::File::create(path).await?;
+        Ok(BufWriter::with_capacity(LOCAL_WRITE_BUFFER_BYTES, file))
+    }
+
+    async fn open(&self, key: &str) -> io::Result<Self::Reader> {
+        let file = tokio::fs::File::open(self.path_for(key)?).await?;
+        Ok(file.take(u64::MAX))
+    }
+
+    async fn open_range(&self, key: &str, range: Range<u64>) -> io::Result<Self::Reader> {
+        let mut file = tokio::fs::File::open(self.path_for(key)?).await?;
+        file.seek(io::SeekFrom::Start(range.start)).await?;
+        Ok(file.take(range.end - range.start))
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let (from, to) = (self.path_for(from)?, self.path_for(to)?);
+        if let Some(parent) = to.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::rename(from, to).await
+    }
+
+    /// 按路径分段的字典序深度优先遍历根目录，内存占用只与单个目录的条目数有关
+    async fn list(&self, after: Option<&str>, limit: usize) -> io::Result<Vec<ObjectInfo>> {
+        let root = self.root.clone();
+        let after: Vec<String> = after
+            .map(|key| key.split('/').map(String::from).collect())
+            .unwrap_or_default();
+        tokio::task::spawn_blocking(move || {
+            let mut objects = Vec::new();
+            list_dir(&root, "", &after, limit, &mut objects)?;
+            Ok(objects)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+/// 按名称顺序遍历 `dir`，把排在 `after`（已按 `/` 分段）之后的文件追加到 `objects`，直到达到 `limit`
+///
+/// 目录不存在时视为空；名称不是 UTF-8 的条目和符号链接不会是本服务写入的对象，直接跳过。
+fn list_dir(
+    dir: &Path,
+    prefix: &str,
+    after: &[String],
+    limit: usize,
+    objects: &mut Vec<ObjectInfo>,
+) -> io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        result => result?,
+    };
+    let mut entries: Vec<_> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            Some((name, entry))
+        })
+        .collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    for (name, entry) in entries {
+        if objects.len() >= limit {
+            break;
+        }
+        let file_type = entry.file_type()?;
+        // 与游标同名的目录只遍历游标之后的部分，同名文件就是游标本身（或排在它之前）
+        let rest = match after.split_first() {
+            Some((first, rest)) => match name.as_str().cmp(first.as_str()) {
+                std::cmp::Ordering::Less => continue,
+                std::cmp::Ordering::Equal if file_type.is_dir() => rest,
+                std::cmp::Ordering::Equal => continue,
+                std::cmp::Ordering::Greater => &[],
+            },
+            None => &[],
+        };
+        let key = format!("{prefix}{name}");
+        if file_type.is_dir() {
+            list_dir(&entry.path(), &format!("{key}/"), rest, limit, objects)?;
+        } else if file_type.is_file() {
+            // 遍历期间被删除的文件直接跳过
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+            objects.push(ObjectInfo { key, modified });
+        }
+    }
+    Ok(())
+}
+
+/// 内存存储中的对象及其写入时间（仅用于测试）
+#[cfg(test)]
+type InMemoryObjects =
+    std::sync::Arc<std::sync::Mutex<std::collections::BTreeMap<String, (Vec<u8>, DateTime<Utc>)>>>;
+
+/// 内存存储实现（仅用于测试）
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct InMemoryStorage {
+    objects: InMemoryObjects,
+}
+
+#[cfg(test)]
+impl InMemoryStorage {
+    /// 直接写入一个对象，已存在时覆盖
+    pub fn insert(&self, key: &str, content: impl Into<Vec<u8>>) {
+        self.insert_at(key, content, Utc::now());
+    }
+
+    /// 直接写入一个对象并指定修改时间，用于模拟早先写入的对象
+    pub fn insert_at(&self, key: &str, content: impl Into<Vec<u8>>, modified: DateTime<Utc>) {
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), (content.into(), modified));
+    }
+
+    /// 读取已提交的对象
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.objects
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|(content, _)| content.clone())
+    }
+
+    /// 已提交的对象数量
+    pub fn object_count(&self) -> usize {
+        self.objects.lock().unwrap().len()
+    }
+}
+
+/// [`InMemoryStorage`] 的写入器，`shutdown` 时提交内容
+#[cfg(test)]
+#[derive(Debug)]
+pub struct InMemoryWriter {
+    key: String,
+    buf: Vec<u8>,
+    objects: InMemoryObjects,
+}
+
+#[cfg(test)]
+impl AsyncWrite for InMemoryWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.buf.extend_from_slice(data);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let data = std::mem::take(&mut self.buf);
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(self.key.clone(), (data, Utc::now()));
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+impl Storage for InMemoryStorage {
+    type Writer = InMemoryWriter;
+    type Reader = io::Cursor<Vec<u8>>;
+
+    async fn create(&self, key: &str) -> io::Result<Self::Writer> {
+        Ok(InMemoryWriter {
+            key: key.to_string(),
+            buf: Vec::new(),
+            objects: self.objects.clone(),
+        })
+    }
+
+    async fn open(&self, key: &str) -> io::Result<Self::Reader> {
+        self.get(key)
+            .map(io::Cursor::new)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("对象不存在：{key}")))
+    }
+
+    async fn open_range(&self, key: &str, range: Range<u64>) -> io::Result<Self::Reader> {
+        let content = self.open(key).await?.into_inner();
+        let end = (range.end as usize).min(content.len());
+        Ok(io::Cursor::new(content[range.start as usize..end].to_vec()))
+    }
+
+    async fn delete(&self, key: &str) -> io::Result<()> {
+        self.objects.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let mut objects = self.objects.lock().unwrap();
+        let (content, _) = objects.remove(from).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("对象不存在：{from}"))
+        })?;
+        objects.insert(to.to_string(), (content, Utc::now()));
+        Ok(())
+    }
+
+    async fn list(&self, after: Option<&str>, limit: usize) -> io::Result<Vec<ObjectInfo>> {
+        use std::ops::Bound;
+
+        let start = after.map_or(Bound::Unbounded, |key| Bound::Excluded(key.to_string()));
+        Ok(self
+            .objects
+            .lock()
+            .unwrap()
+            .range((start, Bound::Unbounded))
+            .take(limit)
+            .map(|(key, (_, modified))| ObjectInfo {
+                key: key.clone(),
+                modified: Some(*modified),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 以多次小块写入的方式提交对象
+    async fn put<S: Storage>(storage: &S, key: &str, content: &[u8]) {
+        let mut writer = storage.create(key).await.unwrap();
+        for piece in content.chunks(7 * 1024 + 3) {
+            writer.write_all(piece).await.unwrap();
+        }
+        writer.shutdown().await.unwrap();
+    }
+
+    async fn read<S: Storage>(storage: &S, key: &str) -> io::Result<Vec<u8>> {
+        let mut content = Vec::new();
+        storage.open(key).await?.read_to_end(&mut content).await?;
+        Ok(content)
+    }
+
+    /// 所有存储后端都必须满足的行为，每个实现的测试都应运行
+    pub(super) async fn assert_storage_contract<S: Storage>(storage: &S) {
+        // 跨越多个 S3 分片的对象
+        let large: Vec<u8> = (0..200 * 1024).map(|i| (i % 251) as u8).collect();
+        put(storage, "1/large.bin", &large).await;
+        assert_eq!(read(storage, "1/large.bin").await.unwrap(), large);
+
+        // 覆盖已有对象
+        put(storage, "1/large.bin", b"replaced").await;
+        assert_eq!(read(storage, "1/large.bin").await.unwrap(), b"replaced");
+
+        put(storage, "1/empty", b"").await;
+        assert_eq!(read(storage, "1/empty").await.unwrap(), b"");
+
+        let err = read(storage, "1/missing").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        // 按区间读取，包括最后一个字节
+        put(storage, "1/range.bin", &large).await;
+        for range in [0..1, 1000..70_000, 200 * 1024 - 5..200 * 1024] {
+            let mut content = Vec::new();
+            storage
+                .open_range("1/range.bin", range.clone())
+                .await
+                .unwrap()
+                .read_to_end(&mut content)
+                .await
+                .unwrap();
+            assert_eq!(content, large[range.start as usize..range.end as usize]);
+        }
+        let Err(err) = storage.open_range("1/missing", 0..1).await else {
+            panic!("不存在的对象不应可读");
+        };
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        storage.delete("1/range.bin").await.unwrap();
+
+        // 删除后不可读，重复删除视为成功
+        storage.delete("1/large.bin").await.unwrap();
+        storage.delete("1/large.bin").await.unwrap();
+        let err = read(storage, "1/large.bin").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        storage.delete("1/empty").await.unwrap();
+
+        // 移动对象，覆盖已有的目标
+        put(storage, "1/moving", b"moved").await;
+        put(storage, "2/target", b"old").await;
+        storage.rename("1/moving", "2/target").await.unwrap();
+        assert_eq!(read(storage, "2/target").await.unwrap(), b"moved");
+        let err = read(storage, "1/moving").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        let err = storage.rename("1/moving", "2/other").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        storage.delete("2/target").await.unwrap();
+
+        // 分批列出全部对象，不遗漏也不重复
+        assert!(storage.list(None, 10).await.unwrap().is_empty());
+        let keys = ["1/a", "1/b&c", "10/a", "2/x.txt", "2/x/y"];
+        for key in keys {
+            put(storage, key, key.as_bytes()).await;
+        }
+        let mut listed = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let page = storage.list(after.as_deref(), 2).await.unwrap();
+            assert!(page.len() <= 2);
+            let Some(last) = page.last() else {
+                break;
+            };
+            after = Some(last.key.clone());
+            for object in page {
+                let modified = object.modified.expect("应提供修改时间");
+                assert!(chrono::Utc::now() - modified < chrono::Duration::minutes(1));
+                listed.push(object.key);
+            }
+        }
+        let mut sorted = listed.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, keys, "分批列出的键：{listed:?}");
+        for key in keys {
+            storage.delete(key).await.unwrap();
+        }
+        assert!(storage.list(None, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_contract() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path());
+        assert_storage_contract(&storage).await;
+
+        put(&storage, "1/a.txt", b"hello").await;
+        assert_eq!(std::fs::read(dir.path().join("1/a.txt")).unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_rejects_escaping_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path());
+        for key in ["../escape", "/etc/passwd", ""] {
+            let err = storage.create(key).await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_storage_contract() {
+        assert_storage_contract(&InMemoryStorage::default()).await;
+    }
+
+    #[tokio::test]
+    async fn test_any_storage_contract() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_storage_contract(&AnyStorage::Local(LocalStorage::new(dir.path()))).await;
+    }
+
+    #[test]
+    fn test_storages_routes_by_backend() {
+        let storages = Storages::single(StorageBackend::Local, "local")
+            .with(StorageBackend::S3, "s3")
+            .with(StorageBackend::S3, "s3-replaced");
+        assert_eq!(storages.default_backend(), StorageBackend::Local);
+        assert_eq!(*storages.primary(), "local");
+        assert_eq!(*storages.get(StorageBackend::S3).unwrap(), "s3-replaced");
+
+        let backends: Vec<_> = storages.iter().map(|(backend, _)| backend).collect();
+        assert_eq!(backends, [StorageBackend::Local, StorageBackend::S3]);
+
+        let storages = Storages::single(StorageBackend::S3, "s3");
+        let err = storages.get(StorageBackend::Local).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+}
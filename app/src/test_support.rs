@@ -0,0 +1,88 @@
+//! 测试辅助工具
+//!
+//! 仅在测试构建中编译，供各模块的单元测试复用。
+
+use axum::http::StatusCode;
+use axum::response::Response;
+use entity::user::UserId;
+use serde_json::Value;
+
+use crate::AppState;
+use crate::core::LogLevelHandle;
+use crate::core::config::AppConfig;
+use crate::core::response::{ApiError, ApiResponse};
+
+/// 断言响应是错误格式的 [`ApiResponse`]，并一次性校验 HTTP 状态码、错误原因和错误消息
+///
+/// 除调用参数外，还会检查 `error.code` 与 HTTP 状态码一致、响应中不含 `data`。
+/// 返回解析出的错误对象，便于继续断言 `errors` 中的位置等字段。
+pub async fn assert_api_error(
+    response: Response,
+    status: StatusCode,
+    reason: &str,
+    message: &str,
+) -> ApiError {
+    assert_eq!(response.status(), status, "HTTP 状态码不符");
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("读取响应体失败");
+    let body: ApiResponse<Value> = serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+        panic!(
+            "响应体不是 ApiResponse（{e}）：{}",
+            String::from_utf8_lossy(&bytes)
+        )
+    });
+
+    assert!(body.data.is_none(), "错误响应不应包含 data");
+    let error = body.error.expect("响应缺少 error 对象");
+    assert_eq!(
+        error.code,
+        status.as_u16(),
+        "error.code 与 HTTP 状态码不一致"
+    );
+    assert_eq!(error.message, message, "error.message 不符");
+    let reasons: Vec<_> = error.errors.iter().map(|e| e.reason.as_str()).collect();
+    assert_eq!(reasons.first(), Some(&reason), "错误原因不符：{reasons:?}");
+    error
+}
+
+/// 使用 `config` 构建的应用状态，数据库为未连接状态、不使用 Redis
+///
+/// 适用于不访问数据库的路由、中间件和文档测试。
+pub fn test_state(config: &AppConfig) -> AppState {
+    let (_layer, log_level) = LogLevelHandle::new(tracing_subscriber::EnvFilter::new("info"));
+    AppState::with_connections(
+        config,
+        log_level,
+        sea_orm::DatabaseConnection::Disconnected,
+        None,
+    )
+    .unwrap()
+}
+
+/// 测试用的第 `n` 个用户 ID
+///
+/// 自增主键时就是 `n`，UUID 主键时为由 `n` 构造的固定 UUID，测试在两种主键类型下都能使用确定的 ID。
+pub const fn user_id(n: u16) -> UserId {
+    #[cfg(feature = "uuid-user-id")]
+    {
+        uuid::Uuid::from_u128(n as u128)
+    }
+    #[cfg(not(feature = "uuid-user-id"))]
+    {
+        n as UserId
+    }
+}
+
+mod tests {
+    use super::*;
+    use crate::AppError;
+    use axum::response::IntoResponse;
+
+    #[tokio::test]
+    #[should_panic(expected = "error.message 不符")]
+    async fn test_assert_api_error_reports_message_mismatch() {
+        let response = AppError::NotFound("用户不存在".to_string()).into_response();
+        assert_api_error(response, StatusCode::NOT_FOUND, "NOT_FOUND", "文件不存在").await;
+    }
+}
@@ -1,3 +1,11 @@
+pub mod scan_status;
+pub mod storage_backend;
+pub mod thumbnail_status;
+pub mod user_role;
 pub mod user_status;
 
+pub use scan_status::ScanStatus;
+pub use storage_backend::StorageBackend;
+pub use thumbnail_status::ThumbnailStatus;
+pub use user_role::UserRole;
 pub use user_status::UserStatus;
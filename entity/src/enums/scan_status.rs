@@ -0,0 +1,40 @@
+use schemars::JsonSchema;
+use sea_orm::{DeriveActiveEnum, EnumIter, sea_query::StringLen};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// 文件的恶意软件扫描状态
+///
+/// 以字符串存储在 `file.scan_status` 列中。新上传的文件为 `pending_scan`，扫描后变为 `clean` 或 `infected`，
+/// 只有 `clean` 的文件可以下载
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    DeriveActiveEnum,
+    EnumIter,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ScanStatus {
+    /// 等待扫描
+    #[sea_orm(string_value = "pending_scan")]
+    PendingScan,
+
+    /// 未发现威胁
+    #[sea_orm(string_value = "clean")]
+    Clean,
+
+    /// 发现恶意内容，内容已隔离
+    #[sea_orm(string_value = "infected")]
+    Infected,
+}
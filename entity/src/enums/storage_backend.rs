@@ -0,0 +1,37 @@
+use schemars::JsonSchema;
+use sea_orm::{DeriveActiveEnum, EnumIter, sea_query::StringLen};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// 文件内容所在的存储后端
+///
+/// 以字符串存储在 `file.storage_backend` 列中，迁移期间新旧后端可以并存，按记录读取
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    DeriveActiveEnum,
+    EnumIter,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum StorageBackend {
+    /// 本地磁盘
+    #[default]
+    #[sea_orm(string_value = "local")]
+    Local,
+
+    /// S3 兼容的对象存储
+    #[sea_orm(string_value = "s3")]
+    S3,
+}
@@ -0,0 +1,36 @@
+use schemars::JsonSchema;
+use sea_orm::{DeriveActiveEnum, EnumIter, sea_query::StringLen};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// 图片文件的缩略图状态
+///
+/// 以字符串存储在 `file.thumbnail_status` 列中，不生成缩略图的文件为 `NULL`。新上传的图片为 `pending`，
+/// 后台生成全部尺寸后变为 `ready`；生成失败或缩略图对象丢失时保持（或回到）`pending`，请求缩略图时重新生成
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    DeriveActiveEnum,
+    EnumIter,
+    Display,
+    EnumString,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ThumbnailStatus {
+    /// 等待生成
+    #[sea_orm(string_value = "pending")]
+    Pending,
+
+    /// 已生成，尺寸记录在 `file.thumbnail_sizes` 中
+    #[sea_orm(string_value = "ready")]
+    Ready,
+}
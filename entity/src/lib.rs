@@ -1,5 +1,11 @@
 pub mod enums;
 
+pub mod admin_audit;
+pub mod blob;
+pub mod file;
+pub mod job_lock;
+pub mod upload_chunk;
+pub mod upload_session;
 pub mod user;
 
 pub mod prelude {
@@ -2,4 +2,8 @@
 
 pub mod prelude;
 
+pub mod admin_audit;
+pub mod file;
+pub mod upload_chunk;
+pub mod upload_session;
 pub mod user;
@@ -1,3 +1,8 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.17
 
+pub use super::admin_audit::Entity as AdminAudit;
+pub use super::blob::Entity as Blob;
+pub use super::file::Entity as File;
+pub use super::upload_chunk::Entity as UploadChunk;
+pub use super::upload_session::Entity as UploadSession;
 pub use super::user::Entity as User;
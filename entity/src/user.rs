@@ -2,22 +2,65 @@
 
 use sea_orm::entity::prelude::*;
 
+/// 用户 ID 类型
+///
+/// 默认为数据库自增的 `i64`；启用 `uuid-user-id` feature 时为应用生成的 UUID v4。
+/// 引用用户的外键列（`file.owner_id` 等）使用同一类型。
+#[cfg(not(feature = "uuid-user-id"))]
+pub type UserId = i64;
+
+/// 用户 ID 类型（UUID）
+#[cfg(feature = "uuid-user-id")]
+pub type UserId = Uuid;
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "user")]
 pub struct Model {
     #[sea_orm(primary_key)]
-    pub id: i32,
+    #[cfg_attr(feature = "uuid-user-id", sea_orm(auto_increment = false))]
+    pub id: UserId,
     #[sea_orm(unique)]
     pub username: String,
     #[sea_orm(unique)]
     pub email: String,
     pub password_hash: String,
     pub status: i16,
+    pub role: i16,
+    pub last_login_at: Option<DateTimeWithTimeZone>,
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<DateTimeWithTimeZone>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub storage_quota_bytes: Option<i64>,
+    pub storage_used_bytes: i64,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub preferences: Json,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_many = "super::file::Entity")]
+    File,
+}
 
-impl ActiveModelBehavior for ActiveModel {}
+impl Related<super::file::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::File.def()
+    }
+}
+
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    /// 插入时未指定 ID 的，UUID 主键由应用生成；自增主键由数据库分配
+    #[cfg_attr(not(feature = "uuid-user-id"), allow(unused_mut, unused_variables))]
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        #[cfg(feature = "uuid-user-id")]
+        if insert && self.id.is_not_set() {
+            self.id = sea_orm::Set(uuid::Uuid::new_v4());
+        }
+        Ok(self)
+    }
+}
@@ -1,12 +1,47 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_user_table;
+mod m20220101_000002_add_user_login_tracking;
+mod m20220101_000003_add_user_role;
+mod m20220101_000004_create_file_table;
+mod m20220101_000005_create_admin_audit_table;
+mod m20220101_000006_add_admin_audit_route_index;
+mod m20220101_000007_add_file_checksum;
+mod m20220101_000008_create_upload_session_table;
+mod m20220101_000009_add_file_storage_backend;
+mod m20220101_000010_add_file_scan_status;
+mod m20220101_000011_add_user_storage_quota;
+mod m20220101_000012_create_blob_table;
+mod m20220101_000013_add_blob_corrupt_at;
+mod m20220101_000014_create_job_lock_table;
+mod m20220101_000015_widen_user_id;
+mod m20220101_000016_add_user_preferences;
+mod m20220101_000017_add_file_thumbnails;
+mod user_id;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_create_user_table::Migration)]
+        vec![
+            Box::new(m20220101_000001_create_user_table::Migration),
+            Box::new(m20220101_000002_add_user_login_tracking::Migration),
+            Box::new(m20220101_000003_add_user_role::Migration),
+            Box::new(m20220101_000004_create_file_table::Migration),
+            Box::new(m20220101_000005_create_admin_audit_table::Migration),
+            Box::new(m20220101_000006_add_admin_audit_route_index::Migration),
+            Box::new(m20220101_000007_add_file_checksum::Migration),
+            Box::new(m20220101_000008_create_upload_session_table::Migration),
+            Box::new(m20220101_000009_add_file_storage_backend::Migration),
+            Box::new(m20220101_000010_add_file_scan_status::Migration),
+            Box::new(m20220101_000011_add_user_storage_quota::Migration),
+            Box::new(m20220101_000012_create_blob_table::Migration),
+            Box::new(m20220101_000013_add_blob_corrupt_at::Migration),
+            Box::new(m20220101_000014_create_job_lock_table::Migration),
+            Box::new(m20220101_000015_widen_user_id::Migration),
+            Box::new(m20220101_000016_add_user_preferences::Migration),
+            Box::new(m20220101_000017_add_file_thumbnails::Migration),
+        ]
     }
 }
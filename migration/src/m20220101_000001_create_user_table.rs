@@ -1,5 +1,7 @@
 use sea_orm_migration::{prelude::*, schema::*};
 
+use crate::user_id::user_id_pk;
+
 #[derive(DeriveMigrationName)]
 pub struct Migration;
 
@@ -11,7 +13,7 @@ impl MigrationTrait for Migration {
                 Table::create()
                     .table(User::Table)
                     .if_not_exists()
-                    .col(pk_auto(User::Id))
+                    .col(user_id_pk(User::Id))
                     .col(string_uniq(User::Username))
                     .col(string_uniq(User::Email))
                     .col(string(User::PasswordHash))
@@ -41,7 +43,7 @@ enum User {
     /// 表名
     Table,
 
-    /// 用户 ID，主键，自增
+    /// 用户 ID，主键（自增整数或 UUID，见 [`crate::user_id`]）
     Id,
 
     /// 用户名，唯一
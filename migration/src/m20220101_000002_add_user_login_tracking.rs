@@ -0,0 +1,68 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite 不支持在一条 ALTER TABLE 中修改多列，这里逐列添加
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column_if_not_exists(timestamp_with_time_zone_null(User::LastLoginAt))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column_if_not_exists(integer(User::FailedLoginAttempts).default(0))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column_if_not_exists(timestamp_with_time_zone_null(User::LockedUntil))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            User::LockedUntil,
+            User::FailedLoginAttempts,
+            User::LastLoginAt,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(User::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    /// 表名
+    Table,
+
+    /// 最近一次登录成功的时间
+    LastLoginAt,
+
+    /// 连续登录失败次数，登录成功后清零
+    FailedLoginAttempts,
+
+    /// 账户锁定截止时间，为空表示未锁定
+    LockedUntil,
+}
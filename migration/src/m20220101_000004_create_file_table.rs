@@ -0,0 +1,88 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::user_id::user_id_ref;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(File::Table)
+                    .if_not_exists()
+                    .col(big_integer(File::Id).auto_increment().primary_key())
+                    .col(user_id_ref(File::OwnerId))
+                    .col(string(File::Filename))
+                    .col(string(File::ContentType))
+                    .col(big_integer(File::SizeBytes))
+                    .col(string(File::StorageKey))
+                    .col(
+                        timestamp_with_time_zone(File::CreatedAt)
+                            .extra("DEFAULT CURRENT_TIMESTAMP"),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_file_owner_id")
+                            .from(File::Table, File::OwnerId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_file_owner_id_created_at")
+                    .table(File::Table)
+                    .col(File::OwnerId)
+                    .col(File::CreatedAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(File::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum File {
+    /// 表名
+    Table,
+
+    /// 文件 ID，主键，自增
+    Id,
+
+    /// 所有者用户 ID，外键关联 user.id
+    OwnerId,
+
+    /// 原始文件名
+    Filename,
+
+    /// MIME 类型
+    ContentType,
+
+    /// 文件大小（字节）
+    SizeBytes,
+
+    /// 存储后端中的对象键
+    StorageKey,
+
+    /// 创建时间，自动设置当前时间戳
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
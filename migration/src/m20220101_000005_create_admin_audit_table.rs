@@ -0,0 +1,89 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::user_id::user_id_ref;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AdminAudit::Table)
+                    .if_not_exists()
+                    .col(big_integer(AdminAudit::Id).auto_increment().primary_key())
+                    .col(user_id_ref(AdminAudit::ActorId))
+                    .col(string_len(AdminAudit::Method, 16))
+                    .col(string(AdminAudit::Route))
+                    .col(text_null(AdminAudit::RequestBody))
+                    .col(small_integer(AdminAudit::Status))
+                    .col(
+                        timestamp_with_time_zone(AdminAudit::CreatedAt)
+                            .extra("DEFAULT CURRENT_TIMESTAMP"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_admin_audit_actor_id_created_at")
+                    .table(AdminAudit::Table)
+                    .col(AdminAudit::ActorId)
+                    .col(AdminAudit::CreatedAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_admin_audit_created_at")
+                    .table(AdminAudit::Table)
+                    .col(AdminAudit::CreatedAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AdminAudit::Table).to_owned())
+            .await
+    }
+}
+
+/// 管理操作审计表
+///
+/// 不对 `actor_id` 建外键：删除用户后其历史操作记录仍需保留。
+#[derive(DeriveIden)]
+enum AdminAudit {
+    /// 表名
+    Table,
+
+    /// 记录 ID，主键，自增
+    Id,
+
+    /// 执行操作的管理员用户 ID
+    ActorId,
+
+    /// HTTP 方法
+    Method,
+
+    /// 匹配的路由模板，如 `/v1/admin/logging/level`
+    Route,
+
+    /// 脱敏后的请求体（JSON），非 JSON 或为空时为 NULL
+    RequestBody,
+
+    /// 响应状态码
+    Status,
+
+    /// 记录时间，自动设置当前时间戳
+    CreatedAt,
+}
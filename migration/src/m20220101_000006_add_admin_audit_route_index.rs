@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// 为按路由前缀查询审计记录添加索引
+///
+/// 按操作者查询使用的 `(actor_id, created_at)` 索引已在建表迁移中创建。
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_admin_audit_route_created_at")
+                    .table(AdminAudit::Table)
+                    .col(AdminAudit::Route)
+                    .col(AdminAudit::CreatedAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_admin_audit_route_created_at")
+                    .table(AdminAudit::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AdminAudit {
+    Table,
+    Route,
+    CreatedAt,
+}
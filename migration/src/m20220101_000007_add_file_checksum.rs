@@ -0,0 +1,53 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite 不支持在一条 ALTER TABLE 中修改多列，这里逐列添加
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(File::Table)
+                    .add_column_if_not_exists(string(File::Checksum).default(""))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(File::Table)
+                    .add_column_if_not_exists(string_null(File::Description))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [File::Description, File::Checksum] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(File::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum File {
+    /// 表名
+    Table,
+
+    /// 文件内容的 SHA-256 摘要（十六进制小写），迁移前上传的文件为空字符串
+    Checksum,
+
+    /// 上传时填写的文件描述
+    Description,
+}
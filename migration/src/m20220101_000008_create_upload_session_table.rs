@@ -0,0 +1,149 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use crate::user_id::user_id_ref;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UploadSession::Table)
+                    .if_not_exists()
+                    .col(string(UploadSession::Id).primary_key())
+                    .col(user_id_ref(UploadSession::OwnerId))
+                    .col(string(UploadSession::Filename))
+                    .col(string(UploadSession::ContentType))
+                    .col(string_null(UploadSession::Description))
+                    .col(big_integer(UploadSession::SizeBytes))
+                    .col(big_integer(UploadSession::ChunkSize))
+                    .col(string(UploadSession::Checksum))
+                    .col(timestamp_with_time_zone(UploadSession::ExpiresAt))
+                    .col(
+                        timestamp_with_time_zone(UploadSession::CreatedAt)
+                            .extra("DEFAULT CURRENT_TIMESTAMP"),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_upload_session_owner_id")
+                            .from(UploadSession::Table, UploadSession::OwnerId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 清理任务按过期时间扫描
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_upload_session_expires_at")
+                    .table(UploadSession::Table)
+                    .col(UploadSession::ExpiresAt)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UploadChunk::Table)
+                    .if_not_exists()
+                    .col(string(UploadChunk::SessionId))
+                    .col(integer(UploadChunk::ChunkIndex))
+                    .col(big_integer(UploadChunk::SizeBytes))
+                    .col(
+                        timestamp_with_time_zone(UploadChunk::UploadedAt)
+                            .extra("DEFAULT CURRENT_TIMESTAMP"),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(UploadChunk::SessionId)
+                            .col(UploadChunk::ChunkIndex),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_upload_chunk_session_id")
+                            .from(UploadChunk::Table, UploadChunk::SessionId)
+                            .to(UploadSession::Table, UploadSession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UploadChunk::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(UploadSession::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UploadSession {
+    /// 表名
+    Table,
+
+    /// 会话 ID（UUID），主键
+    Id,
+
+    /// 所有者用户 ID，外键关联 user.id
+    OwnerId,
+
+    /// 原始文件名
+    Filename,
+
+    /// 声明的 MIME 类型
+    ContentType,
+
+    /// 文件描述
+    Description,
+
+    /// 声明的文件大小（字节）
+    SizeBytes,
+
+    /// 分片大小（字节），最后一个分片可以更小
+    ChunkSize,
+
+    /// 声明的文件内容 SHA-256 摘要（十六进制小写）
+    Checksum,
+
+    /// 过期时间，过期后由清理任务删除
+    ExpiresAt,
+
+    /// 创建时间，自动设置当前时间戳
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum UploadChunk {
+    /// 表名
+    Table,
+
+    /// 所属会话 ID，外键关联 upload_session.id
+    SessionId,
+
+    /// 分片序号，从 0 开始
+    ChunkIndex,
+
+    /// 已接收的分片大小（字节）
+    SizeBytes,
+
+    /// 最近一次上传时间
+    UploadedAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
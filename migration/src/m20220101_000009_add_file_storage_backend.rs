@@ -0,0 +1,38 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(File::Table)
+                    .add_column_if_not_exists(string(File::StorageBackend).default("local"))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(File::Table)
+                    .drop_column(File::StorageBackend)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum File {
+    /// 表名
+    Table,
+
+    /// 文件内容所在的存储后端（local / s3），迁移前上传的文件均在本地磁盘
+    StorageBackend,
+}
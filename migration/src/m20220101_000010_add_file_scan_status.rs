@@ -0,0 +1,60 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(File::Table)
+                    .add_column_if_not_exists(string(File::ScanStatus).default("clean"))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 定期重新投递待扫描文件时按状态查询
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_file_scan_status")
+                    .table(File::Table)
+                    .col(File::ScanStatus)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .if_exists()
+                    .name("idx_file_scan_status")
+                    .table(File::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(File::Table)
+                    .drop_column(File::ScanStatus)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum File {
+    /// 表名
+    Table,
+
+    /// 恶意软件扫描状态（pending_scan / clean / infected），启用扫描前上传的文件视为 clean
+    ScanStatus,
+}
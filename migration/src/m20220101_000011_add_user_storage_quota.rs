@@ -0,0 +1,89 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite 不支持在一条 ALTER TABLE 中修改多列，这里逐列添加
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column_if_not_exists(big_integer_null(User::StorageQuotaBytes))
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column_if_not_exists(big_integer(User::StorageUsedBytes).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 按已有文件回填已用空间
+        let used = Query::select()
+            .expr(Func::coalesce([
+                Func::sum(Expr::col((File::Table, File::SizeBytes))).into(),
+                Expr::val(0).into(),
+            ]))
+            .from(File::Table)
+            .and_where(Expr::col((File::Table, File::OwnerId)).equals((User::Table, User::Id)))
+            .to_owned();
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(User::Table)
+                    .value(
+                        User::StorageUsedBytes,
+                        SimpleExpr::SubQuery(None, Box::new(used.into_sub_query_statement())),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [User::StorageUsedBytes, User::StorageQuotaBytes] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(User::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    /// 表名
+    Table,
+
+    /// 主键
+    Id,
+
+    /// 存储配额（字节），为空时使用 `upload.default_storage_quota_bytes`
+    StorageQuotaBytes,
+
+    /// 已用存储空间（字节），与该用户文件的 `size_bytes` 之和保持一致，随文件元数据在同一事务中更新
+    StorageUsedBytes,
+}
+
+#[derive(DeriveIden)]
+enum File {
+    /// 表名
+    Table,
+
+    /// 所有者用户 ID
+    OwnerId,
+
+    /// 文件大小（字节）
+    SizeBytes,
+}
@@ -0,0 +1,146 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Blob::Table)
+                    .if_not_exists()
+                    .col(string(Blob::Hash).primary_key())
+                    .col(big_integer(Blob::SizeBytes))
+                    .col(big_integer(Blob::RefCount))
+                    .col(string(Blob::StorageKey))
+                    .col(string(Blob::StorageBackend))
+                    .col(
+                        timestamp_with_time_zone(Blob::CreatedAt)
+                            .extra("DEFAULT CURRENT_TIMESTAMP"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 按内容统计引用、同步同一内容的文件记录
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_file_checksum")
+                    .table(File::Table)
+                    .col(File::Checksum)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 已有文件按校验和归并：每组取 ID 最小的文件的内容作为共享内容。文件记录保留各自的对象键，
+        // 重复的副本仍被引用，由后台对账任务把内容移动到 `blobs/{hash}` 后统一改写文件记录并删除旧副本
+        let canonical_id = Query::select()
+            .expr(Expr::col((Alias::new("m"), File::Id)).min())
+            .from_as(File::Table, Alias::new("m"))
+            .and_where(
+                Expr::col((Alias::new("m"), File::Checksum)).equals((File::Table, File::Checksum)),
+            )
+            .to_owned();
+        let ref_count = Query::select()
+            .expr(Expr::col((Alias::new("c"), File::Id)).count())
+            .from_as(File::Table, Alias::new("c"))
+            .and_where(
+                Expr::col((Alias::new("c"), File::Checksum)).equals((File::Table, File::Checksum)),
+            )
+            .to_owned();
+        let canonical = Query::select()
+            .column((File::Table, File::Checksum))
+            .column((File::Table, File::SizeBytes))
+            .expr(SimpleExpr::SubQuery(
+                None,
+                Box::new(ref_count.into_sub_query_statement()),
+            ))
+            .column((File::Table, File::StorageKey))
+            .column((File::Table, File::StorageBackend))
+            .from(File::Table)
+            .and_where(Expr::col((File::Table, File::Checksum)).ne(""))
+            .and_where(Expr::col((File::Table, File::Id)).in_subquery(canonical_id))
+            .to_owned();
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Blob::Table)
+                    .columns([
+                        Blob::Hash,
+                        Blob::SizeBytes,
+                        Blob::RefCount,
+                        Blob::StorageKey,
+                        Blob::StorageBackend,
+                    ])
+                    .select_from(canonical)
+                    .map_err(|e| DbErr::Migration(e.to_string()))?
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .if_exists()
+                    .name("idx_file_checksum")
+                    .table(File::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Blob::Table).if_exists().to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Blob {
+    /// 表名
+    Table,
+
+    /// 内容的 SHA-256 摘要（十六进制小写），主键
+    Hash,
+
+    /// 内容大小（字节）
+    SizeBytes,
+
+    /// 引用该内容的文件记录数，降为 0 时删除记录和存储后端中的内容
+    RefCount,
+
+    /// 存储后端中的对象键（首次上传时写入的位置）
+    StorageKey,
+
+    /// 内容所在的存储后端
+    StorageBackend,
+
+    /// 创建时间
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum File {
+    /// 表名
+    Table,
+
+    /// 主键
+    Id,
+
+    /// 文件内容的 SHA-256 摘要，引用 `blob.hash`；早期上传的文件可能为空，不参与去重
+    Checksum,
+
+    /// 文件大小（字节）
+    SizeBytes,
+
+    /// 对象键
+    StorageKey,
+
+    /// 存储后端
+    StorageBackend,
+}
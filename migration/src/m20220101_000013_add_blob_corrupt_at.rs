@@ -0,0 +1,38 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Blob::Table)
+                    .add_column_if_not_exists(timestamp_with_time_zone_null(Blob::CorruptAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Blob::Table)
+                    .drop_column(Blob::CorruptAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Blob {
+    /// 表名
+    Table,
+
+    /// 完整性检查发现对象在存储后端中缺失的时间，对象恢复后清空
+    CorruptAt,
+}
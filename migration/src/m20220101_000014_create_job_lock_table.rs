@@ -0,0 +1,42 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JobLock::Table)
+                    .if_not_exists()
+                    .col(string(JobLock::Name).primary_key())
+                    .col(string(JobLock::Holder))
+                    .col(timestamp_with_time_zone(JobLock::ExpiresAt))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JobLock::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum JobLock {
+    /// 表名
+    Table,
+
+    /// 锁名称（任务名），主键
+    Name,
+
+    /// 持有者标识，每次获取时随机生成，释放时只删除自己持有的锁
+    Holder,
+
+    /// 租约到期时间，持有者崩溃后锁在此之后可被其他实例获取
+    ExpiresAt,
+}
@@ -0,0 +1,96 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::DbBackend};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// PostgreSQL 上把整数用户 ID 及引用它的列扩展为 bigint
+///
+/// SQLite 的 INTEGER 本身是 64 位，不需要修改；UUID 主键的数据库也不需要修改。
+fn applies(manager: &SchemaManager) -> bool {
+    !cfg!(feature = "uuid-user-id") && manager.get_database_backend() == DbBackend::Postgres
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !applies(manager) {
+            return Ok(());
+        }
+        for (table, col) in columns() {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .modify_column(big_integer(col))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        // serial 列的序列类型为 integer，达到上限后同样无法分配新 ID
+        manager
+            .get_connection()
+            .execute_unprepared(r#"ALTER SEQUENCE IF EXISTS "user_id_seq" AS bigint"#)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if !applies(manager) {
+            return Ok(());
+        }
+        manager
+            .get_connection()
+            .execute_unprepared(r#"ALTER SEQUENCE IF EXISTS "user_id_seq" AS integer"#)
+            .await?;
+        for (table, col) in columns() {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .modify_column(integer(col))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+fn columns() -> [(DynIden, DynIden); 4] {
+    [
+        (User::Table.into_iden(), User::Id.into_iden()),
+        (File::Table.into_iden(), File::OwnerId.into_iden()),
+        (
+            UploadSession::Table.into_iden(),
+            UploadSession::OwnerId.into_iden(),
+        ),
+        (
+            AdminAudit::Table.into_iden(),
+            AdminAudit::ActorId.into_iden(),
+        ),
+    ]
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum File {
+    Table,
+    OwnerId,
+}
+
+#[derive(DeriveIden)]
+enum UploadSession {
+    Table,
+    OwnerId,
+}
+
+#[derive(DeriveIden)]
+enum AdminAudit {
+    Table,
+    ActorId,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(File::Table)
+                    .add_column_if_not_exists(string_null(File::ThumbnailStatus))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(File::Table)
+                    .add_column_if_not_exists(json_binary(File::ThumbnailSizes).default("[]"))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(File::Table)
+                    .drop_column(File::ThumbnailSizes)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(File::Table)
+                    .drop_column(File::ThumbnailStatus)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum File {
+    /// 表名
+    Table,
+
+    /// 缩略图状态（pending / ready），不生成缩略图的文件（非图片、启用缩略图之前上传的文件）为 NULL
+    ThumbnailStatus,
+
+    /// 已生成的缩略图边长（像素）数组（PostgreSQL 中为 JSONB），对象键为 `thumbnails/{checksum}/{size}.png`
+    ThumbnailSizes,
+}
@@ -0,0 +1,25 @@
+//! 用户 ID 列定义
+//!
+//! 用户主键默认为整数自增（PostgreSQL 上由 `m20220101_000015_widen_user_id` 扩展为 bigint）；
+//! 启用 `uuid-user-id` feature 时，建表迁移改为创建 UUID 主键，引用用户的外键列随之改变。
+//! feature 只影响新建的数据库：已有数据的数据库不能通过切换 feature 转换主键类型。
+
+use sea_orm_migration::{prelude::*, schema::*};
+
+/// 用户表主键列
+pub fn user_id_pk<T: IntoIden>(col: T) -> ColumnDef {
+    if cfg!(feature = "uuid-user-id") {
+        uuid(col).primary_key().take()
+    } else {
+        pk_auto(col)
+    }
+}
+
+/// 引用用户 ID 的列
+pub fn user_id_ref<T: IntoIden>(col: T) -> ColumnDef {
+    if cfg!(feature = "uuid-user-id") {
+        uuid(col)
+    } else {
+        integer(col)
+    }
+}
@@ -0,0 +1,35 @@
+//! 迁移可重复执行测试
+//!
+//! 在全新的 SQLite 内存数据库上依次执行 up → down（全部回滚）→ up，
+//! 确保每个迁移的 `down` 能干净地撤销 `up`，回滚后可以重新迁移。
+
+use migration::{Migrator, MigratorTrait};
+use sea_orm_migration::sea_orm::{Database, DatabaseConnection};
+
+async fn fresh_db() -> DatabaseConnection {
+    Database::connect("sqlite::memory:")
+        .await
+        .expect("连接 SQLite 内存数据库失败")
+}
+
+async fn applied(db: &DatabaseConnection) -> usize {
+    Migrator::get_applied_migrations(db)
+        .await
+        .expect("读取迁移记录失败")
+        .len()
+}
+
+#[tokio::test]
+async fn test_migrations_up_down_up() {
+    let db = fresh_db().await;
+    let total = Migrator::migrations().len();
+
+    Migrator::up(&db, None).await.expect("首次 up 失败");
+    assert_eq!(applied(&db).await, total);
+
+    Migrator::down(&db, None).await.expect("全部回滚 down 失败");
+    assert_eq!(applied(&db).await, 0);
+
+    Migrator::up(&db, None).await.expect("回滚后再次 up 失败");
+    assert_eq!(applied(&db).await, total);
+}